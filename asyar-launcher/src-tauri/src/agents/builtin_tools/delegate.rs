@@ -0,0 +1,124 @@
+//! `delegate-to-agent`: hand a sub-task to another agent and get its final
+//! answer back. The registry only holds the descriptor, so the tool can be
+//! selected like any other; the sub-run itself needs the parent run's
+//! providers, event stream and cancellation, so the runner executes it
+//! (see `agents::runner`).
+
+use crate::agents::tools::{BuiltinTool, ToolDescriptor, ToolSource};
+use crate::error::AppError;
+use crate::storage::agents::AgentRow;
+use serde_json::json;
+
+pub const DELEGATE_TOOL_ID: &str = "delegate-to-agent";
+
+pub struct DelegateToAgentTool;
+
+impl DelegateToAgentTool {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for DelegateToAgentTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The validated arguments of a delegation call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DelegateArgs {
+    pub agent_id: String,
+    pub task: String,
+}
+
+impl DelegateArgs {
+    pub fn parse(args: &serde_json::Value) -> Result<Self, AppError> {
+        let field = |name: &str| {
+            args.get(name)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    AppError::Validation(format!("missing required '{name}' string argument"))
+                })
+        };
+        Ok(Self {
+            agent_id: field("agentId")?,
+            task: field("task")?,
+        })
+    }
+}
+
+/// The descriptor offered to `caller_id` in a run. Agent ids are UUIDs the
+/// model has no other way to learn, so every other agent is listed in the
+/// description and `agentId` is constrained to their ids. `None` when there
+/// is no one to delegate to.
+pub fn descriptor_for_caller(agents: &[AgentRow], caller_id: &str) -> Option<ToolDescriptor> {
+    let callable: Vec<&AgentRow> = agents.iter().filter(|a| a.id != caller_id).collect();
+    if callable.is_empty() {
+        return None;
+    }
+    let roster = callable
+        .iter()
+        .map(|agent| {
+            match agent
+                .description
+                .as_deref()
+                .map(str::trim)
+                .filter(|d| !d.is_empty())
+            {
+                Some(description) => format!("- {} ({}): {description}", agent.id, agent.name),
+                None => format!("- {} ({})", agent.id, agent.name),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut descriptor = DelegateToAgentTool.descriptor();
+    descriptor.description = format!(
+        "{}\n\nAgents you can delegate to (id, name, description):\n{roster}",
+        descriptor.description
+    );
+    descriptor.parameters["properties"]["agentId"]["enum"] =
+        json!(callable.iter().map(|a| a.id.as_str()).collect::<Vec<_>>());
+    Some(descriptor)
+}
+
+#[async_trait::async_trait]
+impl BuiltinTool for DelegateToAgentTool {
+    fn descriptor(&self) -> ToolDescriptor {
+        ToolDescriptor {
+            id: DELEGATE_TOOL_ID.into(),
+            name: "Delegate to agent".into(),
+            description: "Hand a self-contained sub-task to another agent, which answers it \
+                          with its own instructions, tools and model. Returns that agent's \
+                          final answer. The sub-agent sees only the task text, so include \
+                          everything it needs."
+                .into(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "agentId": {
+                        "type": "string",
+                        "description": "Id of the agent to delegate to"
+                    },
+                    "task": {
+                        "type": "string",
+                        "description": "The sub-task, written as a complete request"
+                    }
+                },
+                "required": ["agentId", "task"]
+            }),
+            source: ToolSource::Builtin,
+            fully_qualified_id: format!("builtin:{DELEGATE_TOOL_ID}"),
+        }
+    }
+
+    async fn invoke(&self, args: serde_json::Value) -> Result<serde_json::Value, AppError> {
+        DelegateArgs::parse(&args)?;
+        Err(AppError::Validation(format!(
+            "'{DELEGATE_TOOL_ID}' can only run inside an agent run"
+        )))
+    }
+}
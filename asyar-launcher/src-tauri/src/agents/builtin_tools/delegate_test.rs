@@ -0,0 +1,101 @@
+use crate::agents::builtin_tools::delegate::{
+    descriptor_for_caller, DelegateArgs, DelegateToAgentTool,
+};
+use crate::agents::tools::{BuiltinTool, ToolSource};
+use crate::error::AppError;
+use crate::storage::agents::{AgentRow, SilentInputSource, SilentOutputAction};
+use serde_json::json;
+
+fn agent(id: &str, name: &str, description: Option<&str>) -> AgentRow {
+    AgentRow {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: description.map(str::to_string),
+        system_prompt: String::new(),
+        provider_id: "openai".to_string(),
+        model_id: "gpt-4o".to_string(),
+        tool_selection: vec![],
+        silent: false,
+        input_source: SilentInputSource::Argument,
+        output_action: SilentOutputAction::ReplaceSelection,
+        cache_responses: false,
+        shortcode_trigger: ":".to_string(),
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
+        created_at: None,
+        updated_at: None,
+    }
+}
+
+#[test]
+fn descriptor_has_expected_shape() {
+    let desc = DelegateToAgentTool::new().descriptor();
+
+    assert_eq!(desc.id, "delegate-to-agent");
+    assert_eq!(desc.fully_qualified_id, "builtin:delegate-to-agent");
+    assert_eq!(desc.source, ToolSource::Builtin);
+    assert_eq!(desc.parameters["required"], json!(["agentId", "task"]));
+}
+
+#[test]
+fn descriptor_for_caller_lists_every_other_agent() {
+    let agents = [
+        agent("a1", "Planner", Some("Breaks work down")),
+        agent("a2", "Researcher", Some("Finds sources")),
+        agent("a3", "Editor", None),
+    ];
+    let desc = descriptor_for_caller(&agents, "a1").unwrap();
+
+    assert_eq!(
+        desc.parameters["properties"]["agentId"]["enum"],
+        json!(["a2", "a3"])
+    );
+    assert!(desc
+        .description
+        .contains("- a2 (Researcher): Finds sources"));
+    assert!(desc.description.contains("- a3 (Editor)"));
+    assert!(!desc.description.contains("Planner"));
+    assert_eq!(desc.parameters["required"], json!(["agentId", "task"]));
+}
+
+#[test]
+fn descriptor_for_caller_is_none_without_anyone_to_call() {
+    let agents = [agent("a1", "Planner", None)];
+    assert!(descriptor_for_caller(&agents, "a1").is_none());
+    assert!(descriptor_for_caller(&[], "a1").is_none());
+}
+
+#[test]
+fn parse_trims_and_requires_both_fields() {
+    let args = DelegateArgs::parse(&json!({ "agentId": " a2 ", "task": "Summarise" })).unwrap();
+    assert_eq!(
+        args,
+        DelegateArgs {
+            agent_id: "a2".to_string(),
+            task: "Summarise".to_string(),
+        }
+    );
+
+    for bad in [
+        json!({ "task": "Summarise" }),
+        json!({ "agentId": "a2", "task": "  " }),
+        json!({ "agentId": 7, "task": "Summarise" }),
+    ] {
+        assert!(matches!(
+            DelegateArgs::parse(&bad),
+            Err(AppError::Validation(_))
+        ));
+    }
+}
+
+#[tokio::test]
+async fn invoke_outside_a_run_is_refused() {
+    let result = DelegateToAgentTool::new()
+        .invoke(json!({ "agentId": "a2", "task": "Summarise" }))
+        .await;
+    match result {
+        Err(AppError::Validation(message)) => assert!(message.contains("inside an agent run")),
+        other => panic!("expected a validation error, got {other:?}"),
+    }
+}
@@ -1,32 +1,103 @@
-use crate::agents::tools::{BuiltinTool, ToolDescriptor, ToolSource};
+use crate::agents::tool_policy::{decode_capped, ToolSandbox};
+use crate::agents::tools::{BuiltinTool, ToolCaller, ToolDescriptor, ToolSource};
 use crate::error::AppError;
+use crate::storage::agent_tool_audit::AuditOutcome;
+use crate::storage::agent_tool_policies::{AgentToolPolicy, WriteMode};
 use serde_json::json;
+use std::io::Read;
+use std::sync::Arc;
 
-pub struct FsReadTool;
+pub struct FsReadTool {
+    sandbox: Arc<ToolSandbox>,
+}
 
 impl FsReadTool {
-    pub fn new() -> Self {
-        Self
+    pub fn new(sandbox: Arc<ToolSandbox>) -> Self {
+        Self { sandbox }
     }
-}
 
-impl Default for FsReadTool {
-    fn default() -> Self {
-        Self::new()
+    fn read(
+        &self,
+        policy: &AgentToolPolicy,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, AppError> {
+        let path = args.get("path").and_then(|v| v.as_str()).ok_or_else(|| {
+            AppError::Validation("missing required 'path' string argument".into())
+        })?;
+        let target = self.sandbox.check_read(policy, path)?;
+        let cap = policy.max_output_bytes as usize;
+        let mut bytes = Vec::new();
+        std::fs::File::open(&target)
+            .and_then(|file| file.take(cap as u64 + 1).read_to_end(&mut bytes))
+            .map_err(|e| AppError::Other(format!("failed to read '{}': {}", path, e)))?;
+        let (content, truncated) = decode_capped(bytes, cap);
+        let mut result = json!({ "content": content });
+        if truncated {
+            result["truncated"] = json!(true);
+        }
+        Ok(result)
     }
 }
 
-pub struct FsWriteTool;
+pub struct FsWriteTool {
+    sandbox: Arc<ToolSandbox>,
+}
 
 impl FsWriteTool {
-    pub fn new() -> Self {
-        Self
+    pub fn new(sandbox: Arc<ToolSandbox>) -> Self {
+        Self { sandbox }
     }
-}
 
-impl Default for FsWriteTool {
-    fn default() -> Self {
-        Self::new()
+    async fn write(
+        &self,
+        policy: &AgentToolPolicy,
+        args: &serde_json::Value,
+        caller: &ToolCaller,
+    ) -> Result<(serde_json::Value, AuditOutcome), AppError> {
+        let path = args.get("path").and_then(|v| v.as_str()).ok_or_else(|| {
+            AppError::Validation("missing required 'path' string argument".into())
+        })?;
+        let content = args
+            .get("content")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                AppError::Validation("missing required 'content' string argument".into())
+            })?;
+        let target = self.sandbox.check_write(policy, path)?;
+        let bytes = content.len();
+        match policy.write_mode {
+            WriteMode::Direct => {}
+            WriteMode::DryRun => {
+                return Ok((
+                    json!({
+                        "ok": true,
+                        "dryRun": true,
+                        "bytesWritten": 0,
+                        "wouldWrite": bytes,
+                        "exists": target.exists(),
+                    }),
+                    AuditOutcome::DryRun,
+                ));
+            }
+            WriteMode::Confirm => {
+                if !self.sandbox.confirm_write(caller, &target, content).await? {
+                    return Ok((
+                        json!({
+                            "ok": false,
+                            "declined": true,
+                            "message": "The user declined this write.",
+                        }),
+                        AuditOutcome::Declined,
+                    ));
+                }
+            }
+        }
+        std::fs::write(&target, content)
+            .map_err(|e| AppError::Other(format!("failed to write '{}': {}", path, e)))?;
+        Ok((
+            json!({ "ok": true, "bytesWritten": bytes }),
+            AuditOutcome::Ok,
+        ))
     }
 }
 
@@ -36,7 +107,9 @@ impl BuiltinTool for FsReadTool {
         ToolDescriptor {
             id: "fs-read".into(),
             name: "Read File".into(),
-            description: "Read the contents of a UTF-8 text file at the given path.".into(),
+            description: "Read the contents of a UTF-8 text file at the given path. Long \
+                files are cut off at the agent's output cap and flagged 'truncated'."
+                .into(),
             parameters: json!({
                 "type": "object",
                 "properties": {
@@ -50,12 +123,19 @@ impl BuiltinTool for FsReadTool {
     }
 
     async fn invoke(&self, args: serde_json::Value) -> Result<serde_json::Value, AppError> {
-        let path = args.get("path").and_then(|v| v.as_str()).ok_or_else(|| {
-            AppError::Validation("missing required 'path' string argument".into())
-        })?;
-        let content = std::fs::read_to_string(path)
-            .map_err(|e| AppError::Other(format!("failed to read '{}': {}", path, e)))?;
-        Ok(json!({ "content": content }))
+        self.invoke_as(args, &ToolCaller::default()).await
+    }
+
+    async fn invoke_as(
+        &self,
+        args: serde_json::Value,
+        caller: &ToolCaller,
+    ) -> Result<serde_json::Value, AppError> {
+        let policy = self.sandbox.policy_for(caller)?;
+        let result = self.read(&policy, &args);
+        self.sandbox
+            .record_result("fs-read", caller, &args, &result);
+        result
     }
 }
 
@@ -65,7 +145,9 @@ impl BuiltinTool for FsWriteTool {
         ToolDescriptor {
             id: "fs-write".into(),
             name: "Write File".into(),
-            description: "Write UTF-8 text to a file at the given path. Overwrites existing files."
+            description: "Write UTF-8 text to a file at the given path. Overwrites \
+                existing files. Depending on the agent's policy the write may only be \
+                previewed ('dryRun') or need the user's approval ('declined' if they refuse)."
                 .into(),
             parameters: json!({
                 "type": "object",
@@ -81,18 +163,27 @@ impl BuiltinTool for FsWriteTool {
     }
 
     async fn invoke(&self, args: serde_json::Value) -> Result<serde_json::Value, AppError> {
-        let path = args.get("path").and_then(|v| v.as_str()).ok_or_else(|| {
-            AppError::Validation("missing required 'path' string argument".into())
-        })?;
-        let content = args
-            .get("content")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| {
-                AppError::Validation("missing required 'content' string argument".into())
-            })?;
-        let bytes = content.len();
-        std::fs::write(path, content)
-            .map_err(|e| AppError::Other(format!("failed to write '{}': {}", path, e)))?;
-        Ok(json!({ "ok": true, "bytesWritten": bytes }))
+        self.invoke_as(args, &ToolCaller::default()).await
+    }
+
+    async fn invoke_as(
+        &self,
+        args: serde_json::Value,
+        caller: &ToolCaller,
+    ) -> Result<serde_json::Value, AppError> {
+        let policy = self.sandbox.policy_for(caller)?;
+        match self.write(&policy, &args, caller).await {
+            Ok((value, outcome)) => {
+                self.sandbox
+                    .record("fs-write", caller, &args, outcome, None);
+                Ok(value)
+            }
+            Err(e) => {
+                let result = Err(e);
+                self.sandbox
+                    .record_result("fs-write", caller, &args, &result);
+                result
+            }
+        }
     }
 }
@@ -1,12 +1,46 @@
 use crate::agents::builtin_tools::fs::{FsReadTool, FsWriteTool};
-use crate::agents::tools::{BuiltinTool, ToolSource};
+use crate::agents::tool_policy::ToolSandbox;
+use crate::agents::tools::{BuiltinTool, ToolCaller, ToolSource};
+use crate::storage::agent_tool_audit::{self, AuditOutcome};
+use crate::storage::agent_tool_policies::{self, AgentToolPolicy, WriteMode};
+use crate::storage::DataStore;
 use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// A sandbox whose home nothing in these tests lives under, so the
+/// credential deny-list never gets in the way of tempdir paths.
+fn sandbox() -> Arc<ToolSandbox> {
+    sandbox_over(crate::storage::create_test_store())
+}
+
+fn sandbox_over(store: DataStore) -> Arc<ToolSandbox> {
+    Arc::new(ToolSandbox::new(
+        store,
+        PathBuf::from("/nonexistent/asyar-test-home"),
+        Vec::new(),
+    ))
+}
+
+/// A store where agent `a1` runs under `policy`.
+fn store_with_policy(policy: &AgentToolPolicy) -> DataStore {
+    let store = crate::storage::create_test_store();
+    agent_tool_policies::set(&store.conn().unwrap(), "a1", policy, 1).unwrap();
+    store
+}
+
+fn agent() -> ToolCaller {
+    ToolCaller {
+        agent_id: Some("a1".to_string()),
+        thread_id: None,
+    }
+}
 
 // ── 1. read_descriptor_has_expected_shape ────────────────────────────────────
 
 #[test]
 fn read_descriptor_has_expected_shape() {
-    let tool = FsReadTool::new();
+    let tool = FsReadTool::new(sandbox());
     let desc = tool.descriptor();
 
     assert_eq!(desc.id, "fs-read");
@@ -32,7 +66,7 @@ fn read_descriptor_has_expected_shape() {
 
 #[test]
 fn write_descriptor_has_expected_shape() {
-    let tool = FsWriteTool::new();
+    let tool = FsWriteTool::new(sandbox());
     let desc = tool.descriptor();
 
     assert_eq!(desc.id, "fs-write");
@@ -70,7 +104,7 @@ async fn read_returns_file_content() {
     let path = dir.path().join("hello.txt");
     std::fs::write(&path, "hello world\n").expect("failed to write temp file");
 
-    let tool = FsReadTool::new();
+    let tool = FsReadTool::new(sandbox());
     let result = tool.invoke(json!({ "path": path.to_str().unwrap() })).await;
 
     assert!(result.is_ok(), "expected Ok, got {result:?}");
@@ -81,7 +115,7 @@ async fn read_returns_file_content() {
 
 #[tokio::test]
 async fn read_returns_error_when_file_missing() {
-    let tool = FsReadTool::new();
+    let tool = FsReadTool::new(sandbox());
     let result = tool
         .invoke(json!({ "path": "/nonexistent/path/blah_asyar_test_404.txt" }))
         .await;
@@ -100,7 +134,7 @@ async fn read_returns_error_when_file_missing() {
 
 #[tokio::test]
 async fn read_returns_error_for_missing_path() {
-    let tool = FsReadTool::new();
+    let tool = FsReadTool::new(sandbox());
     let result = tool.invoke(json!({})).await;
 
     assert!(
@@ -118,7 +152,7 @@ async fn read_returns_error_for_missing_path() {
 
 #[tokio::test]
 async fn read_returns_error_for_non_string_path() {
-    let tool = FsReadTool::new();
+    let tool = FsReadTool::new(sandbox());
     let result = tool.invoke(json!({ "path": 42 })).await;
 
     assert!(result.is_err(), "non-string 'path' must return Err, got Ok");
@@ -131,7 +165,7 @@ async fn write_creates_file_with_content() {
     let dir = tempfile::tempdir().expect("failed to create temp dir");
     let path = dir.path().join("out.txt");
 
-    let tool = FsWriteTool::new();
+    let tool = FsWriteTool::new(sandbox());
     let result = tool
         .invoke(json!({ "path": path.to_str().unwrap(), "content": "abc" }))
         .await;
@@ -151,7 +185,7 @@ async fn write_overwrites_existing_file() {
     let path = dir.path().join("overwrite.txt");
     std::fs::write(&path, "old content").expect("failed to write initial file");
 
-    let tool = FsWriteTool::new();
+    let tool = FsWriteTool::new(sandbox());
     let result = tool
         .invoke(json!({ "path": path.to_str().unwrap(), "content": "new" }))
         .await;
@@ -169,7 +203,7 @@ async fn write_overwrites_existing_file() {
 
 #[tokio::test]
 async fn write_returns_error_for_missing_path() {
-    let tool = FsWriteTool::new();
+    let tool = FsWriteTool::new(sandbox());
     let result = tool.invoke(json!({ "content": "x" })).await;
 
     assert!(
@@ -182,7 +216,7 @@ async fn write_returns_error_for_missing_path() {
 
 #[tokio::test]
 async fn write_returns_error_for_missing_content() {
-    let tool = FsWriteTool::new();
+    let tool = FsWriteTool::new(sandbox());
     let result = tool.invoke(json!({ "path": "/tmp/x" })).await;
 
     assert!(
@@ -195,7 +229,7 @@ async fn write_returns_error_for_missing_content() {
 
 #[tokio::test]
 async fn write_returns_error_for_non_string_args() {
-    let tool = FsWriteTool::new();
+    let tool = FsWriteTool::new(sandbox());
     let result = tool.invoke(json!({ "path": 42, "content": 7 })).await;
 
     assert!(
@@ -212,9 +246,124 @@ async fn read_handles_empty_file() {
     let path = dir.path().join("empty.txt");
     std::fs::write(&path, "").expect("failed to create empty temp file");
 
-    let tool = FsReadTool::new();
+    let tool = FsReadTool::new(sandbox());
     let result = tool.invoke(json!({ "path": path.to_str().unwrap() })).await;
 
     assert!(result.is_ok(), "expected Ok for empty file, got {result:?}");
     assert_eq!(result.unwrap(), json!({"content": ""}));
 }
+
+// ── 13. read_outside_the_policy_is_denied_and_audited ────────────────────────
+
+#[tokio::test]
+async fn read_outside_the_policy_is_denied_and_audited() {
+    let dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = dir.path().join("private.txt");
+    std::fs::write(&path, "secret").expect("failed to write temp file");
+    let store = store_with_policy(&AgentToolPolicy {
+        read_paths: vec!["/nonexistent/allowed/**".into()],
+        ..AgentToolPolicy::default()
+    });
+
+    let tool = FsReadTool::new(sandbox_over(store.clone()));
+    let result = tool
+        .invoke_as(json!({ "path": path.to_str().unwrap() }), &agent())
+        .await;
+
+    assert!(
+        matches!(result, Err(crate::error::AppError::Permission(_))),
+        "expected a policy denial, got {result:?}"
+    );
+    let audit = agent_tool_audit::list_recent(&store.conn().unwrap(), Some("a1"), 10).unwrap();
+    assert_eq!(audit.len(), 1);
+    assert_eq!(audit[0].tool_id, "fs-read");
+    assert_eq!(audit[0].outcome, AuditOutcome::Denied);
+}
+
+// ── 14. read_is_cut_at_the_output_cap ────────────────────────────────────────
+
+#[tokio::test]
+async fn read_is_cut_at_the_output_cap() {
+    let dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = dir.path().join("long.txt");
+    std::fs::write(&path, "abcdefghij").expect("failed to write temp file");
+    let store = store_with_policy(&AgentToolPolicy {
+        max_output_bytes: 4,
+        ..AgentToolPolicy::default()
+    });
+
+    let tool = FsReadTool::new(sandbox_over(store));
+    let result = tool
+        .invoke_as(json!({ "path": path.to_str().unwrap() }), &agent())
+        .await
+        .unwrap();
+
+    assert_eq!(result, json!({ "content": "abcd", "truncated": true }));
+}
+
+// ── 15. dry_run_write_leaves_the_disk_alone ──────────────────────────────────
+
+#[tokio::test]
+async fn dry_run_write_leaves_the_disk_alone() {
+    let dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = dir.path().join("draft.txt");
+    let store = store_with_policy(&AgentToolPolicy {
+        write_mode: WriteMode::DryRun,
+        ..AgentToolPolicy::default()
+    });
+
+    let tool = FsWriteTool::new(sandbox_over(store.clone()));
+    let result = tool
+        .invoke_as(
+            json!({ "path": path.to_str().unwrap(), "content": "abc" }),
+            &agent(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result["dryRun"], json!(true));
+    assert_eq!(result["wouldWrite"], json!(3));
+    assert!(!path.exists(), "a dry run must not create the file");
+    let audit = agent_tool_audit::list_recent(&store.conn().unwrap(), Some("a1"), 10).unwrap();
+    assert_eq!(audit[0].outcome, AuditOutcome::DryRun);
+}
+
+// ── 16. confirm_write_only_happens_when_approved ─────────────────────────────
+
+#[tokio::test]
+async fn confirm_write_only_happens_when_approved() {
+    let dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = dir.path().join("plan.txt");
+    let store = store_with_policy(&AgentToolPolicy {
+        write_mode: WriteMode::Confirm,
+        ..AgentToolPolicy::default()
+    });
+    let sandbox = sandbox_over(store.clone());
+    let tool = FsWriteTool::new(Arc::clone(&sandbox));
+    let args = json!({ "path": path.to_str().unwrap(), "content": "abc" });
+
+    // Nobody listening for confirmations: declined, nothing written.
+    let declined = tool.invoke_as(args.clone(), &agent()).await.unwrap();
+    assert_eq!(declined["declined"], json!(true));
+    assert!(!path.exists());
+
+    let answering = Arc::clone(&sandbox);
+    sandbox.set_confirm_emitter(Box::new(move |request| {
+        let answering = Arc::clone(&answering);
+        let id = request.id.clone();
+        tokio::spawn(async move {
+            answering.resolve_write(&id, true).unwrap();
+        });
+    }));
+    let written = tool.invoke_as(args, &agent()).await.unwrap();
+    assert_eq!(written, json!({ "ok": true, "bytesWritten": 3 }));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "abc");
+
+    let outcomes: Vec<_> = agent_tool_audit::list_recent(&store.conn().unwrap(), Some("a1"), 10)
+        .unwrap()
+        .into_iter()
+        .map(|row| row.outcome)
+        .collect();
+    assert!(outcomes.contains(&AuditOutcome::Declined));
+    assert!(outcomes.contains(&AuditOutcome::Ok));
+}
@@ -0,0 +1,246 @@
+//! AI-callable long-term memory — save, search, forget. The counterpart to
+//! the Notes tools: `Notes` are documents the user writes, `Memory` is facts
+//! the AI remembers about the user across conversations.
+//!
+//! Memories saved from a chat are tagged with its thread; the `agent` scope
+//! keeps a fact to the agent that saved it, `global` shares it with all.
+
+use crate::agents::tools::{BuiltinTool, ToolCaller, ToolDescriptor, ToolSource};
+use crate::error::AppError;
+use crate::storage::memories::{self, Memory};
+use crate::storage::DataStore;
+use serde_json::json;
+
+/// How many memories `memory-search` returns when the model doesn't say.
+pub const DEFAULT_RECALL_LIMIT: usize = 5;
+
+fn require_str<'a>(args: &'a serde_json::Value, field: &str) -> Result<&'a str, AppError> {
+    args.get(field)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.trim().is_empty())
+        .ok_or_else(|| AppError::Validation(format!("missing or invalid '{field}' argument")))
+}
+
+fn memory_to_json(memory: &Memory) -> serde_json::Value {
+    json!({
+        "id": memory.id,
+        "content": memory.content,
+        "scope": if memory.agent_id.is_some() { "agent" } else { "global" },
+        "updatedAt": memory.updated_at,
+    })
+}
+
+// ── memory-save ──────────────────────────────────────────────────────────────
+
+pub struct MemorySaveTool {
+    data_store: DataStore,
+    master_key: [u8; 32],
+}
+
+impl MemorySaveTool {
+    pub fn new(data_store: DataStore, master_key: [u8; 32]) -> Self {
+        Self {
+            data_store,
+            master_key,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl BuiltinTool for MemorySaveTool {
+    fn descriptor(&self) -> ToolDescriptor {
+        ToolDescriptor {
+            id: "memory-save".into(),
+            name: "Save Memory".into(),
+            description: "Remember a lasting fact about the user for future conversations — \
+                their preferences, circumstances, projects, or anything they ask you to \
+                remember ('remember that I'm vegetarian', 'I work in Berlin now'). Save one \
+                self-contained fact per call, phrased so it makes sense without this \
+                conversation. Don't save passing details or things only relevant to the \
+                current task, and use notes-create instead for documents the user wants to \
+                read later."
+                .into(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "content": { "type": "string", "description": "The fact to remember." },
+                    "scope": {
+                        "type": "string",
+                        "enum": ["global", "agent"],
+                        "description": "'global' (default) shares the memory with every agent; 'agent' keeps it to this one."
+                    }
+                },
+                "required": ["content"]
+            }),
+            source: ToolSource::Builtin,
+            fully_qualified_id: "builtin:memory-save".into(),
+        }
+    }
+
+    async fn invoke(&self, args: serde_json::Value) -> Result<serde_json::Value, AppError> {
+        self.invoke_as(args, &ToolCaller::default()).await
+    }
+
+    async fn invoke_as(
+        &self,
+        args: serde_json::Value,
+        caller: &ToolCaller,
+    ) -> Result<serde_json::Value, AppError> {
+        let content = require_str(&args, "content")?.trim();
+        let agent_id = match args.get("scope").and_then(|v| v.as_str()) {
+            None | Some("global") => None,
+            Some("agent") => Some(caller.agent_id.clone().ok_or_else(|| {
+                AppError::Validation("'agent' scope needs a calling agent".into())
+            })?),
+            Some(other) => {
+                return Err(AppError::Validation(format!(
+                    "'scope' must be 'global' or 'agent', got '{other}'"
+                )))
+            }
+        };
+        let now = chrono::Utc::now().timestamp_millis();
+        let memory = Memory {
+            id: uuid::Uuid::new_v4().to_string(),
+            agent_id,
+            content: content.to_string(),
+            source_thread_id: caller.thread_id.clone(),
+            created_at: now,
+            updated_at: now,
+        };
+        let conn = self.data_store.conn()?;
+        let id = memories::save(&conn, &memory, &self.master_key)?;
+        Ok(json!({ "id": id, "saved": true }))
+    }
+}
+
+// ── memory-search ────────────────────────────────────────────────────────────
+
+pub struct MemorySearchTool {
+    data_store: DataStore,
+    master_key: [u8; 32],
+}
+
+impl MemorySearchTool {
+    pub fn new(data_store: DataStore, master_key: [u8; 32]) -> Self {
+        Self {
+            data_store,
+            master_key,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl BuiltinTool for MemorySearchTool {
+    fn descriptor(&self) -> ToolDescriptor {
+        ToolDescriptor {
+            id: "memory-search".into(),
+            name: "Search Memories".into(),
+            description: "Look up facts you remembered about the user in earlier \
+                conversations. The most relevant memories are already listed in your \
+                instructions; search when the user refers to something you may have been \
+                told before that isn't there, or before saving a fact to check whether an \
+                outdated version should be forgotten first."
+                .into(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Words to look for." },
+                    "limit": { "type": "number", "description": "Max results to return (default 5)." }
+                },
+                "required": ["query"]
+            }),
+            source: ToolSource::Builtin,
+            fully_qualified_id: "builtin:memory-search".into(),
+        }
+    }
+
+    async fn invoke(&self, args: serde_json::Value) -> Result<serde_json::Value, AppError> {
+        self.invoke_as(args, &ToolCaller::default()).await
+    }
+
+    async fn invoke_as(
+        &self,
+        args: serde_json::Value,
+        caller: &ToolCaller,
+    ) -> Result<serde_json::Value, AppError> {
+        let query = require_str(&args, "query")?;
+        let limit = match args.get("limit") {
+            None | Some(serde_json::Value::Null) => DEFAULT_RECALL_LIMIT,
+            Some(value) => value.as_u64().ok_or_else(|| {
+                AppError::Validation("'limit' must be a non-negative integer".into())
+            })? as usize,
+        };
+        let conn = self.data_store.conn()?;
+        let found = memories::search(
+            &conn,
+            caller.agent_id.as_deref(),
+            query,
+            limit,
+            &self.master_key,
+        )?;
+        let results: Vec<serde_json::Value> = found.iter().map(memory_to_json).collect();
+        Ok(json!({ "results": results }))
+    }
+}
+
+// ── memory-forget ────────────────────────────────────────────────────────────
+
+pub struct MemoryForgetTool {
+    data_store: DataStore,
+    master_key: [u8; 32],
+}
+
+impl MemoryForgetTool {
+    pub fn new(data_store: DataStore, master_key: [u8; 32]) -> Self {
+        Self {
+            data_store,
+            master_key,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl BuiltinTool for MemoryForgetTool {
+    fn descriptor(&self) -> ToolDescriptor {
+        ToolDescriptor {
+            id: "memory-forget".into(),
+            name: "Forget Memory".into(),
+            description: "Delete a remembered fact by id (from memory-search or your \
+                instructions). Use this when the user asks you to forget something, or \
+                when a memory is no longer true — forget the old fact before saving the \
+                corrected one."
+                .into(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string", "description": "The memory's id." }
+                },
+                "required": ["id"]
+            }),
+            source: ToolSource::Builtin,
+            fully_qualified_id: "builtin:memory-forget".into(),
+        }
+    }
+
+    async fn invoke(&self, args: serde_json::Value) -> Result<serde_json::Value, AppError> {
+        self.invoke_as(args, &ToolCaller::default()).await
+    }
+
+    async fn invoke_as(
+        &self,
+        args: serde_json::Value,
+        caller: &ToolCaller,
+    ) -> Result<serde_json::Value, AppError> {
+        let id = require_str(&args, "id")?;
+        let conn = self.data_store.conn()?;
+        // Agents may only forget what they can recall: another agent's
+        // private memories are invisible to them.
+        let visible = memories::get(&conn, id, &self.master_key)?
+            .filter(|memory| memory.agent_id.is_none() || memory.agent_id == caller.agent_id);
+        if visible.is_none() {
+            return Err(AppError::NotFound(format!("no memory with id '{id}'")));
+        }
+        memories::remove(&conn, id)?;
+        Ok(json!({ "id": id, "forgotten": true }))
+    }
+}
@@ -0,0 +1,118 @@
+use super::memory::{MemoryForgetTool, MemorySaveTool, MemorySearchTool};
+use crate::agents::tools::{BuiltinTool, ToolCaller};
+use crate::error::AppError;
+use crate::storage::memories;
+use crate::storage::DataStore;
+use serde_json::json;
+
+fn test_key() -> [u8; 32] {
+    let mut k = [0u8; 32];
+    for (i, b) in k.iter_mut().enumerate() {
+        *b = (i * 29) as u8;
+    }
+    k
+}
+
+fn caller(agent_id: &str) -> ToolCaller {
+    ToolCaller {
+        agent_id: Some(agent_id.to_string()),
+        thread_id: Some("thread-1".to_string()),
+    }
+}
+
+fn test_store() -> (DataStore, [u8; 32]) {
+    (crate::storage::create_test_store(), test_key())
+}
+
+#[tokio::test]
+async fn memory_save_records_scope_and_source_thread() {
+    let (store, key) = test_store();
+    let tool = MemorySaveTool::new(store.clone(), key);
+
+    tool.invoke_as(json!({"content": "Prefers tea"}), &caller("a1"))
+        .await
+        .unwrap();
+    tool.invoke_as(
+        json!({"content": "Writes Rust at work", "scope": "agent"}),
+        &caller("a1"),
+    )
+    .await
+    .unwrap();
+
+    let all = memories::list(&store.conn().unwrap(), None, &key).unwrap();
+    let scopes: Vec<_> = all
+        .iter()
+        .map(|m| (m.content.as_str(), m.agent_id.as_deref()))
+        .collect();
+    assert!(scopes.contains(&("Prefers tea", None)));
+    assert!(scopes.contains(&("Writes Rust at work", Some("a1"))));
+    assert!(all
+        .iter()
+        .all(|m| m.source_thread_id.as_deref() == Some("thread-1")));
+}
+
+#[tokio::test]
+async fn memory_save_needs_an_agent_for_agent_scope() {
+    let (store, key) = test_store();
+    let tool = MemorySaveTool::new(store, key);
+
+    let err = tool
+        .invoke(json!({"content": "fact", "scope": "agent"}))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AppError::Validation(_)));
+}
+
+#[tokio::test]
+async fn memory_search_only_sees_global_and_own_memories() {
+    let (store, key) = test_store();
+    let save = MemorySaveTool::new(store.clone(), key);
+    save.invoke_as(json!({"content": "Allergic to peanuts"}), &caller("a1"))
+        .await
+        .unwrap();
+    save.invoke_as(
+        json!({"content": "Peanuts project is due Friday", "scope": "agent"}),
+        &caller("a2"),
+    )
+    .await
+    .unwrap();
+
+    let search = MemorySearchTool::new(store, key);
+    let result = search
+        .invoke_as(json!({"query": "peanuts"}), &caller("a1"))
+        .await
+        .unwrap();
+    let results = result["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["content"], "Allergic to peanuts");
+    assert_eq!(results[0]["scope"], "global");
+}
+
+#[tokio::test]
+async fn memory_forget_cannot_reach_another_agents_memories() {
+    let (store, key) = test_store();
+    let save = MemorySaveTool::new(store.clone(), key);
+    let saved = save
+        .invoke_as(
+            json!({"content": "Private fact", "scope": "agent"}),
+            &caller("a2"),
+        )
+        .await
+        .unwrap();
+    let id = saved["id"].as_str().unwrap();
+
+    let forget = MemoryForgetTool::new(store.clone(), key);
+    let err = forget
+        .invoke_as(json!({"id": id}), &caller("a1"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AppError::NotFound(_)));
+
+    forget
+        .invoke_as(json!({"id": id}), &caller("a2"))
+        .await
+        .unwrap();
+    assert!(memories::get(&store.conn().unwrap(), id, &key)
+        .unwrap()
+        .is_none());
+}
@@ -1,8 +1,11 @@
 pub mod calculator;
 pub mod clipboard;
+pub mod delegate;
 pub mod fs;
+pub mod memory;
 pub mod notes;
 pub mod search;
+pub mod semantic_search;
 pub mod shell;
 pub mod web_fetch;
 
@@ -11,12 +14,18 @@ mod calculator_test;
 #[cfg(test)]
 mod clipboard_test;
 #[cfg(test)]
+mod delegate_test;
+#[cfg(test)]
 mod fs_test;
 #[cfg(test)]
+mod memory_test;
+#[cfg(test)]
 mod notes_test;
 #[cfg(test)]
 mod search_test;
 #[cfg(test)]
+mod semantic_search_test;
+#[cfg(test)]
 mod shell_test;
 #[cfg(test)]
 mod web_fetch_test;
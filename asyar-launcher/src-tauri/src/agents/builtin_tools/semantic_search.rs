@@ -0,0 +1,102 @@
+//! AI-callable retrieval over the user's notes, clipboard history and
+//! snippets. Wraps `ai::semantic_index::hybrid_search` so an agent can ground
+//! an answer in the user's own material by meaning, not just exact words —
+//! the keyword-only `notes-search` misses "the place we stayed in Porto"
+//! when the note says "Airbnb, Ribeira".
+
+use crate::agents::tools::{BuiltinTool, ToolDescriptor, ToolSource};
+use crate::ai::semantic_index::{self, SemanticSearchContext};
+use crate::error::AppError;
+use crate::storage::embeddings::EmbeddingSource;
+use serde_json::json;
+
+const MAX_LIMIT: usize = 25;
+
+pub struct SemanticSearchTool {
+    ctx: SemanticSearchContext,
+}
+
+impl SemanticSearchTool {
+    pub fn new(ctx: SemanticSearchContext) -> Self {
+        Self { ctx }
+    }
+}
+
+fn parse_sources(args: &serde_json::Value) -> Result<Vec<EmbeddingSource>, AppError> {
+    match args.get("sources") {
+        None | Some(serde_json::Value::Null) => Ok(EmbeddingSource::ALL.to_vec()),
+        Some(value) => serde_json::from_value(value.clone()).map_err(|_| {
+            AppError::Validation(
+                "'sources' must be an array of \"note\", \"clipboard\" or \"snippet\"".into(),
+            )
+        }),
+    }
+}
+
+fn parse_limit(args: &serde_json::Value) -> Result<usize, AppError> {
+    match args.get("limit") {
+        None | Some(serde_json::Value::Null) => Ok(8),
+        Some(serde_json::Value::Number(n)) => {
+            let i = n
+                .as_i64()
+                .ok_or_else(|| AppError::Validation("'limit' must be an integer".into()))?;
+            if i < 0 {
+                return Err(AppError::Validation("'limit' must be non-negative".into()));
+            }
+            Ok((i as usize).min(MAX_LIMIT))
+        }
+        _ => Err(AppError::Validation("'limit' must be a number".into())),
+    }
+}
+
+#[async_trait::async_trait]
+impl BuiltinTool for SemanticSearchTool {
+    fn descriptor(&self) -> ToolDescriptor {
+        ToolDescriptor {
+            id: "semantic-search".into(),
+            name: "Semantic Search".into(),
+            description: "Search the user's notes, clipboard history and snippets by \
+                meaning as well as keywords. Use this to ground an answer in the user's \
+                own material when the wording may differ from theirs — e.g. 'what was \
+                that recipe I copied last week', 'what did we decide about pricing'. \
+                Returns the best matches with a short excerpt and their source; for \
+                notes, call notes-get with the returned id for the full body."
+                .into(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "What to look for, in natural language." },
+                    "sources": {
+                        "type": "array",
+                        "items": { "type": "string", "enum": ["note", "clipboard", "snippet"] },
+                        "description": "Restrict to these sources (default: all)."
+                    },
+                    "limit": { "type": "number", "description": "Max results to return (default 8, max 25)." }
+                },
+                "required": ["query"]
+            }),
+            source: ToolSource::Builtin,
+            fully_qualified_id: "builtin:semantic-search".into(),
+        }
+    }
+
+    async fn invoke(&self, args: serde_json::Value) -> Result<serde_json::Value, AppError> {
+        let query = args
+            .get("query")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.trim().is_empty())
+            .ok_or_else(|| AppError::Validation("missing or invalid 'query' argument".into()))?;
+        let sources = parse_sources(&args)?;
+        let limit = parse_limit(&args)?;
+
+        let result = semantic_index::hybrid_search(&self.ctx, query, &sources, limit).await?;
+        let mut response = json!({ "results": result.hits });
+        if result.vector_state != "ready" {
+            response["notice"] = json!(
+                "Semantic matching is unavailable right now (no embeddings indexed or the \
+                 embedding model is unreachable); these are keyword matches only."
+            );
+        }
+        Ok(response)
+    }
+}
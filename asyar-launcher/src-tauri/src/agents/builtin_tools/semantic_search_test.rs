@@ -0,0 +1,82 @@
+use super::semantic_search::SemanticSearchTool;
+use crate::agents::tools::BuiltinTool;
+use crate::ai::semantic_index::{SemanticIndexState, SemanticSearchContext};
+use crate::storage::clipboard_fts::ClipboardFts;
+use crate::storage::notes::{self, Note};
+use crate::storage::notes_fts::NotesFts;
+use serde_json::json;
+use std::sync::Arc;
+
+fn test_key() -> [u8; 32] {
+    let mut k = [0u8; 32];
+    for (i, b) in k.iter_mut().enumerate() {
+        *b = (i * 37) as u8;
+    }
+    k
+}
+
+fn test_ctx() -> SemanticSearchContext {
+    SemanticSearchContext {
+        data_store: crate::storage::create_test_store(),
+        master_key: test_key(),
+        state: Arc::new(SemanticIndexState::new()),
+        notes_fts: Arc::new(NotesFts::new_in_memory().unwrap()),
+        clipboard_fts: Arc::new(ClipboardFts::new_in_memory().unwrap()),
+    }
+}
+
+#[tokio::test]
+async fn semantic_search_falls_back_to_keywords_with_notice() {
+    let ctx = test_ctx();
+    {
+        let conn = ctx.data_store.conn().unwrap();
+        let note = Note {
+            id: "n1".into(),
+            title: "Porto trip".into(),
+            body: "Airbnb in Ribeira".into(),
+            created_at: 1.0,
+            updated_at: 1.0,
+            pinned: false,
+        };
+        notes::upsert_with_fts(&conn, &note, &ctx.master_key, &ctx.notes_fts).unwrap();
+    }
+    crate::storage::notes_fts::mark_ready();
+
+    let tool = SemanticSearchTool::new(ctx);
+    let result = tool
+        .invoke(json!({ "query": "ribeira", "sources": ["note"] }))
+        .await
+        .unwrap();
+    let results = result["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["id"], "n1");
+    assert_eq!(results[0]["source"], "note");
+    assert_eq!(results[0]["keywordMatch"], true);
+    assert!(result["notice"].is_string());
+
+    crate::storage::notes_fts::FTS_READY.store(false, std::sync::atomic::Ordering::Release);
+}
+
+#[tokio::test]
+async fn semantic_search_rejects_bad_arguments() {
+    let tool = SemanticSearchTool::new(test_ctx());
+    for args in [
+        json!({}),
+        json!({ "query": "  " }),
+        json!({ "query": "x", "sources": ["email"] }),
+        json!({ "query": "x", "limit": "ten" }),
+    ] {
+        let err = tool.invoke(args.clone()).await.unwrap_err();
+        assert!(
+            matches!(err, crate::error::AppError::Validation(_)),
+            "{args} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn descriptor_is_builtin() {
+    let d = SemanticSearchTool::new(test_ctx()).descriptor();
+    assert_eq!(d.id, "semantic-search");
+    assert_eq!(d.fully_qualified_id, "builtin:semantic-search");
+}
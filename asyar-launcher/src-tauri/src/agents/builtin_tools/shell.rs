@@ -1,44 +1,28 @@
-use crate::agents::tools::{BuiltinTool, ToolDescriptor, ToolSource};
+use crate::agents::tool_policy::{decode_capped, ToolSandbox};
+use crate::agents::tools::{BuiltinTool, ToolCaller, ToolDescriptor, ToolSource};
 use crate::error::AppError;
+use crate::storage::agent_tool_policies::AgentToolPolicy;
 use serde_json::json;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio::process::Command;
 
-pub struct ShellExecTool;
-
-impl ShellExecTool {
-    pub fn new() -> Self {
-        Self
-    }
-}
-
-impl Default for ShellExecTool {
-    fn default() -> Self {
-        Self::new()
-    }
+pub struct ShellExecTool {
+    sandbox: Arc<ToolSandbox>,
 }
 
-#[async_trait::async_trait]
-impl BuiltinTool for ShellExecTool {
-    fn descriptor(&self) -> ToolDescriptor {
-        ToolDescriptor {
-            id: "shell-exec".into(),
-            name: "Run Shell Command".into(),
-            description: "Execute a command and return stdout, stderr, and exit code.".into(),
-            parameters: json!({
-                "type": "object",
-                "properties": {
-                    "command": { "type": "string", "description": "Executable to run." },
-                    "args":    { "type": "array", "items": {"type": "string"}, "description": "Arguments." },
-                    "cwd":     { "type": "string", "description": "Working directory (optional)." }
-                },
-                "required": ["command"]
-            }),
-            source: ToolSource::Builtin,
-            fully_qualified_id: "builtin:shell-exec".into(),
-        }
+impl ShellExecTool {
+    pub fn new(sandbox: Arc<ToolSandbox>) -> Self {
+        Self { sandbox }
     }
 
-    async fn invoke(&self, args: serde_json::Value) -> Result<serde_json::Value, AppError> {
+    async fn run(
+        &self,
+        policy: &AgentToolPolicy,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, AppError> {
         let command = args
             .get("command")
             .and_then(|v| v.as_str())
@@ -65,34 +49,119 @@ impl BuiltinTool for ShellExecTool {
             }
         };
 
-        let cwd: Option<String> = match args.get("cwd") {
+        let cwd: Option<&str> = match args.get("cwd") {
             None | Some(serde_json::Value::Null) => None,
-            Some(serde_json::Value::String(s)) => Some(s.clone()),
+            Some(serde_json::Value::String(s)) => Some(s.as_str()),
             _ => return Err(AppError::Validation("'cwd' must be a string".into())),
         };
 
+        self.sandbox.check_command(policy, command)?;
+        let cwd = self.sandbox.resolve_cwd(policy, cwd)?;
+
         let mut cmd = Command::new(command);
-        cmd.args(&parsed_args);
+        cmd.args(&parsed_args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
         if let Some(dir) = cwd {
             cmd.current_dir(dir);
         }
 
-        let output = cmd
-            .output()
-            .await
+        let mut child = cmd
+            .spawn()
             .map_err(|e| AppError::Other(format!("failed to spawn '{}': {}", command, e)))?;
+        let cap = policy.max_output_bytes as usize;
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        // Dropping `child` on timeout kills the process.
+        let finished =
+            tokio::time::timeout(Duration::from_secs(u64::from(policy.timeout_secs)), async {
+                let (stdout, stderr) =
+                    tokio::join!(read_capped(stdout, cap), read_capped(stderr, cap));
+                let status = child.wait().await?;
+                Ok::<_, std::io::Error>((stdout?, stderr?, status))
+            })
+            .await
+            .map_err(|_| {
+                AppError::Other(format!(
+                    "'{}' timed out after {} seconds",
+                    command, policy.timeout_secs
+                ))
+            })?
+            .map_err(|e| AppError::Other(format!("failed to run '{}': {}", command, e)))?;
+        let (stdout, stderr, status) = finished;
 
-        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
-        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
-        let exit_code: serde_json::Value = match output.status.code() {
+        let (stdout, stdout_truncated) = decode_capped(stdout, cap);
+        let (stderr, stderr_truncated) = decode_capped(stderr, cap);
+        let exit_code: serde_json::Value = match status.code() {
             Some(code) => json!(code),
             None => json!(null),
         };
 
-        Ok(json!({
+        let mut result = json!({
             "stdout": stdout,
             "stderr": stderr,
             "exitCode": exit_code,
-        }))
+        });
+        if stdout_truncated || stderr_truncated {
+            result["truncated"] = json!(true);
+        }
+        Ok(result)
+    }
+}
+
+/// Reads at most `cap + 1` bytes — one past the cap so the caller can tell
+/// the output was cut. Closing the pipe early stops a chatty process from
+/// filling memory; it gets `SIGPIPE` or runs into the timeout.
+async fn read_capped(
+    stream: Option<impl AsyncRead + Unpin>,
+    cap: usize,
+) -> std::io::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    if let Some(stream) = stream {
+        stream.take(cap as u64 + 1).read_to_end(&mut buf).await?;
+    }
+    Ok(buf)
+}
+
+#[async_trait::async_trait]
+impl BuiltinTool for ShellExecTool {
+    fn descriptor(&self) -> ToolDescriptor {
+        ToolDescriptor {
+            id: "shell-exec".into(),
+            name: "Run Shell Command".into(),
+            description: "Execute a command and return stdout, stderr, and exit code. \
+                The agent's tool policy may limit which commands and working \
+                directories are allowed, and caps output size and run time."
+                .into(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "command": { "type": "string", "description": "Executable to run." },
+                    "args":    { "type": "array", "items": {"type": "string"}, "description": "Arguments." },
+                    "cwd":     { "type": "string", "description": "Working directory (optional)." }
+                },
+                "required": ["command"]
+            }),
+            source: ToolSource::Builtin,
+            fully_qualified_id: "builtin:shell-exec".into(),
+        }
+    }
+
+    async fn invoke(&self, args: serde_json::Value) -> Result<serde_json::Value, AppError> {
+        self.invoke_as(args, &ToolCaller::default()).await
+    }
+
+    async fn invoke_as(
+        &self,
+        args: serde_json::Value,
+        caller: &ToolCaller,
+    ) -> Result<serde_json::Value, AppError> {
+        let policy = self.sandbox.policy_for(caller)?;
+        let result = self.run(&policy, &args).await;
+        self.sandbox
+            .record_result("shell-exec", caller, &args, &result);
+        result
     }
 }
@@ -1,7 +1,13 @@
 use crate::agents::builtin_tools::shell::ShellExecTool;
-use crate::agents::tools::{BuiltinTool, ToolSource};
+use crate::agents::tool_policy::ToolSandbox;
+use crate::agents::tools::{BuiltinTool, ToolCaller, ToolSource};
 use crate::error::AppError;
+use crate::storage::agent_tool_audit::{self, AuditOutcome};
+use crate::storage::agent_tool_policies::{self, AgentToolPolicy};
+use crate::storage::DataStore;
 use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 /// Host shell and its `-c`-style flag. `ShellExecTool` spawns an executable
 /// directly (no implicit shell), so tests that need shell features —
@@ -13,11 +19,37 @@ const SHELL: (&str, &str) = ("cmd", "/C");
 #[cfg(not(windows))]
 const SHELL: (&str, &str) = ("sh", "-c");
 
+fn sandbox() -> Arc<ToolSandbox> {
+    sandbox_over(crate::storage::create_test_store())
+}
+
+fn sandbox_over(store: DataStore) -> Arc<ToolSandbox> {
+    Arc::new(ToolSandbox::new(
+        store,
+        PathBuf::from("/nonexistent/asyar-test-home"),
+        Vec::new(),
+    ))
+}
+
+/// A store where agent `a1` runs under `policy`.
+fn store_with_policy(policy: &AgentToolPolicy) -> DataStore {
+    let store = crate::storage::create_test_store();
+    agent_tool_policies::set(&store.conn().unwrap(), "a1", policy, 1).unwrap();
+    store
+}
+
+fn agent() -> ToolCaller {
+    ToolCaller {
+        agent_id: Some("a1".to_string()),
+        thread_id: None,
+    }
+}
+
 // ── 1. descriptor_has_expected_shape ─────────────────────────────────────────
 
 #[test]
 fn descriptor_has_expected_shape() {
-    let tool = ShellExecTool::new();
+    let tool = ShellExecTool::new(sandbox());
     let desc = tool.descriptor();
 
     assert_eq!(desc.id, "shell-exec");
@@ -43,7 +75,7 @@ fn descriptor_has_expected_shape() {
 
 #[tokio::test]
 async fn invoke_runs_simple_command() {
-    let tool = ShellExecTool::new();
+    let tool = ShellExecTool::new(sandbox());
     let result = tool
         .invoke(json!({ "command": SHELL.0, "args": [SHELL.1, "echo hello"] }))
         .await;
@@ -61,7 +93,7 @@ async fn invoke_runs_simple_command() {
 
 #[tokio::test]
 async fn invoke_returns_exit_code_zero_on_success() {
-    let tool = ShellExecTool::new();
+    let tool = ShellExecTool::new(sandbox());
     let result = tool
         .invoke(json!({ "command": SHELL.0, "args": [SHELL.1, "echo hi"] }))
         .await;
@@ -80,7 +112,7 @@ async fn invoke_returns_exit_code_zero_on_success() {
 
 #[tokio::test]
 async fn invoke_returns_non_zero_exit_code_for_failed_command() {
-    let tool = ShellExecTool::new();
+    let tool = ShellExecTool::new(sandbox());
     let result = tool
         .invoke(json!({ "command": SHELL.0, "args": [SHELL.1, "exit 7"] }))
         .await;
@@ -102,7 +134,7 @@ async fn invoke_returns_non_zero_exit_code_for_failed_command() {
 
 #[tokio::test]
 async fn invoke_captures_stderr() {
-    let tool = ShellExecTool::new();
+    let tool = ShellExecTool::new(sandbox());
     // cmd needs `1>&2`; sh takes `>&2`.
     let to_stderr = if cfg!(windows) {
         "echo err 1>&2"
@@ -126,7 +158,7 @@ async fn invoke_captures_stderr() {
 
 #[tokio::test]
 async fn invoke_returns_error_for_missing_command() {
-    let tool = ShellExecTool::new();
+    let tool = ShellExecTool::new(sandbox());
     let result = tool.invoke(json!({})).await;
 
     assert!(result.is_err(), "missing 'command' must return Err, got Ok");
@@ -143,7 +175,7 @@ async fn invoke_returns_error_for_missing_command() {
 
 #[tokio::test]
 async fn invoke_returns_error_for_non_string_command() {
-    let tool = ShellExecTool::new();
+    let tool = ShellExecTool::new(sandbox());
     let result = tool.invoke(json!({ "command": 42 })).await;
 
     assert!(
@@ -160,7 +192,7 @@ async fn invoke_returns_error_for_non_string_command() {
 
 #[tokio::test]
 async fn invoke_returns_error_for_non_string_array_args() {
-    let tool = ShellExecTool::new();
+    let tool = ShellExecTool::new(sandbox());
     let result = tool
         .invoke(json!({ "command": "echo", "args": [1, 2] }))
         .await;
@@ -179,7 +211,7 @@ async fn invoke_returns_error_for_non_string_array_args() {
 
 #[tokio::test]
 async fn invoke_returns_error_for_non_array_args() {
-    let tool = ShellExecTool::new();
+    let tool = ShellExecTool::new(sandbox());
     let result = tool
         .invoke(json!({ "command": "echo", "args": "not-array" }))
         .await;
@@ -195,7 +227,7 @@ async fn invoke_returns_error_for_non_array_args() {
 
 #[tokio::test]
 async fn invoke_returns_error_for_unknown_binary() {
-    let tool = ShellExecTool::new();
+    let tool = ShellExecTool::new(sandbox());
     let result = tool
         .invoke(json!({ "command": "/this/binary/does/not/exist/asyar_no_such_bin" }))
         .await;
@@ -223,7 +255,7 @@ async fn invoke_runs_in_cwd() {
 
     // `dir /B` lists bare filenames on Windows; `ls` elsewhere.
     let listing = if cfg!(windows) { "dir /B" } else { "ls" };
-    let tool = ShellExecTool::new();
+    let tool = ShellExecTool::new(sandbox());
     let result = tool
         .invoke(json!({
             "command": SHELL.0,
@@ -248,7 +280,7 @@ async fn invoke_treats_args_as_omitted_when_missing() {
     // `hostname` is a real executable (not a shell builtin) on Windows,
     // macOS, and Linux, and exits 0 with no args — so it exercises the
     // "args key absent → empty args, still runs" path without a shell.
-    let tool = ShellExecTool::new();
+    let tool = ShellExecTool::new(sandbox());
     let result = tool.invoke(json!({ "command": "hostname" })).await;
 
     assert!(
@@ -263,3 +295,85 @@ async fn invoke_treats_args_as_omitted_when_missing() {
         val["exitCode"]
     );
 }
+
+// ── 13. commands_outside_the_allow_list_are_denied_and_audited ───────────────
+
+#[tokio::test]
+async fn commands_outside_the_allow_list_are_denied_and_audited() {
+    let store = store_with_policy(&AgentToolPolicy {
+        allowed_commands: vec!["hostname".into()],
+        ..AgentToolPolicy::default()
+    });
+    let tool = ShellExecTool::new(sandbox_over(store.clone()));
+
+    let result = tool
+        .invoke_as(
+            json!({ "command": SHELL.0, "args": [SHELL.1, "echo hi"] }),
+            &agent(),
+        )
+        .await;
+    assert!(
+        matches!(result, Err(AppError::Permission(_))),
+        "expected a policy denial, got {result:?}"
+    );
+    tool.invoke_as(json!({ "command": "hostname" }), &agent())
+        .await
+        .expect("allow-listed command must run");
+
+    let outcomes: Vec<_> = agent_tool_audit::list_recent(&store.conn().unwrap(), Some("a1"), 10)
+        .unwrap()
+        .into_iter()
+        .map(|row| (row.outcome, row.args_summary.contains("hostname")))
+        .collect();
+    assert_eq!(
+        outcomes,
+        [(AuditOutcome::Ok, true), (AuditOutcome::Denied, false)]
+    );
+}
+
+// ── 14. output_is_cut_at_the_policy_cap ──────────────────────────────────────
+
+#[tokio::test]
+async fn output_is_cut_at_the_policy_cap() {
+    let store = store_with_policy(&AgentToolPolicy {
+        max_output_bytes: 5,
+        ..AgentToolPolicy::default()
+    });
+    let tool = ShellExecTool::new(sandbox_over(store));
+
+    let val = tool
+        .invoke_as(
+            json!({ "command": SHELL.0, "args": [SHELL.1, "echo hello-world"] }),
+            &agent(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(val["stdout"], json!("hello"));
+    assert_eq!(val["truncated"], json!(true));
+}
+
+// ── 15. long_running_commands_hit_the_timeout ────────────────────────────────
+
+#[cfg(not(windows))]
+#[tokio::test]
+async fn long_running_commands_hit_the_timeout() {
+    let store = store_with_policy(&AgentToolPolicy {
+        timeout_secs: 1,
+        ..AgentToolPolicy::default()
+    });
+    let tool = ShellExecTool::new(sandbox_over(store.clone()));
+
+    let started = std::time::Instant::now();
+    let result = tool
+        .invoke_as(json!({ "command": "sleep", "args": ["30"] }), &agent())
+        .await;
+
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+    match result {
+        Err(AppError::Other(msg)) => assert!(msg.contains("timed out"), "got: {msg}"),
+        other => panic!("expected a timeout error, got {other:?}"),
+    }
+    let audit = agent_tool_audit::list_recent(&store.conn().unwrap(), Some("a1"), 1).unwrap();
+    assert_eq!(audit[0].outcome, AuditOutcome::Failed);
+}
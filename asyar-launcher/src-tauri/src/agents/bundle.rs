@@ -0,0 +1,497 @@
+//! Portable agent bundles: agent definitions exported to a versioned JSON
+//! file another install can import. A bundle names providers by *type*
+//! (`openai`, `ollama`) rather than by local connection id and carries no
+//! provider configuration at all, so API keys and base URLs never leave the
+//! machine. Import maps each provider type back onto a usable local
+//! connection and checks every tool against the live [`ToolRegistry`].
+
+use std::collections::{BTreeSet, HashMap};
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::agents::editor::AgentProviderDescriptor;
+use crate::agents::lifecycle::{provider_is_usable, resolve_default_agent};
+use crate::agents::tools::{ToolRegistry, ToolSource};
+use crate::ai::routing::{RouteTarget, RoutingPolicy};
+use crate::ai::types::ProviderConfig;
+use crate::commands::agents::{agents_create_impl, AgentCreateInput};
+use crate::error::AppError;
+use crate::storage::agents::{get_agent, AgentRow, SilentInputSource, SilentOutputAction};
+use crate::storage::DataStore;
+use tauri::{AppHandle, Emitter};
+
+/// Identifies an agent bundle, so importing some other JSON file fails with
+/// a clear message instead of a field-by-field parse error.
+pub const AGENT_BUNDLE_FORMAT: &str = "asyar-agents";
+/// Bumped whenever a bundle changes in a way older imports can't read.
+pub const AGENT_BUNDLE_VERSION: u32 = 1;
+
+/// A provider and model, with the provider named by its type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleModelRef {
+    pub provider_type: String,
+    pub model_id: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BundleRouting {
+    pub fallbacks: Vec<BundleModelRef>,
+    pub max_retries: u32,
+    pub offline_fallback: Option<BundleModelRef>,
+}
+
+/// One agent as it travels in a bundle: everything in [`AgentRow`] except
+/// its local id, timestamps and connection ids.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentBundleEntry {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub system_prompt: String,
+    pub model: BundleModelRef,
+    /// Fully-qualified tool ids (`builtin:calculator`,
+    /// `org.example.notes:lookup`, `mcp:linear:list_issues`).
+    #[serde(default)]
+    pub tool_selection: Vec<String>,
+    #[serde(default)]
+    pub silent: bool,
+    pub input_source: SilentInputSource,
+    pub output_action: SilentOutputAction,
+    #[serde(default)]
+    pub cache_responses: bool,
+    pub shortcode_trigger: String,
+    #[serde(default)]
+    pub monthly_budget_usd: Option<f64>,
+    #[serde(default)]
+    pub routing: BundleRouting,
+    #[serde(default)]
+    #[specta(type = Option<specta_typescript::Any>)]
+    pub output_schema: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentBundle {
+    pub format: String,
+    pub version: u32,
+    /// Unix millis.
+    pub exported_at: i64,
+    pub agents: Vec<AgentBundleEntry>,
+}
+
+/// How an imported agent's provider was resolved on this machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportProviderMatch {
+    /// A usable local connection of the same provider type; the bundled
+    /// model is kept.
+    Matched,
+    /// No connection of that type, so the default agent's provider and
+    /// model are used instead.
+    DefaultAgent,
+    /// Nothing usable. The agent is imported pointing at the bundled
+    /// provider type and needs editing (or a configured provider) to run.
+    Unavailable,
+}
+
+/// What importing one bundled agent does, or did.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentImportPlan {
+    pub name: String,
+    pub provider_id: String,
+    pub model_id: String,
+    pub provider_match: ImportProviderMatch,
+    /// The bundled selection minus anything not registered here.
+    pub tool_selection: Vec<String>,
+    /// Every bundled tool id that isn't registered here; dropped on import.
+    pub missing_tools: Vec<String>,
+    /// Tier 2 extensions none of whose tools are registered — most likely
+    /// not installed.
+    pub missing_extensions: Vec<String>,
+    /// MCP servers with no registered tools — not configured or not running.
+    pub missing_mcp_servers: Vec<String>,
+    /// Provider types of routing fallbacks with no usable local connection;
+    /// those fallbacks are dropped.
+    pub dropped_fallbacks: Vec<String>,
+    /// Id of the created agent; `None` in a preview.
+    pub agent_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentImportReport {
+    pub agents: Vec<AgentImportPlan>,
+}
+
+/// The provider type of local connection `provider_id`: named connections
+/// record it in `provider_type`, built-in ones are keyed by it.
+fn provider_type_of<'a>(
+    provider_id: &'a str,
+    configs: &'a HashMap<String, ProviderConfig>,
+) -> &'a str {
+    configs
+        .get(provider_id)
+        .and_then(|config| config.provider_type.as_deref())
+        .unwrap_or(provider_id)
+}
+
+fn model_ref(
+    provider_id: &str,
+    model_id: &str,
+    configs: &HashMap<String, ProviderConfig>,
+) -> BundleModelRef {
+    BundleModelRef {
+        provider_type: provider_type_of(provider_id, configs).to_string(),
+        model_id: model_id.to_string(),
+    }
+}
+
+fn bundle_entry(agent: &AgentRow, configs: &HashMap<String, ProviderConfig>) -> AgentBundleEntry {
+    let target_ref =
+        |target: &RouteTarget| model_ref(&target.provider_id, &target.model_id, configs);
+    AgentBundleEntry {
+        name: agent.name.clone(),
+        description: agent.description.clone(),
+        system_prompt: agent.system_prompt.clone(),
+        model: model_ref(&agent.provider_id, &agent.model_id, configs),
+        tool_selection: agent.tool_selection.clone(),
+        silent: agent.silent,
+        input_source: agent.input_source,
+        output_action: agent.output_action,
+        cache_responses: agent.cache_responses,
+        shortcode_trigger: agent.shortcode_trigger.clone(),
+        monthly_budget_usd: agent.monthly_budget_usd,
+        routing: BundleRouting {
+            fallbacks: agent.routing.fallbacks.iter().map(target_ref).collect(),
+            max_retries: agent.routing.max_retries,
+            offline_fallback: agent.routing.offline_fallback.as_ref().map(target_ref),
+        },
+        output_schema: agent.output_schema.clone(),
+    }
+}
+
+/// Bundles `agent_ids`, in the order given. `configs` is only read to turn
+/// connection ids into provider types; none of it is copied.
+pub fn agents_export_bundle_impl(
+    conn: &Connection,
+    agent_ids: &[String],
+    configs: &HashMap<String, ProviderConfig>,
+) -> Result<AgentBundle, AppError> {
+    if agent_ids.is_empty() {
+        return Err(AppError::Validation(
+            "select at least one agent to export".to_string(),
+        ));
+    }
+    let agents = agent_ids
+        .iter()
+        .map(|id| {
+            let agent = get_agent(conn, id)?
+                .ok_or_else(|| AppError::NotFound(format!("agent '{id}' not found")))?;
+            Ok(bundle_entry(&agent, configs))
+        })
+        .collect::<Result<_, AppError>>()?;
+    Ok(AgentBundle {
+        format: AGENT_BUNDLE_FORMAT.to_string(),
+        version: AGENT_BUNDLE_VERSION,
+        exported_at: chrono::Utc::now().timestamp_millis(),
+        agents,
+    })
+}
+
+pub fn parse_bundle(text: &str) -> Result<AgentBundle, AppError> {
+    let value: serde_json::Value = serde_json::from_str(text)
+        .map_err(|e| AppError::Validation(format!("agent bundle is not valid JSON: {e}")))?;
+    if value.get("format").and_then(|f| f.as_str()) != Some(AGENT_BUNDLE_FORMAT) {
+        return Err(AppError::Validation(
+            "file is not an Asyar agent bundle".to_string(),
+        ));
+    }
+    let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+    if version == 0 || version > u64::from(AGENT_BUNDLE_VERSION) {
+        return Err(AppError::Validation(format!(
+            "agent bundle version {version} is not supported (this build reads up to {AGENT_BUNDLE_VERSION})"
+        )));
+    }
+    serde_json::from_value(value)
+        .map_err(|e| AppError::Validation(format!("agent bundle is malformed: {e}")))
+}
+
+/// The local side of an import: what's registered and configured here.
+pub struct ImportContext<'a> {
+    pub registry: &'a ToolRegistry,
+    pub providers: &'a [AgentProviderDescriptor],
+    pub configs: &'a HashMap<String, ProviderConfig>,
+    pub default_agent: Option<AgentRow>,
+}
+
+impl ImportContext<'_> {
+    /// A usable local connection of `provider_type`, preferring the one
+    /// keyed by the type itself over named connections.
+    fn connection_for(&self, provider_type: &str) -> Option<String> {
+        let mut candidates = self
+            .configs
+            .keys()
+            .filter(|id| provider_type_of(id, self.configs) == provider_type)
+            .filter(|id| provider_is_usable(id, self.providers, self.configs))
+            .collect::<Vec<_>>();
+        candidates.sort();
+        candidates
+            .iter()
+            .find(|id| id.as_str() == provider_type)
+            .or(candidates.first())
+            .map(|id| id.to_string())
+    }
+
+    fn target_for(&self, model: &BundleModelRef) -> Option<RouteTarget> {
+        self.connection_for(&model.provider_type)
+            .map(|provider_id| RouteTarget {
+                provider_id,
+                model_id: model.model_id.clone(),
+            })
+    }
+}
+
+fn plan_entry(
+    entry: &AgentBundleEntry,
+    ctx: &ImportContext<'_>,
+) -> (AgentImportPlan, AgentCreateInput) {
+    let fallback_agent = ctx
+        .default_agent
+        .as_ref()
+        .filter(|agent| provider_is_usable(&agent.provider_id, ctx.providers, ctx.configs));
+    let (provider_id, model_id, provider_match) = match ctx.target_for(&entry.model) {
+        Some(target) => (
+            target.provider_id,
+            target.model_id,
+            ImportProviderMatch::Matched,
+        ),
+        None => match fallback_agent {
+            Some(agent) => (
+                agent.provider_id.clone(),
+                agent.model_id.clone(),
+                ImportProviderMatch::DefaultAgent,
+            ),
+            None => (
+                entry.model.provider_type.clone(),
+                entry.model.model_id.clone(),
+                ImportProviderMatch::Unavailable,
+            ),
+        },
+    };
+
+    let registered = ctx.registry.list_all();
+    let mut tool_selection = Vec::new();
+    let mut missing_tools = Vec::new();
+    let mut missing_extensions = BTreeSet::new();
+    let mut missing_mcp_servers = BTreeSet::new();
+    for fqid in &entry.tool_selection {
+        if registered
+            .iter()
+            .any(|tool| &tool.fully_qualified_id == fqid)
+        {
+            tool_selection.push(fqid.clone());
+            continue;
+        }
+        missing_tools.push(fqid.clone());
+        let Some((owner, _tool)) = fqid.rsplit_once(':') else {
+            continue;
+        };
+        if owner == "builtin" {
+            continue;
+        }
+        if let Some(server_id) = owner.strip_prefix("mcp:") {
+            let source = ToolSource::Mcp(server_id.to_string());
+            if !registered.iter().any(|tool| tool.source == source) {
+                missing_mcp_servers.insert(server_id.to_string());
+            }
+        } else {
+            let source = ToolSource::Tier2(owner.to_string());
+            if !registered.iter().any(|tool| tool.source == source) {
+                missing_extensions.insert(owner.to_string());
+            }
+        }
+    }
+
+    let mut dropped_fallbacks = Vec::new();
+    let mut keep_target = |model: &BundleModelRef| {
+        let target = ctx.target_for(model);
+        if target.is_none() {
+            dropped_fallbacks.push(model.provider_type.clone());
+        }
+        target
+    };
+    let routing = RoutingPolicy {
+        fallbacks: entry
+            .routing
+            .fallbacks
+            .iter()
+            .filter_map(&mut keep_target)
+            .collect(),
+        max_retries: entry.routing.max_retries,
+        offline_fallback: entry
+            .routing
+            .offline_fallback
+            .as_ref()
+            .and_then(keep_target),
+    };
+
+    let input = AgentCreateInput {
+        name: entry.name.clone(),
+        description: entry.description.clone(),
+        system_prompt: entry.system_prompt.clone(),
+        provider_id: provider_id.clone(),
+        model_id: model_id.clone(),
+        tool_selection: tool_selection.clone(),
+        silent: Some(entry.silent),
+        input_source: Some(entry.input_source),
+        output_action: Some(entry.output_action),
+        cache_responses: Some(entry.cache_responses),
+        shortcode_trigger: Some(entry.shortcode_trigger.clone()),
+        monthly_budget_usd: entry.monthly_budget_usd,
+        routing: Some(routing),
+        output_schema: entry.output_schema.clone(),
+    };
+    let plan = AgentImportPlan {
+        name: entry.name.clone(),
+        provider_id,
+        model_id,
+        provider_match,
+        tool_selection,
+        missing_tools,
+        missing_extensions: missing_extensions.into_iter().collect(),
+        missing_mcp_servers: missing_mcp_servers.into_iter().collect(),
+        dropped_fallbacks,
+        agent_id: None,
+    };
+    (plan, input)
+}
+
+/// What importing `bundle` would do, without writing anything.
+pub fn agents_import_preview_impl(
+    bundle: &AgentBundle,
+    ctx: &ImportContext<'_>,
+) -> AgentImportReport {
+    AgentImportReport {
+        agents: bundle
+            .agents
+            .iter()
+            .map(|entry| plan_entry(entry, ctx).0)
+            .collect(),
+    }
+}
+
+/// Creates one new agent per bundled entry (existing agents are never
+/// overwritten) and reports what was remapped or dropped. All or nothing:
+/// one invalid entry rolls the whole import back.
+pub fn agents_import_bundle_impl(
+    conn: &Connection,
+    bundle: &AgentBundle,
+    ctx: &ImportContext<'_>,
+) -> Result<AgentImportReport, AppError> {
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let mut agents = Vec::with_capacity(bundle.agents.len());
+    for entry in &bundle.agents {
+        let (mut plan, input) = plan_entry(entry, ctx);
+        let row = agents_create_impl(&tx, input)?;
+        plan.agent_id = Some(row.id);
+        agents.push(plan);
+    }
+    tx.commit().map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(AgentImportReport { agents })
+}
+
+/// Builds the [`ImportContext`] for a run of the import commands.
+pub fn import_context<'a>(
+    conn: &Connection,
+    registry: &'a ToolRegistry,
+    default_agent_id: Option<&str>,
+    providers: &'a [AgentProviderDescriptor],
+    configs: &'a HashMap<String, ProviderConfig>,
+) -> Result<ImportContext<'a>, AppError> {
+    Ok(ImportContext {
+        registry,
+        providers,
+        configs,
+        default_agent: resolve_default_agent(conn, default_agent_id)?,
+    })
+}
+
+fn read_bundle_file(path: &str) -> Result<AgentBundle, AppError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| AppError::Other(format!("failed to read agent bundle '{path}': {e}")))?;
+    parse_bundle(&text)
+}
+
+/// Writes the bundle for `agent_ids` to `path` as pretty-printed JSON.
+#[tauri::command]
+pub fn agents_export_bundle(
+    db: tauri::State<'_, DataStore>,
+    agent_ids: Vec<String>,
+    configs: HashMap<String, ProviderConfig>,
+    path: String,
+) -> Result<usize, AppError> {
+    let bundle = {
+        let conn = db.conn()?;
+        agents_export_bundle_impl(&conn, &agent_ids, &configs)?
+    };
+    let text = serde_json::to_string_pretty(&bundle)
+        .map_err(|e| AppError::Other(format!("failed to encode agent bundle: {e}")))?;
+    std::fs::write(&path, text)
+        .map_err(|e| AppError::Other(format!("failed to write agent bundle '{path}': {e}")))?;
+    Ok(bundle.agents.len())
+}
+
+#[tauri::command]
+pub fn agents_import_preview(
+    state: tauri::State<'_, crate::agents::tools::ToolRegistryState>,
+    db: tauri::State<'_, DataStore>,
+    path: String,
+    default_agent_id: Option<String>,
+    providers: Vec<AgentProviderDescriptor>,
+    configs: HashMap<String, ProviderConfig>,
+) -> Result<AgentImportReport, AppError> {
+    let bundle = read_bundle_file(&path)?;
+    let conn = db.conn()?;
+    let ctx = import_context(
+        &conn,
+        &state,
+        default_agent_id.as_deref(),
+        &providers,
+        &configs,
+    )?;
+    Ok(agents_import_preview_impl(&bundle, &ctx))
+}
+
+#[tauri::command]
+pub fn agents_import_bundle(
+    app: AppHandle,
+    state: tauri::State<'_, crate::agents::tools::ToolRegistryState>,
+    db: tauri::State<'_, DataStore>,
+    path: String,
+    default_agent_id: Option<String>,
+    providers: Vec<AgentProviderDescriptor>,
+    configs: HashMap<String, ProviderConfig>,
+) -> Result<AgentImportReport, AppError> {
+    let bundle = read_bundle_file(&path)?;
+    let report = {
+        let conn = db.conn()?;
+        let ctx = import_context(
+            &conn,
+            &state,
+            default_agent_id.as_deref(),
+            &providers,
+            &configs,
+        )?;
+        agents_import_bundle_impl(&conn, &bundle, &ctx)?
+    };
+    let _ = app.emit("agents:changed", ());
+    Ok(report)
+}
@@ -0,0 +1,374 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::agents::bundle::{
+    agents_export_bundle_impl, agents_import_bundle_impl, agents_import_preview_impl, parse_bundle,
+    AgentBundle, ImportContext, ImportProviderMatch, AGENT_BUNDLE_FORMAT, AGENT_BUNDLE_VERSION,
+};
+use crate::agents::editor::AgentProviderDescriptor;
+use crate::agents::tools::{BuiltinTool, ManifestTool, ToolDescriptor, ToolRegistry, ToolSource};
+use crate::ai::routing::{RouteTarget, RoutingPolicy};
+use crate::ai::types::ProviderConfig;
+use crate::error::AppError;
+use crate::storage::agents::{
+    insert_agent, list_agents, AgentRow, SilentInputSource, SilentOutputAction,
+};
+use rusqlite::Connection;
+
+struct TestBuiltin;
+
+#[async_trait::async_trait]
+impl BuiltinTool for TestBuiltin {
+    fn descriptor(&self) -> ToolDescriptor {
+        ToolDescriptor {
+            id: "calculator".into(),
+            name: "Calculator".into(),
+            description: "Calculate an expression".into(),
+            parameters: serde_json::json!({}),
+            source: ToolSource::Builtin,
+            fully_qualified_id: "builtin:calculator".into(),
+        }
+    }
+
+    async fn invoke(&self, args: serde_json::Value) -> Result<serde_json::Value, AppError> {
+        Ok(args)
+    }
+}
+
+fn manifest_tool(id: &str) -> ManifestTool {
+    ManifestTool {
+        id: id.into(),
+        name: id.into(),
+        description: String::new(),
+        parameters: serde_json::json!({}),
+    }
+}
+
+fn registry() -> ToolRegistry {
+    let registry = ToolRegistry::new();
+    registry.register_builtin(Arc::new(TestBuiltin)).unwrap();
+    registry
+        .register_tier2("org.example.notes", vec![manifest_tool("lookup")])
+        .unwrap();
+    registry
+        .register_mcp("linear", vec![manifest_tool("list_issues")])
+        .unwrap();
+    registry
+}
+
+fn provider(id: &str) -> AgentProviderDescriptor {
+    AgentProviderDescriptor {
+        id: id.into(),
+        name: id.into(),
+        requires_api_key: true,
+        requires_base_url: false,
+    }
+}
+
+fn config(provider_type: Option<&str>, api_key: &str) -> ProviderConfig {
+    ProviderConfig {
+        enabled: true,
+        name: None,
+        provider_type: provider_type.map(str::to_owned),
+        api_key: Some(api_key.to_owned()),
+        base_url: Some("https://llm.internal.example".to_owned()),
+        last_model_id: None,
+        open_ai_api_mode: None,
+        hosted_web_search: None,
+        reasoning_effort: None,
+        temperature: None,
+        max_tokens: None,
+    }
+}
+
+fn agent(id: &str, provider_id: &str, model_id: &str) -> AgentRow {
+    AgentRow {
+        id: id.to_string(),
+        name: format!("Agent {id}"),
+        description: Some("Summarises notes".to_string()),
+        system_prompt: "You are helpful.".to_string(),
+        provider_id: provider_id.to_string(),
+        model_id: model_id.to_string(),
+        tool_selection: Vec::new(),
+        silent: false,
+        input_source: SilentInputSource::Argument,
+        output_action: SilentOutputAction::ReplaceSelection,
+        cache_responses: false,
+        shortcode_trigger: ":".to_string(),
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
+        created_at: Some(1),
+        updated_at: Some(1),
+    }
+}
+
+fn make_conn() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute("PRAGMA foreign_keys = ON", []).unwrap();
+    crate::storage::agents::init_table(&conn).unwrap();
+    conn
+}
+
+fn bundle_of(entries: Vec<AgentRow>, configs: &HashMap<String, ProviderConfig>) -> AgentBundle {
+    let conn = make_conn();
+    let ids = entries.iter().map(|a| a.id.clone()).collect::<Vec<_>>();
+    for row in &entries {
+        insert_agent(&conn, row).unwrap();
+    }
+    agents_export_bundle_impl(&conn, &ids, configs).unwrap()
+}
+
+#[test]
+fn export_names_providers_by_type_and_carries_no_configuration() {
+    let mut configs = HashMap::new();
+    configs.insert(
+        "work-openai".to_string(),
+        config(Some("openai"), "sk-secret"),
+    );
+    configs.insert("ollama".to_string(), config(None, "unused-key"));
+    let mut row = agent("a1", "work-openai", "gpt-4o");
+    row.tool_selection = vec!["builtin:calculator".to_string()];
+    row.routing = RoutingPolicy {
+        fallbacks: vec![RouteTarget {
+            provider_id: "ollama".to_string(),
+            model_id: "llama3".to_string(),
+        }],
+        max_retries: 2,
+        offline_fallback: None,
+    };
+
+    let bundle = bundle_of(vec![row], &configs);
+
+    assert_eq!(bundle.format, AGENT_BUNDLE_FORMAT);
+    assert_eq!(bundle.version, AGENT_BUNDLE_VERSION);
+    let entry = &bundle.agents[0];
+    assert_eq!(entry.model.provider_type, "openai");
+    assert_eq!(entry.model.model_id, "gpt-4o");
+    assert_eq!(entry.routing.fallbacks[0].provider_type, "ollama");
+    assert_eq!(entry.routing.max_retries, 2);
+    assert_eq!(entry.tool_selection, vec!["builtin:calculator"]);
+
+    let text = serde_json::to_string(&bundle).unwrap();
+    for leaked in ["sk-secret", "llm.internal.example", "work-openai"] {
+        assert!(!text.contains(leaked), "bundle leaked {leaked}: {text}");
+    }
+}
+
+#[test]
+fn export_rejects_empty_and_unknown_selections() {
+    let conn = make_conn();
+    let configs = HashMap::new();
+    assert!(matches!(
+        agents_export_bundle_impl(&conn, &[], &configs),
+        Err(AppError::Validation(_))
+    ));
+    assert!(matches!(
+        agents_export_bundle_impl(&conn, &["missing".to_string()], &configs),
+        Err(AppError::NotFound(_))
+    ));
+}
+
+#[test]
+fn parse_round_trips_and_rejects_foreign_or_newer_files() {
+    let mut configs = HashMap::new();
+    configs.insert("openai".to_string(), config(None, "sk"));
+    let bundle = bundle_of(vec![agent("a1", "openai", "gpt-4o")], &configs);
+    let text = serde_json::to_string_pretty(&bundle).unwrap();
+    assert_eq!(parse_bundle(&text).unwrap(), bundle);
+
+    let newer = text.replace(
+        &format!("\"version\": {AGENT_BUNDLE_VERSION}"),
+        &format!("\"version\": {}", AGENT_BUNDLE_VERSION + 1),
+    );
+    for bad in [
+        "not json",
+        r#"{ "format": "something-else", "version": 1, "agents": [] }"#,
+        newer.as_str(),
+    ] {
+        assert!(matches!(parse_bundle(bad), Err(AppError::Validation(_))));
+    }
+}
+
+#[test]
+fn preview_reports_missing_tools_by_owner() {
+    let mut configs = HashMap::new();
+    configs.insert("openai".to_string(), config(None, "sk"));
+    let mut row = agent("a1", "openai", "gpt-4o");
+    row.tool_selection = vec![
+        "builtin:calculator".to_string(),
+        "builtin:retired-tool".to_string(),
+        "org.example.notes:lookup".to_string(),
+        "org.example.notes:append".to_string(),
+        "org.example.tasks:create".to_string(),
+        "mcp:linear:list_issues".to_string(),
+        "mcp:github:search".to_string(),
+    ];
+    let bundle = bundle_of(vec![row], &configs);
+    let registry = registry();
+    let providers = vec![provider("openai")];
+    let ctx = ImportContext {
+        registry: &registry,
+        providers: &providers,
+        configs: &configs,
+        default_agent: None,
+    };
+
+    let report = agents_import_preview_impl(&bundle, &ctx);
+    let plan = &report.agents[0];
+
+    assert_eq!(
+        plan.tool_selection,
+        vec![
+            "builtin:calculator",
+            "org.example.notes:lookup",
+            "mcp:linear:list_issues"
+        ]
+    );
+    assert_eq!(
+        plan.missing_tools,
+        vec![
+            "builtin:retired-tool",
+            "org.example.notes:append",
+            "org.example.tasks:create",
+            "mcp:github:search"
+        ]
+    );
+    // `org.example.notes` is installed, just older; only wholly absent
+    // owners are reported.
+    assert_eq!(plan.missing_extensions, vec!["org.example.tasks"]);
+    assert_eq!(plan.missing_mcp_servers, vec!["github"]);
+    assert_eq!(plan.agent_id, None);
+}
+
+#[test]
+fn preview_remaps_providers_onto_local_connections() {
+    let mut source_configs = HashMap::new();
+    source_configs.insert("openai".to_string(), config(None, "sk"));
+    source_configs.insert("anthropic".to_string(), config(None, "sk"));
+    let mut routed = agent("a2", "anthropic", "claude");
+    routed.routing.fallbacks = vec![
+        RouteTarget {
+            provider_id: "openai".to_string(),
+            model_id: "gpt-4o-mini".to_string(),
+        },
+        RouteTarget {
+            provider_id: "anthropic".to_string(),
+            model_id: "claude-small".to_string(),
+        },
+    ];
+    let bundle = bundle_of(
+        vec![agent("a1", "openai", "gpt-4o"), routed],
+        &source_configs,
+    );
+
+    // Here, OpenAI only exists as a named connection and Anthropic isn't
+    // configured at all.
+    let mut configs = HashMap::new();
+    configs.insert(
+        "team-openai".to_string(),
+        config(Some("openai"), "sk-local"),
+    );
+    configs.insert("ollama".to_string(), config(None, "unused"));
+    let registry = registry();
+    let providers = vec![provider("openai"), provider("ollama")];
+    let ctx = ImportContext {
+        registry: &registry,
+        providers: &providers,
+        configs: &configs,
+        default_agent: Some(agent("default", "ollama", "llama3")),
+    };
+
+    let report = agents_import_preview_impl(&bundle, &ctx);
+
+    let matched = &report.agents[0];
+    assert_eq!(matched.provider_match, ImportProviderMatch::Matched);
+    assert_eq!(matched.provider_id, "team-openai");
+    assert_eq!(matched.model_id, "gpt-4o");
+
+    let remapped = &report.agents[1];
+    assert_eq!(remapped.provider_match, ImportProviderMatch::DefaultAgent);
+    assert_eq!(remapped.provider_id, "ollama");
+    assert_eq!(remapped.model_id, "llama3");
+    assert_eq!(remapped.dropped_fallbacks, vec!["anthropic"]);
+
+    let bare = ImportContext {
+        default_agent: None,
+        ..ctx
+    };
+    let report = agents_import_preview_impl(&bundle, &bare);
+    let unavailable = &report.agents[1];
+    assert_eq!(unavailable.provider_match, ImportProviderMatch::Unavailable);
+    assert_eq!(unavailable.provider_id, "anthropic");
+    assert_eq!(unavailable.model_id, "claude");
+}
+
+#[test]
+fn import_creates_new_agents_with_the_remapped_settings() {
+    let mut configs = HashMap::new();
+    configs.insert("openai".to_string(), config(None, "sk"));
+    let mut row = agent("a1", "openai", "gpt-4o");
+    row.tool_selection = vec![
+        "builtin:calculator".to_string(),
+        "mcp:github:search".to_string(),
+    ];
+    row.silent = true;
+    row.output_action = SilentOutputAction::Copy;
+    row.monthly_budget_usd = Some(5.0);
+    let bundle = bundle_of(vec![row], &configs);
+
+    let conn = make_conn();
+    insert_agent(&conn, &agent("a1", "openai", "gpt-4o")).unwrap();
+    let registry = registry();
+    let providers = vec![provider("openai")];
+    let ctx = ImportContext {
+        registry: &registry,
+        providers: &providers,
+        configs: &configs,
+        default_agent: None,
+    };
+
+    let report = agents_import_bundle_impl(&conn, &bundle, &ctx).unwrap();
+
+    let new_id = report.agents[0].agent_id.clone().expect("created agent id");
+    assert_ne!(new_id, "a1", "import never overwrites an existing agent");
+    let agents = list_agents(&conn).unwrap();
+    assert_eq!(agents.len(), 2);
+    let created = agents.iter().find(|a| a.id == new_id).unwrap();
+    assert_eq!(created.name, "Agent a1");
+    assert_eq!(created.provider_id, "openai");
+    assert_eq!(created.tool_selection, vec!["builtin:calculator"]);
+    assert!(created.silent);
+    assert_eq!(created.output_action, SilentOutputAction::Copy);
+    assert_eq!(created.monthly_budget_usd, Some(5.0));
+}
+
+#[test]
+fn import_is_all_or_nothing() {
+    let mut configs = HashMap::new();
+    configs.insert("openai".to_string(), config(None, "sk"));
+    let mut bundle = bundle_of(
+        vec![
+            agent("a1", "openai", "gpt-4o"),
+            agent("a2", "openai", "gpt-4o"),
+        ],
+        &configs,
+    );
+    bundle.agents[1].system_prompt = "   ".to_string();
+
+    let conn = make_conn();
+    let registry = registry();
+    let providers = vec![provider("openai")];
+    let ctx = ImportContext {
+        registry: &registry,
+        providers: &providers,
+        configs: &configs,
+        default_agent: None,
+    };
+
+    assert!(matches!(
+        agents_import_bundle_impl(&conn, &bundle, &ctx),
+        Err(AppError::Validation(_))
+    ));
+    assert!(list_agents(&conn).unwrap().is_empty());
+}
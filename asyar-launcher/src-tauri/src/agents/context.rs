@@ -0,0 +1,287 @@
+//! Keeping a thread inside its model's context window.
+//!
+//! Token counts are estimated locally — about four characters per token,
+//! plus a flat charge per message and per image — because exact tokenizers
+//! differ per provider and none are available offline. When the estimate
+//! nears the budget, the runner summarizes the oldest turns into a note
+//! pinned to the thread (see [`compaction_split`]) and sends only the
+//! recent ones verbatim.
+
+use crate::ai::types::{ChatMessage, ContentPart};
+
+const CHARS_PER_TOKEN: usize = 4;
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+const IMAGE_TOKENS: usize = 1_000;
+
+/// Share of the input budget a thread may fill before its older turns are
+/// summarized.
+const COMPACT_AT: f64 = 0.8;
+
+/// Share of the input budget left to the turns kept verbatim after
+/// compaction, so the next few turns don't trigger it again.
+const KEEP_RECENT: f64 = 0.4;
+
+/// Tool results longer than this are cut down to their head and tail before
+/// they're sent; the stored message keeps the full output.
+pub const TOOL_OUTPUT_MAX_CHARS: usize = 12_000;
+
+/// Cap on each message's share of the transcript handed to the summarizer.
+const TRANSCRIPT_MESSAGE_MAX_CHARS: usize = 2_000;
+
+/// Instructions for the summarization request.
+pub const SUMMARY_PROMPT: &str = "You condense conversations between a user and an AI assistant. Write a concise summary of the transcript that keeps everything needed to continue it: the user's goals and preferences, facts and decisions established, results of tool calls, and open questions. Write plain prose or short bullet points, and do not address the user.";
+
+pub fn estimate_text(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+pub fn estimate_message(message: &ChatMessage) -> usize {
+    let tool_calls = message
+        .tool_calls
+        .iter()
+        .flatten()
+        .map(|call| estimate_text(&call.name) + estimate_text(&call.input.to_string()))
+        .sum::<usize>();
+    let parts = message
+        .parts
+        .iter()
+        .flatten()
+        .map(|part| match part {
+            ContentPart::Text { text } => estimate_text(text),
+            ContentPart::Image { .. } => IMAGE_TOKENS,
+            ContentPart::File { text, data, .. } => text
+                .as_deref()
+                .map(estimate_text)
+                .or_else(|| data.as_ref().map(|data| data.len() / CHARS_PER_TOKEN))
+                .unwrap_or_default(),
+        })
+        .sum::<usize>();
+    MESSAGE_OVERHEAD_TOKENS + estimate_text(&message.content) + tool_calls + parts
+}
+
+pub fn estimate_messages(messages: &[ChatMessage]) -> usize {
+    messages.iter().map(estimate_message).sum()
+}
+
+/// Tokens left for the prompt once the answer's `max_tokens` is reserved;
+/// never less than a quarter of the window.
+pub fn input_budget(context_window: usize, max_tokens: u32) -> usize {
+    context_window
+        .saturating_sub(max_tokens as usize)
+        .max(context_window / 4)
+}
+
+/// Whether a prompt of `used` tokens is close enough to `budget` to
+/// compact now.
+pub fn needs_compaction(used: usize, budget: usize) -> bool {
+    used as f64 > budget as f64 * COMPACT_AT
+}
+
+/// Where to cut `messages` for compaction: everything before the returned
+/// index is summarized. The cut always lands on a user message, so a tool
+/// call is never separated from its result, and keeps at least the latest
+/// user turn even when that alone exceeds the target. `None` when there is
+/// nothing older than the kept turns.
+pub fn compaction_split(messages: &[ChatMessage], budget: usize) -> Option<usize> {
+    let target = (budget as f64 * KEEP_RECENT) as usize;
+    let mut kept = 0;
+    let mut split = None;
+    for (index, message) in messages.iter().enumerate().rev() {
+        kept += estimate_message(message);
+        if message.role != "user" {
+            continue;
+        }
+        if split.is_some() && kept > target {
+            break;
+        }
+        split = Some(index);
+    }
+    split.filter(|split| *split > 0)
+}
+
+/// Last resort when a thread still doesn't fit (compaction failed or isn't
+/// available): drop whole turns from the front until it does, keeping the
+/// latest user turn regardless.
+pub fn trim_to_budget(messages: Vec<ChatMessage>, budget: usize) -> Vec<ChatMessage> {
+    let mut total = estimate_messages(&messages);
+    let mut start = 0;
+    while total > budget {
+        let Some(next) = messages[start + 1..]
+            .iter()
+            .position(|message| message.role == "user")
+            .map(|offset| start + 1 + offset)
+        else {
+            break;
+        };
+        total -= estimate_messages(&messages[start..next]);
+        start = next;
+    }
+    messages.into_iter().skip(start).collect()
+}
+
+/// Shortens an oversized tool result to its head and tail. `stored_id`
+/// names the message holding the full output, when there is one.
+pub fn truncate_tool_output(output: &str, stored_id: Option<&str>) -> Option<String> {
+    let total = output.chars().count();
+    if total <= TOOL_OUTPUT_MAX_CHARS {
+        return None;
+    }
+    let head_len = TOOL_OUTPUT_MAX_CHARS * 3 / 4;
+    let tail_len = TOOL_OUTPUT_MAX_CHARS - head_len;
+    let head: String = output.chars().take(head_len).collect();
+    let tail: String = output.chars().skip(total - tail_len).collect();
+    let pointer = match stored_id {
+        Some(id) => format!(" The complete output is stored in this thread as message {id}."),
+        None => String::new(),
+    };
+    Some(format!(
+        "{head}\n\n[Tool output truncated: {} of {total} characters omitted.{pointer}]\n\n{tail}",
+        total - head_len - tail_len
+    ))
+}
+
+/// The system prompt with the thread's summary pinned under it.
+pub fn with_summary(system_prompt: &str, summary: Option<&str>) -> String {
+    match summary {
+        Some(summary) => format!(
+            "{system_prompt}\n\nSummary of the earlier conversation, whose messages are no longer shown:\n{summary}"
+        ),
+        None => system_prompt.to_string(),
+    }
+}
+
+/// The summarizer's input: the previous summary, if any, followed by the
+/// turns being folded into it, each clipped to a readable length and the
+/// whole kept within `budget`, newest turns first to survive.
+pub fn summary_request(previous: Option<&str>, messages: &[ChatMessage], budget: usize) -> String {
+    let mut lines: Vec<String> = messages
+        .iter()
+        .map(|message| {
+            let speaker = match message.role.as_str() {
+                "user" => "User",
+                "assistant" => "Assistant",
+                "tool" => "Tool result",
+                other => other,
+            };
+            let mut text = message.content.clone();
+            if let Some(calls) = message
+                .tool_calls
+                .as_ref()
+                .filter(|calls| !calls.is_empty())
+            {
+                let names: Vec<&str> = calls.iter().map(|call| call.name.as_str()).collect();
+                text.push_str(&format!(" [called {}]", names.join(", ")));
+            }
+            if text.chars().count() > TRANSCRIPT_MESSAGE_MAX_CHARS {
+                text = text.chars().take(TRANSCRIPT_MESSAGE_MAX_CHARS).collect();
+                text.push('…');
+            }
+            format!("{speaker}: {text}")
+        })
+        .collect();
+    let mut remaining = budget * CHARS_PER_TOKEN;
+    let mut kept = Vec::new();
+    while let Some(line) = lines.pop() {
+        let length = line.chars().count();
+        if length > remaining {
+            break;
+        }
+        remaining -= length;
+        kept.push(line);
+    }
+    kept.reverse();
+    let transcript = kept.join("\n\n");
+    match previous {
+        Some(previous) => format!(
+            "Summary so far:\n{previous}\n\nLater transcript:\n{transcript}\n\nRewrite the summary to cover both."
+        ),
+        None => format!("Transcript:\n{transcript}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: &str) -> ChatMessage {
+        ChatMessage {
+            id: format!("{role}-{}", content.len()),
+            role: role.to_string(),
+            content: content.to_string(),
+            timestamp: 0,
+            tool_calls: None,
+            tool_call_id: None,
+            provider_context: None,
+            parts: None,
+        }
+    }
+
+    fn turns(count: usize, size: usize) -> Vec<ChatMessage> {
+        (0..count)
+            .flat_map(|_| {
+                [
+                    message("user", &"u".repeat(size)),
+                    message("assistant", &"a".repeat(size)),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn estimates_round_up_and_charge_per_message() {
+        assert_eq!(estimate_text(""), 0);
+        assert_eq!(estimate_text("abcde"), 2);
+        assert_eq!(estimate_message(&message("user", "abcd")), 5);
+    }
+
+    #[test]
+    fn budget_reserves_the_answer_but_keeps_a_floor() {
+        assert_eq!(input_budget(128_000, 4_096), 123_904);
+        assert_eq!(input_budget(4_096, 4_096), 1_024);
+    }
+
+    #[test]
+    fn compaction_cuts_at_a_user_message_and_keeps_recent_turns() {
+        // Ten turns of 108 tokens each against a 1000-token budget: the
+        // kept tail may use 400, so the last three turns survive.
+        let messages = turns(10, 200);
+        let split = compaction_split(&messages, 1_000).unwrap();
+        assert_eq!(split, 14);
+        assert_eq!(messages[split].role, "user");
+    }
+
+    #[test]
+    fn compaction_keeps_the_latest_turn_even_when_it_alone_is_too_big() {
+        let mut messages = turns(2, 40);
+        messages.push(message("user", &"x".repeat(8_000)));
+        assert_eq!(compaction_split(&messages, 1_000), Some(4));
+        assert_eq!(compaction_split(&messages[4..], 1_000), None);
+    }
+
+    #[test]
+    fn trimming_drops_whole_turns_from_the_front() {
+        let messages = turns(5, 400);
+        let trimmed = trim_to_budget(messages, 450);
+        assert_eq!(trimmed.len(), 4);
+        assert_eq!(trimmed[0].role, "user");
+    }
+
+    #[test]
+    fn long_tool_output_keeps_head_and_tail_with_a_pointer() {
+        assert_eq!(truncate_tool_output("short", Some("m1")), None);
+        let output = format!("{}{}", "h".repeat(20_000), "t".repeat(5_000));
+        let truncated = truncate_tool_output(&output, Some("m1")).unwrap();
+        assert!(truncated.starts_with(&"h".repeat(9_000)));
+        assert!(truncated.ends_with(&"t".repeat(3_000)));
+        assert!(truncated.contains("13000 of 25000 characters omitted"));
+        assert!(truncated.contains("message m1"));
+    }
+
+    #[test]
+    fn summary_request_folds_in_the_previous_summary() {
+        let messages = turns(1, 10);
+        let request = summary_request(Some("Earlier: hi"), &messages, 1_000);
+        assert!(request.starts_with("Summary so far:\nEarlier: hi"));
+        assert!(request.contains("User: uuuuuuuuuu\n\nAssistant: aaaaaaaaaa"));
+    }
+}
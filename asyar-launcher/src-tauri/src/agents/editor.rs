@@ -5,6 +5,7 @@ use specta::Type;
 
 use crate::agents::tools::{ToolDescriptor, ToolRegistry, ToolSource};
 use crate::ai::models::list_models_impl;
+use crate::ai::routing::RoutingPolicy;
 use crate::ai::types::{ModelInfo, ProviderConfig};
 use crate::commands::agents::{
     agents_create_impl, agents_update_impl, AgentCreateInput, AgentUpdateInput,
@@ -75,6 +76,14 @@ pub struct AgentEditorForm {
     pub output_action: SilentOutputAction,
     pub cache_responses: bool,
     pub shortcode_trigger: String,
+    #[serde(default)]
+    pub monthly_budget_usd: Option<f64>,
+    #[serde(default)]
+    pub routing: RoutingPolicy,
+    /// JSON Schema for structured output, as the text the user edits; empty
+    /// means free-text answers.
+    #[serde(default)]
+    pub output_schema: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
@@ -297,6 +306,13 @@ fn editor_form(
             output_action: agent.output_action,
             cache_responses: agent.cache_responses,
             shortcode_trigger: agent.shortcode_trigger.clone(),
+            monthly_budget_usd: agent.monthly_budget_usd,
+            routing: agent.routing.clone(),
+            output_schema: agent
+                .output_schema
+                .as_ref()
+                .and_then(|schema| serde_json::to_string_pretty(schema).ok())
+                .unwrap_or_default(),
         },
         None => {
             let (provider_id, model_id) =
@@ -313,6 +329,9 @@ fn editor_form(
                 output_action: SilentOutputAction::ReplaceSelection,
                 cache_responses: false,
                 shortcode_trigger: ":".to_string(),
+                monthly_budget_usd: None,
+                routing: RoutingPolicy::default(),
+                output_schema: String::new(),
             }
         }
     }
@@ -343,6 +362,13 @@ pub fn agents_editor_save_impl(
     } else {
         Some(form.description)
     };
+    let output_schema = if form.output_schema.trim().is_empty() {
+        None
+    } else {
+        Some(serde_json::from_str(&form.output_schema).map_err(|error| {
+            AppError::Validation(format!("output schema is not valid JSON: {error}"))
+        })?)
+    };
     match agent_id {
         Some(id) => agents_update_impl(
             conn,
@@ -359,6 +385,9 @@ pub fn agents_editor_save_impl(
                 output_action: Some(form.output_action),
                 cache_responses: Some(form.cache_responses),
                 shortcode_trigger: Some(form.shortcode_trigger.clone()),
+                monthly_budget_usd: form.monthly_budget_usd,
+                routing: Some(form.routing),
+                output_schema,
             },
         ),
         None => agents_create_impl(
@@ -375,6 +404,9 @@ pub fn agents_editor_save_impl(
                 output_action: Some(form.output_action),
                 cache_responses: Some(form.cache_responses),
                 shortcode_trigger: Some(form.shortcode_trigger),
+                monthly_budget_usd: form.monthly_budget_usd,
+                routing: Some(form.routing),
+                output_schema,
             },
         ),
     }
@@ -71,6 +71,9 @@ fn agent(id: &str, provider_id: &str, model_id: &str) -> AgentRow {
         output_action: SilentOutputAction::ReplaceSelection,
         cache_responses: false,
         shortcode_trigger: ":".to_string(),
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
         created_at: Some(1),
         updated_at: Some(1),
     }
@@ -489,6 +492,9 @@ fn editor_save_validates_and_persists_the_frontend_form() {
         output_action: SilentOutputAction::ReplaceSelection,
         cache_responses: true,
         shortcode_trigger: ":".to_string(),
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: String::new(),
     };
 
     let row = agents_editor_save_impl(&conn, None, form).unwrap();
@@ -520,6 +526,9 @@ fn editor_save_rejects_invalid_forms_in_rust() {
         output_action: SilentOutputAction::ReplaceSelection,
         cache_responses: false,
         shortcode_trigger: ":".to_string(),
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: String::new(),
     };
 
     assert!(matches!(
@@ -527,3 +536,41 @@ fn editor_save_rejects_invalid_forms_in_rust() {
         Err(AppError::Validation(_))
     ));
 }
+
+#[test]
+fn editor_save_parses_the_output_schema_text() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute("PRAGMA foreign_keys = ON", []).unwrap();
+    crate::storage::agents::init_table(&conn).unwrap();
+    let form = AgentEditorForm {
+        name: "Extractor".into(),
+        description: "".into(),
+        system_prompt: "Extract contacts.".into(),
+        provider_id: "openai".into(),
+        model_id: "gpt-4o".into(),
+        tool_selection: vec![],
+        silent: false,
+        input_source: SilentInputSource::Argument,
+        output_action: SilentOutputAction::ReplaceSelection,
+        cache_responses: false,
+        shortcode_trigger: ":".to_string(),
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: r#"{ "type": "object", "required": ["name"] }"#.into(),
+    };
+
+    let row = agents_editor_save_impl(&conn, None, form.clone()).unwrap();
+    assert_eq!(
+        row.output_schema,
+        Some(serde_json::json!({ "type": "object", "required": ["name"] }))
+    );
+
+    let broken = AgentEditorForm {
+        output_schema: "{ not json".into(),
+        ..form
+    };
+    assert!(matches!(
+        agents_editor_save_impl(&conn, Some(row.id), broken),
+        Err(AppError::Validation(_))
+    ));
+}
@@ -44,6 +44,9 @@ fn default_agent(provider_id: &str, model_id: &str) -> AgentRow {
         output_action: SilentOutputAction::ReplaceSelection,
         cache_responses: false,
         shortcode_trigger: ":".to_string(),
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
         created_at: Some(now),
         updated_at: Some(now),
     }
@@ -142,6 +145,9 @@ pub fn seed_grammar_fix_agent(
         output_action: SilentOutputAction::ReplaceSelection,
         cache_responses: false,
         shortcode_trigger: ":".to_string(),
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
         created_at: Some(now),
         updated_at: Some(now),
     };
@@ -186,6 +192,9 @@ pub fn seed_emoji_fallback_agent(
         output_action: SilentOutputAction::Paste,
         cache_responses: true,
         shortcode_trigger: ":".to_string(),
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
         created_at: Some(now),
         updated_at: Some(now),
     };
@@ -220,7 +229,7 @@ pub fn sanitize_emoji_fallback_output(text: &str) -> String {
     }
 }
 
-fn provider_is_usable(
+pub(crate) fn provider_is_usable(
     provider_id: &str,
     providers: &[crate::agents::editor::AgentProviderDescriptor],
     configs: &std::collections::HashMap<String, crate::ai::types::ProviderConfig>,
@@ -324,6 +333,9 @@ mod tests {
             output_action: crate::storage::agents::SilentOutputAction::ReplaceSelection,
             cache_responses: false,
             shortcode_trigger: ":".to_string(),
+            monthly_budget_usd: None,
+            routing: Default::default(),
+            output_schema: None,
             created_at: Some(1),
             updated_at: Some(1),
         }
@@ -576,6 +588,9 @@ mod tests {
             output_action: crate::storage::agents::SilentOutputAction::Paste,
             cache_responses: true,
             shortcode_trigger: ":".to_string(),
+            monthly_budget_usd: None,
+            routing: Default::default(),
+            output_schema: None,
             created_at: Some(1),
             updated_at: Some(1),
         }
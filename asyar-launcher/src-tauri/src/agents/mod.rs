@@ -1,14 +1,24 @@
 pub mod builtin_tools;
+pub mod bundle;
 pub mod cache;
+pub mod context;
 pub mod editor;
 pub mod lifecycle;
 pub mod runner;
 pub mod tool_executor;
+pub mod tool_policy;
 pub mod tools;
+pub mod triggers;
 
+#[cfg(test)]
+mod bundle_test;
 #[cfg(test)]
 mod editor_test;
 #[cfg(test)]
 mod runner_test;
 #[cfg(test)]
+mod tool_policy_test;
+#[cfg(test)]
 mod tools_test;
+#[cfg(test)]
+mod triggers_test;
@@ -1,24 +1,34 @@
+use crate::agents::builtin_tools::delegate::{self, DelegateArgs, DELEGATE_TOOL_ID};
+use crate::agents::context;
 use crate::agents::editor::AgentProviderDescriptor;
 use crate::agents::lifecycle::resolve_runnable_agent;
-use crate::agents::tools::ToolRegistry;
+use crate::agents::tools::{ToolCaller, ToolRegistry};
+use crate::ai::commands::{send_chat_request, stream_chat_response, RequestFailure};
+use crate::ai::routing::{AttemptOutcome, RouteTarget, Router};
 use crate::ai::types::{
-    ChatMessage, ChatParams, ChatStreamEventPayload, ProviderConfig, ToolCall, ToolDefinition,
+    ChatMessage, ChatParams, ChatStreamEventPayload, ContentPart, ProviderConfig, TokenUsage,
+    ToolCall, ToolDefinition,
 };
 use crate::error::AppError;
 use crate::storage::agents::{
-    get_thread, insert_message, list_messages_for_thread, update_thread_title, AgentRow,
-    MessageRole, MessageRow, SilentInputSource,
+    agent_spend_since, get_thread, get_thread_summary, insert_attempt, insert_message,
+    insert_usage, list_agents, list_thread_path, set_thread_summary, update_thread_title, AgentRow,
+    AttemptRow, MessageRole, MessageRow, SilentInputSource, ThreadSummary, UsageRow,
 };
 use crate::storage::DataStore;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::{HashMap, HashSet};
 use std::future::Future;
+use std::pin::Pin;
 use std::sync::{Arc, Mutex};
 use tokio::sync::{oneshot, watch};
 use uuid::Uuid;
 
 const MAX_TURNS: usize = 20;
+/// How many `delegate-to-agent` hops one run may chain. Sub-runs at the
+/// limit aren't offered the tool at all.
+pub const MAX_DELEGATION_DEPTH: u32 = 3;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -32,6 +42,22 @@ pub enum AgentStreamEvent {
         status: Option<String>,
     },
     AssistantTurnPersisted,
+    /// Token usage of the provider response that just finished, with its
+    /// estimated cost (`None` when the model has no known price).
+    Usage {
+        usage: TokenUsage,
+        cost_usd: Option<f64>,
+    },
+    /// A provider request failed before anything streamed; the run retries
+    /// or moves to its next route after `retry_in_ms`.
+    ProviderAttemptFailed {
+        provider_id: String,
+        model_id: String,
+        outcome: AttemptOutcome,
+        status_code: Option<u16>,
+        message: String,
+        retry_in_ms: u64,
+    },
     ToolDispatch {
         tool_call_id: String,
         extension_id: String,
@@ -51,6 +77,37 @@ pub enum AgentStreamEvent {
     McpPermissionCancelled {
         tool_call_id: String,
     },
+    /// The thread outgrew its model's context window and its oldest
+    /// `summarized_messages` were folded into the thread's summary.
+    ContextCompacted {
+        summarized_messages: u32,
+    },
+    /// The final answer of an agent with an output schema, parsed and
+    /// validated. Sent just before `completed`.
+    StructuredOutput {
+        #[specta(type = specta_typescript::Any)]
+        value: Value,
+    },
+    /// A `delegate-to-agent` call started a sub-run of `agent_id`.
+    SubAgentStarted {
+        tool_call_id: String,
+        agent_id: String,
+        agent_name: String,
+        task: String,
+    },
+    /// An event from inside the sub-run started by `tool_call_id`. Deeper
+    /// delegations arrive wrapped once per level.
+    SubAgentEvent {
+        tool_call_id: String,
+        event: Box<AgentStreamEvent>,
+    },
+    /// The sub-run ended; `error` is set when it failed, which also fails
+    /// the delegating run.
+    SubAgentFinished {
+        tool_call_id: String,
+        cancelled: bool,
+        error: Option<String>,
+    },
     Error {
         message: String,
     },
@@ -74,8 +131,21 @@ pub struct AgentRunConfig {
     pub max_tokens: u32,
 }
 
+/// What a silent run hands back: the text its output action applies and,
+/// for an agent with an output schema, the parsed answer that text was
+/// rendered from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SilentRunOutput {
+    pub text: String,
+    #[specta(type = Option<specta_typescript::Any>)]
+    pub structured: Option<Value>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ExternalToolRequest {
+    /// The agent making the call; a sub-agent's id inside a delegated run.
+    pub agent_id: String,
     pub tool_call_id: String,
     pub tool_id: String,
     pub arguments: Value,
@@ -147,6 +217,17 @@ impl AgentRunnerState {
         self.cancel_pending_requests(stream_id)
     }
 
+    /// Follows `cancel_run` for the active run on `stream_id`, for work
+    /// outside the runner loop (e.g. an MCP call) that should stop with it.
+    pub fn cancellation(&self, stream_id: &str) -> Result<Option<watch::Receiver<bool>>, AppError> {
+        Ok(self
+            .cancellations
+            .lock()
+            .map_err(|_| AppError::Lock)?
+            .get(stream_id)
+            .map(watch::Sender::subscribe))
+    }
+
     pub fn cancel_run(&self, stream_id: &str) -> Result<(), AppError> {
         let sender = self
             .cancellations
@@ -294,6 +375,7 @@ struct TurnOutput {
     text: String,
     tool_calls: Vec<ToolCall>,
     provider_context: Vec<Value>,
+    usage: Option<TokenUsage>,
     status_active: bool,
 }
 
@@ -303,32 +385,122 @@ enum Conversation<'a> {
         thread_id: &'a str,
         run_id: &'a str,
     },
+    /// Silent runs: the transcript lives only for the run, but usage is
+    /// still recorded against the store for spend and budgets.
     Ephemeral {
+        store: &'a DataStore,
         messages: Vec<ChatMessage>,
     },
 }
 
 impl Conversation<'_> {
-    fn messages(&self) -> Result<Vec<ChatMessage>, AppError> {
+    fn store(&self) -> &DataStore {
+        match self {
+            Self::Persistent { store, .. } | Self::Ephemeral { store, .. } => store,
+        }
+    }
+
+    fn thread_id(&self) -> Option<&str> {
+        match self {
+            Self::Persistent { thread_id, .. } => Some(thread_id),
+            Self::Ephemeral { .. } => None,
+        }
+    }
+
+    /// The selected path as sent to the provider: oversized tool results
+    /// truncated, and everything the thread's summary covers replaced by
+    /// that summary, returned alongside.
+    fn messages(&self) -> Result<(Option<String>, Vec<ChatMessage>), AppError> {
+        match self {
+            Self::Persistent {
+                store, thread_id, ..
+            } => {
+                let conn = store.conn()?;
+                let mut rows = list_thread_path(&conn, thread_id)?;
+                let mut summary = None;
+                if let Some(stored) = get_thread_summary(&conn, thread_id)? {
+                    if let Some(through) = rows
+                        .iter()
+                        .position(|row| row.id == stored.through_message_id)
+                    {
+                        rows.drain(..=through);
+                        summary = Some(stored.text);
+                    }
+                }
+                let messages = rows
+                    .iter()
+                    .map(|row| {
+                        let mut message = row_to_chat_message(row);
+                        if row.role == MessageRole::Tool {
+                            if let Some(truncated) =
+                                context::truncate_tool_output(&message.content, Some(&row.id))
+                            {
+                                message.content = truncated;
+                            }
+                        }
+                        message
+                    })
+                    .collect();
+                Ok((summary, messages))
+            }
+            Self::Ephemeral { messages, .. } => Ok((
+                None,
+                messages
+                    .iter()
+                    .cloned()
+                    .map(|mut message| {
+                        if message.role == "tool" {
+                            if let Some(truncated) =
+                                context::truncate_tool_output(&message.content, None)
+                            {
+                                message.content = truncated;
+                            }
+                        }
+                        message
+                    })
+                    .collect(),
+            )),
+        }
+    }
+
+    /// Pin `text` as the thread's summary of everything up to and including
+    /// `through_message_id`. Ephemeral transcripts have nowhere to keep one,
+    /// so they return `false` and rely on trimming instead.
+    fn store_summary(&self, text: String, through_message_id: String) -> Result<bool, AppError> {
         match self {
             Self::Persistent {
                 store, thread_id, ..
             } => {
-                let rows = list_messages_for_thread(&*store.conn()?, thread_id)?;
-                Ok(rows.iter().map(row_to_chat_message).collect())
+                set_thread_summary(
+                    &*store.conn()?,
+                    thread_id,
+                    &ThreadSummary {
+                        text,
+                        through_message_id,
+                    },
+                )?;
+                Ok(true)
             }
-            Self::Ephemeral { messages } => Ok(messages.clone()),
+            Self::Ephemeral { .. } => Ok(false),
         }
     }
 
+    fn can_compact(&self) -> bool {
+        matches!(self, Self::Persistent { .. })
+    }
+
+    /// Returns the new message's id, or `None` when the turn was empty and
+    /// nothing was pushed. `structured` is the validated answer of an agent
+    /// with an output schema; only persistent threads keep it.
     fn push_assistant(
         &mut self,
         text: String,
         tool_calls: Vec<ToolCall>,
         provider_context: Vec<Value>,
-    ) -> Result<bool, AppError> {
+        structured: Option<Value>,
+    ) -> Result<Option<String>, AppError> {
         if text.is_empty() && tool_calls.is_empty() && provider_context.is_empty() {
-            return Ok(false);
+            return Ok(None);
         }
         let id = Uuid::new_v4().to_string();
         let timestamp = chrono::Utc::now().timestamp_millis();
@@ -346,29 +518,82 @@ impl Conversation<'_> {
                 if !provider_context.is_empty() {
                     content["providerContext"] = Value::Array(provider_context);
                 }
+                if let Some(structured) = structured {
+                    content["structured"] = structured;
+                }
                 insert_message(
                     &*store.conn()?,
                     &MessageRow {
-                        id,
+                        id: id.clone(),
                         thread_id: (*thread_id).to_string(),
                         role: MessageRole::Assistant,
                         content,
                         created_at: timestamp,
                         run_id: Some((*run_id).to_string()),
+                        parent_id: None,
                     },
                 )?;
             }
-            Self::Ephemeral { messages } => messages.push(ChatMessage {
-                id,
+            Self::Ephemeral { messages, .. } => messages.push(ChatMessage {
+                id: id.clone(),
                 role: "assistant".to_string(),
                 content: text,
                 timestamp,
                 tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
                 tool_call_id: None,
                 provider_context: (!provider_context.is_empty()).then_some(provider_context),
+                parts: None,
             }),
         }
-        Ok(true)
+        Ok(Some(id))
+    }
+
+    /// Persist one response's usage. `message_id` is only linked for
+    /// persistent threads; ephemeral message ids never reach the database.
+    fn record_usage(
+        &self,
+        agent: &AgentRow,
+        route: &ResolvedRoute,
+        message_id: Option<String>,
+        usage: TokenUsage,
+    ) -> Result<Option<f64>, AppError> {
+        let cost_usd = crate::ai::pricing::cost_usd(&route.engine, &route.target.model_id, &usage);
+        let (message_id, thread_id, run_id) = match self {
+            Self::Persistent {
+                thread_id, run_id, ..
+            } => (
+                message_id,
+                Some((*thread_id).to_string()),
+                Some((*run_id).to_string()),
+            ),
+            Self::Ephemeral { .. } => (None, None, None),
+        };
+        insert_usage(
+            &*self.store().conn()?,
+            &UsageRow {
+                id: Uuid::new_v4().to_string(),
+                message_id,
+                thread_id,
+                run_id,
+                agent_id: agent.id.clone(),
+                provider_id: route.target.provider_id.clone(),
+                model_id: route.target.model_id.clone(),
+                usage,
+                cost_usd,
+                created_at: chrono::Utc::now().timestamp_millis(),
+            },
+        )?;
+        Ok(cost_usd)
+    }
+
+    /// Persist one provider attempt, stamped with this run's id when the
+    /// conversation has one.
+    fn record_attempt(&self, row: AttemptRow) -> Result<(), AppError> {
+        let run_id = match self {
+            Self::Persistent { run_id, .. } => Some((*run_id).to_string()),
+            Self::Ephemeral { .. } => None,
+        };
+        insert_attempt(&*self.store().conn()?, &AttemptRow { run_id, ..row })
     }
 
     fn push_tool_result(&mut self, tool_call_id: String, output: Value) -> Result<(), AppError> {
@@ -393,9 +618,10 @@ impl Conversation<'_> {
                     }),
                     created_at: timestamp,
                     run_id: Some((*run_id).to_string()),
+                    parent_id: None,
                 },
             )?,
-            Self::Ephemeral { messages } => messages.push(ChatMessage {
+            Self::Ephemeral { messages, .. } => messages.push(ChatMessage {
                 id,
                 role: "tool".to_string(),
                 content: output.to_string(),
@@ -403,6 +629,7 @@ impl Conversation<'_> {
                 tool_calls: None,
                 tool_call_id: Some(tool_call_id),
                 provider_context: None,
+                parts: None,
             }),
         }
         Ok(())
@@ -424,6 +651,11 @@ fn row_to_chat_message(row: &MessageRow) -> ChatMessage {
         .pointer("/toolResult/toolUseId")
         .and_then(Value::as_str)
         .map(str::to_string);
+    let parts = row
+        .content
+        .get("parts")
+        .and_then(|value| serde_json::from_value::<Vec<ContentPart>>(value.clone()).ok())
+        .filter(|parts| !parts.is_empty());
     let content = if row.role == MessageRole::Tool {
         row.content
             .pointer("/toolResult/output")
@@ -450,6 +682,7 @@ fn row_to_chat_message(row: &MessageRow) -> ChatMessage {
         tool_calls,
         tool_call_id,
         provider_context,
+        parts,
     }
 }
 
@@ -469,6 +702,9 @@ pub(crate) fn coalesce_consecutive_messages(messages: Vec<ChatMessage>) -> Vec<C
                 (true, _) => message.content,
                 _ => previous.content.clone(),
             };
+            if let Some(parts) = message.parts {
+                previous.parts.get_or_insert_with(Vec::new).extend(parts);
+            }
         } else {
             output.push(message);
         }
@@ -476,11 +712,14 @@ pub(crate) fn coalesce_consecutive_messages(messages: Vec<ChatMessage>) -> Vec<C
     output
 }
 
+/// `memories` are appended after template resolution, so a remembered
+/// `{date}` or `{query}` stays literal.
 pub(crate) async fn build_system_prompt(
     base_prompt: &str,
     hosted_web_search: bool,
     trigger: Option<&str>,
     query: Option<&str>,
+    memories: &[String],
 ) -> String {
     let mut sections = vec![
         "The available horizontal display space is 400px. Format responses for this width and avoid unnecessarily wide content."
@@ -509,9 +748,63 @@ pub(crate) async fn build_system_prompt(
         query: query.map(|s| s.to_string()),
         trigger: trigger.map(|s| s.to_string()),
     };
-    crate::templating::resolve_template(&joined, &final_ctx)
+    let prompt = crate::templating::resolve_template(&joined, &final_ctx)
         .await
-        .unwrap_or(joined)
+        .unwrap_or(joined);
+    if memories.is_empty() {
+        return prompt;
+    }
+    format!(
+        "{prompt}\n\nThings you remember about the user from earlier conversations. Use them when relevant without reciting them, and forget any that turn out to be wrong:\n{}",
+        memories.join("\n")
+    )
+}
+
+/// Memories relevant to `query`, one prompt line each, for agents that have
+/// a memory tool enabled. Recall goes through `memory-search` itself, so
+/// the prompt shows exactly what the model could look up; it is
+/// best-effort, and a failure only leaves the memories out.
+async fn recall_memories(
+    agent: &AgentRow,
+    registry: &ToolRegistry,
+    caller: &ToolCaller,
+    query: Option<&str>,
+) -> Vec<String> {
+    let remembers = agent
+        .tool_selection
+        .iter()
+        .any(|id| id.starts_with("builtin:memory-"));
+    let Some(query) = query.filter(|query| remembers && !query.trim().is_empty()) else {
+        return Vec::new();
+    };
+    let found = crate::agents::tools::invoke_builtin_tool_as(
+        registry,
+        "memory-search",
+        json!({ "query": query }),
+        caller,
+    )
+    .await;
+    match found {
+        Ok(found) => found["results"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|memory| {
+                Some(format!(
+                    "- {} (memory {})",
+                    memory["content"].as_str()?,
+                    memory["id"].as_str()?
+                ))
+            })
+            .collect(),
+        Err(error) => {
+            log::warn!(
+                "[agents] recalling memories for '{}' failed: {error}",
+                agent.id
+            );
+            Vec::new()
+        }
+    }
 }
 
 /// Looks up `provider_id` in the run's provider configs and validates it,
@@ -574,6 +867,174 @@ pub(crate) fn resolve_provider_config<'a>(
     Ok(config)
 }
 
+/// A routing target whose provider passed [`resolve_provider_config`], with
+/// everything a request to it needs worked out up front.
+struct ResolvedRoute {
+    target: RouteTarget,
+    config: ProviderConfig,
+    /// Engine that builds and parses the request (`provider_type`, or the
+    /// provider id for legacy configs).
+    engine: String,
+    /// Rendered per route: hosted web search adds guidance of its own.
+    system_prompt: String,
+}
+
+async fn resolve_route(
+    agent: &AgentRow,
+    target: RouteTarget,
+    config: ProviderConfig,
+    query: Option<&str>,
+    memories: &[String],
+) -> ResolvedRoute {
+    let engine = config
+        .provider_type
+        .clone()
+        .unwrap_or_else(|| target.provider_id.clone());
+    let system_prompt = build_system_prompt(
+        &agent.system_prompt,
+        config.hosted_web_search.unwrap_or(false),
+        Some(&agent.shortcode_trigger),
+        query,
+        memories,
+    )
+    .await;
+    ResolvedRoute {
+        target,
+        config,
+        engine,
+        system_prompt,
+    }
+}
+
+/// The agent's own provider must be usable; fallbacks that aren't
+/// (disabled, missing a key, deleted) are skipped so one stale entry in
+/// the policy can't take the whole run down. The offline fallback is only
+/// honoured when it points at an Ollama connection.
+async fn resolve_router(
+    agent: &AgentRow,
+    config: &AgentRunConfig,
+    query: Option<&str>,
+    memories: &[String],
+) -> Result<Router<ResolvedRoute>, AppError> {
+    let primary_config = resolve_provider_config(&agent.provider_id, &config.configs)?.clone();
+    let primary = RouteTarget {
+        provider_id: agent.provider_id.clone(),
+        model_id: agent.model_id.clone(),
+    };
+    let mut routes = vec![resolve_route(agent, primary, primary_config, query, memories).await];
+    for target in &agent.routing.fallbacks {
+        match resolve_provider_config(&target.provider_id, &config.configs) {
+            Ok(fallback_config) => routes.push(
+                resolve_route(
+                    agent,
+                    target.clone(),
+                    fallback_config.clone(),
+                    query,
+                    memories,
+                )
+                .await,
+            ),
+            Err(error) => log::warn!(
+                "[agents] skipping fallback '{}' for agent '{}': {error}",
+                target.provider_id,
+                agent.id
+            ),
+        }
+    }
+    let mut offline = None;
+    if let Some(target) = &agent.routing.offline_fallback {
+        match resolve_provider_config(&target.provider_id, &config.configs) {
+            Ok(offline_config)
+                if offline_config
+                    .provider_type
+                    .as_deref()
+                    .unwrap_or(&target.provider_id)
+                    == "ollama" =>
+            {
+                offline = Some(
+                    resolve_route(
+                        agent,
+                        target.clone(),
+                        offline_config.clone(),
+                        query,
+                        memories,
+                    )
+                    .await,
+                );
+            }
+            Ok(_) => log::warn!(
+                "[agents] offline fallback '{}' for agent '{}' is not an Ollama connection",
+                target.provider_id,
+                agent.id
+            ),
+            Err(error) => log::warn!(
+                "[agents] skipping offline fallback '{}' for agent '{}': {error}",
+                target.provider_id,
+                agent.id
+            ),
+        }
+    }
+    Ok(Router::new(routes, offline, agent.routing.max_retries))
+}
+
+/// An in-memory turn that is never persisted: the structured-output
+/// correction exchange, or a compaction request.
+fn transient_message(role: &str, text: String) -> ChatMessage {
+    ChatMessage {
+        id: Uuid::new_v4().to_string(),
+        role: role.to_string(),
+        content: text,
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        tool_calls: None,
+        tool_call_id: None,
+        provider_context: None,
+        parts: None,
+    }
+}
+
+/// Provider context (reasoning items, thought signatures) is only
+/// meaningful to the engine that produced it, so it is dropped whenever a
+/// run has been routed away from the agent's own engine.
+fn without_provider_context(messages: &[ChatMessage]) -> Vec<ChatMessage> {
+    messages
+        .iter()
+        .cloned()
+        .map(|message| ChatMessage {
+            provider_context: None,
+            ..message
+        })
+        .collect()
+}
+
+/// Local midnight on the first of the current month, in epoch millis.
+fn month_start_ms() -> i64 {
+    use chrono::{Datelike, Local, TimeZone};
+    let today = Local::now().date_naive();
+    today
+        .with_day(1)
+        .and_then(|first| first.and_hms_opt(0, 0, 0))
+        .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+        .map(|start| start.timestamp_millis())
+        .unwrap_or(0)
+}
+
+/// Refuse the run when the agent's priced spend this month has reached its
+/// `monthly_budget_usd`. Checked once per run, so a run that crosses the
+/// cap mid-loop still finishes.
+pub(crate) fn check_monthly_budget(store: &DataStore, agent: &AgentRow) -> Result<(), AppError> {
+    let Some(budget) = agent.monthly_budget_usd else {
+        return Ok(());
+    };
+    let spent = agent_spend_since(&*store.conn()?, &agent.id, month_start_ms())?;
+    if spent >= budget {
+        return Err(AppError::Validation(format!(
+            "agent '{}' has reached its monthly budget (${spent:.2} of ${budget:.2})",
+            agent.name
+        )));
+    }
+    Ok(())
+}
+
 fn resolve_tools(
     agent: &AgentRow,
     registry: &ToolRegistry,
@@ -603,6 +1064,65 @@ fn resolve_tools(
     Ok((definitions, wire_to_fqid))
 }
 
+/// Cap on the summary's own length, so compaction frees most of what it
+/// folds away.
+const SUMMARY_MAX_TOKENS: u32 = 1_024;
+
+/// One-off, tool-less request folding `messages` (and any `previous`
+/// summary) into a new summary, sent to the route the turn is about to use.
+async fn summarize(
+    route: &ResolvedRoute,
+    previous: Option<&str>,
+    messages: &[ChatMessage],
+    budget: usize,
+    temperature: f64,
+) -> Result<(String, Option<TokenUsage>), AppError> {
+    let request = transient_message(
+        "user",
+        context::summary_request(previous, messages, budget / 2),
+    );
+    let params = ChatParams {
+        model_id: route.target.model_id.clone(),
+        temperature,
+        max_tokens: SUMMARY_MAX_TOKENS,
+        system_prompt: Some(context::SUMMARY_PROMPT.to_string()),
+        tools: None,
+        response_schema: None,
+    };
+    let config = ProviderConfig {
+        hosted_web_search: Some(false),
+        ..route.config.clone()
+    };
+    let spec = crate::ai::providers::build_request(
+        &route.target.provider_id,
+        &config,
+        &[request],
+        &params,
+    )?;
+    let response = send_chat_request(spec, false)
+        .await
+        .map_err(|failure| AppError::Network(failure.message))?;
+    let output = Arc::new(Mutex::new((String::new(), None)));
+    let collected = Arc::clone(&output);
+    stream_chat_response(response, &route.target.provider_id, &config, move |event| {
+        let Ok(mut collected) = collected.lock() else {
+            return;
+        };
+        match event {
+            ChatStreamEventPayload::Token { token } => collected.0.push_str(&token),
+            ChatStreamEventPayload::Usage { usage } => collected.1 = Some(usage),
+            _ => {}
+        }
+    })
+    .await?;
+    let (text, usage) = std::mem::take(&mut *output.lock().map_err(|_| AppError::Lock)?);
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err(AppError::Other("summary came back empty".to_string()));
+    }
+    Ok((text, usage))
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn run_loop<F, D, Fut>(
     agent: &AgentRow,
@@ -613,86 +1133,284 @@ async fn run_loop<F, D, Fut>(
     dispatch_external: D,
     mut cancellation: Option<watch::Receiver<bool>>,
     query: Option<&str>,
+    depth: u32,
 ) -> Result<Option<String>, AppError>
 where
     F: Fn(AgentStreamEvent) + Clone + Send + Sync + 'static,
     D: Fn(ExternalToolRequest) -> Fut + Send + Sync,
     Fut: Future<Output = Result<Value, AppError>> + Send,
 {
-    let provider_config = resolve_provider_config(&agent.provider_id, &config.configs)?.clone();
-    let (tool_definitions, wire_to_fqid) = resolve_tools(agent, registry)?;
+    check_monthly_budget(conversation.store(), agent)?;
+    let caller = ToolCaller {
+        agent_id: Some(agent.id.clone()),
+        thread_id: conversation.thread_id().map(str::to_string),
+    };
+    let memories = recall_memories(agent, registry, &caller, query).await;
+    let mut router = resolve_router(agent, &config, query, &memories).await?;
+    let primary_engine = router.current().engine.clone();
+    let (mut tool_definitions, wire_to_fqid) = resolve_tools(agent, registry)?;
+    let is_delegate = |tool: &ToolDefinition| {
+        wire_to_fqid
+            .get(&tool.name)
+            .and_then(|id| id.strip_prefix("builtin:"))
+            == Some(DELEGATE_TOOL_ID)
+    };
+    if let Some(index) = tool_definitions.iter().position(is_delegate) {
+        let offered = if depth >= MAX_DELEGATION_DEPTH {
+            None
+        } else {
+            let agents = list_agents(&*conversation.store().conn()?)?;
+            delegate::descriptor_for_caller(&agents, &agent.id)
+        };
+        match offered {
+            Some(descriptor) => {
+                tool_definitions[index].description = descriptor.description;
+                tool_definitions[index].parameters = descriptor.parameters;
+            }
+            None => {
+                tool_definitions.remove(index);
+            }
+        }
+    }
     let tools = (!tool_definitions.is_empty()).then_some(tool_definitions);
-    let system_prompt = build_system_prompt(
-        &agent.system_prompt,
-        provider_config.hosted_web_search.unwrap_or(false),
-        Some(&agent.shortcode_trigger),
-        query,
-    )
-    .await;
+    let mut attempt = 0u32;
+    let mut corrections: Vec<ChatMessage> = Vec::new();
 
-    for _ in 0..MAX_TURNS {
+    for turn_index in 0..MAX_TURNS {
         if cancellation.as_ref().is_some_and(|signal| *signal.borrow()) {
             return Ok(None);
         }
-        let messages = coalesce_consecutive_messages(conversation.messages()?);
-        let params = ChatParams {
-            model_id: agent.model_id.clone(),
-            temperature: config.temperature,
-            max_tokens: config.max_tokens,
-            system_prompt: Some(system_prompt.clone()),
-            tools: tools.clone(),
+        // Budget against the route this turn starts on; a failover to a
+        // smaller model can still overflow, which its provider reports.
+        let route = router.current();
+        let budget = context::input_budget(
+            crate::ai::models::context_window(&route.engine, &route.target.model_id),
+            config.max_tokens,
+        );
+        let tool_tokens = tools
+            .iter()
+            .flatten()
+            .map(|tool| {
+                context::estimate_text(&tool.description)
+                    + context::estimate_text(&tool.parameters.to_string())
+            })
+            .sum::<usize>();
+        let fixed_tokens = |summary: Option<&str>| {
+            context::estimate_text(&route.system_prompt)
+                + summary.map(context::estimate_text).unwrap_or_default()
+                + tool_tokens
         };
-        let output = Arc::new(Mutex::new(TurnOutput::default()));
-        let output_for_stream = Arc::clone(&output);
-        let on_stream_event = on_event.clone();
-        let stream_future = crate::ai::commands::ai_stream_chat_impl(
-            &agent.provider_id,
-            provider_config.clone(),
-            messages,
-            params,
-            Uuid::new_v4().to_string(),
-            move |event| {
-                let Ok(mut output) = output_for_stream.lock() else {
-                    return;
+        let (mut summary, mut messages) = conversation.messages()?;
+        let used = fixed_tokens(summary.as_deref()) + context::estimate_messages(&messages);
+        if conversation.can_compact() && context::needs_compaction(used, budget) {
+            if let Some(split) = context::compaction_split(&messages, budget) {
+                on_event(AgentStreamEvent::Status {
+                    status: Some("summarizing".to_string()),
+                });
+                let summarized = summarize(
+                    route,
+                    summary.as_deref(),
+                    &messages[..split],
+                    budget,
+                    config.temperature,
+                );
+                let summarized = if let Some(signal) = cancellation.as_mut() {
+                    tokio::select! {
+                        result = summarized => result,
+                        _ = wait_for_cancellation(signal) => return Ok(None),
+                    }
+                } else {
+                    summarized.await
                 };
-                match event {
-                    ChatStreamEventPayload::Token { token } => {
-                        if output.status_active {
-                            output.status_active = false;
-                            on_stream_event(AgentStreamEvent::Status { status: None });
+                on_event(AgentStreamEvent::Status { status: None });
+                match summarized {
+                    Ok((text, usage)) => {
+                        if let Some(usage) = usage {
+                            let cost_usd = conversation.record_usage(agent, route, None, usage)?;
+                            on_event(AgentStreamEvent::Usage { usage, cost_usd });
                         }
-                        output.text.push_str(&token);
-                        on_stream_event(AgentStreamEvent::TextDelta {
-                            delta: token,
-                            accumulated: output.text.clone(),
+                        conversation.store_summary(text.clone(), messages[split - 1].id.clone())?;
+                        messages.drain(..split);
+                        summary = Some(text);
+                        on_event(AgentStreamEvent::ContextCompacted {
+                            summarized_messages: split as u32,
                         });
                     }
-                    ChatStreamEventPayload::Status { status } => {
-                        output.status_active = true;
-                        on_stream_event(AgentStreamEvent::Status {
-                            status: Some(status),
-                        });
-                    }
-                    ChatStreamEventPayload::ToolCall { id, name, input } => {
-                        output.tool_calls.push(ToolCall { id, name, input });
+                    // Trimming below still keeps the request inside the
+                    // window; the next turn tries compacting again.
+                    Err(error) => log::warn!(
+                        "[agents] compacting thread for agent '{}' failed: {error}",
+                        agent.id
+                    ),
+                }
+            }
+        }
+        let messages = context::trim_to_budget(
+            messages,
+            budget.saturating_sub(fixed_tokens(summary.as_deref())),
+        );
+        let system_prompt_for =
+            |route: &ResolvedRoute| context::with_summary(&route.system_prompt, summary.as_deref());
+        let mut history = coalesce_consecutive_messages(messages);
+        history.extend(corrections.iter().cloned());
+        let output = Arc::new(Mutex::new(TurnOutput::default()));
+        let stream_result = loop {
+            let route = router.current();
+            let target = route.target.clone();
+            let messages = if route.engine == primary_engine {
+                history.clone()
+            } else {
+                without_provider_context(&history)
+            };
+            let params = ChatParams {
+                model_id: target.model_id.clone(),
+                temperature: config.temperature,
+                max_tokens: config.max_tokens,
+                system_prompt: Some(system_prompt_for(route)),
+                tools: tools.clone(),
+                response_schema: agent.output_schema.clone(),
+            };
+            attempt += 1;
+            let started_at = chrono::Utc::now().timestamp_millis();
+            let attempt_row = |outcome, status_code, error| AttemptRow {
+                id: Uuid::new_v4().to_string(),
+                run_id: None,
+                agent_id: agent.id.clone(),
+                turn: turn_index as u32,
+                attempt,
+                provider_id: target.provider_id.clone(),
+                model_id: target.model_id.clone(),
+                outcome,
+                status_code,
+                error,
+                started_at,
+                ended_at: chrono::Utc::now().timestamp_millis(),
+            };
+
+            // Each attempt goes through its own engine's request builder;
+            // a body built for one provider is never replayed to another.
+            let opened = match crate::ai::providers::build_request(
+                &target.provider_id,
+                &route.config,
+                &messages,
+                &params,
+            ) {
+                Ok(spec) => {
+                    let send_future =
+                        send_chat_request(spec, route.config.hosted_web_search == Some(true));
+                    if let Some(signal) = cancellation.as_mut() {
+                        tokio::select! {
+                            result = send_future => result,
+                            _ = wait_for_cancellation(signal) => return Ok(None),
+                        }
+                    } else {
+                        send_future.await
                     }
-                    ChatStreamEventPayload::ProviderContext { item } => {
-                        output.provider_context.push(item);
+                }
+                Err(error) => Err(RequestFailure {
+                    outcome: AttemptOutcome::Failed,
+                    status: None,
+                    retry_after: None,
+                    message: error.to_string(),
+                }),
+            };
+
+            let response = match opened {
+                Ok(response) => response,
+                Err(failure) => {
+                    conversation.record_attempt(attempt_row(
+                        failure.outcome,
+                        failure.status,
+                        Some(failure.message.clone()),
+                    ))?;
+                    let Some(delay) =
+                        router.next_after_failure(failure.outcome, failure.retry_after)
+                    else {
+                        on_event(AgentStreamEvent::Error {
+                            message: failure.message.clone(),
+                        });
+                        break Err(AppError::Other(failure.message));
+                    };
+                    on_event(AgentStreamEvent::ProviderAttemptFailed {
+                        provider_id: target.provider_id,
+                        model_id: target.model_id,
+                        outcome: failure.outcome,
+                        status_code: failure.status,
+                        message: failure.message,
+                        retry_in_ms: delay.as_millis() as u64,
+                    });
+                    let backoff = tokio::time::sleep(delay);
+                    if let Some(signal) = cancellation.as_mut() {
+                        tokio::select! {
+                            _ = backoff => {}
+                            _ = wait_for_cancellation(signal) => return Ok(None),
+                        }
+                    } else {
+                        backoff.await;
                     }
-                    ChatStreamEventPayload::Error { error } => {
-                        on_stream_event(AgentStreamEvent::Error { message: error });
+                    continue;
+                }
+            };
+
+            let output_for_stream = Arc::clone(&output);
+            let on_stream_event = on_event.clone();
+            let stream_future =
+                stream_chat_response(response, &target.provider_id, &route.config, move |event| {
+                    let Ok(mut output) = output_for_stream.lock() else {
+                        return;
+                    };
+                    match event {
+                        ChatStreamEventPayload::Token { token } => {
+                            if output.status_active {
+                                output.status_active = false;
+                                on_stream_event(AgentStreamEvent::Status { status: None });
+                            }
+                            output.text.push_str(&token);
+                            on_stream_event(AgentStreamEvent::TextDelta {
+                                delta: token,
+                                accumulated: output.text.clone(),
+                            });
+                        }
+                        ChatStreamEventPayload::Status { status } => {
+                            output.status_active = true;
+                            on_stream_event(AgentStreamEvent::Status {
+                                status: Some(status),
+                            });
+                        }
+                        ChatStreamEventPayload::ToolCall { id, name, input } => {
+                            output.tool_calls.push(ToolCall { id, name, input });
+                        }
+                        ChatStreamEventPayload::ProviderContext { item } => {
+                            output.provider_context.push(item);
+                        }
+                        ChatStreamEventPayload::Usage { usage } => {
+                            output.usage = Some(usage);
+                        }
+                        ChatStreamEventPayload::Error { error } => {
+                            on_stream_event(AgentStreamEvent::Error { message: error });
+                        }
+                        ChatStreamEventPayload::Done => {}
                     }
-                    ChatStreamEventPayload::Done => {}
+                });
+            let result = if let Some(signal) = cancellation.as_mut() {
+                tokio::select! {
+                    result = stream_future => result,
+                    _ = wait_for_cancellation(signal) => return Ok(None),
                 }
-            },
-        );
-        let stream_result = if let Some(signal) = cancellation.as_mut() {
-            tokio::select! {
-                result = stream_future => result,
-                _ = wait_for_cancellation(signal) => return Ok(None),
+            } else {
+                stream_future.await
+            };
+            // Part of the answer has already reached the user, so a broken
+            // stream ends the run rather than being routed elsewhere.
+            let (outcome, error) = match &result {
+                Ok(()) => (AttemptOutcome::Succeeded, None),
+                Err(error) => (AttemptOutcome::Failed, Some(error.to_string())),
+            };
+            conversation.record_attempt(attempt_row(outcome, None, error))?;
+            if result.is_ok() {
+                router.succeeded();
             }
-        } else {
-            stream_future.await
+            break result;
         };
 
         let mut turn = {
@@ -727,30 +1445,98 @@ where
             });
         }
 
-        let assistant_persisted = conversation.push_assistant(
+        // A final answer under an output schema is checked before it's kept.
+        // The first miss is retried once, in memory, with the errors spelled
+        // out; a second miss is kept for the user to see and fails the run.
+        let mut structured = None;
+        let mut invalid = None;
+        if let Some(schema) = agent
+            .output_schema
+            .as_ref()
+            .filter(|_| resolved_calls.is_empty() && stream_result.is_ok())
+        {
+            match crate::ai::structured::check_answer(schema, &turn.text) {
+                Ok(value) => structured = Some(value),
+                Err(errors) if corrections.is_empty() => {
+                    if let Some(usage) = turn.usage {
+                        let cost_usd =
+                            conversation.record_usage(agent, router.current(), None, usage)?;
+                        on_event(AgentStreamEvent::Usage { usage, cost_usd });
+                    }
+                    let prompt = crate::ai::structured::correction_prompt(&errors);
+                    corrections.push(transient_message("assistant", turn.text));
+                    corrections.push(transient_message("user", prompt));
+                    continue;
+                }
+                Err(errors) => invalid = Some(errors),
+            }
+        }
+
+        let assistant_message_id = conversation.push_assistant(
             turn.text.clone(),
             resolved_calls.clone(),
             turn.provider_context,
+            structured.clone(),
         )?;
-        if assistant_persisted {
+        if assistant_message_id.is_some() {
             on_event(AgentStreamEvent::AssistantTurnPersisted);
         }
+        if let Some(usage) = turn.usage {
+            let cost_usd =
+                conversation.record_usage(agent, router.current(), assistant_message_id, usage)?;
+            on_event(AgentStreamEvent::Usage { usage, cost_usd });
+        }
 
         stream_result?;
+        if let Some(errors) = invalid {
+            return Err(AppError::Validation(format!(
+                "agent output did not match its schema: {}",
+                errors.join("; ")
+            )));
+        }
         if resolved_calls.is_empty() {
-            return Ok(Some(turn.text));
+            return Ok(Some(match structured {
+                Some(value) => {
+                    let rendered = crate::ai::structured::render(&value);
+                    on_event(AgentStreamEvent::StructuredOutput { value });
+                    rendered
+                }
+                None => turn.text,
+            }));
         }
 
         for tool_call in resolved_calls {
-            let output = if let Some(builtin_id) = tool_call.name.strip_prefix("builtin:") {
-                crate::agents::tools::agents_invoke_builtin_tool_impl(
+            let output = if tool_call.name.strip_prefix("builtin:") == Some(DELEGATE_TOOL_ID) {
+                let dispatch = |request: ExternalToolRequest| -> ExternalToolFuture<'_> {
+                    Box::pin(dispatch_external(request))
+                };
+                let sink: Arc<dyn Fn(AgentStreamEvent) + Send + Sync> = Arc::new(on_event.clone());
+                let delegated = run_delegation(
+                    conversation.store(),
+                    registry,
+                    config.clone(),
+                    &tool_call,
+                    depth + 1,
+                    sink,
+                    &dispatch,
+                    cancellation.clone(),
+                )
+                .await?;
+                match delegated {
+                    Some(output) => output,
+                    None => return Ok(None),
+                }
+            } else if let Some(builtin_id) = tool_call.name.strip_prefix("builtin:") {
+                crate::agents::tools::invoke_builtin_tool_as(
                     registry,
                     builtin_id,
                     tool_call.input.clone(),
+                    &caller,
                 )
                 .await?
             } else {
                 let request = ExternalToolRequest {
+                    agent_id: agent.id.clone(),
                     tool_call_id: tool_call.id.clone(),
                     tool_id: tool_call.name.clone(),
                     arguments: tool_call.input.clone(),
@@ -774,6 +1560,80 @@ where
     )))
 }
 
+type ExternalToolFuture<'a> = Pin<Box<dyn Future<Output = Result<Value, AppError>> + Send + 'a>>;
+
+/// The parent run's `dispatch_external`, type-erased so nested delegations
+/// don't instantiate `run_loop` once per level.
+type ErasedDispatch<'a> = dyn Fn(ExternalToolRequest) -> ExternalToolFuture<'a> + Send + Sync + 'a;
+
+/// Runs the sub-agent named in a `delegate-to-agent` call on its task, in
+/// an ephemeral transcript with the sub-agent's own prompt, tools and route.
+/// Its events stream wrapped in `SubAgentEvent`; it shares the parent's
+/// cancellation, and `None` means the run was cancelled meanwhile.
+#[allow(clippy::too_many_arguments)]
+fn run_delegation<'a>(
+    store: &'a DataStore,
+    registry: &'a ToolRegistry,
+    config: AgentRunConfig,
+    call: &'a ToolCall,
+    depth: u32,
+    on_event: Arc<dyn Fn(AgentStreamEvent) + Send + Sync>,
+    dispatch_external: &'a ErasedDispatch<'a>,
+    cancellation: Option<watch::Receiver<bool>>,
+) -> Pin<Box<dyn Future<Output = Result<Option<Value>, AppError>> + Send + 'a>> {
+    Box::pin(async move {
+        if depth > MAX_DELEGATION_DEPTH {
+            return Err(AppError::Validation(format!(
+                "agents can delegate at most {MAX_DELEGATION_DEPTH} levels deep"
+            )));
+        }
+        let args = DelegateArgs::parse(&call.input)?;
+        let (agent, _healed) = resolve_runnable_agent(
+            &*store.conn()?,
+            &args.agent_id,
+            config.default_agent_id.as_deref(),
+            &config.providers,
+            &config.configs,
+        )?;
+        on_event(AgentStreamEvent::SubAgentStarted {
+            tool_call_id: call.id.clone(),
+            agent_id: agent.id.clone(),
+            agent_name: agent.name.clone(),
+            task: args.task.clone(),
+        });
+        let sink = Arc::clone(&on_event);
+        let tool_call_id = call.id.clone();
+        let forward = move |event| {
+            sink(AgentStreamEvent::SubAgentEvent {
+                tool_call_id: tool_call_id.clone(),
+                event: Box::new(event),
+            })
+        };
+        let mut conversation = Conversation::Ephemeral {
+            store,
+            messages: vec![transient_message("user", args.task.clone())],
+        };
+        let result = run_loop(
+            &agent,
+            registry,
+            config,
+            &mut conversation,
+            forward,
+            dispatch_external,
+            cancellation,
+            Some(&args.task),
+            depth,
+        )
+        .await;
+        on_event(AgentStreamEvent::SubAgentFinished {
+            tool_call_id: call.id.clone(),
+            cancelled: matches!(result, Ok(None)),
+            error: result.as_ref().err().map(ToString::to_string),
+        });
+        Ok(result?.map(|answer| json!({ "agentId": agent.id, "answer": answer })))
+    })
+}
+
 async fn wait_for_cancellation(signal: &mut watch::Receiver<bool>) {
     if *signal.borrow() {
         return;
@@ -792,6 +1652,7 @@ pub async fn run_thread_loop_impl<F, D, Fut>(
     agent_id: &str,
     thread_id: &str,
     user_text: String,
+    attachments: Vec<ContentPart>,
     run_id: Option<String>,
     config: AgentRunConfig,
     on_event: F,
@@ -822,22 +1683,28 @@ where
         )));
     }
     let run_id = run_id.unwrap_or_else(|| Uuid::new_v4().to_string());
-    if !user_text.trim().is_empty() {
+    if !user_text.trim().is_empty() || !attachments.is_empty() {
         let derived_title = thread
             .title
             .as_deref()
             .filter(|title| !title.trim().is_empty())
             .is_none()
             .then(|| crate::agents::lifecycle::derive_thread_title(&user_text));
+        let mut content = json!({ "text": user_text });
+        if !attachments.is_empty() {
+            content["parts"] = serde_json::to_value(&attachments)
+                .map_err(|error| AppError::Other(error.to_string()))?;
+        }
         insert_message(
             &*store.conn()?,
             &MessageRow {
                 id: Uuid::new_v4().to_string(),
                 thread_id: thread_id.to_string(),
                 role: MessageRole::User,
-                content: json!({ "text": user_text }),
+                content,
                 created_at: chrono::Utc::now().timestamp_millis(),
                 run_id: Some(run_id.clone()),
+                parent_id: None,
             },
         )?;
         if let Some(title) = derived_title {
@@ -851,6 +1718,19 @@ where
         on_event(AgentStreamEvent::UserMessagePersisted);
     }
 
+    // Regenerating or re-running an edited prompt sends no new text; the
+    // router then matches on the prompt the branch already ends with.
+    let query = if user_text.trim().is_empty() {
+        list_thread_path(&*store.conn()?, thread_id)?
+            .iter()
+            .rev()
+            .find(|row| row.role == MessageRole::User)
+            .and_then(|row| row.content.get("text").and_then(Value::as_str))
+            .map(str::to_string)
+            .unwrap_or_default()
+    } else {
+        user_text
+    };
     let mut conversation = Conversation::Persistent {
         store,
         thread_id,
@@ -864,7 +1744,8 @@ where
         on_event.clone(),
         dispatch_external,
         cancellation,
-        Some(&user_text),
+        Some(&query),
+        0,
     )
     .await?;
     on_event(if result.is_some() {
@@ -875,7 +1756,9 @@ where
     Ok(healed)
 }
 
+#[allow(clippy::too_many_arguments)] // Explicit dependencies keep the runner independently testable.
 pub async fn run_silent_agent_loop_impl<F, D, Fut>(
+    store: &DataStore,
     agent: &AgentRow,
     registry: &ToolRegistry,
     user_text: String,
@@ -883,7 +1766,7 @@ pub async fn run_silent_agent_loop_impl<F, D, Fut>(
     on_event: F,
     dispatch_external: D,
     cancellation: Option<watch::Receiver<bool>>,
-) -> Result<String, AppError>
+) -> Result<SilentRunOutput, AppError>
 where
     F: Fn(AgentStreamEvent) + Clone + Send + Sync + 'static,
     D: Fn(ExternalToolRequest) -> Fut + Send + Sync,
@@ -896,6 +1779,7 @@ where
         )));
     }
     let mut conversation = Conversation::Ephemeral {
+        store,
         messages: vec![ChatMessage {
             id: Uuid::new_v4().to_string(),
             role: "user".to_string(),
@@ -904,32 +1788,53 @@ where
             tool_calls: None,
             tool_call_id: None,
             provider_context: None,
+            parts: None,
         }],
     };
+    // Keep the run's own parsed answer for the result; sub-agent answers
+    // arrive wrapped in `SubAgentEvent` and are left alone.
+    let structured = Arc::new(Mutex::new(None));
+    let capture = {
+        let structured = Arc::clone(&structured);
+        let on_event = on_event.clone();
+        move |event: AgentStreamEvent| {
+            if let AgentStreamEvent::StructuredOutput { value } = &event {
+                if let Ok(mut slot) = structured.lock() {
+                    *slot = Some(value.clone());
+                }
+            }
+            on_event(event);
+        }
+    };
     let result = run_loop(
         agent,
         registry,
         config,
         &mut conversation,
-        on_event.clone(),
+        capture,
         dispatch_external,
         cancellation,
         Some(&user_text),
+        0,
     )
     .await?;
     match result {
         Some(result) => {
             on_event(AgentStreamEvent::Completed);
-            let result = if agent.input_source == SilentInputSource::ShortcodeMiss {
+            let text = if agent.input_source == SilentInputSource::ShortcodeMiss {
                 crate::agents::lifecycle::sanitize_emoji_fallback_output(&result)
             } else {
                 result
             };
-            Ok(result)
+            let structured = structured.lock().map_err(|_| AppError::Lock)?.take();
+            Ok(SilentRunOutput { text, structured })
         }
         None => {
             on_event(AgentStreamEvent::Cancelled);
-            Ok(String::new())
+            Ok(SilentRunOutput {
+                text: String::new(),
+                structured: None,
+            })
         }
     }
 }
@@ -944,7 +1849,7 @@ pub async fn run_silent_loop_impl<F, D, Fut>(
     on_event: F,
     dispatch_external: D,
     cancellation: Option<watch::Receiver<bool>>,
-) -> Result<String, AppError>
+) -> Result<SilentRunOutput, AppError>
 where
     F: Fn(AgentStreamEvent) + Clone + Send + Sync + 'static,
     D: Fn(ExternalToolRequest) -> Fut + Send + Sync,
@@ -958,6 +1863,7 @@ where
         &config.configs,
     )?;
     run_silent_agent_loop_impl(
+        store,
         &agent,
         registry,
         user_text,
@@ -1,12 +1,16 @@
 use crate::agents::runner::{
     build_system_prompt, coalesce_consecutive_messages, resolve_provider_config,
     run_silent_agent_loop_impl, run_silent_loop_impl, run_thread_loop_impl, AgentRunConfig,
-    AgentRunnerState, AgentStreamEvent, ExternalToolRequest,
+    AgentRunnerState, AgentStreamEvent, ExternalToolRequest, SilentRunOutput,
 };
 use crate::agents::tools::ToolRegistry;
+use crate::ai::routing::{AttemptOutcome, RouteTarget, RoutingPolicy};
+use crate::ai::types::{ContentPart, ImageSource};
 use crate::error::AppError;
 use crate::storage::agents::{
-    insert_agent, insert_thread, list_messages_for_thread, AgentRow, MessageRole, ThreadRow,
+    insert_agent, insert_message, insert_thread, insert_usage, list_attempts_for_run,
+    list_messages_for_thread, list_usage_for_thread, AgentRow, MessageRole, MessageRow, ThreadRow,
+    UsageRow,
 };
 use serde_json::json;
 use std::sync::Arc;
@@ -26,6 +30,7 @@ fn chat_message(role: &str, content: &str) -> crate::ai::types::ChatMessage {
         tool_calls: None,
         tool_call_id: None,
         provider_context: None,
+        parts: None,
     }
 }
 
@@ -67,7 +72,7 @@ fn test_coalesce_consecutive_user_messages() {
 
 #[tokio::test]
 async fn test_system_prompt_adds_hosted_search_date_guidance() {
-    let prompt = build_system_prompt(" Be concise. ", true, None, None).await;
+    let prompt = build_system_prompt(" Be concise. ", true, None, None, &[]).await;
 
     assert!(prompt.starts_with("The available horizontal display space is 400px."));
     assert!(prompt.contains("Be concise."));
@@ -82,6 +87,7 @@ async fn test_runner_state_reports_only_matching_tool_result() {
         .begin_tool_call(
             "stream-1",
             &ExternalToolRequest {
+                agent_id: "agent-1".to_string(),
                 tool_call_id: "call-1".to_string(),
                 tool_id: "extension:tool".to_string(),
                 arguments: json!({}),
@@ -156,6 +162,18 @@ async fn test_runner_state_cancels_active_stream() {
     assert!(*cancellation.borrow());
 }
 
+#[tokio::test]
+async fn test_runner_state_cancellation_follows_cancel_run() {
+    let state = AgentRunnerState::default();
+    assert!(state.cancellation("stream-mcp").unwrap().is_none());
+    let _run = state.begin_run("stream-mcp").unwrap();
+    let mut follower = state.cancellation("stream-mcp").unwrap().unwrap();
+
+    state.cancel_run("stream-mcp").unwrap();
+    follower.changed().await.unwrap();
+    assert!(*follower.borrow());
+}
+
 #[test]
 fn test_runner_state_cancellation_is_idempotent_after_receiver_closes() {
     let state = AgentRunnerState::default();
@@ -191,6 +209,9 @@ async fn test_silent_runner_rejects_non_silent_agent() {
         output_action: crate::storage::agents::SilentOutputAction::ReplaceSelection,
         cache_responses: false,
         shortcode_trigger: ":".to_string(),
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
         created_at: None,
         updated_at: None,
     };
@@ -209,6 +230,7 @@ async fn test_silent_runner_rejects_non_silent_agent() {
     };
 
     let error = run_silent_agent_loop_impl(
+        &make_store(),
         &agent,
         &ToolRegistry::new(),
         "hello".to_string(),
@@ -239,12 +261,21 @@ fn shortcode_miss_agent() -> AgentRow {
         output_action: crate::storage::agents::SilentOutputAction::Paste,
         cache_responses: true,
         shortcode_trigger: ":".to_string(),
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
         created_at: None,
         updated_at: None,
     }
 }
 
 async fn run_shortcode_miss_with_mocked_reply(reply_chunks: &[&str]) -> String {
+    run_silent_with_mocked_reply(shortcode_miss_agent(), reply_chunks)
+        .await
+        .text
+}
+
+async fn run_silent_with_mocked_reply(agent: AgentRow, reply_chunks: &[&str]) -> SilentRunOutput {
     let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
     let port = listener.local_addr().unwrap().port();
 
@@ -279,7 +310,8 @@ async fn run_shortcode_miss_with_mocked_reply(reply_chunks: &[&str]) -> String {
     };
 
     run_silent_agent_loop_impl(
-        &shortcode_miss_agent(),
+        &make_store(),
+        &agent,
         &ToolRegistry::new(),
         "party".to_string(),
         run_config("openai", provider, 0.7, 2048),
@@ -291,6 +323,22 @@ async fn run_shortcode_miss_with_mocked_reply(reply_chunks: &[&str]) -> String {
     .unwrap()
 }
 
+#[tokio::test]
+async fn test_silent_runner_returns_the_parsed_structured_answer() {
+    let mut agent = shortcode_miss_agent();
+    agent.input_source = crate::storage::agents::SilentInputSource::Argument;
+    agent.output_schema = Some(json!({
+        "type": "object",
+        "properties": { "name": { "type": "string" } },
+        "required": ["name"],
+    }));
+
+    let output = run_silent_with_mocked_reply(agent, &["\"{\\\"name\\\":\\\"Ada\\\"}\""]).await;
+
+    assert_eq!(output.structured, Some(json!({ "name": "Ada" })));
+    assert_eq!(output.text, "{\n  \"name\": \"Ada\"\n}");
+}
+
 #[tokio::test]
 async fn test_silent_runner_sanitizes_shortcode_miss_prose_to_empty() {
     let result =
@@ -329,6 +377,9 @@ async fn test_thread_runner_rejects_thread_owned_by_another_agent() {
                 output_action: crate::storage::agents::SilentOutputAction::ReplaceSelection,
                 cache_responses: false,
                 shortcode_trigger: ":".to_string(),
+                monthly_budget_usd: None,
+                routing: Default::default(),
+                output_schema: None,
                 created_at: Some(now),
                 updated_at: Some(now),
             },
@@ -341,6 +392,7 @@ async fn test_thread_runner_rejects_thread_owned_by_another_agent() {
             id: "thread-2".to_string(),
             agent_id: "agent-2".to_string(),
             title: None,
+            current_leaf_id: None,
             created_at: Some(now),
             updated_at: Some(now),
         },
@@ -366,6 +418,7 @@ async fn test_thread_runner_rejects_thread_owned_by_another_agent() {
         "agent-1",
         "thread-2",
         "hello".to_string(),
+        Vec::new(),
         None,
         run_config("openai", provider, 0.7, 2048),
         |_| {},
@@ -427,6 +480,9 @@ async fn test_run_thread_loop_text_only() {
             output_action: crate::storage::agents::SilentOutputAction::ReplaceSelection,
             cache_responses: false,
             shortcode_trigger: ":".to_string(),
+            monthly_budget_usd: None,
+            routing: Default::default(),
+            output_schema: None,
             created_at: Some(now),
             updated_at: Some(now),
         },
@@ -439,6 +495,7 @@ async fn test_run_thread_loop_text_only() {
             id: thread_id.clone(),
             agent_id: agent_id.clone(),
             title: None,
+            current_leaf_id: None,
             created_at: Some(now),
             updated_at: Some(now),
         },
@@ -477,6 +534,7 @@ async fn test_run_thread_loop_text_only() {
         &agent_id,
         &thread_id,
         "Hello".to_string(),
+        Vec::new(),
         None,
         run_config("openai", config, 0.25, 123),
         on_event,
@@ -563,6 +621,9 @@ data: {\"choices\":[{\"delta\":{\"content\":\"Final result!\"}}]}\n\ndata: [DONE
             output_action: crate::storage::agents::SilentOutputAction::ReplaceSelection,
             cache_responses: false,
             shortcode_trigger: ":".to_string(),
+            monthly_budget_usd: None,
+            routing: Default::default(),
+            output_schema: None,
             created_at: Some(now),
             updated_at: Some(now),
         },
@@ -575,6 +636,7 @@ data: {\"choices\":[{\"delta\":{\"content\":\"Final result!\"}}]}\n\ndata: [DONE
             id: thread_id.clone(),
             agent_id: agent_id.clone(),
             title: Some("Title".to_string()),
+            current_leaf_id: None,
             created_at: Some(now),
             updated_at: Some(now),
         },
@@ -627,6 +689,7 @@ data: {\"choices\":[{\"delta\":{\"content\":\"Final result!\"}}]}\n\ndata: [DONE
         &agent_id,
         &thread_id,
         "Calculate".to_string(),
+        Vec::new(),
         None,
         run_config("openai", config, 0.7, 2048),
         on_event,
@@ -709,6 +772,9 @@ data: {\"choices\":[{\"delta\":{\"content\":\"Extension result used\"}}]}\n\ndat
             output_action: crate::storage::agents::SilentOutputAction::ReplaceSelection,
             cache_responses: false,
             shortcode_trigger: ":".to_string(),
+            monthly_budget_usd: None,
+            routing: Default::default(),
+            output_schema: None,
             created_at: Some(now),
             updated_at: Some(now),
         },
@@ -721,6 +787,7 @@ data: {\"choices\":[{\"delta\":{\"content\":\"Extension result used\"}}]}\n\ndat
             id: thread_id.clone(),
             agent_id: agent_id.clone(),
             title: Some("Title".to_string()),
+            current_leaf_id: None,
             created_at: Some(now),
             updated_at: Some(now),
         },
@@ -770,6 +837,7 @@ data: {\"choices\":[{\"delta\":{\"content\":\"Extension result used\"}}]}\n\ndat
         &agent_id,
         &thread_id,
         "Run extension".to_string(),
+        Vec::new(),
         None,
         run_config("openai", config, 0.7, 2048),
         on_event,
@@ -858,6 +926,9 @@ data: {\"choices\":[{\"delta\":{\"content\":\"Corrected text\"}}]}\n\ndata: [DON
             output_action: crate::storage::agents::SilentOutputAction::ReplaceSelection,
             cache_responses: false,
             shortcode_trigger: ":".to_string(),
+            monthly_budget_usd: None,
+            routing: Default::default(),
+            output_schema: None,
             created_at: Some(now),
             updated_at: Some(now),
         },
@@ -924,7 +995,8 @@ data: {\"choices\":[{\"delta\":{\"content\":\"Corrected text\"}}]}\n\ndata: [DON
         )
     };
 
-    assert_eq!(result, "Corrected text");
+    assert_eq!(result.text, "Corrected text");
+    assert_eq!(result.structured, None);
     assert_eq!(
         after, before,
         "silent execution must not write threads or messages"
@@ -1015,3 +1087,991 @@ fn resolve_provider_config_errors_when_base_url_is_missing() {
         .to_string()
         .contains("Base URL for provider 'ollama' is not configured"));
 }
+
+#[tokio::test]
+async fn test_run_thread_loop_records_usage_per_assistant_message() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = [0; 4096];
+        let _ = tokio::io::AsyncReadExt::read(&mut socket, &mut buf)
+            .await
+            .unwrap();
+        let response = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\r\n\
+                        data: {\"choices\":[{\"delta\":{\"content\":\"Hi\"}}]}\n\n\
+                        data: {\"choices\":[],\"usage\":{\"prompt_tokens\":1000,\"completion_tokens\":100}}\n\n\
+                        data: [DONE]\n\n";
+        socket.write_all(response.as_bytes()).await.unwrap();
+    });
+
+    let store = make_store();
+    let now = chrono::Utc::now().timestamp_millis();
+    insert_agent(
+        &store.conn().unwrap(),
+        &AgentRow {
+            id: "agent-usage".to_string(),
+            name: "Usage".to_string(),
+            description: None,
+            system_prompt: "Be brief.".to_string(),
+            provider_id: "openai".to_string(),
+            model_id: "gpt-4o".to_string(),
+            tool_selection: vec![],
+            silent: false,
+            input_source: crate::storage::agents::SilentInputSource::Argument,
+            output_action: crate::storage::agents::SilentOutputAction::ReplaceSelection,
+            cache_responses: false,
+            shortcode_trigger: ":".to_string(),
+            monthly_budget_usd: Some(5.0),
+            routing: Default::default(),
+            output_schema: None,
+            created_at: Some(now),
+            updated_at: Some(now),
+        },
+    )
+    .unwrap();
+    insert_thread(
+        &store.conn().unwrap(),
+        &ThreadRow {
+            id: "thread-usage".to_string(),
+            agent_id: "agent-usage".to_string(),
+            title: None,
+            current_leaf_id: None,
+            created_at: Some(now),
+            updated_at: Some(now),
+        },
+    )
+    .unwrap();
+    let provider = crate::ai::types::ProviderConfig {
+        enabled: true,
+        name: None,
+        provider_type: None,
+        api_key: Some("test-key".to_string()),
+        base_url: Some(format!("http://127.0.0.1:{port}")),
+        last_model_id: None,
+        open_ai_api_mode: None,
+        hosted_web_search: None,
+        reasoning_effort: None,
+        temperature: None,
+        max_tokens: None,
+    };
+
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let events_clone = events.clone();
+    run_thread_loop_impl(
+        &store,
+        &ToolRegistry::new(),
+        "agent-usage",
+        "thread-usage",
+        "Hello".to_string(),
+        Vec::new(),
+        Some("run-usage".to_string()),
+        run_config("openai", provider, 0.7, 2048),
+        move |event| events_clone.lock().unwrap().push(event),
+        |_| async { Err(AppError::Other("unexpected tool dispatch".to_string())) },
+        None,
+    )
+    .await
+    .unwrap();
+
+    let conn = store.conn().unwrap();
+    let messages = list_messages_for_thread(&conn, "thread-usage").unwrap();
+    let usage = list_usage_for_thread(&conn, "thread-usage").unwrap();
+    assert_eq!(usage.len(), 1);
+    assert_eq!(
+        usage[0].message_id.as_deref(),
+        Some(messages[1].id.as_str())
+    );
+    assert_eq!(usage[0].run_id.as_deref(), Some("run-usage"));
+    assert_eq!(usage[0].usage.input_tokens, 1000);
+    assert_eq!(usage[0].usage.output_tokens, 100);
+    // 1000 * $2.50/M + 100 * $10/M
+    let cost = usage[0].cost_usd.unwrap();
+    assert!((cost - 0.0035).abs() < 1e-9);
+    assert!(events.lock().unwrap().iter().any(|event| matches!(
+        event,
+        AgentStreamEvent::Usage {
+            cost_usd: Some(_),
+            ..
+        }
+    )));
+}
+
+#[tokio::test]
+async fn test_silent_run_refused_once_monthly_budget_is_spent() {
+    let store = make_store();
+    let mut agent = shortcode_miss_agent();
+    agent.monthly_budget_usd = Some(1.0);
+    insert_usage(
+        &store.conn().unwrap(),
+        &UsageRow {
+            id: "u1".to_string(),
+            message_id: None,
+            thread_id: None,
+            run_id: None,
+            agent_id: agent.id.clone(),
+            provider_id: "openai".to_string(),
+            model_id: "gpt-4o".to_string(),
+            usage: Default::default(),
+            cost_usd: Some(1.25),
+            created_at: chrono::Utc::now().timestamp_millis(),
+        },
+    )
+    .unwrap();
+    let provider = crate::ai::types::ProviderConfig {
+        enabled: true,
+        name: None,
+        provider_type: None,
+        api_key: Some("test-key".to_string()),
+        base_url: Some("http://127.0.0.1:9".to_string()),
+        last_model_id: None,
+        open_ai_api_mode: None,
+        hosted_web_search: None,
+        reasoning_effort: None,
+        temperature: None,
+        max_tokens: None,
+    };
+
+    let error = run_silent_agent_loop_impl(
+        &store,
+        &agent,
+        &ToolRegistry::new(),
+        "party".to_string(),
+        run_config("openai", provider, 0.7, 2048),
+        |_| {},
+        |_| async { Err(AppError::Other("unexpected tool dispatch".to_string())) },
+        None,
+    )
+    .await
+    .unwrap_err();
+
+    assert!(error.to_string().contains("reached its monthly budget"));
+}
+
+const OPENAI_HI: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\r\n\
+                         data: {\"choices\":[{\"delta\":{\"content\":\"Hi\"}}]}\n\n\
+                         data: [DONE]\n\n";
+
+/// Read one HTTP request: headers, then as much body as `Content-Length`
+/// announces.
+async fn read_request(socket: &mut tokio::net::TcpStream) -> String {
+    let mut request = Vec::new();
+    let mut buf = [0; 4096];
+    loop {
+        let read = tokio::io::AsyncReadExt::read(socket, &mut buf)
+            .await
+            .unwrap();
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..read]);
+        let text = String::from_utf8_lossy(&request);
+        if let Some(head_end) = text.find("\r\n\r\n") {
+            let content_length = text[..head_end]
+                .lines()
+                .find_map(|line| {
+                    line.to_ascii_lowercase()
+                        .strip_prefix("content-length:")
+                        .map(|value| value.trim().parse::<usize>().unwrap())
+                })
+                .unwrap_or(0);
+            if request.len() >= head_end + 4 + content_length {
+                break;
+            }
+        }
+    }
+    String::from_utf8_lossy(&request).into_owned()
+}
+
+/// Answer successive connections with `responses`, one each, recording the
+/// requests that arrived.
+async fn serve_sequence(responses: Vec<&'static str>) -> (u16, Arc<std::sync::Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = requests.clone();
+    tokio::spawn(async move {
+        for response in responses {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let request = read_request(&mut socket).await;
+            seen.lock().unwrap().push(request);
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+    (port, requests)
+}
+
+/// A port nothing listens on, for connection-refused failures.
+fn closed_port() -> u16 {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().port()
+}
+
+fn mock_provider(provider_type: Option<&str>, port: u16) -> crate::ai::types::ProviderConfig {
+    crate::ai::types::ProviderConfig {
+        enabled: true,
+        name: None,
+        provider_type: provider_type.map(str::to_string),
+        api_key: Some("test-key".to_string()),
+        base_url: Some(format!("http://127.0.0.1:{port}")),
+        last_model_id: None,
+        open_ai_api_mode: None,
+        hosted_web_search: None,
+        reasoning_effort: None,
+        temperature: None,
+        max_tokens: None,
+    }
+}
+
+fn insert_routed_agent(store: &crate::storage::DataStore, routing: RoutingPolicy) {
+    let now = chrono::Utc::now().timestamp_millis();
+    let conn = store.conn().unwrap();
+    insert_agent(
+        &conn,
+        &AgentRow {
+            id: "agent-routed".to_string(),
+            name: "Routed".to_string(),
+            description: None,
+            system_prompt: "Be brief.".to_string(),
+            provider_id: "openai".to_string(),
+            model_id: "gpt-4o".to_string(),
+            tool_selection: vec![],
+            silent: false,
+            input_source: crate::storage::agents::SilentInputSource::Argument,
+            output_action: crate::storage::agents::SilentOutputAction::ReplaceSelection,
+            cache_responses: false,
+            shortcode_trigger: ":".to_string(),
+            monthly_budget_usd: None,
+            routing,
+            output_schema: None,
+            created_at: Some(now),
+            updated_at: Some(now),
+        },
+    )
+    .unwrap();
+    insert_thread(
+        &conn,
+        &ThreadRow {
+            id: "thread-routed".to_string(),
+            agent_id: "agent-routed".to_string(),
+            title: None,
+            current_leaf_id: None,
+            created_at: Some(now),
+            updated_at: Some(now),
+        },
+    )
+    .unwrap();
+}
+
+async fn run_routed(
+    store: &crate::storage::DataStore,
+    config: AgentRunConfig,
+) -> (Result<bool, AppError>, Vec<AgentStreamEvent>) {
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let events_clone = events.clone();
+    let result = run_thread_loop_impl(
+        store,
+        &ToolRegistry::new(),
+        "agent-routed",
+        "thread-routed",
+        "Hello".to_string(),
+        Vec::new(),
+        Some("run-routed".to_string()),
+        config,
+        move |event| events_clone.lock().unwrap().push(event),
+        |_| async { Err(AppError::Other("unexpected tool dispatch".to_string())) },
+        None,
+    )
+    .await;
+    let events = events.lock().unwrap().clone();
+    (result, events)
+}
+
+fn assistant_text(store: &crate::storage::DataStore) -> String {
+    let messages = list_messages_for_thread(&store.conn().unwrap(), "thread-routed").unwrap();
+    messages
+        .iter()
+        .find(|message| message.role == MessageRole::Assistant)
+        .and_then(|message| message.content["text"].as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_rate_limited_request_is_retried_on_the_same_provider() {
+    let (port, requests) = serve_sequence(vec![
+        "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0\r\nContent-Length: 4\r\n\r\nbusy",
+        OPENAI_HI,
+    ])
+    .await;
+    let store = make_store();
+    insert_routed_agent(
+        &store,
+        RoutingPolicy {
+            max_retries: 1,
+            ..RoutingPolicy::default()
+        },
+    );
+
+    let (result, events) = run_routed(
+        &store,
+        run_config("openai", mock_provider(None, port), 0.7, 2048),
+    )
+    .await;
+
+    result.unwrap();
+    assert_eq!(requests.lock().unwrap().len(), 2);
+    assert_eq!(assistant_text(&store), "Hi");
+    let attempts = list_attempts_for_run(&store.conn().unwrap(), "run-routed").unwrap();
+    let outcomes: Vec<_> = attempts
+        .iter()
+        .map(|a| (a.outcome, a.status_code))
+        .collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            (AttemptOutcome::RateLimited, Some(429)),
+            (AttemptOutcome::Succeeded, None)
+        ]
+    );
+    assert_eq!(attempts[0].error.as_deref(), Some("busy"));
+    assert!(events.contains(&AgentStreamEvent::ProviderAttemptFailed {
+        provider_id: "openai".to_string(),
+        model_id: "gpt-4o".to_string(),
+        outcome: AttemptOutcome::RateLimited,
+        status_code: Some(429),
+        message: "busy".to_string(),
+        retry_in_ms: 0,
+    }));
+}
+
+#[tokio::test]
+async fn test_server_error_falls_over_to_a_provider_with_its_own_request_shape() {
+    let (openai_port, _) = serve_sequence(vec![
+        "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 4\r\n\r\ndown",
+    ])
+    .await;
+    let (anthropic_port, anthropic_requests) = serve_sequence(vec![
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\r\n\
+         data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"From Claude\"}}\n\n\
+         data: {\"type\":\"message_stop\"}\n\n",
+    ])
+    .await;
+    let store = make_store();
+    insert_routed_agent(
+        &store,
+        RoutingPolicy {
+            fallbacks: vec![
+                RouteTarget {
+                    provider_id: "missing".to_string(),
+                    model_id: "skipped".to_string(),
+                },
+                RouteTarget {
+                    provider_id: "anthropic".to_string(),
+                    model_id: "claude-haiku-4-5".to_string(),
+                },
+            ],
+            ..RoutingPolicy::default()
+        },
+    );
+    let mut config = run_config("openai", mock_provider(None, openai_port), 0.7, 2048);
+    config
+        .configs
+        .insert("anthropic".to_string(), mock_provider(None, anthropic_port));
+
+    let (result, events) = run_routed(&store, config).await;
+
+    result.unwrap();
+    assert_eq!(assistant_text(&store), "From Claude");
+    let request = anthropic_requests.lock().unwrap()[0].clone();
+    assert!(request.starts_with("POST /messages "));
+    assert!(request.contains("\"model\":\"claude-haiku-4-5\""));
+    let attempts = list_attempts_for_run(&store.conn().unwrap(), "run-routed").unwrap();
+    let routes: Vec<_> = attempts
+        .iter()
+        .map(|a| (a.provider_id.as_str(), a.outcome))
+        .collect();
+    assert_eq!(
+        routes,
+        vec![
+            ("openai", AttemptOutcome::ServerError),
+            ("anthropic", AttemptOutcome::Succeeded)
+        ]
+    );
+    assert!(events.iter().any(|event| matches!(
+        event,
+        AgentStreamEvent::ProviderAttemptFailed {
+            status_code: Some(503),
+            retry_in_ms: 0,
+            ..
+        }
+    )));
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, AgentStreamEvent::Error { .. })));
+}
+
+#[tokio::test]
+async fn test_offline_run_routes_to_local_ollama_and_skips_cloud_fallbacks() {
+    let (ollama_port, _) = serve_sequence(vec![
+        "HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\n\r\n\
+         {\"message\":{\"role\":\"assistant\",\"content\":\"Local\"},\"done\":false}\n\
+         {\"done\":true,\"prompt_eval_count\":12,\"eval_count\":3}\n",
+    ])
+    .await;
+    let store = make_store();
+    insert_routed_agent(
+        &store,
+        RoutingPolicy {
+            fallbacks: vec![RouteTarget {
+                provider_id: "anthropic".to_string(),
+                model_id: "claude-haiku-4-5".to_string(),
+            }],
+            max_retries: 3,
+            offline_fallback: Some(RouteTarget {
+                provider_id: "ollama".to_string(),
+                model_id: "llama3.2".to_string(),
+            }),
+        },
+    );
+    let mut config = run_config("openai", mock_provider(None, closed_port()), 0.7, 2048);
+    config
+        .configs
+        .insert("anthropic".to_string(), mock_provider(None, closed_port()));
+    config
+        .configs
+        .insert("ollama".to_string(), mock_provider(None, ollama_port));
+
+    let (result, _) = run_routed(&store, config).await;
+
+    result.unwrap();
+    assert_eq!(assistant_text(&store), "Local");
+    let conn = store.conn().unwrap();
+    let attempts = list_attempts_for_run(&conn, "run-routed").unwrap();
+    let routes: Vec<_> = attempts
+        .iter()
+        .map(|a| (a.provider_id.as_str(), a.outcome))
+        .collect();
+    assert_eq!(
+        routes,
+        vec![
+            ("openai", AttemptOutcome::Offline),
+            ("ollama", AttemptOutcome::Succeeded)
+        ]
+    );
+    let usage = list_usage_for_thread(&conn, "thread-routed").unwrap();
+    assert_eq!(usage[0].provider_id, "ollama");
+    assert_eq!(usage[0].model_id, "llama3.2");
+    assert_eq!(usage[0].cost_usd, Some(0.0));
+}
+
+#[tokio::test]
+async fn test_default_policy_fails_on_the_first_error_and_records_it() {
+    let (port, requests) = serve_sequence(vec![
+        "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 4\r\n\r\ndown",
+    ])
+    .await;
+    let store = make_store();
+    insert_routed_agent(&store, RoutingPolicy::default());
+
+    let (result, events) = run_routed(
+        &store,
+        run_config("openai", mock_provider(None, port), 0.7, 2048),
+    )
+    .await;
+
+    assert!(result.unwrap_err().to_string().contains("down"));
+    assert_eq!(requests.lock().unwrap().len(), 1);
+    assert!(events.contains(&AgentStreamEvent::Error {
+        message: "down".to_string()
+    }));
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, AgentStreamEvent::ProviderAttemptFailed { .. })));
+    let attempts = list_attempts_for_run(&store.conn().unwrap(), "run-routed").unwrap();
+    assert_eq!(attempts.len(), 1);
+    assert_eq!(attempts[0].outcome, AttemptOutcome::ServerError);
+    assert_eq!(attempts[0].status_code, Some(503));
+}
+
+#[tokio::test]
+async fn test_thread_attachments_persist_and_reach_the_provider() {
+    let (port, requests) = serve_sequence(vec![OPENAI_HI]).await;
+    let store = make_store();
+    insert_routed_agent(&store, RoutingPolicy::default());
+    let image = ContentPart::Image {
+        media_type: "image/png".to_string(),
+        source: ImageSource::Bytes {
+            data: "aW1n".to_string(),
+        },
+    };
+
+    run_thread_loop_impl(
+        &store,
+        &ToolRegistry::new(),
+        "agent-routed",
+        "thread-routed",
+        "What is this?".to_string(),
+        vec![image.clone()],
+        None,
+        run_config("openai", mock_provider(None, port), 0.7, 2048),
+        |_| {},
+        |_| async { Err(AppError::Other("unexpected tool dispatch".to_string())) },
+        None,
+    )
+    .await
+    .unwrap();
+
+    let messages = list_messages_for_thread(&store.conn().unwrap(), "thread-routed").unwrap();
+    assert_eq!(messages[0].content["parts"], json!([image]));
+    let request = requests.lock().unwrap()[0].clone();
+    assert!(
+        request.contains("\"url\":\"data:image/png;base64,aW1n\""),
+        "{request}"
+    );
+}
+
+const OPENAI_PROSE: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\r\n\
+                            data: {\"choices\":[{\"delta\":{\"content\":\"Ada, I think\"}}]}\n\n\
+                            data: [DONE]\n\n";
+
+const OPENAI_CONTACT: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\r\n\
+                              data: {\"choices\":[{\"delta\":{\"content\":\"{\\\"name\\\":\\\"Ada\\\"}\"}}]}\n\n\
+                              data: [DONE]\n\n";
+
+fn require_contact_schema(store: &crate::storage::DataStore) {
+    let conn = store.conn().unwrap();
+    let mut agent = crate::storage::agents::get_agent(&conn, "agent-routed")
+        .unwrap()
+        .unwrap();
+    agent.output_schema = Some(json!({
+        "type": "object",
+        "properties": { "name": { "type": "string" } },
+        "required": ["name"],
+    }));
+    crate::storage::agents::update_agent(&conn, &agent).unwrap();
+}
+
+#[tokio::test]
+async fn test_invalid_structured_answer_is_corrected_once_before_persisting() {
+    let (port, requests) = serve_sequence(vec![OPENAI_PROSE, OPENAI_CONTACT]).await;
+    let store = make_store();
+    insert_routed_agent(&store, RoutingPolicy::default());
+    require_contact_schema(&store);
+
+    let (result, events) = run_routed(
+        &store,
+        run_config("openai", mock_provider(None, port), 0.7, 2048),
+    )
+    .await;
+
+    result.unwrap();
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].contains("\"response_format\""));
+    assert!(requests[1].contains("Ada, I think"));
+    assert!(requests[1].contains("missing required property 'name'"));
+    assert!(events.contains(&AgentStreamEvent::StructuredOutput {
+        value: json!({ "name": "Ada" })
+    }));
+
+    let messages = list_messages_for_thread(&store.conn().unwrap(), "thread-routed").unwrap();
+    let assistants: Vec<_> = messages
+        .iter()
+        .filter(|message| message.role == MessageRole::Assistant)
+        .collect();
+    assert_eq!(assistants.len(), 1);
+    assert_eq!(
+        assistants[0].content["structured"],
+        json!({ "name": "Ada" })
+    );
+}
+
+#[tokio::test]
+async fn test_second_invalid_structured_answer_fails_the_run() {
+    let (port, requests) = serve_sequence(vec![OPENAI_PROSE, OPENAI_PROSE]).await;
+    let store = make_store();
+    insert_routed_agent(&store, RoutingPolicy::default());
+    require_contact_schema(&store);
+
+    let (result, events) = run_routed(
+        &store,
+        run_config("openai", mock_provider(None, port), 0.7, 2048),
+    )
+    .await;
+
+    assert!(matches!(result, Err(AppError::Validation(_))));
+    assert_eq!(requests.lock().unwrap().len(), 2);
+    assert_eq!(assistant_text(&store), "Ada, I think");
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, AgentStreamEvent::StructuredOutput { .. })));
+}
+
+#[tokio::test]
+async fn test_rerun_after_an_edit_sends_only_the_selected_branch() {
+    let (port, requests) = serve_sequence(vec![OPENAI_HI, OPENAI_HI]).await;
+    let store = make_store();
+    insert_routed_agent(&store, RoutingPolicy::default());
+    let config = || run_config("openai", mock_provider(None, port), 0.7, 2048);
+    let (result, _) = run_routed(&store, config()).await;
+    result.unwrap();
+
+    // Edit the first prompt: a sibling at the root becomes the leaf.
+    crate::storage::agents::insert_message_after(
+        &store.conn().unwrap(),
+        &MessageRow {
+            id: "edited".to_string(),
+            thread_id: "thread-routed".to_string(),
+            role: MessageRole::User,
+            content: json!({ "text": "Bonjour" }),
+            created_at: chrono::Utc::now().timestamp_millis(),
+            run_id: None,
+            parent_id: None,
+        },
+        None,
+    )
+    .unwrap();
+    run_thread_loop_impl(
+        &store,
+        &ToolRegistry::new(),
+        "agent-routed",
+        "thread-routed",
+        String::new(),
+        Vec::new(),
+        None,
+        config(),
+        |_| {},
+        |_| async { Err(AppError::Other("unexpected tool dispatch".to_string())) },
+        None,
+    )
+    .await
+    .unwrap();
+
+    let request = requests.lock().unwrap()[1].clone();
+    assert!(request.contains("Bonjour"), "{request}");
+    assert!(!request.contains("Hello"), "{request}");
+    assert!(!request.contains("\"Hi\""), "{request}");
+    let path =
+        crate::storage::agents::list_thread_path(&store.conn().unwrap(), "thread-routed").unwrap();
+    assert_eq!(path.len(), 2);
+    assert_eq!(path[1].parent_id.as_deref(), Some("edited"));
+    assert_eq!(
+        list_messages_for_thread(&store.conn().unwrap(), "thread-routed")
+            .unwrap()
+            .len(),
+        4
+    );
+}
+
+const OPENAI_SUMMARY: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\r\n\
+                              data: {\"choices\":[{\"delta\":{\"content\":\"The user pasted long notes.\"}}]}\n\n\
+                              data: [DONE]\n\n";
+
+#[tokio::test]
+async fn test_long_thread_is_compacted_into_a_pinned_summary() {
+    let (port, requests) = serve_sequence(vec![OPENAI_SUMMARY, OPENAI_HI]).await;
+    let store = make_store();
+    insert_routed_agent(&store, RoutingPolicy::default());
+    {
+        let conn = store.conn().unwrap();
+        // gpt-4's 8k window leaves a 6k-token budget at max_tokens 2048.
+        let mut agent = crate::storage::agents::get_agent(&conn, "agent-routed")
+            .unwrap()
+            .unwrap();
+        agent.model_id = "gpt-4".to_string();
+        crate::storage::agents::update_agent(&conn, &agent).unwrap();
+        for index in 0..10 {
+            insert_message(
+                &conn,
+                &MessageRow {
+                    id: format!("old-{index}"),
+                    thread_id: "thread-routed".to_string(),
+                    role: if index % 2 == 0 {
+                        MessageRole::User
+                    } else {
+                        MessageRole::Assistant
+                    },
+                    content: json!({ "text": format!("{index}{}", "x".repeat(4_000)) }),
+                    created_at: index,
+                    run_id: None,
+                    parent_id: None,
+                },
+            )
+            .unwrap();
+        }
+    }
+
+    let (result, events) = run_routed(
+        &store,
+        run_config("openai", mock_provider(None, port), 0.7, 2048),
+    )
+    .await;
+
+    result.unwrap();
+    assert!(events.contains(&AgentStreamEvent::ContextCompacted {
+        summarized_messages: 8
+    }));
+    let requests = requests.lock().unwrap();
+    assert!(requests[0].contains("You condense conversations"));
+    assert!(requests[1].contains("The user pasted long notes."));
+    assert!(!requests[1].contains("\"7xxxx"));
+    assert!(requests[1].contains("\"8xxxx"));
+
+    let conn = store.conn().unwrap();
+    let summary = crate::storage::agents::get_thread_summary(&conn, "thread-routed")
+        .unwrap()
+        .unwrap();
+    assert_eq!(summary.through_message_id, "old-7");
+    assert_eq!(
+        list_messages_for_thread(&conn, "thread-routed")
+            .unwrap()
+            .len(),
+        12,
+        "compaction never deletes stored messages"
+    );
+}
+
+#[tokio::test]
+async fn test_relevant_memories_are_recalled_into_the_system_prompt() {
+    let (port, requests) = serve_sequence(vec![OPENAI_HI]).await;
+    let store = make_store();
+    insert_routed_agent(&store, RoutingPolicy::default());
+    let key = [7u8; 32];
+    {
+        let conn = store.conn().unwrap();
+        let mut agent = crate::storage::agents::get_agent(&conn, "agent-routed")
+            .unwrap()
+            .unwrap();
+        agent.tool_selection = vec!["builtin:memory-search".to_string()];
+        crate::storage::agents::update_agent(&conn, &agent).unwrap();
+        for (id, content) in [("m1", "Answer hello in French"), ("m2", "Owns a cat")] {
+            crate::storage::memories::save(
+                &conn,
+                &crate::storage::memories::Memory {
+                    id: id.to_string(),
+                    agent_id: None,
+                    content: content.to_string(),
+                    source_thread_id: None,
+                    created_at: 1,
+                    updated_at: 1,
+                },
+                &key,
+            )
+            .unwrap();
+        }
+    }
+    let registry = ToolRegistry::new();
+    registry
+        .register_builtin(Arc::new(
+            crate::agents::builtin_tools::memory::MemorySearchTool::new(store.clone(), key),
+        ))
+        .unwrap();
+
+    run_thread_loop_impl(
+        &store,
+        &registry,
+        "agent-routed",
+        "thread-routed",
+        "Hello".to_string(),
+        Vec::new(),
+        None,
+        run_config("openai", mock_provider(None, port), 0.7, 256),
+        |_| {},
+        |_| async { Err(AppError::Other("unexpected tool dispatch".to_string())) },
+        None,
+    )
+    .await
+    .unwrap();
+
+    let requests = requests.lock().unwrap();
+    assert!(requests[0].contains("Answer hello in French (memory m1)"));
+    assert!(!requests[0].contains("Owns a cat"));
+}
+
+fn openai_delegate_call(agent_id: &str) -> &'static str {
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\r\n\
+         data: {{\"choices\":[{{\"delta\":{{\"tool_calls\":[{{\"index\":0,\"id\":\"call-{agent_id}\",\"type\":\"function\",\"function\":{{\"name\":\"builtin__delegate-to-agent\",\"arguments\":\"{{\\\"agentId\\\":\\\"{agent_id}\\\",\\\"task\\\":\\\"Say hi\\\"}}\"}}}}]}}}}]}}\n\n\
+         data: [DONE]\n\n"
+    );
+    Box::leak(response.into_boxed_str())
+}
+
+/// `agent-routed` with the delegation tool, plus a plain `agent-helper`.
+fn insert_delegating_agents(store: &crate::storage::DataStore) -> ToolRegistry {
+    insert_routed_agent(store, RoutingPolicy::default());
+    let conn = store.conn().unwrap();
+    let mut agent = crate::storage::agents::get_agent(&conn, "agent-routed")
+        .unwrap()
+        .unwrap();
+    agent.tool_selection = vec!["builtin:delegate-to-agent".to_string()];
+    crate::storage::agents::update_agent(&conn, &agent).unwrap();
+    insert_agent(
+        &conn,
+        &AgentRow {
+            id: "agent-helper".to_string(),
+            name: "Helper".to_string(),
+            system_prompt: "You are the helper.".to_string(),
+            tool_selection: vec![],
+            ..agent
+        },
+    )
+    .unwrap();
+    let registry = ToolRegistry::new();
+    registry
+        .register_builtin(Arc::new(
+            crate::agents::builtin_tools::delegate::DelegateToAgentTool::new(),
+        ))
+        .unwrap();
+    registry
+}
+
+#[tokio::test]
+async fn test_delegated_sub_run_streams_nested_events_and_returns_its_answer() {
+    let (port, requests) = serve_sequence(vec![
+        openai_delegate_call("agent-helper"),
+        OPENAI_HI,
+        OPENAI_PROSE,
+    ])
+    .await;
+    let store = make_store();
+    let registry = insert_delegating_agents(&store);
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let events_clone = events.clone();
+
+    run_thread_loop_impl(
+        &store,
+        &registry,
+        "agent-routed",
+        "thread-routed",
+        "Ask the helper".to_string(),
+        Vec::new(),
+        None,
+        run_config("openai", mock_provider(None, port), 0.7, 256),
+        move |event| events_clone.lock().unwrap().push(event),
+        |_| async { Err(AppError::Other("unexpected tool dispatch".to_string())) },
+        None,
+    )
+    .await
+    .unwrap();
+
+    let requests = requests.lock().unwrap();
+    // The offered tool names the one other agent, never the caller itself.
+    assert!(requests[0].contains(r#""enum":["agent-helper"]"#));
+    assert!(requests[0].contains("agent-helper (Helper)"));
+    assert!(requests[1].contains("You are the helper."));
+    assert!(requests[1].contains("Say hi"));
+    assert!(!requests[1].contains("Ask the helper"));
+    assert!(requests[2].contains(r#"\"answer\":\"Hi\""#));
+    assert_eq!(assistant_text(&store), "Ada, I think");
+
+    let events = events.lock().unwrap();
+    let started = events
+        .iter()
+        .position(|event| {
+            matches!(event, AgentStreamEvent::SubAgentStarted { agent_id, task, .. }
+                if agent_id == "agent-helper" && task == "Say hi")
+        })
+        .expect("sub-run started");
+    let streamed = events
+        .iter()
+        .position(|event| {
+            matches!(event, AgentStreamEvent::SubAgentEvent { tool_call_id, event }
+                if tool_call_id == "call-agent-helper"
+                    && matches!(**event, AgentStreamEvent::TextDelta { ref delta, .. } if delta == "Hi"))
+        })
+        .expect("sub-run text forwarded");
+    let finished = events
+        .iter()
+        .position(|event| {
+            matches!(
+                event,
+                AgentStreamEvent::SubAgentFinished {
+                    cancelled: false,
+                    error: None,
+                    ..
+                }
+            )
+        })
+        .expect("sub-run finished");
+    assert!(started < streamed && streamed < finished);
+    assert_eq!(events.last(), Some(&AgentStreamEvent::Completed));
+}
+
+#[tokio::test]
+async fn test_cancelling_the_parent_cancels_its_sub_run() {
+    let (port, requests) = serve_sequence(vec![openai_delegate_call("agent-helper")]).await;
+    let store = make_store();
+    let registry = insert_delegating_agents(&store);
+    let state = AgentRunnerState::default();
+    let cancellation = state.begin_run("stream-1").unwrap();
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let events_clone = events.clone();
+    let cancel_state = state.clone();
+
+    run_thread_loop_impl(
+        &store,
+        &registry,
+        "agent-routed",
+        "thread-routed",
+        "Ask the helper".to_string(),
+        Vec::new(),
+        None,
+        run_config("openai", mock_provider(None, port), 0.7, 256),
+        move |event| {
+            if matches!(event, AgentStreamEvent::SubAgentStarted { .. }) {
+                cancel_state.cancel_run("stream-1").unwrap();
+            }
+            events_clone.lock().unwrap().push(event);
+        },
+        |_| async { Err(AppError::Other("unexpected tool dispatch".to_string())) },
+        Some(cancellation),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(requests.lock().unwrap().len(), 1);
+    let events = events.lock().unwrap();
+    assert!(events.iter().any(|event| matches!(
+        event,
+        AgentStreamEvent::SubAgentFinished {
+            cancelled: true,
+            ..
+        }
+    )));
+    assert_eq!(events.last(), Some(&AgentStreamEvent::Cancelled));
+}
+
+#[tokio::test]
+async fn test_delegation_tool_is_withheld_at_the_depth_limit() {
+    let call = openai_delegate_call("agent-routed");
+    let (port, requests) = serve_sequence(vec![
+        call, call, call, OPENAI_HI, OPENAI_HI, OPENAI_HI, OPENAI_HI,
+    ])
+    .await;
+    let store = make_store();
+    let registry = insert_delegating_agents(&store);
+
+    run_thread_loop_impl(
+        &store,
+        &registry,
+        "agent-routed",
+        "thread-routed",
+        "Recurse".to_string(),
+        Vec::new(),
+        None,
+        run_config("openai", mock_provider(None, port), 0.7, 256),
+        |_| {},
+        |_| async { Err(AppError::Other("unexpected tool dispatch".to_string())) },
+        None,
+    )
+    .await
+    .unwrap();
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 7);
+    let offers_delegation = |request: &String| request.contains("builtin__delegate-to-agent");
+    assert!(requests[..3].iter().all(offers_delegation));
+    assert!(!offers_delegation(&requests[3]));
+}
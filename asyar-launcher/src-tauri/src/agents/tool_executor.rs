@@ -1,8 +1,9 @@
 use crate::agents::runner::{AgentStreamEvent, ExternalToolRequest, McpPermissionChoice};
 use crate::agents::tools::{ToolRegistry, ToolSource};
 use crate::error::AppError;
-use crate::mcp::McpSupervisor;
+use crate::mcp::{McpCallHooks, McpProgress, McpSupervisor};
 use crate::storage::DataStore;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tauri::ipc::Channel;
@@ -88,9 +89,9 @@ pub fn resolve_agent_tool_target(
 pub async fn execute_agent_tool<R: AgentToolRuntime>(
     registry: &ToolRegistry,
     runtime: &R,
-    agent_id: &str,
     request: &ExternalToolRequest,
 ) -> Result<serde_json::Value, AppError> {
+    let agent_id = request.agent_id.as_str();
     match resolve_agent_tool_target(registry, &request.tool_id)? {
         AgentToolTarget::Builtin { tool_id } => Err(AppError::Validation(format!(
             "built-in tool '{tool_id}' must execute inside the agent runner"
@@ -123,6 +124,19 @@ pub async fn execute_agent_tool<R: AgentToolRuntime>(
     }
 }
 
+/// Chat status line for an MCP progress update, e.g.
+/// `"create_issue: Uploading (3/10)"`.
+fn mcp_progress_status(tool_id: &str, progress: &McpProgress) -> String {
+    let count = match progress.total {
+        Some(total) => format!("{}/{}", progress.progress, total),
+        None => progress.progress.to_string(),
+    };
+    match progress.message.as_deref().filter(|m| !m.is_empty()) {
+        Some(message) => format!("{tool_id}: {message} ({count})"),
+        None => format!("{tool_id}: {count}"),
+    }
+}
+
 async fn await_bridge_response<T>(
     receiver: oneshot::Receiver<T>,
     timeout: Duration,
@@ -179,15 +193,44 @@ impl AgentToolRuntime for TauriAgentToolRuntime {
         agent_id: &str,
         args: serde_json::Value,
     ) -> Result<serde_json::Value, AppError> {
-        crate::mcp::tool_adapter::invoke_mcp_tool(
-            &self.supervisor,
-            &self.store,
-            server_id,
-            tool_id,
-            Some(agent_id),
-            args,
-        )
-        .await
+        let cancel = self.runner_state.cancellation(&self.stream_id)?;
+        let runtime = self.clone();
+        let (server_id, tool_id, agent_id) = (
+            server_id.to_string(),
+            tool_id.to_string(),
+            agent_id.to_string(),
+        );
+        // Spawned because a cancelled run drops this future, which would cut
+        // the call off before `notifications/cancelled` reaches the server.
+        let call = tokio::spawn(async move {
+            let reported = AtomicBool::new(false);
+            let on_progress = |progress: McpProgress| {
+                reported.store(true, Ordering::Relaxed);
+                let _ = runtime.emit(AgentStreamEvent::Status {
+                    status: Some(mcp_progress_status(&tool_id, &progress)),
+                });
+            };
+            let hooks = McpCallHooks {
+                on_progress: Some(&on_progress),
+                cancel,
+            };
+            let result = crate::mcp::tool_adapter::invoke_mcp_tool_with(
+                &runtime.supervisor,
+                &runtime.store,
+                &server_id,
+                &tool_id,
+                Some(&agent_id),
+                args,
+                hooks,
+            )
+            .await;
+            if reported.load(Ordering::Relaxed) {
+                let _ = runtime.emit(AgentStreamEvent::Status { status: None });
+            }
+            result
+        });
+        call.await
+            .map_err(|error| AppError::Other(format!("MCP tool call task failed: {error}")))?
     }
 
     async fn invoke_tier2(
@@ -329,6 +372,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn mcp_progress_status_includes_message_and_count() {
+        let progress = |message: Option<&str>, total: Option<f64>| McpProgress {
+            progress: 3.0,
+            total,
+            message: message.map(str::to_string),
+        };
+        assert_eq!(
+            mcp_progress_status("upload", &progress(Some("Sending"), Some(10.0))),
+            "upload: Sending (3/10)"
+        );
+        assert_eq!(
+            mcp_progress_status("upload", &progress(None, None)),
+            "upload: 3"
+        );
+    }
+
     #[test]
     fn resolves_mcp_target_without_frontend_id_parsing() {
         let registry = ToolRegistry::new();
@@ -436,6 +496,7 @@ mod tests {
 
     fn request(tool_id: &str) -> ExternalToolRequest {
         ExternalToolRequest {
+            agent_id: "agent-1".to_string(),
             tool_call_id: "call-1".to_string(),
             tool_id: tool_id.to_string(),
             arguments: serde_json::json!({ "value": 7 }),
@@ -450,14 +511,9 @@ mod tests {
             .unwrap();
         let runtime = MockRuntime::default();
 
-        let result = execute_agent_tool(
-            &registry,
-            &runtime,
-            "agent-1",
-            &request("org.example.notes:lookup"),
-        )
-        .await
-        .unwrap();
+        let result = execute_agent_tool(&registry, &runtime, &request("org.example.notes:lookup"))
+            .await
+            .unwrap();
 
         assert_eq!(result, serde_json::json!({ "source": "tier2" }));
         assert_eq!(runtime.tier2_calls.lock().unwrap().len(), 1);
@@ -477,14 +533,9 @@ mod tests {
             .unwrap()
             .push(Ok(serde_json::json!({ "issues": [] })));
 
-        let result = execute_agent_tool(
-            &registry,
-            &runtime,
-            "agent-1",
-            &request("mcp:linear:list_issues"),
-        )
-        .await
-        .unwrap();
+        let result = execute_agent_tool(&registry, &runtime, &request("mcp:linear:list_issues"))
+            .await
+            .unwrap();
 
         assert_eq!(result, serde_json::json!({ "issues": [] }));
         assert!(runtime.tier2_calls.lock().unwrap().is_empty());
@@ -507,14 +558,9 @@ mod tests {
         ]);
         *runtime.permission_choice.lock().unwrap() = Some(McpPermissionChoice::AllowOnce);
 
-        let result = execute_agent_tool(
-            &registry,
-            &runtime,
-            "agent-1",
-            &request("mcp:linear:create_issue"),
-        )
-        .await
-        .unwrap();
+        let result = execute_agent_tool(&registry, &runtime, &request("mcp:linear:create_issue"))
+            .await
+            .unwrap();
 
         assert_eq!(result, serde_json::json!({ "created": true }));
         assert_eq!(runtime.mcp_calls.lock().unwrap().len(), 2);
@@ -547,14 +593,9 @@ mod tests {
             }));
         *runtime.permission_choice.lock().unwrap() = Some(McpPermissionChoice::Cancel);
 
-        let error = execute_agent_tool(
-            &registry,
-            &runtime,
-            "agent-1",
-            &request("mcp:linear:create_issue"),
-        )
-        .await
-        .unwrap_err();
+        let error = execute_agent_tool(&registry, &runtime, &request("mcp:linear:create_issue"))
+            .await
+            .unwrap_err();
 
         assert!(error.to_string().contains("cancelled"));
         assert_eq!(runtime.mcp_calls.lock().unwrap().len(), 1);
@@ -0,0 +1,368 @@
+//! Per-agent sandbox for the `shell-exec`, `fs-read` and `fs-write` tools.
+//!
+//! Tool selection decides *whether* an agent gets a tool; the agent's
+//! [`AgentToolPolicy`] decides what it may do with it — which executables,
+//! which working directories, which paths, how much output and for how
+//! long, and whether writes happen, are only previewed, or wait for the
+//! user. Path scopes reuse the `files:read` globs and credential deny-list
+//! from [`crate::files_scope`], so an agent can't be granted `~/.ssh` even
+//! by a `~/**` pattern. Every call, allowed or not, lands in
+//! [`crate::storage::agent_tool_audit`].
+
+use crate::agents::tools::ToolCaller;
+use crate::commands::files::normalize_path;
+use crate::error::AppError;
+use crate::files_scope;
+use crate::fs_watcher::matcher::expand_tilde;
+use crate::storage::agent_tool_audit::{self, AuditOutcome, NewAgentToolAuditEntry};
+use crate::storage::agent_tool_policies::{
+    self, AgentToolPolicy, MAX_OUTPUT_BYTES_LIMIT, TIMEOUT_SECS_LIMIT,
+};
+use crate::storage::DataStore;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// Emitted when a confirm-mode write needs the user's answer.
+pub const AGENT_WRITE_CONFIRM_EVENT: &str = "asyar:agents:confirm-write";
+
+/// How long a confirm-mode write waits before counting as declined.
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How much of the content the confirmation dialog shows.
+const PREVIEW_CHARS: usize = 2_000;
+
+/// Payload of [`AGENT_WRITE_CONFIRM_EVENT`]; answered through
+/// `agents_tool_write_resolve` with the same `id`.
+#[derive(Debug, Clone, PartialEq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteConfirmRequest {
+    pub id: String,
+    pub agent_id: Option<String>,
+    pub path: String,
+    pub bytes: usize,
+    /// Whether the write replaces an existing file.
+    pub exists: bool,
+    /// The start of the content, for the dialog.
+    pub preview: String,
+}
+
+pub type ConfirmEmitFn = Box<dyn Fn(&WriteConfirmRequest) + Send + Sync>;
+
+pub struct ToolSandbox {
+    data_store: DataStore,
+    home: PathBuf,
+    extra_deny: Vec<PathBuf>,
+    canonical_home: PathBuf,
+    canonical_extra_deny: Vec<PathBuf>,
+    pending: Mutex<HashMap<String, oneshot::Sender<bool>>>,
+    emit: Mutex<Option<ConfirmEmitFn>>,
+}
+
+impl ToolSandbox {
+    /// `extra_deny` joins the `files_scope` deny-list — the launcher's own
+    /// app-data dir, so no policy can hand an agent its database.
+    pub fn new(data_store: DataStore, home: PathBuf, extra_deny: Vec<PathBuf>) -> Self {
+        let canonical_home = dunce::canonicalize(&home).unwrap_or_else(|_| home.clone());
+        let canonical_extra_deny = extra_deny
+            .iter()
+            .map(|p| dunce::canonicalize(p).unwrap_or_else(|_| p.clone()))
+            .collect();
+        Self {
+            data_store,
+            home,
+            extra_deny,
+            canonical_home,
+            canonical_extra_deny,
+            pending: Mutex::new(HashMap::new()),
+            emit: Mutex::new(None),
+        }
+    }
+
+    /// Without an emitter every confirm-mode write is declined.
+    pub fn set_confirm_emitter(&self, emit: ConfirmEmitFn) {
+        if let Ok(mut g) = self.emit.lock() {
+            *g = Some(emit);
+        }
+    }
+
+    /// The caller's policy. Agents without a stored one, and calls from
+    /// the launcher itself, get the default.
+    pub fn policy_for(&self, caller: &ToolCaller) -> Result<AgentToolPolicy, AppError> {
+        let Some(agent_id) = caller.agent_id.as_deref() else {
+            return Ok(AgentToolPolicy::default());
+        };
+        let conn = self.data_store.conn()?;
+        Ok(agent_tool_policies::get(&conn, agent_id)?.unwrap_or_default())
+    }
+
+    /// Appends one audit row. A failed insert is logged, never surfaced:
+    /// the tool call itself already happened.
+    pub fn record(
+        &self,
+        tool_id: &str,
+        caller: &ToolCaller,
+        args: &serde_json::Value,
+        outcome: AuditOutcome,
+        detail: Option<String>,
+    ) {
+        let entry = NewAgentToolAuditEntry {
+            tool_id: tool_id.to_string(),
+            agent_id: caller.agent_id.clone(),
+            called_at: chrono::Utc::now().timestamp_millis(),
+            outcome,
+            detail,
+            args_summary: args.to_string().chars().take(200).collect(),
+        };
+        match self.data_store.conn() {
+            Ok(conn) => {
+                if let Err(e) = agent_tool_audit::insert_entry(&conn, &entry) {
+                    log::warn!("[agents] failed to write tool audit entry: {e}");
+                }
+            }
+            Err(e) => log::warn!("[agents] no DB connection for tool audit: {e}"),
+        }
+    }
+
+    /// [`record`](Self::record) for a plain call: policy refusals are
+    /// `Denied`, every other error `Failed`.
+    pub fn record_result(
+        &self,
+        tool_id: &str,
+        caller: &ToolCaller,
+        args: &serde_json::Value,
+        result: &Result<serde_json::Value, AppError>,
+    ) {
+        let (outcome, detail) = match result {
+            Ok(_) => (AuditOutcome::Ok, None),
+            Err(AppError::Permission(msg)) => (AuditOutcome::Denied, Some(msg.clone())),
+            Err(e) => (AuditOutcome::Failed, Some(e.to_string())),
+        };
+        self.record(tool_id, caller, args, outcome, detail);
+    }
+
+    /// Bare names (`git`) must match an allow-list entry exactly, as must
+    /// paths — `/tmp/x/git` doesn't pass on the strength of `git`.
+    pub fn check_command(&self, policy: &AgentToolPolicy, command: &str) -> Result<(), AppError> {
+        if policy.allowed_commands.is_empty()
+            || policy.allowed_commands.iter().any(|c| c == command)
+        {
+            return Ok(());
+        }
+        Err(AppError::Permission(format!(
+            "'{command}' is not in this agent's allowed commands"
+        )))
+    }
+
+    /// The directory a command runs in. With working-directory roots set,
+    /// no `cwd` means the first root, a relative one resolves against it,
+    /// and the result must sit under one of them once symlinks resolve.
+    pub fn resolve_cwd(
+        &self,
+        policy: &AgentToolPolicy,
+        cwd: Option<&str>,
+    ) -> Result<Option<PathBuf>, AppError> {
+        let roots: Vec<PathBuf> = policy
+            .working_dirs
+            .iter()
+            .map(|root| {
+                let root = expand_tilde(root, &self.home);
+                dunce::canonicalize(&root).unwrap_or(root)
+            })
+            .collect();
+        let Some(first) = roots.first() else {
+            return Ok(cwd.map(|dir| expand_tilde(dir, &self.home)));
+        };
+        let Some(dir) = cwd else {
+            return Ok(Some(first.clone()));
+        };
+        let dir = expand_tilde(dir, &self.home);
+        let dir = normalize_path(&if dir.is_absolute() {
+            dir
+        } else {
+            first.join(dir)
+        });
+        let canonical = dunce::canonicalize(&dir).map_err(|e| {
+            AppError::Validation(format!(
+                "working directory '{}' is not accessible: {e}",
+                dir.display()
+            ))
+        })?;
+        if roots
+            .iter()
+            .any(|root| files_scope::starts_with_case_aware(&canonical, root))
+        {
+            Ok(Some(canonical))
+        } else {
+            Err(AppError::Permission(format!(
+                "'{}' is outside this agent's working directories",
+                canonical.display()
+            )))
+        }
+    }
+
+    /// Scope-checks a path for `fs-read` and returns the one to open.
+    pub fn check_read(&self, policy: &AgentToolPolicy, path: &str) -> Result<PathBuf, AppError> {
+        self.check_path(&policy.read_paths, path, "read")
+    }
+
+    /// Scope-checks a path for `fs-write` and returns the one to write.
+    pub fn check_write(&self, policy: &AgentToolPolicy, path: &str) -> Result<PathBuf, AppError> {
+        self.check_path(&policy.write_paths, path, "write")
+    }
+
+    /// Same two-pass shape as `commands::files::validate_scoped_path`: the
+    /// requested form is checked so patterns match what the user typed,
+    /// then the symlink-resolved form so a covered link can't launder
+    /// access elsewhere. A file that doesn't exist yet resolves through
+    /// its parent directory.
+    fn check_path(
+        &self,
+        patterns: &[String],
+        path: &str,
+        access: &str,
+    ) -> Result<PathBuf, AppError> {
+        let expanded = expand_tilde(path, &self.home);
+        if !expanded.is_absolute() {
+            return Err(AppError::Validation(format!(
+                "path must be absolute, got: '{path}'"
+            )));
+        }
+        let normalized = normalize_path(&expanded);
+        self.check_scope(patterns, &normalized, &self.home, &self.extra_deny, access)?;
+
+        let resolved = dunce::canonicalize(&normalized).or_else(|e| {
+            match (normalized.parent(), normalized.file_name()) {
+                (Some(parent), Some(name)) => dunce::canonicalize(parent).map(|p| p.join(name)),
+                _ => Err(e),
+            }
+        });
+        let Ok(resolved) = resolved else {
+            // Nothing on disk to resolve — the read or write reports it.
+            return Ok(normalized);
+        };
+        self.check_scope(
+            patterns,
+            &resolved,
+            &self.canonical_home,
+            &self.canonical_extra_deny,
+            access,
+        )?;
+        Ok(resolved)
+    }
+
+    fn check_scope(
+        &self,
+        patterns: &[String],
+        path: &Path,
+        home: &Path,
+        extra_deny: &[PathBuf],
+        access: &str,
+    ) -> Result<(), AppError> {
+        files_scope::check_path_denied(path, home, extra_deny).map_err(|_| {
+            AppError::Permission(format!(
+                "'{}' is inside a protected location",
+                path.display()
+            ))
+        })?;
+        if patterns.is_empty() {
+            return Ok(());
+        }
+        files_scope::path_covered_by_patterns(patterns, path, home).map_err(|_| {
+            AppError::Permission(format!(
+                "'{}' is outside this agent's {access} paths",
+                path.display()
+            ))
+        })
+    }
+
+    /// Asks the user about a confirm-mode write and waits for the answer.
+    /// No answer within [`CONFIRM_TIMEOUT`] is a no.
+    pub async fn confirm_write(
+        &self,
+        caller: &ToolCaller,
+        path: &Path,
+        content: &str,
+    ) -> Result<bool, AppError> {
+        let request = WriteConfirmRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            agent_id: caller.agent_id.clone(),
+            path: path.to_string_lossy().into_owned(),
+            bytes: content.len(),
+            exists: path.exists(),
+            preview: content.chars().take(PREVIEW_CHARS).collect(),
+        };
+        let (tx, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .map_err(|_| AppError::Lock)?
+            .insert(request.id.clone(), tx);
+        let emitted = match self.emit.lock() {
+            Ok(g) => match g.as_ref() {
+                Some(emit) => {
+                    emit(&request);
+                    true
+                }
+                None => false,
+            },
+            Err(_) => false,
+        };
+        let approved = emitted
+            && matches!(
+                tokio::time::timeout(CONFIRM_TIMEOUT, rx).await,
+                Ok(Ok(true))
+            );
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(&request.id);
+        }
+        Ok(approved)
+    }
+
+    /// Delivers the user's answer. `false` when nothing is waiting on `id`
+    /// (already answered, or timed out).
+    pub fn resolve_write(&self, id: &str, approved: bool) -> Result<bool, AppError> {
+        let waiting = self.pending.lock().map_err(|_| AppError::Lock)?.remove(id);
+        Ok(waiting.is_some_and(|tx| tx.send(approved).is_ok()))
+    }
+}
+
+/// Rejects policies the sandbox can't enforce as written.
+pub fn validate(policy: &AgentToolPolicy) -> Result<(), AppError> {
+    if policy.allowed_commands.iter().any(|c| c.trim().is_empty()) {
+        return Err(AppError::Validation(
+            "allowed commands must not be empty".into(),
+        ));
+    }
+    for dir in &policy.working_dirs {
+        if !(dir == "~" || dir.starts_with("~/") || Path::new(dir).is_absolute()) {
+            return Err(AppError::Validation(format!(
+                "working directory '{dir}' must be absolute or start with '~/'"
+            )));
+        }
+    }
+    for pattern in policy.read_paths.iter().chain(&policy.write_paths) {
+        files_scope::validate_files_read_pattern(pattern)?;
+    }
+    if !(1..=MAX_OUTPUT_BYTES_LIMIT).contains(&policy.max_output_bytes) {
+        return Err(AppError::Validation(format!(
+            "output cap must be between 1 and {MAX_OUTPUT_BYTES_LIMIT} bytes"
+        )));
+    }
+    if !(1..=TIMEOUT_SECS_LIMIT).contains(&policy.timeout_secs) {
+        return Err(AppError::Validation(format!(
+            "timeout must be between 1 and {TIMEOUT_SECS_LIMIT} seconds"
+        )));
+    }
+    Ok(())
+}
+
+/// Cuts `bytes` to `cap` and decodes them, reporting whether anything was
+/// dropped.
+pub fn decode_capped(mut bytes: Vec<u8>, cap: usize) -> (String, bool) {
+    let truncated = bytes.len() > cap;
+    bytes.truncate(cap);
+    (String::from_utf8_lossy(&bytes).into_owned(), truncated)
+}
@@ -0,0 +1,230 @@
+use crate::agents::tool_policy::{decode_capped, validate, ToolSandbox};
+use crate::agents::tools::ToolCaller;
+use crate::error::AppError;
+use crate::storage::agent_tool_policies::{self, AgentToolPolicy};
+use crate::storage::DataStore;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+fn sandbox(home: &Path) -> ToolSandbox {
+    ToolSandbox::new(
+        crate::storage::create_test_store(),
+        home.to_path_buf(),
+        Vec::new(),
+    )
+}
+
+fn agent(id: &str) -> ToolCaller {
+    ToolCaller {
+        agent_id: Some(id.to_string()),
+        thread_id: None,
+    }
+}
+
+fn denied<T: std::fmt::Debug>(result: Result<T, AppError>) -> bool {
+    matches!(result, Err(AppError::Permission(_)))
+}
+
+#[test]
+fn agents_without_a_stored_policy_get_the_default() {
+    let store: DataStore = crate::storage::create_test_store();
+    let policy = AgentToolPolicy {
+        allowed_commands: vec!["git".into()],
+        ..AgentToolPolicy::default()
+    };
+    agent_tool_policies::set(&store.conn().unwrap(), "a1", &policy, 1).unwrap();
+    let sandbox = ToolSandbox::new(store, PathBuf::from("/home/u"), Vec::new());
+
+    assert_eq!(sandbox.policy_for(&agent("a1")).unwrap(), policy);
+    assert_eq!(
+        sandbox.policy_for(&agent("a2")).unwrap(),
+        AgentToolPolicy::default()
+    );
+    assert_eq!(
+        sandbox.policy_for(&ToolCaller::default()).unwrap(),
+        AgentToolPolicy::default()
+    );
+}
+
+#[test]
+fn commands_must_match_the_allow_list_exactly() {
+    let sandbox = sandbox(Path::new("/home/u"));
+    let open = AgentToolPolicy::default();
+    assert!(sandbox.check_command(&open, "rm").is_ok());
+
+    let policy = AgentToolPolicy {
+        allowed_commands: vec!["git".into(), "/usr/bin/ls".into()],
+        ..AgentToolPolicy::default()
+    };
+    assert!(sandbox.check_command(&policy, "git").is_ok());
+    assert!(sandbox.check_command(&policy, "/usr/bin/ls").is_ok());
+    assert!(denied(sandbox.check_command(&policy, "/tmp/evil/git")));
+    assert!(denied(sandbox.check_command(&policy, "rm")));
+}
+
+#[test]
+fn cwd_defaults_to_the_first_root_and_must_stay_inside_the_roots() {
+    let home = tempfile::tempdir().unwrap();
+    let project = home.path().join("project");
+    std::fs::create_dir_all(project.join("src")).unwrap();
+    let home_path = dunce::canonicalize(home.path()).unwrap();
+    let sandbox = sandbox(&home_path);
+    let policy = AgentToolPolicy {
+        working_dirs: vec!["~/project".into()],
+        ..AgentToolPolicy::default()
+    };
+    let root = home_path.join("project");
+
+    assert_eq!(
+        sandbox.resolve_cwd(&policy, None).unwrap(),
+        Some(root.clone())
+    );
+    assert_eq!(
+        sandbox.resolve_cwd(&policy, Some("src")).unwrap(),
+        Some(root.join("src"))
+    );
+    assert!(denied(sandbox.resolve_cwd(&policy, Some(".."))));
+    assert!(denied(
+        sandbox.resolve_cwd(&policy, Some(home_path.to_str().unwrap()))
+    ));
+}
+
+#[test]
+fn reads_are_limited_to_the_policy_globs() {
+    let home = tempfile::tempdir().unwrap();
+    let home_path = dunce::canonicalize(home.path()).unwrap();
+    std::fs::create_dir_all(home_path.join("notes")).unwrap();
+    std::fs::write(home_path.join("notes/todo.md"), "x").unwrap();
+    std::fs::write(home_path.join("secret.txt"), "x").unwrap();
+    let sandbox = sandbox(&home_path);
+    let policy = AgentToolPolicy {
+        read_paths: vec!["~/notes/**".into()],
+        ..AgentToolPolicy::default()
+    };
+
+    assert!(sandbox.check_read(&policy, "~/notes/todo.md").is_ok());
+    assert!(denied(sandbox.check_read(&policy, "~/secret.txt")));
+    assert!(denied(sandbox.check_read(&policy, "~/notes/../secret.txt")));
+    assert!(matches!(
+        sandbox.check_read(&policy, "notes/todo.md"),
+        Err(AppError::Validation(_))
+    ));
+}
+
+#[test]
+fn credential_stores_stay_denied_even_under_a_matching_glob() {
+    let sandbox = sandbox(Path::new("/home/u"));
+    let policy = AgentToolPolicy {
+        read_paths: vec!["~/**".into()],
+        write_paths: vec!["~/**".into()],
+        ..AgentToolPolicy::default()
+    };
+    assert!(denied(
+        sandbox.check_read(&policy, "/home/u/.ssh/id_ed25519")
+    ));
+    assert!(denied(sandbox.check_write(
+        &AgentToolPolicy::default(),
+        "/home/u/.aws/credentials"
+    )));
+}
+
+#[test]
+fn extra_deny_roots_cover_the_launcher_data_dir() {
+    let sandbox = ToolSandbox::new(
+        crate::storage::create_test_store(),
+        PathBuf::from("/home/u"),
+        vec![PathBuf::from("/home/u/.local/share/asyar")],
+    );
+    assert!(denied(sandbox.check_write(
+        &AgentToolPolicy::default(),
+        "/home/u/.local/share/asyar/asyar_data.db"
+    )));
+}
+
+#[cfg(unix)]
+#[test]
+fn a_symlink_cannot_launder_a_write_out_of_scope() {
+    let home = tempfile::tempdir().unwrap();
+    let home_path = dunce::canonicalize(home.path()).unwrap();
+    std::fs::create_dir_all(home_path.join("out")).unwrap();
+    std::fs::create_dir_all(home_path.join("elsewhere")).unwrap();
+    std::os::unix::fs::symlink(home_path.join("elsewhere"), home_path.join("out/link")).unwrap();
+    let sandbox = sandbox(&home_path);
+    let policy = AgentToolPolicy {
+        write_paths: vec!["~/out/**".into()],
+        ..AgentToolPolicy::default()
+    };
+
+    assert!(sandbox.check_write(&policy, "~/out/new.txt").is_ok());
+    assert!(denied(sandbox.check_write(&policy, "~/out/link/new.txt")));
+}
+
+#[test]
+fn validate_rejects_unenforceable_policies() {
+    let bad = [
+        AgentToolPolicy {
+            allowed_commands: vec![" ".into()],
+            ..AgentToolPolicy::default()
+        },
+        AgentToolPolicy {
+            working_dirs: vec!["projects".into()],
+            ..AgentToolPolicy::default()
+        },
+        AgentToolPolicy {
+            write_paths: vec!["~/../etc/**".into()],
+            ..AgentToolPolicy::default()
+        },
+        AgentToolPolicy {
+            max_output_bytes: 0,
+            ..AgentToolPolicy::default()
+        },
+        AgentToolPolicy {
+            timeout_secs: 3_600,
+            ..AgentToolPolicy::default()
+        },
+    ];
+    for policy in bad {
+        assert!(
+            matches!(validate(&policy), Err(AppError::Validation(_))),
+            "{policy:?} should be rejected"
+        );
+    }
+    assert!(validate(&AgentToolPolicy::default()).is_ok());
+}
+
+#[test]
+fn decode_capped_reports_truncation() {
+    assert_eq!(decode_capped(b"hello".to_vec(), 5), ("hello".into(), false));
+    assert_eq!(decode_capped(b"hello!".to_vec(), 5), ("hello".into(), true));
+}
+
+#[tokio::test]
+async fn confirm_without_a_listener_is_declined() {
+    let sandbox = sandbox(Path::new("/home/u"));
+    let approved = sandbox
+        .confirm_write(&agent("a1"), Path::new("/tmp/x.txt"), "x")
+        .await
+        .unwrap();
+    assert!(!approved);
+}
+
+#[tokio::test]
+async fn confirm_waits_for_the_users_answer() {
+    let sandbox = Arc::new(sandbox(Path::new("/home/u")));
+    let answering = Arc::clone(&sandbox);
+    sandbox.set_confirm_emitter(Box::new(move |request| {
+        assert_eq!(request.preview, "draft");
+        let answering = Arc::clone(&answering);
+        let id = request.id.clone();
+        tokio::spawn(async move {
+            assert!(answering.resolve_write(&id, true).unwrap());
+        });
+    }));
+
+    let approved = sandbox
+        .confirm_write(&agent("a1"), Path::new("/tmp/x.txt"), "draft")
+        .await
+        .unwrap();
+    assert!(approved);
+    assert!(!sandbox.resolve_write("unknown", true).unwrap());
+}
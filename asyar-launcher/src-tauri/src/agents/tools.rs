@@ -106,11 +106,29 @@ pub struct ManifestTool {
     pub parameters: serde_json::Value,
 }
 
+/// Who a tool is invoked for. Only tools that keep per-agent state (the
+/// memory tools) look at it; calls from the launcher UI have no caller.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolCaller {
+    pub agent_id: Option<String>,
+    pub thread_id: Option<String>,
+}
+
 /// Trait implemented by every built-in tool.
 #[async_trait::async_trait]
 pub trait BuiltinTool: Send + Sync {
     fn descriptor(&self) -> ToolDescriptor;
     async fn invoke(&self, args: serde_json::Value) -> Result<serde_json::Value, AppError>;
+
+    /// [`invoke`](Self::invoke) on behalf of `caller`. Tools that don't care
+    /// who is calling keep this default.
+    async fn invoke_as(
+        &self,
+        args: serde_json::Value,
+        _caller: &ToolCaller,
+    ) -> Result<serde_json::Value, AppError> {
+        self.invoke(args).await
+    }
 }
 
 /// Central registry for all tools — built-in, Tier 2, and MCP.
@@ -364,11 +382,22 @@ pub async fn agents_invoke_builtin_tool_impl(
     registry: &ToolRegistry,
     id: &str,
     args: serde_json::Value,
+) -> Result<serde_json::Value, AppError> {
+    invoke_builtin_tool_as(registry, id, args, &ToolCaller::default()).await
+}
+
+/// Runs the builtin tool `id` on behalf of `caller` — the agent runner's
+/// entry point, so tools can tell which agent and thread are calling.
+pub async fn invoke_builtin_tool_as(
+    registry: &ToolRegistry,
+    id: &str,
+    args: serde_json::Value,
+    caller: &ToolCaller,
 ) -> Result<serde_json::Value, AppError> {
     let tool = registry
         .get_builtin(id)
         .ok_or_else(|| AppError::NotFound(format!("builtin tool '{}' not found", id)))?;
-    tool.invoke(args).await
+    tool.invoke_as(args, caller).await
 }
 
 /// Tauri command — invoke a built-in tool by its bare id.
@@ -0,0 +1,414 @@
+//! Scheduled and event-triggered agent runs.
+//!
+//! Rust owns *when* a trigger fires; the frontend owns the run itself. A
+//! fire is persisted first (`last_fired_at`, the schedule's next due time)
+//! and then emitted as [`AGENT_TRIGGER_FIRE_EVENT`] — the same
+//! persist-first-emit-second ordering as [`crate::timers`]. The agents
+//! extension listens for it, runs the agent silently with the user's
+//! provider settings, tracks it in the runs registry and delivers the answer
+//! to a note, a notification or the HUD.
+//!
+//! Three sources feed fires:
+//! - schedules, polled by the [`job`] registered with the central scheduler;
+//! - new files in a directory, via [`TriggerWatchers`] on the
+//!   [`FsWatcherRegistry`];
+//! - copied text, via [`on_clipboard_text`] from the clipboard capture path.
+
+use crate::error::AppError;
+use crate::fs_watcher::matcher::expand_tilde;
+use crate::fs_watcher::{FsWatcherOptions, FsWatcherRegistry};
+use crate::storage::agent_triggers::{self, AgentTrigger, TriggerOutput, TriggerSpec};
+use crate::storage::clipboard::ClipboardItem;
+use crate::storage::DataStore;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveTime, TimeZone};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Tauri event emitted when a trigger fires. Payload: [`TriggerFirePayload`].
+pub const AGENT_TRIGGER_FIRE_EVENT: &str = "asyar:agents:trigger";
+
+/// Owner id under which directory watches are registered with the
+/// [`FsWatcherRegistry`]; its emitter routes this owner's events to
+/// [`TriggerWatchers::new_files`] instead of an extension iframe.
+pub const TRIGGER_WATCH_OWNER: &str = "asyar.agents.triggers";
+
+/// Schedules are minute-granular, so a 15s poll is on time to the user.
+const POLL_INTERVAL_SECS: u64 = 15;
+/// Leaves the webview time to subscribe before overdue schedules fire.
+const STARTUP_DELAY_SECS: u64 = 15;
+
+/// User message for a trigger saved without a prompt.
+const DEFAULT_PROMPT: &str = "Run your task.";
+
+/// File-name suffixes of downloads still in progress; the finished file
+/// lands under its real name and fires then.
+const PARTIAL_SUFFIXES: &[&str] = &[".crdownload", ".part", ".download", ".partial", ".tmp"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerFirePayload {
+    pub trigger_id: String,
+    pub agent_id: String,
+    pub output: TriggerOutput,
+    /// The user message for the run: the trigger's prompt plus the event's
+    /// details.
+    pub input: String,
+    pub fired_at: i64,
+}
+
+/// Rejects specs the evaluator could never fire.
+pub fn validate(spec: &TriggerSpec) -> Result<(), AppError> {
+    match spec {
+        TriggerSpec::Schedule { weekdays, time } => {
+            if let Some(day) = weekdays.iter().find(|day| !(1..=7).contains(*day)) {
+                return Err(AppError::Validation(format!(
+                    "weekday {day} is out of range (1 = Monday … 7 = Sunday)"
+                )));
+            }
+            parse_time(time).map(|_| ())
+        }
+        TriggerSpec::FileCreated { directory } => {
+            if directory.trim().is_empty() {
+                return Err(AppError::Validation(
+                    "a file trigger needs a directory".into(),
+                ));
+            }
+            Ok(())
+        }
+        TriggerSpec::ClipboardMatch { pattern } => regex::Regex::new(pattern)
+            .map(|_| ())
+            .map_err(|e| AppError::Validation(format!("invalid clipboard pattern: {e}"))),
+    }
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, AppError> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M")
+        .map_err(|_| AppError::Validation(format!("time must be HH:MM, got '{time}'")))
+}
+
+/// The first moment strictly after `after` that a schedule spec is due, as
+/// Unix millis, in `after`'s time zone. `None` for event triggers. A wall
+/// time skipped by a DST jump is skipped for that day.
+pub fn next_fire_after<Tz: TimeZone>(spec: &TriggerSpec, after: &DateTime<Tz>) -> Option<i64> {
+    let TriggerSpec::Schedule { weekdays, time } = spec else {
+        return None;
+    };
+    let time = parse_time(time).ok()?;
+    let start = after.date_naive();
+    (0..=7)
+        .map(|offset| start + ChronoDuration::days(offset))
+        .filter(|date| {
+            weekdays.is_empty() || weekdays.contains(&(date.weekday().number_from_monday() as u8))
+        })
+        .filter_map(|date| {
+            after
+                .timezone()
+                .from_local_datetime(&date.and_time(time))
+                .earliest()
+        })
+        .find(|candidate| candidate > after)
+        .map(|candidate| candidate.timestamp_millis())
+}
+
+/// [`next_fire_after`] in the local time zone, from Unix millis.
+pub fn next_fire_at(spec: &TriggerSpec, now_ms: i64) -> Option<i64> {
+    let now = chrono::Local.timestamp_millis_opt(now_ms).single()?;
+    next_fire_after(spec, &now)
+}
+
+/// The run's user message: the prompt, then the event's details.
+pub fn compose_input(trigger: &AgentTrigger, detail: Option<&str>) -> String {
+    let prompt = match trigger.prompt.trim() {
+        "" => DEFAULT_PROMPT,
+        prompt => prompt,
+    };
+    match (&trigger.spec, detail) {
+        (TriggerSpec::FileCreated { .. }, Some(path)) => format!("{prompt}\n\nNew file: {path}"),
+        (TriggerSpec::ClipboardMatch { .. }, Some(text)) => {
+            format!("{prompt}\n\nCopied text:\n{text}")
+        }
+        _ => prompt.to_string(),
+    }
+}
+
+/// Records the fire and builds its payload. A schedule is moved to its next
+/// due time after `fired_at`, so one that came due several times while the
+/// app was quit fires once, not once per missed slot.
+pub fn record_fire(
+    conn: &Connection,
+    trigger: &AgentTrigger,
+    detail: Option<&str>,
+    fired_at: i64,
+) -> Result<TriggerFirePayload, AppError> {
+    let next = next_fire_at(&trigger.spec, fired_at);
+    agent_triggers::mark_fired(conn, &trigger.id, fired_at, next)?;
+    Ok(TriggerFirePayload {
+        trigger_id: trigger.id.clone(),
+        agent_id: trigger.agent_id.clone(),
+        output: trigger.output,
+        input: compose_input(trigger, detail),
+        fired_at,
+    })
+}
+
+fn fire(app: &AppHandle, conn: &Connection, trigger: &AgentTrigger, detail: Option<&str>) {
+    let payload = match record_fire(conn, trigger, detail, crate::shell::now_millis() as i64) {
+        Ok(payload) => payload,
+        Err(e) => {
+            log::warn!(
+                "[agent-triggers] recording fire of {} failed — skipping emit: {e}",
+                trigger.id
+            );
+            return;
+        }
+    };
+    if let Err(e) = app.emit(AGENT_TRIGGER_FIRE_EVENT, &payload) {
+        log::warn!("[agent-triggers] failed to emit {AGENT_TRIGGER_FIRE_EVENT}: {e}");
+    }
+}
+
+fn fire_due(app: &AppHandle, store: &DataStore) {
+    let result = store.conn().and_then(|conn| {
+        let now = crate::shell::now_millis() as i64;
+        for trigger in agent_triggers::due(&conn, now)? {
+            fire(app, &conn, &trigger, None);
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        log::warn!("[agent-triggers] schedule poll failed: {e}");
+    }
+}
+
+/// Scheduler job: fire due schedules, overdue ones included, every 15s.
+pub fn job(app: AppHandle, store: DataStore) -> crate::scheduler::Job {
+    crate::scheduler::Job::fixed_interval(
+        "agent-triggers",
+        Duration::from_secs(STARTUP_DELAY_SECS),
+        Duration::from_secs(POLL_INTERVAL_SECS),
+        move || {
+            let app = app.clone();
+            let store = store.clone();
+            async move { fire_due(&app, &store) }
+        },
+    )
+}
+
+/// The enabled clipboard triggers whose pattern matches `text`.
+pub fn clipboard_matches<'a>(triggers: &'a [AgentTrigger], text: &str) -> Vec<&'a AgentTrigger> {
+    triggers
+        .iter()
+        .filter(|trigger| trigger.enabled)
+        .filter(|trigger| match &trigger.spec {
+            TriggerSpec::ClipboardMatch { pattern } => regex::Regex::new(pattern)
+                .map(|re| re.is_match(text))
+                .unwrap_or(false),
+            _ => false,
+        })
+        .collect()
+}
+
+/// The text a fresh capture may hand to clipboard triggers. Items the secret
+/// detector flagged never reach an agent — its prompt goes to a remote model.
+pub fn clipboard_trigger_text(item: &ClipboardItem) -> Option<&str> {
+    if item.item_type != "text" || item.redacted_kinds.as_ref().is_some_and(|k| !k.is_empty()) {
+        return None;
+    }
+    item.content.as_deref()
+}
+
+/// Fires every clipboard trigger matching freshly copied `text`. Errors are
+/// logged: a trigger must never fail the capture that fed it.
+pub fn on_clipboard_text(app: &AppHandle, conn: &Connection, text: &str) {
+    let triggers = match agent_triggers::list_enabled(conn) {
+        Ok(triggers) => triggers,
+        Err(e) => {
+            log::warn!("[agent-triggers] clipboard check failed: {e}");
+            return;
+        }
+    };
+    for trigger in clipboard_matches(&triggers, text) {
+        fire(app, conn, trigger, Some(text));
+    }
+}
+
+struct WatchedDirectory {
+    handle_id: String,
+    directory: PathBuf,
+    /// Entry names already present, so only newcomers fire.
+    known: HashSet<OsString>,
+}
+
+/// Directory watches for the enabled `fileCreated` triggers. Stored as
+/// managed Tauri state (`Arc<Self>`) and re-synced whenever triggers change.
+pub struct TriggerWatchers {
+    registry: Arc<FsWatcherRegistry>,
+    home: PathBuf,
+    watched: Mutex<HashMap<String, WatchedDirectory>>,
+    /// Serializes whole syncs, which drop the map lock midway.
+    sync_lock: Mutex<()>,
+}
+
+impl TriggerWatchers {
+    pub fn new(registry: Arc<FsWatcherRegistry>, home: PathBuf) -> Self {
+        Self {
+            registry,
+            home,
+            watched: Mutex::new(HashMap::new()),
+            sync_lock: Mutex::new(()),
+        }
+    }
+
+    /// Watches the directory of every enabled file trigger in `triggers`
+    /// and drops watches whose trigger is gone, disabled or retargeted. A
+    /// directory that can't be watched (missing, permission denied) is
+    /// logged and retried on the next sync.
+    pub fn sync(&self, triggers: &[AgentTrigger]) -> Result<(), AppError> {
+        let wanted: HashMap<&str, PathBuf> = triggers
+            .iter()
+            .filter(|trigger| trigger.enabled)
+            .filter_map(|trigger| match &trigger.spec {
+                TriggerSpec::FileCreated { directory } => Some((
+                    trigger.id.as_str(),
+                    expand_tilde(directory.trim(), &self.home),
+                )),
+                _ => None,
+            })
+            .collect();
+
+        // The watch map is never locked across a registry call: disposing a
+        // watch joins its debouncer, whose callback may be waiting on the
+        // map in `new_files`.
+        let _syncing = self.sync_lock.lock().map_err(|_| AppError::Lock)?;
+        let (stale, missing) = {
+            let mut watched = self.watched.lock().map_err(|_| AppError::Lock)?;
+            let stale_ids: Vec<String> = watched
+                .iter()
+                .filter(|(id, entry)| wanted.get(id.as_str()) != Some(&entry.directory))
+                .map(|(id, _)| id.clone())
+                .collect();
+            let stale: Vec<WatchedDirectory> = stale_ids
+                .iter()
+                .filter_map(|id| watched.remove(id))
+                .collect();
+            let missing: Vec<(&str, PathBuf)> = wanted
+                .into_iter()
+                .filter(|(id, _)| !watched.contains_key(*id))
+                .collect();
+            (stale, missing)
+        };
+        for entry in stale {
+            self.registry
+                .dispose(TRIGGER_WATCH_OWNER, &entry.handle_id)?;
+        }
+
+        for (id, directory) in missing {
+            let options = FsWatcherOptions {
+                recursive: false,
+                ..Default::default()
+            };
+            match self
+                .registry
+                .create(TRIGGER_WATCH_OWNER, vec![directory.clone()], options)
+            {
+                Ok(handle_id) => {
+                    let known = entry_names(&directory);
+                    self.watched.lock().map_err(|_| AppError::Lock)?.insert(
+                        id.to_string(),
+                        WatchedDirectory {
+                            handle_id,
+                            directory,
+                            known,
+                        },
+                    );
+                }
+                Err(e) => log::warn!(
+                    "[agent-triggers] cannot watch {} for trigger {id}: {e}",
+                    directory.display()
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    /// Called for each change batch on `handle_id`: the files that appeared
+    /// since the last batch, paired with the trigger that watches them.
+    /// Hidden files and in-progress downloads are left for a later batch.
+    pub fn new_files(&self, handle_id: &str) -> Vec<(String, PathBuf)> {
+        let Ok(mut watched) = self.watched.lock() else {
+            return Vec::new();
+        };
+        let Some((trigger_id, entry)) = watched
+            .iter_mut()
+            .find(|(_, entry)| entry.handle_id == handle_id)
+        else {
+            return Vec::new();
+        };
+        let current = entry_names(&entry.directory);
+        let mut landed: Vec<(String, PathBuf)> = current
+            .iter()
+            .filter(|name| !entry.known.contains(*name) && is_settled_name(name))
+            .map(|name| entry.directory.join(name))
+            .filter(|path| path.is_file())
+            .map(|path| (trigger_id.clone(), path))
+            .collect();
+        landed.sort();
+        // Unsettled names stay unknown so they fire once they're renamed
+        // into place or finish writing under the same name.
+        entry.known = current
+            .into_iter()
+            .filter(|name| entry.known.contains(name) || is_settled_name(name))
+            .collect();
+        landed
+    }
+
+    #[cfg(test)]
+    pub(crate) fn handle_for(&self, trigger_id: &str) -> Option<String> {
+        let watched = self.watched.lock().ok()?;
+        watched.get(trigger_id).map(|entry| entry.handle_id.clone())
+    }
+}
+
+fn entry_names(directory: &Path) -> HashSet<OsString> {
+    std::fs::read_dir(directory)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.file_name())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn is_settled_name(name: &OsString) -> bool {
+    let name = name.to_string_lossy().to_lowercase();
+    !name.starts_with('.') && !PARTIAL_SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+}
+
+/// Fs-watcher emitter hook for [`TRIGGER_WATCH_OWNER`] events: fires the
+/// watching trigger once per landed file.
+pub fn on_directory_change(app: &AppHandle, handle_id: &str) {
+    let watchers = app.state::<Arc<TriggerWatchers>>();
+    let landed = watchers.new_files(handle_id);
+    if landed.is_empty() {
+        return;
+    }
+    let store = app.state::<DataStore>();
+    let result = store.conn().and_then(|conn| {
+        for (trigger_id, path) in landed {
+            if let Some(trigger) = agent_triggers::get(&conn, &trigger_id)? {
+                fire(app, &conn, &trigger, Some(&path.to_string_lossy()));
+            }
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        log::warn!("[agent-triggers] file trigger failed: {e}");
+    }
+}
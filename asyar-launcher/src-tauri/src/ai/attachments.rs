@@ -0,0 +1,447 @@
+//! Image and file attachments on chat messages: turning what the user picked
+//! (a clipboard-history image, a file from file search) into
+//! [`ContentPart`]s, enforcing size limits, and checking that the model a
+//! request goes to can actually read them.
+//!
+//! Parts are resolved to inline bytes or text before the message is stored.
+//! A clipboard row can be deleted — and its cached PNG with it — long after
+//! a thread quoted it, and a file on disk can move or change; the stored
+//! message has to keep meaning what the user sent.
+
+use crate::ai::types::{ChatMessage, ContentPart, ImageSource};
+use crate::error::AppError;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use std::io::Read;
+use std::path::Path;
+
+/// Attachments on a single message.
+pub const MAX_PARTS_PER_MESSAGE: usize = 10;
+/// Decoded image size; Anthropic rejects anything larger.
+pub const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+/// Raw size of a document read from disk, and of an inline PDF.
+pub const MAX_DOCUMENT_BYTES: usize = 10 * 1024 * 1024;
+/// Characters of extracted document text (or of an extra text part).
+pub const MAX_TEXT_CHARS: usize = 200_000;
+
+const IMAGE_MEDIA_TYPES: &[&str] = &["image/png", "image/jpeg", "image/gif", "image/webp"];
+const PDF_MEDIA_TYPE: &str = "application/pdf";
+
+/// Inlines every clipboard-cache image in `parts` and validates the result,
+/// which then holds only [`ImageSource::Bytes`] and can be stored as-is.
+pub fn resolve_parts(
+    app_data_dir: &Path,
+    parts: Vec<ContentPart>,
+) -> Result<Vec<ContentPart>, AppError> {
+    check_count(&parts)?;
+    parts
+        .into_iter()
+        .map(|part| match part {
+            ContentPart::Image {
+                media_type,
+                source: ImageSource::ClipboardCache { path },
+            } => {
+                let path = Path::new(&path);
+                if !crate::clipboard_cache::is_cached_image(app_data_dir, path) {
+                    return Err(AppError::Validation(format!(
+                        "{} is not a clipboard history image",
+                        path.display()
+                    )));
+                }
+                let bytes = std::fs::read(path).map_err(|error| {
+                    AppError::NotFound(format!(
+                        "clipboard image {} is no longer available: {error}",
+                        path.display()
+                    ))
+                })?;
+                let part = ContentPart::Image {
+                    media_type,
+                    source: ImageSource::Bytes {
+                        data: BASE64.encode(bytes),
+                    },
+                };
+                validate_part(&part)?;
+                Ok(part)
+            }
+            part => {
+                validate_part(&part)?;
+                Ok(part)
+            }
+        })
+        .collect()
+}
+
+/// Checks already-resolved parts against the limits, for messages that are
+/// stored without going through [`resolve_parts`].
+pub fn validate_parts(parts: &[ContentPart]) -> Result<(), AppError> {
+    check_count(parts)?;
+    parts.iter().try_for_each(validate_part)
+}
+
+fn check_count(parts: &[ContentPart]) -> Result<(), AppError> {
+    if parts.len() > MAX_PARTS_PER_MESSAGE {
+        return Err(AppError::Validation(format!(
+            "a message can carry at most {MAX_PARTS_PER_MESSAGE} attachments, got {}",
+            parts.len()
+        )));
+    }
+    Ok(())
+}
+
+fn validate_part(part: &ContentPart) -> Result<(), AppError> {
+    match part {
+        ContentPart::Text { text } => check_text("text attachment", text),
+        ContentPart::Image { media_type, source } => {
+            if !IMAGE_MEDIA_TYPES.contains(&media_type.as_str()) {
+                return Err(AppError::Validation(format!(
+                    "unsupported image type '{media_type}'; use PNG, JPEG, GIF or WebP"
+                )));
+            }
+            let ImageSource::Bytes { data } = source else {
+                return Err(AppError::Validation(
+                    "clipboard images must be resolved before they are stored".to_string(),
+                ));
+            };
+            let size = decoded_len(data, "image")?;
+            if size > MAX_IMAGE_BYTES {
+                return Err(AppError::Validation(format!(
+                    "image is {} and the limit is {}",
+                    format_size(size),
+                    format_size(MAX_IMAGE_BYTES)
+                )));
+            }
+            Ok(())
+        }
+        ContentPart::File {
+            name,
+            media_type,
+            text,
+            data,
+        } => match (text, data) {
+            (Some(text), None) => check_text(name, text),
+            (None, Some(data)) => {
+                if media_type != PDF_MEDIA_TYPE {
+                    return Err(AppError::Validation(format!(
+                        "{name}: only PDFs can be attached without extracted text"
+                    )));
+                }
+                let size = decoded_len(data, name)?;
+                if size > MAX_DOCUMENT_BYTES {
+                    return Err(AppError::Validation(format!(
+                        "{name} is {} and the limit is {}",
+                        format_size(size),
+                        format_size(MAX_DOCUMENT_BYTES)
+                    )));
+                }
+                Ok(())
+            }
+            _ => Err(AppError::Validation(format!(
+                "{name}: a file attachment needs either extracted text or PDF data"
+            ))),
+        },
+    }
+}
+
+fn check_text(label: &str, text: &str) -> Result<(), AppError> {
+    let chars = text.chars().count();
+    if chars > MAX_TEXT_CHARS {
+        return Err(AppError::Validation(format!(
+            "{label} has {chars} characters and the limit is {MAX_TEXT_CHARS}"
+        )));
+    }
+    Ok(())
+}
+
+fn decoded_len(data: &str, label: &str) -> Result<usize, AppError> {
+    BASE64
+        .decode(data)
+        .map(|bytes| bytes.len())
+        .map_err(|error| AppError::Validation(format!("{label} is not valid base64: {error}")))
+}
+
+fn format_size(bytes: usize) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Builds the part for a file the user attached from disk: images become
+/// image parts, PDFs are sent as documents, Word/OpenDocument text and
+/// anything that decodes as UTF-8 is sent as extracted text.
+pub fn file_part(path: &Path) -> Result<ContentPart, AppError> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+    let size = std::fs::metadata(path)
+        .map_err(|error| AppError::NotFound(format!("{}: {error}", path.display())))?
+        .len() as usize;
+    if size > MAX_DOCUMENT_BYTES {
+        return Err(AppError::Validation(format!(
+            "{name} is {} and the limit is {}",
+            format_size(size),
+            format_size(MAX_DOCUMENT_BYTES)
+        )));
+    }
+    let bytes = std::fs::read(path)
+        .map_err(|error| AppError::Other(format!("{}: {error}", path.display())))?;
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+
+    let image_type = match extension.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    };
+    let part = if let Some(media_type) = image_type {
+        ContentPart::Image {
+            media_type: media_type.to_string(),
+            source: ImageSource::Bytes {
+                data: BASE64.encode(&bytes),
+            },
+        }
+    } else {
+        let (media_type, text, data) = match extension.as_str() {
+            "pdf" => (PDF_MEDIA_TYPE, None, Some(BASE64.encode(&bytes))),
+            "docx" => (
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+                Some(office_text(&bytes, "word/document.xml", &name)?),
+                None,
+            ),
+            "odt" => (
+                "application/vnd.oasis.opendocument.text",
+                Some(office_text(&bytes, "content.xml", &name)?),
+                None,
+            ),
+            _ => match String::from_utf8(bytes) {
+                Ok(text) if !text.contains('\0') => ("text/plain", Some(text), None),
+                _ => {
+                    return Err(AppError::Validation(format!(
+                    "can't extract text from {name}; attach a text, Word, OpenDocument or PDF file"
+                )))
+                }
+            },
+        };
+        ContentPart::File {
+            name,
+            media_type: media_type.to_string(),
+            text,
+            data,
+        }
+    };
+    validate_part(&part)?;
+    Ok(part)
+}
+
+/// Text of a zipped office document's main XML entry, one line per
+/// paragraph. Formatting, tables and embedded objects are dropped.
+fn office_text(bytes: &[u8], entry: &str, name: &str) -> Result<String, AppError> {
+    let unreadable =
+        |error: &dyn std::fmt::Display| AppError::Validation(format!("can't read {name}: {error}"));
+    let mut archive =
+        zip::ZipArchive::new(std::io::Cursor::new(bytes)).map_err(|e| unreadable(&e))?;
+    let mut xml = String::new();
+    archive
+        .by_name(entry)
+        .map_err(|e| unreadable(&e))?
+        .read_to_string(&mut xml)
+        .map_err(|e| unreadable(&e))?;
+    Ok(xml_text(&xml))
+}
+
+fn xml_text(xml: &str) -> String {
+    let paragraph_end = regex::Regex::new(r"</(?:w:p|text:p|text:h)>").expect("valid regex");
+    let tag = regex::Regex::new(r"<[^>]*>").expect("valid regex");
+    let text = paragraph_end.replace_all(xml, "\n");
+    tag.replace_all(&text, "")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+/// Fails with a message the user can act on when `messages` carry
+/// attachments the target can't read, instead of letting the provider
+/// reject the request with something opaque.
+pub fn ensure_supported(
+    engine: &str,
+    model_id: &str,
+    messages: &[ChatMessage],
+) -> Result<(), AppError> {
+    let parts = || {
+        messages
+            .iter()
+            .flat_map(|m| m.parts.as_deref().unwrap_or_default())
+    };
+    if parts().any(|part| matches!(part, ContentPart::Image { .. }))
+        && crate::ai::models::supports_vision(engine, model_id) == Some(false)
+    {
+        return Err(AppError::Validation(format!(
+            "{model_id} can't read images; switch to a vision-capable model or remove the image"
+        )));
+    }
+    if engine == "ollama"
+        && parts().any(|part| matches!(part, ContentPart::File { data: Some(_), .. }))
+    {
+        return Err(AppError::Validation(
+            "Ollama can't read PDF attachments; attach the document as text instead".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// `data:` URL for an inline attachment, the form OpenAI-compatible APIs
+/// take images and files in.
+pub(crate) fn data_url(media_type: &str, data: &str) -> String {
+    format!("data:{media_type};base64,{data}")
+}
+
+/// How a document with extracted text is spliced into a prompt on engines
+/// that have no native text-document block.
+pub(crate) fn document_text(name: &str, text: &str) -> String {
+    format!("<document name=\"{name}\">\n{text}\n</document>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn temp_dir(tag: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "asyar_attachments_{tag}_{}_{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn image(data: &[u8]) -> ContentPart {
+        ContentPart::Image {
+            media_type: "image/png".to_string(),
+            source: ImageSource::Bytes {
+                data: BASE64.encode(data),
+            },
+        }
+    }
+
+    #[test]
+    fn clipboard_images_are_inlined_from_the_cache_only() {
+        let root = temp_dir("clip");
+        let cached = crate::clipboard_cache::cached_image_path(&root, "item-1").unwrap();
+        std::fs::create_dir_all(cached.parent().unwrap()).unwrap();
+        std::fs::write(&cached, b"png-bytes").unwrap();
+        let clipboard = |path: &Path| ContentPart::Image {
+            media_type: "image/png".to_string(),
+            source: ImageSource::ClipboardCache {
+                path: path.display().to_string(),
+            },
+        };
+
+        let resolved = resolve_parts(&root, vec![clipboard(&cached)]).unwrap();
+        assert_eq!(resolved, vec![image(b"png-bytes")]);
+
+        let outside = root.join("secret.png");
+        std::fs::write(&outside, b"nope").unwrap();
+        let error = resolve_parts(&root, vec![clipboard(&outside)]).unwrap_err();
+        assert!(matches!(error, AppError::Validation(_)));
+    }
+
+    #[test]
+    fn limits_are_enforced() {
+        let root = temp_dir("limits");
+        let too_many = vec![ContentPart::Text { text: "x".into() }; MAX_PARTS_PER_MESSAGE + 1];
+        assert!(resolve_parts(&root, too_many).is_err());
+
+        let huge = image(&vec![0u8; MAX_IMAGE_BYTES + 1]);
+        let message = resolve_parts(&root, vec![huge]).unwrap_err().to_string();
+        assert!(message.contains("limit is 5.0 MB"), "{message}");
+
+        let bmp = ContentPart::Image {
+            media_type: "image/bmp".to_string(),
+            source: ImageSource::Bytes {
+                data: BASE64.encode(b"x"),
+            },
+        };
+        assert!(resolve_parts(&root, vec![bmp]).is_err());
+    }
+
+    #[test]
+    fn files_become_text_pdf_or_image_parts() {
+        let dir = temp_dir("files");
+        let notes = dir.join("notes.md");
+        std::fs::write(&notes, "# Plan\nship it").unwrap();
+        assert!(matches!(
+            file_part(&notes).unwrap(),
+            ContentPart::File { text: Some(text), data: None, .. } if text == "# Plan\nship it"
+        ));
+
+        let pdf = dir.join("report.pdf");
+        std::fs::write(&pdf, b"%PDF-1.7").unwrap();
+        assert!(matches!(
+            file_part(&pdf).unwrap(),
+            ContentPart::File { media_type, data: Some(_), .. } if media_type == PDF_MEDIA_TYPE
+        ));
+
+        let shot = dir.join("shot.PNG");
+        std::fs::write(&shot, b"png").unwrap();
+        assert_eq!(file_part(&shot).unwrap(), image(b"png"));
+
+        let binary = dir.join("blob.bin");
+        std::fs::write(&binary, [0xff, 0x00, 0xfe]).unwrap();
+        assert!(file_part(&binary).is_err());
+    }
+
+    #[test]
+    fn docx_text_is_extracted_per_paragraph() {
+        let dir = temp_dir("docx");
+        let path = dir.join("memo.docx");
+        let mut writer = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        writer
+            .start_file(
+                "word/document.xml",
+                zip::write::SimpleFileOptions::default(),
+            )
+            .unwrap();
+        writer
+            .write_all(
+                b"<w:document><w:body><w:p><w:r><w:t>Q3 &amp; Q4</w:t></w:r></w:p>\
+                  <w:p><w:r><w:t>Budget</w:t></w:r></w:p></w:body></w:document>",
+            )
+            .unwrap();
+        writer.finish().unwrap();
+
+        assert!(matches!(
+            file_part(&path).unwrap(),
+            ContentPart::File { text: Some(text), .. } if text == "Q3 & Q4\nBudget"
+        ));
+    }
+
+    #[test]
+    fn text_only_models_reject_images_with_a_clear_error() {
+        let message = ChatMessage {
+            id: "1".to_string(),
+            role: "user".to_string(),
+            content: "what is this?".to_string(),
+            timestamp: 0,
+            tool_calls: None,
+            tool_call_id: None,
+            provider_context: None,
+            parts: Some(vec![image(b"png")]),
+        };
+        let error = ensure_supported("openai", "gpt-3.5-turbo", std::slice::from_ref(&message))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("gpt-3.5-turbo can't read images"), "{error}");
+        assert!(ensure_supported("openai", "gpt-4o", &[message]).is_ok());
+    }
+}
@@ -1,8 +1,12 @@
 use crate::ai::providers;
+use crate::ai::routing::{classify_status, parse_retry_after, AttemptOutcome};
 use crate::ai::sse::LineBuffer;
-use crate::ai::types::{ChatMessage, ChatParams, ChatStreamEventPayload, ProviderConfig};
+use crate::ai::types::{
+    ChatMessage, ChatParams, ChatStreamEventPayload, ProviderConfig, RequestSpec,
+};
 use crate::error::AppError;
 use futures_util::StreamExt;
+use std::time::Duration;
 
 fn emit_stream_event<F>(event: crate::ai::types::ChatStreamEvent, on_event: &F)
 where
@@ -21,27 +25,52 @@ where
         crate::ai::types::ChatStreamEvent::ProviderContext { item } => {
             on_event(ChatStreamEventPayload::ProviderContext { item });
         }
+        crate::ai::types::ChatStreamEvent::Usage { usage } => {
+            on_event(ChatStreamEventPayload::Usage { usage });
+        }
     }
 }
 
-pub async fn ai_stream_chat_impl<F>(
-    provider_id: &str,
-    config: ProviderConfig,
-    messages: Vec<ChatMessage>,
-    params: ChatParams,
-    _stream_id: String,
-    on_event: F,
-) -> Result<(), AppError>
-where
-    F: Fn(ChatStreamEventPayload) + Send + Sync + 'static,
-{
-    let spec = providers::build_request(provider_id, &config, &messages, &params)?;
+/// A provider request that failed before any of its response was streamed —
+/// the only point at which it is safe to retry it or send it elsewhere.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestFailure {
+    pub outcome: AttemptOutcome,
+    pub status: Option<u16>,
+    pub retry_after: Option<Duration>,
+    pub message: String,
+}
+
+impl RequestFailure {
+    fn from_transport(error: reqwest::Error) -> Self {
+        let outcome = if error.is_connect() {
+            AttemptOutcome::Offline
+        } else if error.is_timeout() {
+            AttemptOutcome::Timeout
+        } else {
+            AttemptOutcome::Failed
+        };
+        Self {
+            outcome,
+            status: None,
+            retry_after: None,
+            message: error.to_string(),
+        }
+    }
+}
 
+/// Send one built request and return the response once its status says it
+/// will stream. Emits nothing: the caller decides whether a failure is
+/// final.
+pub async fn send_chat_request(
+    spec: RequestSpec,
+    hosted_web_search: bool,
+) -> Result<reqwest::Response, RequestFailure> {
     let client = reqwest::Client::new();
-    let timeout = if config.hosted_web_search == Some(true) {
-        std::time::Duration::from_secs(120)
+    let timeout = if hosted_web_search {
+        Duration::from_secs(120)
     } else {
-        std::time::Duration::from_secs(30)
+        Duration::from_secs(30)
     };
 
     let mut req_builder = client.post(&spec.url);
@@ -49,33 +78,48 @@ where
         req_builder = req_builder.header(k, v);
     }
 
-    let response = req_builder.json(&spec.body).timeout(timeout).send().await;
-
-    let res = match response {
-        Ok(r) => r,
-        Err(e) => {
-            on_event(ChatStreamEventPayload::Error {
-                error: e.to_string(),
-            });
-            return Err(AppError::Other(e.to_string()));
-        }
-    };
+    let res = req_builder
+        .json(&spec.body)
+        .timeout(timeout)
+        .send()
+        .await
+        .map_err(RequestFailure::from_transport)?;
 
     if !res.status().is_success() {
         let status = res.status();
+        let retry_after = res
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
         let err_body = res
             .text()
             .await
             .unwrap_or_else(|_| format!("HTTP {}", status));
-        on_event(ChatStreamEventPayload::Error {
-            error: err_body.clone(),
+        return Err(RequestFailure {
+            outcome: classify_status(status.as_u16()),
+            status: Some(status.as_u16()),
+            retry_after,
+            message: err_body,
         });
-        return Err(AppError::Other(err_body));
     }
+    Ok(res)
+}
 
+/// Parse a successful response to the end, emitting events as they arrive
+/// and `Done` once the provider has finished.
+pub async fn stream_chat_response<F>(
+    res: reqwest::Response,
+    provider_id: &str,
+    config: &ProviderConfig,
+    on_event: F,
+) -> Result<(), AppError>
+where
+    F: Fn(ChatStreamEventPayload) + Send + Sync + 'static,
+{
     let mut stream = res.bytes_stream();
     let mut line_buffer = LineBuffer::new();
-    let mut parser = providers::ProviderStreamParser::new(provider_id, &config);
+    let mut parser = providers::ProviderStreamParser::new(provider_id, config);
 
     while let Some(chunk_result) = stream.next().await {
         let chunk = match chunk_result {
@@ -110,6 +154,32 @@ where
     Ok(())
 }
 
+pub async fn ai_stream_chat_impl<F>(
+    provider_id: &str,
+    config: ProviderConfig,
+    messages: Vec<ChatMessage>,
+    params: ChatParams,
+    _stream_id: String,
+    on_event: F,
+) -> Result<(), AppError>
+where
+    F: Fn(ChatStreamEventPayload) + Send + Sync + 'static,
+{
+    let spec = providers::build_request(provider_id, &config, &messages, &params)?;
+
+    let res = match send_chat_request(spec, config.hosted_web_search == Some(true)).await {
+        Ok(r) => r,
+        Err(failure) => {
+            on_event(ChatStreamEventPayload::Error {
+                error: failure.message.clone(),
+            });
+            return Err(AppError::Other(failure.message));
+        }
+    };
+
+    stream_chat_response(res, provider_id, &config, on_event).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -167,6 +237,7 @@ mod tests {
                 tool_calls: None,
                 tool_call_id: None,
                 provider_context: None,
+                parts: None,
             }],
             ChatParams {
                 model_id: "test".to_string(),
@@ -174,6 +245,7 @@ mod tests {
                 max_tokens: 100,
                 system_prompt: None,
                 tools: None,
+                response_schema: None,
             },
             "stream-123".to_string(),
             move |ev| {
@@ -194,4 +266,53 @@ mod tests {
             _ => panic!("Expected done event"),
         }
     }
+
+    #[tokio::test]
+    async fn test_send_chat_request_classifies_rate_limit_with_retry_after() {
+        use tokio::io::AsyncWriteExt;
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            if let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = [0; 1024];
+                let _ = tokio::io::AsyncReadExt::read(&mut socket, &mut buf).await;
+                let response = "HTTP/1.1 429 Too Many Requests\r\n\
+                                Retry-After: 7\r\n\
+                                Content-Length: 4\r\n\r\n\
+                                slow";
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let spec = RequestSpec {
+            url: format!("http://{addr}/v1/chat/completions"),
+            headers: Default::default(),
+            body: serde_json::json!({}),
+        };
+        let failure = send_chat_request(spec, false).await.unwrap_err();
+
+        assert_eq!(failure.outcome, AttemptOutcome::RateLimited);
+        assert_eq!(failure.status, Some(429));
+        assert_eq!(failure.retry_after, Some(Duration::from_secs(7)));
+        assert_eq!(failure.message, "slow");
+    }
+
+    #[tokio::test]
+    async fn test_send_chat_request_reports_refused_connection_as_offline() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let spec = RequestSpec {
+            url: format!("http://{addr}/v1/chat/completions"),
+            headers: Default::default(),
+            body: serde_json::json!({}),
+        };
+        let failure = send_chat_request(spec, false).await.unwrap_err();
+
+        assert_eq!(failure.outcome, AttemptOutcome::Offline);
+        assert_eq!(failure.status, None);
+    }
 }
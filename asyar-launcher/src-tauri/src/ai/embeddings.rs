@@ -0,0 +1,378 @@
+//! Embedding requests for the local semantic index.
+//!
+//! Chat goes through `providers::build_request`; embeddings need a different
+//! endpoint and response shape per engine, so they get their own builder and
+//! parser here. Ollama (`/api/embed`) and OpenAI-compatible servers
+//! (`/embeddings`) cover the local-first default; Gemini is supported through
+//! `batchEmbedContents`. Anthropic has no embeddings endpoint and is rejected
+//! up front rather than failing at request time.
+
+use crate::ai::types::{ProviderConfig, RequestSpec};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Model used when the user has not picked an embedding provider. Small,
+/// multilingual-enough, and a one-line `ollama pull` away.
+pub const DEFAULT_OLLAMA_EMBEDDING_MODEL: &str = "nomic-embed-text";
+
+const DEFAULT_OLLAMA_BASE_URL: &str = "http://localhost:11434";
+
+/// Inputs longer than this are cut before embedding. Most local embedding
+/// models have a 2k–8k token window; characters are a cheap upper bound.
+pub const MAX_INPUT_CHARS: usize = 8_000;
+
+/// Which provider and model produce vectors for the semantic index. The
+/// frontend owns `settings.ai.providers` and pushes the resolved config down
+/// (see `services/ai/semanticIndexSync.svelte.ts`), the same way agent runs
+/// receive their provider configs as data.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddingSettings {
+    pub provider_id: String,
+    pub model_id: String,
+    pub config: ProviderConfig,
+}
+
+impl EmbeddingSettings {
+    /// A stock Ollama install on the default port.
+    pub fn local_default() -> Self {
+        Self {
+            provider_id: "ollama".to_string(),
+            model_id: DEFAULT_OLLAMA_EMBEDDING_MODEL.to_string(),
+            config: ProviderConfig {
+                enabled: true,
+                name: None,
+                provider_type: None,
+                api_key: None,
+                base_url: Some(DEFAULT_OLLAMA_BASE_URL.to_string()),
+                last_model_id: None,
+                open_ai_api_mode: None,
+                hosted_web_search: None,
+                reasoning_effort: None,
+                temperature: None,
+                max_tokens: None,
+            },
+        }
+    }
+
+    /// Key stored next to every vector. Vectors from different models live in
+    /// different spaces, so switching model invalidates the whole index.
+    pub fn model_key(&self) -> String {
+        format!("{}:{}", self.engine(), self.model_id)
+    }
+
+    fn engine(&self) -> &str {
+        self.config
+            .provider_type
+            .as_deref()
+            .unwrap_or(&self.provider_id)
+    }
+}
+
+fn truncate_input(input: &str) -> String {
+    input.chars().take(MAX_INPUT_CHARS).collect()
+}
+
+pub fn build_embedding_request(
+    settings: &EmbeddingSettings,
+    inputs: &[String],
+) -> Result<RequestSpec, AppError> {
+    if settings.model_id.trim().is_empty() {
+        return Err(AppError::Validation(
+            "embedding model must not be empty".to_string(),
+        ));
+    }
+    let config = &settings.config;
+    let inputs: Vec<String> = inputs.iter().map(|s| truncate_input(s)).collect();
+    let mut headers = HashMap::new();
+    headers.insert("Content-Type".to_string(), "application/json".to_string());
+
+    match settings.engine() {
+        "ollama" => {
+            let base_url = config
+                .base_url
+                .as_deref()
+                .unwrap_or(DEFAULT_OLLAMA_BASE_URL);
+            Ok(RequestSpec {
+                url: format!("{}/api/embed", base_url.trim_end_matches('/')),
+                headers,
+                body: json!({ "model": settings.model_id, "input": inputs }),
+            })
+        }
+        engine @ ("openai" | "openrouter" | "custom") => {
+            let base_url = match engine {
+                "openai" => config
+                    .base_url
+                    .as_deref()
+                    .unwrap_or("https://api.openai.com/v1"),
+                "openrouter" => "https://openrouter.ai/api/v1",
+                _ => config.base_url.as_deref().unwrap_or(""),
+            };
+            if base_url.trim().is_empty() {
+                return Err(AppError::Validation(
+                    "Custom provider base URL must not be empty".to_string(),
+                ));
+            }
+            if let Some(key) = config.api_key.as_deref().filter(|k| !k.trim().is_empty()) {
+                headers.insert("Authorization".to_string(), format!("Bearer {key}"));
+            }
+            Ok(RequestSpec {
+                url: format!("{}/embeddings", base_url.trim_end_matches('/')),
+                headers,
+                body: json!({
+                    "model": settings.model_id,
+                    "input": inputs,
+                    "encoding_format": "float",
+                }),
+            })
+        }
+        "google" => {
+            headers.insert(
+                "x-goog-api-key".to_string(),
+                config.api_key.clone().unwrap_or_default(),
+            );
+            let model = format!("models/{}", settings.model_id);
+            Ok(RequestSpec {
+                url: format!(
+                    "https://generativelanguage.googleapis.com/v1beta/{model}:batchEmbedContents"
+                ),
+                headers,
+                body: json!({
+                    "requests": inputs
+                        .iter()
+                        .map(|text| json!({ "model": model, "content": { "parts": [{ "text": text }] } }))
+                        .collect::<Vec<_>>(),
+                }),
+            })
+        }
+        "anthropic" => Err(AppError::Validation(
+            "Anthropic has no embeddings endpoint; pick Ollama, OpenAI, Gemini or an \
+             OpenAI-compatible server for semantic search"
+                .to_string(),
+        )),
+        other => Err(AppError::Other(format!("Unknown provider: {other}"))),
+    }
+}
+
+fn as_vector(value: &Value) -> Option<Vec<f32>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|v| v.as_f64().map(|f| f as f32))
+        .collect()
+}
+
+/// Extract one vector per input, in input order. Rejects responses whose
+/// count or dimensions don't line up — a silently misaligned batch would
+/// attach the wrong vector to a document.
+pub fn parse_embedding_response(
+    settings: &EmbeddingSettings,
+    body: &Value,
+    expected: usize,
+) -> Result<Vec<Vec<f32>>, AppError> {
+    let malformed = || AppError::Other("malformed embeddings response".to_string());
+    let vectors: Vec<Vec<f32>> = match settings.engine() {
+        "ollama" => body
+            .get("embeddings")
+            .and_then(Value::as_array)
+            .ok_or_else(malformed)?
+            .iter()
+            .map(as_vector)
+            .collect::<Option<_>>()
+            .ok_or_else(malformed)?,
+        "google" => body
+            .get("embeddings")
+            .and_then(Value::as_array)
+            .ok_or_else(malformed)?
+            .iter()
+            .map(|e| e.get("values").and_then(as_vector))
+            .collect::<Option<_>>()
+            .ok_or_else(malformed)?,
+        _ => {
+            let mut indexed: Vec<(u64, Vec<f32>)> = body
+                .get("data")
+                .and_then(Value::as_array)
+                .ok_or_else(malformed)?
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    let index = item
+                        .get("index")
+                        .and_then(Value::as_u64)
+                        .unwrap_or(i as u64);
+                    item.get("embedding")
+                        .and_then(as_vector)
+                        .map(|v| (index, v))
+                })
+                .collect::<Option<_>>()
+                .ok_or_else(malformed)?;
+            indexed.sort_by_key(|(index, _)| *index);
+            indexed.into_iter().map(|(_, v)| v).collect()
+        }
+    };
+
+    if vectors.len() != expected {
+        return Err(AppError::Other(format!(
+            "embeddings response returned {} vectors for {expected} inputs",
+            vectors.len()
+        )));
+    }
+    let dims = vectors.first().map(Vec::len).unwrap_or(0);
+    if vectors.iter().any(|v| v.is_empty() || v.len() != dims) {
+        return Err(AppError::Other(
+            "embeddings response has inconsistent dimensions".to_string(),
+        ));
+    }
+    Ok(vectors)
+}
+
+/// Embed `inputs` in one request. Callers batch; this does not split.
+pub async fn embed(
+    settings: &EmbeddingSettings,
+    inputs: &[String],
+) -> Result<Vec<Vec<f32>>, AppError> {
+    if inputs.is_empty() {
+        return Ok(Vec::new());
+    }
+    let spec = build_embedding_request(settings, inputs)?;
+    let mut request = reqwest::Client::new()
+        .post(&spec.url)
+        .timeout(std::time::Duration::from_secs(60));
+    for (k, v) in spec.headers {
+        request = request.header(k, v);
+    }
+    let response = request.json(&spec.body).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| format!("HTTP {status}"));
+        return Err(AppError::Other(format!(
+            "embeddings request failed ({status}): {body}"
+        )));
+    }
+    let body: Value = response.json().await?;
+    parse_embedding_response(settings, &body, inputs.len())
+}
+
+/// Scale `v` to unit length in place so cosine similarity reduces to a dot
+/// product at query time. Zero vectors are left untouched.
+pub fn normalize(v: &mut [f32]) {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > f32::EPSILON {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(provider_id: &str, base_url: Option<&str>) -> EmbeddingSettings {
+        let mut s = EmbeddingSettings::local_default();
+        s.provider_id = provider_id.to_string();
+        s.model_id = "embed-model".to_string();
+        s.config.base_url = base_url.map(str::to_string);
+        s.config.api_key = Some("sk-test".to_string());
+        s
+    }
+
+    #[test]
+    fn ollama_request_targets_api_embed_without_auth() {
+        let spec =
+            build_embedding_request(&EmbeddingSettings::local_default(), &["hello".to_string()])
+                .unwrap();
+        assert_eq!(spec.url, "http://localhost:11434/api/embed");
+        assert_eq!(spec.body["model"], DEFAULT_OLLAMA_EMBEDDING_MODEL);
+        assert_eq!(spec.body["input"], json!(["hello"]));
+        assert!(!spec.headers.contains_key("Authorization"));
+    }
+
+    #[test]
+    fn openai_compatible_request_uses_embeddings_path_and_bearer() {
+        let spec = build_embedding_request(
+            &settings("custom", Some("http://127.0.0.1:1234/v1/")),
+            &["a".to_string(), "b".to_string()],
+        )
+        .unwrap();
+        assert_eq!(spec.url, "http://127.0.0.1:1234/v1/embeddings");
+        assert_eq!(spec.headers["Authorization"], "Bearer sk-test");
+        assert_eq!(spec.body["input"], json!(["a", "b"]));
+    }
+
+    #[test]
+    fn custom_without_base_url_and_anthropic_are_rejected() {
+        assert!(build_embedding_request(&settings("custom", None), &["a".into()]).is_err());
+        let err = build_embedding_request(&settings("anthropic", None), &["a".into()]).unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+    }
+
+    #[test]
+    fn long_inputs_are_truncated() {
+        let long = "x".repeat(MAX_INPUT_CHARS + 50);
+        let spec = build_embedding_request(&EmbeddingSettings::local_default(), &[long]).unwrap();
+        assert_eq!(
+            spec.body["input"][0].as_str().unwrap().len(),
+            MAX_INPUT_CHARS
+        );
+    }
+
+    #[test]
+    fn parses_each_engine_shape_in_input_order() {
+        let ollama = json!({ "embeddings": [[1.0, 0.0], [0.0, 1.0]] });
+        assert_eq!(
+            parse_embedding_response(&EmbeddingSettings::local_default(), &ollama, 2).unwrap(),
+            vec![vec![1.0, 0.0], vec![0.0, 1.0]]
+        );
+
+        let openai = json!({ "data": [
+            { "index": 1, "embedding": [0.0, 1.0] },
+            { "index": 0, "embedding": [1.0, 0.0] },
+        ]});
+        assert_eq!(
+            parse_embedding_response(&settings("openai", None), &openai, 2).unwrap()[0],
+            vec![1.0, 0.0]
+        );
+
+        let google = json!({ "embeddings": [{ "values": [0.5, 0.5] }] });
+        assert_eq!(
+            parse_embedding_response(&settings("google", None), &google, 1).unwrap(),
+            vec![vec![0.5, 0.5]]
+        );
+    }
+
+    #[test]
+    fn rejects_count_and_dimension_mismatches() {
+        let s = EmbeddingSettings::local_default();
+        assert!(parse_embedding_response(&s, &json!({ "embeddings": [[1.0]] }), 2).is_err());
+        assert!(
+            parse_embedding_response(&s, &json!({ "embeddings": [[1.0], [1.0, 2.0]] }), 2).is_err()
+        );
+        assert!(parse_embedding_response(&s, &json!({ "error": "no" }), 1).is_err());
+    }
+
+    #[test]
+    fn model_key_includes_engine() {
+        assert_eq!(
+            EmbeddingSettings::local_default().model_key(),
+            "ollama:nomic-embed-text"
+        );
+    }
+
+    #[test]
+    fn normalize_gives_unit_dot_product() {
+        let mut v = vec![3.0, 4.0];
+        normalize(&mut v);
+        assert!((dot(&v, &v) - 1.0).abs() < 1e-6);
+        let mut zero = vec![0.0, 0.0];
+        normalize(&mut zero);
+        assert_eq!(zero, vec![0.0, 0.0]);
+    }
+}
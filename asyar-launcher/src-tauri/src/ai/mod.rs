@@ -1,7 +1,13 @@
+pub mod attachments;
 pub mod commands;
+pub mod embeddings;
 pub mod models;
+pub mod pricing;
 pub mod providers;
+pub mod routing;
+pub mod semantic_index;
 pub mod sse;
+pub mod structured;
 pub mod types;
 
 #[cfg(test)]
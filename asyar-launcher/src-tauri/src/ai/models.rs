@@ -232,6 +232,93 @@ fn google_reasoning_efforts(model_id: &str) -> Option<Vec<String>> {
     }
 }
 
+/// Whether `model_id` accepts image input: `Some(false)` only for models
+/// known to be text-only, `None` when the engine gives no way to tell (a
+/// custom endpoint, an Ollama model outside the known vision families), in
+/// which case the request is sent and the provider has the final say.
+pub fn supports_vision(engine: &str, model_id: &str) -> Option<bool> {
+    let id = model_id.to_ascii_lowercase();
+    match engine {
+        "openai" => {
+            const TEXT_ONLY: &[&str] = &["gpt-3.5", "gpt-4-0", "gpt-4-32k", "o1-mini", "o3-mini"];
+            Some(id != "gpt-4" && !TEXT_ONLY.iter().any(|prefix| id.starts_with(prefix)))
+        }
+        "anthropic" => Some(!id.starts_with("claude-2") && !id.starts_with("claude-instant")),
+        "google" => Some(id.starts_with("gemini")),
+        "ollama" => {
+            const VISION_FAMILIES: &[&str] = &[
+                "llava",
+                "bakllava",
+                "moondream",
+                "minicpm-v",
+                "llama3.2-vision",
+                "llama4",
+                "gemma3",
+                "qwen2.5vl",
+                "qwen3-vl",
+                "granite3.2-vision",
+                "mistral-small3.1",
+            ];
+            VISION_FAMILIES
+                .iter()
+                .any(|family| id.starts_with(family))
+                .then_some(true)
+        }
+        _ => None,
+    }
+}
+
+/// Window assumed for models missing from [`CONTEXT_WINDOWS`]: small
+/// enough that an unknown model is compacted early rather than overflowed.
+pub const DEFAULT_CONTEXT_WINDOW: usize = 8_192;
+
+/// Ollama truncates prompts to its `num_ctx`, which requests don't raise,
+/// whatever the model itself supports.
+const OLLAMA_CONTEXT_WINDOW: usize = 4_096;
+
+/// `(model id prefix, context window in tokens)`; the longest matching
+/// prefix wins, as in `ai::pricing`.
+const CONTEXT_WINDOWS: &[(&str, usize)] = &[
+    // OpenAI
+    ("gpt-5", 400_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo", 16_385),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4-mini", 200_000),
+    // Anthropic
+    ("claude", 200_000),
+    ("claude-2", 100_000),
+    // Google
+    ("gemini-1.5-pro", 2_097_152),
+    ("gemini-1.5-flash", 1_048_576),
+    ("gemini-2", 1_048_576),
+    ("gemini-3", 1_048_576),
+    // Open-weight families served by OpenRouter or custom endpoints
+    ("llama-3", 131_072),
+    ("llama3", 131_072),
+    ("mistral", 32_768),
+    ("qwen", 32_768),
+    ("deepseek", 65_536),
+];
+
+/// How many tokens `model_id` accepts per request, prompt and answer
+/// together.
+pub fn context_window(engine: &str, model_id: &str) -> usize {
+    if engine == "ollama" {
+        return OLLAMA_CONTEXT_WINDOW;
+    }
+    let id = crate::ai::pricing::normalize_model_id(model_id).to_ascii_lowercase();
+    CONTEXT_WINDOWS
+        .iter()
+        .filter(|(prefix, _)| id.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map_or(DEFAULT_CONTEXT_WINDOW, |(_, window)| *window)
+}
+
 fn model(id: &str, label: Option<&str>, reasoning_efforts: Option<Vec<String>>) -> ModelInfo {
     ModelInfo {
         id: id.to_owned(),
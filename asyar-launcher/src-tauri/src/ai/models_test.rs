@@ -1,6 +1,6 @@
 use super::models::{
-    list_models_impl, ollama_reasoning_efforts_from_capabilities, parse_ollama_show_capabilities,
-    parse_provider_models, provider_model_request,
+    context_window, list_models_impl, ollama_reasoning_efforts_from_capabilities,
+    parse_ollama_show_capabilities, parse_provider_models, provider_model_request,
 };
 use crate::ai::types::ProviderConfig;
 use serde_json::json;
@@ -225,3 +225,19 @@ async fn list_models_impl_only_offers_reasoning_effort_for_thinking_capable_olla
         Some(vec!["low".into(), "medium".into(), "high".into()])
     );
 }
+
+#[test]
+fn context_windows_match_the_longest_prefix_and_default_when_unknown() {
+    assert_eq!(context_window("openai", "gpt-4o-mini-2024-07-18"), 128_000);
+    assert_eq!(context_window("openai", "gpt-4-0613"), 8_192);
+    assert_eq!(
+        context_window("openrouter", "anthropic/claude-sonnet-4"),
+        200_000
+    );
+    assert_eq!(context_window("google", "models/gemini-2.5-pro"), 1_048_576);
+    assert_eq!(context_window("ollama", "llama3.1:70b"), 4_096);
+    assert_eq!(
+        context_window("custom", "my-finetune"),
+        super::models::DEFAULT_CONTEXT_WINDOW
+    );
+}
@@ -0,0 +1,147 @@
+//! Local list-price table for turning [`TokenUsage`] into an estimated cost.
+//!
+//! Prices are USD per million tokens as published by each vendor and are
+//! deliberately kept in-tree rather than fetched: spend reports must work
+//! offline and be reproducible. Models are matched by longest id prefix so
+//! dated snapshots (`gpt-4o-2024-08-06`, `claude-sonnet-4-20250514`) pick up
+//! their family's price. An unknown model yields `None` — reported as
+//! "unpriced", never guessed.
+
+use crate::ai::types::TokenUsage;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelPrice {
+    pub input: f64,
+    /// Price for prompt tokens served from the provider's cache.
+    pub cached_input: f64,
+    pub output: f64,
+}
+
+const fn price(input: f64, cached_input: f64, output: f64) -> ModelPrice {
+    ModelPrice {
+        input,
+        cached_input,
+        output,
+    }
+}
+
+/// `(model id prefix, price)`. Order doesn't matter — lookup takes the
+/// longest matching prefix.
+const PRICES: &[(&str, ModelPrice)] = &[
+    // OpenAI
+    ("gpt-5", price(1.25, 0.125, 10.0)),
+    ("gpt-5-mini", price(0.25, 0.025, 2.0)),
+    ("gpt-5-nano", price(0.05, 0.005, 0.4)),
+    ("gpt-4.1", price(2.0, 0.5, 8.0)),
+    ("gpt-4.1-mini", price(0.4, 0.1, 1.6)),
+    ("gpt-4.1-nano", price(0.1, 0.025, 0.4)),
+    ("gpt-4o", price(2.5, 1.25, 10.0)),
+    ("gpt-4o-mini", price(0.15, 0.075, 0.6)),
+    ("o3", price(2.0, 0.5, 8.0)),
+    ("o4-mini", price(1.1, 0.275, 4.4)),
+    // Anthropic
+    ("claude-opus-4", price(15.0, 1.5, 75.0)),
+    ("claude-opus-4-5", price(5.0, 0.5, 25.0)),
+    ("claude-sonnet-4", price(3.0, 0.3, 15.0)),
+    ("claude-3-7-sonnet", price(3.0, 0.3, 15.0)),
+    ("claude-3-5-sonnet", price(3.0, 0.3, 15.0)),
+    ("claude-haiku-4-5", price(1.0, 0.1, 5.0)),
+    ("claude-3-5-haiku", price(0.8, 0.08, 4.0)),
+    // Google
+    ("gemini-2.5-pro", price(1.25, 0.31, 10.0)),
+    ("gemini-2.5-flash", price(0.3, 0.075, 2.5)),
+    ("gemini-2.5-flash-lite", price(0.1, 0.025, 0.4)),
+    ("gemini-2.0-flash", price(0.1, 0.025, 0.4)),
+];
+
+/// Strip routing decorations so OpenRouter's `openai/gpt-4o` and Gemini's
+/// `models/gemini-2.5-pro` look up the same row as the bare id.
+pub(crate) fn normalize_model_id(model_id: &str) -> &str {
+    let model_id = model_id.trim();
+    model_id.rsplit('/').next().unwrap_or(model_id)
+}
+
+pub fn price_for(engine: &str, model_id: &str) -> Option<ModelPrice> {
+    if engine == "ollama" {
+        return Some(price(0.0, 0.0, 0.0));
+    }
+    let model_id = normalize_model_id(model_id);
+    PRICES
+        .iter()
+        .filter(|(prefix, _)| model_id.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, price)| *price)
+}
+
+/// Estimated USD cost of one response, or `None` when the model isn't in
+/// the table.
+pub fn cost_usd(engine: &str, model_id: &str, usage: &TokenUsage) -> Option<f64> {
+    let price = price_for(engine, model_id)?;
+    let cached = usage.cached_tokens.min(usage.input_tokens) as f64;
+    let uncached = usage.input_tokens as f64 - cached;
+    Some(
+        (uncached * price.input
+            + cached * price.cached_input
+            + usage.output_tokens as f64 * price.output)
+            / 1_000_000.0,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_prefix_wins() {
+        assert_eq!(
+            price_for("openai", "gpt-4o-mini-2024-07-18").unwrap().input,
+            0.15
+        );
+        assert_eq!(price_for("openai", "gpt-4o-2024-08-06").unwrap().input, 2.5);
+        assert_eq!(
+            price_for("anthropic", "claude-opus-4-5-20251101")
+                .unwrap()
+                .output,
+            25.0
+        );
+        assert_eq!(
+            price_for("anthropic", "claude-opus-4-1-20250805")
+                .unwrap()
+                .output,
+            75.0
+        );
+    }
+
+    #[test]
+    fn routed_ids_are_normalized() {
+        assert_eq!(
+            price_for("openrouter", "openai/gpt-4o"),
+            price_for("openai", "gpt-4o")
+        );
+        assert!(price_for("google", "models/gemini-2.5-pro").is_some());
+    }
+
+    #[test]
+    fn local_is_free_and_unknown_is_unpriced() {
+        let usage = TokenUsage {
+            input_tokens: 1000,
+            output_tokens: 1000,
+            ..TokenUsage::default()
+        };
+        assert_eq!(cost_usd("ollama", "llama3.2", &usage), Some(0.0));
+        assert_eq!(cost_usd("custom", "my-finetune", &usage), None);
+    }
+
+    #[test]
+    fn cached_tokens_are_billed_at_the_cache_rate() {
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cached_tokens: 400_000,
+            reasoning_tokens: 0,
+        };
+        // 600k * 2.5 + 400k * 1.25 + 100k * 10 = 1.5 + 0.5 + 1.0
+        let cost = cost_usd("openai", "gpt-4o", &usage).unwrap();
+        assert!((cost - 3.0).abs() < 1e-9);
+    }
+}
@@ -1,4 +1,8 @@
-use crate::ai::types::{ChatMessage, ChatParams, ChatStreamEvent, ProviderConfig, RequestSpec};
+use crate::ai::attachments::{data_url, document_text};
+use crate::ai::types::{
+    ChatMessage, ChatParams, ChatStreamEvent, ContentPart, ImageSource, ProviderConfig,
+    RequestSpec, TokenUsage,
+};
 use crate::error::AppError;
 use serde_json::{json, Value};
 use std::collections::{BTreeMap, HashMap};
@@ -10,6 +14,14 @@ pub fn build_request(
     params: &ChatParams,
 ) -> Result<RequestSpec, AppError> {
     let engine_type = config.provider_type.as_deref().unwrap_or(provider_id);
+    crate::ai::attachments::ensure_supported(engine_type, &params.model_id, messages)?;
+    let prompted;
+    let params = if needs_prompted_schema(engine_type, params) {
+        prompted = prompt_schema(params);
+        &prompted
+    } else {
+        params
+    };
     match engine_type {
         "openai" => build_openai_request(config, messages, params),
         "anthropic" => build_anthropic_request(config, messages, params),
@@ -21,6 +33,54 @@ pub fn build_request(
     }
 }
 
+/// Whether `engine_type` can't carry `params.response_schema` natively.
+/// OpenAI-style `json_schema` modes only accept an object at the root, and
+/// Gemini won't combine a JSON response with function declarations.
+fn needs_prompted_schema(engine_type: &str, params: &ChatParams) -> bool {
+    let Some(schema) = &params.response_schema else {
+        return false;
+    };
+    let has_tools = params.tools.as_ref().is_some_and(|tools| !tools.is_empty());
+    match engine_type {
+        "ollama" => false,
+        "google" => has_tools,
+        "anthropic" => true,
+        _ => schema.get("type") != Some(&json!("object")),
+    }
+}
+
+/// Moves the response schema into the system prompt.
+fn prompt_schema(params: &ChatParams) -> ChatParams {
+    let mut prompted = params.clone();
+    if let Some(schema) = prompted.response_schema.take() {
+        let instruction = crate::ai::structured::schema_instruction(&schema);
+        prompted.system_prompt = Some(match params.system_prompt.as_deref() {
+            Some(prompt) if !prompt.trim().is_empty() => format!("{prompt}\n\n{instruction}"),
+            _ => instruction,
+        });
+    }
+    prompted
+}
+
+/// Chat Completions `response_format` for a schema'd request.
+fn openai_response_format(params: &ChatParams) -> Option<Value> {
+    params.response_schema.as_ref().map(|schema| {
+        json!({
+            "type": "json_schema",
+            "json_schema": { "name": "output", "schema": schema, "strict": false },
+        })
+    })
+}
+
+/// Responses API `text` options for a schema'd request.
+fn openai_responses_text(params: &ChatParams) -> Option<Value> {
+    params.response_schema.as_ref().map(|schema| {
+        json!({
+            "format": { "type": "json_schema", "name": "output", "schema": schema, "strict": false },
+        })
+    })
+}
+
 #[derive(Default)]
 struct PendingToolCall {
     id: String,
@@ -37,6 +97,9 @@ struct AnthropicToolBlock {
 
 /// Stateful parser for provider streaming protocols. Tool arguments may span
 /// multiple SSE/NDJSON chunks, so parsing cannot be a stateless line mapping.
+/// Usage is reported piecemeal too (Anthropic splits input and output counts
+/// across two events, Gemini repeats a running total on every chunk), so it
+/// is accumulated here and emitted once from [`Self::finish`].
 pub struct ProviderStreamParser {
     provider_id: String,
     config: ProviderConfig,
@@ -44,6 +107,7 @@ pub struct ProviderStreamParser {
     anthropic_tool: Option<AnthropicToolBlock>,
     google_tool_counter: u32,
     ollama_tool_counter: u32,
+    usage: Option<TokenUsage>,
 }
 
 impl ProviderStreamParser {
@@ -59,6 +123,7 @@ impl ProviderStreamParser {
             anthropic_tool: None,
             google_tool_counter: 0,
             ollama_tool_counter: 0,
+            usage: None,
         }
     }
 
@@ -100,6 +165,9 @@ impl ProviderStreamParser {
                 input,
             });
         }
+        if let Some(usage) = self.usage.take() {
+            events.push(ChatStreamEvent::Usage { usage });
+        }
         Ok(events)
     }
 
@@ -107,6 +175,16 @@ impl ProviderStreamParser {
         let value: Value = serde_json::from_str(payload).map_err(|error| {
             AppError::Other(format!("invalid OpenAI-compatible stream event: {error}"))
         })?;
+        // With `stream_options.include_usage` the totals arrive on a final
+        // chunk whose `choices` is empty.
+        if let Some(usage) = value.get("usage").filter(|usage| usage.is_object()) {
+            self.usage = Some(TokenUsage {
+                input_tokens: count_at(usage, "/prompt_tokens"),
+                output_tokens: count_at(usage, "/completion_tokens"),
+                cached_tokens: count_at(usage, "/prompt_tokens_details/cached_tokens"),
+                reasoning_tokens: count_at(usage, "/completion_tokens_details/reasoning_tokens"),
+            });
+        }
         let Some(choice) = value.get("choices").and_then(|choices| choices.get(0)) else {
             return Ok(Vec::new());
         };
@@ -204,6 +282,20 @@ impl ProviderStreamParser {
                     _ => Ok(Vec::new()),
                 }
             }
+            "response.completed" => {
+                if let Some(usage) = value.pointer("/response/usage") {
+                    self.usage = Some(TokenUsage {
+                        input_tokens: count_at(usage, "/input_tokens"),
+                        output_tokens: count_at(usage, "/output_tokens"),
+                        cached_tokens: count_at(usage, "/input_tokens_details/cached_tokens"),
+                        reasoning_tokens: count_at(
+                            usage,
+                            "/output_tokens_details/reasoning_tokens",
+                        ),
+                    });
+                }
+                Ok(Vec::new())
+            }
             "error" | "response.failed" => {
                 let error = value
                     .get("error")
@@ -225,6 +317,32 @@ impl ProviderStreamParser {
         let value: Value = serde_json::from_str(payload)
             .map_err(|error| AppError::Other(format!("invalid Anthropic stream event: {error}")))?;
         match value.get("type").and_then(Value::as_str) {
+            Some("message_start") => {
+                // Anthropic's `input_tokens` excludes cache reads and writes;
+                // fold them back in so `input_tokens` means the same thing
+                // for every provider.
+                if let Some(usage) = value.pointer("/message/usage") {
+                    let cached = count_at(usage, "/cache_read_input_tokens");
+                    self.usage = Some(TokenUsage {
+                        input_tokens: count_at(usage, "/input_tokens")
+                            + cached
+                            + count_at(usage, "/cache_creation_input_tokens"),
+                        output_tokens: count_at(usage, "/output_tokens"),
+                        cached_tokens: cached,
+                        reasoning_tokens: 0,
+                    });
+                }
+                Ok(Vec::new())
+            }
+            Some("message_delta") => {
+                // `output_tokens` here is cumulative for the message.
+                if let Some(output) = value.pointer("/usage/output_tokens") {
+                    self.usage
+                        .get_or_insert_with(TokenUsage::default)
+                        .output_tokens = output.as_u64().unwrap_or(0) as u32;
+                }
+                Ok(Vec::new())
+            }
             Some("content_block_start") => {
                 let block = value.get("content_block").unwrap_or(&Value::Null);
                 if block.get("type").and_then(Value::as_str) == Some("tool_use") {
@@ -292,6 +410,16 @@ impl ProviderStreamParser {
     fn parse_google(&mut self, payload: &str) -> Result<Vec<ChatStreamEvent>, AppError> {
         let value: Value = serde_json::from_str(payload)
             .map_err(|error| AppError::Other(format!("invalid Gemini stream event: {error}")))?;
+        // Every chunk carries the running total; the last one wins.
+        if let Some(usage) = value.get("usageMetadata") {
+            let thoughts = count_at(usage, "/thoughtsTokenCount");
+            self.usage = Some(TokenUsage {
+                input_tokens: count_at(usage, "/promptTokenCount"),
+                output_tokens: count_at(usage, "/candidatesTokenCount") + thoughts,
+                cached_tokens: count_at(usage, "/cachedContentTokenCount"),
+                reasoning_tokens: thoughts,
+            });
+        }
         let mut events = Vec::new();
         for part in value
             .pointer("/candidates/0/content/parts")
@@ -328,6 +456,13 @@ impl ProviderStreamParser {
     fn parse_ollama(&mut self, payload: &str) -> Result<Vec<ChatStreamEvent>, AppError> {
         let value: Value = serde_json::from_str(payload)
             .map_err(|error| AppError::Other(format!("invalid Ollama stream event: {error}")))?;
+        if value.get("done").and_then(Value::as_bool) == Some(true) {
+            self.usage = Some(TokenUsage {
+                input_tokens: count_at(&value, "/prompt_eval_count"),
+                output_tokens: count_at(&value, "/eval_count"),
+                ..TokenUsage::default()
+            });
+        }
         let mut events = Vec::new();
         if let Some(token) = value.pointer("/message/content").and_then(Value::as_str) {
             if !token.is_empty() {
@@ -359,54 +494,159 @@ impl ProviderStreamParser {
     }
 }
 
+fn count_at(value: &Value, pointer: &str) -> u32 {
+    value
+        .pointer(pointer)
+        .and_then(Value::as_u64)
+        .map(|count| count.min(u32::MAX as u64) as u32)
+        .unwrap_or(0)
+}
+
 fn encode_tool_id_for_wire(id: &str) -> String {
     id.replace(':', "__").replace('.', "--")
 }
 
-fn openai_messages(messages: &[ChatMessage], stringify_tool_arguments: bool) -> Vec<Value> {
+fn parts(message: &ChatMessage) -> &[ContentPart] {
+    message.parts.as_deref().unwrap_or_default()
+}
+
+/// Base64 data of an image part. Clipboard-cache sources are inlined before
+/// a message is stored, so meeting one here is a caller bug.
+fn image_data(source: &ImageSource) -> Result<&str, AppError> {
+    match source {
+        ImageSource::Bytes { data } => Ok(data),
+        ImageSource::ClipboardCache { .. } => Err(AppError::Validation(
+            "clipboard images must be resolved before they are sent".to_string(),
+        )),
+    }
+}
+
+/// The two dialects that share [`openai_messages`]: Ollama's `/api/chat`
+/// takes tool arguments as objects and images as a bare base64 list.
+#[derive(Clone, Copy, PartialEq)]
+enum ChatWire {
+    OpenAiCompatible,
+    Ollama,
+}
+
+fn openai_user_content(message: &ChatMessage) -> Result<Value, AppError> {
+    if parts(message).is_empty() {
+        return Ok(json!(message.content));
+    }
+    let mut content = Vec::new();
+    if !message.content.is_empty() {
+        content.push(json!({ "type": "text", "text": message.content }));
+    }
+    for part in parts(message) {
+        content.push(match part {
+            ContentPart::Text { text } => json!({ "type": "text", "text": text }),
+            ContentPart::Image { media_type, source } => json!({
+                "type": "image_url",
+                "image_url": { "url": data_url(media_type, image_data(source)?) },
+            }),
+            ContentPart::File {
+                name,
+                text: Some(text),
+                ..
+            } => json!({ "type": "text", "text": document_text(name, text) }),
+            ContentPart::File {
+                name,
+                media_type,
+                data,
+                ..
+            } => json!({
+                "type": "file",
+                "file": {
+                    "filename": name,
+                    "file_data": data_url(media_type, data.as_deref().unwrap_or_default()),
+                },
+            }),
+        });
+    }
+    Ok(Value::Array(content))
+}
+
+fn ollama_user_message(message: &ChatMessage) -> Result<Value, AppError> {
+    let mut text = message.content.clone();
+    let mut images = Vec::new();
+    for part in parts(message) {
+        let extra = match part {
+            ContentPart::Text { text } => text.clone(),
+            ContentPart::Image { source, .. } => {
+                images.push(json!(image_data(source)?));
+                continue;
+            }
+            ContentPart::File {
+                name,
+                text: Some(document),
+                ..
+            } => document_text(name, document),
+            ContentPart::File { name, .. } => {
+                return Err(AppError::Validation(format!(
+                    "Ollama can't read {name}; attach the document as text instead"
+                )))
+            }
+        };
+        if !text.is_empty() {
+            text.push_str("\n\n");
+        }
+        text.push_str(&extra);
+    }
+    let mut value = json!({ "role": message.role, "content": text });
+    if !images.is_empty() {
+        value["images"] = Value::Array(images);
+    }
+    Ok(value)
+}
+
+fn openai_messages(messages: &[ChatMessage], wire: ChatWire) -> Result<Vec<Value>, AppError> {
+    let stringify_tool_arguments = wire == ChatWire::OpenAiCompatible;
     messages
         .iter()
         .filter(|message| message.role != "system")
-        .map(|message| match message.role.as_str() {
-            "assistant" => {
-                let mut value = json!({
-                    "role": "assistant",
-                    "content": message.content,
-                });
-                if let Some(tool_calls) = &message.tool_calls {
-                    value["tool_calls"] = Value::Array(
-                        tool_calls
-                            .iter()
-                            .map(|tool_call| {
-                                json!({
-                                    "id": tool_call.id,
-                                    "type": "function",
-                                    "function": {
-                                        "name": encode_tool_id_for_wire(&tool_call.name),
-                                        "arguments": if stringify_tool_arguments {
-                                            Value::String(tool_call.input.to_string())
-                                        } else {
-                                            tool_call.input.clone()
+        .map(|message| -> Result<Value, AppError> {
+            Ok(match message.role.as_str() {
+                "assistant" => {
+                    let mut value = json!({
+                        "role": "assistant",
+                        "content": message.content,
+                    });
+                    if let Some(tool_calls) = &message.tool_calls {
+                        value["tool_calls"] = Value::Array(
+                            tool_calls
+                                .iter()
+                                .map(|tool_call| {
+                                    json!({
+                                        "id": tool_call.id,
+                                        "type": "function",
+                                        "function": {
+                                            "name": encode_tool_id_for_wire(&tool_call.name),
+                                            "arguments": if stringify_tool_arguments {
+                                                Value::String(tool_call.input.to_string())
+                                            } else {
+                                                tool_call.input.clone()
+                                            },
                                         },
-                                    },
+                                    })
                                 })
-                            })
-                            .collect(),
-                    );
-                }
-                if let Some(context) = &message.provider_context {
-                    if !context.is_empty() {
-                        value["reasoning_details"] = Value::Array(context.clone());
+                                .collect(),
+                        );
                     }
+                    if let Some(context) = &message.provider_context {
+                        if !context.is_empty() {
+                            value["reasoning_details"] = Value::Array(context.clone());
+                        }
+                    }
+                    value
                 }
-                value
-            }
-            "tool" => json!({
-                "role": "tool",
-                "tool_call_id": message.tool_call_id,
-                "content": message.content,
-            }),
-            _ => json!({ "role": message.role, "content": message.content }),
+                "tool" => json!({
+                    "role": "tool",
+                    "tool_call_id": message.tool_call_id,
+                    "content": message.content,
+                }),
+                _ if wire == ChatWire::Ollama => ollama_user_message(message)?,
+                _ => json!({ "role": message.role, "content": openai_user_content(message)? }),
+            })
         })
         .collect()
 }
@@ -430,7 +670,45 @@ fn openai_tool_definitions(params: &ChatParams) -> Vec<Value> {
         .collect()
 }
 
-fn openai_responses_input(messages: &[ChatMessage], system_prompt: Option<&str>) -> Vec<Value> {
+fn openai_responses_user_content(message: &ChatMessage) -> Result<Value, AppError> {
+    if parts(message).is_empty() {
+        return Ok(json!(message.content));
+    }
+    let mut content = Vec::new();
+    if !message.content.is_empty() {
+        content.push(json!({ "type": "input_text", "text": message.content }));
+    }
+    for part in parts(message) {
+        content.push(match part {
+            ContentPart::Text { text } => json!({ "type": "input_text", "text": text }),
+            ContentPart::Image { media_type, source } => json!({
+                "type": "input_image",
+                "image_url": data_url(media_type, image_data(source)?),
+            }),
+            ContentPart::File {
+                name,
+                text: Some(text),
+                ..
+            } => json!({ "type": "input_text", "text": document_text(name, text) }),
+            ContentPart::File {
+                name,
+                media_type,
+                data,
+                ..
+            } => json!({
+                "type": "input_file",
+                "filename": name,
+                "file_data": data_url(media_type, data.as_deref().unwrap_or_default()),
+            }),
+        });
+    }
+    Ok(Value::Array(content))
+}
+
+fn openai_responses_input(
+    messages: &[ChatMessage],
+    system_prompt: Option<&str>,
+) -> Result<Vec<Value>, AppError> {
     let mut input = Vec::new();
     if let Some(system_prompt) = system_prompt.filter(|prompt| !prompt.trim().is_empty()) {
         input.push(json!({ "role": "system", "content": system_prompt }));
@@ -462,11 +740,11 @@ fn openai_responses_input(messages: &[ChatMessage], system_prompt: Option<&str>)
             }
             _ => input.push(json!({
                 "role": message.role,
-                "content": message.content,
+                "content": openai_responses_user_content(message)?,
             })),
         }
     }
-    input
+    Ok(input)
 }
 
 // ─── OpenAI ──────────────────────────────────────────────────────────────────
@@ -494,7 +772,7 @@ fn build_openai_request(
     headers.insert("Authorization".to_string(), format!("Bearer {api_key}"));
 
     let body = if is_responses {
-        let input = openai_responses_input(messages, params.system_prompt.as_deref());
+        let input = openai_responses_input(messages, params.system_prompt.as_deref())?;
 
         let mut body_map = json!({
             "model": params.model_id,
@@ -535,6 +813,9 @@ fn build_openai_request(
             if !tools.is_empty() {
                 obj.insert("tools".to_string(), Value::Array(tools));
             }
+            if let Some(text) = openai_responses_text(params) {
+                obj.insert("text".to_string(), text);
+            }
         }
         body_map
     } else {
@@ -544,7 +825,7 @@ fn build_openai_request(
                 msgs.push(json!({ "role": "system", "content": sys }));
             }
         }
-        msgs.extend(openai_messages(messages, true));
+        msgs.extend(openai_messages(messages, ChatWire::OpenAiCompatible)?);
 
         let mut body_map = json!({
             "model": params.model_id,
@@ -555,6 +836,10 @@ fn build_openai_request(
         if let Some(obj) = body_map.as_object_mut() {
             obj.insert("max_tokens".to_string(), json!(params.max_tokens));
             obj.insert("temperature".to_string(), json!(params.temperature));
+            obj.insert(
+                "stream_options".to_string(),
+                json!({ "include_usage": true }),
+            );
 
             if let Some(ref effort) = config.reasoning_effort {
                 obj.insert("reasoning_effort".to_string(), json!(effort));
@@ -563,6 +848,9 @@ fn build_openai_request(
             if !tools.is_empty() {
                 obj.insert("tools".to_string(), Value::Array(tools));
             }
+            if let Some(format) = openai_response_format(params) {
+                obj.insert("response_format".to_string(), format);
+            }
         }
         body_map
     };
@@ -572,6 +860,49 @@ fn build_openai_request(
 
 // ─── Anthropic ───────────────────────────────────────────────────────────────
 
+fn anthropic_user_content(message: &ChatMessage) -> Result<Value, AppError> {
+    if parts(message).is_empty() {
+        return Ok(json!(message.content));
+    }
+    let mut blocks = Vec::new();
+    if !message.content.is_empty() {
+        blocks.push(json!({ "type": "text", "text": message.content }));
+    }
+    for part in parts(message) {
+        blocks.push(match part {
+            ContentPart::Text { text } => json!({ "type": "text", "text": text }),
+            ContentPart::Image { media_type, source } => json!({
+                "type": "image",
+                "source": {
+                    "type": "base64",
+                    "media_type": media_type,
+                    "data": image_data(source)?,
+                },
+            }),
+            ContentPart::File {
+                name,
+                text: Some(text),
+                ..
+            } => json!({
+                "type": "document",
+                "title": name,
+                "source": { "type": "text", "media_type": "text/plain", "data": text },
+            }),
+            ContentPart::File {
+                name,
+                media_type,
+                data,
+                ..
+            } => json!({
+                "type": "document",
+                "title": name,
+                "source": { "type": "base64", "media_type": media_type, "data": data },
+            }),
+        });
+    }
+    Ok(Value::Array(blocks))
+}
+
 fn build_anthropic_request(
     config: &ProviderConfig,
     messages: &[ChatMessage],
@@ -592,40 +923,42 @@ fn build_anthropic_request(
     let filtered = messages
         .iter()
         .filter(|message| message.role != "system")
-        .map(|message| match message.role.as_str() {
-            "assistant" => {
-                let mut blocks = Vec::new();
-                if !message.content.is_empty() {
-                    blocks.push(json!({ "type": "text", "text": message.content }));
+        .map(|message| -> Result<Value, AppError> {
+            Ok(match message.role.as_str() {
+                "assistant" => {
+                    let mut blocks = Vec::new();
+                    if !message.content.is_empty() {
+                        blocks.push(json!({ "type": "text", "text": message.content }));
+                    }
+                    blocks.extend(
+                        message
+                            .tool_calls
+                            .as_deref()
+                            .unwrap_or_default()
+                            .iter()
+                            .map(|tool_call| {
+                                json!({
+                                    "type": "tool_use",
+                                    "id": tool_call.id,
+                                    "name": encode_tool_id_for_wire(&tool_call.name),
+                                    "input": tool_call.input,
+                                })
+                            }),
+                    );
+                    json!({ "role": "assistant", "content": blocks })
                 }
-                blocks.extend(
-                    message
-                        .tool_calls
-                        .as_deref()
-                        .unwrap_or_default()
-                        .iter()
-                        .map(|tool_call| {
-                            json!({
-                                "type": "tool_use",
-                                "id": tool_call.id,
-                                "name": encode_tool_id_for_wire(&tool_call.name),
-                                "input": tool_call.input,
-                            })
-                        }),
-                );
-                json!({ "role": "assistant", "content": blocks })
-            }
-            "tool" => json!({
-                "role": "user",
-                "content": [{
-                    "type": "tool_result",
-                    "tool_use_id": message.tool_call_id,
-                    "content": message.content,
-                }],
-            }),
-            _ => json!({ "role": "user", "content": message.content }),
+                "tool" => json!({
+                    "role": "user",
+                    "content": [{
+                        "type": "tool_result",
+                        "tool_use_id": message.tool_call_id,
+                        "content": message.content,
+                    }],
+                }),
+                _ => json!({ "role": "user", "content": anthropic_user_content(message)? }),
+            })
         })
-        .collect::<Vec<_>>();
+        .collect::<Result<Vec<_>, AppError>>()?;
 
     let mut body_map = json!({
         "model": params.model_id,
@@ -679,6 +1012,30 @@ fn build_anthropic_request(
 
 // ─── Google Gemini ───────────────────────────────────────────────────────────
 
+fn google_user_parts(message: &ChatMessage) -> Result<Value, AppError> {
+    let mut output = Vec::new();
+    if !message.content.is_empty() || parts(message).is_empty() {
+        output.push(json!({ "text": message.content }));
+    }
+    for part in parts(message) {
+        output.push(match part {
+            ContentPart::Text { text } => json!({ "text": text }),
+            ContentPart::Image { media_type, source } => json!({
+                "inlineData": { "mimeType": media_type, "data": image_data(source)? },
+            }),
+            ContentPart::File {
+                name,
+                text: Some(text),
+                ..
+            } => json!({ "text": document_text(name, text) }),
+            ContentPart::File {
+                media_type, data, ..
+            } => json!({ "inlineData": { "mimeType": media_type, "data": data } }),
+        });
+    }
+    Ok(Value::Array(output))
+}
+
 fn build_google_request(
     config: &ProviderConfig,
     messages: &[ChatMessage],
@@ -707,7 +1064,8 @@ fn build_google_request(
     let contents = messages
         .iter()
         .filter(|message| message.role != "system")
-        .map(|message| match message.role.as_str() {
+        .map(|message| -> Result<Value, AppError> {
+            Ok(match message.role.as_str() {
             "assistant" => {
                 let mut parts = Vec::new();
                 if !message.content.is_empty() {
@@ -746,9 +1104,10 @@ fn build_google_request(
                     }]
                 })
             }
-            _ => json!({ "role": "user", "parts": [{ "text": message.content }] }),
+            _ => json!({ "role": "user", "parts": google_user_parts(message)? }),
+            })
         })
-        .collect::<Vec<_>>();
+        .collect::<Result<Vec<_>, AppError>>()?;
 
     let mut body_map = json!({
         "contents": contents,
@@ -757,6 +1116,11 @@ fn build_google_request(
             "maxOutputTokens": params.max_tokens,
         }
     });
+    if let Some(schema) = &params.response_schema {
+        let generation = body_map["generationConfig"].as_object_mut().unwrap();
+        generation.insert("responseMimeType".to_string(), json!("application/json"));
+        generation.insert("responseJsonSchema".to_string(), schema.clone());
+    }
 
     if let Some(ref sys) = params.system_prompt {
         if !sys.trim().is_empty() {
@@ -830,7 +1194,7 @@ fn build_ollama_request(
             msgs.push(json!({ "role": "system", "content": sys }));
         }
     }
-    msgs.extend(openai_messages(messages, false));
+    msgs.extend(openai_messages(messages, ChatWire::Ollama)?);
 
     let mut body_map = json!({
         "model": params.model_id,
@@ -851,6 +1215,12 @@ fn build_ollama_request(
             .unwrap()
             .insert("tools".to_string(), Value::Array(tools));
     }
+    if let Some(schema) = &params.response_schema {
+        body_map
+            .as_object_mut()
+            .unwrap()
+            .insert("format".to_string(), schema.clone());
+    }
 
     Ok(RequestSpec {
         url,
@@ -881,7 +1251,7 @@ fn build_openrouter_request(
             msgs.push(json!({ "role": "system", "content": sys }));
         }
     }
-    msgs.extend(openai_messages(messages, true));
+    msgs.extend(openai_messages(messages, ChatWire::OpenAiCompatible)?);
 
     let mut body_map = json!({
         "model": params.model_id,
@@ -892,6 +1262,10 @@ fn build_openrouter_request(
     if let Some(obj) = body_map.as_object_mut() {
         obj.insert("max_tokens".to_string(), json!(params.max_tokens));
         obj.insert("temperature".to_string(), json!(params.temperature));
+        obj.insert(
+            "stream_options".to_string(),
+            json!({ "include_usage": true }),
+        );
 
         if let Some(ref effort) = config.reasoning_effort {
             obj.insert("reasoning".to_string(), json!({ "effort": effort }));
@@ -900,6 +1274,9 @@ fn build_openrouter_request(
         if !tools.is_empty() {
             obj.insert("tools".to_string(), Value::Array(tools));
         }
+        if let Some(format) = openai_response_format(params) {
+            obj.insert("response_format".to_string(), format);
+        }
     }
 
     Ok(RequestSpec {
@@ -938,7 +1315,7 @@ fn build_custom_request(
     }
 
     let body = if is_responses {
-        let input = openai_responses_input(messages, params.system_prompt.as_deref());
+        let input = openai_responses_input(messages, params.system_prompt.as_deref())?;
 
         let mut body_map = json!({
             "model": params.model_id,
@@ -979,6 +1356,9 @@ fn build_custom_request(
             if !tools.is_empty() {
                 obj.insert("tools".to_string(), Value::Array(tools));
             }
+            if let Some(text) = openai_responses_text(params) {
+                obj.insert("text".to_string(), text);
+            }
         }
         body_map
     } else {
@@ -988,7 +1368,7 @@ fn build_custom_request(
                 msgs.push(json!({ "role": "system", "content": sys }));
             }
         }
-        msgs.extend(openai_messages(messages, true));
+        msgs.extend(openai_messages(messages, ChatWire::OpenAiCompatible)?);
 
         let mut body_map = json!({
             "model": params.model_id,
@@ -1007,6 +1387,9 @@ fn build_custom_request(
             if !tools.is_empty() {
                 obj.insert("tools".to_string(), Value::Array(tools));
             }
+            if let Some(format) = openai_response_format(params) {
+                obj.insert("response_format".to_string(), format);
+            }
         }
         body_map
     };
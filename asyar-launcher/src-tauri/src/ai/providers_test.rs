@@ -1,6 +1,7 @@
 use super::providers::{build_request, ProviderStreamParser};
 use super::types::{
-    ChatMessage, ChatParams, ChatStreamEvent, ProviderConfig, ToolCall, ToolDefinition,
+    ChatMessage, ChatParams, ChatStreamEvent, ContentPart, ImageSource, ProviderConfig, TokenUsage,
+    ToolCall, ToolDefinition,
 };
 use serde_json::json;
 
@@ -14,6 +15,7 @@ fn mock_messages() -> Vec<ChatMessage> {
             tool_calls: None,
             tool_call_id: None,
             provider_context: None,
+            parts: None,
         },
         ChatMessage {
             id: "2".to_string(),
@@ -23,6 +25,7 @@ fn mock_messages() -> Vec<ChatMessage> {
             tool_calls: None,
             tool_call_id: None,
             provider_context: None,
+            parts: None,
         },
     ]
 }
@@ -34,6 +37,7 @@ fn mock_params(system: Option<&str>) -> ChatParams {
         max_tokens: 1024,
         system_prompt: system.map(|s| s.to_string()),
         tools: None,
+        response_schema: None,
     }
 }
 
@@ -242,6 +246,7 @@ fn tool_history() -> Vec<ChatMessage> {
             tool_calls: None,
             tool_call_id: None,
             provider_context: None,
+            parts: None,
         },
         ChatMessage {
             id: "assistant-1".to_string(),
@@ -255,6 +260,7 @@ fn tool_history() -> Vec<ChatMessage> {
             }]),
             tool_call_id: None,
             provider_context: None,
+            parts: None,
         },
         ChatMessage {
             id: "tool-1".to_string(),
@@ -264,6 +270,7 @@ fn tool_history() -> Vec<ChatMessage> {
             tool_calls: None,
             tool_call_id: Some("call-1".to_string()),
             provider_context: None,
+            parts: None,
         },
     ]
 }
@@ -436,3 +443,320 @@ fn test_build_request_and_parser_with_custom_named_instance() {
         ChatStreamEvent::Token { token } if token == "hello"
     ));
 }
+
+fn finished_usage(parser: &mut ProviderStreamParser) -> Option<TokenUsage> {
+    parser
+        .finish()
+        .unwrap()
+        .into_iter()
+        .find_map(|event| match event {
+            ChatStreamEvent::Usage { usage } => Some(usage),
+            _ => None,
+        })
+}
+
+#[test]
+fn test_openai_chat_request_asks_for_usage() {
+    let req = build_request("openai", &mock_config(), &[], &mock_params(None)).unwrap();
+    assert_eq!(req.body["stream_options"]["include_usage"], true);
+}
+
+#[test]
+fn test_parse_openai_usage_chunk_before_done() {
+    let mut parser = ProviderStreamParser::new("openai", &mock_config());
+    parser
+        .push_line(
+            r#"data: {"choices":[],"usage":{"prompt_tokens":120,"completion_tokens":30,"prompt_tokens_details":{"cached_tokens":100},"completion_tokens_details":{"reasoning_tokens":12}}}"#,
+        )
+        .unwrap();
+    let events = parser.push_line("data: [DONE]").unwrap();
+    assert!(matches!(
+        events.as_slice(),
+        [ChatStreamEvent::Usage {
+            usage: TokenUsage {
+                input_tokens: 120,
+                output_tokens: 30,
+                cached_tokens: 100,
+                reasoning_tokens: 12,
+            }
+        }]
+    ));
+    // The trailing finish() from the stream driver must not repeat it.
+    assert_eq!(finished_usage(&mut parser), None);
+}
+
+#[test]
+fn test_parse_openai_responses_completed_usage() {
+    let mut config = mock_config();
+    config.open_ai_api_mode = Some("responses".into());
+    let mut parser = ProviderStreamParser::new("openai", &config);
+    parser
+        .push_line(
+            r#"data: {"type":"response.completed","response":{"usage":{"input_tokens":50,"output_tokens":20,"input_tokens_details":{"cached_tokens":10},"output_tokens_details":{"reasoning_tokens":5}}}}"#,
+        )
+        .unwrap();
+    assert_eq!(
+        finished_usage(&mut parser),
+        Some(TokenUsage {
+            input_tokens: 50,
+            output_tokens: 20,
+            cached_tokens: 10,
+            reasoning_tokens: 5,
+        })
+    );
+}
+
+#[test]
+fn test_parse_anthropic_usage_across_events() {
+    let mut parser = ProviderStreamParser::new("anthropic", &mock_config());
+    parser
+        .push_line(
+            r#"data: {"type":"message_start","message":{"usage":{"input_tokens":10,"cache_read_input_tokens":200,"cache_creation_input_tokens":5,"output_tokens":1}}}"#,
+        )
+        .unwrap();
+    parser
+        .push_line(r#"data: {"type":"message_delta","usage":{"output_tokens":42}}"#)
+        .unwrap();
+    assert_eq!(
+        finished_usage(&mut parser),
+        Some(TokenUsage {
+            input_tokens: 215,
+            output_tokens: 42,
+            cached_tokens: 200,
+            reasoning_tokens: 0,
+        })
+    );
+}
+
+#[test]
+fn test_parse_google_usage_keeps_last_total() {
+    let mut parser = ProviderStreamParser::new("google", &mock_config());
+    parser
+        .push_line(
+            r#"data: {"candidates":[{"content":{"parts":[{"text":"a"}]}}],"usageMetadata":{"promptTokenCount":8,"candidatesTokenCount":1}}"#,
+        )
+        .unwrap();
+    parser
+        .push_line(
+            r#"data: {"candidates":[{"content":{"parts":[{"text":"b"}]}}],"usageMetadata":{"promptTokenCount":8,"candidatesTokenCount":6,"thoughtsTokenCount":4,"cachedContentTokenCount":2}}"#,
+        )
+        .unwrap();
+    assert_eq!(
+        finished_usage(&mut parser),
+        Some(TokenUsage {
+            input_tokens: 8,
+            output_tokens: 10,
+            cached_tokens: 2,
+            reasoning_tokens: 4,
+        })
+    );
+}
+
+#[test]
+fn test_parse_ollama_usage_on_done() {
+    let mut parser = ProviderStreamParser::new("ollama", &mock_config());
+    parser
+        .push_line(
+            r#"{"message":{"content":""},"done":true,"prompt_eval_count":26,"eval_count":290}"#,
+        )
+        .unwrap();
+    assert_eq!(
+        finished_usage(&mut parser),
+        Some(TokenUsage {
+            input_tokens: 26,
+            output_tokens: 290,
+            ..TokenUsage::default()
+        })
+    );
+}
+
+fn multimodal_messages() -> Vec<ChatMessage> {
+    vec![ChatMessage {
+        id: "1".to_string(),
+        role: "user".to_string(),
+        content: "Summarise these".to_string(),
+        timestamp: 1000,
+        tool_calls: None,
+        tool_call_id: None,
+        provider_context: None,
+        parts: Some(vec![
+            ContentPart::Image {
+                media_type: "image/png".to_string(),
+                source: ImageSource::Bytes {
+                    data: "aW1n".to_string(),
+                },
+            },
+            ContentPart::File {
+                name: "notes.md".to_string(),
+                media_type: "text/plain".to_string(),
+                text: Some("ship it".to_string()),
+                data: None,
+            },
+        ]),
+    }]
+}
+
+#[test]
+fn test_openai_encodes_attachments_as_content_parts() {
+    let req = build_request(
+        "openai",
+        &mock_config(),
+        &multimodal_messages(),
+        &mock_params(None),
+    )
+    .unwrap();
+    let content = &req.body["messages"][0]["content"];
+    assert_eq!(
+        content[0],
+        json!({ "type": "text", "text": "Summarise these" })
+    );
+    assert_eq!(
+        content[1],
+        json!({ "type": "image_url", "image_url": { "url": "data:image/png;base64,aW1n" } })
+    );
+    assert_eq!(
+        content[2]["text"],
+        "<document name=\"notes.md\">\nship it\n</document>"
+    );
+
+    let mut config = mock_config();
+    config.open_ai_api_mode = Some("responses".to_string());
+    let req = build_request(
+        "openai",
+        &config,
+        &multimodal_messages(),
+        &mock_params(None),
+    )
+    .unwrap();
+    let content = &req.body["input"][0]["content"];
+    assert_eq!(content[1]["type"], "input_image");
+    assert_eq!(content[1]["image_url"], "data:image/png;base64,aW1n");
+}
+
+#[test]
+fn test_anthropic_encodes_images_and_documents() {
+    let mut messages = multimodal_messages();
+    messages[0].parts.as_mut().unwrap().push(ContentPart::File {
+        name: "report.pdf".to_string(),
+        media_type: "application/pdf".to_string(),
+        text: None,
+        data: Some("cGRm".to_string()),
+    });
+    let mut params = mock_params(None);
+    params.model_id = "claude-sonnet-4-6".to_string();
+    let req = build_request("anthropic", &mock_config(), &messages, &params).unwrap();
+    let content = &req.body["messages"][0]["content"];
+    assert_eq!(
+        content[1]["source"],
+        json!({ "type": "base64", "media_type": "image/png", "data": "aW1n" })
+    );
+    assert_eq!(content[2]["type"], "document");
+    assert_eq!(content[2]["source"]["type"], "text");
+    assert_eq!(content[3]["title"], "report.pdf");
+    assert_eq!(content[3]["source"]["media_type"], "application/pdf");
+}
+
+#[test]
+fn test_google_and_ollama_encode_images_natively() {
+    let mut params = mock_params(None);
+    params.model_id = "gemini-2.5-flash".to_string();
+    let req = build_request("google", &mock_config(), &multimodal_messages(), &params).unwrap();
+    assert_eq!(
+        req.body["contents"][0]["parts"][1],
+        json!({ "inlineData": { "mimeType": "image/png", "data": "aW1n" } })
+    );
+
+    params.model_id = "llava:13b".to_string();
+    let req = build_request("ollama", &mock_config(), &multimodal_messages(), &params).unwrap();
+    let message = &req.body["messages"][0];
+    assert_eq!(message["images"], json!(["aW1n"]));
+    assert_eq!(
+        message["content"],
+        "Summarise these\n\n<document name=\"notes.md\">\nship it\n</document>"
+    );
+}
+
+#[test]
+fn test_text_only_model_rejects_images_before_building() {
+    let mut params = mock_params(None);
+    params.model_id = "gpt-3.5-turbo".to_string();
+    let error = build_request("openai", &mock_config(), &multimodal_messages(), &params)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("can't read images"), "{error}");
+}
+
+// ── Structured output ───────────────────────────────────────────────────────
+
+fn schema_params(schema: serde_json::Value) -> ChatParams {
+    let mut params = mock_params(Some("Extract contacts."));
+    params.response_schema = Some(schema);
+    params
+}
+
+fn contact_schema() -> serde_json::Value {
+    json!({ "type": "object", "properties": { "name": { "type": "string" } } })
+}
+
+#[test]
+fn test_openai_schema_uses_native_json_modes() {
+    let params = schema_params(contact_schema());
+    let req = build_request("openai", &mock_config(), &mock_messages(), &params).unwrap();
+    assert_eq!(req.body["response_format"]["type"], "json_schema");
+    assert_eq!(
+        req.body["response_format"]["json_schema"]["schema"],
+        contact_schema()
+    );
+    assert_eq!(req.body["messages"][0]["content"], "Extract contacts.");
+
+    let mut config = mock_config();
+    config.open_ai_api_mode = Some("responses".to_string());
+    let req = build_request("openai", &config, &mock_messages(), &params).unwrap();
+    assert_eq!(req.body["text"]["format"]["type"], "json_schema");
+    assert_eq!(req.body["text"]["format"]["schema"], contact_schema());
+}
+
+#[test]
+fn test_non_object_schema_falls_back_to_prompt_for_openai() {
+    let schema = json!({ "type": "array", "items": { "type": "string" } });
+    let params = schema_params(schema);
+    let req = build_request("openai", &mock_config(), &mock_messages(), &params).unwrap();
+    assert!(req.body.get("response_format").is_none());
+    let system = req.body["messages"][0]["content"].as_str().unwrap();
+    assert!(system.starts_with("Extract contacts.\n\nReply with only a JSON value"));
+}
+
+#[test]
+fn test_anthropic_schema_is_prompt_level() {
+    let params = schema_params(contact_schema());
+    let req = build_request("anthropic", &mock_config(), &mock_messages(), &params).unwrap();
+    let system = req.body["system"].as_str().unwrap();
+    assert!(system.contains("\"properties\""), "{system}");
+}
+
+#[test]
+fn test_google_schema_is_native_unless_tools_are_offered() {
+    let params = schema_params(contact_schema());
+    let req = build_request("google", &mock_config(), &mock_messages(), &params).unwrap();
+    let generation = &req.body["generationConfig"];
+    assert_eq!(generation["responseMimeType"], "application/json");
+    assert_eq!(generation["responseJsonSchema"], contact_schema());
+
+    let mut params = tool_params();
+    params.response_schema = Some(contact_schema());
+    let req = build_request("google", &mock_config(), &mock_messages(), &params).unwrap();
+    assert!(req.body["generationConfig"]
+        .get("responseMimeType")
+        .is_none());
+    let system = req.body["systemInstruction"]["parts"][0]["text"]
+        .as_str()
+        .unwrap();
+    assert!(system.contains("JSON Schema"), "{system}");
+}
+
+#[test]
+fn test_ollama_schema_goes_in_format() {
+    let params = schema_params(contact_schema());
+    let req = build_request("ollama", &mock_config(), &mock_messages(), &params).unwrap();
+    assert_eq!(req.body["format"], contact_schema());
+}
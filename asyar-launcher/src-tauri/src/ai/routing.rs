@@ -0,0 +1,255 @@
+//! Provider routing for agent runs: which provider/model pairs a run may
+//! fall back to, and what to do when a request to one of them fails.
+//!
+//! Only failures that happen before any of the response has streamed are
+//! routed. Once tokens have reached the user, switching providers would
+//! splice two different answers together, so a mid-stream error still ends
+//! the run. Every attempt rebuilds its request through the target's own
+//! `providers::build_request`; nothing is replayed across engines.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Upper bound on `RoutingPolicy::max_retries`, enforced on save.
+pub const MAX_RETRIES: u32 = 5;
+
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteTarget {
+    pub provider_id: String,
+    pub model_id: String,
+}
+
+/// Per-agent routing. The default policy — no retries, no fallbacks — is
+/// exactly the pre-routing behaviour, so existing agents are unaffected.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RoutingPolicy {
+    /// Tried in order once the agent's own provider/model gives up.
+    pub fallbacks: Vec<RouteTarget>,
+    /// Retries per target on rate limits, 5xx, timeouts and connection
+    /// failures before moving to the next fallback.
+    pub max_retries: u32,
+    /// Ollama connection to switch to as soon as a request can't connect
+    /// at all, skipping retries and the remaining cloud fallbacks.
+    pub offline_fallback: Option<RouteTarget>,
+}
+
+/// How one provider request ended. Stored as a short stable string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum AttemptOutcome {
+    Succeeded,
+    /// HTTP 429.
+    RateLimited,
+    /// HTTP 5xx, including Anthropic's 529 "overloaded".
+    ServerError,
+    /// HTTP 408 or the client-side request timeout.
+    Timeout,
+    /// Connection could not be established (DNS, refused, no network).
+    Offline,
+    /// Anything else: bad credentials, a rejected request, a stream that
+    /// broke after it started. Never retried against the same target.
+    Failed,
+}
+
+impl AttemptOutcome {
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            AttemptOutcome::RateLimited
+                | AttemptOutcome::ServerError
+                | AttemptOutcome::Timeout
+                | AttemptOutcome::Offline
+        )
+    }
+
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            AttemptOutcome::Succeeded => "succeeded",
+            AttemptOutcome::RateLimited => "rateLimited",
+            AttemptOutcome::ServerError => "serverError",
+            AttemptOutcome::Timeout => "timeout",
+            AttemptOutcome::Offline => "offline",
+            AttemptOutcome::Failed => "failed",
+        }
+    }
+
+    pub(crate) fn parse(value: &str) -> Self {
+        match value {
+            "succeeded" => AttemptOutcome::Succeeded,
+            "rateLimited" => AttemptOutcome::RateLimited,
+            "serverError" => AttemptOutcome::ServerError,
+            "timeout" => AttemptOutcome::Timeout,
+            "offline" => AttemptOutcome::Offline,
+            _ => AttemptOutcome::Failed,
+        }
+    }
+}
+
+pub fn classify_status(status: u16) -> AttemptOutcome {
+    match status {
+        200..=299 => AttemptOutcome::Succeeded,
+        408 => AttemptOutcome::Timeout,
+        429 => AttemptOutcome::RateLimited,
+        500..=599 => AttemptOutcome::ServerError,
+        _ => AttemptOutcome::Failed,
+    }
+}
+
+/// `Retry-After` in its delta-seconds form. The HTTP-date form is rare from
+/// model APIs and falls back to exponential backoff.
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+/// Delay before retry number `retry` (0-based): the server's `Retry-After`
+/// when it sent one, otherwise 0.5s doubling, both capped at 30s.
+pub fn backoff_delay(retry: u32, retry_after: Option<Duration>) -> Duration {
+    retry_after
+        .unwrap_or_else(|| BASE_BACKOFF.saturating_mul(2u32.saturating_pow(retry)))
+        .min(MAX_BACKOFF)
+}
+
+/// Walks a run through its routes. Generic over the route payload so the
+/// runner can carry resolved provider configs while tests use plain ids.
+/// Lives for the whole run: after a failover, later turns stay on the
+/// route that last worked instead of hammering the one that failed.
+pub struct Router<T> {
+    routes: Vec<T>,
+    offline: Option<T>,
+    max_retries: u32,
+    current: usize,
+    retries: u32,
+    offline_active: bool,
+}
+
+impl<T> Router<T> {
+    /// `routes` must be non-empty: the agent's own target first, then its
+    /// usable fallbacks in policy order.
+    pub fn new(routes: Vec<T>, offline: Option<T>, max_retries: u32) -> Self {
+        debug_assert!(!routes.is_empty());
+        Self {
+            routes,
+            offline,
+            max_retries,
+            current: 0,
+            retries: 0,
+            offline_active: false,
+        }
+    }
+
+    pub fn current(&self) -> &T {
+        match (&self.offline, self.offline_active) {
+            (Some(offline), true) => offline,
+            _ => &self.routes[self.current],
+        }
+    }
+
+    pub fn succeeded(&mut self) {
+        self.retries = 0;
+    }
+
+    /// Decide what follows a failed attempt: `Some(delay)` to wait and try
+    /// [`Self::current`] (which may now be a different route), `None` when
+    /// every option is spent.
+    pub fn next_after_failure(
+        &mut self,
+        outcome: AttemptOutcome,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration> {
+        if outcome == AttemptOutcome::Offline && !self.offline_active && self.offline.is_some() {
+            self.offline_active = true;
+            self.retries = 0;
+            return Some(Duration::ZERO);
+        }
+        if outcome.is_retryable() && self.retries < self.max_retries {
+            let delay = backoff_delay(self.retries, retry_after);
+            self.retries += 1;
+            return Some(delay);
+        }
+        if !self.offline_active && self.current + 1 < self.routes.len() {
+            self.current += 1;
+            self.retries = 0;
+            return Some(Duration::ZERO);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statuses_classify_into_retryable_and_terminal() {
+        assert_eq!(classify_status(429), AttemptOutcome::RateLimited);
+        assert_eq!(classify_status(503), AttemptOutcome::ServerError);
+        assert_eq!(classify_status(529), AttemptOutcome::ServerError);
+        assert_eq!(classify_status(408), AttemptOutcome::Timeout);
+        assert_eq!(classify_status(401), AttemptOutcome::Failed);
+        assert!(!classify_status(400).is_retryable());
+    }
+
+    #[test]
+    fn backoff_doubles_and_honours_retry_after() {
+        assert_eq!(backoff_delay(0, None), Duration::from_millis(500));
+        assert_eq!(backoff_delay(2, None), Duration::from_secs(2));
+        assert_eq!(backoff_delay(20, None), MAX_BACKOFF);
+        assert_eq!(backoff_delay(3, parse_retry_after(" 0 ")), Duration::ZERO);
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    }
+
+    #[test]
+    fn retries_then_falls_over_then_gives_up() {
+        let mut router = Router::new(vec!["openai", "anthropic"], None, 1);
+        assert!(router
+            .next_after_failure(AttemptOutcome::RateLimited, None)
+            .is_some());
+        assert_eq!(*router.current(), "openai");
+        assert_eq!(
+            router.next_after_failure(AttemptOutcome::RateLimited, None),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(*router.current(), "anthropic");
+        assert!(router
+            .next_after_failure(AttemptOutcome::ServerError, None)
+            .is_some());
+        assert_eq!(
+            router.next_after_failure(AttemptOutcome::ServerError, None),
+            None
+        );
+    }
+
+    #[test]
+    fn terminal_failures_skip_retries() {
+        let mut router = Router::new(vec!["openai", "anthropic"], None, 3);
+        router.next_after_failure(AttemptOutcome::Failed, None);
+        assert_eq!(*router.current(), "anthropic");
+    }
+
+    #[test]
+    fn offline_jumps_straight_to_the_local_route() {
+        let mut router = Router::new(vec!["openai", "anthropic"], Some("ollama"), 2);
+        assert_eq!(
+            router.next_after_failure(AttemptOutcome::Offline, None),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(*router.current(), "ollama");
+        router.next_after_failure(AttemptOutcome::Failed, None);
+        assert_eq!(*router.current(), "ollama");
+        assert_eq!(
+            router.next_after_failure(AttemptOutcome::Failed, None),
+            None
+        );
+    }
+
+    #[test]
+    fn default_policy_round_trips_from_an_empty_object() {
+        let policy: RoutingPolicy = serde_json::from_str("{}").unwrap();
+        assert_eq!(policy, RoutingPolicy::default());
+    }
+}
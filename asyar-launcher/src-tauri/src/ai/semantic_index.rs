@@ -0,0 +1,757 @@
+//! Hybrid (BM25 + vector) search over notes, clipboard history and snippets.
+//!
+//! Keyword ranking comes from the existing in-memory FTS5 indexes
+//! (`notes_fts`, `clipboard_fts`) plus a plain substring scan for snippets,
+//! which have no FTS table. Vector ranking comes from
+//! [`VectorIndex`](crate::storage::embeddings::VectorIndex). The ranked lists
+//! are merged with reciprocal rank fusion, which needs no score calibration
+//! between bm25 and cosine and degrades gracefully to keyword-only search
+//! when no embeddings exist yet or the embedding endpoint is unreachable.
+//!
+//! Indexing is explicit ([`sync`]): it hashes every document, embeds only the
+//! ones whose hash changed, and prunes vectors whose source row is gone.
+//! Secret-bearing rows (non-empty `redacted_kinds`) are never sent to an
+//! embedding provider.
+
+use crate::ai::embeddings::{self, EmbeddingSettings};
+use crate::error::AppError;
+use crate::storage::clipboard_fts::ClipboardFts;
+use crate::storage::embeddings::{self as store, DocKey, EmbeddingSource, VectorIndex};
+use crate::storage::notes_fts::NotesFts;
+use crate::storage::{clipboard, notes, snippets, DataStore};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+/// Documents per embeddings request during [`sync`].
+const EMBED_BATCH: usize = 32;
+
+/// Reciprocal rank fusion constant. 60 is the value from the original RRF
+/// paper and works without tuning for short result lists.
+const RRF_K: f32 = 60.0;
+
+/// Characters of document text returned with each hit.
+const SNIPPET_CHARS: usize = 200;
+
+pub struct SemanticIndexState {
+    settings: RwLock<Option<EmbeddingSettings>>,
+    /// Model key whose vectors are currently in `vectors`, if any.
+    loaded_model: RwLock<Option<String>>,
+    pub vectors: VectorIndex,
+    /// Serialises [`sync`] so two concurrent runs can't embed the same
+    /// documents twice.
+    sync_lock: tokio::sync::Mutex<()>,
+}
+
+impl Default for SemanticIndexState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SemanticIndexState {
+    pub fn new() -> Self {
+        Self {
+            settings: RwLock::new(None),
+            loaded_model: RwLock::new(None),
+            vectors: VectorIndex::new(),
+            sync_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// The configured embedding target, or a local Ollama install when the
+    /// user hasn't picked one.
+    pub fn settings(&self) -> EmbeddingSettings {
+        self.settings
+            .read()
+            .ok()
+            .and_then(|s| s.clone())
+            .unwrap_or_else(EmbeddingSettings::local_default)
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.settings.read().map(|s| s.is_some()).unwrap_or(false)
+    }
+
+    pub fn set_settings(&self, settings: Option<EmbeddingSettings>) -> Result<(), AppError> {
+        *self.settings.write().map_err(|_| AppError::Lock)? = settings;
+        Ok(())
+    }
+
+    /// True when the vectors in memory belong to the current model.
+    pub fn is_loaded(&self) -> bool {
+        let current = self.settings().model_key();
+        self.loaded_model
+            .read()
+            .map(|m| m.as_deref() == Some(current.as_str()))
+            .unwrap_or(false)
+    }
+
+    /// Replace the in-memory vectors with the stored ones for the current
+    /// model. Called at startup and whenever the model changes.
+    pub fn load_from_disk(
+        &self,
+        conn: &Connection,
+        master_key: &[u8; 32],
+    ) -> Result<usize, AppError> {
+        let model = self.settings().model_key();
+        self.vectors.clear()?;
+        let rows = store::load_all(conn, &model, master_key)?;
+        let count = rows.len();
+        for (key, vector) in rows {
+            self.vectors.upsert(key, vector)?;
+        }
+        *self.loaded_model.write().map_err(|_| AppError::Lock)? = Some(model);
+        Ok(count)
+    }
+}
+
+/// One unit of embeddable text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexDocument {
+    pub key: DocKey,
+    pub title: String,
+    pub text: String,
+}
+
+fn has_secrets(kinds: &Option<Vec<String>>) -> bool {
+    kinds.as_ref().is_some_and(|k| !k.is_empty())
+}
+
+fn first_line(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .unwrap_or_default()
+        .chars()
+        .take(80)
+        .collect()
+}
+
+fn clipboard_text(item_type: &str, content: &str) -> Option<String> {
+    match item_type {
+        "text" => Some(content.to_string()),
+        "html" => Some(crate::clipboard_markup::strip_html(content)),
+        "rtf" => Some(crate::clipboard_markup::strip_rtf(content)),
+        _ => None,
+    }
+}
+
+/// Every indexable document for `sources`, decrypted.
+pub fn collect_documents(
+    conn: &Connection,
+    master_key: &[u8; 32],
+    sources: &[EmbeddingSource],
+) -> Result<Vec<IndexDocument>, AppError> {
+    let mut docs = Vec::new();
+    if sources.contains(&EmbeddingSource::Note) {
+        for note in notes::get_all(conn, master_key)? {
+            if note.title.trim().is_empty() && note.body.trim().is_empty() {
+                continue;
+            }
+            docs.push(IndexDocument {
+                key: (EmbeddingSource::Note, note.id),
+                text: format!("{}\n\n{}", note.title, note.body),
+                title: note.title,
+            });
+        }
+    }
+    if sources.contains(&EmbeddingSource::Clipboard) {
+        for item in clipboard::get_all(conn, master_key)? {
+            if has_secrets(&item.redacted_kinds) {
+                continue;
+            }
+            let Some(text) = item
+                .content
+                .as_deref()
+                .and_then(|c| clipboard_text(&item.item_type, c))
+                .filter(|t| !t.trim().is_empty())
+            else {
+                continue;
+            };
+            docs.push(IndexDocument {
+                key: (EmbeddingSource::Clipboard, item.id),
+                title: first_line(&text),
+                text,
+            });
+        }
+    }
+    if sources.contains(&EmbeddingSource::Snippet) {
+        for snippet in snippets::get_all(conn, master_key)? {
+            if has_secrets(&snippet.redacted_kinds) || snippet.expansion.trim().is_empty() {
+                continue;
+            }
+            docs.push(IndexDocument {
+                key: (EmbeddingSource::Snippet, snippet.id),
+                text: format!("{}\n{}", snippet.name, snippet.expansion),
+                title: snippet.name,
+            });
+        }
+    }
+    Ok(docs)
+}
+
+fn content_hash(master_key: &[u8; 32], text: &str) -> Vec<u8> {
+    crate::crypto::hmac::hmac_sha256(master_key, text.as_bytes()).to_vec()
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexSyncReport {
+    pub model: String,
+    pub embedded: u32,
+    pub unchanged: u32,
+    pub removed: u32,
+}
+
+/// What [`sync`] has to do, worked out from the current documents and the
+/// stored hashes. Pure so the diffing can be tested without a provider.
+#[derive(Debug, Default)]
+struct SyncPlan {
+    pending: Vec<(IndexDocument, Vec<u8>)>,
+    unchanged: u32,
+    stale: Vec<DocKey>,
+}
+
+fn plan_sync(
+    docs: Vec<IndexDocument>,
+    stored: &HashMap<DocKey, Vec<u8>>,
+    master_key: &[u8; 32],
+) -> SyncPlan {
+    let mut plan = SyncPlan::default();
+    let live: HashSet<DocKey> = docs.iter().map(|d| d.key.clone()).collect();
+    for doc in docs {
+        let hash = content_hash(master_key, &doc.text);
+        if stored.get(&doc.key) == Some(&hash) {
+            plan.unchanged += 1;
+        } else {
+            plan.pending.push((doc, hash));
+        }
+    }
+    plan.stale = stored
+        .keys()
+        .filter(|key| !live.contains(*key))
+        .cloned()
+        .collect();
+    plan
+}
+
+fn now_ms() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as f64)
+        .unwrap_or(0.0)
+}
+
+async fn blocking<T, F>(f: F) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| AppError::Other(format!("semantic index task failed: {e}")))?
+}
+
+/// Bring the stored vectors up to date with notes, clipboard and snippets.
+/// Database work runs in `spawn_blocking` and no pooled connection is held
+/// across an embeddings request.
+pub async fn sync(
+    data_store: DataStore,
+    master_key: [u8; 32],
+    state: Arc<SemanticIndexState>,
+) -> Result<IndexSyncReport, AppError> {
+    let _guard = state.sync_lock.lock().await;
+    let settings = state.settings();
+    let model = settings.model_key();
+
+    let plan = {
+        let (data_store, state, model) = (data_store.clone(), state.clone(), model.clone());
+        blocking(move || {
+            let conn = data_store.conn()?;
+            store::remove_other_models(&conn, &model)?;
+            if !state.is_loaded() {
+                state.load_from_disk(&conn, &master_key)?;
+            }
+            let docs = collect_documents(&conn, &master_key, &EmbeddingSource::ALL)?;
+            let stored = store::hashes(&conn, &model)?;
+            let plan = plan_sync(docs, &stored, &master_key);
+            for (source, id) in &plan.stale {
+                store::remove(&conn, *source, id)?;
+            }
+            Ok(plan)
+        })
+        .await?
+    };
+
+    for key in &plan.stale {
+        state.vectors.remove(key)?;
+    }
+
+    let mut report = IndexSyncReport {
+        model: model.clone(),
+        embedded: 0,
+        unchanged: plan.unchanged,
+        removed: plan.stale.len() as u32,
+    };
+
+    for batch in plan.pending.chunks(EMBED_BATCH) {
+        let inputs: Vec<String> = batch.iter().map(|(doc, _)| doc.text.clone()).collect();
+        let vectors = embeddings::embed(&settings, &inputs).await?;
+        let rows: Vec<(DocKey, Vec<u8>, Vec<f32>)> = batch
+            .iter()
+            .zip(vectors)
+            .map(|((doc, hash), vector)| (doc.key.clone(), hash.clone(), vector))
+            .collect();
+        let written = {
+            let (data_store, model) = (data_store.clone(), model.clone());
+            blocking(move || {
+                let conn = data_store.conn()?;
+                let updated_at = now_ms();
+                for (key, hash, vector) in &rows {
+                    store::upsert(&conn, key, &model, hash, vector, updated_at, &master_key)?;
+                }
+                Ok(rows)
+            })
+            .await?
+        };
+        for (key, _, vector) in written {
+            state.vectors.upsert(key, vector)?;
+            report.embedded += 1;
+        }
+    }
+
+    Ok(report)
+}
+
+/// Merge ranked lists with reciprocal rank fusion: each document scores
+/// `Σ 1 / (k + rank)` over the lists it appears in. Ties break on id so the
+/// order is deterministic.
+pub fn reciprocal_rank_fusion(lists: &[Vec<DocKey>]) -> Vec<(DocKey, f32)> {
+    let mut scores: HashMap<&DocKey, f32> = HashMap::new();
+    for list in lists {
+        for (rank, key) in list.iter().enumerate() {
+            *scores.entry(key).or_default() += 1.0 / (RRF_K + rank as f32 + 1.0);
+        }
+    }
+    let mut fused: Vec<(DocKey, f32)> = scores.into_iter().map(|(k, s)| (k.clone(), s)).collect();
+    fused.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0 .1.cmp(&b.0 .1)));
+    fused
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticHit {
+    pub source: EmbeddingSource,
+    pub id: String,
+    pub title: String,
+    pub snippet: String,
+    /// Fused RRF score; only meaningful relative to other hits.
+    pub score: f32,
+    /// Cosine similarity when the hit came up in the vector ranking.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<f32>,
+    /// True when the hit came up in a keyword (BM25 or substring) ranking.
+    pub keyword_match: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticSearchResult {
+    pub hits: Vec<SemanticHit>,
+    /// `"ready"` when vectors took part in ranking, `"empty"` when nothing
+    /// has been indexed for the current model yet, `"unavailable"` when the
+    /// query could not be embedded. The last two are keyword-only results.
+    pub vector_state: &'static str,
+}
+
+/// Everything a hybrid query needs. Cheap to clone; shared by the Tauri
+/// command and the `semantic-search` agent tool.
+#[derive(Clone)]
+pub struct SemanticSearchContext {
+    pub data_store: DataStore,
+    pub master_key: [u8; 32],
+    pub state: Arc<SemanticIndexState>,
+    pub notes_fts: Arc<NotesFts>,
+    pub clipboard_fts: Arc<ClipboardFts>,
+}
+
+fn snippet_keyword_ranking(
+    conn: &Connection,
+    master_key: &[u8; 32],
+    query: &str,
+    limit: usize,
+) -> Result<Vec<DocKey>, AppError> {
+    let tokens: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    if tokens.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut all = snippets::get_all(conn, master_key)?;
+    // Pinned first, then `get_all`'s newest-first order.
+    all.sort_by_key(|s| !s.pinned);
+    Ok(all
+        .into_iter()
+        .filter(|s| {
+            let haystack = format!(
+                "{} {} {}",
+                s.name,
+                s.keyword.as_deref().unwrap_or_default(),
+                s.expansion
+            )
+            .to_lowercase();
+            tokens.iter().all(|t| haystack.contains(t.as_str()))
+        })
+        .take(limit)
+        .map(|s| (EmbeddingSource::Snippet, s.id))
+        .collect())
+}
+
+fn excerpt(text: &str) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= SNIPPET_CHARS {
+        collapsed
+    } else {
+        let cut: String = collapsed.chars().take(SNIPPET_CHARS).collect();
+        format!("{cut}…")
+    }
+}
+
+/// Title and excerpt for a document, or `None` if its row is gone.
+fn hydrate(
+    conn: &Connection,
+    master_key: &[u8; 32],
+    (source, id): &DocKey,
+    snippets_by_id: &mut Option<HashMap<String, snippets::Snippet>>,
+) -> Result<Option<(String, String)>, AppError> {
+    match source {
+        EmbeddingSource::Note => {
+            Ok(notes::get_by_id(conn, id, master_key)?.map(|n| (n.title, excerpt(&n.body))))
+        }
+        EmbeddingSource::Clipboard => {
+            Ok(clipboard::get_item(conn, id, master_key)?.and_then(|item| {
+                let text = clipboard_text(&item.item_type, item.content.as_deref()?)?;
+                Some((first_line(&text), excerpt(&text)))
+            }))
+        }
+        EmbeddingSource::Snippet => {
+            if snippets_by_id.is_none() {
+                *snippets_by_id = Some(
+                    snippets::get_all(conn, master_key)?
+                        .into_iter()
+                        .map(|s| (s.id.clone(), s))
+                        .collect(),
+                );
+            }
+            Ok(snippets_by_id
+                .as_ref()
+                .and_then(|m| m.get(id))
+                .map(|s| (s.name.clone(), excerpt(&s.expansion))))
+        }
+    }
+}
+
+/// Rank and hydrate. Synchronous and network-free: the caller supplies the
+/// already-embedded query vector (or `None` for keyword-only).
+pub fn rank(
+    ctx: &SemanticSearchContext,
+    conn: &Connection,
+    query: &str,
+    query_vector: Option<&[f32]>,
+    sources: &[EmbeddingSource],
+    limit: usize,
+) -> Result<Vec<SemanticHit>, AppError> {
+    // Over-fetch each list so fusion has something to work with.
+    let depth = (limit * 3).max(20);
+    let mut keyword_lists: Vec<Vec<DocKey>> = Vec::new();
+    if sources.contains(&EmbeddingSource::Note) && crate::storage::notes_fts::is_ready() {
+        keyword_lists.push(
+            ctx.notes_fts
+                .search(query, depth)?
+                .into_iter()
+                .map(|id| (EmbeddingSource::Note, id))
+                .collect(),
+        );
+    }
+    if sources.contains(&EmbeddingSource::Clipboard) && crate::storage::clipboard_fts::is_ready() {
+        keyword_lists.push(
+            ctx.clipboard_fts
+                .search(query, depth)?
+                .into_iter()
+                .map(|id| (EmbeddingSource::Clipboard, id))
+                .collect(),
+        );
+    }
+    if sources.contains(&EmbeddingSource::Snippet) {
+        keyword_lists.push(snippet_keyword_ranking(
+            conn,
+            &ctx.master_key,
+            query,
+            depth,
+        )?);
+    }
+    let keyword_hits: HashSet<DocKey> = keyword_lists.iter().flatten().cloned().collect();
+
+    let vector_ranking = match query_vector {
+        Some(v) => ctx.state.vectors.knn(v, depth, sources)?,
+        None => Vec::new(),
+    };
+    let similarities: HashMap<DocKey, f32> = vector_ranking.iter().cloned().collect();
+
+    let mut lists = keyword_lists;
+    lists.push(vector_ranking.into_iter().map(|(key, _)| key).collect());
+
+    let mut snippets_by_id = None;
+    let mut hits = Vec::new();
+    for (key, score) in reciprocal_rank_fusion(&lists) {
+        if hits.len() >= limit {
+            break;
+        }
+        let Some((title, snippet)) = hydrate(conn, &ctx.master_key, &key, &mut snippets_by_id)?
+        else {
+            continue;
+        };
+        hits.push(SemanticHit {
+            similarity: similarities.get(&key).copied(),
+            keyword_match: keyword_hits.contains(&key),
+            source: key.0,
+            id: key.1,
+            title,
+            snippet,
+            score,
+        });
+    }
+    Ok(hits)
+}
+
+/// Embed the query (when there is anything to compare it against) and run
+/// [`rank`]. An unreachable embedding endpoint falls back to keyword-only
+/// results instead of failing the search.
+pub async fn hybrid_search(
+    ctx: &SemanticSearchContext,
+    query: &str,
+    sources: &[EmbeddingSource],
+    limit: usize,
+) -> Result<SemanticSearchResult, AppError> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err(AppError::Validation("query must not be empty".to_string()));
+    }
+    let sources: Vec<EmbeddingSource> = if sources.is_empty() {
+        EmbeddingSource::ALL.to_vec()
+    } else {
+        sources.to_vec()
+    };
+
+    let (query_vector, vector_state) = if !ctx.state.is_loaded() || ctx.state.vectors.is_empty() {
+        (None, "empty")
+    } else {
+        match embeddings::embed(&ctx.state.settings(), &[query.to_string()]).await {
+            Ok(mut v) => (v.pop(), "ready"),
+            Err(e) => {
+                log::warn!("[semantic-index] query embedding failed, keyword-only: {e}");
+                (None, "unavailable")
+            }
+        }
+    };
+
+    let ctx = ctx.clone();
+    let query = query.to_string();
+    let hits = blocking(move || {
+        let conn = ctx.data_store.conn()?;
+        rank(
+            &ctx,
+            &conn,
+            &query,
+            query_vector.as_deref(),
+            &sources,
+            limit,
+        )
+    })
+    .await?;
+    Ok(SemanticSearchResult { hits, vector_state })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::notes::Note;
+    use crate::storage::snippets::Snippet;
+
+    fn test_key() -> [u8; 32] {
+        let mut k = [0u8; 32];
+        for (i, b) in k.iter_mut().enumerate() {
+            *b = (i * 31) as u8;
+        }
+        k
+    }
+
+    fn context() -> SemanticSearchContext {
+        SemanticSearchContext {
+            data_store: crate::storage::create_test_store(),
+            master_key: test_key(),
+            state: Arc::new(SemanticIndexState::new()),
+            notes_fts: Arc::new(NotesFts::new_in_memory().unwrap()),
+            clipboard_fts: Arc::new(ClipboardFts::new_in_memory().unwrap()),
+        }
+    }
+
+    fn seed_note(ctx: &SemanticSearchContext, id: &str, title: &str, body: &str) {
+        let conn = ctx.data_store.conn().unwrap();
+        let note = Note {
+            id: id.into(),
+            title: title.into(),
+            body: body.into(),
+            created_at: 1.0,
+            updated_at: 1.0,
+            pinned: false,
+        };
+        notes::upsert_with_fts(&conn, &note, &ctx.master_key, &ctx.notes_fts).unwrap();
+    }
+
+    fn seed_snippet(ctx: &SemanticSearchContext, id: &str, name: &str, redacted: bool) {
+        let conn = ctx.data_store.conn().unwrap();
+        let snippet = Snippet {
+            id: id.into(),
+            keyword: Some(format!(";{id}")),
+            expansion: format!("{name} expansion text"),
+            name: name.into(),
+            created_at: 1.0,
+            pinned: false,
+            redacted_kinds: redacted.then(|| vec!["aws_key".to_string()]),
+            trigger: Default::default(),
+            expand_mode: Default::default(),
+            app_scope: None,
+        };
+        snippets::upsert(&conn, &snippet, &ctx.master_key).unwrap();
+    }
+
+    #[test]
+    fn rrf_rewards_documents_ranked_in_several_lists() {
+        let a = (EmbeddingSource::Note, "a".to_string());
+        let b = (EmbeddingSource::Note, "b".to_string());
+        let c = (EmbeddingSource::Snippet, "c".to_string());
+        let fused =
+            reciprocal_rank_fusion(&[vec![a.clone(), b.clone()], vec![b.clone(), c.clone()]]);
+        let order: Vec<&str> = fused.iter().map(|(k, _)| k.1.as_str()).collect();
+        assert_eq!(order, vec!["b", "a", "c"]);
+    }
+
+    #[test]
+    fn collect_skips_secret_bearing_snippets() {
+        let ctx = context();
+        seed_note(&ctx, "n1", "Trip", "flights to Lisbon");
+        seed_snippet(&ctx, "s1", "Address", false);
+        seed_snippet(&ctx, "s2", "AWS", true);
+
+        let conn = ctx.data_store.conn().unwrap();
+        let docs = collect_documents(&conn, &ctx.master_key, &EmbeddingSource::ALL).unwrap();
+        let keys: Vec<&str> = docs.iter().map(|d| d.key.1.as_str()).collect();
+        assert!(keys.contains(&"n1"));
+        assert!(keys.contains(&"s1"));
+        assert!(!keys.contains(&"s2"));
+    }
+
+    #[test]
+    fn plan_sync_embeds_changed_and_prunes_missing() {
+        let key = test_key();
+        let doc = |id: &str, text: &str| IndexDocument {
+            key: (EmbeddingSource::Note, id.to_string()),
+            title: id.to_string(),
+            text: text.to_string(),
+        };
+        let mut stored = HashMap::new();
+        stored.insert(
+            (EmbeddingSource::Note, "same".to_string()),
+            content_hash(&key, "unchanged"),
+        );
+        stored.insert(
+            (EmbeddingSource::Note, "edited".to_string()),
+            content_hash(&key, "before"),
+        );
+        stored.insert(
+            (EmbeddingSource::Note, "deleted".to_string()),
+            content_hash(&key, "gone"),
+        );
+
+        let plan = plan_sync(
+            vec![
+                doc("same", "unchanged"),
+                doc("edited", "after"),
+                doc("new", "fresh"),
+            ],
+            &stored,
+            &key,
+        );
+        assert_eq!(plan.unchanged, 1);
+        let pending: Vec<&str> = plan.pending.iter().map(|(d, _)| d.key.1.as_str()).collect();
+        assert_eq!(pending, vec!["edited", "new"]);
+        assert_eq!(
+            plan.stale,
+            vec![(EmbeddingSource::Note, "deleted".to_string())]
+        );
+    }
+
+    #[test]
+    fn rank_fuses_keyword_and_vector_hits() {
+        let ctx = context();
+        seed_note(&ctx, "n1", "Packing list", "passport, charger");
+        seed_note(&ctx, "n2", "Holiday ideas", "somewhere warm by the sea");
+        crate::storage::notes_fts::mark_ready();
+
+        // "n2" only matches semantically; "n1" only by keyword.
+        ctx.state
+            .vectors
+            .upsert((EmbeddingSource::Note, "n2".into()), vec![0.0, 1.0])
+            .unwrap();
+        ctx.state
+            .vectors
+            .upsert((EmbeddingSource::Note, "n1".into()), vec![1.0, 0.0])
+            .unwrap();
+
+        let conn = ctx.data_store.conn().unwrap();
+        let hits = rank(
+            &ctx,
+            &conn,
+            "passport",
+            Some(&[0.1, 1.0]),
+            &[EmbeddingSource::Note],
+            10,
+        )
+        .unwrap();
+        assert_eq!(hits.len(), 2);
+        let n1 = hits.iter().find(|h| h.id == "n1").unwrap();
+        assert!(n1.keyword_match);
+        let n2 = hits.iter().find(|h| h.id == "n2").unwrap();
+        assert!(!n2.keyword_match);
+        assert!(n2.similarity.unwrap() > n1.similarity.unwrap());
+        assert_eq!(n2.title, "Holiday ideas");
+
+        let keyword_only =
+            rank(&ctx, &conn, "passport", None, &[EmbeddingSource::Note], 10).unwrap();
+        assert_eq!(keyword_only.len(), 1);
+        assert_eq!(keyword_only[0].id, "n1");
+    }
+
+    #[test]
+    fn snippet_keyword_ranking_matches_all_tokens() {
+        let ctx = context();
+        seed_snippet(&ctx, "s1", "Home address", false);
+        seed_snippet(&ctx, "s2", "Work phone", false);
+        let conn = ctx.data_store.conn().unwrap();
+        let hits = snippet_keyword_ranking(&conn, &ctx.master_key, "HOME expansion", 10).unwrap();
+        assert_eq!(hits, vec![(EmbeddingSource::Snippet, "s1".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn hybrid_search_without_vectors_is_keyword_only() {
+        let ctx = context();
+        seed_snippet(&ctx, "s1", "Home address", false);
+        let result = hybrid_search(&ctx, "home", &[EmbeddingSource::Snippet], 5)
+            .await
+            .unwrap();
+        assert_eq!(result.vector_state, "empty");
+        assert_eq!(result.hits.len(), 1);
+        assert!(hybrid_search(&ctx, "  ", &[], 5).await.is_err());
+    }
+}
@@ -0,0 +1,362 @@
+//! Structured output: holding an agent's final answer to a JSON Schema.
+//!
+//! Providers are asked for JSON natively where they support it (see the
+//! builders in `ai::providers`); the rest get [`schema_instruction`] in their
+//! system prompt. Either way the answer is checked here, because native modes
+//! differ in how much of JSON Schema they enforce and prompt-level requests
+//! enforce none of it.
+//!
+//! The validator covers the subset agents actually use — `type`, `enum`,
+//! `const`, `properties`, `required`, `additionalProperties`, `items`,
+//! `anyOf`, `oneOf`, and the length/count/range bounds. Unknown keywords are
+//! ignored rather than rejected, as JSON Schema itself specifies.
+
+use crate::error::AppError;
+use serde_json::Value;
+
+const TYPES: &[&str] = &[
+    "string", "number", "integer", "boolean", "object", "array", "null",
+];
+
+/// Cap on errors reported back, so a wildly wrong answer doesn't turn the
+/// correction prompt into an essay.
+const MAX_ERRORS: usize = 8;
+
+/// Rejects schemas the validator can't apply, at save time.
+pub fn check_schema(schema: &Value) -> Result<(), AppError> {
+    check_schema_at(schema, "#")
+}
+
+fn check_schema_at(schema: &Value, path: &str) -> Result<(), AppError> {
+    let Some(object) = schema.as_object() else {
+        return Err(AppError::Validation(format!(
+            "output schema {path} must be a JSON object"
+        )));
+    };
+    let known_type = |value: &Value| value.as_str().is_some_and(|name| TYPES.contains(&name));
+    match object.get("type") {
+        None => {}
+        Some(Value::Array(types)) if types.iter().all(known_type) => {}
+        Some(value) if known_type(value) => {}
+        Some(value) => {
+            return Err(AppError::Validation(format!(
+                "output schema {path}: unsupported type {value}"
+            )))
+        }
+    }
+    if let Some(properties) = object.get("properties") {
+        let Some(properties) = properties.as_object() else {
+            return Err(AppError::Validation(format!(
+                "output schema {path}: properties must be an object"
+            )));
+        };
+        for (name, property) in properties {
+            check_schema_at(property, &format!("{path}/properties/{name}"))?;
+        }
+    }
+    if let Some(items) = object.get("items") {
+        check_schema_at(items, &format!("{path}/items"))?;
+    }
+    for keyword in ["anyOf", "oneOf"] {
+        for (index, branch) in object
+            .get(keyword)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            check_schema_at(branch, &format!("{path}/{keyword}/{index}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Every way `value` fails `schema`, as `<pointer>: <problem>` lines.
+pub fn validate(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_at(schema, value, "", &mut errors);
+    errors.truncate(MAX_ERRORS);
+    errors
+}
+
+fn type_matches(name: &str, value: &Value) -> bool {
+    match name {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+    let at = if path.is_empty() { "/" } else { path };
+
+    if let Some(expected) = schema.get("type") {
+        let names: Vec<&str> = match expected {
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            other => other.as_str().into_iter().collect(),
+        };
+        if !names.is_empty() && !names.iter().any(|name| type_matches(name, value)) {
+            errors.push(format!("{at}: expected {}", names.join(" or ")));
+            return;
+        }
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            errors.push(format!(
+                "{at}: must be one of {}",
+                Value::Array(allowed.clone())
+            ));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            errors.push(format!("{at}: must equal {expected}"));
+        }
+    }
+    for keyword in ["anyOf", "oneOf"] {
+        if let Some(branches) = schema.get(keyword).and_then(Value::as_array) {
+            let matching = branches
+                .iter()
+                .filter(|branch| {
+                    let mut scratch = Vec::new();
+                    validate_at(branch, value, path, &mut scratch);
+                    scratch.is_empty()
+                })
+                .count();
+            let ok = if keyword == "oneOf" {
+                matching == 1
+            } else {
+                matching > 0
+            };
+            if !ok {
+                errors.push(format!("{at}: does not match {keyword}"));
+            }
+        }
+    }
+
+    match value {
+        Value::String(text) => {
+            let length = text.chars().count() as u64;
+            if bound(schema, "minLength").is_some_and(|min| length < min as u64) {
+                errors.push(format!("{at}: shorter than minLength"));
+            }
+            if bound(schema, "maxLength").is_some_and(|max| length > max as u64) {
+                errors.push(format!("{at}: longer than maxLength"));
+            }
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            if bound(schema, "minimum").is_some_and(|min| number < min) {
+                errors.push(format!("{at}: below minimum"));
+            }
+            if bound(schema, "maximum").is_some_and(|max| number > max) {
+                errors.push(format!("{at}: above maximum"));
+            }
+        }
+        Value::Array(items) => {
+            let count = items.len() as f64;
+            if bound(schema, "minItems").is_some_and(|min| count < min) {
+                errors.push(format!("{at}: fewer than minItems"));
+            }
+            if bound(schema, "maxItems").is_some_and(|max| count > max) {
+                errors.push(format!("{at}: more than maxItems"));
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_at(item_schema, item, &format!("{path}/{index}"), errors);
+                }
+            }
+        }
+        Value::Object(fields) => {
+            for name in schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+            {
+                if !fields.contains_key(name) {
+                    errors.push(format!("{at}: missing required property '{name}'"));
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (name, field) in fields {
+                match properties.and_then(|properties| properties.get(name)) {
+                    Some(property) => {
+                        validate_at(property, field, &format!("{path}/{name}"), errors)
+                    }
+                    None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                        errors.push(format!("{at}: unexpected property '{name}'"));
+                    }
+                    None => {}
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn bound(schema: &serde_json::Map<String, Value>, keyword: &str) -> Option<f64> {
+    schema.get(keyword).and_then(Value::as_f64)
+}
+
+/// Parses a model's answer, repairing the usual wrappers first: Markdown
+/// code fences and prose around a single JSON object or array.
+pub fn parse_answer(text: &str) -> Option<Value> {
+    let trimmed = text.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Some(value);
+    }
+    let unfenced = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.trim_end().strip_suffix("```"))
+        .map(str::trim);
+    if let Some(value) = unfenced.and_then(|inner| serde_json::from_str(inner).ok()) {
+        return Some(value);
+    }
+    [('{', '}'), ('[', ']')].iter().find_map(|(open, close)| {
+        let start = trimmed.find(*open)?;
+        let end = trimmed.rfind(*close)?;
+        (start < end)
+            .then(|| serde_json::from_str(&trimmed[start..=end]).ok())
+            .flatten()
+    })
+}
+
+/// Parses and validates an answer; the error lists what to fix.
+pub fn check_answer(schema: &Value, text: &str) -> Result<Value, Vec<String>> {
+    let Some(value) = parse_answer(text) else {
+        return Err(vec!["/: the reply is not valid JSON".to_string()]);
+    };
+    let errors = validate(schema, &value);
+    if errors.is_empty() {
+        Ok(value)
+    } else {
+        Err(errors)
+    }
+}
+
+/// System-prompt addition for providers without a native JSON mode.
+pub fn schema_instruction(schema: &Value) -> String {
+    format!(
+        "Reply with only a JSON value that matches this JSON Schema. Do not wrap it in Markdown or add any other text.\n\n{schema}"
+    )
+}
+
+/// The single follow-up sent when an answer fails validation.
+pub fn correction_prompt(errors: &[String]) -> String {
+    format!(
+        "Your reply did not match the required JSON Schema:\n{}\n\nReply again with only the corrected JSON.",
+        errors
+            .iter()
+            .map(|error| format!("- {error}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    )
+}
+
+/// Text handed to output actions: a JSON string verbatim, anything else
+/// pretty-printed.
+pub fn render(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => serde_json::to_string_pretty(other).unwrap_or_else(|_| other.to_string()),
+    }
+}
+
+/// Recovers the value behind [`render`]'s output, for answers replayed from
+/// the response cache, which keeps only the rendered text.
+pub fn unrender(schema: &Value, text: &str) -> Option<Value> {
+    if let Ok(value) = check_answer(schema, text) {
+        return Some(value);
+    }
+    let value = Value::String(text.to_string());
+    validate(schema, &value).is_empty().then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn contact_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string", "minLength": 1 },
+                "age": { "type": "integer", "minimum": 0 },
+                "tags": { "type": "array", "items": { "enum": ["work", "home"] } },
+            },
+            "required": ["name"],
+            "additionalProperties": false,
+        })
+    }
+
+    #[test]
+    fn valid_answers_pass() {
+        let value = json!({ "name": "Ada", "age": 36, "tags": ["work"] });
+        assert!(validate(&contact_schema(), &value).is_empty());
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_value() {
+        let value = json!({ "age": -1.5, "tags": ["gym"], "extra": true });
+        let mut errors = validate(&contact_schema(), &value);
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec![
+                "/: missing required property 'name'",
+                "/: unexpected property 'extra'",
+                "/age: expected integer",
+                "/tags/0: must be one of [\"work\",\"home\"]",
+            ]
+        );
+    }
+
+    #[test]
+    fn fenced_and_chatty_answers_are_repaired() {
+        assert_eq!(
+            parse_answer("```json\n{\"name\": \"Ada\"}\n```"),
+            Some(json!({ "name": "Ada" }))
+        );
+        assert_eq!(
+            parse_answer("Sure! Here you go: {\"name\": \"Ada\"} Hope that helps."),
+            Some(json!({ "name": "Ada" }))
+        );
+        assert_eq!(parse_answer("no json here"), None);
+    }
+
+    #[test]
+    fn unsupported_schemas_are_rejected_on_save() {
+        assert!(check_schema(&contact_schema()).is_ok());
+        assert!(check_schema(&json!("object")).is_err());
+        assert!(check_schema(&json!({ "type": "date" })).is_err());
+        assert!(check_schema(&json!({ "properties": { "a": 1 } })).is_err());
+    }
+
+    #[test]
+    fn render_unwraps_strings_only() {
+        assert_eq!(render(&json!("done")), "done");
+        assert_eq!(render(&json!({ "a": 1 })), "{\n  \"a\": 1\n}");
+    }
+
+    #[test]
+    fn unrender_round_trips_render() {
+        let contact = contact_schema();
+        let value = json!({ "name": "Ada" });
+        assert_eq!(unrender(&contact, &render(&value)), Some(value));
+        let string = json!({ "type": "string" });
+        assert_eq!(unrender(&string, "done"), Some(json!("done")));
+        assert_eq!(unrender(&contact, "done"), None);
+    }
+}
@@ -30,6 +30,43 @@ pub struct ChatMessage {
     pub tool_call_id: Option<String>,
     #[specta(type = Option<Vec<specta_typescript::Any>>)]
     pub provider_context: Option<Vec<serde_json::Value>>,
+    /// Attachments sent after `content`; user messages only.
+    pub parts: Option<Vec<ContentPart>>,
+}
+
+/// Where an image attachment's bytes come from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ImageSource {
+    /// Base64-encoded image data.
+    Bytes { data: String },
+    /// A clipboard-history image under `$APPDATA/clipboard_cache/`. Inlined
+    /// as `Bytes` before the message is stored; see `ai::attachments`.
+    ClipboardCache { path: String },
+}
+
+/// One typed piece of a multimodal message. Each provider builder encodes
+/// these into its own wire format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ContentPart {
+    Text {
+        text: String,
+    },
+    Image {
+        #[serde(rename = "mediaType")]
+        media_type: String,
+        source: ImageSource,
+    },
+    /// A document: `text` when it could be extracted, otherwise base64
+    /// `data` (PDFs only, which most providers read natively).
+    File {
+        name: String,
+        #[serde(rename = "mediaType")]
+        media_type: String,
+        text: Option<String>,
+        data: Option<String>,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
@@ -65,6 +102,11 @@ pub struct ChatParams {
     pub max_tokens: u32,
     pub system_prompt: Option<String>,
     pub tools: Option<Vec<ToolDefinition>>,
+    /// JSON Schema the reply must match. Engines with a native JSON mode
+    /// get it in the request; the rest get it as a system-prompt instruction.
+    #[serde(default)]
+    #[specta(type = Option<specta_typescript::Any>)]
+    pub response_schema: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,6 +116,20 @@ pub struct RequestSpec {
     pub body: serde_json::Value,
 }
 
+/// Token counts for one provider response, normalised across engines:
+/// `input_tokens` includes any prompt tokens served from cache, and
+/// `output_tokens` includes any reasoning tokens. `cached_tokens` and
+/// `reasoning_tokens` are the respective subsets, zero when the provider
+/// doesn't report them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenUsage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub cached_tokens: u32,
+    pub reasoning_tokens: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum ChatStreamEvent {
@@ -93,6 +149,9 @@ pub enum ChatStreamEvent {
         #[specta(type = specta_typescript::Any)]
         item: serde_json::Value,
     },
+    Usage {
+        usage: TokenUsage,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
@@ -121,6 +180,9 @@ pub enum ChatStreamEventPayload {
         #[specta(type = specta_typescript::Any)]
         item: serde_json::Value,
     },
+    Usage {
+        usage: TokenUsage,
+    },
     Done,
     Error {
         error: String,
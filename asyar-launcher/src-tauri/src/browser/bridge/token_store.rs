@@ -12,12 +12,21 @@ pub trait SecretBackend: Send + Sync {
     fn remove(&self, account: &str) -> Result<(), String>;
 }
 
-/// Production backend: persists secrets in the OS keychain via `keyring`.
-pub struct KeyringBackend;
+/// Production backend: persists secrets in the OS keychain via `keyring`,
+/// under one keychain service.
+pub struct KeyringBackend {
+    service: &'static str,
+}
+
+impl KeyringBackend {
+    pub const fn new(service: &'static str) -> Self {
+        Self { service }
+    }
+}
 
 impl SecretBackend for KeyringBackend {
     fn read(&self, account: &str) -> Result<Option<String>, String> {
-        let entry = keyring::Entry::new(KEYRING_SERVICE, account).map_err(|e| e.to_string())?;
+        let entry = keyring::Entry::new(self.service, account).map_err(|e| e.to_string())?;
         match entry.get_password() {
             Ok(t) => Ok(Some(t)),
             Err(keyring::Error::NoEntry) => Ok(None),
@@ -25,11 +34,11 @@ impl SecretBackend for KeyringBackend {
         }
     }
     fn write(&self, account: &str, secret: &str) -> Result<(), String> {
-        let entry = keyring::Entry::new(KEYRING_SERVICE, account).map_err(|e| e.to_string())?;
+        let entry = keyring::Entry::new(self.service, account).map_err(|e| e.to_string())?;
         entry.set_password(secret).map_err(|e| e.to_string())
     }
     fn remove(&self, account: &str) -> Result<(), String> {
-        let entry = keyring::Entry::new(KEYRING_SERVICE, account).map_err(|e| e.to_string())?;
+        let entry = keyring::Entry::new(self.service, account).map_err(|e| e.to_string())?;
         match entry.delete_credential() {
             Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
             Err(e) => Err(e.to_string()),
@@ -66,7 +75,7 @@ pub struct KeyringTokenStore {
 
 impl KeyringTokenStore {
     pub fn new() -> Self {
-        Self::with_backend(Arc::new(KeyringBackend))
+        Self::with_backend(Arc::new(KeyringBackend::new(KEYRING_SERVICE)))
     }
 
     pub fn with_backend(backend: Arc<dyn SecretBackend>) -> Self {
@@ -1,18 +1,33 @@
 use crate::agents::runner::{
     AgentRunConfig, AgentRunnerState, AgentStreamEvent, ExternalToolRequest, McpPermissionChoice,
+    SilentRunOutput,
 };
 use crate::agents::tool_executor::{execute_agent_tool, TauriAgentToolRuntime};
+use crate::agents::tool_policy::{self, ToolSandbox};
 use crate::agents::tools::ToolRegistryState;
+use crate::agents::triggers::{self, TriggerWatchers};
+use crate::ai::routing::{RouteTarget, RoutingPolicy, MAX_RETRIES};
+use crate::ai::types::ContentPart;
+use crate::crypto::keystore::KeystoreState;
 use crate::error::AppError;
+use crate::files_scope;
 use crate::mcp::McpSupervisor;
+use crate::storage::agent_tool_audit::{self, AgentToolAuditRow};
+use crate::storage::agent_tool_policies::{self, AgentToolPolicy};
+use crate::storage::agent_triggers::{self, AgentTrigger, TriggerOutput, TriggerSpec};
 use crate::storage::agents::{
-    backfill_thread_titles, delete_agent, delete_thread, find_run_origin, get_agent, get_thread,
-    insert_agent, insert_message, insert_thread, list_agents, list_messages_for_thread,
-    list_threads_for_agent, update_agent, update_thread_title, AgentRow, MessageRole, MessageRow,
-    RunOrigin, SilentInputSource, SilentOutputAction, ThreadRow,
+    backfill_thread_titles, delete_agent, delete_thread, find_run_origin, get_agent, get_message,
+    get_thread, get_thread_summary, insert_agent, insert_message, insert_message_after,
+    insert_thread, list_agents, list_attempts_for_run, list_thread_path,
+    list_thread_path_with_siblings, list_threads_for_agent, list_usage_for_thread,
+    newest_leaf_under, set_current_leaf, spend_summary, update_agent, update_thread_title,
+    AgentRow, AttemptRow, MessageRole, MessageRow, PathMessage, RunOrigin, SilentInputSource,
+    SilentOutputAction, SpendGroup, SpendRow, ThreadRow, ThreadSummary, UsageRow,
 };
+use crate::storage::memories::{self, Memory};
 use crate::storage::DataStore;
 use rusqlite::Connection;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::ipc::Channel;
@@ -37,6 +52,47 @@ fn require_non_empty(value: &str, field: &str) -> Result<String, AppError> {
     Ok(trimmed)
 }
 
+fn validate_budget(budget: Option<f64>) -> Result<Option<f64>, AppError> {
+    match budget {
+        Some(value) if !value.is_finite() || value <= 0.0 => Err(AppError::Validation(
+            "monthly_budget_usd must be a positive amount".to_string(),
+        )),
+        other => Ok(other),
+    }
+}
+
+fn validate_routing(policy: RoutingPolicy) -> Result<RoutingPolicy, AppError> {
+    if policy.max_retries > MAX_RETRIES {
+        return Err(AppError::Validation(format!(
+            "routing.max_retries must be at most {MAX_RETRIES}"
+        )));
+    }
+    let validate_target = |target: RouteTarget| -> Result<RouteTarget, AppError> {
+        Ok(RouteTarget {
+            provider_id: require_non_empty(&target.provider_id, "routing provider_id")?,
+            model_id: require_non_empty(&target.model_id, "routing model_id")?,
+        })
+    };
+    Ok(RoutingPolicy {
+        fallbacks: policy
+            .fallbacks
+            .into_iter()
+            .map(validate_target)
+            .collect::<Result<_, _>>()?,
+        max_retries: policy.max_retries,
+        offline_fallback: policy.offline_fallback.map(validate_target).transpose()?,
+    })
+}
+
+fn validate_output_schema(
+    schema: Option<serde_json::Value>,
+) -> Result<Option<serde_json::Value>, AppError> {
+    if let Some(schema) = &schema {
+        crate::ai::structured::check_schema(schema)?;
+    }
+    Ok(schema)
+}
+
 // ── Input structs ─────────────────────────────────────────────────────────────
 
 #[derive(serde::Deserialize, specta::Type)]
@@ -62,6 +118,33 @@ pub struct AgentCreateInput {
     pub cache_responses: Option<bool>,
     #[serde(default)]
     pub shortcode_trigger: Option<String>,
+    /// Monthly spend cap in USD; `None` leaves the agent uncapped.
+    #[serde(default)]
+    pub monthly_budget_usd: Option<f64>,
+    /// Retry/fallback rules; omitted means no retries and no fallbacks.
+    #[serde(default)]
+    pub routing: Option<RoutingPolicy>,
+    /// JSON Schema the final answer must satisfy; `None` keeps answers
+    /// free text.
+    #[serde(default)]
+    #[specta(type = Option<specta_typescript::Any>)]
+    pub output_schema: Option<serde_json::Value>,
+}
+
+/// A trigger to create (no `id`) or replace.
+#[derive(serde::Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentTriggerInput {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub agent_id: String,
+    pub spec: TriggerSpec,
+    #[serde(default)]
+    pub prompt: Option<String>,
+    pub output: TriggerOutput,
+    /// Defaults to `true`.
+    #[serde(default)]
+    pub enabled: Option<bool>,
 }
 
 #[derive(serde::Deserialize, specta::Type)]
@@ -84,6 +167,16 @@ pub struct AgentUpdateInput {
     pub cache_responses: Option<bool>,
     #[serde(default)]
     pub shortcode_trigger: Option<String>,
+    /// Replaces the stored cap, like `description`: omitting it clears it.
+    #[serde(default)]
+    pub monthly_budget_usd: Option<f64>,
+    /// Omitting it keeps the stored policy, like the silent fields.
+    #[serde(default)]
+    pub routing: Option<RoutingPolicy>,
+    /// Replaces the stored schema, like `description`: omitting it clears it.
+    #[serde(default)]
+    #[specta(type = Option<specta_typescript::Any>)]
+    pub output_schema: Option<serde_json::Value>,
 }
 
 #[derive(serde::Deserialize, specta::Type)]
@@ -112,6 +205,9 @@ pub fn agents_create_impl(
     let system_prompt = require_non_empty(&input.system_prompt, "system_prompt")?;
     let provider_id = require_non_empty(&input.provider_id, "provider_id")?;
     let model_id = require_non_empty(&input.model_id, "model_id")?;
+    let monthly_budget_usd = validate_budget(input.monthly_budget_usd)?;
+    let routing = validate_routing(input.routing.unwrap_or_default())?;
+    let output_schema = validate_output_schema(input.output_schema)?;
     let now = now_ms();
     let row = AgentRow {
         id: new_id(),
@@ -128,6 +224,9 @@ pub fn agents_create_impl(
             .unwrap_or(SilentOutputAction::ReplaceSelection),
         cache_responses: input.cache_responses.unwrap_or(false),
         shortcode_trigger: input.shortcode_trigger.unwrap_or_else(|| ":".to_string()),
+        monthly_budget_usd,
+        routing,
+        output_schema,
         created_at: Some(now),
         updated_at: Some(now),
     };
@@ -165,6 +264,9 @@ pub fn agents_update_impl(
         shortcode_trigger: input
             .shortcode_trigger
             .unwrap_or(existing.shortcode_trigger),
+        monthly_budget_usd: validate_budget(input.monthly_budget_usd)?,
+        routing: validate_routing(input.routing.unwrap_or(existing.routing))?,
+        output_schema: validate_output_schema(input.output_schema)?,
         created_at: existing.created_at,
         updated_at: Some(now_ms()),
     };
@@ -173,6 +275,9 @@ pub fn agents_update_impl(
 }
 
 pub fn agents_delete_impl(conn: &Connection, id: String) -> Result<(), AppError> {
+    agent_triggers::remove_for_agent(conn, &id)?;
+    agent_tool_policies::remove(conn, &id)?;
+    memories::remove_for_agent(conn, &id)?;
     delete_agent(conn, &id)
 }
 
@@ -195,6 +300,7 @@ pub fn agents_thread_create_impl(
         id: new_id(),
         agent_id: input.agent_id,
         title: input.title,
+        current_leaf_id: None,
         created_at: Some(now),
         updated_at: Some(now),
     };
@@ -244,6 +350,11 @@ pub fn agents_message_insert_impl(
 ) -> Result<MessageRow, AppError> {
     get_thread(conn, &input.thread_id)?
         .ok_or_else(|| AppError::NotFound(format!("thread {}", input.thread_id)))?;
+    if let Some(parts) = input.content.get("parts") {
+        let parts = serde_json::from_value::<Vec<ContentPart>>(parts.clone())
+            .map_err(|error| AppError::Validation(format!("invalid message parts: {error}")))?;
+        crate::ai::attachments::validate_parts(&parts)?;
+    }
     let row = MessageRow {
         id: new_id(),
         thread_id: input.thread_id,
@@ -251,16 +362,249 @@ pub fn agents_message_insert_impl(
         content: input.content,
         created_at: now_ms(),
         run_id: input.run_id,
+        parent_id: None,
     };
     insert_message(conn, &row)?;
-    Ok(row)
+    Ok(get_message(conn, &row.id)?.unwrap_or(row))
 }
 
 pub fn agents_messages_list_impl(
     conn: &Connection,
     thread_id: String,
-) -> Result<Vec<MessageRow>, AppError> {
-    list_messages_for_thread(conn, &thread_id)
+) -> Result<Vec<PathMessage>, AppError> {
+    list_thread_path_with_siblings(conn, &thread_id)
+}
+
+fn thread_message(
+    conn: &Connection,
+    thread_id: &str,
+    message_id: &str,
+) -> Result<MessageRow, AppError> {
+    get_message(conn, message_id)?
+        .filter(|message| message.thread_id == thread_id)
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "message '{message_id}' not found in thread '{thread_id}'"
+            ))
+        })
+}
+
+/// Forks an edited copy of a user message as its sibling and selects the new
+/// branch; the frontend then re-runs the thread from it. The original stays
+/// reachable through the branch switcher.
+pub fn agents_message_edit_impl(
+    conn: &Connection,
+    thread_id: String,
+    message_id: String,
+    text: String,
+) -> Result<MessageRow, AppError> {
+    let original = thread_message(conn, &thread_id, &message_id)?;
+    if original.role != MessageRole::User {
+        return Err(AppError::Validation(
+            "only user messages can be edited".to_string(),
+        ));
+    }
+    let mut content = original.content.clone();
+    content["text"] = serde_json::Value::String(text);
+    let has_parts = content
+        .get("parts")
+        .and_then(serde_json::Value::as_array)
+        .is_some_and(|parts| !parts.is_empty());
+    if content["text"]
+        .as_str()
+        .unwrap_or_default()
+        .trim()
+        .is_empty()
+        && !has_parts
+    {
+        return Err(AppError::Validation(
+            "edited message cannot be empty".to_string(),
+        ));
+    }
+    let row = MessageRow {
+        id: new_id(),
+        thread_id,
+        role: MessageRole::User,
+        content,
+        created_at: now_ms(),
+        run_id: None,
+        parent_id: original.parent_id.clone(),
+    };
+    insert_message_after(conn, &row, original.parent_id.as_deref())?;
+    Ok(row)
+}
+
+/// Rewinds the selected branch to its last user message so the next run
+/// answers it again as a sibling of the previous answer.
+pub fn agents_thread_regenerate_impl(conn: &Connection, thread_id: String) -> Result<(), AppError> {
+    let last_prompt = list_thread_path(conn, &thread_id)?
+        .into_iter()
+        .rev()
+        .find(|message| message.role == MessageRole::User)
+        .ok_or_else(|| {
+            AppError::Validation("thread has no user message to regenerate from".to_string())
+        })?;
+    set_current_leaf(conn, &thread_id, Some(&last_prompt.id))
+}
+
+/// Switches the thread to the branch containing `message_id`, continuing
+/// down to that branch's most recent message.
+pub fn agents_thread_select_branch_impl(
+    conn: &Connection,
+    thread_id: String,
+    message_id: String,
+) -> Result<(), AppError> {
+    thread_message(conn, &thread_id, &message_id)?;
+    let leaf = newest_leaf_under(conn, &message_id)?;
+    set_current_leaf(conn, &thread_id, Some(&leaf))
+}
+
+pub fn agents_thread_summary_impl(
+    conn: &Connection,
+    thread_id: String,
+) -> Result<Option<ThreadSummary>, AppError> {
+    get_thread_summary(conn, &thread_id)
+}
+
+/// Memories for review: every entry when `agent_id` is `None`, otherwise
+/// what that agent can recall (the global ones plus its own).
+pub fn agents_memories_list_impl(
+    conn: &Connection,
+    agent_id: Option<String>,
+    master_key: &[u8; 32],
+) -> Result<Vec<Memory>, AppError> {
+    match agent_id {
+        Some(agent_id) => memories::visible_to(conn, Some(&agent_id), master_key),
+        None => memories::list(conn, None, master_key),
+    }
+}
+
+pub fn agents_memory_delete_impl(conn: &Connection, id: String) -> Result<(), AppError> {
+    if !memories::remove(conn, &id)? {
+        return Err(AppError::NotFound(format!("memory '{id}' not found")));
+    }
+    Ok(())
+}
+
+pub fn agents_triggers_list_impl(
+    conn: &Connection,
+    agent_id: Option<String>,
+) -> Result<Vec<AgentTrigger>, AppError> {
+    agent_triggers::list(conn, agent_id.as_deref())
+}
+
+/// Validates and stores a trigger. Saving reschedules it from now, so an
+/// edited or re-enabled schedule never fires for a slot it was off during.
+pub fn agents_trigger_save_impl(
+    conn: &Connection,
+    input: AgentTriggerInput,
+) -> Result<AgentTrigger, AppError> {
+    triggers::validate(&input.spec)?;
+    get_agent(conn, &input.agent_id)?
+        .ok_or_else(|| AppError::NotFound(format!("agent {}", input.agent_id)))?;
+    let existing = match &input.id {
+        Some(id) => Some(
+            agent_triggers::get(conn, id)?
+                .ok_or_else(|| AppError::NotFound(format!("trigger '{id}' not found")))?,
+        ),
+        None => None,
+    };
+    let now = now_ms();
+    let enabled = input.enabled.unwrap_or(true);
+    let trigger = AgentTrigger {
+        id: input.id.unwrap_or_else(new_id),
+        agent_id: input.agent_id,
+        next_fire_at: if enabled {
+            triggers::next_fire_at(&input.spec, now)
+        } else {
+            None
+        },
+        spec: input.spec,
+        prompt: input.prompt.unwrap_or_default().trim().to_string(),
+        output: input.output,
+        enabled,
+        last_fired_at: existing.as_ref().and_then(|t| t.last_fired_at),
+        created_at: existing.as_ref().map_or(now, |t| t.created_at),
+        updated_at: now,
+    };
+    agent_triggers::upsert(conn, &trigger)?;
+    Ok(trigger)
+}
+
+pub fn agents_trigger_delete_impl(conn: &Connection, id: String) -> Result<(), AppError> {
+    if !agent_triggers::remove(conn, &id)? {
+        return Err(AppError::NotFound(format!("trigger '{id}' not found")));
+    }
+    Ok(())
+}
+
+/// The agent's shell/file policy, or the default when it has none.
+pub fn agents_tool_policy_get_impl(
+    conn: &Connection,
+    agent_id: String,
+) -> Result<AgentToolPolicy, AppError> {
+    Ok(agent_tool_policies::get(conn, &agent_id)?.unwrap_or_default())
+}
+
+pub fn agents_tool_policy_set_impl(
+    conn: &Connection,
+    agent_id: String,
+    policy: AgentToolPolicy,
+) -> Result<AgentToolPolicy, AppError> {
+    tool_policy::validate(&policy)?;
+    get_agent(conn, &agent_id)?.ok_or_else(|| AppError::NotFound(format!("agent {agent_id}")))?;
+    agent_tool_policies::set(conn, &agent_id, &policy, now_ms())?;
+    Ok(policy)
+}
+
+/// Default page size for `agents_tool_audit_list`.
+const DEFAULT_TOOL_AUDIT_LIMIT: u32 = 100;
+
+pub fn agents_tool_audit_list_impl(
+    conn: &Connection,
+    agent_id: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<AgentToolAuditRow>, AppError> {
+    agent_tool_audit::list_recent(
+        conn,
+        agent_id.as_deref(),
+        limit.unwrap_or(DEFAULT_TOOL_AUDIT_LIMIT),
+    )
+}
+
+/// Re-points the directory watches at the stored triggers after a change.
+fn sync_trigger_watchers(conn: &Connection, watchers: &TriggerWatchers) -> Result<(), AppError> {
+    watchers.sync(&agent_triggers::list_enabled(conn)?)
+}
+
+pub fn agents_thread_usage_impl(
+    conn: &Connection,
+    thread_id: String,
+) -> Result<Vec<UsageRow>, AppError> {
+    list_usage_for_thread(conn, &thread_id)
+}
+
+pub fn agents_spend_summary_impl(
+    conn: &Connection,
+    group: SpendGroup,
+    since_ms: Option<i64>,
+    until_ms: Option<i64>,
+) -> Result<Vec<SpendRow>, AppError> {
+    if let (Some(since), Some(until)) = (since_ms, until_ms) {
+        if since >= until {
+            return Err(AppError::Validation(
+                "since_ms must be earlier than until_ms".to_string(),
+            ));
+        }
+    }
+    spend_summary(conn, group, since_ms, until_ms)
+}
+
+pub fn agents_run_attempts_impl(
+    conn: &Connection,
+    run_id: String,
+) -> Result<Vec<AttemptRow>, AppError> {
+    list_attempts_for_run(conn, &run_id)
 }
 
 // ── Tauri command wrappers ────────────────────────────────────────────────────
@@ -297,11 +641,13 @@ pub async fn agents_update(
 pub async fn agents_delete(
     app: AppHandle,
     db: State<'_, DataStore>,
+    watchers: State<'_, Arc<TriggerWatchers>>,
     id: String,
 ) -> Result<(), AppError> {
     {
         let conn = db.conn()?;
         agents_delete_impl(&conn, id)?;
+        sync_trigger_watchers(&conn, &watchers)?;
     }
     let _ = app.emit("agents:changed", ());
     Ok(())
@@ -444,11 +790,215 @@ pub async fn agents_message_insert(
 pub async fn agents_messages_list(
     db: State<'_, DataStore>,
     thread_id: String,
-) -> Result<Vec<MessageRow>, AppError> {
+) -> Result<Vec<PathMessage>, AppError> {
     let conn = db.conn()?;
     agents_messages_list_impl(&conn, thread_id)
 }
 
+#[tauri::command]
+pub async fn agents_message_edit(
+    db: State<'_, DataStore>,
+    thread_id: String,
+    message_id: String,
+    text: String,
+) -> Result<MessageRow, AppError> {
+    let conn = db.conn()?;
+    agents_message_edit_impl(&conn, thread_id, message_id, text)
+}
+
+#[tauri::command]
+pub async fn agents_thread_regenerate(
+    db: State<'_, DataStore>,
+    thread_id: String,
+) -> Result<(), AppError> {
+    let conn = db.conn()?;
+    agents_thread_regenerate_impl(&conn, thread_id)
+}
+
+#[tauri::command]
+pub async fn agents_thread_select_branch(
+    db: State<'_, DataStore>,
+    thread_id: String,
+    message_id: String,
+) -> Result<(), AppError> {
+    let conn = db.conn()?;
+    agents_thread_select_branch_impl(&conn, thread_id, message_id)
+}
+
+#[tauri::command]
+pub async fn agents_thread_summary(
+    db: State<'_, DataStore>,
+    thread_id: String,
+) -> Result<Option<ThreadSummary>, AppError> {
+    let conn = db.conn()?;
+    agents_thread_summary_impl(&conn, thread_id)
+}
+
+#[tauri::command]
+pub async fn agents_memories_list(
+    db: State<'_, DataStore>,
+    keystore: State<'_, KeystoreState>,
+    agent_id: Option<String>,
+) -> Result<Vec<Memory>, AppError> {
+    let conn = db.conn()?;
+    agents_memories_list_impl(&conn, agent_id, keystore.master_key())
+}
+
+#[tauri::command]
+pub async fn agents_memory_delete(db: State<'_, DataStore>, id: String) -> Result<(), AppError> {
+    let conn = db.conn()?;
+    agents_memory_delete_impl(&conn, id)
+}
+
+#[tauri::command]
+pub async fn agents_triggers_list(
+    db: State<'_, DataStore>,
+    agent_id: Option<String>,
+) -> Result<Vec<AgentTrigger>, AppError> {
+    let conn = db.conn()?;
+    agents_triggers_list_impl(&conn, agent_id)
+}
+
+#[tauri::command]
+pub async fn agents_trigger_save(
+    db: State<'_, DataStore>,
+    watchers: State<'_, Arc<TriggerWatchers>>,
+    input: AgentTriggerInput,
+) -> Result<AgentTrigger, AppError> {
+    let conn = db.conn()?;
+    let trigger = agents_trigger_save_impl(&conn, input)?;
+    sync_trigger_watchers(&conn, &watchers)?;
+    Ok(trigger)
+}
+
+#[tauri::command]
+pub async fn agents_trigger_delete(
+    db: State<'_, DataStore>,
+    watchers: State<'_, Arc<TriggerWatchers>>,
+    id: String,
+) -> Result<(), AppError> {
+    let conn = db.conn()?;
+    agents_trigger_delete_impl(&conn, id)?;
+    sync_trigger_watchers(&conn, &watchers)
+}
+
+#[tauri::command]
+pub async fn agents_tool_policy_get(
+    db: State<'_, DataStore>,
+    agent_id: String,
+) -> Result<AgentToolPolicy, AppError> {
+    let conn = db.conn()?;
+    agents_tool_policy_get_impl(&conn, agent_id)
+}
+
+#[tauri::command]
+pub async fn agents_tool_policy_set(
+    db: State<'_, DataStore>,
+    agent_id: String,
+    policy: AgentToolPolicy,
+) -> Result<AgentToolPolicy, AppError> {
+    let conn = db.conn()?;
+    agents_tool_policy_set_impl(&conn, agent_id, policy)
+}
+
+#[tauri::command]
+pub async fn agents_tool_audit_list(
+    db: State<'_, DataStore>,
+    agent_id: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<AgentToolAuditRow>, AppError> {
+    let conn = db.conn()?;
+    agents_tool_audit_list_impl(&conn, agent_id, limit)
+}
+
+/// The user's answer to a confirm-mode `fs-write`. Returns `false` when
+/// the write is no longer waiting (answered elsewhere or timed out).
+#[tauri::command]
+pub async fn agents_tool_write_resolve(
+    sandbox: State<'_, Arc<ToolSandbox>>,
+    id: String,
+    approved: bool,
+) -> Result<bool, AppError> {
+    sandbox.resolve_write(&id, approved)
+}
+
+#[tauri::command]
+pub async fn agents_thread_usage(
+    db: State<'_, DataStore>,
+    thread_id: String,
+) -> Result<Vec<UsageRow>, AppError> {
+    let conn = db.conn()?;
+    agents_thread_usage_impl(&conn, thread_id)
+}
+
+/// Token and cost totals grouped by day, agent, provider, model or thread,
+/// over an optional `[since_ms, until_ms)` window.
+#[tauri::command]
+pub async fn agents_spend_summary(
+    db: State<'_, DataStore>,
+    group: SpendGroup,
+    since_ms: Option<i64>,
+    until_ms: Option<i64>,
+) -> Result<Vec<SpendRow>, AppError> {
+    let conn = db.conn()?;
+    agents_spend_summary_impl(&conn, group, since_ms, until_ms)
+}
+
+/// Every provider request a run made, including retries and fallbacks.
+#[tauri::command]
+pub async fn agents_run_attempts(
+    db: State<'_, DataStore>,
+    run_id: String,
+) -> Result<Vec<AttemptRow>, AppError> {
+    let conn = db.conn()?;
+    agents_run_attempts_impl(&conn, run_id)
+}
+
+/// Reads `path` into an attachment part once it has cleared the
+/// `files_scope` deny-list, both as requested and after resolving symlinks,
+/// so a picked file can't hand the model an SSH key or the launcher's own
+/// state.
+pub(crate) fn agents_attachment_from_file_impl(
+    path: &str,
+    home: &Path,
+    extra_deny: &[PathBuf],
+) -> Result<ContentPart, AppError> {
+    let requested = Path::new(path);
+    if !requested.is_absolute() {
+        return Err(AppError::Validation(format!(
+            "Attachment path must be absolute, got: '{path}'"
+        )));
+    }
+    let normalized = crate::commands::files::normalize_path(requested);
+    files_scope::check_path_denied(&normalized, home, extra_deny)?;
+    let canonical = dunce::canonicalize(&normalized).map_err(|error| {
+        AppError::Validation(format!(
+            "Attachment '{}' does not exist or is not accessible: {error}",
+            normalized.display()
+        ))
+    })?;
+    let canonical_home = dunce::canonicalize(home).unwrap_or_else(|_| home.to_path_buf());
+    let canonical_extra_deny: Vec<PathBuf> = extra_deny
+        .iter()
+        .map(|root| dunce::canonicalize(root).unwrap_or_else(|_| root.clone()))
+        .collect();
+    files_scope::check_path_denied(&canonical, &canonical_home, &canonical_extra_deny)?;
+    crate::ai::attachments::file_part(&canonical)
+}
+
+/// Reads a file picked from file search into an attachment part, extracting
+/// its text where possible.
+#[tauri::command]
+pub async fn agents_attachment_from_file(
+    app: AppHandle,
+    path: String,
+) -> Result<ContentPart, AppError> {
+    let (home, extra_deny) = crate::commands::files::extension_scope_env(&app)?;
+    tokio::task::spawn_blocking(move || agents_attachment_from_file_impl(&path, &home, &extra_deny))
+        .await
+        .map_err(|error| AppError::Other(error.to_string()))?
+}
+
 #[tauri::command]
 #[allow(clippy::too_many_arguments)] // Tauri injects state alongside the typed wire arguments.
 pub async fn agents_run_thread(
@@ -460,11 +1010,19 @@ pub async fn agents_run_thread(
     agent_id: String,
     thread_id: String,
     user_text: String,
+    attachments: Option<Vec<ContentPart>>,
     run_id: Option<String>,
     config: AgentRunConfig,
     stream_id: String,
     on_event: Channel<AgentStreamEvent>,
 ) -> Result<(), AppError> {
+    let attachments = match attachments.filter(|parts| !parts.is_empty()) {
+        Some(parts) => {
+            let app_data_dir = crate::extensions::get_app_data_dir(&app)?;
+            crate::ai::attachments::resolve_parts(&app_data_dir, parts)?
+        }
+        None => Vec::new(),
+    };
     let cancellation = runner_state.begin_run(&stream_id)?;
 
     // Per-caller event sink: the channel is scoped to the invoking view,
@@ -481,12 +1039,10 @@ pub async fn agents_run_thread(
         store.inner().clone(),
     );
     let dispatch_registry = registry.inner().clone();
-    let dispatch_agent_id = agent_id.clone();
     let dispatch_external = move |request: ExternalToolRequest| {
         let runtime = tool_runtime.clone();
         let registry = dispatch_registry.clone();
-        let agent_id = dispatch_agent_id.clone();
-        async move { execute_agent_tool(&registry, &runtime, &agent_id, &request).await }
+        async move { execute_agent_tool(&registry, &runtime, &request).await }
     };
 
     let result = crate::agents::runner::run_thread_loop_impl(
@@ -495,6 +1051,7 @@ pub async fn agents_run_thread(
         &agent_id,
         &thread_id,
         user_text,
+        attachments,
         run_id,
         config,
         emit_event,
@@ -522,7 +1079,7 @@ pub async fn agents_run_silent(
     config: AgentRunConfig,
     stream_id: String,
     on_event: Channel<AgentStreamEvent>,
-) -> Result<String, AppError> {
+) -> Result<SilentRunOutput, AppError> {
     let agent = {
         let conn = store.conn()?;
         let (agent, healed) = crate::agents::lifecycle::resolve_runnable_agent(
@@ -541,8 +1098,12 @@ pub async fn agents_run_silent(
     use tauri::Manager;
     if agent.cache_responses {
         if let Some(cache) = app.try_state::<crate::agents::cache::AgentResponseCache>() {
-            if let Some(cached_output) = cache.get(&agent.id, &user_text) {
-                return Ok(cached_output);
+            if let Some(text) = cache.get(&agent.id, &user_text) {
+                let structured = agent
+                    .output_schema
+                    .as_ref()
+                    .and_then(|schema| crate::ai::structured::unrender(schema, &text));
+                return Ok(SilentRunOutput { text, structured });
             }
         }
     }
@@ -564,15 +1125,14 @@ pub async fn agents_run_silent(
         store.inner().clone(),
     );
     let dispatch_registry = registry.inner().clone();
-    let dispatch_agent_id = agent.id.clone();
     let dispatch_external = move |request: ExternalToolRequest| {
         let runtime = tool_runtime.clone();
         let registry = dispatch_registry.clone();
-        let agent_id = dispatch_agent_id.clone();
-        async move { execute_agent_tool(&registry, &runtime, &agent_id, &request).await }
+        async move { execute_agent_tool(&registry, &runtime, &request).await }
     };
 
     let result = crate::agents::runner::run_silent_agent_loop_impl(
+        &store,
         &agent,
         &registry,
         user_text,
@@ -584,17 +1144,17 @@ pub async fn agents_run_silent(
     .await;
     let cleanup = runner_state.finish_run(&stream_id);
 
-    if let Ok(ref text) = result {
+    if let Ok(ref output) = result {
         if agent.cache_responses {
             if let Some(cache) = app.try_state::<crate::agents::cache::AgentResponseCache>() {
-                cache.set(&agent.id, &cache_key, text);
+                cache.set(&agent.id, &cache_key, &output.text);
             }
         }
     }
 
     match (result, cleanup) {
         (Err(error), _) | (Ok(_), Err(error)) => Err(error),
-        (Ok(text), Ok(())) => Ok(text),
+        (Ok(output), Ok(())) => Ok(output),
     }
 }
 
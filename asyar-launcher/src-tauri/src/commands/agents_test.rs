@@ -1,12 +1,19 @@
+use crate::ai::routing::{RouteTarget, RoutingPolicy};
 use crate::commands::agents::{
-    agents_create_impl, agents_delete_impl, agents_get_impl, agents_list_impl,
-    agents_message_insert_impl, agents_messages_list_impl, agents_thread_create_impl,
-    agents_thread_delete_impl, agents_threads_list_impl, agents_update_impl, AgentCreateInput,
+    agents_attachment_from_file_impl, agents_create_impl, agents_delete_impl, agents_get_impl,
+    agents_list_impl, agents_memories_list_impl, agents_memory_delete_impl,
+    agents_message_edit_impl, agents_message_insert_impl, agents_messages_list_impl,
+    agents_thread_create_impl, agents_thread_delete_impl, agents_thread_regenerate_impl,
+    agents_thread_select_branch_impl, agents_threads_list_impl, agents_tool_policy_get_impl,
+    agents_tool_policy_set_impl, agents_trigger_delete_impl, agents_trigger_save_impl,
+    agents_triggers_list_impl, agents_update_impl, AgentCreateInput, AgentTriggerInput,
     AgentUpdateInput, MessageInsertInput, ThreadCreateInput,
 };
 use crate::error::AppError;
+use crate::storage::agent_tool_policies::{AgentToolPolicy, WriteMode};
+use crate::storage::agent_triggers::{TriggerOutput, TriggerSpec};
 use crate::storage::agents::{
-    insert_thread, MessageRole, SilentInputSource, SilentOutputAction, ThreadRow,
+    insert_thread, MessageRole, MessageRow, SilentInputSource, SilentOutputAction, ThreadRow,
 };
 use rusqlite::Connection;
 
@@ -14,6 +21,9 @@ fn make_conn() -> Connection {
     let conn = Connection::open_in_memory().expect("in-memory db");
     conn.execute("PRAGMA foreign_keys = ON", []).unwrap();
     crate::storage::agents::init_table(&conn).unwrap();
+    crate::storage::agent_triggers::init_table(&conn).unwrap();
+    crate::storage::agent_tool_policies::init_table(&conn).unwrap();
+    crate::storage::memories::init_table(&conn).unwrap();
     conn
 }
 
@@ -30,6 +40,9 @@ fn valid_create_input() -> AgentCreateInput {
         output_action: None,
         cache_responses: None,
         shortcode_trigger: None,
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
     }
 }
 
@@ -70,6 +83,9 @@ fn agents_create_impl_rejects_empty_name() {
         output_action: None,
         cache_responses: None,
         shortcode_trigger: None,
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
     };
     let result = agents_create_impl(&conn, input);
     assert!(
@@ -93,6 +109,9 @@ fn agents_create_impl_rejects_empty_system_prompt() {
         output_action: None,
         cache_responses: None,
         shortcode_trigger: None,
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
     };
     let result = agents_create_impl(&conn, input);
     assert!(
@@ -116,6 +135,9 @@ fn agents_create_impl_rejects_empty_provider_id() {
         output_action: None,
         cache_responses: None,
         shortcode_trigger: None,
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
     };
     let result = agents_create_impl(&conn, input);
     assert!(
@@ -139,6 +161,9 @@ fn agents_create_impl_rejects_empty_model_id() {
         output_action: None,
         cache_responses: None,
         shortcode_trigger: None,
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
     };
     let result = agents_create_impl(&conn, input);
     assert!(
@@ -167,6 +192,9 @@ fn agents_update_impl_updates_existing() {
         output_action: None,
         cache_responses: None,
         shortcode_trigger: None,
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
     };
     let updated = agents_update_impl(&conn, update_input).unwrap();
 
@@ -197,6 +225,9 @@ fn agents_update_impl_errors_when_id_unknown() {
         output_action: None,
         cache_responses: None,
         shortcode_trigger: None,
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
     };
     let result = agents_update_impl(&conn, input);
     assert!(
@@ -238,6 +269,9 @@ fn agents_list_impl_returns_all() {
         output_action: None,
         cache_responses: None,
         shortcode_trigger: None,
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
     };
     let input2 = AgentCreateInput {
         name: "Second".to_string(),
@@ -251,6 +285,9 @@ fn agents_list_impl_returns_all() {
         output_action: None,
         cache_responses: None,
         shortcode_trigger: None,
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
     };
     agents_create_impl(&conn, input1).unwrap();
     agents_create_impl(&conn, input2).unwrap();
@@ -329,6 +366,7 @@ fn agents_threads_list_impl_orders_desc() {
         id: "t-older-00000000-0000-0000-0000-000000000001".to_string(),
         agent_id: agent.id.clone(),
         title: Some("Older".to_string()),
+        current_leaf_id: None,
         created_at: Some(1000),
         updated_at: Some(1000),
     };
@@ -336,6 +374,7 @@ fn agents_threads_list_impl_orders_desc() {
         id: "t-newer-00000000-0000-0000-0000-000000000002".to_string(),
         agent_id: agent.id.clone(),
         title: Some("Newer".to_string()),
+        current_leaf_id: None,
         created_at: Some(5000),
         updated_at: Some(5000),
     };
@@ -399,7 +438,37 @@ fn agents_message_insert_impl_inserts() {
 
     let messages = agents_messages_list_impl(&conn, thread.id).unwrap();
     assert_eq!(messages.len(), 1);
-    assert_eq!(messages[0].id, msg.id);
+    assert_eq!(messages[0].message.id, msg.id);
+}
+
+#[test]
+fn agents_message_insert_impl_rejects_unresolved_attachments() {
+    let conn = make_conn();
+    let agent = agents_create_impl(&conn, valid_create_input()).unwrap();
+    let thread = agents_thread_create_impl(
+        &conn,
+        ThreadCreateInput {
+            agent_id: agent.id.clone(),
+            title: None,
+        },
+    )
+    .unwrap();
+
+    let input = MessageInsertInput {
+        thread_id: thread.id,
+        role: MessageRole::User,
+        content: serde_json::json!({
+            "text": "look",
+            "parts": [{
+                "type": "image",
+                "mediaType": "image/png",
+                "source": { "type": "clipboardCache", "path": "/etc/passwd" },
+            }],
+        }),
+        run_id: None,
+    };
+    let result = agents_message_insert_impl(&conn, input);
+    assert!(matches!(result, Err(AppError::Validation(_))));
 }
 
 // ── agents_messages_list ─────────────────────────────────────────────────────
@@ -426,6 +495,7 @@ fn agents_messages_list_impl_orders_asc() {
         content: serde_json::json!({"text": "first"}),
         created_at: 1000,
         run_id: None,
+        parent_id: None,
     };
     let m2 = crate::storage::agents::MessageRow {
         id: "msg-late-00000000-0000-0000-0000-000000000002".to_string(),
@@ -434,14 +504,98 @@ fn agents_messages_list_impl_orders_asc() {
         content: serde_json::json!({"text": "second"}),
         created_at: 9000,
         run_id: None,
+        parent_id: None,
     };
     crate::storage::agents::insert_message(&conn, &m1).unwrap();
     crate::storage::agents::insert_message(&conn, &m2).unwrap();
 
     let messages = agents_messages_list_impl(&conn, thread.id).unwrap();
     assert_eq!(messages.len(), 2, "expected 2 messages");
-    assert_eq!(messages[0].id, m1.id, "earliest message must be first");
-    assert_eq!(messages[1].id, m2.id);
+    assert_eq!(
+        messages[0].message.id, m1.id,
+        "earliest message must be first"
+    );
+    assert_eq!(messages[1].message.id, m2.id);
+}
+
+fn insert_exchange(conn: &Connection, thread_id: &str) -> (MessageRow, MessageRow) {
+    let prompt = agents_message_insert_impl(
+        conn,
+        MessageInsertInput {
+            thread_id: thread_id.to_string(),
+            role: MessageRole::User,
+            content: serde_json::json!({"text": "first"}),
+            run_id: None,
+        },
+    )
+    .unwrap();
+    let answer = agents_message_insert_impl(
+        conn,
+        MessageInsertInput {
+            thread_id: thread_id.to_string(),
+            role: MessageRole::Assistant,
+            content: serde_json::json!({"text": "answer"}),
+            run_id: None,
+        },
+    )
+    .unwrap();
+    (prompt, answer)
+}
+
+#[test]
+fn agents_message_edit_impl_forks_a_sibling_prompt() {
+    let conn = make_conn();
+    let agent = agents_create_impl(&conn, valid_create_input()).unwrap();
+    let thread = agents_thread_create_impl(
+        &conn,
+        ThreadCreateInput {
+            agent_id: agent.id.clone(),
+            title: None,
+        },
+    )
+    .unwrap();
+    let (prompt, answer) = insert_exchange(&conn, &thread.id);
+    assert_eq!(answer.parent_id.as_deref(), Some(prompt.id.as_str()));
+
+    let edited =
+        agents_message_edit_impl(&conn, thread.id.clone(), prompt.id.clone(), "second".into())
+            .unwrap();
+    assert_eq!(edited.content["text"], "second");
+
+    let path = agents_messages_list_impl(&conn, thread.id.clone()).unwrap();
+    assert_eq!(path.len(), 1);
+    assert_eq!(path[0].message.id, edited.id);
+    assert_eq!(path[0].sibling_ids, vec![prompt.id.clone(), edited.id]);
+
+    agents_thread_select_branch_impl(&conn, thread.id.clone(), prompt.id).unwrap();
+    let path = agents_messages_list_impl(&conn, thread.id.clone()).unwrap();
+    assert_eq!(path.len(), 2, "switching back resumes at the old answer");
+    assert_eq!(path[1].message.id, answer.id);
+
+    let result = agents_message_edit_impl(&conn, thread.id, answer.id, "x".into());
+    assert!(matches!(result, Err(AppError::Validation(_))));
+}
+
+#[test]
+fn agents_thread_regenerate_impl_rewinds_to_the_last_prompt() {
+    let conn = make_conn();
+    let agent = agents_create_impl(&conn, valid_create_input()).unwrap();
+    let thread = agents_thread_create_impl(
+        &conn,
+        ThreadCreateInput {
+            agent_id: agent.id.clone(),
+            title: None,
+        },
+    )
+    .unwrap();
+    let result = agents_thread_regenerate_impl(&conn, thread.id.clone());
+    assert!(matches!(result, Err(AppError::Validation(_))));
+
+    let (prompt, _) = insert_exchange(&conn, &thread.id);
+    agents_thread_regenerate_impl(&conn, thread.id.clone()).unwrap();
+    let path = agents_messages_list_impl(&conn, thread.id).unwrap();
+    assert_eq!(path.len(), 1);
+    assert_eq!(path[0].message.id, prompt.id);
 }
 
 // ── Silent AI command create/update ──────────────────────────────────────────
@@ -470,6 +624,9 @@ fn agents_create_impl_persists_silent_fields_when_provided() {
         output_action: Some(SilentOutputAction::ReplaceSelection),
         cache_responses: Some(true),
         shortcode_trigger: Some(":".to_string()),
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
     };
     let row = agents_create_impl(&conn, input).unwrap();
     assert!(row.silent);
@@ -498,6 +655,9 @@ fn agents_update_impl_preserves_silent_fields_when_unspecified() {
             output_action: Some(SilentOutputAction::Copy),
             cache_responses: Some(true),
             shortcode_trigger: Some(":".to_string()),
+            monthly_budget_usd: None,
+            routing: Default::default(),
+            output_schema: None,
         },
     )
     .unwrap();
@@ -518,6 +678,9 @@ fn agents_update_impl_preserves_silent_fields_when_unspecified() {
             output_action: None,
             cache_responses: None,
             shortcode_trigger: None,
+            monthly_budget_usd: None,
+            routing: Default::default(),
+            output_schema: None,
         },
     )
     .unwrap();
@@ -549,6 +712,9 @@ fn agents_update_impl_overrides_silent_fields_when_specified() {
             output_action: Some(SilentOutputAction::Hud),
             cache_responses: Some(false),
             shortcode_trigger: Some(":".to_string()),
+            monthly_budget_usd: None,
+            routing: Default::default(),
+            output_schema: None,
         },
     )
     .unwrap();
@@ -557,3 +723,368 @@ fn agents_update_impl_overrides_silent_fields_when_specified() {
     assert_eq!(updated.input_source, SilentInputSource::Selection);
     assert_eq!(updated.output_action, SilentOutputAction::Hud);
 }
+
+#[test]
+fn agents_create_impl_validates_monthly_budget() {
+    let conn = make_conn();
+    for bad in [0.0, -3.0, f64::NAN] {
+        let input = AgentCreateInput {
+            monthly_budget_usd: Some(bad),
+            ..valid_create_input()
+        };
+        assert!(matches!(
+            agents_create_impl(&conn, input),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    let input = AgentCreateInput {
+        monthly_budget_usd: Some(20.0),
+        ..valid_create_input()
+    };
+    let row = agents_create_impl(&conn, input).unwrap();
+    assert_eq!(row.monthly_budget_usd, Some(20.0));
+}
+
+#[test]
+fn agents_create_impl_validates_routing_policy() {
+    let conn = make_conn();
+    let too_many_retries = AgentCreateInput {
+        routing: Some(RoutingPolicy {
+            max_retries: 6,
+            ..RoutingPolicy::default()
+        }),
+        ..valid_create_input()
+    };
+    assert!(matches!(
+        agents_create_impl(&conn, too_many_retries),
+        Err(AppError::Validation(_))
+    ));
+
+    let blank_fallback = AgentCreateInput {
+        routing: Some(RoutingPolicy {
+            fallbacks: vec![RouteTarget {
+                provider_id: " ".to_string(),
+                model_id: "gpt-4o".to_string(),
+            }],
+            ..RoutingPolicy::default()
+        }),
+        ..valid_create_input()
+    };
+    assert!(matches!(
+        agents_create_impl(&conn, blank_fallback),
+        Err(AppError::Validation(_))
+    ));
+}
+
+#[test]
+fn agents_update_impl_keeps_routing_when_omitted() {
+    let conn = make_conn();
+    let routing = RoutingPolicy {
+        fallbacks: vec![RouteTarget {
+            provider_id: "anthropic".to_string(),
+            model_id: "claude-sonnet-4".to_string(),
+        }],
+        max_retries: 1,
+        offline_fallback: None,
+    };
+    let created = agents_create_impl(
+        &conn,
+        AgentCreateInput {
+            routing: Some(routing.clone()),
+            ..valid_create_input()
+        },
+    )
+    .unwrap();
+
+    let updated = agents_update_impl(
+        &conn,
+        AgentUpdateInput {
+            id: created.id,
+            name: created.name,
+            description: created.description,
+            system_prompt: created.system_prompt,
+            provider_id: created.provider_id,
+            model_id: created.model_id,
+            tool_selection: created.tool_selection,
+            silent: None,
+            input_source: None,
+            output_action: None,
+            cache_responses: None,
+            shortcode_trigger: None,
+            monthly_budget_usd: None,
+            routing: None,
+            output_schema: None,
+        },
+    )
+    .unwrap();
+
+    assert_eq!(updated.routing, routing);
+}
+
+#[test]
+fn agents_create_impl_validates_output_schema() {
+    let conn = make_conn();
+    let schema = serde_json::json!({
+        "type": "object",
+        "properties": { "name": { "type": "string" } },
+    });
+    let created = agents_create_impl(
+        &conn,
+        AgentCreateInput {
+            output_schema: Some(schema.clone()),
+            ..valid_create_input()
+        },
+    )
+    .unwrap();
+    assert_eq!(created.output_schema, Some(schema));
+
+    let unsupported = AgentCreateInput {
+        output_schema: Some(serde_json::json!({ "type": "date" })),
+        ..valid_create_input()
+    };
+    assert!(matches!(
+        agents_create_impl(&conn, unsupported),
+        Err(AppError::Validation(_))
+    ));
+}
+
+#[test]
+fn memories_list_scopes_to_an_agent_and_delete_requires_the_entry() {
+    let conn = make_conn();
+    let agent = agents_create_impl(&conn, valid_create_input()).unwrap();
+    let other = agents_create_impl(&conn, valid_create_input()).unwrap();
+    let key = [3u8; 32];
+    for (id, agent_id) in [
+        ("g", None),
+        ("own", Some(&agent.id)),
+        ("other", Some(&other.id)),
+    ] {
+        crate::storage::memories::save(
+            &conn,
+            &crate::storage::memories::Memory {
+                id: id.to_string(),
+                agent_id: agent_id.cloned(),
+                content: format!("fact {id}"),
+                source_thread_id: None,
+                created_at: 1,
+                updated_at: 1,
+            },
+            &key,
+        )
+        .unwrap();
+    }
+
+    let ids = |agent_id: Option<String>| {
+        let mut ids: Vec<String> = agents_memories_list_impl(&conn, agent_id, &key)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        ids.sort();
+        ids
+    };
+    assert_eq!(ids(Some(agent.id.clone())), ["g", "own"]);
+    assert_eq!(ids(None), ["g", "other", "own"]);
+
+    agents_memory_delete_impl(&conn, "own".to_string()).unwrap();
+    assert!(matches!(
+        agents_memory_delete_impl(&conn, "own".to_string()),
+        Err(AppError::NotFound(_))
+    ));
+}
+
+#[test]
+fn deleting_an_agent_removes_its_memories_without_foreign_keys() {
+    use crate::storage::memories::{self, Memory};
+    // The production pool: `foreign_keys` stays off, so no cascade helps.
+    let store = crate::storage::create_test_store();
+    let conn = store.conn().unwrap();
+    let agent = agents_create_impl(&conn, valid_create_input()).unwrap();
+    let key = [5u8; 32];
+    for (id, agent_id) in [("own", Some(agent.id.clone())), ("global", None)] {
+        let memory = Memory {
+            id: id.to_string(),
+            agent_id,
+            content: format!("fact {id}"),
+            source_thread_id: None,
+            created_at: 1,
+            updated_at: 1,
+        };
+        memories::save(&conn, &memory, &key).unwrap();
+    }
+
+    agents_delete_impl(&conn, agent.id).unwrap();
+
+    assert!(memories::get(&conn, "own", &key).unwrap().is_none());
+    assert!(memories::get(&conn, "global", &key).unwrap().is_some());
+}
+
+fn schedule_input(agent_id: &str, time: &str) -> AgentTriggerInput {
+    AgentTriggerInput {
+        id: None,
+        agent_id: agent_id.to_string(),
+        spec: TriggerSpec::Schedule {
+            weekdays: vec![1, 2, 3, 4, 5],
+            time: time.to_string(),
+        },
+        prompt: Some("  Brief me on today  ".to_string()),
+        output: TriggerOutput::Note,
+        enabled: None,
+    }
+}
+
+#[test]
+fn trigger_save_schedules_the_next_fire_and_edits_keep_creation_time() {
+    let conn = make_conn();
+    let agent = agents_create_impl(&conn, valid_create_input()).unwrap();
+
+    let created = agents_trigger_save_impl(&conn, schedule_input(&agent.id, "09:00")).unwrap();
+    assert!(created.enabled);
+    assert_eq!(created.prompt, "Brief me on today");
+    assert!(created.next_fire_at.is_some());
+
+    let mut edit = schedule_input(&agent.id, "17:30");
+    edit.id = Some(created.id.clone());
+    edit.enabled = Some(false);
+    let edited = agents_trigger_save_impl(&conn, edit).unwrap();
+    assert_eq!(edited.created_at, created.created_at);
+    assert!(
+        edited.next_fire_at.is_none(),
+        "disabled triggers never come due"
+    );
+    assert_eq!(
+        agents_triggers_list_impl(&conn, Some(agent.id.clone())).unwrap(),
+        [edited]
+    );
+}
+
+#[test]
+fn trigger_save_rejects_invalid_specs_and_unknown_agents() {
+    let conn = make_conn();
+    let agent = agents_create_impl(&conn, valid_create_input()).unwrap();
+
+    assert!(matches!(
+        agents_trigger_save_impl(&conn, schedule_input(&agent.id, "25:00")),
+        Err(AppError::Validation(_))
+    ));
+    assert!(matches!(
+        agents_trigger_save_impl(&conn, schedule_input("missing", "09:00")),
+        Err(AppError::NotFound(_))
+    ));
+    let mut unknown = schedule_input(&agent.id, "09:00");
+    unknown.id = Some("nope".to_string());
+    assert!(matches!(
+        agents_trigger_save_impl(&conn, unknown),
+        Err(AppError::NotFound(_))
+    ));
+}
+
+#[test]
+fn triggers_go_with_their_agent_and_delete_requires_the_entry() {
+    let conn = make_conn();
+    let agent = agents_create_impl(&conn, valid_create_input()).unwrap();
+    let trigger = agents_trigger_save_impl(&conn, schedule_input(&agent.id, "09:00")).unwrap();
+
+    agents_trigger_delete_impl(&conn, trigger.id.clone()).unwrap();
+    assert!(matches!(
+        agents_trigger_delete_impl(&conn, trigger.id),
+        Err(AppError::NotFound(_))
+    ));
+
+    agents_trigger_save_impl(&conn, schedule_input(&agent.id, "09:00")).unwrap();
+    agents_delete_impl(&conn, agent.id).unwrap();
+    assert!(agents_triggers_list_impl(&conn, None).unwrap().is_empty());
+}
+
+#[test]
+fn tool_policy_defaults_until_set_and_goes_with_its_agent() {
+    let conn = make_conn();
+    let agent = agents_create_impl(&conn, valid_create_input()).unwrap();
+    assert_eq!(
+        agents_tool_policy_get_impl(&conn, agent.id.clone()).unwrap(),
+        AgentToolPolicy::default()
+    );
+
+    let policy = AgentToolPolicy {
+        allowed_commands: vec!["git".to_string()],
+        write_paths: vec!["~/Projects/**".to_string()],
+        write_mode: WriteMode::Confirm,
+        ..AgentToolPolicy::default()
+    };
+    agents_tool_policy_set_impl(&conn, agent.id.clone(), policy.clone()).unwrap();
+    assert_eq!(
+        agents_tool_policy_get_impl(&conn, agent.id.clone()).unwrap(),
+        policy
+    );
+
+    agents_delete_impl(&conn, agent.id.clone()).unwrap();
+    assert_eq!(
+        agents_tool_policy_get_impl(&conn, agent.id).unwrap(),
+        AgentToolPolicy::default()
+    );
+}
+
+#[test]
+fn tool_policy_set_rejects_invalid_policies_and_unknown_agents() {
+    let conn = make_conn();
+    let agent = agents_create_impl(&conn, valid_create_input()).unwrap();
+    let invalid = AgentToolPolicy {
+        timeout_secs: 0,
+        ..AgentToolPolicy::default()
+    };
+    assert!(matches!(
+        agents_tool_policy_set_impl(&conn, agent.id, invalid),
+        Err(AppError::Validation(_))
+    ));
+    assert!(matches!(
+        agents_tool_policy_set_impl(&conn, "missing".to_string(), AgentToolPolicy::default()),
+        Err(AppError::NotFound(_))
+    ));
+}
+
+#[test]
+fn attachment_from_a_protected_location_is_denied() {
+    let home = tempfile::TempDir::new().unwrap();
+    let ssh = home.path().join(".ssh");
+    std::fs::create_dir_all(&ssh).unwrap();
+    let key = ssh.join("id_rsa");
+    std::fs::write(&key, "PRIVATE KEY").unwrap();
+
+    let err =
+        agents_attachment_from_file_impl(key.to_str().unwrap(), home.path(), &[]).unwrap_err();
+    assert!(matches!(err, AppError::Permission(_)), "got: {err}");
+}
+
+#[cfg(unix)]
+#[test]
+fn attachment_symlinked_into_app_data_is_denied() {
+    let home = tempfile::TempDir::new().unwrap();
+    let app_data = home.path().join("app-data");
+    std::fs::create_dir_all(&app_data).unwrap();
+    let settings = app_data.join("settings.dat");
+    std::fs::write(&settings, "{}").unwrap();
+    let innocent = home.path().join("notes.txt");
+    std::os::unix::fs::symlink(&settings, &innocent).unwrap();
+
+    let err =
+        agents_attachment_from_file_impl(innocent.to_str().unwrap(), home.path(), &[app_data])
+            .unwrap_err();
+    assert!(matches!(err, AppError::Permission(_)), "got: {err}");
+}
+
+#[test]
+fn attachment_requires_an_absolute_path() {
+    let home = tempfile::TempDir::new().unwrap();
+    let err = agents_attachment_from_file_impl("notes.txt", home.path(), &[]).unwrap_err();
+    assert!(matches!(err, AppError::Validation(_)), "got: {err}");
+}
+
+#[test]
+fn attachment_outside_the_deny_list_is_read() {
+    let home = tempfile::TempDir::new().unwrap();
+    let notes = home.path().join("notes.txt");
+    std::fs::write(&notes, "hello").unwrap();
+
+    assert!(agents_attachment_from_file_impl(notes.to_str().unwrap(), home.path(), &[]).is_ok());
+}
@@ -1,7 +1,7 @@
 //! Extension command handlers — thin wrappers delegating to extension service modules.
 
 use crate::error::AppError;
-use crate::extensions::scheduler::{self, ScheduledTaskInfo, SchedulerState};
+use crate::extensions::scheduler::{self, ScheduledTaskInfo, SchedulerState, UpcomingFire};
 use crate::extensions::{
     self, headless::HeadlessRegistry, ExtensionRecord, ExtensionRegistryState, ThemeDefinition,
 };
@@ -29,10 +29,11 @@ pub async fn uninstall_extension(
     app_handle: AppHandle,
     registry: tauri::State<'_, ExtensionRegistryState>,
     scheduler: tauri::State<'_, SchedulerState>,
+    jobs: tauri::State<'_, crate::scheduler::Scheduler>,
     runtime_manager: tauri::State<'_, crate::runtimes::RuntimeManager>,
     extension_id: String,
 ) -> Result<(), AppError> {
-    scheduler::stop_tasks_for_extension(&scheduler, &extension_id)?;
+    scheduler::stop_tasks_for_extension(&scheduler, &jobs, &extension_id)?;
     extensions::lifecycle::uninstall(&app_handle, &extension_id, &registry, &runtime_manager).await
 }
 
@@ -140,10 +141,11 @@ pub async fn discover_extensions(
     app_handle: AppHandle,
     registry: tauri::State<'_, ExtensionRegistryState>,
     scheduler: tauri::State<'_, SchedulerState>,
+    jobs: tauri::State<'_, crate::scheduler::Scheduler>,
 ) -> Result<Vec<ExtensionRecord>, AppError> {
     let result = extensions::lifecycle::discover_all(&app_handle, &registry)?;
     // Restart all scheduled tasks based on updated registry
-    scheduler::start_all_tasks(&app_handle, &registry, &scheduler)?;
+    scheduler::start_all_tasks(&app_handle, &registry, &scheduler, &jobs)?;
     // Seed the agent ToolRegistry from every already-enabled Tier 2
     // extension's manifest. Without this, manifest-declared tools are
     // absent until the user toggles enable/disable post-restart.
@@ -156,14 +158,21 @@ pub async fn set_extension_enabled(
     app_handle: AppHandle,
     registry: tauri::State<'_, ExtensionRegistryState>,
     scheduler: tauri::State<'_, SchedulerState>,
+    jobs: tauri::State<'_, crate::scheduler::Scheduler>,
     extension_id: String,
     enabled: bool,
 ) -> Result<(), AppError> {
     extensions::lifecycle::set_enabled(&app_handle, &registry, &extension_id, enabled).await?;
     if enabled {
-        scheduler::start_tasks_for_extension(&app_handle, &registry, &scheduler, &extension_id)?;
+        scheduler::start_tasks_for_extension(
+            &app_handle,
+            &registry,
+            &scheduler,
+            &jobs,
+            &extension_id,
+        )?;
     } else {
-        scheduler::stop_tasks_for_extension(&scheduler, &extension_id)?;
+        scheduler::stop_tasks_for_extension(&scheduler, &jobs, &extension_id)?;
     }
     Ok(())
 }
@@ -186,8 +195,18 @@ pub async fn get_extension(
 pub async fn get_scheduled_tasks(
     registry: tauri::State<'_, ExtensionRegistryState>,
     scheduler: tauri::State<'_, SchedulerState>,
+    jobs: tauri::State<'_, crate::scheduler::Scheduler>,
 ) -> Result<Vec<ScheduledTaskInfo>, AppError> {
-    scheduler::get_scheduled_task_info(&registry, &scheduler)
+    scheduler::get_scheduled_task_info(&registry, &scheduler, &jobs)
+}
+
+#[tauri::command]
+pub async fn get_extension_upcoming_fires(
+    registry: tauri::State<'_, ExtensionRegistryState>,
+    extension_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<UpcomingFire>, AppError> {
+    scheduler::get_upcoming_fires(&registry, &extension_id, limit)
 }
 
 #[tauri::command]
@@ -196,7 +196,7 @@ pub async fn files_read_text<R: tauri::Runtime>(
 /// The launcher's own app-data dir (settings.dat, consent records, MCP
 /// config) joins the deny-list — a broad consented glob like `~/**` must
 /// not read the launcher's internal state.
-fn extension_scope_env<R: tauri::Runtime>(
+pub(crate) fn extension_scope_env<R: tauri::Runtime>(
     app_handle: &tauri::AppHandle<R>,
 ) -> Result<(PathBuf, Vec<PathBuf>), AppError> {
     let home = app_handle
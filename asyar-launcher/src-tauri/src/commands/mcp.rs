@@ -1,5 +1,9 @@
+use crate::agents::runner::McpPermissionChoice;
 use crate::agents::tools::ToolRegistryState;
+use crate::ai::types::ContentPart;
 use crate::error::AppError;
+use crate::mcp::catalog::{self, McpCatalogEntry};
+use crate::mcp::host::{McpHost, McpHostStatus};
 use crate::mcp::install::{
     detect_existing_configs, install_server_checking_runtime_ensuring, list_servers_with_status,
     parse_mcp_config_json, test_server, DetectedConfig, McpInstallOutcomeResponse,
@@ -9,12 +13,17 @@ use crate::mcp::lifecycle::{
     mcp_cleanup_on_delete, mcp_sync_on_enable_change_checking_runtime_ensuring,
     McpSetEnabledOutcomeResponse,
 };
+use crate::mcp::server_requests::{ElicitationAction, McpServerRequests, SamplingAnswer};
 use crate::mcp::tool_adapter::invoke_mcp_tool;
-use crate::mcp::{McpSupervisor, McpToolDescriptor};
+use crate::mcp::{
+    McpPrompt, McpPromptResult, McpResource, McpResourceContents, McpResourceTemplate,
+    McpSupervisor, McpToolDescriptor,
+};
 use crate::runtimes::RuntimeManager;
 use crate::storage::mcp_audit::McpAuditRow;
 use crate::storage::mcp_permissions;
 use crate::storage::DataStore;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use tauri::{AppHandle, State};
 
@@ -196,6 +205,103 @@ pub async fn mcp_list_server_tools(
         .map_err(|e| AppError::Other(format!("{e}")))
 }
 
+// ── Resources and prompts ─────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn mcp_list_resources(
+    supervisor: State<'_, Arc<McpSupervisor>>,
+    server_id: String,
+) -> Result<Vec<McpResource>, AppError> {
+    supervisor
+        .list_resources(&server_id)
+        .await
+        .map_err(|e| AppError::Other(format!("{e}")))
+}
+
+#[tauri::command]
+pub async fn mcp_list_resource_templates(
+    supervisor: State<'_, Arc<McpSupervisor>>,
+    server_id: String,
+) -> Result<Vec<McpResourceTemplate>, AppError> {
+    supervisor
+        .list_resource_templates(&server_id)
+        .await
+        .map_err(|e| AppError::Other(format!("{e}")))
+}
+
+#[tauri::command]
+pub async fn mcp_list_prompts(
+    supervisor: State<'_, Arc<McpSupervisor>>,
+    server_id: String,
+) -> Result<Vec<McpPrompt>, AppError> {
+    supervisor
+        .list_prompts(&server_id)
+        .await
+        .map_err(|e| AppError::Other(format!("{e}")))
+}
+
+#[tauri::command]
+pub async fn mcp_read_resource(
+    supervisor: State<'_, Arc<McpSupervisor>>,
+    server_id: String,
+    uri: String,
+) -> Result<Vec<McpResourceContents>, AppError> {
+    supervisor
+        .read_resource(&server_id, &uri)
+        .await
+        .map_err(|e| AppError::Other(format!("{e}")))
+}
+
+#[tauri::command]
+pub async fn mcp_get_prompt(
+    supervisor: State<'_, Arc<McpSupervisor>>,
+    server_id: String,
+    name: String,
+    arguments: BTreeMap<String, String>,
+) -> Result<McpPromptResult, AppError> {
+    supervisor
+        .get_prompt(&server_id, &name, &arguments)
+        .await
+        .map_err(|e| AppError::Other(format!("{e}")))
+}
+
+/// Resources and prompts of every connected server, as launcher commands.
+#[tauri::command]
+pub async fn mcp_list_catalog(
+    supervisor: State<'_, Arc<McpSupervisor>>,
+) -> Result<Vec<McpCatalogEntry>, AppError> {
+    Ok(catalog::build_entries(&supervisor.catalog()))
+}
+
+/// Reads a resource and converts it into chat attachments.
+#[tauri::command]
+pub async fn mcp_resource_attachments(
+    supervisor: State<'_, Arc<McpSupervisor>>,
+    server_id: String,
+    uri: String,
+) -> Result<Vec<ContentPart>, AppError> {
+    let contents = supervisor
+        .read_resource(&server_id, &uri)
+        .await
+        .map_err(|e| AppError::Other(format!("{e}")))?;
+    catalog::resource_parts(contents)
+}
+
+/// Renders a prompt into the text of one chat message.
+#[tauri::command]
+pub async fn mcp_render_prompt(
+    supervisor: State<'_, Arc<McpSupervisor>>,
+    server_id: String,
+    name: String,
+    arguments: BTreeMap<String, String>,
+) -> Result<String, AppError> {
+    let result = supervisor
+        .get_prompt(&server_id, &name, &arguments)
+        .await
+        .map_err(|e| AppError::Other(format!("{e}")))?;
+    catalog::prompt_text(&result)
+}
+
 // ── mcp_list_permissions ──────────────────────────────────────────────────────
 
 #[tauri::command]
@@ -262,6 +368,117 @@ pub async fn mcp_set_strict_mode(
     crate::storage::mcp_settings::set_strict_mode(&conn, enabled)
 }
 
+// ── mcp_host_* ────────────────────────────────────────────────────────────────
+//
+// Asyar's own MCP server (`mcp::host`): the opt-in toggle, connection details
+// for the UI, and the frontend's answers to permission prompts and Tier-2
+// tool dispatches.
+
+async fn host_status(host: &McpHost, data_store: &DataStore) -> Result<McpHostStatus, AppError> {
+    let enabled = {
+        let conn = data_store.conn()?;
+        crate::storage::mcp_settings::get_host_enabled(&conn)?
+    };
+    let url = host
+        .port()
+        .await
+        .map(|port| format!("http://127.0.0.1:{port}/mcp"));
+    let exe = std::env::current_exe()
+        .map(|exe| exe.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| "asyar".to_string());
+    Ok(McpHostStatus {
+        enabled,
+        url,
+        token: enabled.then(|| host.token()).flatten(),
+        stdio_command: vec![exe, crate::mcp::host::stdio::SUBCOMMAND.to_string()],
+    })
+}
+
+#[tauri::command]
+pub async fn mcp_host_status(
+    host: State<'_, Arc<McpHost>>,
+    data_store: State<'_, DataStore>,
+) -> Result<McpHostStatus, AppError> {
+    host_status(&host, &data_store).await
+}
+
+#[tauri::command]
+pub async fn mcp_host_set_enabled(
+    host: State<'_, Arc<McpHost>>,
+    data_store: State<'_, DataStore>,
+    enabled: bool,
+) -> Result<McpHostStatus, AppError> {
+    {
+        let conn = data_store.conn()?;
+        crate::storage::mcp_settings::set_host_enabled(&conn, enabled)?;
+    }
+    if enabled {
+        crate::mcp::host::start_with_keyring(&host).await?;
+    } else {
+        host.stop().await;
+    }
+    host_status(&host, &data_store).await
+}
+
+/// Issues a new token; clients configured with the old one stop working.
+#[tauri::command]
+pub async fn mcp_host_rotate_token(
+    host: State<'_, Arc<McpHost>>,
+    data_store: State<'_, DataStore>,
+) -> Result<McpHostStatus, AppError> {
+    let token = tokio::task::spawn_blocking(|| {
+        crate::mcp::host::token::rotate(&crate::mcp::host::token::keyring())
+    })
+    .await
+    .map_err(|e| AppError::Other(format!("token task failed: {e}")))??;
+    host.set_token(Some(token));
+    host_status(&host, &data_store).await
+}
+
+#[tauri::command]
+pub async fn mcp_host_resolve_permission(
+    host: State<'_, Arc<McpHost>>,
+    id: String,
+    decision: McpPermissionChoice,
+) -> Result<bool, AppError> {
+    host.resolve_permission(&id, decision)
+}
+
+#[tauri::command]
+pub async fn mcp_host_report_tool_result(
+    host: State<'_, Arc<McpHost>>,
+    id: String,
+    result: Option<serde_json::Value>,
+    error: Option<String>,
+) -> Result<bool, AppError> {
+    let result = match error {
+        Some(error) => Err(error),
+        None => Ok(result.unwrap_or(serde_json::Value::Null)),
+    };
+    host.resolve_tool_call(&id, result)
+}
+
+#[tauri::command]
+pub async fn mcp_resolve_sampling(
+    requests: State<'_, Arc<McpServerRequests>>,
+    id: String,
+    answer: SamplingAnswer,
+) -> Result<bool, AppError> {
+    requests.resolve_sampling(&id, answer)
+}
+
+/// A `Validation` error means the accepted content doesn't fit the form;
+/// the request keeps waiting for a corrected answer.
+#[tauri::command]
+pub async fn mcp_resolve_elicitation(
+    requests: State<'_, Arc<McpServerRequests>>,
+    id: String,
+    action: ElicitationAction,
+    content: Option<serde_json::Value>,
+) -> Result<bool, AppError> {
+    requests.resolve_elicitation(&id, action, content)
+}
+
 fn now_millis() -> i64 {
     use std::time::{SystemTime, UNIX_EPOCH};
     SystemTime::now()
@@ -39,6 +39,7 @@ pub mod scripts;
 pub mod searchbar_accessory;
 pub mod secret_detection;
 pub mod selection;
+pub mod semantic_index;
 pub mod shell;
 pub mod shortcuts;
 pub mod snap_guides;
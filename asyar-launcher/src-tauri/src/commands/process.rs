@@ -1,19 +1,30 @@
 //! Tauri command layer for the process service. Thin wrappers over
 //! `process_manager`, gated by the extension permission registry.
 //!
-//! Both commands are `async` and run the heavy `sysinfo` enumeration on a
+//! List and kill are `async` and run the heavy `sysinfo` enumeration on a
 //! blocking pool (`spawn_blocking`). Synchronous Tauri commands execute on the
 //! main thread, so the enumeration's CPU-delta sleep + full process scan froze
 //! the UI event loop (and, under the view's auto-refresh, did so continuously).
 //! Off-loading keeps kills instant and the launcher responsive.
+//!
+//! The history commands only read what the background sampler
+//! (`process_manager::sampler`) already collected, so they never scan.
 
 use crate::error::AppError;
 use crate::permissions::ExtensionPermissionRegistry;
+use crate::process_manager::history::{
+    ResourceAlert, ResourceMetric, ResourceSeries, ResourceTrend,
+};
+use crate::process_manager::sampler::{ProcessSampler, WINDOW_MINUTES};
 use crate::process_manager::types::{AppGroup, KillResult, SortBy};
-use tauri::State;
+use tauri::{AppHandle, State};
 
 const READ_PERMISSION: &str = "process:read";
 const KILL_PERMISSION: &str = "process:kill";
+/// Alerts end in an OS notification, so setting one needs both.
+const NOTIFY_PERMISSION: &str = "notifications:send";
+const DEFAULT_TOP_LIMIT: u32 = 10;
+const MAX_TOP_LIMIT: u32 = 100;
 
 #[tauri::command]
 pub async fn process_list(
@@ -42,6 +53,110 @@ pub async fn process_kill(
         .await
 }
 
+/// Take or renew the caller's sampling lease, starting the sampler if it was
+/// idle. Hold it while trends are on screen by taking it again within
+/// `LEASE_TTL`; each caller has at most one.
+#[tauri::command]
+pub async fn process_sampling_start(
+    app: AppHandle,
+    sampler: State<'_, ProcessSampler>,
+    permissions: State<'_, ExtensionPermissionRegistry>,
+    extension_id: Option<String>,
+) -> Result<(), AppError> {
+    ensure_can_list(&permissions, &extension_id)?;
+    sampler.acquire(extension_id, now_millis())?;
+    sampler.sync_schedule(&app);
+    Ok(())
+}
+
+/// Give the lease back. Sampling stops once no lease or enabled alert is left.
+#[tauri::command]
+pub async fn process_sampling_stop(
+    app: AppHandle,
+    sampler: State<'_, ProcessSampler>,
+    permissions: State<'_, ExtensionPermissionRegistry>,
+    extension_id: Option<String>,
+) -> Result<(), AppError> {
+    ensure_can_list(&permissions, &extension_id)?;
+    sampler.release(&extension_id)?;
+    sampler.sync_schedule(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn process_history(
+    sampler: State<'_, ProcessSampler>,
+    permissions: State<'_, ExtensionPermissionRegistry>,
+    extension_id: Option<String>,
+    app_names: Vec<String>,
+    minutes: Option<u32>,
+) -> Result<Vec<ResourceSeries>, AppError> {
+    ensure_can_list(&permissions, &extension_id)?;
+    sampler.series(&app_names, minutes.unwrap_or(WINDOW_MINUTES), now_millis())
+}
+
+#[tauri::command]
+pub async fn process_top(
+    sampler: State<'_, ProcessSampler>,
+    permissions: State<'_, ExtensionPermissionRegistry>,
+    extension_id: Option<String>,
+    metric: ResourceMetric,
+    minutes: Option<u32>,
+    limit: Option<u32>,
+) -> Result<Vec<ResourceTrend>, AppError> {
+    ensure_can_list(&permissions, &extension_id)?;
+    let limit = limit.unwrap_or(DEFAULT_TOP_LIMIT).clamp(1, MAX_TOP_LIMIT);
+    sampler.top(
+        metric,
+        minutes.unwrap_or(WINDOW_MINUTES),
+        limit as usize,
+        now_millis(),
+    )
+}
+
+/// The caller's own alerts; nobody sees another extension's.
+#[tauri::command]
+pub async fn process_alerts_list(
+    sampler: State<'_, ProcessSampler>,
+    permissions: State<'_, ExtensionPermissionRegistry>,
+    extension_id: Option<String>,
+) -> Result<Vec<ResourceAlert>, AppError> {
+    ensure_can_list(&permissions, &extension_id)?;
+    sampler.alerts(&extension_id)
+}
+
+#[tauri::command]
+pub async fn process_alert_save(
+    app: AppHandle,
+    sampler: State<'_, ProcessSampler>,
+    permissions: State<'_, ExtensionPermissionRegistry>,
+    extension_id: Option<String>,
+    alert: ResourceAlert,
+) -> Result<(), AppError> {
+    ensure_can_alert(&permissions, &extension_id)?;
+    sampler.save_alert(extension_id, alert)?;
+    sampler.sync_schedule(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn process_alert_delete(
+    app: AppHandle,
+    sampler: State<'_, ProcessSampler>,
+    permissions: State<'_, ExtensionPermissionRegistry>,
+    extension_id: Option<String>,
+    alert_id: String,
+) -> Result<bool, AppError> {
+    ensure_can_alert(&permissions, &extension_id)?;
+    let removed = sampler.delete_alert(&extension_id, &alert_id)?;
+    sampler.sync_schedule(&app);
+    Ok(removed)
+}
+
+fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
 /// Permission gate for `process_list`. Separated so the command stays a thin
 /// thread-offload wrapper and the gate is directly unit-testable.
 fn ensure_can_list(
@@ -59,6 +174,15 @@ fn ensure_can_kill(
     permissions.check(extension_id, KILL_PERMISSION)
 }
 
+/// Permission gate for saving and deleting resource alerts.
+fn ensure_can_alert(
+    permissions: &ExtensionPermissionRegistry,
+    extension_id: &Option<String>,
+) -> Result<(), AppError> {
+    permissions.check(extension_id, READ_PERMISSION)?;
+    permissions.check(extension_id, NOTIFY_PERMISSION)
+}
+
 /// Run a blocking closure on Tauri's blocking pool and surface a panic as an
 /// `AppError` rather than tearing down the runtime.
 async fn spawn_blocking_result<T, F>(f: F) -> Result<T, AppError>
@@ -120,11 +244,31 @@ mod tests {
         assert!(ensure_can_kill(&perms, &Some("ext-a".into())).is_ok());
     }
 
+    #[test]
+    fn alert_needs_read_and_notification_permissions() {
+        let read_only = perms_with("ext-a", "process:read");
+        let err = ensure_can_alert(&read_only, &Some("ext-a".into())).unwrap_err();
+        assert!(matches!(err, AppError::Permission(_)));
+
+        let notify_only = perms_with("ext-a", "notifications:send");
+        assert!(ensure_can_alert(&notify_only, &Some("ext-a".into())).is_err());
+
+        let both = perms_with("ext-a", "process:read");
+        both.inner
+            .lock()
+            .unwrap()
+            .get_mut("ext-a")
+            .unwrap()
+            .insert("notifications:send".to_string());
+        assert!(ensure_can_alert(&both, &Some("ext-a".into())).is_ok());
+    }
+
     #[test]
     fn core_caller_bypasses_gate() {
         // None extension_id = privileged host call, always allowed.
         let perms = ExtensionPermissionRegistry::new();
         assert!(ensure_can_list(&perms, &None).is_ok());
         assert!(ensure_can_kill(&perms, &None).is_ok());
+        assert!(ensure_can_alert(&perms, &None).is_ok());
     }
 }
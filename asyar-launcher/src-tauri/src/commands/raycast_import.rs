@@ -1,4 +1,4 @@
-//! Thin Tauri command wrapper for the Raycast importer.
+//! Thin Tauri command wrapper for the Raycast / Espanso / TextExpander importer.
 //!
 //! All parsing/decryption/translation logic lives in `crate::raycast_import`.
 
@@ -6,19 +6,36 @@ use crate::error::AppError;
 use crate::raycast_import::{self, ParseOutcome};
 use crate::search_engine::models::SearchableItem;
 use crate::search_engine::SearchState;
+use std::path::Path;
 use std::sync::Arc;
 
-/// Parse a Raycast export file into a normalized import bundle. App hotkeys
-/// are resolved against the current search index so the frontend receives
+/// Parse an export file into a normalized import bundle. App hotkeys are
+/// resolved against the current search index so the frontend receives
 /// ready-to-register shortcut targets.
+///
+/// `path` may also be an Espanso config directory (or its `match`
+/// subdirectory); every YAML file under it is parsed into one bundle.
 #[tauri::command]
 pub async fn raycast_import_parse(
     path: String,
     password: Option<String>,
     search_state: tauri::State<'_, Arc<SearchState>>,
 ) -> Result<ParseOutcome, AppError> {
-    let bytes = std::fs::read(&path)?;
-    let mut outcome = raycast_import::parse_export(&bytes, password.as_deref())?;
+    let path = Path::new(&path);
+    let mut outcome = if path.is_dir() {
+        let files = collect_espanso_files(path)?;
+        if files.is_empty() {
+            return Err(AppError::Validation(
+                "No Espanso match files (.yml) found in this folder".to_string(),
+            ));
+        }
+        ParseOutcome::Ok {
+            bundle: raycast_import::espanso::parse_files(&files)?,
+        }
+    } else {
+        let bytes = std::fs::read(path)?;
+        raycast_import::parse_export(&bytes, password.as_deref())?
+    };
 
     if let ParseOutcome::Ok { bundle } = &mut outcome {
         let apps: Vec<_> = search_state
@@ -36,3 +53,57 @@ pub async fn raycast_import_parse(
 
     Ok(outcome)
 }
+
+/// Espanso keeps matches under `<config>/match`; accept either that folder
+/// or the config root. Files are read in sorted order so the bundle (and
+/// therefore the import summary) is deterministic.
+fn collect_espanso_files(dir: &Path) -> Result<Vec<Vec<u8>>, AppError> {
+    let match_dir = dir.join("match");
+    let root = if match_dir.is_dir() {
+        match_dir
+    } else {
+        dir.to_path_buf()
+    };
+
+    let mut paths = Vec::new();
+    let mut pending = vec![root];
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| e.eq_ignore_ascii_case("yml") || e.eq_ignore_ascii_case("yaml"))
+            {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort();
+    paths
+        .iter()
+        .map(|p| std::fs::read(p).map_err(AppError::from))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_yaml_files_from_match_subdirectory() {
+        let dir = tempfile::tempdir().unwrap();
+        let match_dir = dir.path().join("match");
+        std::fs::create_dir_all(match_dir.join("packages")).unwrap();
+        std::fs::create_dir_all(dir.path().join("config")).unwrap();
+        std::fs::write(match_dir.join("base.yml"), "matches: []\n").unwrap();
+        std::fs::write(match_dir.join("packages/extra.yaml"), "matches: []\n").unwrap();
+        std::fs::write(match_dir.join("notes.txt"), "ignored").unwrap();
+        std::fs::write(dir.path().join("config/default.yml"), "toggle_key: ALT").unwrap();
+
+        let files = collect_espanso_files(dir.path()).unwrap();
+        assert_eq!(files.len(), 2);
+    }
+}
@@ -3,11 +3,16 @@ use crate::notifications::backend::populate_registry_and_send;
 use crate::notifications::{
     BackendAction, NotificationActionRegistry, NotificationBackend, NotificationRequest,
 };
+use crate::runs::ansi::{self, AnsiSpan};
 use crate::runs::buckets::upsert_run_bucket;
+use crate::runs::logs::{
+    clear_run_logs, finish_run_log, search_run_logs, start_run_log, RunLogMatch, RunLogPage,
+    RunLogStore,
+};
 use crate::runs::output_buffer::{format_tail_output, OutputBuffer};
 use crate::runs::registry::{now_millis, RunRegistry};
 use crate::runs::{Run, RunBucketKind, RunKind, RunStatus};
-use crate::storage::{runs_history, DataStore};
+use crate::storage::{run_logs, runs_history, DataStore};
 use rusqlite::Connection;
 use tauri::{AppHandle, Emitter, State};
 
@@ -264,22 +269,26 @@ pub fn maybe_send_run_notification(
 
 // ── Tauri command wrappers ────────────────────────────────────────────────────
 
-/// Start a new run; exposed as a Tauri IPC command.
+/// Start a new run; exposed as a Tauri IPC command. `inputs` is whatever
+/// the caller needs to start the same run again — see `runs_get_inputs`.
 #[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub async fn runs_start(
     app: AppHandle,
+    store: State<'_, DataStore>,
+    logs: State<'_, RunLogStore>,
     id: String,
     kind: RunKind,
     label: String,
     extension_id: Option<String>,
     cancellable: bool,
     subject_id: Option<String>,
+    inputs: Option<serde_json::Value>,
 ) -> Result<Run, AppError> {
     let registry = RunRegistry::instance();
     let buffer = OutputBuffer::instance();
     let emit = |event: &str, payload: &serde_json::Value| app.emit(event, payload);
-    runs_start_impl(
+    let run = runs_start_impl(
         registry,
         buffer,
         &emit,
@@ -289,21 +298,47 @@ pub async fn runs_start(
         extension_id,
         cancellable,
         subject_id,
-    )
+    )?;
+    // The log is a best-effort record; a run that can't get one still runs.
+    let logged = store
+        .conn()
+        .and_then(|conn| start_run_log(&logs, &conn, &run.id, run.started_at, inputs.as_ref()));
+    if let Err(e) = logged {
+        log::warn!("[runs] no log for run {}: {e}", run.id);
+    }
+    Ok(run)
 }
 
 #[tauri::command]
-pub async fn runs_write(app: AppHandle, id: String, line: String) -> Result<(), AppError> {
+pub async fn runs_write(
+    app: AppHandle,
+    logs: State<'_, RunLogStore>,
+    id: String,
+    line: String,
+) -> Result<(), AppError> {
     let registry = RunRegistry::instance();
     let buffer = OutputBuffer::instance();
     let emit = |event: &str, payload: &serde_json::Value| app.emit(event, payload);
-    runs_write_impl(registry, buffer, &emit, id, line)
+    runs_write_impl(registry, buffer, &emit, id.clone(), line.clone())?;
+    if let Err(e) = logs.append(&id, &line) {
+        log::warn!("[runs] failed to log output for run {id}: {e}");
+    }
+    Ok(())
+}
+
+/// Compress a run's log once it reaches a terminal state. Failures are
+/// logged, never surfaced: the run itself already finished.
+fn finish_log(logs: &RunLogStore, conn: &Connection, id: &str) {
+    if let Err(e) = finish_run_log(logs, conn, id) {
+        log::warn!("[runs] failed to finish log for run {id}: {e}");
+    }
 }
 
 #[tauri::command]
 pub async fn runs_done(
     app: AppHandle,
     store: State<'_, DataStore>,
+    logs: State<'_, RunLogStore>,
     registry: State<'_, std::sync::Arc<NotificationActionRegistry>>,
     backend: State<'_, std::sync::Arc<dyn NotificationBackend>>,
     id: String,
@@ -313,6 +348,7 @@ pub async fn runs_done(
     let conn = store.conn()?;
     let emit = |event: &str, payload: &serde_json::Value| app.emit(event, payload);
     runs_done_impl(run_registry, buffer, &emit, &conn, id.clone())?;
+    finish_log(&logs, &conn, &id);
 
     if let Some(run) = run_registry.get(&id) {
         let _ = maybe_send_run_notification(registry.inner(), backend.inner().as_ref(), &run);
@@ -324,6 +360,7 @@ pub async fn runs_done(
 pub async fn runs_fail(
     app: AppHandle,
     store: State<'_, DataStore>,
+    logs: State<'_, RunLogStore>,
     registry: State<'_, std::sync::Arc<NotificationActionRegistry>>,
     backend: State<'_, std::sync::Arc<dyn NotificationBackend>>,
     id: String,
@@ -334,6 +371,7 @@ pub async fn runs_fail(
     let conn = store.conn()?;
     let emit = |event: &str, payload: &serde_json::Value| app.emit(event, payload);
     runs_fail_impl(run_registry, buffer, &emit, &conn, id.clone(), error)?;
+    finish_log(&logs, &conn, &id);
 
     if let Some(run) = run_registry.get(&id) {
         let _ = maybe_send_run_notification(registry.inner(), backend.inner().as_ref(), &run);
@@ -345,13 +383,16 @@ pub async fn runs_fail(
 pub async fn runs_cancel(
     app: AppHandle,
     store: State<'_, DataStore>,
+    logs: State<'_, RunLogStore>,
     id: String,
 ) -> Result<(), AppError> {
     let registry = RunRegistry::instance();
     let buffer = OutputBuffer::instance();
     let conn = store.conn()?;
     let emit = |event: &str, payload: &serde_json::Value| app.emit(event, payload);
-    runs_cancel_impl(registry, buffer, &emit, &conn, id)
+    runs_cancel_impl(registry, buffer, &emit, &conn, id.clone())?;
+    finish_log(&logs, &conn, &id);
+    Ok(())
 }
 
 // ── Read-side Tauri command wrappers ──────────────────────────────────────────
@@ -378,11 +419,16 @@ pub async fn runs_history_list(
     runs_history_list_impl(&conn, limit.unwrap_or(50))
 }
 
-/// Clear all rows from the run history table.
+/// Clear all rows from the run history table, along with every finished
+/// run log.
 #[tauri::command]
-pub async fn runs_history_clear(db: State<'_, DataStore>) -> Result<(), AppError> {
+pub async fn runs_history_clear(
+    db: State<'_, DataStore>,
+    logs: State<'_, RunLogStore>,
+) -> Result<(), AppError> {
     let conn = db.conn()?;
-    runs_history_clear_impl(&conn)
+    runs_history_clear_impl(&conn)?;
+    clear_run_logs(&logs, &conn)
 }
 
 /// Return the buffered output lines for an active run.
@@ -391,6 +437,75 @@ pub async fn runs_get_output(id: String) -> Result<Vec<String>, AppError> {
     Ok(runs_get_output_impl(OutputBuffer::instance(), &id))
 }
 
+/// Return one page of a run's full log, starting at line `offset`
+/// (default 0), up to `limit` lines (default 200).
+#[tauri::command]
+pub async fn runs_read_log(
+    logs: State<'_, RunLogStore>,
+    id: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<RunLogPage, AppError> {
+    let logs = logs.inner().clone();
+    spawn_blocking_log_io(move || logs.read_page(&id, offset.unwrap_or(0), limit.unwrap_or(200)))
+        .await
+}
+
+/// Search every finished run log for `query` (case-insensitive), most
+/// recently finished runs first; up to `limit` matches (default 100).
+#[tauri::command]
+pub async fn runs_search_logs(
+    db: State<'_, DataStore>,
+    logs: State<'_, RunLogStore>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<RunLogMatch>, AppError> {
+    let db = db.inner().clone();
+    let logs = logs.inner().clone();
+    spawn_blocking_log_io(move || {
+        let conn = db.conn()?;
+        search_run_logs(&logs, &conn, &query, limit.unwrap_or(100))
+    })
+    .await
+}
+
+/// Run log reads inflate gzip and walk files, so they go to Tauri's blocking
+/// pool instead of stalling the async runtime; a panic surfaces as an
+/// `AppError`.
+async fn spawn_blocking_log_io<T, F>(f: F) -> Result<T, AppError>
+where
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
+    T: Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| AppError::Other(format!("run log task failed: {e}")))?
+}
+
+/// Return the inputs a run was started with, or `None` when it recorded
+/// none or its log has been deleted.
+#[tauri::command]
+pub async fn runs_get_inputs(
+    db: State<'_, DataStore>,
+    id: String,
+) -> Result<Option<serde_json::Value>, AppError> {
+    let conn = db.conn()?;
+    Ok(run_logs::get(&conn, &id)?.and_then(|record| record.inputs))
+}
+
+/// Split output `lines` into ANSI-styled spans for RunView. Style carries
+/// from line to line, so pass a page at a time rather than single lines.
+#[tauri::command]
+pub async fn runs_render_ansi(lines: Vec<String>) -> Result<Vec<Vec<AnsiSpan>>, AppError> {
+    Ok(ansi::render_lines(&lines))
+}
+
+/// `line` with its ANSI escapes removed — for showing output as plain text.
+#[tauri::command]
+pub async fn runs_strip_ansi(line: String) -> Result<String, AppError> {
+    Ok(ansi::strip(&line))
+}
+
 /// Drop the per-run output buffer for `id`. Called when the user dismisses
 /// a kept run-row from the launcher list.
 #[tauri::command]
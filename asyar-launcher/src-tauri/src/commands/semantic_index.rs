@@ -0,0 +1,107 @@
+//! Semantic index commands: embedding target, re-indexing, hybrid search.
+//!
+//! The heavy lifting lives in `ai::semantic_index`; these wrappers only pull
+//! managed state together.
+
+use crate::ai::embeddings::EmbeddingSettings;
+use crate::ai::semantic_index::{
+    self, IndexSyncReport, SemanticIndexState, SemanticSearchContext, SemanticSearchResult,
+};
+use crate::crypto::keystore::KeystoreState;
+use crate::error::AppError;
+use crate::storage::clipboard_fts::ClipboardFts;
+use crate::storage::embeddings::EmbeddingSource;
+use crate::storage::notes_fts::NotesFts;
+use crate::storage::DataStore;
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::State;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticIndexStatus {
+    /// False while the local Ollama default is in effect.
+    pub configured: bool,
+    pub model: String,
+    pub vector_count: usize,
+}
+
+/// Set (or clear, with `None`) the embedding target. Switching model swaps
+/// the in-memory vectors for the ones stored under the new model; the next
+/// [`semantic_index_sync`] fills any gaps.
+#[tauri::command]
+pub async fn semantic_index_configure(
+    settings: Option<EmbeddingSettings>,
+    store: State<'_, DataStore>,
+    keystore: State<'_, KeystoreState>,
+    state: State<'_, Arc<SemanticIndexState>>,
+) -> Result<(), AppError> {
+    let state = state.inner().clone();
+    state.set_settings(settings)?;
+    if state.is_loaded() {
+        return Ok(());
+    }
+    let store = store.inner().clone();
+    let master_key = *keystore.master_key();
+    tokio::task::spawn_blocking(move || {
+        let conn = store.conn()?;
+        state.load_from_disk(&conn, &master_key).map(|_| ())
+    })
+    .await
+    .map_err(|e| AppError::Other(format!("semantic index load failed: {e}")))?
+}
+
+#[tauri::command]
+pub async fn semantic_index_sync(
+    store: State<'_, DataStore>,
+    keystore: State<'_, KeystoreState>,
+    state: State<'_, Arc<SemanticIndexState>>,
+) -> Result<IndexSyncReport, AppError> {
+    semantic_index::sync(
+        store.inner().clone(),
+        *keystore.master_key(),
+        state.inner().clone(),
+    )
+    .await
+}
+
+#[tauri::command]
+pub fn semantic_index_status(
+    state: State<'_, Arc<SemanticIndexState>>,
+) -> Result<SemanticIndexStatus, AppError> {
+    Ok(SemanticIndexStatus {
+        configured: state.is_configured(),
+        model: state.settings().model_key(),
+        vector_count: state.vectors.len(),
+    })
+}
+
+/// Hybrid BM25 + vector search. `sources` defaults to all of notes,
+/// clipboard and snippets; `limit` defaults to 10.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn semantic_search(
+    query: String,
+    sources: Option<Vec<EmbeddingSource>>,
+    limit: Option<u32>,
+    store: State<'_, DataStore>,
+    keystore: State<'_, KeystoreState>,
+    state: State<'_, Arc<SemanticIndexState>>,
+    notes_fts: State<'_, Arc<NotesFts>>,
+    clipboard_fts: State<'_, Arc<ClipboardFts>>,
+) -> Result<SemanticSearchResult, AppError> {
+    let ctx = SemanticSearchContext {
+        data_store: store.inner().clone(),
+        master_key: *keystore.master_key(),
+        state: state.inner().clone(),
+        notes_fts: notes_fts.inner().clone(),
+        clipboard_fts: clipboard_fts.inner().clone(),
+    };
+    semantic_index::hybrid_search(
+        &ctx,
+        &query,
+        sources.as_deref().unwrap_or_default(),
+        limit.unwrap_or(10) as usize,
+    )
+    .await
+}
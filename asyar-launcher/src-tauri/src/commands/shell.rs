@@ -1,10 +1,11 @@
 use crate::error::AppError;
 use crate::permissions::ExtensionPermissionRegistry;
-use crate::shell::{self, ShellDescriptor, ShellEntry, ShellProcessRegistry};
+use crate::shell::{self, PtySize, ShellDescriptor, ShellEntry, ShellProcessRegistry, ShellSignal};
 use crate::storage::{
     shell::{self as shell_storage, TrustedBinary},
     DataStore,
 };
+use std::collections::HashMap;
 use tauri::{AppHandle, Emitter, State};
 
 const REQUIRED_PERMISSION: &str = "shell:spawn";
@@ -20,6 +21,8 @@ pub async fn shell_spawn(
     spawn_id: String,
     program: String,
     args: Vec<String>,
+    env: Option<HashMap<String, String>>,
+    pty: Option<PtySize>,
 ) -> Result<(), AppError> {
     extension_permissions.check(&Some(extension_id.clone()), REQUIRED_PERMISSION)?;
 
@@ -34,7 +37,28 @@ pub async fn shell_spawn(
         )));
     }
 
-    shell::spawn(app, &shell_registry, spawn_id, extension_id, program, args)
+    let env = env.unwrap_or_default();
+    match pty {
+        Some(size) => shell::pty::spawn(
+            app,
+            &shell_registry,
+            spawn_id,
+            extension_id,
+            program,
+            args,
+            env,
+            size,
+        ),
+        None => shell::spawn(
+            app,
+            &shell_registry,
+            spawn_id,
+            extension_id,
+            program,
+            args,
+            env,
+        ),
+    }
 }
 
 #[tauri::command]
@@ -62,6 +86,37 @@ pub async fn shell_close_stdin(
     shell::close_stdin(&shell_registry, &spawn_id).await
 }
 
+#[tauri::command]
+pub fn shell_resize(
+    shell_registry: State<'_, ShellProcessRegistry>,
+    extension_permissions: State<'_, ExtensionPermissionRegistry>,
+    extension_id: String,
+    spawn_id: String,
+    cols: u16,
+    rows: u16,
+) -> Result<(), AppError> {
+    extension_permissions.check(&Some(extension_id.clone()), REQUIRED_PERMISSION)?;
+    verify_spawn_ownership(&shell_registry, &extension_id, &spawn_id)?;
+    shell_registry.resize_pty(&spawn_id, PtySize { cols, rows })
+}
+
+#[tauri::command]
+pub fn shell_signal(
+    shell_registry: State<'_, ShellProcessRegistry>,
+    extension_permissions: State<'_, ExtensionPermissionRegistry>,
+    extension_id: String,
+    spawn_id: String,
+    signal: ShellSignal,
+) -> Result<(), AppError> {
+    shell_signal_inner(
+        &shell_registry,
+        &extension_permissions,
+        extension_id,
+        spawn_id,
+        signal,
+    )
+}
+
 #[tauri::command]
 pub fn shell_kill(
     shell_registry: State<'_, ShellProcessRegistry>,
@@ -202,6 +257,18 @@ pub(crate) fn shell_list_inner(
     registry.list_for_extension(&extension_id)
 }
 
+pub(crate) fn shell_signal_inner(
+    registry: &ShellProcessRegistry,
+    permissions: &ExtensionPermissionRegistry,
+    extension_id: String,
+    spawn_id: String,
+    signal: ShellSignal,
+) -> Result<(), AppError> {
+    permissions.check(&Some(extension_id.clone()), REQUIRED_PERMISSION)?;
+    let entry = verify_spawn_ownership(registry, &extension_id, &spawn_id)?;
+    shell::signal(registry, &spawn_id, &entry, signal)
+}
+
 pub(crate) fn shell_attach_inner(
     registry: &ShellProcessRegistry,
     permissions: &ExtensionPermissionRegistry,
@@ -218,6 +285,7 @@ pub(crate) fn shell_attach_inner(
         args: entry.args.clone(),
         pid: entry.pid,
         started_at: entry.started_at,
+        pty: entry.pty,
     };
 
     let terminal = if entry.finished {
@@ -357,6 +425,43 @@ mod tests {
         assert!(matches!(err, AppError::NotFound(_)), "got: {err:?}");
     }
 
+    #[test]
+    fn shell_signal_refuses_other_extensions_spawns() {
+        let registry = seeded_registry();
+        registry
+            .register_spawn("s1", "ext-a", "/bin/sleep", &[], 100)
+            .unwrap();
+        let perms = registered_permissions("ext-b");
+        let err = shell_signal_inner(
+            &registry,
+            &perms,
+            "ext-b".into(),
+            "s1".into(),
+            ShellSignal::Terminate,
+        )
+        .unwrap_err();
+        assert!(matches!(err, AppError::Permission(_)), "got: {err:?}");
+    }
+
+    #[test]
+    fn shell_signal_to_finished_spawn_is_a_no_op() {
+        // The pid may have been reused; a stray signal must not reach it.
+        let registry = seeded_registry();
+        registry
+            .register_spawn("s1", "ext-a", "/bin/sleep", &[], 100)
+            .unwrap();
+        registry.mark_finished("s1", Some(0)).unwrap();
+        let perms = registered_permissions("ext-a");
+        shell_signal_inner(
+            &registry,
+            &perms,
+            "ext-a".into(),
+            "s1".into(),
+            ShellSignal::Interrupt,
+        )
+        .expect("no-op");
+    }
+
     #[test]
     fn verify_spawn_ownership_checks_owner_and_existence() {
         let registry = seeded_registry();
@@ -22,6 +22,43 @@ pub fn sync_snippets_to_rust(
     Ok(())
 }
 
+/// Syncs user snippets that carry matching options (regex trigger, delimiter
+/// mode, app scope). Rules whose regex does not compile are left out and
+/// returned by snippet id; every other rule replaces the active set.
+#[tauri::command]
+pub fn sync_snippet_rules_to_rust(
+    rules: Vec<crate::snippets::SnippetRule>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<crate::snippets::SnippetRuleError>, AppError> {
+    sync_snippet_rules_inner(rules, &state)
+}
+
+pub(crate) fn sync_snippet_rules_inner(
+    rules: Vec<crate::snippets::SnippetRule>,
+    state: &AppState,
+) -> Result<Vec<crate::snippets::SnippetRuleError>, AppError> {
+    let (compiled, errors) = crate::snippets::compile_rules(rules);
+    for e in &errors {
+        log::warn!(
+            "[snippets] skipping snippet {}: {}",
+            e.snippet_id,
+            e.message
+        );
+    }
+    let mut guard = state.snippet_rules.lock().map_err(|_| AppError::Lock)?;
+    *guard = std::sync::Arc::new(compiled);
+    Ok(errors)
+}
+
+/// Checks a regex trigger before the editor saves it. Returns the compile
+/// error, or `None` when the listener will accept the pattern.
+#[tauri::command]
+pub fn check_snippet_regex(keyword: String) -> Option<String> {
+    crate::snippets::trigger_regex(&keyword)
+        .err()
+        .map(|e| e.to_string())
+}
+
 /// Enables or disables the snippet expansion listener.
 #[tauri::command]
 pub fn set_snippets_enabled(
@@ -139,6 +176,7 @@ mod contribute_tests {
             launcher_keep_expanded: AtomicBool::new(false),
             active_snippets: Mutex::new(HashMap::new()),
             contributed_snippets: Mutex::new(HashMap::new()),
+            snippet_rules: Mutex::new(std::sync::Arc::new(Vec::new())),
             shortcode_triggers: Mutex::new(vec![]),
             listener_started: AtomicBool::new(false),
             #[cfg(target_os = "windows")]
@@ -186,6 +224,38 @@ mod contribute_tests {
         assert!(contributed.get("ext.b").is_some());
     }
 
+    #[test]
+    fn sync_rules_keeps_good_rules_and_reports_bad_ones_by_id() {
+        use crate::snippets::{SnippetExpandMode, SnippetRule, SnippetTriggerKind};
+        let state = fresh_state();
+        let good = SnippetRule {
+            id: "mail".into(),
+            keyword: ";mail(\\w+)".into(),
+            expansion: "$1@company.com".into(),
+            trigger: SnippetTriggerKind::Regex,
+            expand_mode: SnippetExpandMode::Delimiter,
+            app_scope: None,
+        };
+        let bad = SnippetRule {
+            id: "broken".into(),
+            keyword: "(unclosed".into(),
+            ..good.clone()
+        };
+
+        let errors = sync_snippet_rules_inner(vec![bad, good], &state).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].snippet_id, "broken");
+        let rules = state.snippet_rules.lock().unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].rule.id, "mail");
+    }
+
+    #[test]
+    fn check_snippet_regex_reports_only_invalid_patterns() {
+        assert_eq!(check_snippet_regex(";n(?P<n>\\d+)".into()), None);
+        assert!(check_snippet_regex("(?<=x)y".into()).is_some());
+    }
+
     #[test]
     fn contribute_rejects_malformed_keys_atomically() {
         let state = fresh_state();
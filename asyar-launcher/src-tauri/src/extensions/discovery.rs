@@ -319,7 +319,7 @@ pub fn scan_extensions_dir(dir: &Path, is_built_in: bool) -> Vec<ExtensionRecord
                 // Validate schedule declarations — strip invalid ones gracefully
                 for cmd in &mut manifest.commands {
                     if let Some(ref schedule) = cmd.schedule {
-                        if let Err(e) = scheduler::validate_interval(schedule.interval_seconds) {
+                        if let Err(e) = scheduler::validate_schedule(schedule) {
                             warn!(
                                 "Extension '{}' command '{}': {}. Stripping schedule.",
                                 manifest.id, cmd.id, e
@@ -1163,7 +1163,8 @@ mod discovery_tests {
             component: None,
             icon: None,
             schedule: Some(ScheduleDeclaration {
-                interval_seconds: 5,
+                interval_seconds: Some(5),
+                ..Default::default()
             }),
             searchable: None,
             preferences: None,
@@ -1174,7 +1175,7 @@ mod discovery_tests {
         };
         // Simulate what discovery does
         if let Some(ref schedule) = cmd.schedule {
-            if scheduler::validate_interval(schedule.interval_seconds).is_err() {
+            if scheduler::validate_schedule(schedule).is_err() {
                 cmd.schedule = None;
             }
         }
@@ -1192,7 +1193,8 @@ mod discovery_tests {
             component: None,
             icon: None,
             schedule: Some(ScheduleDeclaration {
-                interval_seconds: 300,
+                interval_seconds: Some(300),
+                ..Default::default()
             }),
             searchable: None,
             preferences: None,
@@ -1202,12 +1204,38 @@ mod discovery_tests {
             search_bar_accessory: None,
         };
         if let Some(ref schedule) = cmd.schedule {
-            if scheduler::validate_interval(schedule.interval_seconds).is_err() {
+            if scheduler::validate_schedule(schedule).is_err() {
                 cmd.schedule = None;
             }
         }
         assert!(cmd.schedule.is_some());
-        assert_eq!(cmd.schedule.unwrap().interval_seconds, 300);
+        assert_eq!(cmd.schedule.unwrap().interval_seconds, Some(300));
+    }
+
+    #[test]
+    fn test_schedule_validation_checks_cron_and_timezone() {
+        let schedule = |json: &str| serde_json::from_str::<ScheduleDeclaration>(json).unwrap();
+        for valid in [
+            r#"{ "cron": "30 8 * * mon-fri" }"#,
+            r#"{ "cron": "0 9 * * mon#1", "timezone": "America/New_York" }"#,
+        ] {
+            assert!(
+                scheduler::validate_schedule(&schedule(valid)).is_ok(),
+                "{valid}"
+            );
+        }
+        for invalid in [
+            r#"{}"#,
+            r#"{ "cron": "30 8 * *" }"#,
+            r#"{ "cron": "0 9 * * *", "timezone": "Mars/Olympus" }"#,
+            r#"{ "cron": "0 9 * * *", "intervalSeconds": 60 }"#,
+            r#"{ "intervalSeconds": 60, "timezone": "UTC" }"#,
+        ] {
+            assert!(
+                scheduler::validate_schedule(&schedule(invalid)).is_err(),
+                "{invalid}"
+            );
+        }
     }
 
     fn unique_temp_dir(prefix: &str) -> std::path::PathBuf {
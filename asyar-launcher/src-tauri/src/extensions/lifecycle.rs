@@ -316,6 +316,26 @@ pub(crate) async fn uninstall(
         }
     }
 
+    // Drop this extension's sampling lease and resource alerts, stopping the
+    // process sampler if nothing else needs it.
+    if let Some(sampler) = app_handle.try_state::<crate::process_manager::sampler::ProcessSampler>()
+    {
+        match sampler.release_extension(extension_id) {
+            Ok(n) if n > 0 => {
+                info!(
+                    "Deleted {} resource alert(s) for extension '{}'",
+                    n, extension_id
+                )
+            }
+            Ok(_) => {}
+            Err(e) => warn!(
+                "Failed to release process sampling for '{}': {}",
+                extension_id, e
+            ),
+        }
+        sampler.sync_schedule(app_handle);
+    }
+
     // Remove all system-event subscriptions held by this extension.
     if let Some(hub) =
         app_handle.try_state::<std::sync::Arc<crate::system_events::SystemEventsHub>>()
@@ -1044,6 +1064,27 @@ pub(crate) async fn set_enabled(
             }
         }
 
+        // Resource alerts go too: they'd notify for an extension that can't
+        // respond. It saves them again when it next starts.
+        if let Some(sampler) =
+            app_handle.try_state::<crate::process_manager::sampler::ProcessSampler>()
+        {
+            match sampler.release_extension(extension_id) {
+                Ok(n) if n > 0 => {
+                    info!(
+                        "Deleted {} resource alert(s) for disabled extension '{}'",
+                        n, extension_id
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => warn!(
+                    "Failed to release process sampling for disabled '{}': {}",
+                    extension_id, e
+                ),
+            }
+            sampler.sync_schedule(app_handle);
+        }
+
         // Drop any scheduled one-shot timers. A disabled extension's iframe
         // won't exist, so leaving timers in place would produce silent
         // misfires; user can reschedule on re-enable if they want.
@@ -101,10 +101,19 @@ pub(crate) fn get_dev_extension_paths(
     Ok(dev_extensions)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A command's `schedule`: a fixed interval, or a cron expression read in an
+/// IANA time zone (the system's own when omitted). Exactly one of
+/// `interval_seconds` and `cron` is set — see
+/// [`scheduler::validate_schedule`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ScheduleDeclaration {
-    pub interval_seconds: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_seconds: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cron: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -1247,7 +1256,24 @@ mod tests {
         }"#;
         let cmd: ExtensionCommand = serde_json::from_str(json).unwrap();
         assert!(cmd.schedule.is_some());
-        assert_eq!(cmd.schedule.unwrap().interval_seconds, 300);
+        assert_eq!(cmd.schedule.unwrap().interval_seconds, Some(300));
+    }
+
+    #[test]
+    fn test_deserialize_command_with_cron_schedule() {
+        let json = r#"{
+            "id": "morning-digest",
+            "name": "Morning Digest",
+            "mode": "background",
+            "schedule": { "cron": "30 8 * * mon-fri", "timezone": "Europe/Berlin" }
+        }"#;
+        let schedule = serde_json::from_str::<ExtensionCommand>(json)
+            .unwrap()
+            .schedule
+            .unwrap();
+        assert_eq!(schedule.interval_seconds, None);
+        assert_eq!(schedule.cron.as_deref(), Some("30 8 * * mon-fri"));
+        assert_eq!(schedule.timezone.as_deref(), Some("Europe/Berlin"));
     }
 
     #[test]
@@ -1264,11 +1290,13 @@ mod tests {
     #[test]
     fn test_schedule_roundtrip_serialization() {
         let decl = ScheduleDeclaration {
-            interval_seconds: 600,
+            interval_seconds: Some(600),
+            ..Default::default()
         };
         let json = serde_json::to_string(&decl).unwrap();
+        assert_eq!(json, r#"{"intervalSeconds":600}"#);
         let parsed: ScheduleDeclaration = serde_json::from_str(&json).unwrap();
-        assert_eq!(parsed.interval_seconds, 600);
+        assert_eq!(parsed.interval_seconds, Some(600));
     }
 
     #[test]
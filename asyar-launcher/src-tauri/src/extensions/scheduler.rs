@@ -1,5 +1,7 @@
 //! Extension background scheduler — manages tokio timers for declarative scheduled commands.
 
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use log::warn;
 use serde::Serialize;
 use std::collections::HashMap;
@@ -7,18 +9,27 @@ use std::sync::Mutex;
 use tauri::{AppHandle, Manager};
 use tokio::task::JoinHandle;
 
-use super::ExtensionRegistryState;
+use super::{ExtensionCommand, ExtensionRecord, ExtensionRegistryState, ScheduleDeclaration};
 use crate::error::AppError;
 use crate::extensions::extension_runtime::emitter::{emit_typed, EventEmitter};
 use crate::extensions::extension_runtime::{
     ContextRole, DispatchOutcome, ExtensionRuntimeManager, MessageKind, PendingMessage,
     TriggerSource, EVENT_DELIVER, EVENT_MOUNT,
 };
+use crate::scheduler::{CronSchedule, Job, Scheduler};
 use std::sync::Arc;
 
 const MIN_INTERVAL_SECS: u64 = 10;
 const MAX_INTERVAL_SECS: u64 = 86400;
 
+/// Cron schedules run as jobs of the central [`Scheduler`] under
+/// `extension-schedule:<extension>::<command>`, which gives them DST-aware
+/// next-fire times, catch-up after sleep or quit, and persisted run state.
+const CRON_JOB_PREFIX: &str = "extension-schedule:";
+
+const DEFAULT_UPCOMING_FIRES: usize = 5;
+const MAX_UPCOMING_FIRES: usize = 50;
+
 pub fn validate_interval(seconds: u64) -> Result<u64, AppError> {
     if !(MIN_INTERVAL_SECS..=MAX_INTERVAL_SECS).contains(&seconds) {
         return Err(AppError::Validation(format!(
@@ -29,6 +40,45 @@ pub fn validate_interval(seconds: u64) -> Result<u64, AppError> {
     Ok(seconds)
 }
 
+/// The zone a cron schedule is read in: the declared IANA name, or the
+/// system's own zone when none is declared.
+pub fn resolve_timezone(name: Option<&str>) -> Result<Tz, AppError> {
+    match name {
+        Some(name) => name
+            .parse()
+            .map_err(|_| AppError::Validation(format!("Unknown schedule timezone '{name}'"))),
+        None => Ok(iana_time_zone::get_timezone()
+            .ok()
+            .and_then(|name| name.parse().ok())
+            .unwrap_or(chrono_tz::UTC)),
+    }
+}
+
+/// Checks a manifest `schedule` at discovery time: exactly one of
+/// `intervalSeconds` and `cron`, and whichever it is well-formed.
+pub fn validate_schedule(schedule: &ScheduleDeclaration) -> Result<(), AppError> {
+    match (schedule.interval_seconds, schedule.cron.as_deref()) {
+        (Some(_), Some(_)) => Err(AppError::Validation(
+            "Schedule declares both intervalSeconds and cron; pick one".to_string(),
+        )),
+        (None, None) => Err(AppError::Validation(
+            "Schedule needs intervalSeconds or cron".to_string(),
+        )),
+        (Some(_), None) if schedule.timezone.is_some() => Err(AppError::Validation(
+            "Schedule timezone only applies to cron schedules".to_string(),
+        )),
+        (Some(seconds), None) => validate_interval(seconds).map(|_| ()),
+        (None, Some(expression)) => {
+            CronSchedule::parse(expression)?;
+            resolve_timezone(schedule.timezone.as_deref()).map(|_| ())
+        }
+    }
+}
+
+fn cron_job_id(extension_id: &str, command_id: &str) -> String {
+    format!("{CRON_JOB_PREFIX}{extension_id}::{command_id}")
+}
+
 /// Tauri-managed state holding all active scheduler task handles.
 pub struct SchedulerState {
     pub tasks: Mutex<HashMap<String, JoinHandle<()>>>,
@@ -62,8 +112,23 @@ pub struct ScheduledTaskInfo {
     pub extension_name: String,
     pub command_id: String,
     pub command_name: String,
-    pub interval_seconds: u64,
+    pub interval_seconds: Option<u64>,
+    pub cron: Option<String>,
+    /// The zone the cron expression is read in, resolved when omitted.
+    pub timezone: Option<String>,
     pub active: bool,
+    /// Unix millis of the next cron fire; interval timers don't report one.
+    pub next_fire_at: Option<i64>,
+}
+
+/// One upcoming fire of an extension's cron schedules.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpcomingFire {
+    pub command_id: String,
+    pub command_name: String,
+    /// Unix millis.
+    pub at: i64,
 }
 
 /// Build the `PendingMessage` that the scheduler enqueues for a single tick.
@@ -136,6 +201,26 @@ fn handle_dispatch_outcome(
     }
 }
 
+/// Dispatch one scheduled tick of `command_id` into the extension's Worker
+/// machine.
+fn dispatch_tick(app_handle: &AppHandle, extension_id: &str, command_id: &str) {
+    use crate::extensions::extension_runtime::emitter::TauriEventEmitter;
+    let now = std::time::Instant::now();
+    let msg = build_scheduled_command_message(command_id, now);
+    if let Some(mgr) = app_handle.try_state::<Arc<ExtensionRuntimeManager>>() {
+        let outcome = mgr.enqueue_worker(extension_id, msg, now);
+        let emitter = TauriEventEmitter {
+            app: app_handle.clone(),
+        };
+        handle_dispatch_outcome(&emitter, extension_id, command_id, &outcome);
+    } else {
+        warn!(
+            "Scheduler: ExtensionRuntimeManager unavailable for {}::{}",
+            extension_id, command_id
+        );
+    }
+}
+
 /// Spawn a tokio task that dispatches a scheduled Command into the Worker machine.
 fn spawn_timer(
     app_handle: AppHandle,
@@ -143,70 +228,112 @@ fn spawn_timer(
     command_id: String,
     interval_secs: u64,
 ) -> JoinHandle<()> {
-    use crate::extensions::extension_runtime::emitter::TauriEventEmitter;
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_secs));
         // Skip the first tick (fires immediately by default)
         interval.tick().await;
         loop {
             interval.tick().await;
-            let now = std::time::Instant::now();
-            let msg = build_scheduled_command_message(&command_id, now);
-            if let Some(mgr) = app_handle.try_state::<Arc<ExtensionRuntimeManager>>() {
-                let outcome = mgr.enqueue_worker(&extension_id, msg, now);
-                let emitter = TauriEventEmitter {
-                    app: app_handle.clone(),
-                };
-                handle_dispatch_outcome(&emitter, &extension_id, &command_id, &outcome);
-            } else {
-                warn!(
-                    "Scheduler: ExtensionRuntimeManager unavailable for {}::{}",
-                    extension_id, command_id
-                );
-            }
+            dispatch_tick(&app_handle, &extension_id, &command_id);
         }
     })
 }
 
+/// Register a cron schedule with the central scheduler. Its default
+/// catch-up policy runs a fire missed during sleep or while Asyar was quit
+/// once, as soon as it can.
+fn register_cron(
+    app_handle: &AppHandle,
+    jobs: &Scheduler,
+    extension_id: &str,
+    command_id: &str,
+    schedule: &ScheduleDeclaration,
+) -> Result<(), AppError> {
+    let expression = schedule.cron.as_deref().unwrap_or_default();
+    let timezone = resolve_timezone(schedule.timezone.as_deref())?;
+    let app = app_handle.clone();
+    let (ext_id, cmd_id) = (extension_id.to_string(), command_id.to_string());
+    let job = Job::cron(
+        cron_job_id(extension_id, command_id),
+        expression,
+        timezone,
+        move || {
+            let app = app.clone();
+            let (ext_id, cmd_id) = (ext_id.clone(), cmd_id.clone());
+            async move { dispatch_tick(&app, &ext_id, &cmd_id) }
+        },
+    )?;
+    jobs.register(job);
+    log::info!(
+        "Scheduler: registered cron '{}' ({}) for {}::{}",
+        expression,
+        timezone.name(),
+        extension_id,
+        command_id
+    );
+    Ok(())
+}
+
+/// Start every valid schedule of one enabled, compatible extension.
+fn start_record_tasks(
+    app_handle: &AppHandle,
+    jobs: &Scheduler,
+    tasks: &mut HashMap<String, JoinHandle<()>>,
+    ext_id: &str,
+    record: &ExtensionRecord,
+) {
+    if !record.enabled {
+        return;
+    }
+    if record.compatibility != crate::extensions::CompatibilityStatus::Compatible {
+        return;
+    }
+    for cmd in &record.manifest.commands {
+        let Some(ref schedule) = cmd.schedule else {
+            continue;
+        };
+        if validate_schedule(schedule).is_err() {
+            continue;
+        }
+        if let Some(interval_seconds) = schedule.interval_seconds {
+            let task_key = format!("{}::{}", ext_id, cmd.id);
+            let handle = spawn_timer(
+                app_handle.clone(),
+                ext_id.to_string(),
+                cmd.id.clone(),
+                interval_seconds,
+            );
+            tasks.insert(task_key, handle);
+            log::info!(
+                "Scheduler: started timer for {}::{} (every {}s)",
+                ext_id,
+                cmd.id,
+                interval_seconds
+            );
+        } else if let Err(e) = register_cron(app_handle, jobs, ext_id, &cmd.id, schedule) {
+            warn!(
+                "Scheduler: could not schedule {}::{}: {}",
+                ext_id, cmd.id, e
+            );
+        }
+    }
+}
+
 /// Start scheduled tasks for ALL enabled extensions in the registry.
 pub fn start_all_tasks(
     app_handle: &AppHandle,
     registry: &ExtensionRegistryState,
     scheduler: &SchedulerState,
+    jobs: &Scheduler,
 ) -> Result<(), AppError> {
     // Stop any existing tasks first
-    stop_all_tasks(scheduler)?;
+    stop_all_tasks(scheduler, jobs)?;
 
     let reg = registry.extensions.lock().map_err(|_| AppError::Lock)?;
     let mut tasks = scheduler.tasks.lock().map_err(|_| AppError::Lock)?;
 
     for (ext_id, record) in reg.iter() {
-        if !record.enabled {
-            continue;
-        }
-        if record.compatibility != crate::extensions::CompatibilityStatus::Compatible {
-            continue;
-        }
-        for cmd in &record.manifest.commands {
-            if let Some(ref schedule) = cmd.schedule {
-                if validate_interval(schedule.interval_seconds).is_ok() {
-                    let task_key = format!("{}::{}", ext_id, cmd.id);
-                    let handle = spawn_timer(
-                        app_handle.clone(),
-                        ext_id.clone(),
-                        cmd.id.clone(),
-                        schedule.interval_seconds,
-                    );
-                    tasks.insert(task_key, handle);
-                    log::info!(
-                        "Scheduler: started timer for {}::{} (every {}s)",
-                        ext_id,
-                        cmd.id,
-                        schedule.interval_seconds
-                    );
-                }
-            }
-        }
+        start_record_tasks(app_handle, jobs, &mut tasks, ext_id, record);
     }
     Ok(())
 }
@@ -216,38 +343,14 @@ pub fn start_tasks_for_extension(
     app_handle: &AppHandle,
     registry: &ExtensionRegistryState,
     scheduler: &SchedulerState,
+    jobs: &Scheduler,
     extension_id: &str,
 ) -> Result<(), AppError> {
     let reg = registry.extensions.lock().map_err(|_| AppError::Lock)?;
     let mut tasks = scheduler.tasks.lock().map_err(|_| AppError::Lock)?;
 
     if let Some(record) = reg.get(extension_id) {
-        if !record.enabled {
-            return Ok(());
-        }
-        if record.compatibility != crate::extensions::CompatibilityStatus::Compatible {
-            return Ok(());
-        }
-        for cmd in &record.manifest.commands {
-            if let Some(ref schedule) = cmd.schedule {
-                if validate_interval(schedule.interval_seconds).is_ok() {
-                    let task_key = format!("{}::{}", extension_id, cmd.id);
-                    let handle = spawn_timer(
-                        app_handle.clone(),
-                        extension_id.to_string(),
-                        cmd.id.clone(),
-                        schedule.interval_seconds,
-                    );
-                    tasks.insert(task_key, handle);
-                    log::info!(
-                        "Scheduler: started timer for {}::{} (every {}s)",
-                        extension_id,
-                        cmd.id,
-                        schedule.interval_seconds
-                    );
-                }
-            }
-        }
+        start_record_tasks(app_handle, jobs, &mut tasks, extension_id, record);
     }
     Ok(())
 }
@@ -255,6 +358,7 @@ pub fn start_tasks_for_extension(
 /// Stop all scheduled tasks for a given extension.
 pub fn stop_tasks_for_extension(
     scheduler: &SchedulerState,
+    jobs: &Scheduler,
     extension_id: &str,
 ) -> Result<(), AppError> {
     let mut tasks = scheduler.tasks.lock().map_err(|_| AppError::Lock)?;
@@ -270,16 +374,22 @@ pub fn stop_tasks_for_extension(
             log::info!("Scheduler: stopped timer for {}", key);
         }
     }
+    for id in jobs.unregister_prefix(&cron_job_id(extension_id, "")) {
+        log::info!("Scheduler: unregistered {}", id);
+    }
     Ok(())
 }
 
 /// Stop ALL scheduled tasks.
-pub fn stop_all_tasks(scheduler: &SchedulerState) -> Result<(), AppError> {
+pub fn stop_all_tasks(scheduler: &SchedulerState, jobs: &Scheduler) -> Result<(), AppError> {
     let mut tasks = scheduler.tasks.lock().map_err(|_| AppError::Lock)?;
     for (key, handle) in tasks.drain() {
         handle.abort();
         log::info!("Scheduler: stopped timer for {}", key);
     }
+    for id in jobs.unregister_prefix(CRON_JOB_PREFIX) {
+        log::info!("Scheduler: unregistered {}", id);
+    }
     Ok(())
 }
 
@@ -287,6 +397,7 @@ pub fn stop_all_tasks(scheduler: &SchedulerState) -> Result<(), AppError> {
 pub fn get_scheduled_task_info(
     registry: &ExtensionRegistryState,
     scheduler: &SchedulerState,
+    jobs: &Scheduler,
 ) -> Result<Vec<ScheduledTaskInfo>, AppError> {
     let reg = registry.extensions.lock().map_err(|_| AppError::Lock)?;
     let tasks = scheduler.tasks.lock().map_err(|_| AppError::Lock)?;
@@ -295,14 +406,26 @@ pub fn get_scheduled_task_info(
     for (ext_id, record) in reg.iter() {
         for cmd in &record.manifest.commands {
             if let Some(ref schedule) = cmd.schedule {
-                let task_key = format!("{}::{}", ext_id, cmd.id);
+                let (active, next_fire_at, timezone) = if schedule.cron.is_some() {
+                    let job = jobs.job(&cron_job_id(ext_id, &cmd.id));
+                    let timezone = resolve_timezone(schedule.timezone.as_deref())
+                        .ok()
+                        .map(|tz| tz.name().to_string());
+                    (job.is_some(), job.and_then(|job| job.next_run_at), timezone)
+                } else {
+                    let task_key = format!("{}::{}", ext_id, cmd.id);
+                    (tasks.contains_key(&task_key), None, None)
+                };
                 infos.push(ScheduledTaskInfo {
                     extension_id: ext_id.clone(),
                     extension_name: record.manifest.name.clone(),
                     command_id: cmd.id.clone(),
                     command_name: cmd.name.clone(),
                     interval_seconds: schedule.interval_seconds,
-                    active: tasks.contains_key(&task_key),
+                    cron: schedule.cron.clone(),
+                    timezone,
+                    active,
+                    next_fire_at,
                 });
             }
         }
@@ -310,6 +433,66 @@ pub fn get_scheduled_task_info(
     Ok(infos)
 }
 
+/// The next `limit` fires across `commands`' cron schedules, soonest first.
+/// Interval timers count from when the extension loaded, so they have no
+/// calendar to project and are left out.
+pub fn upcoming_fires(
+    commands: &[ExtensionCommand],
+    now: DateTime<Utc>,
+    limit: usize,
+) -> Vec<UpcomingFire> {
+    let mut fires = Vec::new();
+    for cmd in commands {
+        let Some(schedule) = &cmd.schedule else {
+            continue;
+        };
+        let Some(expression) = schedule.cron.as_deref() else {
+            continue;
+        };
+        let (Ok(cron), Ok(timezone)) = (
+            CronSchedule::parse(expression),
+            resolve_timezone(schedule.timezone.as_deref()),
+        ) else {
+            continue;
+        };
+        let mut after = now.with_timezone(&timezone);
+        for _ in 0..limit {
+            let Some(next) = cron.next_after(&after) else {
+                break;
+            };
+            fires.push(UpcomingFire {
+                command_id: cmd.id.clone(),
+                command_name: cmd.name.clone(),
+                at: next.timestamp_millis(),
+            });
+            after = next;
+        }
+    }
+    fires.sort_by_key(|fire| fire.at);
+    fires.truncate(limit);
+    fires
+}
+
+/// Upcoming cron fires of one extension for its settings page. A disabled
+/// extension has none.
+pub fn get_upcoming_fires(
+    registry: &ExtensionRegistryState,
+    extension_id: &str,
+    limit: Option<usize>,
+) -> Result<Vec<UpcomingFire>, AppError> {
+    let reg = registry.extensions.lock().map_err(|_| AppError::Lock)?;
+    let record = reg
+        .get(extension_id)
+        .ok_or_else(|| AppError::NotFound(format!("Extension not found: {}", extension_id)))?;
+    if !record.enabled {
+        return Ok(Vec::new());
+    }
+    let limit = limit
+        .unwrap_or(DEFAULT_UPCOMING_FIRES)
+        .min(MAX_UPCOMING_FIRES);
+    Ok(upcoming_fires(&record.manifest.commands, Utc::now(), limit))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -340,6 +523,52 @@ mod tests {
         assert_eq!(validate_interval(3600).unwrap(), 3600);
     }
 
+    fn command(json: &str) -> ExtensionCommand {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn upcoming_fires_merge_cron_schedules_soonest_first() {
+        use chrono::TimeZone;
+        let commands = vec![
+            command(
+                r#"{ "id": "digest", "name": "Digest", "mode": "background",
+                     "schedule": { "cron": "30 8 * * mon-fri", "timezone": "Europe/Berlin" } }"#,
+            ),
+            command(
+                r#"{ "id": "report", "name": "Report", "mode": "background",
+                     "schedule": { "cron": "0 9 * * mon#1", "timezone": "UTC" } }"#,
+            ),
+            command(
+                r#"{ "id": "poll", "name": "Poll", "mode": "background",
+                     "schedule": { "intervalSeconds": 60 } }"#,
+            ),
+        ];
+        // Friday 29 May 2026, 12:00 UTC.
+        let now = Utc.with_ymd_and_hms(2026, 5, 29, 12, 0, 0).unwrap();
+        let at = |d: u32, h: u32, m: u32| {
+            Utc.with_ymd_and_hms(2026, 6, d, h, m, 0)
+                .unwrap()
+                .timestamp_millis()
+        };
+
+        let fires = upcoming_fires(&commands, now, 3);
+        let summary: Vec<(&str, i64)> = fires
+            .iter()
+            .map(|fire| (fire.command_id.as_str(), fire.at))
+            .collect();
+        // 08:30 in Berlin is 06:30 UTC in summer; the first Monday of June
+        // is the 1st.
+        assert_eq!(
+            summary,
+            vec![
+                ("digest", at(1, 6, 30)),
+                ("report", at(1, 9, 0)),
+                ("digest", at(2, 6, 30)),
+            ]
+        );
+    }
+
     #[test]
     fn scheduled_command_message_must_use_command_kind_not_action() {
         // Contract: scheduler ticks are dispatched to extension workers via
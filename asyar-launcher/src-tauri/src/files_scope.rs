@@ -203,7 +203,7 @@ const CASE_INSENSITIVE_FS: bool = cfg!(any(windows, target_os = "macos"));
 /// Component-wise prefix check, case-insensitive on Windows and macOS so
 /// `c:\windows\...` or `~/library/keychains/...` can't sidestep a
 /// protected root.
-fn starts_with_case_aware(path: &Path, root: &Path) -> bool {
+pub(crate) fn starts_with_case_aware(path: &Path, root: &Path) -> bool {
     if path.starts_with(root) {
         return true;
     }
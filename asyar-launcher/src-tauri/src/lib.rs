@@ -35,6 +35,11 @@ pub struct AppState {
     /// active matcher view at lookup time. User-created snippets in
     /// `active_snippets` shadow these on key collision.
     pub contributed_snippets: Mutex<crate::snippets::ContributedSnippets>,
+    /// User snippets carrying matching options (regex triggers, delimiter
+    /// mode, app scopes), compiled at sync time. Tried before the flat
+    /// keyword maps above. Behind an `Arc` so each keystroke takes a cheap
+    /// snapshot instead of cloning every compiled regex.
+    pub snippet_rules: Mutex<std::sync::Arc<Vec<crate::snippets::CompiledSnippetRule>>>,
     /// Active trigger characters/delimiters for shortcode miss events.
     pub shortcode_triggers: Mutex<Vec<String>>,
     /// Guards against registering the global event listener more than once.
@@ -211,19 +216,44 @@ pub fn apply_linux_webkit_dmabuf_workaround() {
     }
 }
 
+/// Handles launcher sub-commands that run instead of the app — currently
+/// only `asyar mcp`, the stdio MCP bridge. Returns the exit code, or `None`
+/// when the launcher should start normally.
+pub fn run_subcommand() -> Option<i32> {
+    match std::env::args().nth(1).as_deref() {
+        Some(mcp::host::stdio::SUBCOMMAND) => Some(mcp::host::stdio::run()),
+        _ => None,
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Build the MCP transport factory before entering the builder chain. Its
     // runtime resolver starts with no `AppHandle`; `setup_app` wires the real
     // one in once it's available (see `AppRuntimeResolver`).
     let mcp_runtime_resolver = std::sync::Arc::new(AppRuntimeResolver::new());
-    let mcp_factory = std::sync::Arc::new(mcp::MultiTransportFactory::new(
-        mcp_runtime_resolver.clone(),
-    ));
-    let mcp_supervisor = std::sync::Arc::new(mcp::McpSupervisor::new(
-        mcp_factory,
-        mcp::SupervisorConfig::default(),
-    ));
+    // Remote servers that need OAuth sign in through the browser; the URL
+    // opener is wired in `setup_app` alongside the runtime resolver.
+    let mcp_authorizer = std::sync::Arc::new(mcp::oauth::McpAuthorizer::new(std::sync::Arc::new(
+        mcp::oauth::keyring(),
+    )));
+    let mcp_factory = std::sync::Arc::new(
+        mcp::MultiTransportFactory::new(mcp_runtime_resolver.clone())
+            .with_authorizer(mcp_authorizer.clone()),
+    );
+    // Sampling and elicitation requests from connected servers; the store
+    // and the frontend emitter are attached in `setup_app`.
+    let mcp_server_requests = std::sync::Arc::new(mcp::server_requests::McpServerRequests::new());
+    let request_handlers: mcp::RequestHandlerFactory = {
+        let requests = mcp_server_requests.clone();
+        std::sync::Arc::new(move |config: &mcp::McpServerConfig| {
+            requests.for_server(&config.id, &config.display_name)
+        })
+    };
+    let mcp_supervisor = std::sync::Arc::new(
+        mcp::McpSupervisor::new(mcp_factory, mcp::SupervisorConfig::default())
+            .with_request_handlers(request_handlers),
+    );
 
     let builder = tauri::Builder::default()
         // Single-instance must be the FIRST plugin: it intercepts a second
@@ -344,11 +374,16 @@ pub fn run() {
         .manage(agents::runner::AgentRunnerState::default())
         .manage(mcp_supervisor)
         .manage(mcp_runtime_resolver)
+        .manage(mcp_authorizer)
+        .manage(mcp_server_requests)
         .manage(ext_builder::ExtBuilderState::default())
         .manage(calculator::CalculatorState::default())
         .manage(runtimes::RuntimeManager::new())
         .manage(feedback::channel::FeedbackChannelState::default())
         .manage(crate::agents::cache::AgentResponseCache::default())
+        .manage(std::sync::Arc::new(
+            crate::ai::semantic_index::SemanticIndexState::new(),
+        ))
         .manage(AppState {
             focus_locked: AtomicBool::new(false),
             user_shortcuts: Mutex::new(HashMap::new()),
@@ -358,6 +393,7 @@ pub fn run() {
             launcher_keep_expanded: AtomicBool::new(false),
             active_snippets: Mutex::new(HashMap::new()),
             contributed_snippets: Mutex::new(HashMap::new()),
+            snippet_rules: Mutex::new(std::sync::Arc::new(Vec::new())),
             shortcode_triggers: Mutex::new(vec![":".to_string()]),
             listener_started: AtomicBool::new(false),
             #[cfg(target_os = "windows")]
@@ -426,6 +462,7 @@ pub fn run() {
             commands::set_extension_enabled,
             commands::get_extension,
             commands::get_scheduled_tasks,
+            commands::get_extension_upcoming_fires,
             commands::extension_runtime::dispatch_to_extension,
             commands::extension_runtime::iframe_ready_ack,
             commands::extension_runtime::iframe_unmount_ack,
@@ -523,6 +560,8 @@ pub fn run() {
             extension_tray::commands::tray_remove_all_for_extension,
             commands::expand_and_paste,
             commands::sync_snippets_to_rust,
+            commands::sync_snippet_rules_to_rust,
+            commands::check_snippet_regex,
             commands::set_snippets_enabled,
             commands::check_snippet_permission,
             commands::open_accessibility_preferences,
@@ -650,6 +689,8 @@ pub fn run() {
             commands::shell_attach,
             commands::shell_write_stdin,
             commands::shell_close_stdin,
+            commands::shell_resize,
+            commands::shell_signal,
             commands::shell_resolve_path,
             commands::shell_check_trust,
             commands::shell_grant_trust,
@@ -699,6 +740,13 @@ pub fn run() {
             commands::screen_pick_color,
             commands::process::process_list,
             commands::process::process_kill,
+            commands::process::process_sampling_start,
+            commands::process::process_sampling_stop,
+            commands::process::process_history,
+            commands::process::process_top,
+            commands::process::process_alerts_list,
+            commands::process::process_alert_save,
+            commands::process::process_alert_delete,
             commands::system_events_subscribe,
             commands::system_events_unsubscribe,
             commands::app_events_subscribe,
@@ -754,6 +802,11 @@ pub fn run() {
             commands::runs::runs_history_list,
             commands::runs::runs_history_clear,
             commands::runs::runs_get_output,
+            commands::runs::runs_read_log,
+            commands::runs::runs_search_logs,
+            commands::runs::runs_get_inputs,
+            commands::runs::runs_render_ansi,
+            commands::runs::runs_strip_ansi,
             commands::runs::runs_dismiss,
             commands::runs::runs_upsert_bucket,
             commands::templating::resolve_template,
@@ -776,6 +829,23 @@ pub fn run() {
             commands::agents::agents_backfill_thread_titles,
             commands::agents::agents_message_insert,
             commands::agents::agents_messages_list,
+            commands::agents::agents_message_edit,
+            commands::agents::agents_thread_regenerate,
+            commands::agents::agents_thread_select_branch,
+            commands::agents::agents_thread_summary,
+            commands::agents::agents_memories_list,
+            commands::agents::agents_memory_delete,
+            commands::agents::agents_triggers_list,
+            commands::agents::agents_trigger_save,
+            commands::agents::agents_trigger_delete,
+            commands::agents::agents_tool_policy_get,
+            commands::agents::agents_tool_policy_set,
+            commands::agents::agents_tool_audit_list,
+            commands::agents::agents_tool_write_resolve,
+            commands::agents::agents_thread_usage,
+            commands::agents::agents_spend_summary,
+            commands::agents::agents_run_attempts,
+            commands::agents::agents_attachment_from_file,
             commands::agents::agents_run_thread,
             commands::agents::agents_run_silent,
             commands::agents::agents_report_tool_result,
@@ -790,11 +860,19 @@ pub fn run() {
             agents::editor::agents_editor_list_models,
             agents::editor::agents_editor_save,
             agents::editor::agents_provider_removal_blockers,
+            agents::bundle::agents_export_bundle,
+            agents::bundle::agents_import_preview,
+            agents::bundle::agents_import_bundle,
             // Agent tools registry
             agents::tools::agents_tools_list,
             agents::tools::agents_tools_register_tier2,
             agents::tools::agents_tools_unregister_tier2,
             agents::tools::agents_invoke_builtin_tool,
+            // Semantic index (hybrid BM25 + vector search)
+            commands::semantic_index::semantic_index_configure,
+            commands::semantic_index::semantic_index_sync,
+            commands::semantic_index::semantic_index_status,
+            commands::semantic_index::semantic_search,
             // MCP server management
             commands::mcp::mcp_list_servers,
             commands::mcp::mcp_install_server,
@@ -808,10 +886,25 @@ pub fn run() {
             commands::mcp::mcp_set_permission,
             commands::mcp::mcp_get_permission,
             commands::mcp::mcp_list_server_tools,
+            commands::mcp::mcp_list_resources,
+            commands::mcp::mcp_list_resource_templates,
+            commands::mcp::mcp_list_prompts,
+            commands::mcp::mcp_read_resource,
+            commands::mcp::mcp_get_prompt,
+            commands::mcp::mcp_list_catalog,
+            commands::mcp::mcp_resource_attachments,
+            commands::mcp::mcp_render_prompt,
             commands::mcp::mcp_list_permissions,
             commands::mcp::mcp_delete_permission,
             commands::mcp::mcp_get_strict_mode,
             commands::mcp::mcp_set_strict_mode,
+            commands::mcp::mcp_host_status,
+            commands::mcp::mcp_host_set_enabled,
+            commands::mcp::mcp_host_rotate_token,
+            commands::mcp::mcp_host_resolve_permission,
+            commands::mcp::mcp_host_report_tool_result,
+            commands::mcp::mcp_resolve_sampling,
+            commands::mcp::mcp_resolve_elicitation,
             // AI Extension Builder
             ext_builder::commands::ext_builder_start,
             ext_builder::commands::ext_builder_check_runtimes,
@@ -953,9 +1046,8 @@ fn register_builtin_tools(
     use crate::agents::builtin_tools::{
         calculator::CalculatorTool,
         clipboard::{ClipboardProvider, ClipboardReadTool, ClipboardWriteTool, SystemClipboard},
-        fs::{FsReadTool, FsWriteTool},
+        delegate::DelegateToAgentTool,
         search::SearchTool,
-        shell::ShellExecTool,
         web_fetch::WebFetchTool,
     };
     use std::sync::Arc;
@@ -977,21 +1069,15 @@ fn register_builtin_tools(
     registry
         .register_builtin(Arc::new(ClipboardWriteTool::new(clipboard_provider)))
         .map_err(|e| Box::<dyn std::error::Error>::from(e.to_string()))?;
-    registry
-        .register_builtin(Arc::new(FsReadTool::new()))
-        .map_err(|e| Box::<dyn std::error::Error>::from(e.to_string()))?;
-    registry
-        .register_builtin(Arc::new(FsWriteTool::new()))
-        .map_err(|e| Box::<dyn std::error::Error>::from(e.to_string()))?;
-    registry
-        .register_builtin(Arc::new(ShellExecTool::new()))
-        .map_err(|e| Box::<dyn std::error::Error>::from(e.to_string()))?;
     registry
         .register_builtin(Arc::new(WebFetchTool::new()))
         .map_err(|e| Box::<dyn std::error::Error>::from(e.to_string()))?;
     registry
         .register_builtin(Arc::new(SearchTool::new(search_state)))
         .map_err(|e| Box::<dyn std::error::Error>::from(e.to_string()))?;
+    registry
+        .register_builtin(Arc::new(DelegateToAgentTool::new()))
+        .map_err(|e| Box::<dyn std::error::Error>::from(e.to_string()))?;
     Ok(())
 }
 
@@ -1048,6 +1134,141 @@ fn register_notes_tools(
     Ok(())
 }
 
+/// Registers the memory AI tools. They need the `DataStore` and master key,
+/// so like `register_notes_tools` they're registered once the keystore is
+/// unlocked rather than from `register_builtin_tools`.
+fn register_memory_tools(app_handle: &tauri::AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::agents::builtin_tools::memory::{
+        MemoryForgetTool, MemorySaveTool, MemorySearchTool,
+    };
+    use std::sync::Arc;
+    use tauri::Manager;
+
+    let registry = app_handle
+        .try_state::<crate::agents::tools::ToolRegistryState>()
+        .ok_or("ToolRegistry not managed")?;
+    let data_store = app_handle
+        .try_state::<storage::DataStore>()
+        .ok_or("DataStore not managed")?
+        .inner()
+        .clone();
+    let master_key: [u8; 32] = *app_handle
+        .try_state::<crate::crypto::keystore::KeystoreState>()
+        .ok_or("KeystoreState not managed")?
+        .master_key();
+
+    registry
+        .register_builtin(Arc::new(MemorySaveTool::new(
+            data_store.clone(),
+            master_key,
+        )))
+        .map_err(|e| Box::<dyn std::error::Error>::from(e.to_string()))?;
+    registry
+        .register_builtin(Arc::new(MemorySearchTool::new(
+            data_store.clone(),
+            master_key,
+        )))
+        .map_err(|e| Box::<dyn std::error::Error>::from(e.to_string()))?;
+    registry
+        .register_builtin(Arc::new(MemoryForgetTool::new(data_store, master_key)))
+        .map_err(|e| Box::<dyn std::error::Error>::from(e.to_string()))?;
+    Ok(())
+}
+
+/// Registers the shell and file-system AI tools behind the per-agent
+/// `ToolSandbox`, which needs the `DataStore` for policies and the audit
+/// log. Manages the sandbox so `agents_tool_write_resolve` can answer
+/// confirm-mode writes, which reach the frontend as
+/// `AGENT_WRITE_CONFIRM_EVENT`.
+fn register_sandboxed_tools(
+    app_handle: &tauri::AppHandle,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::agents::builtin_tools::{
+        fs::{FsReadTool, FsWriteTool},
+        shell::ShellExecTool,
+    };
+    use crate::agents::tool_policy::{ToolSandbox, AGENT_WRITE_CONFIRM_EVENT};
+    use std::sync::Arc;
+    use tauri::{Emitter, Manager};
+
+    let registry = app_handle
+        .try_state::<crate::agents::tools::ToolRegistryState>()
+        .ok_or("ToolRegistry not managed")?;
+    let data_store = app_handle
+        .try_state::<storage::DataStore>()
+        .ok_or("DataStore not managed")?
+        .inner()
+        .clone();
+    let (home, extra_deny) = commands::files::extension_scope_env(app_handle)?;
+
+    let sandbox = Arc::new(ToolSandbox::new(data_store, home, extra_deny));
+    let emit_handle = app_handle.clone();
+    sandbox.set_confirm_emitter(Box::new(move |request| {
+        if let Err(e) = emit_handle.emit(AGENT_WRITE_CONFIRM_EVENT, request) {
+            log::warn!("[agents] failed to emit write confirmation: {e}");
+        }
+    }));
+
+    registry
+        .register_builtin(Arc::new(FsReadTool::new(Arc::clone(&sandbox))))
+        .map_err(|e| Box::<dyn std::error::Error>::from(e.to_string()))?;
+    registry
+        .register_builtin(Arc::new(FsWriteTool::new(Arc::clone(&sandbox))))
+        .map_err(|e| Box::<dyn std::error::Error>::from(e.to_string()))?;
+    registry
+        .register_builtin(Arc::new(ShellExecTool::new(Arc::clone(&sandbox))))
+        .map_err(|e| Box::<dyn std::error::Error>::from(e.to_string()))?;
+    app_handle.manage(sandbox);
+    Ok(())
+}
+
+/// Registers the `semantic-search` AI tool. Like `register_notes_tools`, it
+/// has to wait until both in-memory FTS indexes exist, since hybrid search
+/// ranks keyword hits from them alongside the vector matches.
+fn register_semantic_search_tool(
+    app_handle: &tauri::AppHandle,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::agents::builtin_tools::semantic_search::SemanticSearchTool;
+    use crate::ai::semantic_index::{SemanticIndexState, SemanticSearchContext};
+    use std::sync::Arc;
+    use tauri::Manager;
+
+    let registry = app_handle
+        .try_state::<crate::agents::tools::ToolRegistryState>()
+        .ok_or("ToolRegistry not managed")?;
+    let ctx = SemanticSearchContext {
+        data_store: app_handle
+            .try_state::<storage::DataStore>()
+            .ok_or("DataStore not managed")?
+            .inner()
+            .clone(),
+        master_key: *app_handle
+            .try_state::<crate::crypto::keystore::KeystoreState>()
+            .ok_or("KeystoreState not managed")?
+            .master_key(),
+        state: app_handle
+            .try_state::<Arc<SemanticIndexState>>()
+            .ok_or("SemanticIndexState not managed")?
+            .inner()
+            .clone(),
+        notes_fts: app_handle
+            .try_state::<Arc<crate::storage::notes_fts::NotesFts>>()
+            .ok_or("NotesFts not managed")?
+            .inner()
+            .clone(),
+        clipboard_fts: app_handle
+            .try_state::<Arc<crate::storage::clipboard_fts::ClipboardFts>>()
+            .ok_or("ClipboardFts not managed")?
+            .inner()
+            .clone(),
+    };
+
+    registry
+        .register_builtin(Arc::new(SemanticSearchTool::new(ctx)))
+        .map_err(|e| Box::<dyn std::error::Error>::from(e.to_string()))?;
+    Ok(())
+}
+
 fn setup_app(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     // Honor any pending factory-reset request from the previous session
     // FIRST, before literally anything else touches `app_data_dir` —
@@ -1579,7 +1800,20 @@ fn setup_app(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     ));
     app.manage(std::sync::Arc::new(thumbnail::ThumbnailState::default()));
 
+    // Resource history sampler; loads saved alerts now and starts sampling
+    // from the scheduler block below if any are enabled.
+    app.manage(process_manager::sampler::ProcessSampler::new(
+        data_store.clone(),
+    ));
     app.manage(data_store);
+    // Full run output logs live next to the database; runs started before
+    // this point simply aren't logged.
+    app.manage(runs::RunLogStore::new(
+        app.path()
+            .app_data_dir()
+            .map(|p| p.join("run_logs"))
+            .unwrap_or_else(|_| std::env::temp_dir().join("asyar_run_logs")),
+    ));
 
     // Clipboard FTS: build the in-memory index and spawn a background task
     // that decrypts every row, feeds the FTS, and backfills content_hash for
@@ -1663,6 +1897,33 @@ fn setup_app(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
         // would mean these three don't exist yet.
         register_notes_tools(app.handle(), fts)?;
     }
+    register_memory_tools(app.handle())?;
+    register_sandboxed_tools(app.handle())?;
+
+    // Semantic index: decrypt the stored vectors for the default model into
+    // memory, off the setup path like the FTS rebuilds above. The frontend
+    // pushes the user's embedding settings shortly after and reloads if the
+    // model differs. The tool is registered now; searches made before the
+    // load finishes simply rank by keywords.
+    {
+        let state = app
+            .state::<std::sync::Arc<crate::ai::semantic_index::SemanticIndexState>>()
+            .inner()
+            .clone();
+        let store = app.state::<storage::DataStore>().inner().clone();
+        let master_key: [u8; 32] = *app
+            .state::<crate::crypto::keystore::KeystoreState>()
+            .master_key();
+        tauri::async_runtime::spawn_blocking(move || {
+            let loaded = store
+                .conn()
+                .and_then(|conn| state.load_from_disk(&conn, &master_key));
+            if let Err(e) = loaded {
+                log::warn!("[semantic-index] loading stored vectors failed: {e}");
+            }
+        });
+        register_semantic_search_tool(app.handle())?;
+    }
 
     // Re-open every pinned note's sticky window. Runs after the DataStore is
     // managed (it reads `sticky_notes`) and is fail-soft: a sticky that can't
@@ -1678,6 +1939,50 @@ fn setup_app(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     if let Some(resolver) = app.try_state::<std::sync::Arc<AppRuntimeResolver>>() {
         resolver.set_app_handle(app.handle().clone());
     }
+    if let Some(authorizer) = app.try_state::<std::sync::Arc<mcp::oauth::McpAuthorizer>>() {
+        let opener_handle = app.handle().clone();
+        authorizer.set_opener(Box::new(move |url| {
+            use tauri_plugin_opener::OpenerExt;
+            opener_handle
+                .opener()
+                .open_url(url, None::<&str>)
+                .map_err(|e| e.to_string())
+        }));
+    }
+    // Sampling and elicitation prompts go to the frontend, which answers
+    // through `mcp_resolve_sampling` / `mcp_resolve_elicitation`.
+    if let Some(requests) =
+        app.try_state::<std::sync::Arc<mcp::server_requests::McpServerRequests>>()
+    {
+        let data_store = app.state::<storage::DataStore>().inner().clone();
+        let emit_handle = app.handle().clone();
+        requests.attach(
+            data_store,
+            Box::new(move |event, payload| {
+                if let Err(e) = tauri::Emitter::emit(&emit_handle, event, payload) {
+                    log::warn!("[mcp] failed to emit {event}: {e}");
+                }
+            }),
+        );
+    }
+
+    // MCP: servers that change their tool list while connected get their
+    // registry entries replaced in place. Subscribed before the seed so a
+    // change announced right after connecting isn't missed.
+    {
+        let supervisor = app
+            .state::<std::sync::Arc<crate::mcp::McpSupervisor>>()
+            .inner()
+            .clone();
+        let registry = app
+            .state::<agents::tools::ToolRegistryState>()
+            .inner()
+            .clone();
+        let changes = supervisor.subscribe_tools_changed();
+        tauri::async_runtime::spawn(async move {
+            crate::mcp::lifecycle::follow_tool_list_changes(&supervisor, &registry, changes).await;
+        });
+    }
 
     // MCP: seed enabled servers at startup. Runs after both register_builtin_tools
     // and app.manage(data_store) so both managed states are available.
@@ -1943,11 +2248,14 @@ fn setup_app(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     // Setup global shortcut with default configuration
     setup_global_shortcut(handle);
 
-    // Central background scheduler: one registry of fixed-interval jobs,
+    // Central background scheduler: one registry of background jobs,
     // replacing the per-feature copy-pasted spawn-loop daemons. Adding a
     // periodic job is one register() call with a cadence + work closure.
+    // The store goes in first: each job reads its persisted run state when
+    // it is registered.
     {
         let sched = app.state::<crate::scheduler::Scheduler>();
+        sched.attach_store(app.state::<storage::DataStore>().inner().clone());
         let handle = app.handle();
         sched.register(crate::app_updater::scheduler::job(handle.clone()));
         sched.register(crate::extensions::update_scheduler::job(handle.clone()));
@@ -1961,6 +2269,16 @@ fn setup_app(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
                 .inner()
                 .clone(),
         ));
+        sched.register(crate::agents::triggers::job(
+            handle.clone(),
+            app.state::<storage::DataStore>().inner().clone(),
+        ));
+        sched.register(crate::runs::logs::retention_job(
+            app.state::<runs::RunLogStore>().inner().clone(),
+            app.state::<storage::DataStore>().inner().clone(),
+        ));
+        app.state::<crate::process_manager::sampler::ProcessSampler>()
+            .sync_schedule(handle);
     }
 
     // Wire the system-events hub emitter to Tauri's AppHandle and start the
@@ -2187,6 +2505,10 @@ fn setup_app(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
         let reg: tauri::State<'_, std::sync::Arc<fs_watcher::FsWatcherRegistry>> = app.state();
         let reg_arc: std::sync::Arc<fs_watcher::FsWatcherRegistry> = reg.inner().clone();
         reg_arc.set_emitter(Box::new(move |ext_id, handle_id, event| {
+            if ext_id == agents::triggers::TRIGGER_WATCH_OWNER {
+                agents::triggers::on_directory_change(&app_handle_for_fs_watch, &handle_id);
+                return;
+            }
             let paths: Vec<String> = event
                 .paths
                 .into_iter()
@@ -2206,6 +2528,25 @@ fn setup_app(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
                 log::warn!("[fs_watcher] failed to emit Tauri event: {e}");
             }
         }));
+
+        // Agent file triggers watch through the same registry under their
+        // own owner id (routed above). Managed before the first sync so a
+        // batch arriving mid-sync can already find the state.
+        let home = dirs::home_dir().unwrap_or_default();
+        let watchers = std::sync::Arc::new(agents::triggers::TriggerWatchers::new(reg_arc, home));
+        app.manage(watchers.clone());
+        let store = app.state::<storage::DataStore>();
+        match store
+            .conn()
+            .and_then(|conn| storage::agent_triggers::list_enabled(&conn))
+        {
+            Ok(triggers) => {
+                if let Err(e) = watchers.sync(&triggers) {
+                    log::warn!("[agent-triggers] initial watch sync failed: {e}");
+                }
+            }
+            Err(e) => log::warn!("[agent-triggers] failed to load triggers: {e}"),
+        }
     }
 
     // Apply any pending update from previous session.
@@ -2250,6 +2591,43 @@ fn setup_app(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
         );
     }
 
+    // Asyar's own MCP server: opt-in, so it only starts when the user has
+    // turned it on. The emitter routes consent prompts and Tier-2 tool calls
+    // to the frontend, which answers through the `mcp_host_*` commands.
+    {
+        use crate::mcp::host::McpHost;
+        use std::sync::Arc;
+        use tauri::Emitter;
+
+        let registry = app
+            .state::<agents::tools::ToolRegistryState>()
+            .inner()
+            .clone();
+        let data_store = app.state::<storage::DataStore>().inner().clone();
+        let host = Arc::new(McpHost::new(registry, data_store.clone()));
+        let emit_handle = app.handle().clone();
+        host.set_emitter(Box::new(move |event, payload| {
+            if let Err(e) = emit_handle.emit(event, payload) {
+                log::warn!("[mcp-host] failed to emit {event}: {e}");
+            }
+        }));
+
+        let enabled = data_store
+            .conn()
+            .and_then(|conn| storage::mcp_settings::get_host_enabled(&conn))
+            .unwrap_or(false);
+        if enabled {
+            let host_for_start = Arc::clone(&host);
+            tauri::async_runtime::spawn(async move {
+                match mcp::host::start_with_keyring(&host_for_start).await {
+                    Ok(port) => log::info!("MCP server listening on 127.0.0.1:{port}"),
+                    Err(e) => log::error!("failed to start the MCP server: {e}"),
+                }
+            });
+        }
+        app.manage(host);
+    }
+
     // Browser bridge: local axum WS server companions connect to.
     // Tokens persisted in the OS keychain via `KeyringTokenStore`.
     {
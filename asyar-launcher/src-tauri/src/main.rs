@@ -2,6 +2,9 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    if let Some(code) = asyar_lib::run_subcommand() {
+        std::process::exit(code);
+    }
     asyar_lib::apply_linux_webkit_dmabuf_workaround();
     asyar_lib::run()
 }
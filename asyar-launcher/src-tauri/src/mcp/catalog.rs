@@ -0,0 +1,374 @@
+//! MCP resources and prompts as launcher commands.
+//!
+//! Every resource and prompt a connected server publishes becomes a dynamic
+//! command of the built-in `mcp` extension: a resource is attached to the
+//! default agent's chat, a prompt is rendered and sent to it. Prompt
+//! arguments become [`CommandArgument`]s, so the search bar collects them
+//! inline and `extensions::argument_model` resolves them like any other
+//! command's arguments.
+
+use crate::ai::types::{ContentPart, ImageSource};
+use crate::error::AppError;
+use crate::extensions::dynamic_commands::validate_arguments;
+use crate::extensions::{CommandArgument, CommandArgumentType};
+use crate::mcp::supervisor::ServerCatalog;
+use crate::mcp::types::{McpPrompt, McpPromptResult, McpResource, McpResourceContents};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum McpCatalogKind {
+    Resource,
+    Prompt,
+}
+
+/// One launcher command derived from a server's resource or prompt.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct McpCatalogEntry {
+    /// Stable across restarts: `res-`/`prompt-` plus a hash of the server
+    /// id and the resource URI or prompt name.
+    pub dynamic_id: String,
+    pub kind: McpCatalogKind,
+    pub server_id: String,
+    /// The resource URI or the prompt name — what the server is asked for.
+    pub target: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub mime_type: Option<String>,
+    pub arguments: Vec<CommandArgument>,
+}
+
+/// Flattens the connected servers' catalogs into launcher commands. Prompts
+/// whose arguments can't be expressed as command arguments (too many, or
+/// names the search bar can't address) are left out with a warning.
+pub fn build_entries(catalog: &[ServerCatalog]) -> Vec<McpCatalogEntry> {
+    let mut entries = Vec::new();
+    for server in catalog {
+        entries.extend(
+            server
+                .resources
+                .iter()
+                .map(|resource| resource_entry(&server.server_id, resource)),
+        );
+        for prompt in &server.prompts {
+            let arguments = prompt_arguments(prompt);
+            if let Err(e) = validate_arguments(&arguments) {
+                log::warn!(
+                    "MCP server '{}': prompt '{}' not offered as a command: {e}",
+                    server.server_id,
+                    prompt.name
+                );
+                continue;
+            }
+            entries.push(McpCatalogEntry {
+                dynamic_id: dynamic_id("prompt", &server.server_id, &prompt.name),
+                kind: McpCatalogKind::Prompt,
+                server_id: server.server_id.clone(),
+                target: prompt.name.clone(),
+                title: prompt.title.clone().unwrap_or_else(|| prompt.name.clone()),
+                description: prompt.description.clone(),
+                mime_type: None,
+                arguments,
+            });
+        }
+    }
+    entries
+}
+
+fn resource_entry(server_id: &str, resource: &McpResource) -> McpCatalogEntry {
+    McpCatalogEntry {
+        dynamic_id: dynamic_id("res", server_id, &resource.uri),
+        kind: McpCatalogKind::Resource,
+        server_id: server_id.to_string(),
+        target: resource.uri.clone(),
+        title: resource
+            .title
+            .clone()
+            .unwrap_or_else(|| resource.name.clone()),
+        description: resource
+            .description
+            .clone()
+            .or_else(|| Some(resource.uri.clone())),
+        mime_type: resource.mime_type.clone(),
+        arguments: vec![],
+    }
+}
+
+/// Prompt arguments are free-form strings; required ones go first because a
+/// command can't declare a required argument after an optional one.
+fn prompt_arguments(prompt: &McpPrompt) -> Vec<CommandArgument> {
+    let mut args = prompt.arguments.clone();
+    args.sort_by_key(|arg| !arg.required);
+    args.into_iter()
+        .map(|arg| CommandArgument {
+            placeholder: Some(arg.description.unwrap_or_else(|| arg.name.clone())),
+            name: arg.name,
+            argument_type: CommandArgumentType::Text,
+            required: Some(arg.required),
+            default: None,
+            data: None,
+            seed: None,
+        })
+        .collect()
+}
+
+fn dynamic_id(prefix: &str, server_id: &str, target: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(server_id.as_bytes());
+    hasher.update([0]);
+    hasher.update(target.as_bytes());
+    let hex: String = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("{prefix}-{}", &hex[..16])
+}
+
+/// Turns a `resources/read` result into chat attachments: text becomes a
+/// file part, base64 images and PDFs are passed through. Other binary
+/// content can't be read by a model and is refused.
+pub fn resource_parts(contents: Vec<McpResourceContents>) -> Result<Vec<ContentPart>, AppError> {
+    let parts = contents
+        .into_iter()
+        .map(|item| {
+            let name = resource_name(&item.uri);
+            match (item.text, item.blob) {
+                (Some(text), _) => Ok(ContentPart::File {
+                    name,
+                    media_type: item.mime_type.unwrap_or_else(|| "text/plain".to_string()),
+                    text: Some(text),
+                    data: None,
+                }),
+                (None, Some(data)) => match item.mime_type.as_deref() {
+                    Some(media_type) if media_type.starts_with("image/") => {
+                        Ok(ContentPart::Image {
+                            media_type: media_type.to_string(),
+                            source: ImageSource::Bytes { data },
+                        })
+                    }
+                    Some(media_type @ "application/pdf") => Ok(ContentPart::File {
+                        name,
+                        media_type: media_type.to_string(),
+                        text: None,
+                        data: Some(data),
+                    }),
+                    other => Err(AppError::Validation(format!(
+                        "{name}: binary resources of type '{}' can't be attached",
+                        other.unwrap_or("unknown")
+                    ))),
+                },
+                (None, None) => Err(AppError::Validation(format!(
+                    "{name}: the server returned no content"
+                ))),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    crate::ai::attachments::validate_parts(&parts)?;
+    Ok(parts)
+}
+
+fn resource_name(uri: &str) -> String {
+    uri.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .filter(|segment| !segment.is_empty())
+        .unwrap_or(uri)
+        .to_string()
+}
+
+/// Joins the text of a rendered prompt into one chat message. Embedded
+/// text resources are inlined; images and audio are skipped.
+pub fn prompt_text(result: &McpPromptResult) -> Result<String, AppError> {
+    let blocks: Vec<&str> = result
+        .messages
+        .iter()
+        .filter_map(|message| match message.content["type"].as_str() {
+            Some("text") => message.content["text"].as_str(),
+            Some("resource") => message.content["resource"]["text"].as_str(),
+            _ => None,
+        })
+        .collect();
+    if blocks.is_empty() {
+        return Err(AppError::Validation(
+            "the prompt rendered no text".to_string(),
+        ));
+    }
+    Ok(blocks.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::types::{McpPromptArgument, McpPromptMessage};
+
+    fn prompt(name: &str, args: &[(&str, bool)]) -> McpPrompt {
+        McpPrompt {
+            name: name.to_string(),
+            title: None,
+            description: None,
+            arguments: args
+                .iter()
+                .map(|(name, required)| McpPromptArgument {
+                    name: name.to_string(),
+                    description: None,
+                    required: *required,
+                })
+                .collect(),
+        }
+    }
+
+    fn server(prompts: Vec<McpPrompt>) -> ServerCatalog {
+        ServerCatalog {
+            server_id: "github".to_string(),
+            resources: vec![McpResource {
+                uri: "file:///notes/today.md".to_string(),
+                name: "today.md".to_string(),
+                title: None,
+                description: None,
+                mime_type: Some("text/markdown".to_string()),
+            }],
+            prompts,
+        }
+    }
+
+    // 1. build_entries_maps_resources_and_prompts_with_stable_ids
+    #[test]
+    fn build_entries_maps_resources_and_prompts_with_stable_ids() {
+        let catalog = [server(vec![prompt("review", &[])])];
+        let entries = build_entries(&catalog);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].kind, McpCatalogKind::Resource);
+        assert_eq!(entries[0].title, "today.md");
+        assert_eq!(
+            entries[0].description.as_deref(),
+            Some("file:///notes/today.md")
+        );
+        assert!(entries[0].dynamic_id.starts_with("res-"));
+        assert_eq!(entries[1].kind, McpCatalogKind::Prompt);
+        assert!(entries[1].dynamic_id.starts_with("prompt-"));
+        assert_eq!(build_entries(&catalog), entries, "ids are deterministic");
+        for entry in &entries {
+            crate::extensions::dynamic_commands::validate_dynamic_id(&entry.dynamic_id)
+                .expect("valid dynamic id");
+        }
+    }
+
+    // 2. prompt_arguments_put_required_first
+    #[test]
+    fn prompt_arguments_put_required_first() {
+        let entries = build_entries(&[server(vec![prompt(
+            "review",
+            &[("style", false), ("code", true)],
+        )])]);
+        let names: Vec<_> = entries[1]
+            .arguments
+            .iter()
+            .map(|a| a.name.as_str())
+            .collect();
+        assert_eq!(names, ["code", "style"]);
+        assert_eq!(entries[1].arguments[0].required, Some(true));
+        assert_eq!(
+            entries[1].arguments[0].argument_type,
+            CommandArgumentType::Text
+        );
+    }
+
+    // 3. prompts_that_cannot_be_commands_are_skipped
+    #[test]
+    fn prompts_that_cannot_be_commands_are_skipped() {
+        let entries = build_entries(&[server(vec![
+            prompt("bad-name", &[("file-path", true)]),
+            prompt(
+                "too-many",
+                &[("a", true), ("b", true), ("c", true), ("d", true)],
+            ),
+        ])]);
+        assert!(entries.iter().all(|e| e.kind == McpCatalogKind::Resource));
+    }
+
+    // 4. resource_parts_maps_text_images_and_pdfs
+    #[test]
+    fn resource_parts_maps_text_images_and_pdfs() {
+        let parts = resource_parts(vec![
+            McpResourceContents {
+                uri: "file:///notes/today.md".to_string(),
+                mime_type: Some("text/markdown".to_string()),
+                text: Some("hello".to_string()),
+                blob: None,
+            },
+            McpResourceContents {
+                uri: "file:///shot.png".to_string(),
+                mime_type: Some("image/png".to_string()),
+                text: None,
+                blob: Some("aGk=".to_string()),
+            },
+            McpResourceContents {
+                uri: "file:///spec.pdf".to_string(),
+                mime_type: Some("application/pdf".to_string()),
+                text: None,
+                blob: Some("aGk=".to_string()),
+            },
+        ])
+        .expect("parts");
+
+        assert!(matches!(
+            &parts[0],
+            ContentPart::File { name, text: Some(text), .. } if name == "today.md" && text == "hello"
+        ));
+        assert!(
+            matches!(&parts[1], ContentPart::Image { media_type, .. } if media_type == "image/png")
+        );
+        assert!(matches!(&parts[2], ContentPart::File { data: Some(_), .. }));
+    }
+
+    // 5. resource_parts_rejects_unreadable_binary
+    #[test]
+    fn resource_parts_rejects_unreadable_binary() {
+        let err = resource_parts(vec![McpResourceContents {
+            uri: "file:///archive.zip".to_string(),
+            mime_type: Some("application/zip".to_string()),
+            text: None,
+            blob: Some("aGk=".to_string()),
+        }])
+        .unwrap_err();
+        assert!(matches!(err, AppError::Validation(msg) if msg.contains("archive.zip")));
+    }
+
+    // 6. prompt_text_joins_text_and_embedded_resources
+    #[test]
+    fn prompt_text_joins_text_and_embedded_resources() {
+        let result = McpPromptResult {
+            description: None,
+            messages: vec![
+                McpPromptMessage {
+                    role: "user".to_string(),
+                    content: serde_json::json!({ "type": "text", "text": "Review this:" }),
+                },
+                McpPromptMessage {
+                    role: "user".to_string(),
+                    content: serde_json::json!({ "type": "image", "data": "aGk=" }),
+                },
+                McpPromptMessage {
+                    role: "user".to_string(),
+                    content: serde_json::json!({
+                        "type": "resource",
+                        "resource": { "uri": "file:///a.rs", "text": "fn main() {}" }
+                    }),
+                },
+            ],
+        };
+        assert_eq!(
+            prompt_text(&result).unwrap(),
+            "Review this:\n\nfn main() {}"
+        );
+        assert!(prompt_text(&McpPromptResult {
+            description: None,
+            messages: vec![],
+        })
+        .is_err());
+    }
+}
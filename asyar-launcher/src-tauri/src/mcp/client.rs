@@ -1,11 +1,72 @@
 use crate::mcp::transport::Transport;
-use crate::mcp::types::{McpCallResult, McpClientError, McpToolDescriptor};
+use crate::mcp::types::{
+    McpCallHooks, McpCallResult, McpClientError, McpProgress, McpPrompt, McpPromptResult,
+    McpResource, McpResourceContents, McpResourceTemplate, McpToolDescriptor,
+};
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Upper bound on `nextCursor` pages followed by one list call, so a server
+/// that keeps handing out cursors can't stall the handshake forever.
+const MAX_LIST_PAGES: usize = 50;
+
+/// Moves `key` out of a JSON-RPC result, leaving `null` when the result is
+/// not an object or lacks the key (which then fails to deserialize).
+fn take_field(result: &mut serde_json::Value, key: &str) -> serde_json::Value {
+    result
+        .get_mut(key)
+        .map(serde_json::Value::take)
+        .unwrap_or(serde_json::Value::Null)
+}
+
+/// Resolves once `cancel` reads `true`; never, if its sender is gone.
+async fn cancelled(cancel: &mut watch::Receiver<bool>) {
+    if cancel.wait_for(|cancelled| *cancelled).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Runs `fut` to completion unless `cancel` fires first (then `None`).
+async fn unless_cancelled<F: Future>(
+    cancel: Option<&mut watch::Receiver<bool>>,
+    fut: F,
+) -> Option<F::Output> {
+    match cancel {
+        Some(cancel) => tokio::select! {
+            output = fut => Some(output),
+            _ = cancelled(cancel) => None,
+        },
+        None => Some(fut.await),
+    }
+}
+
+/// JSON-RPC "method not found", sent for server requests nobody handles.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC "internal error", sent when a handler fails without an RPC code.
+const INTERNAL_ERROR: i64 = -32603;
+
+/// Answers requests the server sends to the client (`sampling/createMessage`,
+/// `elicitation/create`). Returning `McpClientError::Rpc` sends that code and
+/// message back; any other error is reported as an internal error.
+#[async_trait::async_trait]
+pub trait ServerRequestHandler: Send + Sync {
+    async fn handle(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, McpClientError>;
+}
 
 pub struct McpClient {
     transport: Box<dyn Transport>,
     next_id: u64,
     server_info: Option<serde_json::Value>,
     capabilities: Option<serde_json::Value>,
+    tools_changed: bool,
+    request_handler: Option<Arc<dyn ServerRequestHandler>>,
 }
 
 impl McpClient {
@@ -15,9 +76,18 @@ impl McpClient {
             next_id: 0,
             server_info: None,
             capabilities: None,
+            tools_changed: false,
+            request_handler: None,
         }
     }
 
+    /// Installs the handler for server-initiated requests. Must be set
+    /// before `initialize` so the matching client capabilities are
+    /// advertised; without one, such requests get "method not found".
+    pub fn set_request_handler(&mut self, handler: Arc<dyn ServerRequestHandler>) {
+        self.request_handler = Some(handler);
+    }
+
     fn alloc_id(&mut self) -> u64 {
         self.next_id += 1;
         self.next_id
@@ -29,6 +99,16 @@ impl McpClient {
         params: serde_json::Value,
     ) -> Result<u64, McpClientError> {
         let id = self.alloc_id();
+        self.send_request_with_id(id, method, params).await?;
+        Ok(id)
+    }
+
+    async fn send_request_with_id(
+        &mut self,
+        id: u64,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<(), McpClientError> {
         let msg = serde_json::json!({
             "jsonrpc": "2.0",
             "id": id,
@@ -37,7 +117,7 @@ impl McpClient {
         });
         let line = serde_json::to_string(&msg)?;
         self.transport.send(&line).await?;
-        Ok(id)
+        Ok(())
     }
 
     async fn send_notification(
@@ -58,16 +138,38 @@ impl McpClient {
     async fn recv_response(
         &mut self,
         expected_id: u64,
+    ) -> Result<serde_json::Value, McpClientError> {
+        self.recv_response_with(expected_id, &mut McpCallHooks::default())
+            .await
+    }
+
+    async fn recv_response_with(
+        &mut self,
+        expected_id: u64,
+        hooks: &mut McpCallHooks<'_>,
     ) -> Result<serde_json::Value, McpClientError> {
         loop {
-            match self.transport.recv().await? {
+            let received =
+                match unless_cancelled(hooks.cancel.as_mut(), self.transport.recv()).await {
+                    Some(received) => received?,
+                    None => return Err(self.cancel_request(expected_id).await),
+                };
+            match received {
                 None => return Err(McpClientError::EarlyExit),
                 Some(line) => {
                     let v: serde_json::Value = serde_json::from_str(&line)
                         .map_err(|_| McpClientError::Protocol(format!("malformed JSON: {line}")))?;
-                    // Skip messages without an id (notifications from server)
+                    // Messages with a method are the server's own requests
+                    // (when they carry an id) or notifications (when not).
+                    if v.get("method").is_some() && v.get("id").is_some() {
+                        self.answer_server_request(&v).await?;
+                        continue;
+                    }
                     match v.get("id") {
-                        None => continue,
+                        None => {
+                            self.handle_notification(&v, Some(expected_id), hooks);
+                            continue;
+                        }
                         Some(id_val) => {
                             let id = id_val.as_u64().ok_or_else(|| {
                                 McpClientError::Protocol("non-integer id".to_string())
@@ -90,13 +192,127 @@ impl McpClient {
         }
     }
 
+    /// Records `tools/list_changed` and routes `progress` for the request
+    /// in flight (its id doubles as the progress token) to `hooks`.
+    fn handle_notification(
+        &mut self,
+        msg: &serde_json::Value,
+        in_flight: Option<u64>,
+        hooks: &McpCallHooks<'_>,
+    ) {
+        match msg.get("method").and_then(|m| m.as_str()) {
+            Some("notifications/tools/list_changed") => self.tools_changed = true,
+            Some("notifications/progress") => {
+                let params = &msg["params"];
+                if in_flight.is_none() || params["progressToken"].as_u64() != in_flight {
+                    return;
+                }
+                if let Some(on_progress) = hooks.on_progress {
+                    match serde_json::from_value::<McpProgress>(params.clone()) {
+                        Ok(progress) => on_progress(progress),
+                        Err(e) => log::debug!("MCP: ignoring malformed progress: {e}"),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Runs a server-initiated request through the installed handler and
+    /// sends the reply. Only failures to send the reply are returned.
+    async fn answer_server_request(
+        &mut self,
+        msg: &serde_json::Value,
+    ) -> Result<(), McpClientError> {
+        let id = msg["id"].clone();
+        let method = msg["method"].as_str().unwrap_or_default();
+        let outcome = match (method, self.request_handler.clone()) {
+            ("ping", _) => Ok(serde_json::json!({})),
+            (_, Some(handler)) => handler.handle(method, msg["params"].clone()).await,
+            (_, None) => Err(McpClientError::Rpc {
+                code: METHOD_NOT_FOUND,
+                message: format!("method not supported: {method}"),
+            }),
+        };
+        let reply = match outcome {
+            Ok(result) => serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": result,
+            }),
+            Err(e) => {
+                let (code, message) = match e {
+                    McpClientError::Rpc { code, message } => (code, message),
+                    other => (INTERNAL_ERROR, other.to_string()),
+                };
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": code, "message": message },
+                })
+            }
+        };
+        let line = serde_json::to_string(&reply)?;
+        self.transport.send(&line).await?;
+        Ok(())
+    }
+
+    /// Tells the server to stop working on request `id`. A response that
+    /// still arrives is skipped by the next `recv_response` (ids differ).
+    async fn cancel_request(&mut self, id: u64) -> McpClientError {
+        let params = serde_json::json!({
+            "requestId": id,
+            "reason": "cancelled by the user",
+        });
+        if let Err(e) = self
+            .send_notification("notifications/cancelled", params)
+            .await
+        {
+            log::warn!("MCP: failed to send notifications/cancelled for id={id}: {e}");
+        }
+        McpClientError::Cancelled
+    }
+
+    /// Handles notifications that arrived while no request was in flight,
+    /// without waiting for more. Stdio servers can announce a changed tool
+    /// list at any time; nothing else reads their output between requests.
+    pub async fn poll_notifications(&mut self) -> Result<(), McpClientError> {
+        use futures_util::FutureExt;
+        while let Some(received) = self.transport.recv().now_or_never() {
+            let Some(line) = received? else {
+                break;
+            };
+            match serde_json::from_str::<serde_json::Value>(&line) {
+                Ok(msg) if msg.get("id").is_none() => {
+                    self.handle_notification(&msg, None, &McpCallHooks::default())
+                }
+                Ok(msg) if msg.get("method").is_some() => self.answer_server_request(&msg).await?,
+                // A late response to a cancelled request.
+                Ok(_) => {}
+                Err(_) => log::warn!("MCP: ignoring malformed message: {line}"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the server sent `notifications/tools/list_changed` since the
+    /// last check. Clears the flag.
+    pub fn take_tools_changed(&mut self) -> bool {
+        std::mem::take(&mut self.tools_changed)
+    }
+
     pub async fn initialize(&mut self) -> Result<(), McpClientError> {
+        let capabilities = if self.request_handler.is_some() {
+            serde_json::json!({ "sampling": {}, "elicitation": {} })
+        } else {
+            serde_json::json!({})
+        };
         let id = self
             .send_request(
                 "initialize",
                 serde_json::json!({
                     "protocolVersion": "2025-06-18",
-                    "capabilities": {},
+                    "capabilities": capabilities,
                     "clientInfo": {
                         "name": "Asyar",
                         "version": env!("CARGO_PKG_VERSION"),
@@ -153,16 +369,40 @@ impl McpClient {
         name: &str,
         arguments: serde_json::Value,
     ) -> Result<McpCallResult, McpClientError> {
-        let id = self
-            .send_request(
-                "tools/call",
-                serde_json::json!({
-                    "name": name,
-                    "arguments": arguments,
-                }),
-            )
-            .await?;
-        let result = self.recv_response(id).await?;
+        self.call_tool_with(name, arguments, McpCallHooks::default())
+            .await
+    }
+
+    /// `tools/call` that asks for progress updates when `hooks.on_progress`
+    /// is set, and gives up with `notifications/cancelled` once
+    /// `hooks.cancel` flips to `true`.
+    pub async fn call_tool_with(
+        &mut self,
+        name: &str,
+        arguments: serde_json::Value,
+        mut hooks: McpCallHooks<'_>,
+    ) -> Result<McpCallResult, McpClientError> {
+        if hooks.cancel.as_ref().is_some_and(|cancel| *cancel.borrow()) {
+            return Err(McpClientError::Cancelled);
+        }
+        let id = self.alloc_id();
+        let mut params = serde_json::json!({
+            "name": name,
+            "arguments": arguments,
+        });
+        if hooks.on_progress.is_some() {
+            params["_meta"] = serde_json::json!({ "progressToken": id });
+        }
+        let sent = unless_cancelled(
+            hooks.cancel.as_mut(),
+            self.send_request_with_id(id, "tools/call", params),
+        )
+        .await;
+        match sent {
+            Some(sent) => sent?,
+            None => return Err(self.cancel_request(id).await),
+        }
+        let result = self.recv_response_with(id, &mut hooks).await?;
 
         #[derive(serde::Deserialize)]
         struct WireCallResult {
@@ -187,6 +427,94 @@ impl McpClient {
         })
     }
 
+    /// Whether the server advertised `capability` (`"resources"`,
+    /// `"prompts"`, …) in its `initialize` result. Servers without it answer
+    /// the matching methods with "method not found", so callers check first.
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities
+            .as_ref()
+            .and_then(|caps| caps.get(capability))
+            .is_some_and(|value| !value.is_null())
+    }
+
+    /// Runs a paginated list method, following `nextCursor` until the server
+    /// stops returning one, and collects the `key` array of every page.
+    async fn list_paginated<T: DeserializeOwned>(
+        &mut self,
+        method: &str,
+        key: &str,
+    ) -> Result<Vec<T>, McpClientError> {
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_LIST_PAGES {
+            let params = match &cursor {
+                Some(cursor) => serde_json::json!({ "cursor": cursor }),
+                None => serde_json::json!({}),
+            };
+            let id = self.send_request(method, params).await?;
+            let mut result = self.recv_response(id).await?;
+            let page: Vec<T> = serde_json::from_value(take_field(&mut result, key))
+                .map_err(|e| McpClientError::Protocol(format!("{method} result: {e}")))?;
+            items.extend(page);
+            cursor = result
+                .get("nextCursor")
+                .and_then(|c| c.as_str())
+                .map(str::to_string);
+            if cursor.is_none() {
+                return Ok(items);
+            }
+        }
+        log::warn!("MCP {method}: stopped after {MAX_LIST_PAGES} pages");
+        Ok(items)
+    }
+
+    pub async fn list_resources(&mut self) -> Result<Vec<McpResource>, McpClientError> {
+        self.list_paginated("resources/list", "resources").await
+    }
+
+    pub async fn list_resource_templates(
+        &mut self,
+    ) -> Result<Vec<McpResourceTemplate>, McpClientError> {
+        self.list_paginated("resources/templates/list", "resourceTemplates")
+            .await
+    }
+
+    pub async fn read_resource(
+        &mut self,
+        uri: &str,
+    ) -> Result<Vec<McpResourceContents>, McpClientError> {
+        let id = self
+            .send_request("resources/read", serde_json::json!({ "uri": uri }))
+            .await?;
+        let mut result = self.recv_response(id).await?;
+        serde_json::from_value(take_field(&mut result, "contents"))
+            .map_err(|e| McpClientError::Protocol(format!("resources/read result: {e}")))
+    }
+
+    pub async fn list_prompts(&mut self) -> Result<Vec<McpPrompt>, McpClientError> {
+        self.list_paginated("prompts/list", "prompts").await
+    }
+
+    /// Renders prompt `name`. MCP prompt arguments are always strings.
+    pub async fn get_prompt(
+        &mut self,
+        name: &str,
+        arguments: &BTreeMap<String, String>,
+    ) -> Result<McpPromptResult, McpClientError> {
+        let id = self
+            .send_request(
+                "prompts/get",
+                serde_json::json!({
+                    "name": name,
+                    "arguments": arguments,
+                }),
+            )
+            .await?;
+        let result = self.recv_response(id).await?;
+        serde_json::from_value(result)
+            .map_err(|e| McpClientError::Protocol(format!("prompts/get result: {e}")))
+    }
+
     pub async fn shutdown(mut self) -> Result<(), McpClientError> {
         self.transport.close().await
     }
@@ -497,6 +825,415 @@ mod tests {
         }
     }
 
+    // Completes the handshake on the server side, advertising `capabilities`.
+    async fn accept_initialize(server: &mut crate::mcp::transport::ServerSide, capabilities: &str) {
+        let _init = server.recv_line().await.unwrap();
+        server
+            .send_line(&format!(
+                r#"{{"jsonrpc":"2.0","id":1,"result":{{"protocolVersion":"2025-06-18","capabilities":{capabilities},"serverInfo":{{"name":"x","version":"0"}}}}}}"#
+            ))
+            .await;
+        let _ = server.recv_line().await; // notifications/initialized
+    }
+
+    // 8b. supports_reflects_advertised_capabilities
+    #[tokio::test]
+    async fn supports_reflects_advertised_capabilities() {
+        let (transport, mut server) = duplex_pair();
+        let mut client = McpClient::new(transport);
+
+        let server_task = tokio::spawn(async move {
+            accept_initialize(
+                &mut server,
+                r#"{"tools":{},"prompts":{"listChanged":true}}"#,
+            )
+            .await;
+            server
+        });
+
+        client.initialize().await.unwrap();
+        let _ = server_task.await.unwrap();
+        assert!(client.supports("tools"));
+        assert!(client.supports("prompts"));
+        assert!(!client.supports("resources"));
+    }
+
+    // 8c. list_resources_follows_next_cursor_across_pages
+    #[tokio::test]
+    async fn list_resources_follows_next_cursor_across_pages() {
+        let (transport, mut server) = duplex_pair();
+        let mut client = McpClient::new(transport);
+
+        let server_task = tokio::spawn(async move {
+            accept_initialize(&mut server, r#"{"resources":{}}"#).await;
+            let first = server.recv_line().await.unwrap();
+            assert!(first.contains("\"resources/list\""), "{first}");
+            assert!(
+                !first.contains("cursor"),
+                "first page has no cursor: {first}"
+            );
+            server
+                .send_line(
+                    r#"{"jsonrpc":"2.0","id":2,"result":{"resources":[{"uri":"file:///a.md","name":"a.md","mimeType":"text/markdown"}],"nextCursor":"p2"}}"#,
+                )
+                .await;
+            let second = server.recv_line().await.unwrap();
+            assert!(second.contains("\"cursor\":\"p2\""), "{second}");
+            server
+                .send_line(
+                    r#"{"jsonrpc":"2.0","id":3,"result":{"resources":[{"uri":"file:///b.md","name":"b.md"}]}}"#,
+                )
+                .await;
+            server
+        });
+
+        client.initialize().await.unwrap();
+        let resources = client.list_resources().await.unwrap();
+        let _ = server_task.await.unwrap();
+        let uris: Vec<_> = resources.iter().map(|r| r.uri.as_str()).collect();
+        assert_eq!(uris, ["file:///a.md", "file:///b.md"]);
+        assert_eq!(resources[0].mime_type.as_deref(), Some("text/markdown"));
+    }
+
+    // 8d. list_resource_templates_parses_uri_templates
+    #[tokio::test]
+    async fn list_resource_templates_parses_uri_templates() {
+        let (transport, mut server) = duplex_pair();
+        let mut client = McpClient::new(transport);
+
+        let server_task = tokio::spawn(async move {
+            accept_initialize(&mut server, r#"{"resources":{}}"#).await;
+            let req = server.recv_line().await.unwrap();
+            assert!(req.contains("\"resources/templates/list\""), "{req}");
+            server
+                .send_line(
+                    r#"{"jsonrpc":"2.0","id":2,"result":{"resourceTemplates":[{"uriTemplate":"issue://{id}","name":"Issue"}]}}"#,
+                )
+                .await;
+            server
+        });
+
+        client.initialize().await.unwrap();
+        let templates = client.list_resource_templates().await.unwrap();
+        let _ = server_task.await.unwrap();
+        assert_eq!(templates.len(), 1);
+        assert_eq!(templates[0].uri_template, "issue://{id}");
+    }
+
+    // 8e. read_resource_sends_uri_and_parses_text_and_blob_contents
+    #[tokio::test]
+    async fn read_resource_sends_uri_and_parses_text_and_blob_contents() {
+        let (transport, mut server) = duplex_pair();
+        let mut client = McpClient::new(transport);
+
+        let server_task = tokio::spawn(async move {
+            accept_initialize(&mut server, r#"{"resources":{}}"#).await;
+            let req = server.recv_line().await.unwrap();
+            assert!(req.contains("\"resources/read\""), "{req}");
+            assert!(req.contains("\"uri\":\"file:///a.md\""), "{req}");
+            server
+                .send_line(
+                    r#"{"jsonrpc":"2.0","id":2,"result":{"contents":[{"uri":"file:///a.md","mimeType":"text/markdown","text":"Notes A"},{"uri":"file:///a.png","mimeType":"image/png","blob":"iVBO"}]}}"#,
+                )
+                .await;
+            server
+        });
+
+        client.initialize().await.unwrap();
+        let contents = client.read_resource("file:///a.md").await.unwrap();
+        let _ = server_task.await.unwrap();
+        assert_eq!(contents.len(), 2);
+        assert_eq!(contents[0].text.as_deref(), Some("Notes A"));
+        assert_eq!(contents[1].blob.as_deref(), Some("iVBO"));
+    }
+
+    // 8f. list_prompts_and_get_prompt_round_trip_arguments
+    #[tokio::test]
+    async fn list_prompts_and_get_prompt_round_trip_arguments() {
+        let (transport, mut server) = duplex_pair();
+        let mut client = McpClient::new(transport);
+
+        let server_task = tokio::spawn(async move {
+            accept_initialize(&mut server, r#"{"prompts":{}}"#).await;
+            let _list = server.recv_line().await.unwrap();
+            server
+                .send_line(
+                    r#"{"jsonrpc":"2.0","id":2,"result":{"prompts":[{"name":"review","description":"Review code","arguments":[{"name":"code","required":true}]}]}}"#,
+                )
+                .await;
+            let get = server.recv_line().await.unwrap();
+            let v: serde_json::Value = serde_json::from_str(&get).unwrap();
+            assert_eq!(v["method"], "prompts/get");
+            assert_eq!(v["params"]["name"], "review");
+            assert_eq!(v["params"]["arguments"]["code"], "fn main() {}");
+            server
+                .send_line(
+                    r#"{"jsonrpc":"2.0","id":3,"result":{"messages":[{"role":"user","content":{"type":"text","text":"Review: fn main() {}"}}]}}"#,
+                )
+                .await;
+            server
+        });
+
+        client.initialize().await.unwrap();
+        let prompts = client.list_prompts().await.unwrap();
+        let mut args = BTreeMap::new();
+        args.insert("code".to_string(), "fn main() {}".to_string());
+        let rendered = client.get_prompt("review", &args).await.unwrap();
+        let _ = server_task.await.unwrap();
+        assert_eq!(prompts[0].name, "review");
+        assert!(prompts[0].arguments[0].required);
+        assert_eq!(rendered.messages.len(), 1);
+        assert_eq!(rendered.messages[0].content["text"], "Review: fn main() {}");
+    }
+
+    // 8g. call_tool_with_requests_progress_and_forwards_matching_updates
+    #[tokio::test]
+    async fn call_tool_with_requests_progress_and_forwards_matching_updates() {
+        let (transport, mut server) = duplex_pair();
+        let mut client = McpClient::new(transport);
+
+        let server_task = tokio::spawn(async move {
+            accept_initialize(&mut server, r#"{"tools":{}}"#).await;
+            let call = server.recv_line().await.unwrap();
+            let v: serde_json::Value = serde_json::from_str(&call).unwrap();
+            assert_eq!(v["params"]["_meta"]["progressToken"], 2, "{call}");
+            server
+                .send_line(r#"{"jsonrpc":"2.0","method":"notifications/progress","params":{"progressToken":2,"progress":3,"total":10,"message":"Uploading"}}"#)
+                .await;
+            // Another request's token — not ours to report.
+            server
+                .send_line(r#"{"jsonrpc":"2.0","method":"notifications/progress","params":{"progressToken":99,"progress":1}}"#)
+                .await;
+            server
+                .send_line(r#"{"jsonrpc":"2.0","id":2,"result":{"content":[]}}"#)
+                .await;
+            server
+        });
+
+        client.initialize().await.unwrap();
+        let updates = std::sync::Mutex::new(Vec::new());
+        let on_progress = |progress: McpProgress| updates.lock().unwrap().push(progress);
+        let hooks = McpCallHooks {
+            on_progress: Some(&on_progress),
+            cancel: None,
+        };
+        client
+            .call_tool_with("upload", serde_json::json!({}), hooks)
+            .await
+            .unwrap();
+        let _ = server_task.await.unwrap();
+        let updates = updates.into_inner().unwrap();
+        assert_eq!(
+            updates,
+            [McpProgress {
+                progress: 3.0,
+                total: Some(10.0),
+                message: Some("Uploading".to_string()),
+            }]
+        );
+    }
+
+    // 8h. call_tool_with_sends_cancelled_notification_when_cancel_fires
+    #[tokio::test]
+    async fn call_tool_with_sends_cancelled_notification_when_cancel_fires() {
+        let (transport, mut server) = duplex_pair();
+        let mut client = McpClient::new(transport);
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+
+        let server_task = tokio::spawn(async move {
+            accept_initialize(&mut server, r#"{"tools":{}}"#).await;
+            let _call = server.recv_line().await.unwrap();
+            cancel_tx.send(true).unwrap();
+            let notif = server.recv_line().await.unwrap();
+            let v: serde_json::Value = serde_json::from_str(&notif).unwrap();
+            assert_eq!(v["method"], "notifications/cancelled", "{notif}");
+            assert_eq!(v["params"]["requestId"], 2, "{notif}");
+            assert!(v.get("id").is_none(), "{notif}");
+            // The late response is skipped by the next request.
+            server
+                .send_line(r#"{"jsonrpc":"2.0","id":2,"result":{"content":[]}}"#)
+                .await;
+            let _list = server.recv_line().await.unwrap();
+            server
+                .send_line(r#"{"jsonrpc":"2.0","id":3,"result":{"tools":[]}}"#)
+                .await;
+            server
+        });
+
+        client.initialize().await.unwrap();
+        let hooks = McpCallHooks {
+            on_progress: None,
+            cancel: Some(cancel_rx),
+        };
+        let result = client
+            .call_tool_with("slow", serde_json::json!({}), hooks)
+            .await;
+        assert!(
+            matches!(result, Err(McpClientError::Cancelled)),
+            "{result:?}"
+        );
+        assert!(client.list_tools().await.unwrap().is_empty());
+        let _ = server_task.await.unwrap();
+    }
+
+    // 8i. call_tool_with_does_not_send_when_already_cancelled
+    #[tokio::test]
+    async fn call_tool_with_does_not_send_when_already_cancelled() {
+        let (transport, mut server) = duplex_pair();
+        let mut client = McpClient::new(transport);
+        let (_cancel_tx, cancel_rx) = watch::channel(true);
+
+        let hooks = McpCallHooks {
+            on_progress: None,
+            cancel: Some(cancel_rx),
+        };
+        let result = client
+            .call_tool_with("slow", serde_json::json!({}), hooks)
+            .await;
+        drop(client);
+        assert!(matches!(result, Err(McpClientError::Cancelled)));
+        assert!(server.recv_line().await.is_none(), "nothing may be sent");
+    }
+
+    // 8j. tools_list_changed_is_recorded_mid_request_and_while_idle
+    #[tokio::test]
+    async fn tools_list_changed_is_recorded_mid_request_and_while_idle() {
+        let (transport, mut server) = duplex_pair();
+        let mut client = McpClient::new(transport);
+
+        let server_task = tokio::spawn(async move {
+            accept_initialize(&mut server, r#"{"tools":{"listChanged":true}}"#).await;
+            let _call = server.recv_line().await.unwrap();
+            server
+                .send_line(r#"{"jsonrpc":"2.0","method":"notifications/tools/list_changed"}"#)
+                .await;
+            server
+                .send_line(r#"{"jsonrpc":"2.0","id":2,"result":{"content":[]}}"#)
+                .await;
+            server
+        });
+
+        client.initialize().await.unwrap();
+        assert!(!client.take_tools_changed());
+        client
+            .call_tool("echo", serde_json::json!({}))
+            .await
+            .unwrap();
+        let mut server = server_task.await.unwrap();
+        assert!(client.take_tools_changed());
+        assert!(!client.take_tools_changed(), "taking clears the flag");
+
+        server
+            .send_line(r#"{"jsonrpc":"2.0","method":"notifications/tools/list_changed"}"#)
+            .await;
+        client.poll_notifications().await.unwrap();
+        assert!(client.take_tools_changed());
+    }
+
+    struct EchoHandler;
+
+    #[async_trait::async_trait]
+    impl ServerRequestHandler for EchoHandler {
+        async fn handle(
+            &self,
+            method: &str,
+            params: serde_json::Value,
+        ) -> Result<serde_json::Value, McpClientError> {
+            match method {
+                "sampling/createMessage" => Ok(serde_json::json!({ "echo": params })),
+                _ => Err(McpClientError::Rpc {
+                    code: -1,
+                    message: "declined".to_string(),
+                }),
+            }
+        }
+    }
+
+    // 8k. server_requests_mid_call_are_answered_by_the_handler
+    #[tokio::test]
+    async fn server_requests_mid_call_are_answered_by_the_handler() {
+        let (transport, mut server) = duplex_pair();
+        let mut client = McpClient::new(transport);
+        client.set_request_handler(Arc::new(EchoHandler));
+
+        let server_task = tokio::spawn(async move {
+            let init = server.recv_line().await.unwrap();
+            let v: serde_json::Value = serde_json::from_str(&init).unwrap();
+            assert_eq!(
+                v["params"]["capabilities"],
+                serde_json::json!({ "sampling": {}, "elicitation": {} })
+            );
+            server
+                .send_line(r#"{"jsonrpc":"2.0","id":1,"result":{"capabilities":{}}}"#)
+                .await;
+            let _ = server.recv_line().await; // notifications/initialized
+            let _call = server.recv_line().await.unwrap();
+            server
+                .send_line(r#"{"jsonrpc":"2.0","id":"s1","method":"sampling/createMessage","params":{"maxTokens":5}}"#)
+                .await;
+            let reply: serde_json::Value =
+                serde_json::from_str(&server.recv_line().await.unwrap()).unwrap();
+            assert_eq!(reply["id"], "s1");
+            assert_eq!(reply["result"]["echo"]["maxTokens"], 5);
+            server
+                .send_line(
+                    r#"{"jsonrpc":"2.0","id":"s2","method":"elicitation/create","params":{}}"#,
+                )
+                .await;
+            let reply: serde_json::Value =
+                serde_json::from_str(&server.recv_line().await.unwrap()).unwrap();
+            assert_eq!(reply["error"]["code"], -1);
+            assert_eq!(reply["error"]["message"], "declined");
+            server
+                .send_line(r#"{"jsonrpc":"2.0","id":2,"result":{"content":[]}}"#)
+                .await;
+        });
+
+        client.initialize().await.unwrap();
+        client
+            .call_tool("echo", serde_json::json!({}))
+            .await
+            .unwrap();
+        server_task.await.unwrap();
+    }
+
+    // 8l. server_requests_without_handler_get_method_not_found_but_ping_works
+    #[tokio::test]
+    async fn server_requests_without_handler_get_method_not_found_but_ping_works() {
+        let (transport, mut server) = duplex_pair();
+        let mut client = McpClient::new(transport);
+
+        let server_task = tokio::spawn(async move {
+            let init = server.recv_line().await.unwrap();
+            assert!(init.contains(r#""capabilities":{}"#), "{init}");
+            server
+                .send_line(r#"{"jsonrpc":"2.0","id":1,"result":{"capabilities":{}}}"#)
+                .await;
+            let _ = server.recv_line().await; // notifications/initialized
+            server
+        });
+
+        client.initialize().await.unwrap();
+        let mut server = server_task.await.unwrap();
+
+        server
+            .send_line(r#"{"jsonrpc":"2.0","id":7,"method":"sampling/createMessage","params":{}}"#)
+            .await;
+        server
+            .send_line(r#"{"jsonrpc":"2.0","id":8,"method":"ping"}"#)
+            .await;
+        client.poll_notifications().await.unwrap();
+
+        let reply: serde_json::Value =
+            serde_json::from_str(&server.recv_line().await.unwrap()).unwrap();
+        assert_eq!(reply["id"], 7);
+        assert_eq!(reply["error"]["code"], METHOD_NOT_FOUND);
+        let reply: serde_json::Value =
+            serde_json::from_str(&server.recv_line().await.unwrap()).unwrap();
+        assert_eq!(reply["id"], 8);
+        assert_eq!(reply["result"], serde_json::json!({}));
+    }
+
     // ── HTTP path (using mockito) ─────────────────────────────────────────────
 
     // 9. http_initialize_posts_to_url_and_parses_body
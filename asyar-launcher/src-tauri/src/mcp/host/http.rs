@@ -0,0 +1,353 @@
+//! Streamable HTTP transport for [`McpHost`]: `POST /mcp` on 127.0.0.1,
+//! answered with plain JSON. The host never pushes anything, so the optional
+//! SSE stream (`GET /mcp`) is refused with 405.
+//!
+//! Every `/mcp` request needs the keychain token as a Bearer header, and a
+//! browser `Origin` other than loopback is rejected outright, so a web page
+//! can't reach the endpoint through DNS rebinding.
+
+use crate::mcp::host::{rpc_error, McpHost, INVALID_REQUEST, PARSE_ERROR};
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// The stdio bridge finds the launcher by probing this range, so the host
+/// binds within it — clear of the browser bridge's 54300-54320.
+pub const HOST_PORT_RANGE: std::ops::RangeInclusive<u16> = 54330..=54340;
+
+pub const SESSION_HEADER: &str = "mcp-session-id";
+
+async fn bind_in_range(ports: impl IntoIterator<Item = u16>) -> Result<TcpListener, String> {
+    for port in ports {
+        if let Ok(listener) = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], port))).await {
+            return Ok(listener);
+        }
+    }
+    Err("no free port available in the MCP server range (54330-54340)".to_string())
+}
+
+pub struct ServerHandle {
+    port: u16,
+    shutdown_tx: tokio::sync::oneshot::Sender<()>,
+    join: JoinHandle<()>,
+}
+
+impl ServerHandle {
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub async fn shutdown(self) {
+        let _ = self.shutdown_tx.send(());
+        let _ = self.join.await;
+    }
+}
+
+/// What `asyar mcp` looks for when probing the port range.
+async fn discover_handler() -> Json<Value> {
+    Json(json!({
+        "name": "asyar",
+        "service": "mcp",
+        "version": env!("CARGO_PKG_VERSION"),
+    }))
+}
+
+fn check_access(host: &McpHost, headers: &HeaderMap) -> Result<(), Response> {
+    if let Some(origin) = headers.get(header::ORIGIN) {
+        let origin = origin.to_str().unwrap_or_default();
+        let loopback = ["http://127.0.0.1", "http://localhost", "http://[::1]"]
+            .iter()
+            .any(|allowed| {
+                origin == *allowed
+                    || origin
+                        .strip_prefix(allowed)
+                        .is_some_and(|rest| rest.starts_with(':'))
+            });
+        if !loopback {
+            return Err((StatusCode::FORBIDDEN, "origin not allowed").into_response());
+        }
+    }
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|s| s.strip_prefix("Bearer "));
+    if !token.is_some_and(|token| host.is_authorized(token)) {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            "missing or invalid token",
+        )
+            .into_response());
+    }
+    Ok(())
+}
+
+async fn post_handler(
+    State(host): State<Arc<McpHost>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if let Err(denied) = check_access(&host, &headers) {
+        return denied;
+    }
+    let message: Value = match serde_json::from_slice(&body) {
+        Ok(message) => message,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(rpc_error(
+                    Value::Null,
+                    PARSE_ERROR,
+                    &format!("invalid JSON: {e}"),
+                )),
+            )
+                .into_response()
+        }
+    };
+    if !message.is_object() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(rpc_error(
+                Value::Null,
+                INVALID_REQUEST,
+                "batched messages are not supported",
+            )),
+        )
+            .into_response();
+    }
+
+    if message["method"] == "initialize" {
+        let (reply, session) = host.initialize(&message);
+        return (StatusCode::OK, [(SESSION_HEADER, session)], Json(reply)).into_response();
+    }
+
+    let Some(session) = headers.get(SESSION_HEADER).and_then(|h| h.to_str().ok()) else {
+        return (StatusCode::BAD_REQUEST, "missing Mcp-Session-Id header").into_response();
+    };
+    // 404 tells the client to start over with `initialize`.
+    let Some(client) = host.session_client(session) else {
+        return (StatusCode::NOT_FOUND, "unknown session").into_response();
+    };
+    match host.handle(&client, message).await {
+        Some(reply) => (StatusCode::OK, Json(reply)).into_response(),
+        None => StatusCode::ACCEPTED.into_response(),
+    }
+}
+
+async fn delete_handler(State(host): State<Arc<McpHost>>, headers: HeaderMap) -> Response {
+    if let Err(denied) = check_access(&host, &headers) {
+        return denied;
+    }
+    let closed = headers
+        .get(SESSION_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .is_some_and(|session| host.close_session(session));
+    if closed {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        StatusCode::NOT_FOUND.into_response()
+    }
+}
+
+pub async fn start_server(
+    host: Arc<McpHost>,
+    ports: impl IntoIterator<Item = u16>,
+) -> Result<ServerHandle, String> {
+    let app = Router::new()
+        .route("/discover", get(discover_handler))
+        .route(
+            "/mcp",
+            get(|| async { StatusCode::METHOD_NOT_ALLOWED })
+                .post(post_handler)
+                .delete(delete_handler),
+        )
+        .with_state(host);
+
+    let listener = bind_in_range(ports).await?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+    let join = tokio::spawn(async move {
+        let _ = axum::serve(listener, app)
+            .with_graceful_shutdown(async {
+                let _ = shutdown_rx.await;
+            })
+            .await;
+    });
+    Ok(ServerHandle {
+        port,
+        shutdown_tx,
+        join,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::host::tests::test_host;
+
+    async fn start(token: &str) -> (Arc<McpHost>, ServerHandle, String) {
+        let host = test_host();
+        host.set_token(Some(token.to_string()));
+        let handle = start_server(Arc::clone(&host), [0]).await.unwrap();
+        let url = format!("http://127.0.0.1:{}/mcp", handle.port());
+        (host, handle, url)
+    }
+
+    fn initialize() -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": { "clientInfo": { "name": "Zed" } },
+        })
+    }
+
+    // 1. requests_need_the_token_and_a_loopback_origin
+    #[tokio::test]
+    async fn requests_need_the_token_and_a_loopback_origin() {
+        let (_host, handle, url) = start("secret").await;
+        let client = reqwest::Client::new();
+
+        let missing = client.post(&url).json(&initialize()).send().await.unwrap();
+        assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+        let wrong = client
+            .post(&url)
+            .bearer_auth("nope")
+            .json(&initialize())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(wrong.status(), StatusCode::UNAUTHORIZED);
+        let foreign = client
+            .post(&url)
+            .bearer_auth("secret")
+            .header("origin", "http://localhost.evil.example")
+            .json(&initialize())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(foreign.status(), StatusCode::FORBIDDEN);
+        let local = client
+            .post(&url)
+            .bearer_auth("secret")
+            .header("origin", "http://localhost:3000")
+            .json(&initialize())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(local.status(), StatusCode::OK);
+        handle.shutdown().await;
+    }
+
+    // 2. sessions_gate_everything_after_initialize
+    #[tokio::test]
+    async fn sessions_gate_everything_after_initialize() {
+        let (host, handle, url) = start("secret").await;
+        let client = reqwest::Client::new();
+        let list = json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" });
+
+        let response = client
+            .post(&url)
+            .bearer_auth("secret")
+            .json(&initialize())
+            .send()
+            .await
+            .unwrap();
+        let session = response.headers()[SESSION_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(host.session_client(&session).as_deref(), Some("Zed"));
+
+        let no_session = client
+            .post(&url)
+            .bearer_auth("secret")
+            .json(&list)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(no_session.status(), StatusCode::BAD_REQUEST);
+
+        let listed: Value = client
+            .post(&url)
+            .bearer_auth("secret")
+            .header(SESSION_HEADER, &session)
+            .json(&list)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(listed["id"], 2);
+        assert!(listed["result"]["tools"].is_array());
+
+        let notified = client
+            .post(&url)
+            .bearer_auth("secret")
+            .header(SESSION_HEADER, &session)
+            .json(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(notified.status(), StatusCode::ACCEPTED);
+
+        let closed = client
+            .delete(&url)
+            .bearer_auth("secret")
+            .header(SESSION_HEADER, &session)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(closed.status(), StatusCode::NO_CONTENT);
+        let stale = client
+            .post(&url)
+            .bearer_auth("secret")
+            .header(SESSION_HEADER, &session)
+            .json(&list)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(stale.status(), StatusCode::NOT_FOUND);
+        handle.shutdown().await;
+    }
+
+    // 3. malformed_and_batched_bodies_are_rejected
+    #[tokio::test]
+    async fn malformed_and_batched_bodies_are_rejected() {
+        let (_host, handle, url) = start("secret").await;
+        let client = reqwest::Client::new();
+
+        let garbage: Value = client
+            .post(&url)
+            .bearer_auth("secret")
+            .body("{not json")
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(garbage["error"]["code"], PARSE_ERROR);
+
+        let batch = client
+            .post(&url)
+            .bearer_auth("secret")
+            .json(&json!([initialize()]))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(batch.status(), StatusCode::BAD_REQUEST);
+        handle.shutdown().await;
+    }
+}
@@ -0,0 +1,874 @@
+//! Asyar as an MCP server.
+//!
+//! The mirror image of [`crate::mcp::client`]: external MCP hosts — editors,
+//! CLI agents — connect to the launcher and call a curated set of its tools.
+//! The launcher serves Streamable HTTP on loopback ([`http`]); the `asyar mcp`
+//! sub-command ([`stdio`]) bridges stdio clients to that endpoint, so every
+//! call runs in the launcher process, next to the tools, the extension
+//! workers and the consent dialog.
+//!
+//! Exposed are the [`EXPOSED_BUILTINS`] and every Tier-2 extension tool in the
+//! [`ToolRegistry`](crate::agents::tools::ToolRegistry). Each call goes
+//! through the MCP permission registry under the pseudo-server
+//! [`HOST_SERVER_ID`], with the client standing in for the agent
+//! (`mcp-client:<name>`), and lands in the MCP audit log the same way.
+//! Unlike calls out to MCP servers there is no read-only shortcut: a client
+//! is asked about every tool once, whatever the strict-mode setting.
+
+pub mod http;
+pub mod stdio;
+pub mod token;
+
+use crate::agents::runner::McpPermissionChoice;
+use crate::agents::tools::{
+    invoke_builtin_tool_as, ToolCaller, ToolDescriptor, ToolRegistryState, ToolSource,
+};
+use crate::error::AppError;
+use crate::storage::mcp_audit::{self, NewMcpAuditEntry};
+use crate::storage::mcp_permissions::{self, McpPermissionRow, PermissionDecision};
+use crate::storage::DataStore;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// The server id host calls are recorded under in `mcp_permissions` and
+/// `mcp_audit`.
+pub const HOST_SERVER_ID: &str = "asyar";
+
+/// Prefix of the agent id a client's permissions and audit rows carry.
+pub const CLIENT_AGENT_PREFIX: &str = "mcp-client:";
+
+/// Built-in tools external clients may call. Everything touching the file
+/// system, the shell, the network or agent memory stays launcher-only.
+pub const EXPOSED_BUILTINS: &[&str] = &[
+    "search",
+    "notes-search",
+    "notes-list",
+    "notes-get",
+    "notes-create",
+    "notes-append",
+    "clipboard-read",
+    "clipboard-write",
+    "calculator",
+];
+
+/// Emitted when a client calls a tool it has no standing decision for;
+/// answered through `mcp_host_resolve_permission`.
+pub const HOST_PERMISSION_EVENT: &str = "asyar:mcp-host:permission-request";
+
+/// Emitted to run a Tier-2 tool in its extension's worker; answered through
+/// `mcp_host_report_tool_result`.
+pub const HOST_TOOL_DISPATCH_EVENT: &str = "asyar:mcp-host:tool-dispatch";
+
+/// Newest first; a client asking for anything else gets the newest.
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// How long a call waits for the user before counting as cancelled.
+const PERMISSION_TIMEOUT: Duration = Duration::from_secs(120);
+
+const TIER2_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Clients reject longer tool names, or names outside `[A-Za-z0-9_-]`.
+const MAX_TOOL_NAME_LEN: usize = 64;
+
+const MAX_CLIENT_NAME_LEN: usize = 64;
+
+pub(crate) const PARSE_ERROR: i64 = -32700;
+pub(crate) const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+pub(crate) const SERVER_ERROR: i64 = -32000;
+
+/// Payload of [`HOST_PERMISSION_EVENT`]. `server_id`, `tool_id` and
+/// `agent_id` are the permission key, so the answer can be stored as is.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostPermissionRequest {
+    pub id: String,
+    pub server_id: String,
+    pub tool_id: String,
+    pub agent_id: String,
+    pub client_name: String,
+}
+
+/// Payload of [`HOST_TOOL_DISPATCH_EVENT`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostToolDispatch {
+    pub id: String,
+    pub extension_id: String,
+    pub tool_id: String,
+    pub arguments: Value,
+}
+
+/// What the settings UI shows: whether the server is on and how to reach it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpHostStatus {
+    pub enabled: bool,
+    /// `None` while stopped, or when the port range was taken.
+    pub url: Option<String>,
+    pub token: Option<String>,
+    /// The command stdio clients launch.
+    pub stdio_command: Vec<String>,
+}
+
+/// A registry tool as clients see it: `name` is the MCP tool name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExposedTool {
+    pub name: String,
+    pub descriptor: ToolDescriptor,
+}
+
+pub type HostEmitFn = Box<dyn Fn(&'static str, Value) + Send + Sync>;
+
+pub struct McpHost {
+    registry: ToolRegistryState,
+    data_store: DataStore,
+    token: RwLock<Option<String>>,
+    /// Session id → client name, from `initialize`.
+    sessions: Mutex<HashMap<String, String>>,
+    pending_permissions: Mutex<HashMap<String, oneshot::Sender<McpPermissionChoice>>>,
+    pending_calls: Mutex<HashMap<String, oneshot::Sender<Result<Value, String>>>>,
+    emit: Mutex<Option<HostEmitFn>>,
+    server: tokio::sync::Mutex<Option<http::ServerHandle>>,
+}
+
+impl McpHost {
+    pub fn new(registry: ToolRegistryState, data_store: DataStore) -> Self {
+        Self {
+            registry,
+            data_store,
+            token: RwLock::new(None),
+            sessions: Mutex::new(HashMap::new()),
+            pending_permissions: Mutex::new(HashMap::new()),
+            pending_calls: Mutex::new(HashMap::new()),
+            emit: Mutex::new(None),
+            server: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Without an emitter every permission request is cancelled and Tier-2
+    /// tools fail.
+    pub fn set_emitter(&self, emit: HostEmitFn) {
+        if let Ok(mut g) = self.emit.lock() {
+            *g = Some(emit);
+        }
+    }
+
+    pub fn set_token(&self, token: Option<String>) {
+        if let Ok(mut g) = self.token.write() {
+            *g = token;
+        }
+    }
+
+    pub fn token(&self) -> Option<String> {
+        self.token.read().ok().and_then(|g| g.clone())
+    }
+
+    /// No token means nobody gets in.
+    pub fn is_authorized(&self, presented: &str) -> bool {
+        self.token().is_some_and(|token| token == presented)
+    }
+
+    /// Starts the HTTP endpoint unless it's already up; returns its port.
+    pub async fn start(self: &Arc<Self>) -> Result<u16, AppError> {
+        let mut server = self.server.lock().await;
+        if let Some(handle) = server.as_ref() {
+            return Ok(handle.port());
+        }
+        let handle = http::start_server(Arc::clone(self), http::HOST_PORT_RANGE)
+            .await
+            .map_err(AppError::Other)?;
+        let port = handle.port();
+        *server = Some(handle);
+        Ok(port)
+    }
+
+    /// Stops the endpoint and forgets every session.
+    pub async fn stop(&self) {
+        if let Some(handle) = self.server.lock().await.take() {
+            handle.shutdown().await;
+        }
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.clear();
+        }
+    }
+
+    pub async fn port(&self) -> Option<u16> {
+        self.server.lock().await.as_ref().map(|h| h.port())
+    }
+
+    /// Answers `initialize` and opens a session for the client it names.
+    pub fn initialize(&self, message: &Value) -> (Value, String) {
+        let params = &message["params"];
+        let requested = params["protocolVersion"].as_str().unwrap_or_default();
+        let version = PROTOCOL_VERSIONS
+            .iter()
+            .find(|v| **v == requested)
+            .unwrap_or(&PROTOCOL_VERSIONS[0]);
+        let client_name = client_name(params["clientInfo"]["name"].as_str());
+
+        let session = uuid::Uuid::new_v4().to_string();
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.insert(session.clone(), client_name.clone());
+        }
+        log::info!("[mcp-host] '{client_name}' connected (protocol {version})");
+
+        let result = json!({
+            "protocolVersion": version,
+            "capabilities": { "tools": { "listChanged": false } },
+            "serverInfo": {
+                "name": "asyar",
+                "title": "Asyar",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "instructions": "Tools from the Asyar launcher: app and file search, notes, \
+                the clipboard, the calculator, and tools from installed extensions. \
+                The user approves each tool the first time it is called.",
+        });
+        (rpc_result(message["id"].clone(), result), session)
+    }
+
+    pub fn session_client(&self, session: &str) -> Option<String> {
+        self.sessions.lock().ok()?.get(session).cloned()
+    }
+
+    pub fn close_session(&self, session: &str) -> bool {
+        self.sessions
+            .lock()
+            .is_ok_and(|mut sessions| sessions.remove(session).is_some())
+    }
+
+    /// Handles one JSON-RPC message from an initialized `client`. Returns
+    /// `None` for notifications and responses, which get no reply.
+    pub async fn handle(&self, client: &str, message: Value) -> Option<Value> {
+        let id = message.get("id").cloned()?;
+        let method = message.get("method").and_then(Value::as_str)?;
+        let reply = match method {
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({
+                "tools": self.exposed_tools().iter().map(tool_entry).collect::<Vec<_>>(),
+            })),
+            "tools/call" => self.call_tool(client, &message["params"]).await,
+            other => Err((METHOD_NOT_FOUND, format!("method not found: {other}"))),
+        };
+        Some(match reply {
+            Ok(result) => rpc_result(id, result),
+            Err((code, message)) => rpc_error(id, code, &message),
+        })
+    }
+
+    /// The allow-listed built-ins and every Tier-2 tool. MCP tools are never
+    /// re-exported: their servers can be added to the client directly.
+    pub fn exposed_tools(&self) -> Vec<ExposedTool> {
+        self.registry
+            .list_all()
+            .into_iter()
+            .filter(|d| match &d.source {
+                ToolSource::Builtin => EXPOSED_BUILTINS.contains(&d.id.as_str()),
+                ToolSource::Tier2(_) => true,
+                ToolSource::Mcp(_) => false,
+            })
+            .map(|descriptor| ExposedTool {
+                name: tool_name(&descriptor),
+                descriptor,
+            })
+            .collect()
+    }
+
+    async fn call_tool(&self, client: &str, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params["name"]
+            .as_str()
+            .ok_or((INVALID_PARAMS, "missing tool name".to_string()))?;
+        let tool = self
+            .exposed_tools()
+            .into_iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| (INVALID_PARAMS, format!("unknown tool: {name}")))?;
+        let arguments = match params.get("arguments") {
+            None | Some(Value::Null) => json!({}),
+            Some(args) => args.clone(),
+        };
+
+        let agent_id = format!("{CLIENT_AGENT_PREFIX}{client}");
+        let fqid = tool.descriptor.fully_qualified_id.as_str();
+        let args_summary: String = arguments.to_string().chars().take(200).collect();
+        let result = match self.check_permission(client, &agent_id, fqid).await {
+            Ok(true) => self.invoke(&tool.descriptor, arguments).await,
+            Ok(false) => Err(AppError::Permission(format!(
+                "{client} may not call {fqid}"
+            ))),
+            Err(e) => Err(e),
+        };
+        self.audit(fqid, &agent_id, &result, args_summary);
+        Ok(tool_result(result))
+    }
+
+    /// A standing decision answers straight away (an allow-once is used up);
+    /// otherwise the user is asked, and "always"/"never" are remembered.
+    async fn check_permission(
+        &self,
+        client: &str,
+        agent_id: &str,
+        fqid: &str,
+    ) -> Result<bool, AppError> {
+        let standing = {
+            let conn = self.data_store.conn()?;
+            mcp_permissions::consume_allow_once(&conn, HOST_SERVER_ID, fqid, agent_id)?
+        };
+        match standing {
+            Some(PermissionDecision::Never) => return Ok(false),
+            Some(_) => return Ok(true),
+            None => {}
+        }
+
+        let choice = self.request_permission(client, agent_id, fqid).await?;
+        let remembered = match choice {
+            McpPermissionChoice::AllowAlways => Some(PermissionDecision::AllowAlways),
+            McpPermissionChoice::Never => Some(PermissionDecision::Never),
+            McpPermissionChoice::AllowOnce | McpPermissionChoice::Cancel => None,
+        };
+        if let Some(decision) = remembered {
+            let conn = self.data_store.conn()?;
+            mcp_permissions::set_permission(
+                &conn,
+                &McpPermissionRow {
+                    server_id: HOST_SERVER_ID.to_string(),
+                    tool_id: fqid.to_string(),
+                    agent_id: agent_id.to_string(),
+                    decision,
+                    set_at: chrono::Utc::now().timestamp_millis(),
+                },
+            )?;
+        }
+        Ok(matches!(
+            choice,
+            McpPermissionChoice::AllowOnce | McpPermissionChoice::AllowAlways
+        ))
+    }
+
+    /// No answer within [`PERMISSION_TIMEOUT`] is a cancel.
+    async fn request_permission(
+        &self,
+        client: &str,
+        agent_id: &str,
+        fqid: &str,
+    ) -> Result<McpPermissionChoice, AppError> {
+        let request = HostPermissionRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            server_id: HOST_SERVER_ID.to_string(),
+            tool_id: fqid.to_string(),
+            agent_id: agent_id.to_string(),
+            client_name: client.to_string(),
+        };
+        let (tx, rx) = oneshot::channel();
+        self.pending_permissions
+            .lock()
+            .map_err(|_| AppError::Lock)?
+            .insert(request.id.clone(), tx);
+        let choice = if self.emit_event(HOST_PERMISSION_EVENT, &request) {
+            match tokio::time::timeout(PERMISSION_TIMEOUT, rx).await {
+                Ok(Ok(choice)) => choice,
+                _ => McpPermissionChoice::Cancel,
+            }
+        } else {
+            McpPermissionChoice::Cancel
+        };
+        if let Ok(mut pending) = self.pending_permissions.lock() {
+            pending.remove(&request.id);
+        }
+        Ok(choice)
+    }
+
+    /// Delivers the user's answer. `false` when nothing is waiting on `id`
+    /// (already answered, or timed out).
+    pub fn resolve_permission(
+        &self,
+        id: &str,
+        choice: McpPermissionChoice,
+    ) -> Result<bool, AppError> {
+        let waiting = self
+            .pending_permissions
+            .lock()
+            .map_err(|_| AppError::Lock)?
+            .remove(id);
+        Ok(waiting.is_some_and(|tx| tx.send(choice).is_ok()))
+    }
+
+    async fn invoke(&self, descriptor: &ToolDescriptor, args: Value) -> Result<Value, AppError> {
+        match &descriptor.source {
+            ToolSource::Builtin => {
+                invoke_builtin_tool_as(&self.registry, &descriptor.id, args, &ToolCaller::default())
+                    .await
+            }
+            ToolSource::Tier2(extension_id) => {
+                self.dispatch_tier2(extension_id, &descriptor.id, args)
+                    .await
+            }
+            ToolSource::Mcp(server_id) => Err(AppError::Permission(format!(
+                "tools of MCP server '{server_id}' are not served"
+            ))),
+        }
+    }
+
+    /// Tier-2 tools run in their extension's worker iframe, so the call is
+    /// handed to the frontend and the result comes back through
+    /// [`resolve_tool_call`](Self::resolve_tool_call).
+    async fn dispatch_tier2(
+        &self,
+        extension_id: &str,
+        tool_id: &str,
+        arguments: Value,
+    ) -> Result<Value, AppError> {
+        let request = HostToolDispatch {
+            id: uuid::Uuid::new_v4().to_string(),
+            extension_id: extension_id.to_string(),
+            tool_id: tool_id.to_string(),
+            arguments,
+        };
+        let (tx, rx) = oneshot::channel();
+        self.pending_calls
+            .lock()
+            .map_err(|_| AppError::Lock)?
+            .insert(request.id.clone(), tx);
+        let result = if self.emit_event(HOST_TOOL_DISPATCH_EVENT, &request) {
+            match tokio::time::timeout(TIER2_TIMEOUT, rx).await {
+                Ok(Ok(result)) => result.map_err(AppError::Other),
+                Ok(Err(_)) => Err(AppError::Other(format!(
+                    "{extension_id}:{tool_id} was dropped"
+                ))),
+                Err(_) => Err(AppError::Other(format!(
+                    "{extension_id}:{tool_id} timed out"
+                ))),
+            }
+        } else {
+            Err(AppError::Other(
+                "extension tools need the launcher window".to_string(),
+            ))
+        };
+        if let Ok(mut pending) = self.pending_calls.lock() {
+            pending.remove(&request.id);
+        }
+        result
+    }
+
+    /// Delivers a Tier-2 result. `false` when nothing is waiting on `id`.
+    pub fn resolve_tool_call(
+        &self,
+        id: &str,
+        result: Result<Value, String>,
+    ) -> Result<bool, AppError> {
+        let waiting = self
+            .pending_calls
+            .lock()
+            .map_err(|_| AppError::Lock)?
+            .remove(id);
+        Ok(waiting.is_some_and(|tx| tx.send(result).is_ok()))
+    }
+
+    fn emit_event<T: Serialize>(&self, event: &'static str, payload: &T) -> bool {
+        let Ok(payload) = serde_json::to_value(payload) else {
+            return false;
+        };
+        match self.emit.lock() {
+            Ok(g) => match g.as_ref() {
+                Some(emit) => {
+                    emit(event, payload);
+                    true
+                }
+                None => false,
+            },
+            Err(_) => false,
+        }
+    }
+
+    /// A failed insert is logged, never surfaced: the call already happened.
+    fn audit(
+        &self,
+        fqid: &str,
+        agent_id: &str,
+        result: &Result<Value, AppError>,
+        args_summary: String,
+    ) {
+        let entry = NewMcpAuditEntry {
+            server_id: HOST_SERVER_ID.to_string(),
+            tool_id: fqid.to_string(),
+            agent_id: Some(agent_id.to_string()),
+            called_at: chrono::Utc::now().timestamp_millis(),
+            success: result.is_ok(),
+            error_summary: result
+                .as_ref()
+                .err()
+                .map(|e| e.to_string().chars().take(200).collect()),
+            args_summary,
+        };
+        match self.data_store.conn() {
+            Ok(conn) => {
+                if let Err(e) = mcp_audit::insert_entry(&conn, &entry) {
+                    log::warn!("[mcp-host] failed to write audit log: {e}");
+                }
+            }
+            Err(e) => log::warn!("[mcp-host] no DB connection for audit: {e}"),
+        }
+    }
+}
+
+/// Loads (or first creates) the keychain token and starts the endpoint.
+pub async fn start_with_keyring(host: &Arc<McpHost>) -> Result<u16, AppError> {
+    let token = tokio::task::spawn_blocking(|| token::load_or_create(&token::keyring()))
+        .await
+        .map_err(|e| AppError::Other(format!("token task failed: {e}")))??;
+    host.set_token(Some(token));
+    host.start().await
+}
+
+fn client_name(name: Option<&str>) -> String {
+    let name: String = name
+        .map(str::trim)
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_CLIENT_NAME_LEN)
+        .collect();
+    if name.is_empty() {
+        "unknown client".to_string()
+    } else {
+        name
+    }
+}
+
+/// Built-ins keep their id; Tier-2 tools become `<extension>__<tool>` with
+/// everything outside `[A-Za-z0-9_-]` replaced.
+fn tool_name(descriptor: &ToolDescriptor) -> String {
+    let raw = match &descriptor.source {
+        ToolSource::Tier2(extension_id) => format!("{extension_id}__{}", descriptor.id),
+        _ => descriptor.id.clone(),
+    };
+    raw.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_TOOL_NAME_LEN)
+        .collect()
+}
+
+fn tool_entry(tool: &ExposedTool) -> Value {
+    // MCP requires an object schema; manifests may leave `type` out.
+    let input_schema = match &tool.descriptor.parameters {
+        Value::Object(schema) => {
+            let mut schema = schema.clone();
+            schema
+                .entry("type")
+                .or_insert_with(|| Value::String("object".to_string()));
+            Value::Object(schema)
+        }
+        _ => json!({ "type": "object" }),
+    };
+    json!({
+        "name": tool.name,
+        "title": tool.descriptor.name,
+        "description": tool.descriptor.description,
+        "inputSchema": input_schema,
+    })
+}
+
+/// Tool failures, refusals included, are results with `isError` so the
+/// client's model sees them, not protocol errors.
+fn tool_result(result: Result<Value, AppError>) -> Value {
+    let (text, is_error) = match result {
+        Ok(Value::String(text)) => (text, false),
+        Ok(value) => (
+            serde_json::to_string_pretty(&value).unwrap_or_else(|_| value.to_string()),
+            false,
+        ),
+        Err(e) => (e.to_string(), true),
+    };
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error,
+    })
+}
+
+fn rpc_result(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+pub(crate) fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::tools::{BuiltinTool, ManifestTool, ToolRegistry};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct EchoTool {
+        id: &'static str,
+    }
+
+    #[async_trait::async_trait]
+    impl BuiltinTool for EchoTool {
+        fn descriptor(&self) -> ToolDescriptor {
+            ToolDescriptor {
+                id: self.id.to_string(),
+                name: format!("Echo {}", self.id),
+                description: "Echoes its input.".to_string(),
+                parameters: json!({}),
+                source: ToolSource::Builtin,
+                fully_qualified_id: format!("builtin:{}", self.id),
+            }
+        }
+
+        async fn invoke(&self, args: Value) -> Result<Value, AppError> {
+            Ok(args)
+        }
+    }
+
+    pub(super) fn test_host() -> Arc<McpHost> {
+        let registry = Arc::new(ToolRegistry::new());
+        registry
+            .register_builtin(Arc::new(EchoTool { id: "calculator" }))
+            .unwrap();
+        registry
+            .register_builtin(Arc::new(EchoTool { id: "fs-write" }))
+            .unwrap();
+        registry
+            .register_tier2(
+                "org.example.notes",
+                vec![ManifestTool {
+                    id: "lookup".to_string(),
+                    name: "Lookup".to_string(),
+                    description: "Looks a note up.".to_string(),
+                    parameters: json!({ "properties": { "q": { "type": "string" } } }),
+                }],
+            )
+            .unwrap();
+        registry
+            .register_mcp(
+                "github",
+                vec![ManifestTool {
+                    id: "list_issues".to_string(),
+                    name: "list_issues".to_string(),
+                    description: String::new(),
+                    parameters: json!({ "type": "object" }),
+                }],
+            )
+            .unwrap();
+        Arc::new(McpHost::new(registry, crate::storage::create_test_store()))
+    }
+
+    /// Answers every permission request with `choice` and counts them.
+    fn answer_permissions(host: &Arc<McpHost>, choice: McpPermissionChoice) -> Arc<AtomicUsize> {
+        let asked = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&asked);
+        let answering = Arc::clone(host);
+        host.set_emitter(Box::new(move |event, payload| {
+            if event == HOST_PERMISSION_EVENT {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let id = payload["id"].as_str().unwrap().to_string();
+                answering.resolve_permission(&id, choice).unwrap();
+            }
+        }));
+        asked
+    }
+
+    fn call(name: &str, arguments: Value) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "tools/call",
+            "params": { "name": name, "arguments": arguments },
+        })
+    }
+
+    // 1. initialize_negotiates_version_and_opens_a_session
+    #[test]
+    fn initialize_negotiates_version_and_opens_a_session() {
+        let host = test_host();
+        let (reply, session) = host.initialize(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-03-26",
+                "clientInfo": { "name": "Zed", "version": "1.0" },
+            },
+        }));
+        assert_eq!(reply["id"], 1);
+        assert_eq!(reply["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(reply["result"]["serverInfo"]["name"], "asyar");
+        assert_eq!(host.session_client(&session).as_deref(), Some("Zed"));
+
+        let (reply, session) = host.initialize(&json!({
+            "id": 2,
+            "method": "initialize",
+            "params": { "protocolVersion": "1999-01-01" },
+        }));
+        assert_eq!(reply["result"]["protocolVersion"], PROTOCOL_VERSIONS[0]);
+        assert_eq!(
+            host.session_client(&session).as_deref(),
+            Some("unknown client")
+        );
+        assert!(host.close_session(&session));
+        assert!(host.session_client(&session).is_none());
+    }
+
+    // 2. tools_list_exposes_allowlisted_builtins_and_tier2_tools
+    #[tokio::test]
+    async fn tools_list_exposes_allowlisted_builtins_and_tier2_tools() {
+        let host = test_host();
+        let reply = host
+            .handle(
+                "Zed",
+                json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }),
+            )
+            .await
+            .unwrap();
+        let tools = reply["result"]["tools"].as_array().unwrap();
+        let mut names: Vec<_> = tools.iter().map(|t| t["name"].as_str().unwrap()).collect();
+        names.sort();
+        assert_eq!(names, ["calculator", "org_example_notes__lookup"]);
+        for tool in tools {
+            assert_eq!(tool["inputSchema"]["type"], "object");
+        }
+    }
+
+    // 3. tools_call_asks_once_and_remembers_allow_always
+    #[tokio::test]
+    async fn tools_call_asks_once_and_remembers_allow_always() {
+        let host = test_host();
+        let asked = answer_permissions(&host, McpPermissionChoice::AllowAlways);
+
+        for _ in 0..2 {
+            let reply = host
+                .handle("Zed", call("calculator", json!({ "expression": "1+1" })))
+                .await
+                .unwrap();
+            assert_eq!(reply["result"]["isError"], false);
+            assert!(reply["result"]["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .contains("1+1"));
+        }
+        assert_eq!(asked.load(Ordering::SeqCst), 1);
+
+        // The decision is per client.
+        host.handle("Cursor", call("calculator", json!({})))
+            .await
+            .unwrap();
+        assert_eq!(asked.load(Ordering::SeqCst), 2);
+
+        let conn = host.data_store.conn().unwrap();
+        let row = mcp_permissions::get_permission(
+            &conn,
+            HOST_SERVER_ID,
+            "builtin:calculator",
+            "mcp-client:Zed",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(row.decision, PermissionDecision::AllowAlways);
+    }
+
+    // 4. refused_calls_are_tool_errors_and_audited_per_client
+    #[tokio::test]
+    async fn refused_calls_are_tool_errors_and_audited_per_client() {
+        let host = test_host();
+        answer_permissions(&host, McpPermissionChoice::Cancel);
+
+        let reply = host
+            .handle("Zed", call("calculator", json!({})))
+            .await
+            .unwrap();
+        assert_eq!(reply["result"]["isError"], true);
+
+        // Hidden tools can't be called by name either.
+        let reply = host
+            .handle("Zed", call("fs-write", json!({})))
+            .await
+            .unwrap();
+        assert_eq!(reply["error"]["code"], INVALID_PARAMS);
+
+        let conn = host.data_store.conn().unwrap();
+        let rows = mcp_audit::list_recent(&conn, Some(HOST_SERVER_ID), 10).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].tool_id, "builtin:calculator");
+        assert_eq!(rows[0].agent_id.as_deref(), Some("mcp-client:Zed"));
+        assert!(!rows[0].success);
+    }
+
+    // 5. tier2_calls_are_dispatched_to_the_frontend
+    #[tokio::test]
+    async fn tier2_calls_are_dispatched_to_the_frontend() {
+        let host = test_host();
+        {
+            let conn = host.data_store.conn().unwrap();
+            mcp_permissions::set_permission(
+                &conn,
+                &McpPermissionRow {
+                    server_id: HOST_SERVER_ID.to_string(),
+                    tool_id: "org.example.notes:lookup".to_string(),
+                    agent_id: "mcp-client:Zed".to_string(),
+                    decision: PermissionDecision::AllowOnce,
+                    set_at: 0,
+                },
+            )
+            .unwrap();
+        }
+        let answering = Arc::clone(&host);
+        host.set_emitter(Box::new(move |event, payload| {
+            assert_eq!(event, HOST_TOOL_DISPATCH_EVENT);
+            assert_eq!(payload["extensionId"], "org.example.notes");
+            assert_eq!(payload["toolId"], "lookup");
+            let id = payload["id"].as_str().unwrap().to_string();
+            answering
+                .resolve_tool_call(&id, Ok(json!("found it")))
+                .unwrap();
+        }));
+
+        let reply = host
+            .handle(
+                "Zed",
+                call("org_example_notes__lookup", json!({ "q": "todo" })),
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply["result"]["content"][0]["text"], "found it");
+        assert!(!host.resolve_tool_call("gone", Ok(Value::Null)).unwrap());
+    }
+
+    // 6. notifications_get_no_reply_and_unknown_methods_error
+    #[tokio::test]
+    async fn notifications_get_no_reply_and_unknown_methods_error() {
+        let host = test_host();
+        assert!(host
+            .handle(
+                "Zed",
+                json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })
+            )
+            .await
+            .is_none());
+        let reply = host
+            .handle(
+                "Zed",
+                json!({ "jsonrpc": "2.0", "id": "a", "method": "resources/list" }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply["id"], "a");
+        assert_eq!(reply["error"]["code"], METHOD_NOT_FOUND);
+    }
+}
@@ -0,0 +1,309 @@
+//! `asyar mcp`: the stdio transport, for MCP clients that launch their
+//! servers as subprocesses. It serves nothing itself — each line of
+//! JSON-RPC on stdin is forwarded to the running launcher's HTTP endpoint
+//! ([`super::http`]) and the reply written to stdout, so tools, consent
+//! prompts and the audit log all stay in the launcher.
+//!
+//! The launcher may restart while a client stays connected: an unreachable
+//! endpoint is looked up again, and a forgotten session is reopened by
+//! replaying the client's `initialize`.
+
+use crate::mcp::host::http::{HOST_PORT_RANGE, SESSION_HEADER};
+use crate::mcp::host::{rpc_error, token, PARSE_ERROR, SERVER_ERROR};
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+/// `asyar <SUBCOMMAND>` runs the bridge instead of the launcher.
+pub const SUBCOMMAND: &str = "mcp";
+
+const NOT_RUNNING: &str =
+    "Asyar isn't running, or its MCP server is off (turn it on from Manage MCP Servers)";
+
+const NO_TOKEN: &str = "Asyar's MCP server has never been turned on on this machine";
+
+const PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Runs the bridge until stdin closes; returns the process exit code.
+pub fn run() -> i32 {
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("asyar mcp: failed to start: {e}");
+            return 1;
+        }
+    };
+    runtime.block_on(async {
+        let mut proxy = StdioProxy::new(
+            HOST_PORT_RANGE.collect(),
+            Box::new(|| token::load(&token::keyring()).ok().flatten()),
+        );
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        let mut stdout = tokio::io::stdout();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let Some(reply) = proxy.forward(&line).await else {
+                continue;
+            };
+            let written = async {
+                stdout.write_all(reply.as_bytes()).await?;
+                stdout.write_all(b"\n").await?;
+                stdout.flush().await
+            };
+            if written.await.is_err() {
+                return 1;
+            }
+        }
+        0
+    })
+}
+
+pub type TokenSource = Box<dyn Fn() -> Option<String> + Send + Sync>;
+
+#[derive(Clone)]
+struct Endpoint {
+    base_url: String,
+    token: String,
+}
+
+enum PostError {
+    /// Nothing listening, or the token changed: look the endpoint up again.
+    Unreachable,
+    /// The launcher no longer knows the session.
+    SessionExpired,
+    Failed(String),
+}
+
+pub struct StdioProxy {
+    client: reqwest::Client,
+    ports: Vec<u16>,
+    token: TokenSource,
+    endpoint: Option<Endpoint>,
+    session: Option<String>,
+    /// The client's `initialize`, replayed when a session has to be reopened.
+    initialize: Option<Value>,
+}
+
+impl StdioProxy {
+    pub fn new(ports: Vec<u16>, token: TokenSource) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            ports,
+            token,
+            endpoint: None,
+            session: None,
+            initialize: None,
+        }
+    }
+
+    /// Forwards one message; `None` when it gets no reply. Failures become
+    /// JSON-RPC errors for requests and are dropped for notifications.
+    pub async fn forward(&mut self, line: &str) -> Option<String> {
+        let message: Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(e) => {
+                let error = rpc_error(Value::Null, PARSE_ERROR, &format!("invalid JSON: {e}"));
+                return Some(error.to_string());
+            }
+        };
+        if is_initialize(&message) {
+            self.initialize = Some(message.clone());
+            self.session = None;
+        }
+        match self.send(&message).await {
+            Ok(reply) => reply.map(|reply| reply.to_string()),
+            Err(error) => {
+                let id = message
+                    .get("id")
+                    .filter(|_| message.get("method").is_some())?;
+                Some(rpc_error(id.clone(), SERVER_ERROR, &error).to_string())
+            }
+        }
+    }
+
+    async fn send(&mut self, message: &Value) -> Result<Option<Value>, String> {
+        let mut retried = false;
+        loop {
+            let endpoint = self.endpoint().await?;
+            let reopen = match (&self.session, &self.initialize) {
+                (None, Some(initialize)) if !is_initialize(message) => Some(initialize.clone()),
+                _ => None,
+            };
+            let result = match reopen {
+                Some(initialize) => match self.post(&endpoint, &initialize).await {
+                    Ok(_) => self.post(&endpoint, message).await,
+                    Err(e) => Err(e),
+                },
+                None => self.post(&endpoint, message).await,
+            };
+            match result {
+                Ok(reply) => return Ok(reply),
+                Err(PostError::Failed(error)) => return Err(error),
+                Err(PostError::Unreachable) if !retried => {
+                    self.endpoint = None;
+                    self.session = None;
+                }
+                Err(PostError::SessionExpired) if !retried => self.session = None,
+                Err(PostError::Unreachable) => return Err(NOT_RUNNING.to_string()),
+                Err(PostError::SessionExpired) => {
+                    return Err("the launcher keeps dropping the session".to_string())
+                }
+            }
+            retried = true;
+        }
+    }
+
+    async fn endpoint(&mut self) -> Result<Endpoint, String> {
+        if let Some(endpoint) = &self.endpoint {
+            return Ok(endpoint.clone());
+        }
+        let token = (self.token)().ok_or(NO_TOKEN)?;
+        for port in &self.ports {
+            let base_url = format!("http://127.0.0.1:{port}");
+            let probe = self
+                .client
+                .get(format!("{base_url}/discover"))
+                .timeout(PROBE_TIMEOUT)
+                .send()
+                .await;
+            let Ok(response) = probe else { continue };
+            let is_host = response
+                .json::<Value>()
+                .await
+                .is_ok_and(|body| body["name"] == "asyar" && body["service"] == "mcp");
+            if is_host {
+                let endpoint = Endpoint { base_url, token };
+                self.endpoint = Some(endpoint.clone());
+                return Ok(endpoint);
+            }
+        }
+        Err(NOT_RUNNING.to_string())
+    }
+
+    async fn post(
+        &mut self,
+        endpoint: &Endpoint,
+        message: &Value,
+    ) -> Result<Option<Value>, PostError> {
+        let mut request = self
+            .client
+            .post(format!("{}/mcp", endpoint.base_url))
+            .bearer_auth(&endpoint.token)
+            .header("accept", "application/json, text/event-stream")
+            .json(message);
+        if let Some(session) = &self.session {
+            request = request.header(SESSION_HEADER, session);
+        }
+        let response = request.send().await.map_err(|_| PostError::Unreachable)?;
+        if let Some(session) = response
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|h| h.to_str().ok())
+        {
+            self.session = Some(session.to_string());
+        }
+        match response.status() {
+            StatusCode::ACCEPTED => Ok(None),
+            StatusCode::NOT_FOUND => Err(PostError::SessionExpired),
+            StatusCode::UNAUTHORIZED => Err(PostError::Unreachable),
+            status => match response.json::<Value>().await {
+                Ok(reply) => Ok(Some(reply)),
+                Err(_) => Err(PostError::Failed(format!("the launcher answered {status}"))),
+            },
+        }
+    }
+}
+
+fn is_initialize(message: &Value) -> bool {
+    message["method"] == "initialize"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::host::http::start_server;
+    use crate::mcp::host::tests::test_host;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn proxy_for(port: u16) -> StdioProxy {
+        StdioProxy::new(vec![port], Box::new(|| Some("secret".to_string())))
+    }
+
+    fn line(value: Value) -> String {
+        value.to_string()
+    }
+
+    // 1. forwards_requests_and_drops_notification_replies
+    #[tokio::test]
+    async fn forwards_requests_and_drops_notification_replies() {
+        let host = test_host();
+        host.set_token(Some("secret".to_string()));
+        let handle = start_server(Arc::clone(&host), [0]).await.unwrap();
+        let mut proxy = proxy_for(handle.port());
+
+        let reply = proxy
+            .forward(&line(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": { "clientInfo": { "name": "Claude" } },
+            })))
+            .await
+            .unwrap();
+        let reply: Value = serde_json::from_str(&reply).unwrap();
+        assert_eq!(reply["result"]["serverInfo"]["name"], "asyar");
+
+        let none = proxy
+            .forward(&line(
+                json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+            ))
+            .await;
+        assert!(none.is_none());
+
+        // The launcher forgot the session: the proxy reopens it transparently.
+        let session = proxy.session.clone().unwrap();
+        assert!(host.close_session(&session));
+        let reply = proxy
+            .forward(&line(
+                json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }),
+            ))
+            .await
+            .unwrap();
+        let reply: Value = serde_json::from_str(&reply).unwrap();
+        assert!(reply["result"]["tools"].is_array());
+        assert_ne!(proxy.session.as_deref(), Some(session.as_str()));
+        handle.shutdown().await;
+    }
+
+    // 2. reports_a_missing_launcher_as_a_json_rpc_error
+    #[tokio::test]
+    async fn reports_a_missing_launcher_as_a_json_rpc_error() {
+        let mut proxy = StdioProxy::new(vec![], Box::new(|| Some("secret".to_string())));
+        let reply = proxy
+            .forward(&line(
+                json!({ "jsonrpc": "2.0", "id": 3, "method": "tools/list" }),
+            ))
+            .await
+            .unwrap();
+        let reply: Value = serde_json::from_str(&reply).unwrap();
+        assert_eq!(reply["id"], 3);
+        assert_eq!(reply["error"]["code"], SERVER_ERROR);
+        assert_eq!(reply["error"]["message"], NOT_RUNNING);
+
+        assert!(proxy
+            .forward(&line(
+                json!({ "jsonrpc": "2.0", "method": "notifications/x" })
+            ))
+            .await
+            .is_none());
+        let reply = proxy.forward("{oops").await.unwrap();
+        assert!(reply.contains(&PARSE_ERROR.to_string()));
+    }
+}
@@ -0,0 +1,74 @@
+//! The bearer token MCP clients present to the host endpoint. It lives in
+//! the OS keychain so client configs keep working across restarts, and so
+//! the `asyar mcp` stdio bridge — a separate process — can read it too.
+
+use crate::browser::bridge::token_store::{generate_token, KeyringBackend, SecretBackend};
+use crate::error::AppError;
+
+const KEYRING_SERVICE: &str = "asyar-mcp-host";
+const KEYRING_ACCOUNT: &str = "token";
+
+pub fn keyring() -> KeyringBackend {
+    KeyringBackend::new(KEYRING_SERVICE)
+}
+
+pub fn load(backend: &dyn SecretBackend) -> Result<Option<String>, AppError> {
+    backend.read(KEYRING_ACCOUNT).map_err(AppError::Other)
+}
+
+pub fn load_or_create(backend: &dyn SecretBackend) -> Result<String, AppError> {
+    match load(backend)? {
+        Some(token) => Ok(token),
+        None => rotate(backend),
+    }
+}
+
+/// Replaces the token; every configured client has to be updated.
+pub fn rotate(backend: &dyn SecretBackend) -> Result<String, AppError> {
+    let token = generate_token();
+    backend
+        .write(KEYRING_ACCOUNT, &token)
+        .map_err(AppError::Other)?;
+    Ok(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryBackend(Mutex<HashMap<String, String>>);
+
+    impl SecretBackend for MemoryBackend {
+        fn read(&self, account: &str) -> Result<Option<String>, String> {
+            Ok(self.0.lock().unwrap().get(account).cloned())
+        }
+        fn write(&self, account: &str, secret: &str) -> Result<(), String> {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(account.to_string(), secret.to_string());
+            Ok(())
+        }
+        fn remove(&self, account: &str) -> Result<(), String> {
+            self.0.lock().unwrap().remove(account);
+            Ok(())
+        }
+    }
+
+    // 1. load_or_create_is_stable_until_rotated
+    #[test]
+    fn load_or_create_is_stable_until_rotated() {
+        let backend = MemoryBackend::default();
+        assert_eq!(load(&backend).unwrap(), None);
+
+        let first = load_or_create(&backend).unwrap();
+        assert_eq!(load_or_create(&backend).unwrap(), first);
+
+        let rotated = rotate(&backend).unwrap();
+        assert_ne!(rotated, first);
+        assert_eq!(load(&backend).unwrap(), Some(rotated));
+    }
+}
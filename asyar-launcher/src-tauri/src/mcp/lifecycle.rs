@@ -4,9 +4,10 @@ use crate::mcp::install::{
     required_runtime_for_command_with_probe, transport_from_row, RuntimeAvailability,
     SingleRuntimeAvailability,
 };
-use crate::mcp::supervisor::McpSupervisor;
+use crate::mcp::oauth::{self, McpAuthorizer};
+use crate::mcp::supervisor::{McpSupervisor, ToolsChangedEvent};
 use crate::mcp::tool_adapter::descriptors_from_mcp_tools;
-use crate::mcp::types::McpServerConfig;
+use crate::mcp::types::{McpServerConfig, McpServerStatus, McpTransportSpec};
 use crate::storage::mcp_audit;
 use crate::storage::mcp_permissions;
 use crate::storage::mcp_servers;
@@ -19,6 +20,51 @@ use tauri::Manager;
 /// Timeout used by `enable_and_wait_for_tools` in startup seed and enable-toggle flows.
 const ENABLE_WAIT_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// The OAuth resource a transport signs in to, if it's a remote server.
+fn oauth_resource(transport: &McpTransportSpec) -> Option<String> {
+    match transport {
+        McpTransportSpec::Http { url, .. } => reqwest::Url::parse(url)
+            .ok()
+            .map(|url| oauth::resource_for(&url)),
+        McpTransportSpec::Stdio { .. } => None,
+    }
+}
+
+// ── mcp_follow_tool_list_changes ─────────────────────────────────────────────
+
+/// Keeps the tool registry in step with servers that announce
+/// `tools/list_changed` while connected. `changes` comes from
+/// `McpSupervisor::subscribe_tools_changed`; runs until the supervisor
+/// is dropped.
+pub async fn follow_tool_list_changes(
+    supervisor: &McpSupervisor,
+    registry: &ToolRegistryState,
+    mut changes: tokio::sync::broadcast::Receiver<ToolsChangedEvent>,
+) {
+    use tokio::sync::broadcast::error::RecvError;
+    loop {
+        let event = match changes.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(missed)) => {
+                log::warn!("[mcp] missed {missed} tool list changes");
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+        // The server may have been disabled while it was being re-listed.
+        if supervisor.status(&event.server_id).await != Some(McpServerStatus::Connected) {
+            continue;
+        }
+        let manifest_tools = descriptors_from_mcp_tools(&event.server_id, event.tools);
+        if let Err(e) = registry.register_mcp(&event.server_id, manifest_tools) {
+            log::warn!(
+                "[mcp] failed to re-register tools for '{}': {e}",
+                event.server_id
+            );
+        }
+    }
+}
+
 // ── mcp_seed_enabled_servers_at_startup ───────────────────────────────────────
 
 /// Testable core of the startup seed loop. `path_probe` and `runtime_installed`
@@ -190,6 +236,16 @@ pub async fn mcp_sync_on_enable_change<R: tauri::Runtime>(
 
         let transport = transport_from_row(&row)?;
 
+        // Turning a remote server on is the user's cue to sign in again,
+        // so leave room for the browser round trip.
+        let mut wait = ENABLE_WAIT_TIMEOUT;
+        if let Some(resource) = oauth_resource(&transport) {
+            if let Some(authorizer) = app.try_state::<Arc<McpAuthorizer>>() {
+                authorizer.allow_sign_in(&resource);
+            }
+            wait += oauth::SIGN_IN_TIMEOUT;
+        }
+
         let config = McpServerConfig {
             id: row.id.clone(),
             display_name: row.display_name.clone(),
@@ -199,7 +255,7 @@ pub async fn mcp_sync_on_enable_change<R: tauri::Runtime>(
 
         // Enable watchdog and wait for the first handshake — one round-trip.
         let tools = supervisor
-            .enable_and_wait_for_tools(config, ENABLE_WAIT_TIMEOUT)
+            .enable_and_wait_for_tools(config, wait)
             .await
             .map_err(|e| {
                 AppError::Other(format!(
@@ -420,13 +476,15 @@ pub async fn mcp_cleanup_on_delete<R: tauri::Runtime>(
         .try_state::<crate::storage::DataStore>()
         .ok_or_else(|| AppError::Other("DataStore not managed".to_string()))?;
 
-    let required_runtime = {
+    let transport = {
         let conn = store.conn()?;
         mcp_servers::get_server(&conn, server_id)?
             .as_ref()
             .and_then(|row| transport_from_row(row).ok())
-            .and_then(|transport| required_runtime_for_command_with_probe(&transport, &path_probe))
     };
+    let required_runtime = transport
+        .as_ref()
+        .and_then(|transport| required_runtime_for_command_with_probe(transport, &path_probe));
 
     // Stop watchdog (idempotent if not running).
     supervisor
@@ -454,6 +512,14 @@ pub async fn mcp_cleanup_on_delete<R: tauri::Runtime>(
         runtime_manager.remove_consumer(name, &format!("mcp:{server_id}"));
     }
 
+    // Signed-in credentials for a remote server go with it.
+    let resource = transport.as_ref().and_then(oauth_resource);
+    if let (Some(resource), Some(authorizer)) = (resource, app.try_state::<Arc<McpAuthorizer>>()) {
+        if let Err(e) = authorizer.forget(&resource).await {
+            log::warn!("[mcp] failed to remove stored sign-in for '{server_id}': {e}");
+        }
+    }
+
     Ok(())
 }
 
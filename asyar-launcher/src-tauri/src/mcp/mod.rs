@@ -1,6 +1,10 @@
+pub mod catalog;
 pub mod client;
+pub mod host;
 pub mod install;
 pub mod lifecycle;
+pub mod oauth;
+pub mod server_requests;
 pub mod sidecar;
 pub mod supervisor;
 pub mod tool_adapter;
@@ -11,12 +15,15 @@ pub use client::McpClient;
 pub use install::{
     DetectedConfig, InstallOutcome, McpServerInstallInput, McpServerSummary, McpTestResult,
 };
-pub use supervisor::{McpSupervisor, SupervisorConfig};
+pub use supervisor::{
+    McpSupervisor, RequestHandlerFactory, ServerCatalog, SupervisorConfig, ToolsChangedEvent,
+};
 pub use transport::{
     HttpTransportFactory, MultiTransportFactory, RuntimeResolver, StdioTransportFactory, Transport,
     TransportFactory,
 };
 pub use types::{
-    McpCallResult, McpClientError, McpServerConfig, McpServerId, McpServerStatus,
-    McpToolDescriptor, McpTransportSpec,
+    McpCallHooks, McpCallResult, McpClientError, McpProgress, McpPrompt, McpPromptResult,
+    McpResource, McpResourceContents, McpResourceTemplate, McpServerConfig, McpServerId,
+    McpServerStatus, McpToolDescriptor, McpTransportSpec,
 };
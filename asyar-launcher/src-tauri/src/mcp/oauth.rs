@@ -0,0 +1,959 @@
+//! OAuth 2.1 for remote MCP servers, following the MCP authorization spec.
+//! A server that answers 401 is asked where its authorization server lives
+//! (protected-resource metadata, RFC 9728); Asyar registers itself there
+//! (dynamic client registration, RFC 7591) and signs the user in through
+//! the browser with PKCE, redirecting to a one-shot loopback listener
+//! (RFC 8252). Every authorization and token request names the server as
+//! the `resource` (RFC 8707), so tokens are bound to it.
+//!
+//! Credentials are kept per server in the OS keychain, keyed by the
+//! server's URL. Access tokens are refreshed shortly before they expire and
+//! again when the server rejects one; a rotated refresh token replaces the
+//! old one. Servers whose config already sets an `Authorization` header
+//! are left alone.
+
+use crate::browser::bridge::token_store::{KeyringBackend, SecretBackend};
+use crate::error::AppError;
+use crate::oauth::service::{
+    build_token_from_response, is_token_expired, now_secs, TokenExchangeResponse,
+};
+use crate::oauth::{pkce, OAuthToken};
+use axum::{extract::Query, response::Html, routing::get, Router};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+
+const KEYRING_SERVICE: &str = "asyar-mcp-oauth";
+
+const CALLBACK_PATH: &str = "/callback";
+
+/// How long the user has to finish signing in in the browser.
+pub const SIGN_IN_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// After a sign-in fails or is abandoned, reconnect attempts fail quietly
+/// for this long instead of opening a browser tab on every retry.
+const SIGN_IN_COOLDOWN: Duration = Duration::from_secs(600);
+
+pub fn keyring() -> KeyringBackend {
+    KeyringBackend::new(KEYRING_SERVICE)
+}
+
+/// Opens a URL in the user's browser.
+pub type UrlOpener = Box<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+/// The canonical form of a server URL: what credentials are stored under
+/// and what is sent as the `resource` parameter.
+pub fn resource_for(url: &reqwest::Url) -> String {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url.to_string()
+}
+
+/// What the keychain holds for one server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Credentials {
+    client_id: String,
+    #[serde(default)]
+    client_secret: Option<String>,
+    token_endpoint: String,
+    token: OAuthToken,
+}
+
+#[derive(Deserialize)]
+struct ProtectedResourceMetadata {
+    authorization_servers: Vec<String>,
+    #[serde(default)]
+    scopes_supported: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct AuthServerMetadata {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    #[serde(default)]
+    registration_endpoint: Option<String>,
+    #[serde(default)]
+    code_challenge_methods_supported: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct RegisteredClient {
+    client_id: String,
+    #[serde(default)]
+    client_secret: Option<String>,
+}
+
+pub struct McpAuthorizer {
+    client: reqwest::Client,
+    secrets: Arc<dyn SecretBackend>,
+    /// Wired in `setup_app` once an `AppHandle` exists.
+    opener: RwLock<Option<UrlOpener>>,
+    /// One refresh or sign-in per server at a time; concurrent 401s wait
+    /// for it and reuse the result.
+    locks: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+    failed_sign_ins: Mutex<HashMap<String, Instant>>,
+    /// Keychain contents already read, so requests don't hit the keychain.
+    cache: Mutex<HashMap<String, Credentials>>,
+}
+
+impl McpAuthorizer {
+    pub fn new(secrets: Arc<dyn SecretBackend>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            secrets,
+            opener: RwLock::new(None),
+            locks: Mutex::new(HashMap::new()),
+            failed_sign_ins: Mutex::new(HashMap::new()),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn set_opener(&self, opener: UrlOpener) {
+        *self.opener.write().unwrap() = Some(opener);
+    }
+
+    /// The bearer token to send to `resource`, refreshed first if it's
+    /// about to expire. `None` when there is none yet or the refresh
+    /// failed; the server's 401 then leads to [`Self::reauthorize`].
+    pub async fn access_token(&self, resource: &str) -> Option<String> {
+        let stored = self.load(resource).await?;
+        if !is_token_expired(&stored.token) {
+            return Some(stored.token.access_token);
+        }
+        let lock = self.lock_for(resource);
+        let _guard = lock.lock().await;
+        let stored = self.load(resource).await?;
+        if !is_token_expired(&stored.token) {
+            return Some(stored.token.access_token);
+        }
+        match self.refresh(resource, stored).await {
+            Ok(token) => Some(token),
+            Err(e) => {
+                log::info!("[mcp oauth] refresh for {resource} failed: {e}");
+                None
+            }
+        }
+    }
+
+    /// Called when `resource` answered 401 to `rejected` (the token that
+    /// was sent, if any). Refreshes when possible, otherwise signs in
+    /// through the browser. `challenge` is the `WWW-Authenticate` header.
+    pub async fn reauthorize(
+        &self,
+        resource: &str,
+        challenge: Option<&str>,
+        rejected: Option<&str>,
+    ) -> Result<String, AppError> {
+        let lock = self.lock_for(resource);
+        let _guard = lock.lock().await;
+
+        if let Some(stored) = self.load(resource).await {
+            // Another request already replaced the rejected token.
+            if rejected != Some(stored.token.access_token.as_str())
+                && !is_token_expired(&stored.token)
+            {
+                return Ok(stored.token.access_token);
+            }
+            if stored.token.refresh_token.is_some() {
+                match self.refresh(resource, stored).await {
+                    Ok(token) => return Ok(token),
+                    Err(e) => log::info!("[mcp oauth] refresh for {resource} failed: {e}"),
+                }
+            }
+        }
+
+        let recently_failed = self
+            .failed_sign_ins
+            .lock()
+            .unwrap()
+            .get(resource)
+            .is_some_and(|at| at.elapsed() < SIGN_IN_COOLDOWN);
+        if recently_failed {
+            return Err(AppError::OAuth(format!(
+                "{resource} needs you to sign in; turn the server off and on to try again"
+            )));
+        }
+        match self.sign_in(resource, challenge).await {
+            Ok(token) => {
+                self.failed_sign_ins.lock().unwrap().remove(resource);
+                Ok(token)
+            }
+            Err(e) => {
+                self.failed_sign_ins
+                    .lock()
+                    .unwrap()
+                    .insert(resource.to_string(), Instant::now());
+                Err(e)
+            }
+        }
+    }
+
+    /// Lets the next 401 from `resource` open the browser again, even
+    /// within the cool-down after a failed sign-in.
+    pub fn allow_sign_in(&self, resource: &str) {
+        self.failed_sign_ins.lock().unwrap().remove(resource);
+    }
+
+    /// Drops the stored credentials for `resource` (the server was removed).
+    pub async fn forget(&self, resource: &str) -> Result<(), AppError> {
+        self.allow_sign_in(resource);
+        self.cache.lock().unwrap().remove(resource);
+        let secrets = Arc::clone(&self.secrets);
+        let account = resource.to_string();
+        tokio::task::spawn_blocking(move || secrets.remove(&account))
+            .await
+            .map_err(|e| AppError::Other(e.to_string()))?
+            .map_err(AppError::Other)
+    }
+
+    fn lock_for(&self, resource: &str) -> Arc<tokio::sync::Mutex<()>> {
+        Arc::clone(
+            self.locks
+                .lock()
+                .unwrap()
+                .entry(resource.to_string())
+                .or_default(),
+        )
+    }
+
+    async fn load(&self, resource: &str) -> Option<Credentials> {
+        if let Some(cached) = self.cache.lock().unwrap().get(resource) {
+            return Some(cached.clone());
+        }
+        let secrets = Arc::clone(&self.secrets);
+        let account = resource.to_string();
+        let stored = tokio::task::spawn_blocking(move || secrets.read(&account))
+            .await
+            .ok()?
+            .ok()??;
+        let credentials: Credentials = serde_json::from_str(&stored).ok()?;
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(resource.to_string(), credentials.clone());
+        Some(credentials)
+    }
+
+    async fn save(&self, resource: &str, credentials: &Credentials) -> Result<(), AppError> {
+        let secrets = Arc::clone(&self.secrets);
+        let account = resource.to_string();
+        let value = serde_json::to_string(credentials)?;
+        tokio::task::spawn_blocking(move || secrets.write(&account, &value))
+            .await
+            .map_err(|e| AppError::Other(e.to_string()))?
+            .map_err(AppError::Other)?;
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(resource.to_string(), credentials.clone());
+        Ok(())
+    }
+
+    async fn refresh(&self, resource: &str, stored: Credentials) -> Result<String, AppError> {
+        let Some(refresh_token) = stored.token.refresh_token.clone() else {
+            return Err(AppError::OAuth("no refresh token".into()));
+        };
+        let mut token = self
+            .request_token(
+                &stored.token_endpoint,
+                vec![
+                    ("grant_type", "refresh_token"),
+                    ("refresh_token", refresh_token.as_str()),
+                    ("client_id", stored.client_id.as_str()),
+                    ("resource", resource),
+                ],
+                stored.client_secret.as_deref(),
+            )
+            .await?;
+        // A server that doesn't rotate refresh tokens leaves it out.
+        if token.refresh_token.is_none() {
+            token.refresh_token = Some(refresh_token);
+        }
+        let access_token = token.access_token.clone();
+        self.save(resource, &Credentials { token, ..stored })
+            .await?;
+        Ok(access_token)
+    }
+
+    async fn sign_in(&self, resource: &str, challenge: Option<&str>) -> Result<String, AppError> {
+        let (metadata, scopes) = self.discover(resource, challenge).await?;
+
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let redirect_uri = format!(
+            "http://127.0.0.1:{}{CALLBACK_PATH}",
+            listener.local_addr()?.port()
+        );
+        let client = self.register(&metadata, &redirect_uri).await?;
+        let (code_verifier, code_challenge) = pkce::generate_pkce_pair();
+        let state = pkce::generate_state();
+        let auth_url = authorization_url(
+            &metadata.authorization_endpoint,
+            &client.client_id,
+            &code_challenge,
+            &state,
+            &scopes,
+            &redirect_uri,
+            resource,
+        )?;
+
+        let callback = CallbackListener::start(listener);
+        self.open(&auth_url)?;
+        let params = tokio::time::timeout(SIGN_IN_TIMEOUT, callback.wait())
+            .await
+            .map_err(|_| AppError::OAuth(format!("signing in to {resource} timed out")))??;
+        if params.get("state") != Some(&state) {
+            return Err(AppError::OAuth(
+                "sign-in returned an unexpected state".into(),
+            ));
+        }
+        if let Some(error) = params.get("error") {
+            let description = params.get("error_description").unwrap_or(error);
+            return Err(AppError::OAuth(format!(
+                "sign-in was refused: {description}"
+            )));
+        }
+        let code = params
+            .get("code")
+            .ok_or_else(|| AppError::OAuth("sign-in returned no authorization code".into()))?;
+
+        let token = self
+            .request_token(
+                &metadata.token_endpoint,
+                vec![
+                    ("grant_type", "authorization_code"),
+                    ("code", code.as_str()),
+                    ("redirect_uri", redirect_uri.as_str()),
+                    ("client_id", client.client_id.as_str()),
+                    ("code_verifier", code_verifier.as_str()),
+                    ("resource", resource),
+                ],
+                client.client_secret.as_deref(),
+            )
+            .await?;
+        let access_token = token.access_token.clone();
+        let credentials = Credentials {
+            client_id: client.client_id,
+            client_secret: client.client_secret,
+            token_endpoint: metadata.token_endpoint,
+            token,
+        };
+        self.save(resource, &credentials).await?;
+        Ok(access_token)
+    }
+
+    /// Finds the authorization server for `resource` and the scopes to ask
+    /// for: those named in the 401 challenge, else all the server lists.
+    async fn discover(
+        &self,
+        resource: &str,
+        challenge: Option<&str>,
+    ) -> Result<(AuthServerMetadata, Vec<String>), AppError> {
+        let resource_url = url::Url::parse(resource)
+            .map_err(|e| AppError::Validation(format!("invalid server URL: {e}")))?;
+        let mut candidates: Vec<String> = challenge
+            .and_then(|c| challenge_param(c, "resource_metadata"))
+            .into_iter()
+            .collect();
+        candidates.extend(well_known_urls(&resource_url, "oauth-protected-resource"));
+        let protected: Option<ProtectedResourceMetadata> = self.first_json(&candidates).await;
+
+        // Servers from before resource metadata existed are their own
+        // authorization server.
+        let issuer = protected
+            .as_ref()
+            .and_then(|m| m.authorization_servers.first().cloned())
+            .unwrap_or_else(|| resource_url.origin().ascii_serialization());
+        let issuer_url = url::Url::parse(&issuer)
+            .map_err(|e| AppError::OAuth(format!("invalid authorization server URL: {e}")))?;
+        let mut candidates = well_known_urls(&issuer_url, "oauth-authorization-server");
+        candidates.extend(well_known_urls(&issuer_url, "openid-configuration"));
+        let metadata: AuthServerMetadata = self.first_json(&candidates).await.ok_or_else(|| {
+            AppError::OAuth(format!(
+                "no authorization server metadata found for {issuer}"
+            ))
+        })?;
+        if let Some(methods) = &metadata.code_challenge_methods_supported {
+            if !methods.iter().any(|m| m == "S256") {
+                return Err(AppError::OAuth(
+                    "the authorization server doesn't support PKCE (S256)".into(),
+                ));
+            }
+        }
+
+        let scopes = challenge
+            .and_then(|c| challenge_param(c, "scope"))
+            .map(|s| s.split_whitespace().map(String::from).collect())
+            .or(protected.map(|m| m.scopes_supported))
+            .unwrap_or_default();
+        Ok((metadata, scopes))
+    }
+
+    async fn first_json<T: DeserializeOwned>(&self, urls: &[String]) -> Option<T> {
+        for url in urls {
+            let response = self
+                .client
+                .get(url)
+                .header("Accept", "application/json")
+                .send()
+                .await;
+            let Ok(response) = response else { continue };
+            if !response.status().is_success() {
+                continue;
+            }
+            if let Ok(parsed) = response.json::<T>().await {
+                return Some(parsed);
+            }
+        }
+        None
+    }
+
+    /// Registers Asyar as a public client for this sign-in. The loopback
+    /// port changes every time, so the registration isn't reused.
+    async fn register(
+        &self,
+        metadata: &AuthServerMetadata,
+        redirect_uri: &str,
+    ) -> Result<RegisteredClient, AppError> {
+        let Some(endpoint) = &metadata.registration_endpoint else {
+            return Err(AppError::OAuth(
+                "the server's authorization server doesn't allow client registration; \
+                 add an Authorization header to the server instead"
+                    .into(),
+            ));
+        };
+        let response = self
+            .client
+            .post(endpoint)
+            .json(&json!({
+                "client_name": "Asyar",
+                "redirect_uris": [redirect_uri],
+                "grant_types": ["authorization_code", "refresh_token"],
+                "response_types": ["code"],
+                "token_endpoint_auth_method": "none",
+            }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::OAuth(format!(
+                "Client registration failed ({status}): {body}"
+            )));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| AppError::OAuth(format!("Failed to parse registration response: {e}")))
+    }
+
+    async fn request_token<'a>(
+        &self,
+        endpoint: &str,
+        mut params: Vec<(&'a str, &'a str)>,
+        client_secret: Option<&'a str>,
+    ) -> Result<OAuthToken, AppError> {
+        if let Some(secret) = client_secret {
+            params.push(("client_secret", secret));
+        }
+        let response = self
+            .client
+            .post(endpoint)
+            .header("Accept", "application/json")
+            .form(&params)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::OAuth(format!(
+                "Token request failed ({status}): {body}"
+            )));
+        }
+        let resp: TokenExchangeResponse = response
+            .json()
+            .await
+            .map_err(|e| AppError::OAuth(format!("Failed to parse token response: {e}")))?;
+        Ok(build_token_from_response(resp, now_secs()))
+    }
+
+    fn open(&self, url: &str) -> Result<(), AppError> {
+        let opener = self.opener.read().unwrap();
+        let opener = opener
+            .as_ref()
+            .ok_or_else(|| AppError::OAuth("no browser available to sign in".into()))?;
+        opener(url).map_err(AppError::OAuth)
+    }
+}
+
+/// Serves the redirect URI until the browser lands on it once.
+struct CallbackListener {
+    params: tokio::sync::oneshot::Receiver<HashMap<String, String>>,
+    server: tokio::task::JoinHandle<()>,
+}
+
+impl CallbackListener {
+    fn start(listener: TcpListener) -> Self {
+        let (tx, params) = tokio::sync::oneshot::channel();
+        let tx = Arc::new(Mutex::new(Some(tx)));
+        let app = Router::new().route(
+            CALLBACK_PATH,
+            get(move |Query(query): Query<HashMap<String, String>>| {
+                if let Some(tx) = tx.lock().unwrap().take() {
+                    let _ = tx.send(query);
+                }
+                async {
+                    Html("<p>You're signed in. You can close this tab and return to Asyar.</p>")
+                }
+            }),
+        );
+        let server = tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+        Self { params, server }
+    }
+
+    async fn wait(mut self) -> Result<HashMap<String, String>, AppError> {
+        (&mut self.params)
+            .await
+            .map_err(|_| AppError::OAuth("sign-in callback closed".into()))
+    }
+}
+
+impl Drop for CallbackListener {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+fn authorization_url(
+    endpoint: &str,
+    client_id: &str,
+    code_challenge: &str,
+    state: &str,
+    scopes: &[String],
+    redirect_uri: &str,
+    resource: &str,
+) -> Result<String, AppError> {
+    let base = pkce::build_auth_url(
+        endpoint,
+        client_id,
+        code_challenge,
+        state,
+        scopes,
+        redirect_uri,
+    )?;
+    let mut url = url::Url::parse(&base)
+        .map_err(|_| AppError::Validation("Invalid authorization URL".into()))?;
+    // An empty `scope` lets the authorization server pick its default.
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, value)| !(key == "scope" && value.is_empty()))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair("resource", resource);
+    Ok(url.to_string())
+}
+
+/// RFC 8414 / RFC 9728 well-known locations for `url`: the path-suffixed
+/// form first, then the root.
+fn well_known_urls(url: &url::Url, name: &str) -> Vec<String> {
+    let origin = url.origin().ascii_serialization();
+    let path = url.path().trim_end_matches('/');
+    let mut urls = Vec::new();
+    if !path.is_empty() {
+        urls.push(format!("{origin}/.well-known/{name}{path}"));
+    }
+    urls.push(format!("{origin}/.well-known/{name}"));
+    urls
+}
+
+/// Reads one parameter of a `WWW-Authenticate: Bearer …` challenge.
+fn challenge_param(challenge: &str, name: &str) -> Option<String> {
+    let params = challenge.trim_start();
+    let params = params
+        .get(..6)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("bearer"))
+        .map_or(params, |_| &params[6..]);
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in params.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            ',' if !quoted => parts.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    parts.push(current);
+    parts.iter().find_map(|part| {
+        let (key, value) = part.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::transport::{HttpTransportFactory, Transport, TransportFactory};
+    use crate::mcp::types::{McpClientError, McpTransportSpec};
+    use axum::extract::State;
+    use axum::http::{header, HeaderMap, StatusCode};
+    use axum::response::{IntoResponse, Redirect, Response};
+    use axum::{Form, Json};
+    use serde_json::Value;
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct MemoryBackend(Mutex<HashMap<String, String>>);
+
+    impl SecretBackend for MemoryBackend {
+        fn read(&self, account: &str) -> Result<Option<String>, String> {
+            Ok(self.0.lock().unwrap().get(account).cloned())
+        }
+        fn write(&self, account: &str, secret: &str) -> Result<(), String> {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(account.to_string(), secret.to_string());
+            Ok(())
+        }
+        fn remove(&self, account: &str) -> Result<(), String> {
+            self.0.lock().unwrap().remove(account);
+            Ok(())
+        }
+    }
+
+    /// An MCP server that is also its own authorization server.
+    #[derive(Default)]
+    struct MockServer {
+        base: Mutex<String>,
+        challenge: Mutex<Option<String>>,
+        issued: AtomicUsize,
+        access: Mutex<Option<String>>,
+        refresh: Mutex<Option<String>>,
+        mcp_tokens: Mutex<Vec<Option<String>>>,
+        /// Issue tokens that are already inside the refresh margin.
+        short_lived: AtomicBool,
+    }
+
+    impl MockServer {
+        fn issue(&self) -> Value {
+            let n = self.issued.fetch_add(1, Ordering::SeqCst) + 1;
+            let access = format!("access-{n}");
+            let refresh = format!("refresh-{n}");
+            *self.access.lock().unwrap() = Some(access.clone());
+            *self.refresh.lock().unwrap() = Some(refresh.clone());
+            json!({
+                "access_token": access,
+                "refresh_token": refresh,
+                "token_type": "Bearer",
+                "expires_in": if self.short_lived.load(Ordering::SeqCst) { 30 } else { 3600 },
+            })
+        }
+
+        fn resource(&self) -> String {
+            format!("{}/mcp", self.base.lock().unwrap())
+        }
+    }
+
+    type Mock = State<Arc<MockServer>>;
+
+    async fn mcp(State(mock): Mock, headers: HeaderMap, body: String) -> Response {
+        let bearer = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .map(String::from);
+        mock.mcp_tokens.lock().unwrap().push(bearer.clone());
+        if bearer.is_none() || bearer != *mock.access.lock().unwrap() {
+            let challenge = format!(
+                r#"Bearer resource_metadata="{}/.well-known/oauth-protected-resource/mcp", scope="mcp:read mcp:write""#,
+                mock.base.lock().unwrap()
+            );
+            return (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, challenge)],
+            )
+                .into_response();
+        }
+        let request: Value = serde_json::from_str(&body).unwrap();
+        Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": {} })).into_response()
+    }
+
+    async fn protected_resource(State(mock): Mock) -> Json<Value> {
+        Json(json!({
+            "resource": mock.resource(),
+            "authorization_servers": [*mock.base.lock().unwrap()],
+        }))
+    }
+
+    async fn auth_server(State(mock): Mock) -> Json<Value> {
+        let base = mock.base.lock().unwrap().clone();
+        Json(json!({
+            "issuer": base,
+            "authorization_endpoint": format!("{base}/authorize"),
+            "token_endpoint": format!("{base}/token"),
+            "registration_endpoint": format!("{base}/register"),
+            "code_challenge_methods_supported": ["S256"],
+        }))
+    }
+
+    async fn register(Json(body): Json<Value>) -> Json<Value> {
+        assert_eq!(body["token_endpoint_auth_method"], "none");
+        Json(json!({ "client_id": "client-1" }))
+    }
+
+    async fn authorize(
+        State(mock): Mock,
+        Query(query): Query<HashMap<String, String>>,
+    ) -> Redirect {
+        assert_eq!(query["client_id"], "client-1");
+        assert_eq!(query["code_challenge_method"], "S256");
+        assert_eq!(query["scope"], "mcp:read mcp:write");
+        assert_eq!(query["resource"], mock.resource());
+        *mock.challenge.lock().unwrap() = Some(query["code_challenge"].clone());
+        Redirect::to(&format!(
+            "{}?code=code-1&state={}",
+            query["redirect_uri"], query["state"]
+        ))
+    }
+
+    async fn token(State(mock): Mock, Form(form): Form<HashMap<String, String>>) -> Response {
+        assert_eq!(form["resource"], mock.resource());
+        let accepted = match form["grant_type"].as_str() {
+            "authorization_code" => {
+                let challenge = mock.challenge.lock().unwrap().clone();
+                form["code"] == "code-1"
+                    && challenge == Some(pkce::generate_code_challenge(&form["code_verifier"]))
+            }
+            "refresh_token" => {
+                Some(&form["refresh_token"]) == mock.refresh.lock().unwrap().as_ref()
+            }
+            _ => false,
+        };
+        if !accepted {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "invalid_grant" })),
+            )
+                .into_response();
+        }
+        Json(mock.issue()).into_response()
+    }
+
+    async fn start_mock() -> Arc<MockServer> {
+        let mock = Arc::new(MockServer::default());
+        let app = Router::new()
+            .route("/mcp", axum::routing::post(mcp))
+            .route(
+                "/.well-known/oauth-protected-resource/mcp",
+                get(protected_resource),
+            )
+            .route("/.well-known/oauth-authorization-server", get(auth_server))
+            .route("/register", axum::routing::post(register))
+            .route("/authorize", get(authorize))
+            .route("/token", axum::routing::post(token))
+            .with_state(Arc::clone(&mock));
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        *mock.base.lock().unwrap() = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+        mock
+    }
+
+    /// Plays the browser: follows the authorization redirect back to the
+    /// loopback callback.
+    fn authorizer(opened: Arc<AtomicUsize>) -> (Arc<McpAuthorizer>, Arc<MemoryBackend>) {
+        let secrets = Arc::new(MemoryBackend::default());
+        let auth = Arc::new(McpAuthorizer::new(secrets.clone()));
+        auth.set_opener(Box::new(move |url| {
+            opened.fetch_add(1, Ordering::SeqCst);
+            let url = url.to_string();
+            tokio::spawn(async move {
+                let _ = reqwest::get(url).await;
+            });
+            Ok(())
+        }));
+        (auth, secrets)
+    }
+
+    async fn call(
+        auth: &Arc<McpAuthorizer>,
+        url: &str,
+        headers: BTreeMap<String, String>,
+    ) -> Result<Option<String>, McpClientError> {
+        let factory = HttpTransportFactory::new().with_authorizer(Arc::clone(auth));
+        let spec = McpTransportSpec::Http {
+            url: url.to_string(),
+            headers,
+        };
+        let mut transport = factory.connect(&spec).await?;
+        transport
+            .send(r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#)
+            .await?;
+        transport.recv().await
+    }
+
+    // 1. a_401_signs_in_through_the_browser_and_the_token_is_reused
+    #[tokio::test]
+    async fn a_401_signs_in_through_the_browser_and_the_token_is_reused() {
+        let mock = start_mock().await;
+        let opened = Arc::new(AtomicUsize::new(0));
+        let (auth, secrets) = authorizer(opened.clone());
+
+        let reply = call(&auth, &mock.resource(), BTreeMap::new())
+            .await
+            .unwrap();
+        assert!(reply.unwrap().contains("\"result\""));
+        assert_eq!(opened.load(Ordering::SeqCst), 1);
+
+        let stored: Credentials =
+            serde_json::from_str(&secrets.read(&mock.resource()).unwrap().unwrap()).unwrap();
+        assert_eq!(stored.client_id, "client-1");
+        assert_eq!(stored.token.access_token, "access-1");
+
+        call(&auth, &mock.resource(), BTreeMap::new())
+            .await
+            .unwrap();
+        assert_eq!(opened.load(Ordering::SeqCst), 1, "no second sign-in");
+        assert_eq!(
+            mock.mcp_tokens.lock().unwrap().last().unwrap().as_deref(),
+            Some("access-1")
+        );
+    }
+
+    // 2. a_rejected_token_is_refreshed_and_the_rotated_refresh_token_kept
+    #[tokio::test]
+    async fn a_rejected_token_is_refreshed_and_the_rotated_refresh_token_kept() {
+        let mock = start_mock().await;
+        let opened = Arc::new(AtomicUsize::new(0));
+        let (auth, secrets) = authorizer(opened.clone());
+        call(&auth, &mock.resource(), BTreeMap::new())
+            .await
+            .unwrap();
+
+        // The server revokes the access token but keeps the refresh token.
+        *mock.access.lock().unwrap() = Some("revoked".into());
+        call(&auth, &mock.resource(), BTreeMap::new())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            opened.load(Ordering::SeqCst),
+            1,
+            "refreshed, not re-signed-in"
+        );
+        let stored: Credentials =
+            serde_json::from_str(&secrets.read(&mock.resource()).unwrap().unwrap()).unwrap();
+        assert_eq!(stored.token.access_token, "access-2");
+        assert_eq!(stored.token.refresh_token.as_deref(), Some("refresh-2"));
+    }
+
+    // 3. an_expiring_token_is_refreshed_before_it_is_sent
+    #[tokio::test]
+    async fn an_expiring_token_is_refreshed_before_it_is_sent() {
+        let mock = start_mock().await;
+        let opened = Arc::new(AtomicUsize::new(0));
+        let (auth, _) = authorizer(opened.clone());
+        mock.short_lived.store(true, Ordering::SeqCst);
+        call(&auth, &mock.resource(), BTreeMap::new())
+            .await
+            .unwrap();
+
+        call(&auth, &mock.resource(), BTreeMap::new())
+            .await
+            .unwrap();
+        let sent = mock.mcp_tokens.lock().unwrap().clone();
+        assert_eq!(sent.last().unwrap().as_deref(), Some("access-2"));
+        assert_eq!(sent.len(), 3, "no 401 round trip for the expiring token");
+    }
+
+    // 4. a_failed_sign_in_is_not_retried_during_the_cooldown
+    #[tokio::test]
+    async fn a_failed_sign_in_is_not_retried_during_the_cooldown() {
+        let mock = start_mock().await;
+        let opened = Arc::new(AtomicUsize::new(0));
+        let auth = Arc::new(McpAuthorizer::new(Arc::new(MemoryBackend::default())));
+        let counter = opened.clone();
+        auth.set_opener(Box::new(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Err("no browser".into())
+        }));
+
+        assert!(call(&auth, &mock.resource(), BTreeMap::new())
+            .await
+            .is_err());
+        assert!(call(&auth, &mock.resource(), BTreeMap::new())
+            .await
+            .is_err());
+        assert_eq!(opened.load(Ordering::SeqCst), 1);
+
+        auth.allow_sign_in(&mock.resource());
+        assert!(call(&auth, &mock.resource(), BTreeMap::new())
+            .await
+            .is_err());
+        assert_eq!(opened.load(Ordering::SeqCst), 2);
+    }
+
+    // 5. a_configured_authorization_header_bypasses_oauth
+    #[tokio::test]
+    async fn a_configured_authorization_header_bypasses_oauth() {
+        let mock = start_mock().await;
+        let opened = Arc::new(AtomicUsize::new(0));
+        let (auth, _) = authorizer(opened.clone());
+        let headers = BTreeMap::from([("Authorization".to_string(), "Bearer static".to_string())]);
+
+        let result = call(&auth, &mock.resource(), headers).await;
+        assert!(matches!(result, Err(McpClientError::Transport(_))));
+        assert_eq!(opened.load(Ordering::SeqCst), 0);
+    }
+
+    // 6. challenge_params_are_parsed
+    #[test]
+    fn challenge_params_are_parsed() {
+        let challenge = r#"Bearer error="invalid_token", resource_metadata="https://x.example/.well-known/oauth-protected-resource", scope="a b""#;
+        assert_eq!(
+            challenge_param(challenge, "resource_metadata").as_deref(),
+            Some("https://x.example/.well-known/oauth-protected-resource")
+        );
+        assert_eq!(challenge_param(challenge, "scope").as_deref(), Some("a b"));
+        assert_eq!(challenge_param("Bearer", "scope"), None);
+        assert_eq!(
+            challenge_param("bearer realm=x, scope=solo", "scope").as_deref(),
+            Some("solo")
+        );
+    }
+
+    // 7. well_known_urls_try_the_path_suffixed_form_first
+    #[test]
+    fn well_known_urls_try_the_path_suffixed_form_first() {
+        let url = url::Url::parse("https://api.example.com/v1/mcp").unwrap();
+        assert_eq!(
+            well_known_urls(&url, "oauth-protected-resource"),
+            vec![
+                "https://api.example.com/.well-known/oauth-protected-resource/v1/mcp",
+                "https://api.example.com/.well-known/oauth-protected-resource",
+            ]
+        );
+        let root = url::Url::parse("https://auth.example.com/").unwrap();
+        assert_eq!(
+            well_known_urls(&root, "oauth-authorization-server"),
+            vec!["https://auth.example.com/.well-known/oauth-authorization-server"]
+        );
+    }
+}
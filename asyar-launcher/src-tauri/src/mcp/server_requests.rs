@@ -0,0 +1,847 @@
+//! Requests MCP servers send to the launcher.
+//!
+//! A connected server may ask the host to run a completion on its behalf
+//! (`sampling/createMessage`) or to collect structured input from the user
+//! (`elicitation/create`). Both are answered here, through the frontend:
+//!
+//! - Sampling is gated by a per-server policy stored in `mcp_permissions`
+//!   under the pseudo-tool [`sampling_permission_id`], so it shows up (and is
+//!   revoked) next to the server's tool permissions. Without a standing
+//!   decision the user is asked; the frontend answers with the provider and
+//!   model of the default agent, and the completion runs here.
+//! - Elicitation accepts flat forms of primitive fields only, the subset the
+//!   spec allows. The frontend renders the form and reports accept, decline
+//!   or cancel; accepted content is checked against the schema first.
+
+use crate::agents::runner::{resolve_provider_config, McpPermissionChoice};
+use crate::ai::commands::{send_chat_request, stream_chat_response};
+use crate::ai::types::{
+    ChatMessage, ChatParams, ChatStreamEventPayload, ContentPart, ImageSource, ProviderConfig,
+};
+use crate::error::AppError;
+use crate::mcp::client::ServerRequestHandler;
+use crate::mcp::host::HostEmitFn;
+use crate::mcp::types::McpClientError;
+use crate::storage::mcp_permissions::{self, McpPermissionRow, PermissionDecision};
+use crate::storage::DataStore;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// Emitted when a server asks for a completion; answered through
+/// `mcp_resolve_sampling`.
+pub const SAMPLING_REQUEST_EVENT: &str = "asyar:mcp:sampling-request";
+
+/// Emitted when a server asks the user for input; answered through
+/// `mcp_resolve_elicitation`.
+pub const ELICITATION_REQUEST_EVENT: &str = "asyar:mcp:elicitation-request";
+
+/// The `tool_id` a server's sampling policy is stored under. Tool names are
+/// free-form, so a tool of the same name is dropped when the server's tools
+/// are registered (see `tool_adapter::descriptors_from_mcp_tools`) rather
+/// than letting it share the policy.
+pub fn sampling_permission_id(server_id: &str) -> String {
+    format!("mcp:{server_id}#sampling")
+}
+
+/// How long a sampling request waits for the user before counting as
+/// cancelled.
+const SAMPLING_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Forms take longer to fill in than a yes/no.
+const ELICITATION_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Used when a server leaves out the (required) `maxTokens`.
+const DEFAULT_SAMPLING_MAX_TOKENS: u32 = 1024;
+
+/// The code the spec suggests for requests the user turned down.
+const USER_REJECTED: i64 = -1;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+/// One message of a sampling request, flattened for the approval dialog.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SamplingPreviewMessage {
+    pub role: String,
+    pub text: String,
+}
+
+/// Payload of [`SAMPLING_REQUEST_EVENT`]. With `needs_approval` unset the
+/// server is already allowed: the frontend answers with an allow and the
+/// route, without asking.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SamplingRequest {
+    pub id: String,
+    pub server_id: String,
+    pub server_name: String,
+    pub system_prompt: Option<String>,
+    pub messages: Vec<SamplingPreviewMessage>,
+    pub max_tokens: u32,
+    pub needs_approval: bool,
+}
+
+/// The provider and model a sampling request runs on.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SamplingRoute {
+    pub provider_id: String,
+    pub model_id: String,
+    pub config: ProviderConfig,
+    /// The user's default; a temperature the server asks for wins.
+    pub temperature: f64,
+}
+
+/// The frontend's answer to a [`SamplingRequest`]. `route` is `None` when
+/// no provider is configured.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SamplingAnswer {
+    pub choice: McpPermissionChoice,
+    pub route: Option<SamplingRoute>,
+}
+
+/// Payload of [`ELICITATION_REQUEST_EVENT`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitationRequest {
+    pub id: String,
+    pub server_id: String,
+    pub server_name: String,
+    pub message: String,
+    pub requested_schema: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ElicitationAction {
+    Accept,
+    Decline,
+    Cancel,
+}
+
+struct PendingElicitation {
+    schema: Value,
+    tx: oneshot::Sender<Value>,
+}
+
+/// A parsed `sampling/createMessage`.
+struct SamplingParams {
+    messages: Vec<ChatMessage>,
+    preview: Vec<SamplingPreviewMessage>,
+    system_prompt: Option<String>,
+    max_tokens: u32,
+    temperature: Option<f64>,
+}
+
+pub struct McpServerRequests {
+    data_store: RwLock<Option<DataStore>>,
+    emit: Mutex<Option<HostEmitFn>>,
+    pending_sampling: Mutex<HashMap<String, oneshot::Sender<SamplingAnswer>>>,
+    pending_elicitation: Mutex<HashMap<String, PendingElicitation>>,
+}
+
+impl Default for McpServerRequests {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl McpServerRequests {
+    pub fn new() -> Self {
+        Self {
+            data_store: RwLock::new(None),
+            emit: Mutex::new(None),
+            pending_sampling: Mutex::new(HashMap::new()),
+            pending_elicitation: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wires in the store and the frontend once the app is set up. Until
+    /// then sampling is refused and elicitation is cancelled.
+    pub fn attach(&self, data_store: DataStore, emit: HostEmitFn) {
+        if let Ok(mut g) = self.data_store.write() {
+            *g = Some(data_store);
+        }
+        if let Ok(mut g) = self.emit.lock() {
+            *g = Some(emit);
+        }
+    }
+
+    /// The handler one server's client answers its requests with.
+    pub fn for_server(
+        self: &Arc<Self>,
+        server_id: &str,
+        server_name: &str,
+    ) -> Arc<dyn ServerRequestHandler> {
+        Arc::new(ServerRequests {
+            requests: Arc::clone(self),
+            server_id: server_id.to_string(),
+            server_name: server_name.to_string(),
+        })
+    }
+
+    async fn sample(
+        &self,
+        server_id: &str,
+        server_name: &str,
+        params: &Value,
+    ) -> Result<Value, McpClientError> {
+        let request = parse_sampling(params)?;
+        let standing = self.sampling_policy(server_id).map_err(internal)?;
+        if standing == Some(PermissionDecision::Never) {
+            return Err(rejected("sampling is turned off for this server"));
+        }
+
+        let needs_approval = standing.is_none();
+        let answer = self
+            .request_sampling(SamplingRequest {
+                id: uuid::Uuid::new_v4().to_string(),
+                server_id: server_id.to_string(),
+                server_name: server_name.to_string(),
+                system_prompt: request.system_prompt.clone(),
+                messages: request.preview.clone(),
+                max_tokens: request.max_tokens,
+                needs_approval,
+            })
+            .await
+            .map_err(internal)?;
+        if needs_approval {
+            self.remember_sampling_choice(server_id, answer.choice)
+                .map_err(internal)?;
+        }
+        if !matches!(
+            answer.choice,
+            McpPermissionChoice::AllowOnce | McpPermissionChoice::AllowAlways
+        ) {
+            return Err(rejected("the user declined the sampling request"));
+        }
+        let Some(route) = answer.route else {
+            return Err(McpClientError::Rpc {
+                code: INTERNAL_ERROR,
+                message: "no AI provider is configured".to_string(),
+            });
+        };
+
+        let text = complete(&route, request).await.map_err(internal)?;
+        Ok(json!({
+            "role": "assistant",
+            "content": { "type": "text", "text": text },
+            "model": route.model_id,
+            "stopReason": "endTurn",
+        }))
+    }
+
+    /// A standing allow-once is used up, like a tool permission.
+    fn sampling_policy(&self, server_id: &str) -> Result<Option<PermissionDecision>, AppError> {
+        let guard = self.data_store.read().map_err(|_| AppError::Lock)?;
+        let Some(data_store) = guard.as_ref() else {
+            return Ok(None);
+        };
+        let conn = data_store.conn()?;
+        mcp_permissions::consume_allow_once(
+            &conn,
+            server_id,
+            &sampling_permission_id(server_id),
+            "",
+        )
+    }
+
+    fn remember_sampling_choice(
+        &self,
+        server_id: &str,
+        choice: McpPermissionChoice,
+    ) -> Result<(), AppError> {
+        let decision = match choice {
+            McpPermissionChoice::AllowAlways => PermissionDecision::AllowAlways,
+            McpPermissionChoice::Never => PermissionDecision::Never,
+            McpPermissionChoice::AllowOnce | McpPermissionChoice::Cancel => return Ok(()),
+        };
+        let guard = self.data_store.read().map_err(|_| AppError::Lock)?;
+        let Some(data_store) = guard.as_ref() else {
+            return Ok(());
+        };
+        let conn = data_store.conn()?;
+        mcp_permissions::set_permission(
+            &conn,
+            &McpPermissionRow {
+                server_id: server_id.to_string(),
+                tool_id: sampling_permission_id(server_id),
+                agent_id: String::new(),
+                decision,
+                set_at: chrono::Utc::now().timestamp_millis(),
+            },
+        )
+    }
+
+    /// No answer within [`SAMPLING_TIMEOUT`] is a cancel.
+    async fn request_sampling(&self, request: SamplingRequest) -> Result<SamplingAnswer, AppError> {
+        let cancelled = SamplingAnswer {
+            choice: McpPermissionChoice::Cancel,
+            route: None,
+        };
+        let (tx, rx) = oneshot::channel();
+        self.pending_sampling
+            .lock()
+            .map_err(|_| AppError::Lock)?
+            .insert(request.id.clone(), tx);
+        let answer = if self.emit_event(SAMPLING_REQUEST_EVENT, &request) {
+            match tokio::time::timeout(SAMPLING_TIMEOUT, rx).await {
+                Ok(Ok(answer)) => answer,
+                _ => cancelled,
+            }
+        } else {
+            cancelled
+        };
+        if let Ok(mut pending) = self.pending_sampling.lock() {
+            pending.remove(&request.id);
+        }
+        Ok(answer)
+    }
+
+    /// Delivers the user's answer. `false` when nothing is waiting on `id`
+    /// (already answered, or timed out).
+    pub fn resolve_sampling(&self, id: &str, answer: SamplingAnswer) -> Result<bool, AppError> {
+        let waiting = self
+            .pending_sampling
+            .lock()
+            .map_err(|_| AppError::Lock)?
+            .remove(id);
+        Ok(waiting.is_some_and(|tx| tx.send(answer).is_ok()))
+    }
+
+    async fn elicit(
+        &self,
+        server_id: &str,
+        server_name: &str,
+        params: &Value,
+    ) -> Result<Value, McpClientError> {
+        let message = params["message"]
+            .as_str()
+            .ok_or_else(|| invalid_params("elicitation/create needs a message"))?;
+        let schema = params["requestedSchema"].clone();
+        check_form_schema(&schema).map_err(invalid_params)?;
+
+        let request = ElicitationRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            server_id: server_id.to_string(),
+            server_name: server_name.to_string(),
+            message: message.to_string(),
+            requested_schema: schema.clone(),
+        };
+        let (tx, rx) = oneshot::channel();
+        self.pending_elicitation
+            .lock()
+            .map_err(|_| internal(AppError::Lock))?
+            .insert(request.id.clone(), PendingElicitation { schema, tx });
+        let result = if self.emit_event(ELICITATION_REQUEST_EVENT, &request) {
+            match tokio::time::timeout(ELICITATION_TIMEOUT, rx).await {
+                Ok(Ok(result)) => result,
+                _ => json!({ "action": "cancel" }),
+            }
+        } else {
+            json!({ "action": "cancel" })
+        };
+        if let Ok(mut pending) = self.pending_elicitation.lock() {
+            pending.remove(&request.id);
+        }
+        Ok(result)
+    }
+
+    /// Delivers the user's answer to a form. Accepted content that fails the
+    /// requested schema is a `Validation` error and leaves the request
+    /// waiting, so the form can show what to fix. `false` when nothing is
+    /// waiting on `id`.
+    pub fn resolve_elicitation(
+        &self,
+        id: &str,
+        action: ElicitationAction,
+        content: Option<Value>,
+    ) -> Result<bool, AppError> {
+        let mut pending = self
+            .pending_elicitation
+            .lock()
+            .map_err(|_| AppError::Lock)?;
+        let result = match action {
+            ElicitationAction::Accept => {
+                let Some(waiting) = pending.get(id) else {
+                    return Ok(false);
+                };
+                let content = content.unwrap_or_else(|| json!({}));
+                let errors = crate::ai::structured::validate(&waiting.schema, &content);
+                if !errors.is_empty() {
+                    return Err(AppError::Validation(errors.join("; ")));
+                }
+                json!({ "action": "accept", "content": content })
+            }
+            ElicitationAction::Decline => json!({ "action": "decline" }),
+            ElicitationAction::Cancel => json!({ "action": "cancel" }),
+        };
+        Ok(pending
+            .remove(id)
+            .is_some_and(|waiting| waiting.tx.send(result).is_ok()))
+    }
+
+    fn emit_event<T: Serialize>(&self, event: &'static str, payload: &T) -> bool {
+        let Ok(payload) = serde_json::to_value(payload) else {
+            return false;
+        };
+        match self.emit.lock() {
+            Ok(g) => match g.as_ref() {
+                Some(emit) => {
+                    emit(event, payload);
+                    true
+                }
+                None => false,
+            },
+            Err(_) => false,
+        }
+    }
+}
+
+/// [`McpServerRequests`] bound to one server.
+struct ServerRequests {
+    requests: Arc<McpServerRequests>,
+    server_id: String,
+    server_name: String,
+}
+
+#[async_trait::async_trait]
+impl ServerRequestHandler for ServerRequests {
+    async fn handle(&self, method: &str, params: Value) -> Result<Value, McpClientError> {
+        match method {
+            "sampling/createMessage" => {
+                self.requests
+                    .sample(&self.server_id, &self.server_name, &params)
+                    .await
+            }
+            "elicitation/create" => {
+                self.requests
+                    .elicit(&self.server_id, &self.server_name, &params)
+                    .await
+            }
+            other => Err(McpClientError::Rpc {
+                code: METHOD_NOT_FOUND,
+                message: format!("method not supported: {other}"),
+            }),
+        }
+    }
+}
+
+fn rejected(message: &str) -> McpClientError {
+    McpClientError::Rpc {
+        code: USER_REJECTED,
+        message: message.to_string(),
+    }
+}
+
+fn invalid_params(message: impl Into<String>) -> McpClientError {
+    McpClientError::Rpc {
+        code: INVALID_PARAMS,
+        message: message.into(),
+    }
+}
+
+fn internal(e: AppError) -> McpClientError {
+    McpClientError::Rpc {
+        code: INTERNAL_ERROR,
+        message: e.to_string(),
+    }
+}
+
+/// Text and images become chat messages; audio has nowhere to go.
+fn parse_sampling(params: &Value) -> Result<SamplingParams, McpClientError> {
+    let raw = params["messages"]
+        .as_array()
+        .filter(|messages| !messages.is_empty())
+        .ok_or_else(|| invalid_params("sampling/createMessage needs messages"))?;
+    let mut messages = Vec::with_capacity(raw.len());
+    let mut preview = Vec::with_capacity(raw.len());
+    for message in raw {
+        let role = match message["role"].as_str() {
+            Some(role @ ("user" | "assistant")) => role,
+            _ => return Err(invalid_params("message role must be user or assistant")),
+        };
+        // A single content block, or (newer servers) a list of them.
+        let blocks: Vec<&Value> = match &message["content"] {
+            Value::Array(blocks) => blocks.iter().collect(),
+            block => vec![block],
+        };
+        let mut text = Vec::new();
+        let mut parts = Vec::new();
+        let mut shown = Vec::new();
+        for block in blocks {
+            match block["type"].as_str() {
+                Some("text") => {
+                    let chunk = block["text"].as_str().unwrap_or_default();
+                    text.push(chunk.to_string());
+                    shown.push(chunk.to_string());
+                }
+                Some("image") if role == "user" => {
+                    let (Some(data), Some(media_type)) =
+                        (block["data"].as_str(), block["mimeType"].as_str())
+                    else {
+                        return Err(invalid_params("image content needs data and mimeType"));
+                    };
+                    parts.push(ContentPart::Image {
+                        media_type: media_type.to_string(),
+                        source: ImageSource::Bytes {
+                            data: data.to_string(),
+                        },
+                    });
+                    shown.push(format!("[{media_type} image]"));
+                }
+                other => {
+                    return Err(invalid_params(format!(
+                        "unsupported {role} content: {}",
+                        other.unwrap_or("untyped")
+                    )))
+                }
+            }
+        }
+        messages.push(ChatMessage {
+            id: uuid::Uuid::new_v4().to_string(),
+            role: role.to_string(),
+            content: text.join("\n"),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            tool_calls: None,
+            tool_call_id: None,
+            provider_context: None,
+            parts: (!parts.is_empty()).then_some(parts),
+        });
+        preview.push(SamplingPreviewMessage {
+            role: role.to_string(),
+            text: shown.join("\n"),
+        });
+    }
+    Ok(SamplingParams {
+        messages,
+        preview,
+        system_prompt: params["systemPrompt"].as_str().map(str::to_string),
+        max_tokens: params["maxTokens"]
+            .as_u64()
+            .and_then(|max| u32::try_from(max).ok())
+            .unwrap_or(DEFAULT_SAMPLING_MAX_TOKENS),
+        temperature: params["temperature"].as_f64(),
+    })
+}
+
+/// Elicitation forms are flat: an object whose properties are strings
+/// (optionally an `enum`), numbers, integers or booleans.
+fn check_form_schema(schema: &Value) -> Result<(), String> {
+    if schema["type"] != "object" {
+        return Err("requestedSchema must be an object schema".to_string());
+    }
+    let properties = schema["properties"]
+        .as_object()
+        .ok_or_else(|| "requestedSchema needs properties".to_string())?;
+    for (name, property) in properties {
+        match property["type"].as_str() {
+            Some("string" | "number" | "integer" | "boolean") => {}
+            _ => return Err(format!("field '{name}' is not a primitive type")),
+        }
+    }
+    Ok(())
+}
+
+/// One-shot, non-streamed completion, collected the way the runner's
+/// summaries are.
+async fn complete(route: &SamplingRoute, request: SamplingParams) -> Result<String, AppError> {
+    let configs = HashMap::from([(route.provider_id.clone(), route.config.clone())]);
+    let config = ProviderConfig {
+        hosted_web_search: Some(false),
+        ..resolve_provider_config(&route.provider_id, &configs)?.clone()
+    };
+    let params = ChatParams {
+        model_id: route.model_id.clone(),
+        temperature: request.temperature.unwrap_or(route.temperature),
+        max_tokens: request.max_tokens,
+        system_prompt: request.system_prompt,
+        tools: None,
+        response_schema: None,
+    };
+    let spec = crate::ai::providers::build_request(
+        &route.provider_id,
+        &config,
+        &request.messages,
+        &params,
+    )?;
+    let response = send_chat_request(spec, false)
+        .await
+        .map_err(|failure| AppError::Network(failure.message))?;
+    let output = Arc::new(Mutex::new(String::new()));
+    let collected = Arc::clone(&output);
+    stream_chat_response(response, &route.provider_id, &config, move |event| {
+        if let (ChatStreamEventPayload::Token { token }, Ok(mut text)) = (event, collected.lock()) {
+            text.push_str(&token);
+        }
+    })
+    .await?;
+    let text = std::mem::take(&mut *output.lock().map_err(|_| AppError::Lock)?);
+    Ok(text.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_requests() -> (Arc<McpServerRequests>, DataStore) {
+        let requests = Arc::new(McpServerRequests::new());
+        let data_store = crate::storage::create_test_store();
+        (requests, data_store)
+    }
+
+    /// Answers every event through `answer` and records the payloads.
+    fn answer_events<F>(
+        requests: &Arc<McpServerRequests>,
+        data_store: &DataStore,
+        answer: F,
+    ) -> Arc<Mutex<Vec<Value>>>
+    where
+        F: Fn(&McpServerRequests, &'static str, &Value) + Send + Sync + 'static,
+    {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&seen);
+        let answering = Arc::clone(requests);
+        requests.attach(
+            data_store.clone(),
+            Box::new(move |event, payload| {
+                recorder.lock().unwrap().push(payload.clone());
+                answer(&answering, event, &payload);
+            }),
+        );
+        seen
+    }
+
+    fn sampling_params() -> Value {
+        json!({
+            "messages": [
+                { "role": "user", "content": { "type": "text", "text": "Summarise this" } },
+            ],
+            "systemPrompt": "Be brief.",
+            "maxTokens": 64,
+        })
+    }
+
+    fn form_params() -> Value {
+        json!({
+            "message": "Who should the ticket go to?",
+            "requestedSchema": {
+                "type": "object",
+                "properties": {
+                    "assignee": { "type": "string" },
+                    "priority": { "type": "string", "enum": ["low", "high"] },
+                    "notify": { "type": "boolean" },
+                },
+                "required": ["assignee"],
+            },
+        })
+    }
+
+    fn policy(data_store: &DataStore, server_id: &str) -> Option<PermissionDecision> {
+        let conn = data_store.conn().unwrap();
+        mcp_permissions::get_permission(&conn, server_id, &sampling_permission_id(server_id), "")
+            .unwrap()
+            .map(|row| row.decision)
+    }
+
+    fn rpc_code(result: Result<Value, McpClientError>) -> i64 {
+        match result {
+            Err(McpClientError::Rpc { code, .. }) => code,
+            other => panic!("expected an rpc error, got {other:?}"),
+        }
+    }
+
+    // 1. sampling_prompts_with_a_preview_and_remembers_never
+    #[tokio::test]
+    async fn sampling_prompts_with_a_preview_and_remembers_never() {
+        let (requests, data_store) = test_requests();
+        let seen = answer_events(&requests, &data_store, |requests, event, payload| {
+            assert_eq!(event, SAMPLING_REQUEST_EVENT);
+            let answer = SamplingAnswer {
+                choice: McpPermissionChoice::Never,
+                route: None,
+            };
+            let id = payload["id"].as_str().unwrap();
+            requests.resolve_sampling(id, answer).unwrap();
+        });
+        let handler = requests.for_server("github", "GitHub");
+
+        let result = handler
+            .handle("sampling/createMessage", sampling_params())
+            .await;
+        assert_eq!(rpc_code(result), USER_REJECTED);
+        assert_eq!(
+            policy(&data_store, "github"),
+            Some(PermissionDecision::Never)
+        );
+        let payload = seen.lock().unwrap()[0].clone();
+        assert_eq!(payload["serverName"], "GitHub");
+        assert_eq!(payload["systemPrompt"], "Be brief.");
+        assert_eq!(payload["messages"][0]["text"], "Summarise this");
+        assert_eq!(payload["maxTokens"], 64);
+        assert_eq!(payload["needsApproval"], true);
+
+        // Never sticks: the user isn't asked again.
+        let result = handler
+            .handle("sampling/createMessage", sampling_params())
+            .await;
+        assert_eq!(rpc_code(result), USER_REJECTED);
+        assert_eq!(seen.lock().unwrap().len(), 1);
+    }
+
+    // 2. allowed_sampling_skips_approval_and_validates_the_route
+    #[tokio::test]
+    async fn allowed_sampling_skips_approval_and_validates_the_route() {
+        let (requests, data_store) = test_requests();
+        {
+            let conn = data_store.conn().unwrap();
+            mcp_permissions::set_permission(
+                &conn,
+                &McpPermissionRow {
+                    server_id: "github".to_string(),
+                    tool_id: sampling_permission_id("github"),
+                    agent_id: String::new(),
+                    decision: PermissionDecision::AllowAlways,
+                    set_at: 0,
+                },
+            )
+            .unwrap();
+        }
+        let seen = answer_events(&requests, &data_store, |requests, _, payload| {
+            let config: ProviderConfig = serde_json::from_value(json!({
+                "enabled": false,
+                "apiKey": "sk-test",
+            }))
+            .unwrap();
+            let answer = SamplingAnswer {
+                choice: McpPermissionChoice::AllowOnce,
+                route: Some(SamplingRoute {
+                    provider_id: "openai".to_string(),
+                    model_id: "gpt-test".to_string(),
+                    config,
+                    temperature: 0.7,
+                }),
+            };
+            let id = payload["id"].as_str().unwrap();
+            requests.resolve_sampling(id, answer).unwrap();
+        });
+
+        let result = requests
+            .for_server("github", "GitHub")
+            .handle("sampling/createMessage", sampling_params())
+            .await;
+        match result {
+            Err(McpClientError::Rpc { code, message }) => {
+                assert_eq!(code, INTERNAL_ERROR);
+                assert!(message.contains("disabled"), "{message}");
+            }
+            other => panic!("expected the disabled provider to fail, got {other:?}"),
+        }
+        assert_eq!(seen.lock().unwrap()[0]["needsApproval"], false);
+    }
+
+    // 3. sampling_without_a_frontend_is_cancelled_and_bad_content_is_refused
+    #[tokio::test]
+    async fn sampling_without_a_frontend_is_cancelled_and_bad_content_is_refused() {
+        let requests = Arc::new(McpServerRequests::new());
+        let handler = requests.for_server("github", "GitHub");
+        let result = handler
+            .handle("sampling/createMessage", sampling_params())
+            .await;
+        assert_eq!(rpc_code(result), USER_REJECTED);
+
+        let audio = json!({
+            "messages": [{ "role": "user", "content": { "type": "audio", "data": "", "mimeType": "audio/wav" } }],
+            "maxTokens": 10,
+        });
+        let result = handler.handle("sampling/createMessage", audio).await;
+        assert_eq!(rpc_code(result), INVALID_PARAMS);
+    }
+
+    // 4. elicitation_accept_checks_content_against_the_schema
+    #[tokio::test]
+    async fn elicitation_accept_checks_content_against_the_schema() {
+        let (requests, data_store) = test_requests();
+        answer_events(&requests, &data_store, |requests, event, payload| {
+            assert_eq!(event, ELICITATION_REQUEST_EVENT);
+            assert_eq!(payload["message"], "Who should the ticket go to?");
+            let id = payload["id"].as_str().unwrap();
+            let invalid = requests.resolve_elicitation(
+                id,
+                ElicitationAction::Accept,
+                Some(json!({ "priority": "urgent" })),
+            );
+            assert!(matches!(invalid, Err(AppError::Validation(_))));
+            let accepted = requests.resolve_elicitation(
+                id,
+                ElicitationAction::Accept,
+                Some(json!({ "assignee": "sam", "notify": true })),
+            );
+            assert!(accepted.unwrap());
+        });
+
+        let result = requests
+            .for_server("jira", "Jira")
+            .handle("elicitation/create", form_params())
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            json!({ "action": "accept", "content": { "assignee": "sam", "notify": true } })
+        );
+    }
+
+    // 5. elicitation_decline_and_cancel_carry_no_content
+    #[tokio::test]
+    async fn elicitation_decline_and_cancel_carry_no_content() {
+        for (action, expected) in [
+            (ElicitationAction::Decline, "decline"),
+            (ElicitationAction::Cancel, "cancel"),
+        ] {
+            let (requests, data_store) = test_requests();
+            answer_events(&requests, &data_store, move |requests, _, payload| {
+                let id = payload["id"].as_str().unwrap();
+                requests.resolve_elicitation(id, action, None).unwrap();
+            });
+            let result = requests
+                .for_server("jira", "Jira")
+                .handle("elicitation/create", form_params())
+                .await
+                .unwrap();
+            assert_eq!(result, json!({ "action": expected }));
+        }
+
+        // No frontend to show the form: cancelled.
+        let requests = Arc::new(McpServerRequests::new());
+        let result = requests
+            .for_server("jira", "Jira")
+            .handle("elicitation/create", form_params())
+            .await
+            .unwrap();
+        assert_eq!(result, json!({ "action": "cancel" }));
+    }
+
+    // 6. elicitation_rejects_nested_schemas
+    #[tokio::test]
+    async fn elicitation_rejects_nested_schemas() {
+        let requests = Arc::new(McpServerRequests::new());
+        let nested = json!({
+            "message": "Address?",
+            "requestedSchema": {
+                "type": "object",
+                "properties": { "address": { "type": "object" } },
+            },
+        });
+        let result = requests
+            .for_server("jira", "Jira")
+            .handle("elicitation/create", nested)
+            .await;
+        assert_eq!(rpc_code(result), INVALID_PARAMS);
+        let resolved = requests.resolve_elicitation("missing", ElicitationAction::Cancel, None);
+        assert!(!resolved.unwrap(), "nothing was waiting");
+    }
+}
@@ -1,11 +1,12 @@
-use crate::mcp::client::McpClient;
+use crate::mcp::client::{McpClient, ServerRequestHandler};
 use crate::mcp::transport::TransportFactory;
 use crate::mcp::types::{
-    McpCallResult, McpClientError, McpServerConfig, McpServerId, McpServerStatus,
+    McpCallHooks, McpCallResult, McpClientError, McpPrompt, McpPromptResult, McpResource,
+    McpResourceContents, McpResourceTemplate, McpServerConfig, McpServerId, McpServerStatus,
     McpToolDescriptor, McpTransportSpec,
 };
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{broadcast, Mutex, Notify};
@@ -20,12 +21,31 @@ pub struct StatusEvent {
     pub tools_count: u32,
 }
 
+/// Emitted after a connected server announced `tools/list_changed` and was
+/// re-listed, so the tool registry can swap in the new set.
+#[derive(Debug, Clone)]
+pub struct ToolsChangedEvent {
+    pub server_id: McpServerId,
+    pub tools: Vec<McpToolDescriptor>,
+}
+
+/// Resources and prompts one connected server published during its
+/// handshake. Feeds the launcher's dynamic commands (see `mcp::catalog`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ServerCatalog {
+    pub server_id: McpServerId,
+    pub resources: Vec<McpResource>,
+    pub prompts: Vec<McpPrompt>,
+}
+
 #[derive(Debug, Clone)]
 pub struct SupervisorConfig {
     pub crash_window: Duration,
     pub max_crashes_in_window: u32,
     pub initial_backoff: Duration,
     pub max_backoff: Duration,
+    /// How often an idle connection is checked for server notifications.
+    pub notification_poll_interval: Duration,
 }
 
 impl Default for SupervisorConfig {
@@ -35,6 +55,7 @@ impl Default for SupervisorConfig {
             max_crashes_in_window: 3,
             initial_backoff: Duration::from_millis(500),
             max_backoff: Duration::from_secs(30),
+            notification_poll_interval: Duration::from_secs(2),
         }
     }
 }
@@ -42,6 +63,9 @@ impl Default for SupervisorConfig {
 struct ServerHandle {
     status: McpServerStatus,
     tools: Vec<McpToolDescriptor>,
+    resources: Vec<McpResource>,
+    resource_templates: Vec<McpResourceTemplate>,
+    prompts: Vec<McpPrompt>,
     client: Option<Arc<Mutex<McpClient>>>,
     cancel: Arc<Notify>,
     /// Fired by `call_tool` when it detects a transport error mid-session so
@@ -53,33 +77,55 @@ struct ServerHandle {
 struct Inner {
     servers: HashMap<McpServerId, ServerHandle>,
     status_tx: broadcast::Sender<StatusEvent>,
+    tools_tx: broadcast::Sender<ToolsChangedEvent>,
 }
 
+/// Builds the handler a server's client answers server-initiated requests
+/// (sampling, elicitation) with.
+pub type RequestHandlerFactory =
+    Arc<dyn Fn(&McpServerConfig) -> Arc<dyn ServerRequestHandler> + Send + Sync>;
+
 pub struct McpSupervisor {
     factory: Arc<dyn TransportFactory>,
     cfg: SupervisorConfig,
     inner: Arc<std::sync::Mutex<Inner>>,
+    request_handlers: Option<RequestHandlerFactory>,
 }
 
 impl McpSupervisor {
     pub fn new(factory: Arc<dyn TransportFactory>, cfg: SupervisorConfig) -> Self {
         let (status_tx, _) = broadcast::channel(64);
+        let (tools_tx, _) = broadcast::channel(16);
         Self {
             factory,
             cfg,
             inner: Arc::new(std::sync::Mutex::new(Inner {
                 servers: HashMap::new(),
                 status_tx,
+                tools_tx,
             })),
+            request_handlers: None,
         }
     }
 
+    /// Lets connected servers send requests back to the launcher. Without
+    /// it they are told the client supports none.
+    pub fn with_request_handlers(mut self, request_handlers: RequestHandlerFactory) -> Self {
+        self.request_handlers = Some(request_handlers);
+        self
+    }
+
     /// Subscribe to per-server status transitions. The launcher's `setup_app`
     /// forwards these to a Tauri event so the frontend never has to poll.
     pub fn subscribe_status(&self) -> broadcast::Receiver<StatusEvent> {
         self.inner.lock().unwrap().status_tx.subscribe()
     }
 
+    /// Subscribe to tool lists re-fetched after `tools/list_changed`.
+    pub fn subscribe_tools_changed(&self) -> broadcast::Receiver<ToolsChangedEvent> {
+        self.inner.lock().unwrap().tools_tx.subscribe()
+    }
+
     pub async fn enable(&self, config: McpServerConfig) -> Result<(), McpClientError> {
         // Idempotency: cancel and drop any previous watchdog for this id.
         self.disable(&config.id).await?;
@@ -93,12 +139,14 @@ impl McpSupervisor {
         let factory = self.factory.clone();
         let cfg = self.cfg.clone();
         let spec = config.transport.clone();
+        let request_handler = self.request_handlers.as_ref().map(|build| build(&config));
 
         let watchdog = tokio::spawn(async move {
             run_watchdog(
                 id.clone(),
                 factory,
                 spec,
+                request_handler,
                 cfg,
                 cancel_clone,
                 client_died_clone,
@@ -110,6 +158,9 @@ impl McpSupervisor {
         let handle = ServerHandle {
             status: McpServerStatus::Starting,
             tools: vec![],
+            resources: vec![],
+            resource_templates: vec![],
+            prompts: vec![],
             client: None,
             cancel,
             client_died,
@@ -159,6 +210,119 @@ impl McpSupervisor {
         name: &str,
         args: serde_json::Value,
     ) -> Result<McpCallResult, McpClientError> {
+        self.call_tool_with(id, name, args, McpCallHooks::default())
+            .await
+    }
+
+    /// `call_tool` with progress and cancellation; see `McpCallHooks`.
+    pub async fn call_tool_with(
+        &self,
+        id: &McpServerId,
+        name: &str,
+        args: serde_json::Value,
+        hooks: McpCallHooks<'_>,
+    ) -> Result<McpCallResult, McpClientError> {
+        let (client, client_died) = self.live_client(id)?;
+        let result = {
+            let mut guard = client.lock().await;
+            let result = guard.call_tool_with(name, args, hooks).await;
+            refresh_tools_if_changed(&self.inner, id, &mut guard).await;
+            result
+        };
+        signal_if_dead(&client_died, &result);
+        result
+    }
+
+    /// Resources the server listed when it connected. Empty when the server
+    /// doesn't advertise the `resources` capability.
+    pub async fn list_resources(
+        &self,
+        id: &McpServerId,
+    ) -> Result<Vec<McpResource>, McpClientError> {
+        self.cached(id, |h| h.resources.clone())
+    }
+
+    pub async fn list_resource_templates(
+        &self,
+        id: &McpServerId,
+    ) -> Result<Vec<McpResourceTemplate>, McpClientError> {
+        self.cached(id, |h| h.resource_templates.clone())
+    }
+
+    pub async fn list_prompts(&self, id: &McpServerId) -> Result<Vec<McpPrompt>, McpClientError> {
+        self.cached(id, |h| h.prompts.clone())
+    }
+
+    pub async fn read_resource(
+        &self,
+        id: &McpServerId,
+        uri: &str,
+    ) -> Result<Vec<McpResourceContents>, McpClientError> {
+        let (client, client_died) = self.live_client(id)?;
+        let result = {
+            let mut guard = client.lock().await;
+            let result = guard.read_resource(uri).await;
+            refresh_tools_if_changed(&self.inner, id, &mut guard).await;
+            result
+        };
+        signal_if_dead(&client_died, &result);
+        result
+    }
+
+    pub async fn get_prompt(
+        &self,
+        id: &McpServerId,
+        name: &str,
+        arguments: &BTreeMap<String, String>,
+    ) -> Result<McpPromptResult, McpClientError> {
+        let (client, client_died) = self.live_client(id)?;
+        let result = {
+            let mut guard = client.lock().await;
+            let result = guard.get_prompt(name, arguments).await;
+            refresh_tools_if_changed(&self.inner, id, &mut guard).await;
+            result
+        };
+        signal_if_dead(&client_died, &result);
+        result
+    }
+
+    /// Resources and prompts of every `Connected` server, sorted by server id
+    /// so the derived command list is stable between syncs.
+    pub fn catalog(&self) -> Vec<ServerCatalog> {
+        let guard = self.inner.lock().unwrap();
+        let mut catalog: Vec<ServerCatalog> = guard
+            .servers
+            .iter()
+            .filter(|(_, h)| h.status == McpServerStatus::Connected)
+            .map(|(id, h)| ServerCatalog {
+                server_id: id.clone(),
+                resources: h.resources.clone(),
+                prompts: h.prompts.clone(),
+            })
+            .collect();
+        catalog.sort_by(|a, b| a.server_id.cmp(&b.server_id));
+        catalog
+    }
+
+    fn cached<T>(
+        &self,
+        id: &McpServerId,
+        read: impl FnOnce(&ServerHandle) -> T,
+    ) -> Result<T, McpClientError> {
+        let guard = self.inner.lock().unwrap();
+        guard
+            .servers
+            .get(id)
+            .map(read)
+            .ok_or_else(|| McpClientError::Transport(format!("unknown server: {id}")))
+    }
+
+    /// The live client for `id`, plus the handle's `client_died` signal. With
+    /// no live client the watchdog is told to restart immediately.
+    fn live_client(
+        &self,
+        id: &McpServerId,
+    ) -> Result<(Arc<Mutex<McpClient>>, Option<Arc<Notify>>), McpClientError> {
         let (client, client_died) = {
             let guard = self.inner.lock().unwrap();
             let handle = guard.servers.get(id);
@@ -168,6 +332,7 @@ impl McpSupervisor {
             )
         };
         match client {
+            Some(client) => Ok((client, client_died)),
             None => {
                 // No live client — signal the watchdog to restart immediately.
                 if let Some(died) = client_died {
@@ -177,26 +342,6 @@ impl McpSupervisor {
                     "server {id} not connected"
                 )))
             }
-            Some(arc_client) => {
-                let result = {
-                    let mut guard = arc_client.lock().await;
-                    guard.call_tool(name, args).await
-                };
-                if let Err(ref e) = result {
-                    let is_transport_err = matches!(
-                        e,
-                        McpClientError::Io(_)
-                            | McpClientError::EarlyExit
-                            | McpClientError::Transport(_)
-                    );
-                    if is_transport_err {
-                        if let Some(died) = client_died {
-                            died.notify_one();
-                        }
-                    }
-                }
-                result
-            }
         }
     }
 
@@ -268,10 +413,12 @@ impl McpSupervisor {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn run_watchdog(
     id: McpServerId,
     factory: Arc<dyn TransportFactory>,
     spec: McpTransportSpec,
+    request_handler: Option<Arc<dyn ServerRequestHandler>>,
     cfg: SupervisorConfig,
     cancel: Arc<Notify>,
     client_died: Arc<Notify>,
@@ -289,7 +436,7 @@ async fn run_watchdog(
                 set_status(&inner, &id, McpServerStatus::Disabled);
                 return;
             }
-            r = attempt_connect(&factory, &spec, &inner, &id) => r,
+            r = attempt_connect(&factory, &spec, request_handler.clone(), &inner, &id) => r,
         };
 
         match result {
@@ -303,7 +450,13 @@ async fn run_watchdog(
                         set_status(&inner, &id, McpServerStatus::Disabled);
                         return;
                     }
-                    _ = monitor_client(client_clone, client_died.clone()) => {
+                    _ = monitor_client(
+                        client_clone,
+                        client_died.clone(),
+                        cfg.notification_poll_interval,
+                        &inner,
+                        &id,
+                    ) => {
                         // Connection dropped or transport error — will retry below
                         clear_client(&inner, &id);
                     }
@@ -343,19 +496,45 @@ async fn run_watchdog(
 async fn attempt_connect(
     factory: &Arc<dyn TransportFactory>,
     spec: &McpTransportSpec,
+    request_handler: Option<Arc<dyn ServerRequestHandler>>,
     inner: &Arc<std::sync::Mutex<Inner>>,
     id: &McpServerId,
 ) -> Result<Arc<Mutex<McpClient>>, McpClientError> {
     let transport = factory.connect(spec).await?;
     let mut client = McpClient::new(transport);
+    if let Some(handler) = request_handler {
+        client.set_request_handler(handler);
+    }
     client.initialize().await?;
     let tools = client.list_tools().await?;
+    // Resources and prompts are optional extras: a server that advertises
+    // them but fails to list them still serves its tools.
+    let (resources, resource_templates) = if client.supports("resources") {
+        (
+            or_warn(id, "resources/list", client.list_resources().await),
+            or_warn(
+                id,
+                "resources/templates/list",
+                client.list_resource_templates().await,
+            ),
+        )
+    } else {
+        (vec![], vec![])
+    };
+    let prompts = if client.supports("prompts") {
+        or_warn(id, "prompts/list", client.list_prompts().await)
+    } else {
+        vec![]
+    };
     let arc_client = Arc::new(Mutex::new(client));
 
     {
         let mut guard = inner.lock().unwrap();
         if let Some(handle) = guard.servers.get_mut(id) {
             handle.tools = tools;
+            handle.resources = resources;
+            handle.resource_templates = resource_templates;
+            handle.prompts = prompts;
             handle.client = Some(arc_client.clone());
         }
     }
@@ -364,13 +543,50 @@ async fn attempt_connect(
     Ok(arc_client)
 }
 
-async fn monitor_client(client: Arc<Mutex<McpClient>>, client_died: Arc<Notify>) {
+fn or_warn<T: Default>(id: &McpServerId, method: &str, result: Result<T, McpClientError>) -> T {
+    result.unwrap_or_else(|e| {
+        log::warn!("MCP server '{id}': {method} failed: {e}");
+        T::default()
+    })
+}
+
+/// Wakes the watchdog when a request failed because the connection is gone
+/// rather than because the server rejected it.
+fn signal_if_dead<T>(client_died: &Option<Arc<Notify>>, result: &Result<T, McpClientError>) {
+    let is_transport_err = matches!(
+        result,
+        Err(McpClientError::Io(_) | McpClientError::EarlyExit | McpClientError::Transport(_))
+    );
+    if is_transport_err {
+        if let Some(died) = client_died {
+            died.notify_one();
+        }
+    }
+}
+
+async fn monitor_client(
+    client: Arc<Mutex<McpClient>>,
+    client_died: Arc<Notify>,
+    poll_interval: Duration,
+    inner: &Arc<std::sync::Mutex<Inner>>,
+    id: &McpServerId,
+) {
+    let mut poll = tokio::time::interval(poll_interval);
     loop {
         tokio::select! {
-            _ = tokio::time::sleep(Duration::from_secs(60)) => {
+            _ = poll.tick() => {
                 if Arc::strong_count(&client) == 1 {
                     break;
                 }
+                // A busy client reads its own notifications mid-request.
+                let Ok(mut guard) = client.try_lock() else {
+                    continue;
+                };
+                if let Err(e) = guard.poll_notifications().await {
+                    log::warn!("MCP monitor_client: reading notifications failed: {e}");
+                    break;
+                }
+                refresh_tools_if_changed(inner, id, &mut guard).await;
             }
             _ = client_died.notified() => {
                 // A call_tool detected a transport error — return immediately
@@ -382,6 +598,42 @@ async fn monitor_client(client: Arc<Mutex<McpClient>>, client_died: Arc<Notify>)
     }
 }
 
+/// Re-lists tools after the server sent `tools/list_changed`, then updates
+/// the cached list, the status event's tool count, and `tools_tx`.
+async fn refresh_tools_if_changed(
+    inner: &Arc<std::sync::Mutex<Inner>>,
+    id: &McpServerId,
+    client: &mut McpClient,
+) {
+    if !client.take_tools_changed() {
+        return;
+    }
+    let tools = match client.list_tools().await {
+        Ok(tools) => tools,
+        Err(e) => {
+            log::warn!("MCP server '{id}': tools/list after list_changed failed: {e}");
+            return;
+        }
+    };
+    let mut guard = inner.lock().unwrap();
+    let status = match guard.servers.get_mut(id) {
+        Some(handle) => {
+            handle.tools = tools.clone();
+            handle.status
+        }
+        None => return,
+    };
+    let _ = guard.status_tx.send(StatusEvent {
+        server_id: id.clone(),
+        status,
+        tools_count: tools.len() as u32,
+    });
+    let _ = guard.tools_tx.send(ToolsChangedEvent {
+        server_id: id.clone(),
+        tools,
+    });
+}
+
 fn set_status(inner: &Arc<std::sync::Mutex<Inner>>, id: &McpServerId, status: McpServerStatus) {
     let mut guard = inner.lock().unwrap();
     let tools_count = match guard.servers.get_mut(id) {
@@ -423,6 +675,14 @@ mod tests {
         Fail,
         /// Server stream closes immediately after receiving initialize (crash)
         ImmediateCrash,
+        /// Like `Succeed`, but also advertises and answers resources + prompts
+        SucceedWithCatalog,
+        /// Like `Succeed`, but announces `tools/list_changed` after the first
+        /// `tools/list` and lists a second tool from then on
+        SucceedThenChangeTools,
+        /// Like `Succeed`, but answers `tools/call` with whatever the client
+        /// replied to a `sampling/createMessage` sent mid-call
+        SucceedAndSample,
     }
 
     struct MockTransportFactory {
@@ -461,9 +721,17 @@ mod tests {
                 MockConnectBehavior::Fail => Err(McpClientError::Transport(
                     "mock: connection refused".to_string(),
                 )),
-                MockConnectBehavior::Succeed | MockConnectBehavior::ImmediateCrash => {
+                MockConnectBehavior::Succeed
+                | MockConnectBehavior::ImmediateCrash
+                | MockConnectBehavior::SucceedWithCatalog
+                | MockConnectBehavior::SucceedThenChangeTools
+                | MockConnectBehavior::SucceedAndSample => {
                     let (transport, mut server) = duplex_pair();
                     let is_crash = matches!(behavior, MockConnectBehavior::ImmediateCrash);
+                    let with_catalog = matches!(behavior, MockConnectBehavior::SucceedWithCatalog);
+                    let change_tools =
+                        matches!(behavior, MockConnectBehavior::SucceedThenChangeTools);
+                    let sample = matches!(behavior, MockConnectBehavior::SucceedAndSample);
                     tokio::spawn(async move {
                         // Handle initialize
                         let req = server.recv_line().await;
@@ -475,21 +743,65 @@ mod tests {
                             drop(server);
                             return;
                         }
+                        let capabilities = if with_catalog {
+                            r#"{"tools":{},"resources":{},"prompts":{}}"#
+                        } else {
+                            "{}"
+                        };
                         server
-                            .send_line(r#"{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2025-06-18","capabilities":{},"serverInfo":{"name":"mock","version":"0"}}}"#)
+                            .send_line(&format!(r#"{{"jsonrpc":"2.0","id":1,"result":{{"protocolVersion":"2025-06-18","capabilities":{capabilities},"serverInfo":{{"name":"mock","version":"0"}}}}}}"#))
                             .await;
                         let _ = server.recv_line().await; // notifications/initialized
 
-                        // Handle list_tools
-                        let _list = server.recv_line().await;
-                        server
-                            .send_line(r#"{"jsonrpc":"2.0","id":2,"result":{"tools":[{"name":"mock_tool","description":"a tool","inputSchema":{"type":"object"}}]}}"#)
-                            .await;
-
-                        // Keep server alive until dropped
-                        loop {
-                            if server.recv_line().await.is_none() {
-                                break;
+                        // Answer requests until dropped
+                        let mut tools_listed = false;
+                        while let Some(line) = server.recv_line().await {
+                            let req: serde_json::Value = serde_json::from_str(&line).unwrap();
+                            let result = match req["method"].as_str().unwrap_or_default() {
+                                "tools/call" if sample => {
+                                    server
+                                        .send_line(r#"{"jsonrpc":"2.0","id":"s1","method":"sampling/createMessage","params":{"messages":[],"maxTokens":8}}"#)
+                                        .await;
+                                    let Some(reply) = server.recv_line().await else {
+                                        return;
+                                    };
+                                    let reply: serde_json::Value =
+                                        serde_json::from_str(&reply).unwrap();
+                                    serde_json::json!({ "content": reply["result"] })
+                                }
+                                "tools/list" if change_tools && tools_listed => serde_json::json!({
+                                    "tools": [
+                                        {"name": "mock_tool", "inputSchema": {"type": "object"}},
+                                        {"name": "new_tool", "inputSchema": {"type": "object"}}
+                                    ]
+                                }),
+                                "tools/list" => serde_json::json!({
+                                    "tools": [{"name": "mock_tool", "description": "a tool", "inputSchema": {"type": "object"}}]
+                                }),
+                                "resources/list" if with_catalog => serde_json::json!({
+                                    "resources": [{"uri": "file:///notes.md", "name": "notes.md", "mimeType": "text/markdown"}]
+                                }),
+                                "resources/templates/list" if with_catalog => serde_json::json!({
+                                    "resourceTemplates": [{"uriTemplate": "issue://{id}", "name": "Issue"}]
+                                }),
+                                "resources/read" if with_catalog => serde_json::json!({
+                                    "contents": [{"uri": req["params"]["uri"], "mimeType": "text/markdown", "text": "hello"}]
+                                }),
+                                "prompts/list" if with_catalog => serde_json::json!({
+                                    "prompts": [{"name": "review", "arguments": [{"name": "code", "required": true}]}]
+                                }),
+                                "prompts/get" if with_catalog => serde_json::json!({
+                                    "messages": [{"role": "user", "content": {"type": "text", "text": format!("Review {}", req["params"]["arguments"]["code"].as_str().unwrap_or_default())}}]
+                                }),
+                                _ => continue,
+                            };
+                            let reply = serde_json::json!({"jsonrpc": "2.0", "id": req["id"], "result": result});
+                            server.send_line(&reply.to_string()).await;
+                            if change_tools && req["method"] == "tools/list" && !tools_listed {
+                                tools_listed = true;
+                                server
+                                    .send_line(r#"{"jsonrpc":"2.0","method":"notifications/tools/list_changed"}"#)
+                                    .await;
                             }
                         }
                     });
@@ -655,6 +967,7 @@ mod tests {
             max_crashes_in_window: 3,
             initial_backoff: Duration::from_millis(10),
             max_backoff: Duration::from_millis(100),
+            ..SupervisorConfig::default()
         };
         let supervisor = McpSupervisor::new(factory.clone(), cfg);
         let config = make_config("srv5");
@@ -699,6 +1012,7 @@ mod tests {
             max_crashes_in_window: 3,
             initial_backoff: Duration::from_millis(10),
             max_backoff: Duration::from_millis(50),
+            ..SupervisorConfig::default()
         };
         let supervisor = McpSupervisor::new(factory, cfg);
         let config = make_config("srv6");
@@ -926,6 +1240,7 @@ mod tests {
             max_crashes_in_window: 10, // don't go to Failed too quickly
             initial_backoff: Duration::from_millis(500),
             max_backoff: Duration::from_secs(30),
+            ..SupervisorConfig::default()
         };
         let supervisor = McpSupervisor::new(factory, cfg);
         let config = make_config("srv12");
@@ -941,4 +1256,151 @@ mod tests {
             "error message should mention the server id, got: {err_msg}"
         );
     }
+
+    // 13. connect_lists_resources_and_prompts_when_advertised
+    #[tokio::test]
+    async fn connect_lists_resources_and_prompts_when_advertised() {
+        let factory = Arc::new(MockTransportFactory::new(vec![
+            MockConnectBehavior::SucceedWithCatalog,
+        ]));
+        let cfg = SupervisorConfig {
+            initial_backoff: Duration::from_millis(10),
+            ..SupervisorConfig::default()
+        };
+        let supervisor = McpSupervisor::new(factory, cfg);
+        let id = "srv13".to_string();
+
+        supervisor
+            .enable_and_wait_for_tools(make_config(&id), Duration::from_millis(2000))
+            .await
+            .expect("connect");
+
+        let resources = supervisor.list_resources(&id).await.unwrap();
+        assert_eq!(resources[0].uri, "file:///notes.md");
+        let templates = supervisor.list_resource_templates(&id).await.unwrap();
+        assert_eq!(templates[0].uri_template, "issue://{id}");
+        let prompts = supervisor.list_prompts(&id).await.unwrap();
+        assert_eq!(prompts[0].name, "review");
+
+        let catalog = supervisor.catalog();
+        assert_eq!(catalog.len(), 1);
+        assert_eq!(catalog[0].server_id, id);
+
+        let contents = supervisor
+            .read_resource(&id, "file:///notes.md")
+            .await
+            .expect("read_resource");
+        assert_eq!(contents[0].text.as_deref(), Some("hello"));
+
+        let mut args = BTreeMap::new();
+        args.insert("code".to_string(), "x".to_string());
+        let rendered = supervisor
+            .get_prompt(&id, "review", &args)
+            .await
+            .expect("get_prompt");
+        assert_eq!(rendered.messages[0].content["text"], "Review x");
+    }
+
+    // 14. connect_skips_resources_and_prompts_when_not_advertised
+    #[tokio::test]
+    async fn connect_skips_resources_and_prompts_when_not_advertised() {
+        let factory = Arc::new(MockTransportFactory::new(vec![
+            MockConnectBehavior::Succeed,
+        ]));
+        let cfg = SupervisorConfig {
+            initial_backoff: Duration::from_millis(10),
+            ..SupervisorConfig::default()
+        };
+        let supervisor = McpSupervisor::new(factory, cfg);
+        let id = "srv14".to_string();
+
+        supervisor
+            .enable_and_wait_for_tools(make_config(&id), Duration::from_millis(2000))
+            .await
+            .expect("connect");
+
+        assert!(supervisor.list_resources(&id).await.unwrap().is_empty());
+        assert!(supervisor.list_prompts(&id).await.unwrap().is_empty());
+        assert_eq!(
+            supervisor.catalog()[0],
+            ServerCatalog {
+                server_id: id,
+                ..ServerCatalog::default()
+            }
+        );
+    }
+
+    // 15. tools_list_changed_relists_tools_and_notifies_subscribers
+    #[tokio::test]
+    async fn tools_list_changed_relists_tools_and_notifies_subscribers() {
+        let factory = Arc::new(MockTransportFactory::new(vec![
+            MockConnectBehavior::SucceedThenChangeTools,
+        ]));
+        let cfg = SupervisorConfig {
+            initial_backoff: Duration::from_millis(10),
+            notification_poll_interval: Duration::from_millis(10),
+            ..SupervisorConfig::default()
+        };
+        let supervisor = McpSupervisor::new(factory, cfg);
+        let mut changes = supervisor.subscribe_tools_changed();
+        let mut statuses = supervisor.subscribe_status();
+        let id = "srv15".to_string();
+
+        supervisor.enable(make_config(&id)).await.expect("enable");
+
+        let event = tokio::time::timeout(Duration::from_secs(2), changes.recv())
+            .await
+            .expect("tools changed event within 2s")
+            .expect("event");
+        assert_eq!(event.server_id, id);
+        let names: Vec<_> = event.tools.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["mock_tool", "new_tool"]);
+        assert_eq!(supervisor.list_tools(&id).await.unwrap().len(), 2);
+
+        let mut last_count = 0;
+        while let Ok(status) = statuses.try_recv() {
+            last_count = status.tools_count;
+        }
+        assert_eq!(last_count, 2, "status event must carry the new tool count");
+    }
+
+    struct NamedHandler(String);
+
+    #[async_trait]
+    impl ServerRequestHandler for NamedHandler {
+        async fn handle(
+            &self,
+            method: &str,
+            _params: serde_json::Value,
+        ) -> Result<serde_json::Value, McpClientError> {
+            Ok(serde_json::json!({ "method": method, "server": self.0 }))
+        }
+    }
+
+    // 16. request_handlers_answer_server_requests_during_calls
+    #[tokio::test]
+    async fn request_handlers_answer_server_requests_during_calls() {
+        let factory = Arc::new(MockTransportFactory::new(vec![
+            MockConnectBehavior::SucceedAndSample,
+        ]));
+        let handlers: RequestHandlerFactory = Arc::new(|config: &McpServerConfig| {
+            Arc::new(NamedHandler(config.display_name.clone())) as Arc<dyn ServerRequestHandler>
+        });
+        let supervisor = McpSupervisor::new(factory, SupervisorConfig::default())
+            .with_request_handlers(handlers);
+        let id = "srv16".to_string();
+
+        supervisor
+            .enable_and_wait_for_tools(make_config(&id), Duration::from_secs(2))
+            .await
+            .expect("connect");
+        let result = supervisor
+            .call_tool(&id, "mock_tool", serde_json::json!({}))
+            .await
+            .expect("call");
+        assert_eq!(
+            result.content,
+            serde_json::json!({ "method": "sampling/createMessage", "server": "Server srv16" })
+        );
+    }
 }
@@ -1,6 +1,7 @@
 use crate::agents::tools::ManifestTool;
 use crate::error::AppError;
-use crate::mcp::types::{McpCallResult, McpClientError, McpToolDescriptor};
+use crate::mcp::server_requests::sampling_permission_id;
+use crate::mcp::types::{McpCallHooks, McpCallResult, McpClientError, McpToolDescriptor};
 use crate::mcp::McpSupervisor;
 use crate::storage::mcp_audit::NewMcpAuditEntry;
 use crate::storage::DataStore;
@@ -27,13 +28,17 @@ pub fn is_tool_read_only(tool_id: &str) -> bool {
 
 /// Converts a list of MCP tool descriptors (from the MCP protocol) into the
 /// `ManifestTool` shape used by the tool registry. The MCP tool's `name` field
-/// becomes both `id` and `name`; `input_schema` becomes `parameters`.
+/// becomes both `id` and `name`; `input_schema` becomes `parameters`. A tool
+/// named after the server's sampling policy key is dropped so it can't inherit
+/// that policy's decision.
 pub fn descriptors_from_mcp_tools(
-    _server_id: &str,
+    server_id: &str,
     tools: Vec<McpToolDescriptor>,
 ) -> Vec<ManifestTool> {
+    let sampling_id = sampling_permission_id(server_id);
     tools
         .into_iter()
+        .filter(|t| t.name != sampling_id)
         .map(|t| ManifestTool {
             id: t.name.clone(),
             name: t.name,
@@ -52,6 +57,29 @@ pub async fn invoke_mcp_tool(
     tool_id: &str,
     agent_id: Option<&str>,
     args: serde_json::Value,
+) -> Result<serde_json::Value, AppError> {
+    invoke_mcp_tool_with(
+        supervisor,
+        store,
+        server_id,
+        tool_id,
+        agent_id,
+        args,
+        McpCallHooks::default(),
+    )
+    .await
+}
+
+/// `invoke_mcp_tool` for callers that show progress or can cancel the call
+/// (agent runs); see `McpCallHooks`.
+pub async fn invoke_mcp_tool_with(
+    supervisor: &McpSupervisor,
+    store: &DataStore,
+    server_id: &str,
+    tool_id: &str,
+    agent_id: Option<&str>,
+    args: serde_json::Value,
+    hooks: McpCallHooks<'_>,
 ) -> Result<serde_json::Value, AppError> {
     log::debug!(
         "invoke_mcp_tool: server={} tool={} agent={:?}",
@@ -112,7 +140,7 @@ pub async fn invoke_mcp_tool(
     let called_at = now_millis();
 
     let result: Result<McpCallResult, McpClientError> = supervisor
-        .call_tool(&server_id.to_string(), tool_id, args)
+        .call_tool_with(&server_id.to_string(), tool_id, args, hooks)
         .await;
 
     match result {
@@ -812,4 +840,23 @@ mod tests {
             "error_summary must not be empty"
         );
     }
+
+    // ── 9. descriptors_from_mcp_tools_drops_a_tool_named_like_the_sampling_policy ─
+
+    #[test]
+    fn descriptors_from_mcp_tools_drops_a_tool_named_like_the_sampling_policy() {
+        let tools = ["sampling", "mcp:srv1#sampling"]
+            .into_iter()
+            .map(|name| McpToolDescriptor {
+                name: name.to_string(),
+                description: None,
+                input_schema: serde_json::json!({}),
+            })
+            .collect();
+
+        let result = descriptors_from_mcp_tools("srv1", tools);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id, "sampling");
+    }
 }
@@ -1,3 +1,4 @@
+use crate::mcp::oauth::{resource_for, McpAuthorizer};
 use crate::mcp::sidecar::{resolve_command_with_probe, system_command_exists, ResolvedCommand};
 use crate::mcp::types::{McpClientError, McpTransportSpec};
 use async_trait::async_trait;
@@ -91,14 +92,23 @@ pub fn resolve_stdio_command<F: Fn(&str) -> bool>(
 
 pub struct HttpTransportFactory {
     client: reqwest::Client,
+    authorizer: Option<Arc<McpAuthorizer>>,
 }
 
 impl HttpTransportFactory {
     pub fn new() -> Self {
         Self {
             client: reqwest::Client::new(),
+            authorizer: None,
         }
     }
+
+    /// Servers that answer 401 get signed in to through `authorizer`
+    /// (see `mcp::oauth`) instead of failing.
+    pub fn with_authorizer(mut self, authorizer: Arc<McpAuthorizer>) -> Self {
+        self.authorizer = Some(authorizer);
+        self
+    }
 }
 
 impl Default for HttpTransportFactory {
@@ -217,31 +227,66 @@ struct HttpTransport {
     /// server can route follow-up calls (`tools/list`, `tools/call`) to the
     /// same logical session.
     session_id: std::sync::Mutex<Option<String>>,
+    /// `None` when OAuth is off for this server: no authorizer, or the
+    /// config sets its own `Authorization` header.
+    authorizer: Option<Arc<McpAuthorizer>>,
 }
 
 impl HttpTransport {
-    /// POST `line` to the server and return the response frames.
-    /// Returns an empty vec for notification POSTs (caller discards).
-    ///
-    /// For `text/event-stream` responses, frames are extracted incrementally
-    /// from the byte stream so large SSE payloads don't require full buffering.
-    /// For all other content types, the response body is read in full.
-    async fn do_post(&self, line: &str) -> Result<Vec<String>, McpClientError> {
+    async fn post(
+        &self,
+        line: &str,
+        bearer: Option<&str>,
+    ) -> Result<reqwest::Response, McpClientError> {
         let mut req = self.client.post(self.url.clone());
         for (k, v) in &self.headers {
             req = req.header(k.as_str(), v.as_str());
         }
+        if let Some(token) = bearer {
+            req = req.bearer_auth(token);
+        }
         if let Some(sid) = self.session_id.lock().unwrap().as_ref() {
             req = req.header("Mcp-Session-Id", sid.as_str());
         }
-        let response = req
-            .header("content-type", "application/json")
+        req.header("content-type", "application/json")
             .header("accept", "application/json, text/event-stream")
             .header("mcp-protocol-version", "2025-06-18")
             .body(line.to_string())
             .send()
             .await
-            .map_err(|e| McpClientError::Transport(e.to_string()))?;
+            .map_err(|e| McpClientError::Transport(e.to_string()))
+    }
+
+    /// POST `line` to the server and return the response frames.
+    /// Returns an empty vec for notification POSTs (caller discards).
+    ///
+    /// For `text/event-stream` responses, frames are extracted incrementally
+    /// from the byte stream so large SSE payloads don't require full buffering.
+    /// For all other content types, the response body is read in full.
+    ///
+    /// With an authorizer, a 401 is answered by refreshing or signing in
+    /// and the message is sent once more.
+    async fn do_post(&self, line: &str) -> Result<Vec<String>, McpClientError> {
+        let resource = resource_for(&self.url);
+        let bearer = match &self.authorizer {
+            Some(authorizer) => authorizer.access_token(&resource).await,
+            None => None,
+        };
+        let mut response = self.post(line, bearer.as_deref()).await?;
+        if let Some(authorizer) = &self.authorizer {
+            if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+                let challenge = response
+                    .headers()
+                    .get("www-authenticate")
+                    .and_then(|v| v.to_str().ok())
+                    .map(String::from);
+                let token = authorizer
+                    .reauthorize(&resource, challenge.as_deref(), bearer.as_deref())
+                    .await
+                    .map_err(|e| McpClientError::Transport(e.to_string()))?;
+                response = self.post(line, Some(token.as_str())).await?;
+            }
+        }
 
         if !response.status().is_success() {
             return Err(McpClientError::Transport(format!(
@@ -356,6 +401,9 @@ impl TransportFactory for HttpTransportFactory {
             McpTransportSpec::Http { url, headers } => {
                 let parsed = reqwest::Url::parse(url)
                     .map_err(|e| McpClientError::Transport(e.to_string()))?;
+                let own_authorization = headers
+                    .keys()
+                    .any(|k| k.eq_ignore_ascii_case("authorization"));
                 Ok(Box::new(HttpTransport {
                     client: self.client.clone(),
                     url: parsed,
@@ -363,6 +411,7 @@ impl TransportFactory for HttpTransportFactory {
                     buf: std::collections::VecDeque::new(),
                     closed: false,
                     session_id: std::sync::Mutex::new(None),
+                    authorizer: self.authorizer.clone().filter(|_| !own_authorization),
                 }))
             }
             McpTransportSpec::Stdio { .. } => Err(McpClientError::Transport(
@@ -391,6 +440,12 @@ impl MultiTransportFactory {
             http: HttpTransportFactory::new(),
         }
     }
+
+    /// See `HttpTransportFactory::with_authorizer`.
+    pub fn with_authorizer(mut self, authorizer: Arc<McpAuthorizer>) -> Self {
+        self.http = self.http.with_authorizer(authorizer);
+        self
+    }
 }
 
 #[async_trait]
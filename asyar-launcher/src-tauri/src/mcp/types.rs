@@ -50,6 +50,106 @@ pub struct McpCallResult {
     pub is_error: bool,
 }
 
+/// An entry from `resources/list`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct McpResource {
+    pub uri: String,
+    pub name: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+}
+
+/// An entry from `resources/templates/list`: a parameterised URI
+/// (RFC 6570) the server can read once the variables are filled in.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct McpResourceTemplate {
+    pub uri_template: String,
+    pub name: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+}
+
+/// One item of a `resources/read` result. Exactly one of `text` and `blob`
+/// (base64) is set by a well-behaved server.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct McpResourceContents {
+    pub uri: String,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub blob: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct McpPromptArgument {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// An entry from `prompts/list`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct McpPrompt {
+    pub name: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<McpPromptArgument>,
+}
+
+/// One message of a `prompts/get` result. `content` is kept as the raw
+/// content block (`text`, `image`, `audio`, `resource`, …).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct McpPromptMessage {
+    pub role: String,
+    pub content: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct McpPromptResult {
+    #[serde(default)]
+    pub description: Option<String>,
+    pub messages: Vec<McpPromptMessage>,
+}
+
+/// Params of a `notifications/progress` message for an in-flight request.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct McpProgress {
+    pub progress: f64,
+    #[serde(default)]
+    pub total: Option<f64>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// Optional observers for a long `tools/call`: `on_progress` receives the
+/// server's progress updates, and flipping `cancel` to `true` abandons the
+/// call with `notifications/cancelled`.
+#[derive(Default)]
+pub struct McpCallHooks<'a> {
+    pub on_progress: Option<&'a (dyn Fn(McpProgress) + Send + Sync)>,
+    pub cancel: Option<tokio::sync::watch::Receiver<bool>>,
+}
+
 #[derive(Debug, Error)]
 pub enum McpClientError {
     #[error("transport error: {0}")]
@@ -62,6 +162,8 @@ pub enum McpClientError {
     EarlyExit,
     #[error("timeout waiting for response to id={0}")]
     Timeout(u64),
+    #[error("request cancelled")]
+    Cancelled,
     #[error("io: {0}")]
     Io(#[from] std::io::Error),
     #[error("json: {0}")]
@@ -169,4 +271,30 @@ mod tests {
         assert!(!back.enabled);
         assert_eq!(config, back);
     }
+
+    // 7. prompt_parses_wire_shape_with_optional_fields_omitted
+    #[test]
+    fn prompt_parses_wire_shape_with_optional_fields_omitted() {
+        let prompt: McpPrompt = serde_json::from_value(serde_json::json!({
+            "name": "review",
+            "arguments": [{ "name": "code", "required": true }, { "name": "style" }]
+        }))
+        .expect("deserialize");
+        assert_eq!(prompt.title, None);
+        assert_eq!(prompt.arguments.len(), 2);
+        assert!(prompt.arguments[0].required);
+        assert!(!prompt.arguments[1].required, "required defaults to false");
+    }
+
+    // 8. resource_uses_camel_case_mime_type
+    #[test]
+    fn resource_uses_camel_case_mime_type() {
+        let resource: McpResource = serde_json::from_value(serde_json::json!({
+            "uri": "file:///notes.md",
+            "name": "notes.md",
+            "mimeType": "text/markdown"
+        }))
+        .expect("deserialize");
+        assert_eq!(resource.mime_type.as_deref(), Some("text/markdown"));
+    }
 }
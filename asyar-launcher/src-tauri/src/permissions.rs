@@ -170,6 +170,8 @@ fn get_required_permission(call_type: &str) -> Option<&'static str> {
         "asyar:api:shell:attach" => Some("shell:spawn"),
         "asyar:api:shell:write-stdin" => Some("shell:spawn"),
         "asyar:api:shell:close-stdin" => Some("shell:spawn"),
+        "asyar:api:shell:resize" => Some("shell:spawn"),
+        "asyar:api:shell:signal" => Some("shell:spawn"),
         // Entitlement service — requires subscription read permission
         "asyar:api:entitlements:check" => Some("entitlements:read"),
         "asyar:api:entitlements:getAll" => Some("entitlements:read"),
@@ -214,6 +216,14 @@ fn get_required_permission(call_type: &str) -> Option<&'static str> {
         // Process service (list/kill)
         "asyar:api:process:list" => Some("process:read"),
         "asyar:api:process:kill" => Some("process:kill"),
+        "asyar:api:process:startSampling" => Some("process:read"),
+        "asyar:api:process:stopSampling" => Some("process:read"),
+        "asyar:api:process:history" => Some("process:read"),
+        "asyar:api:process:top" => Some("process:read"),
+        "asyar:api:process:listAlerts" => Some("process:read"),
+        // The command also requires `notifications:send`.
+        "asyar:api:process:saveAlert" => Some("process:read"),
+        "asyar:api:process:deleteAlert" => Some("process:read"),
         // System events (OS sleep/wake/lid/battery push)
         "asyar:api:systemEvents:subscribe" => Some("systemEvents:read"),
         "asyar:api:systemEvents:unsubscribe" => Some("systemEvents:read"),
@@ -705,6 +715,21 @@ mod tests {
             get_required_permission("asyar:api:process:kill"),
             Some("process:kill")
         );
+        for wire in [
+            "startSampling",
+            "stopSampling",
+            "history",
+            "top",
+            "listAlerts",
+            "saveAlert",
+            "deleteAlert",
+        ] {
+            assert_eq!(
+                get_required_permission(&format!("asyar:api:process:{wire}")),
+                Some("process:read"),
+                "{wire}"
+            );
+        }
     }
 
     #[test]
@@ -1152,6 +1177,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn shell_resize_and_signal_wire_types_map_to_shell_spawn() {
+        assert_eq!(
+            get_required_permission("asyar:api:shell:resize"),
+            Some("shell:spawn")
+        );
+        assert_eq!(
+            get_required_permission("asyar:api:shell:signal"),
+            Some("shell:spawn")
+        );
+    }
+
     #[test]
     fn oauth_authorize_maps_to_oauth_use() {
         assert_eq!(
@@ -168,21 +168,28 @@ pub fn register_snippet_monitor(app_handle: AppHandle) {
                 crate::snippets::merge_active_snippets(&user_guard, &contributed_guard)
             };
 
-            for (keyword, expansion) in merged.iter() {
-                if current.ends_with(keyword.as_str()) {
-                    let kw_len = keyword.chars().count();
-                    let exp = expansion.clone();
-                    buffer.clear();
-                    let _ = app.emit_to(
-                        crate::SPOTLIGHT_LABEL,
-                        "expand-snippet",
-                        serde_json::json!({
-                            "keywordLen": kw_len,
-                            "expansion": exp
-                        }),
-                    );
-                    return;
-                }
+            let rules = std::sync::Arc::clone(
+                &state
+                    .snippet_rules
+                    .lock()
+                    .unwrap_or_else(|p| p.into_inner()),
+            );
+            if let Some(hit) = crate::snippets::resolve_expansion_at_end(
+                &current,
+                &rules,
+                &merged,
+                crate::snippets::frontmost_for_scope,
+            ) {
+                buffer.clear();
+                let _ = app.emit_to(
+                    crate::SPOTLIGHT_LABEL,
+                    "expand-snippet",
+                    serde_json::json!({
+                        "keywordLen": hit.keyword_len,
+                        "expansion": hit.expansion
+                    }),
+                );
+                return;
             }
             let triggers = {
                 if let Ok(guard) = state.shortcode_triggers.lock() {
@@ -196,7 +203,7 @@ pub fn register_snippet_monitor(app_handle: AppHandle) {
                     if let Some(candidate) =
                         crate::snippets::detect_completed_shortcode_at_end(&current, &trigger)
                     {
-                        if !merged.contains_key(&candidate) {
+                        if !crate::snippets::is_known_shortcode(&candidate, &rules, &merged) {
                             let _ = app.emit_to(
                                 crate::SPOTLIGHT_LABEL,
                                 "shortcode-miss",
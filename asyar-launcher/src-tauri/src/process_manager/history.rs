@@ -0,0 +1,446 @@
+//! Sliding-window resource history per app group, and the threshold rules
+//! evaluated against it. Pure: the sampler hands in timestamps and per-app
+//! totals, so windowing, trends and alert firing are unit-tested without a
+//! live system.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+/// One app group's totals at one sampler tick. Not sent over IPC.
+#[derive(Debug, Clone)]
+pub struct AppTotals {
+    pub app_name: String,
+    pub cpu: f32,
+    pub memory_bytes: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceSample {
+    /// Unix millis.
+    pub at: i64,
+    /// Summed over the group; 100 is one full core.
+    pub cpu: f32,
+    pub memory_bytes: u64,
+}
+
+/// Sparkline data for one app, oldest sample first.
+#[derive(Debug, Clone, PartialEq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceSeries {
+    pub app_name: String,
+    pub samples: Vec<ResourceSample>,
+}
+
+/// One app's usage summarised over a time range.
+#[derive(Debug, Clone, PartialEq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceTrend {
+    pub app_name: String,
+    pub sample_count: u32,
+    pub avg_cpu: f32,
+    pub peak_cpu: f32,
+    pub avg_memory_bytes: u64,
+    pub peak_memory_bytes: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum ResourceMetric {
+    Cpu,
+    Memory,
+}
+
+impl ResourceMetric {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ResourceMetric::Cpu => "cpu",
+            ResourceMetric::Memory => "memory",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "cpu" => Some(ResourceMetric::Cpu),
+            "memory" => Some(ResourceMetric::Memory),
+            _ => None,
+        }
+    }
+
+    fn of(self, sample: &ResourceSample) -> f64 {
+        match self {
+            ResourceMetric::Cpu => f64::from(sample.cpu),
+            ResourceMetric::Memory => sample.memory_bytes as f64,
+        }
+    }
+
+    fn of_trend(self, trend: &ResourceTrend) -> f64 {
+        match self {
+            ResourceMetric::Cpu => f64::from(trend.avg_cpu),
+            ResourceMetric::Memory => trend.avg_memory_bytes as f64,
+        }
+    }
+}
+
+/// "Tell me when an app stays above `threshold` for `sustained_secs`."
+/// Ids are chosen by the owner and unique per owner, so saving the same id
+/// again replaces the rule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceAlert {
+    pub id: String,
+    /// The app group to watch; `None` watches every app.
+    #[serde(default)]
+    pub app_name: Option<String>,
+    pub metric: ResourceMetric,
+    /// Percent (100 = one core) for `cpu`, bytes for `memory`.
+    pub threshold: f64,
+    pub sustained_secs: u32,
+    pub enabled: bool,
+    /// Command of the owning extension that the notification's "Show"
+    /// action runs, with `{ appName, alertId }` as arguments.
+    #[serde(default)]
+    pub command_id: Option<String>,
+}
+
+/// Per-app ring buffers holding the last `window_ms` of samples.
+#[derive(Debug)]
+pub struct ResourceHistory {
+    window_ms: i64,
+    /// Samples further apart than this belong to separate runs of the
+    /// sampler, so a sustained breach can't be bridged across a pause.
+    max_gap_ms: i64,
+    series: HashMap<String, VecDeque<ResourceSample>>,
+}
+
+impl ResourceHistory {
+    pub fn new(window_ms: i64, max_gap_ms: i64) -> Self {
+        Self {
+            window_ms,
+            max_gap_ms,
+            series: HashMap::new(),
+        }
+    }
+
+    /// Append one tick and drop whatever fell out of the window, including
+    /// apps that haven't been seen for a whole window.
+    pub fn record(&mut self, at: i64, totals: &[AppTotals]) {
+        for t in totals {
+            self.series
+                .entry(t.app_name.clone())
+                .or_default()
+                .push_back(ResourceSample {
+                    at,
+                    cpu: t.cpu,
+                    memory_bytes: t.memory_bytes,
+                });
+        }
+        let cutoff = at - self.window_ms;
+        self.series.retain(|_, samples| {
+            while samples.front().is_some_and(|s| s.at < cutoff) {
+                samples.pop_front();
+            }
+            !samples.is_empty()
+        });
+    }
+
+    /// Samples at or after `since` for each requested app, in request order.
+    /// Unknown apps come back with an empty series.
+    pub fn series(&self, app_names: &[String], since: i64) -> Vec<ResourceSeries> {
+        app_names
+            .iter()
+            .map(|name| ResourceSeries {
+                app_name: name.clone(),
+                samples: self
+                    .series
+                    .get(name)
+                    .map(|s| s.iter().filter(|x| x.at >= since).copied().collect())
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// The `limit` heaviest apps since `since`, by average of `metric`.
+    pub fn top(&self, metric: ResourceMetric, since: i64, limit: usize) -> Vec<ResourceTrend> {
+        let mut trends: Vec<ResourceTrend> = self
+            .series
+            .iter()
+            .filter_map(|(name, samples)| trend(name, samples.iter().filter(|s| s.at >= since)))
+            .collect();
+        trends.sort_by(|a, b| {
+            metric
+                .of_trend(b)
+                .partial_cmp(&metric.of_trend(a))
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.app_name.cmp(&b.app_name))
+        });
+        trends.truncate(limit);
+        trends
+    }
+
+    /// Apps that `alert` currently breaches, with their latest value: sampled
+    /// at `now`, and above the threshold on every sample for at least
+    /// `sustained_secs` without a gap.
+    pub fn breaching(&self, alert: &ResourceAlert, now: i64) -> Vec<(String, f64)> {
+        let sustained_ms = i64::from(alert.sustained_secs) * 1000;
+        let mut apps: Vec<(String, f64)> = self
+            .series
+            .iter()
+            .filter(|(name, _)| alert.app_name.as_ref().is_none_or(|want| want == *name))
+            .filter_map(|(name, samples)| {
+                let latest = samples.back()?;
+                let start = over_since(samples, alert.metric, alert.threshold, self.max_gap_ms)?;
+                (latest.at == now && now - start >= sustained_ms)
+                    .then(|| (name.clone(), alert.metric.of(latest)))
+            })
+            .collect();
+        apps.sort_by(|a, b| a.0.cmp(&b.0));
+        apps
+    }
+}
+
+/// Start of the unbroken over-threshold run that ends with the newest
+/// sample, if the newest sample is over at all.
+fn over_since(
+    samples: &VecDeque<ResourceSample>,
+    metric: ResourceMetric,
+    threshold: f64,
+    max_gap_ms: i64,
+) -> Option<i64> {
+    let mut start = None;
+    let mut prev_at = samples.back()?.at;
+    for s in samples.iter().rev() {
+        if metric.of(s) <= threshold || prev_at - s.at > max_gap_ms {
+            break;
+        }
+        start = Some(s.at);
+        prev_at = s.at;
+    }
+    start
+}
+
+fn trend<'a>(
+    app_name: &str,
+    samples: impl Iterator<Item = &'a ResourceSample>,
+) -> Option<ResourceTrend> {
+    let mut count = 0u32;
+    let (mut cpu_sum, mut peak_cpu) = (0f64, 0f32);
+    let (mut mem_sum, mut peak_mem) = (0u128, 0u64);
+    for s in samples {
+        count += 1;
+        cpu_sum += f64::from(s.cpu);
+        peak_cpu = peak_cpu.max(s.cpu);
+        mem_sum += u128::from(s.memory_bytes);
+        peak_mem = peak_mem.max(s.memory_bytes);
+    }
+    if count == 0 {
+        return None;
+    }
+    Some(ResourceTrend {
+        app_name: app_name.to_string(),
+        sample_count: count,
+        avg_cpu: (cpu_sum / f64::from(count)) as f32,
+        peak_cpu,
+        avg_memory_bytes: (mem_sum / u128::from(count)) as u64,
+        peak_memory_bytes: peak_mem,
+    })
+}
+
+/// Remembers which (alert, app) pairs are already breaching, so each breach
+/// notifies once and re-arms only after it clears.
+#[derive(Debug)]
+pub struct AlertTracker<K> {
+    firing: HashSet<K>,
+}
+
+impl<K> Default for AlertTracker<K> {
+    fn default() -> Self {
+        Self {
+            firing: HashSet::new(),
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone> AlertTracker<K> {
+    /// Replace the breaching set with `current`; returns the pairs that
+    /// weren't breaching on the previous tick.
+    pub fn advance(&mut self, current: HashSet<K>) -> Vec<K> {
+        let fresh = current
+            .iter()
+            .filter(|k| !self.firing.contains(*k))
+            .cloned()
+            .collect();
+        self.firing = current;
+        fresh
+    }
+
+    pub fn clear(&mut self) {
+        self.firing.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    fn totals(app: &str, cpu: f32, memory_bytes: u64) -> AppTotals {
+        AppTotals {
+            app_name: app.into(),
+            cpu,
+            memory_bytes,
+        }
+    }
+
+    fn names(breaching: Vec<(String, f64)>) -> Vec<String> {
+        breaching.into_iter().map(|(name, _)| name).collect()
+    }
+
+    fn memory_alert(app: Option<&str>, threshold: u64, sustained_secs: u32) -> ResourceAlert {
+        ResourceAlert {
+            id: "mem".into(),
+            app_name: app.map(Into::into),
+            metric: ResourceMetric::Memory,
+            threshold: threshold as f64,
+            sustained_secs,
+            enabled: true,
+            command_id: None,
+        }
+    }
+
+    #[test]
+    fn record_drops_samples_and_apps_outside_the_window() {
+        let mut h = ResourceHistory::new(10_000, 5_000);
+        h.record(0, &[totals("old", 1.0, 1), totals("kept", 1.0, 1)]);
+        h.record(8_000, &[totals("kept", 2.0, 2)]);
+        h.record(12_000, &[totals("kept", 3.0, 3)]);
+
+        let series = h.series(&["kept".into(), "old".into()], 0);
+        assert_eq!(
+            series[0].samples.iter().map(|s| s.at).collect::<Vec<_>>(),
+            [8_000, 12_000]
+        );
+        assert!(series[1].samples.is_empty(), "old app aged out entirely");
+    }
+
+    #[test]
+    fn series_respects_since() {
+        let mut h = ResourceHistory::new(60_000, 5_000);
+        for at in [0, 5_000, 10_000] {
+            h.record(at, &[totals("a", 1.0, 1)]);
+        }
+        let series = h.series(&["a".into()], 5_000);
+        assert_eq!(series[0].samples.len(), 2);
+    }
+
+    #[test]
+    fn top_ranks_by_average_and_reports_peaks() {
+        let mut h = ResourceHistory::new(60_000, 5_000);
+        h.record(
+            0,
+            &[totals("spiky", 100.0, GB), totals("steady", 40.0, 2 * GB)],
+        );
+        h.record(
+            5_000,
+            &[totals("spiky", 0.0, GB), totals("steady", 40.0, 2 * GB)],
+        );
+
+        let by_cpu = h.top(ResourceMetric::Cpu, 0, 10);
+        assert_eq!(by_cpu[0].app_name, "spiky");
+        assert_eq!(by_cpu[0].avg_cpu, 50.0);
+        assert_eq!(by_cpu[0].peak_cpu, 100.0);
+        assert_eq!(by_cpu[0].sample_count, 2);
+
+        let by_memory = h.top(ResourceMetric::Memory, 0, 1);
+        assert_eq!(by_memory.len(), 1);
+        assert_eq!(by_memory[0].app_name, "steady");
+        assert_eq!(by_memory[0].avg_memory_bytes, 2 * GB);
+    }
+
+    #[test]
+    fn top_only_counts_samples_since() {
+        let mut h = ResourceHistory::new(60_000, 5_000);
+        h.record(0, &[totals("earlier", 90.0, 0)]);
+        h.record(5_000, &[totals("recent", 10.0, 0)]);
+        let top = h.top(ResourceMetric::Cpu, 5_000, 10);
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].app_name, "recent");
+    }
+
+    #[test]
+    fn alert_needs_the_whole_sustained_period_over_threshold() {
+        let mut h = ResourceHistory::new(600_000, 10_000);
+        let alert = memory_alert(Some("chrome"), 4 * GB, 10);
+        h.record(0, &[totals("chrome", 0.0, 5 * GB)]);
+        h.record(5_000, &[totals("chrome", 0.0, 5 * GB)]);
+        assert!(h.breaching(&alert, 5_000).is_empty(), "only 5s so far");
+        h.record(10_000, &[totals("chrome", 0.0, 6 * GB)]);
+        assert_eq!(
+            h.breaching(&alert, 10_000),
+            [("chrome".to_string(), (6 * GB) as f64)]
+        );
+    }
+
+    #[test]
+    fn a_dip_below_threshold_restarts_the_clock() {
+        let mut h = ResourceHistory::new(600_000, 10_000);
+        let alert = memory_alert(None, 4 * GB, 10);
+        h.record(0, &[totals("a", 0.0, 5 * GB)]);
+        h.record(5_000, &[totals("a", 0.0, 3 * GB)]);
+        h.record(10_000, &[totals("a", 0.0, 5 * GB)]);
+        h.record(15_000, &[totals("a", 0.0, 5 * GB)]);
+        assert!(h.breaching(&alert, 15_000).is_empty());
+        h.record(20_000, &[totals("a", 0.0, 5 * GB)]);
+        assert_eq!(names(h.breaching(&alert, 20_000)), ["a"]);
+    }
+
+    #[test]
+    fn a_gap_in_sampling_restarts_the_clock() {
+        let mut h = ResourceHistory::new(600_000, 10_000);
+        let alert = memory_alert(None, 4 * GB, 10);
+        h.record(0, &[totals("a", 0.0, 5 * GB)]);
+        h.record(60_000, &[totals("a", 0.0, 5 * GB)]);
+        assert!(h.breaching(&alert, 60_000).is_empty());
+    }
+
+    #[test]
+    fn an_app_that_exited_no_longer_breaches() {
+        let mut h = ResourceHistory::new(600_000, 10_000);
+        let alert = memory_alert(None, 4 * GB, 0);
+        h.record(0, &[totals("gone", 0.0, 5 * GB)]);
+        h.record(5_000, &[totals("other", 0.0, 0)]);
+        assert!(h.breaching(&alert, 5_000).is_empty());
+    }
+
+    #[test]
+    fn alert_scoped_to_an_app_ignores_the_others() {
+        let mut h = ResourceHistory::new(600_000, 10_000);
+        let mut alert = memory_alert(Some("a"), 0, 0);
+        alert.metric = ResourceMetric::Cpu;
+        alert.threshold = 50.0;
+        h.record(0, &[totals("a", 10.0, 0), totals("b", 90.0, 0)]);
+        assert!(h.breaching(&alert, 0).is_empty());
+        alert.app_name = None;
+        assert_eq!(names(h.breaching(&alert, 0)), ["b"]);
+    }
+
+    #[test]
+    fn tracker_fires_once_per_breach_and_rearms_after_it_clears() {
+        let mut t = AlertTracker::default();
+        let key = || ("mem".to_string(), "chrome".to_string());
+        assert_eq!(t.advance(HashSet::from([key()])), [key()]);
+        assert!(t.advance(HashSet::from([key()])).is_empty());
+        assert!(t.advance(HashSet::new()).is_empty());
+        assert_eq!(t.advance(HashSet::from([key()])), [key()]);
+    }
+
+    #[test]
+    fn metric_round_trips_through_its_storage_name() {
+        for m in [ResourceMetric::Cpu, ResourceMetric::Memory] {
+            assert_eq!(ResourceMetric::parse(m.as_str()), Some(m));
+        }
+        assert_eq!(ResourceMetric::parse("disk"), None);
+    }
+}
@@ -5,7 +5,9 @@
 //! pure and unit-tested here; live enumeration is a thin `sysinfo` shim.
 
 pub mod grouping;
+pub mod history;
 pub mod protected;
+pub mod sampler;
 pub mod types;
 
 use crate::process_manager::grouping::{filter_groups, group, sort_groups};
@@ -0,0 +1,679 @@
+//! Background resource sampling behind per-app trends and threshold alerts.
+//!
+//! Runs as the `process-sampler` scheduler job only while something needs
+//! it: a caller holds a sampling lease (typically while its process view is
+//! open) or an enabled alert exists. Leases lapse after [`LEASE_TTL`] unless
+//! taken again, so a view that goes away without giving its lease back
+//! doesn't keep the sampler running. A tick is one `sysinfo` refresh of a
+//! `System` kept between ticks — the previous tick is the CPU-delta
+//! baseline, so unlike [`super::list`] there's no sleep — folded into
+//! per-app totals and appended to a [`ResourceHistory`].
+
+use crate::error::AppError;
+use crate::notifications::backend::{
+    populate_registry_and_send, BackendAction, NotificationBackend, NotificationRequest,
+};
+use crate::notifications::NotificationActionRegistry;
+use crate::process_manager::grouping::app_name_for;
+use crate::process_manager::history::{
+    AlertTracker, AppTotals, ResourceAlert, ResourceHistory, ResourceMetric, ResourceSeries,
+    ResourceTrend,
+};
+use crate::process_manager::types::RawProcess;
+use crate::process_manager::CURRENT_OS;
+use crate::scheduler::{Job, Scheduler};
+use crate::storage::process_alerts::{self, StoredAlert};
+use crate::storage::DataStore;
+use log::warn;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+const JOB_ID: &str = "process-sampler";
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+/// How far back series, trends and alerts can look.
+pub const WINDOW_MINUTES: u32 = 30;
+/// A longer silence means sampling was paused; a breach doesn't span it.
+const MAX_GAP_MILLIS: i64 = 3 * SAMPLE_INTERVAL.as_millis() as i64;
+/// How long a lease lasts without being taken again.
+pub const LEASE_TTL: Duration = Duration::from_secs(60);
+const MAX_ALERTS_PER_OWNER: usize = 32;
+const MAX_ALERT_ID_LEN: usize = 128;
+
+/// Whoever holds a lease or owns an alert: an extension, or `None` for the
+/// launcher itself.
+type Owner = Option<String>;
+
+/// One breach that wasn't breaching on the previous tick.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Breach {
+    pub extension_id: Owner,
+    pub alert: ResourceAlert,
+    pub app_name: String,
+    /// The app's latest value, in the alert metric's unit.
+    pub value: f64,
+}
+
+struct SamplerState {
+    /// `None` while paused, so an idle launcher doesn't hold a process table.
+    system: Option<sysinfo::System>,
+    history: ResourceHistory,
+    /// Each holder's lease expiry, in epoch millis.
+    leases: HashMap<Owner, i64>,
+    alerts: Vec<StoredAlert>,
+    /// Keyed by (owner, alert id, app name).
+    tracker: AlertTracker<(Owner, String, String)>,
+}
+
+/// Managed state. Cheap to clone; clones share the history.
+#[derive(Clone)]
+pub struct ProcessSampler {
+    store: DataStore,
+    state: Arc<Mutex<SamplerState>>,
+}
+
+impl ProcessSampler {
+    /// Loads saved alerts; sampling itself waits for [`Self::sync_schedule`].
+    pub fn new(store: DataStore) -> Self {
+        let alerts = store
+            .conn()
+            .and_then(|conn| process_alerts::list_all(&conn))
+            .unwrap_or_else(|e| {
+                warn!("[process] failed to load resource alerts: {e}");
+                Vec::new()
+            });
+        Self {
+            store,
+            state: Arc::new(Mutex::new(SamplerState {
+                system: None,
+                history: ResourceHistory::new(i64::from(WINDOW_MINUTES) * 60_000, MAX_GAP_MILLIS),
+                leases: HashMap::new(),
+                alerts,
+                tracker: AlertTracker::default(),
+            })),
+        }
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, SamplerState>, AppError> {
+        self.state.lock().map_err(|_| AppError::Lock)
+    }
+
+    /// Whether the sampler job should be running.
+    pub fn is_active(&self) -> bool {
+        self.lock()
+            .map(|s| !s.leases.is_empty() || s.alerts.iter().any(|a| a.alert.enabled))
+            .unwrap_or(false)
+    }
+
+    /// Idempotent per owner: one lease each, however many times it's taken.
+    /// Taking it again pushes its expiry to [`LEASE_TTL`] past `now`.
+    pub fn acquire(&self, owner: Owner, now: i64) -> Result<(), AppError> {
+        let expires_at = now + LEASE_TTL.as_millis() as i64;
+        self.lock()?.leases.insert(owner, expires_at);
+        Ok(())
+    }
+
+    pub fn release(&self, owner: &Owner) -> Result<(), AppError> {
+        self.lock()?.leases.remove(owner);
+        Ok(())
+    }
+
+    /// Drop leases that expired by `now`; returns whether any did.
+    pub fn expire_leases(&self, now: i64) -> Result<bool, AppError> {
+        let mut state = self.lock()?;
+        let before = state.leases.len();
+        state.leases.retain(|_, expires_at| *expires_at > now);
+        Ok(state.leases.len() != before)
+    }
+
+    pub fn alerts(&self, owner: &Owner) -> Result<Vec<ResourceAlert>, AppError> {
+        Ok(self
+            .lock()?
+            .alerts
+            .iter()
+            .filter(|a| &a.extension_id == owner)
+            .map(|a| a.alert.clone())
+            .collect())
+    }
+
+    /// Validate, persist, then replace the owner's alert with the same id.
+    pub fn save_alert(&self, owner: Owner, alert: ResourceAlert) -> Result<(), AppError> {
+        validate_alert(&alert)?;
+        let mut state = self.lock()?;
+        let existing = state
+            .alerts
+            .iter()
+            .position(|a| a.extension_id == owner && a.alert.id == alert.id);
+        if existing.is_none()
+            && state
+                .alerts
+                .iter()
+                .filter(|a| a.extension_id == owner)
+                .count()
+                >= MAX_ALERTS_PER_OWNER
+        {
+            return Err(AppError::Validation(format!(
+                "At most {MAX_ALERTS_PER_OWNER} resource alerts may be saved at once."
+            )));
+        }
+        let conn = self.store.conn()?;
+        process_alerts::upsert(&conn, owner.as_deref(), &alert)?;
+        let stored = StoredAlert {
+            extension_id: owner,
+            alert,
+        };
+        match existing {
+            Some(i) => state.alerts[i] = stored,
+            None => state.alerts.push(stored),
+        }
+        Ok(())
+    }
+
+    /// Returns whether the owner had an alert with that id.
+    pub fn delete_alert(&self, owner: &Owner, id: &str) -> Result<bool, AppError> {
+        let mut state = self.lock()?;
+        let conn = self.store.conn()?;
+        let removed = process_alerts::delete(&conn, owner.as_deref(), id)?;
+        state
+            .alerts
+            .retain(|a| !(&a.extension_id == owner && a.alert.id == id));
+        Ok(removed)
+    }
+
+    /// Drop an extension's lease and delete its alerts; returns how many
+    /// alerts went. Uninstall and disable both call this.
+    pub fn release_extension(&self, extension_id: &str) -> Result<usize, AppError> {
+        let owner = Some(extension_id.to_string());
+        let mut state = self.lock()?;
+        state.leases.remove(&owner);
+        state.alerts.retain(|a| a.extension_id != owner);
+        let conn = self.store.conn()?;
+        process_alerts::delete_for_extension(&conn, extension_id)
+    }
+
+    /// Sparkline series for `app_names` over the last `minutes`.
+    pub fn series(
+        &self,
+        app_names: &[String],
+        minutes: u32,
+        now: i64,
+    ) -> Result<Vec<ResourceSeries>, AppError> {
+        Ok(self.lock()?.history.series(app_names, since(now, minutes)))
+    }
+
+    /// The `limit` heaviest apps by `metric` over the last `minutes`.
+    pub fn top(
+        &self,
+        metric: ResourceMetric,
+        minutes: u32,
+        limit: usize,
+        now: i64,
+    ) -> Result<Vec<ResourceTrend>, AppError> {
+        Ok(self.lock()?.history.top(metric, since(now, minutes), limit))
+    }
+
+    /// Append one tick's totals and evaluate every enabled alert against it.
+    /// Returns only breaches that are new since the previous tick.
+    pub fn record(&self, now: i64, totals: &[AppTotals]) -> Result<Vec<Breach>, AppError> {
+        let mut state = self.lock()?;
+        state.history.record(now, totals);
+
+        let mut found = HashMap::new();
+        for stored in state.alerts.iter().filter(|a| a.alert.enabled) {
+            for (app_name, value) in state.history.breaching(&stored.alert, now) {
+                found.insert(
+                    (
+                        stored.extension_id.clone(),
+                        stored.alert.id.clone(),
+                        app_name.clone(),
+                    ),
+                    Breach {
+                        extension_id: stored.extension_id.clone(),
+                        alert: stored.alert.clone(),
+                        app_name,
+                        value,
+                    },
+                );
+            }
+        }
+        let current = found.keys().cloned().collect();
+        let mut fresh: Vec<Breach> = state
+            .tracker
+            .advance(current)
+            .into_iter()
+            .filter_map(|key| found.remove(&key))
+            .collect();
+        fresh.sort_by(|a, b| (&a.alert.id, &a.app_name).cmp(&(&b.alert.id, &b.app_name)));
+        Ok(fresh)
+    }
+
+    /// One live tick. Refreshes outside the lock so queries from the view
+    /// never wait on the process scan.
+    pub fn sample(&self) -> Result<Vec<Breach>, AppError> {
+        let mut system = self
+            .lock()?
+            .system
+            .take()
+            .unwrap_or_else(sysinfo::System::new);
+        refresh(&mut system);
+        let totals = totals_by_app(&raw_processes(&system));
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let breaches = self.record(now, &totals)?;
+        self.lock()?.system = Some(system);
+        Ok(breaches)
+    }
+
+    /// Start or stop the sampler job to match [`Self::is_active`]. Call after
+    /// anything that changes leases or alerts.
+    pub fn sync_schedule(&self, app: &AppHandle) {
+        let Some(scheduler) = app.try_state::<Scheduler>() else {
+            return;
+        };
+        if self.is_active() {
+            if scheduler.job(JOB_ID).is_none() {
+                scheduler.register(job(self.clone(), app.clone()));
+            }
+        } else if !scheduler.unregister_prefix(JOB_ID).is_empty() {
+            self.pause();
+        }
+    }
+
+    /// Free the process table and forget what was firing. History is kept:
+    /// reopening the view shows the gap rather than an empty chart.
+    fn pause(&self) {
+        if let Ok(mut state) = self.lock() {
+            state.system = None;
+            state.tracker.clear();
+        }
+    }
+}
+
+fn since(now: i64, minutes: u32) -> i64 {
+    now - i64::from(minutes.clamp(1, WINDOW_MINUTES)) * 60_000
+}
+
+fn validate_alert(alert: &ResourceAlert) -> Result<(), AppError> {
+    if alert.id.trim().is_empty() || alert.id.len() > MAX_ALERT_ID_LEN {
+        return Err(AppError::Validation(format!(
+            "Alert id must be 1-{MAX_ALERT_ID_LEN} characters."
+        )));
+    }
+    if alert
+        .app_name
+        .as_deref()
+        .is_some_and(|n| n.trim().is_empty())
+    {
+        return Err(AppError::Validation(
+            "Alert appName must not be empty; omit it to watch every app.".to_string(),
+        ));
+    }
+    if !alert.threshold.is_finite() || alert.threshold <= 0.0 {
+        return Err(AppError::Validation(format!(
+            "Alert threshold must be a positive number, got {}.",
+            alert.threshold
+        )));
+    }
+    if alert.sustained_secs > WINDOW_MINUTES * 60 {
+        return Err(AppError::Validation(format!(
+            "Alert sustainedSecs may be at most {} ({WINDOW_MINUTES} minutes).",
+            WINDOW_MINUTES * 60
+        )));
+    }
+    Ok(())
+}
+
+/// CPU and memory only; exe paths are fetched once per process because the
+/// macOS bundle name, and so the group, comes from them.
+fn refresh(system: &mut sysinfo::System) {
+    use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, UpdateKind};
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        ProcessRefreshKind::new()
+            .with_cpu()
+            .with_memory()
+            .with_exe(UpdateKind::OnlyIfNotSet),
+    );
+}
+
+/// Sampling needs less than [`super::raw_processes`]: owners aren't part of
+/// the group key, so the user table is never loaded.
+fn raw_processes(system: &sysinfo::System) -> Vec<RawProcess> {
+    system
+        .processes()
+        .values()
+        .map(|p| RawProcess {
+            pid: p.pid().as_u32(),
+            parent_pid: None,
+            name: p.name().to_string_lossy().to_string(),
+            cpu_percent: p.cpu_usage(),
+            memory_bytes: p.memory(),
+            exe_path: p
+                .exe()
+                .map(|e| e.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            owner: String::new(),
+        })
+        .collect()
+}
+
+/// Per-app sums, grouped exactly like the list view so names line up.
+fn totals_by_app(raw: &[RawProcess]) -> Vec<AppTotals> {
+    let mut by_app: HashMap<String, AppTotals> = HashMap::new();
+    for p in raw {
+        let app_name = app_name_for(CURRENT_OS, p);
+        let totals = by_app.entry(app_name.clone()).or_insert(AppTotals {
+            app_name,
+            cpu: 0.0,
+            memory_bytes: 0,
+        });
+        totals.cpu += p.cpu_percent;
+        totals.memory_bytes += p.memory_bytes;
+    }
+    by_app.into_values().collect()
+}
+
+/// Scheduler job: sample immediately, then every [`SAMPLE_INTERVAL`], and
+/// notify about new breaches. Lapsed leases are dropped each tick; when that
+/// leaves nothing to sample for, the job unregisters itself.
+fn job(sampler: ProcessSampler, app: AppHandle) -> Job {
+    Job::fixed_interval(JOB_ID, Duration::ZERO, SAMPLE_INTERVAL, move || {
+        let sampler = sampler.clone();
+        let app = app.clone();
+        async move {
+            if sampler.expire_leases(chrono::Utc::now().timestamp_millis())? {
+                sampler.sync_schedule(&app);
+                if !sampler.is_active() {
+                    return Ok(());
+                }
+            }
+            let breaches = tauri::async_runtime::spawn_blocking(move || sampler.sample())
+                .await
+                .map_err(|e| AppError::Other(format!("process sampler task failed: {e}")))??;
+            notify(&app, &breaches);
+            Ok::<(), AppError>(())
+        }
+    })
+}
+
+fn notify(app: &AppHandle, breaches: &[Breach]) {
+    if breaches.is_empty() {
+        return;
+    }
+    let (Some(registry), Some(backend)) = (
+        app.try_state::<Arc<NotificationActionRegistry>>(),
+        app.try_state::<Arc<dyn NotificationBackend>>(),
+    ) else {
+        return;
+    };
+    for breach in breaches {
+        let request = build_alert_notification(breach);
+        if let Err(e) =
+            populate_registry_and_send(registry.inner(), backend.inner().as_ref(), request)
+        {
+            warn!("[process] failed to send resource alert: {e}");
+        }
+    }
+}
+
+/// "Google Chrome is using 4.3 GB of memory" / "Above 4.0 GB for 5 minutes."
+pub fn build_alert_notification(breach: &Breach) -> NotificationRequest {
+    let alert = &breach.alert;
+    let title = format!(
+        "{} is using {}",
+        breach.app_name,
+        format_metric(alert.metric, breach.value)
+    );
+    let threshold = format_metric(alert.metric, alert.threshold);
+    let body = match alert.sustained_secs {
+        0 => format!("Above {threshold}."),
+        secs => format!("Above {threshold} for {}.", format_duration(secs)),
+    };
+    // Only an extension has commands for the action to run.
+    let actions = match (&breach.extension_id, &alert.command_id) {
+        (Some(_), Some(command_id)) => vec![BackendAction {
+            id: "show".to_string(),
+            title: "Show".to_string(),
+            command_id: command_id.clone(),
+            args_json: Some(
+                serde_json::json!({
+                    "arguments": {
+                        "appName": breach.app_name,
+                        "alertId": alert.id
+                    }
+                })
+                .to_string(),
+            ),
+        }],
+        _ => Vec::new(),
+    };
+    NotificationRequest {
+        notification_id: format!("notif_{}", uuid::Uuid::new_v4()),
+        title,
+        body,
+        actions,
+        extension_id: breach
+            .extension_id
+            .clone()
+            .unwrap_or_else(|| "process".to_string()),
+    }
+}
+
+fn format_metric(metric: ResourceMetric, value: f64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    const GB: f64 = MB * 1024.0;
+    match metric {
+        ResourceMetric::Cpu => format!("{value:.0}% CPU"),
+        ResourceMetric::Memory if value >= GB => format!("{:.1} GB of memory", value / GB),
+        ResourceMetric::Memory => format!("{:.0} MB of memory", value / MB),
+    }
+}
+
+fn format_duration(secs: u32) -> String {
+    let plural = |n: u32, unit: &str| format!("{n} {unit}{}", if n == 1 { "" } else { "s" });
+    if secs % 60 == 0 {
+        plural(secs / 60, "minute")
+    } else {
+        plural(secs, "second")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::create_test_store;
+
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    fn sampler() -> ProcessSampler {
+        ProcessSampler::new(create_test_store())
+    }
+
+    fn memory_alert(id: &str, threshold: u64, sustained_secs: u32) -> ResourceAlert {
+        ResourceAlert {
+            id: id.into(),
+            app_name: None,
+            metric: ResourceMetric::Memory,
+            threshold: threshold as f64,
+            sustained_secs,
+            enabled: true,
+            command_id: Some("show-processes".into()),
+        }
+    }
+
+    fn totals(app: &str, memory_bytes: u64) -> AppTotals {
+        AppTotals {
+            app_name: app.into(),
+            cpu: 0.0,
+            memory_bytes,
+        }
+    }
+
+    #[test]
+    fn active_while_a_lease_or_an_enabled_alert_exists() {
+        let s = sampler();
+        assert!(!s.is_active());
+        s.acquire(Some("ext.a".into()), 0).unwrap();
+        s.acquire(Some("ext.a".into()), 0).unwrap();
+        assert!(s.is_active());
+        s.release(&Some("ext.a".into())).unwrap();
+        assert!(!s.is_active(), "a repeated acquire is still one lease");
+
+        let mut alert = memory_alert("mem", GB, 0);
+        alert.enabled = false;
+        s.save_alert(None, alert.clone()).unwrap();
+        assert!(!s.is_active());
+        alert.enabled = true;
+        s.save_alert(None, alert).unwrap();
+        assert!(s.is_active());
+    }
+
+    #[test]
+    fn alerts_survive_a_restart_and_stay_per_owner() {
+        let store = create_test_store();
+        let s = ProcessSampler::new(store.clone());
+        s.save_alert(Some("ext.a".into()), memory_alert("mem", GB, 60))
+            .unwrap();
+
+        let reloaded = ProcessSampler::new(store);
+        assert_eq!(reloaded.alerts(&Some("ext.a".into())).unwrap().len(), 1);
+        assert!(reloaded.alerts(&Some("ext.b".into())).unwrap().is_empty());
+        assert!(!reloaded.delete_alert(&None, "mem").unwrap());
+        assert!(reloaded.delete_alert(&Some("ext.a".into()), "mem").unwrap());
+    }
+
+    #[test]
+    fn invalid_alerts_are_rejected() {
+        let s = sampler();
+        let mut cases = vec![
+            memory_alert(" ", GB, 0),
+            memory_alert("mem", 0, 0),
+            memory_alert("mem", GB, WINDOW_MINUTES * 60 + 1),
+        ];
+        let mut blank_app = memory_alert("mem", GB, 0);
+        blank_app.app_name = Some(String::new());
+        cases.push(blank_app);
+        for alert in cases {
+            assert!(matches!(
+                s.save_alert(None, alert),
+                Err(AppError::Validation(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn alert_count_is_capped_per_owner() {
+        let s = sampler();
+        let owner = Some("ext.a".to_string());
+        for i in 0..MAX_ALERTS_PER_OWNER {
+            s.save_alert(owner.clone(), memory_alert(&format!("a{i}"), GB, 0))
+                .unwrap();
+        }
+        assert!(s
+            .save_alert(owner.clone(), memory_alert("one-more", GB, 0))
+            .is_err());
+        // Replacing an existing id isn't a new alert.
+        s.save_alert(owner, memory_alert("a0", 2 * GB, 0)).unwrap();
+        s.save_alert(None, memory_alert("host", GB, 0)).unwrap();
+    }
+
+    #[test]
+    fn record_reports_each_breach_once() {
+        let s = sampler();
+        s.save_alert(Some("ext.a".into()), memory_alert("mem", 4 * GB, 5))
+            .unwrap();
+
+        assert!(s.record(0, &[totals("chrome", 5 * GB)]).unwrap().is_empty());
+        let fired = s.record(5_000, &[totals("chrome", 5 * GB)]).unwrap();
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].app_name, "chrome");
+        assert_eq!(fired[0].extension_id.as_deref(), Some("ext.a"));
+        assert!(s
+            .record(10_000, &[totals("chrome", 5 * GB)])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn leases_lapse_unless_taken_again() {
+        let s = sampler();
+        let ttl = LEASE_TTL.as_millis() as i64;
+        s.acquire(Some("ext.a".into()), 0).unwrap();
+        s.acquire(None, 0).unwrap();
+        assert!(!s.expire_leases(ttl - 1).unwrap());
+
+        s.acquire(Some("ext.a".into()), ttl - 1).unwrap();
+        assert!(s.expire_leases(ttl).unwrap(), "the launcher's lease lapsed");
+        assert!(s.is_active(), "the refreshed lease is still held");
+
+        assert!(s.expire_leases(2 * ttl).unwrap());
+        assert!(!s.is_active());
+    }
+
+    #[test]
+    fn release_extension_drops_its_lease_and_alerts() {
+        let s = sampler();
+        s.acquire(Some("ext.a".into()), 0).unwrap();
+        s.save_alert(Some("ext.a".into()), memory_alert("mem", GB, 0))
+            .unwrap();
+        s.save_alert(Some("ext.b".into()), memory_alert("mem", GB, 0))
+            .unwrap();
+
+        assert_eq!(s.release_extension("ext.a").unwrap(), 1);
+        assert!(s.alerts(&Some("ext.a".into())).unwrap().is_empty());
+        assert_eq!(s.alerts(&Some("ext.b".into())).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn queries_clamp_minutes_to_the_window() {
+        let s = sampler();
+        s.record(0, &[totals("a", GB)]).unwrap();
+        let now = i64::from(WINDOW_MINUTES) * 60_000;
+        s.record(now, &[totals("a", GB)]).unwrap();
+        let series = s.series(&["a".into()], u32::MAX, now).unwrap();
+        assert_eq!(series[0].samples.len(), 2);
+        let top = s.top(ResourceMetric::Memory, 0, 10, now).unwrap();
+        assert_eq!(top[0].sample_count, 1, "0 minutes reads as 1");
+    }
+
+    #[test]
+    fn notification_names_the_app_and_links_the_owner_command() {
+        let breach = Breach {
+            extension_id: Some("ext.a".into()),
+            alert: memory_alert("mem", 4 * GB, 300),
+            app_name: "Google Chrome".into(),
+            value: (4 * GB + GB / 2) as f64,
+        };
+        let req = build_alert_notification(&breach);
+        assert_eq!(req.title, "Google Chrome is using 4.5 GB of memory");
+        assert_eq!(req.body, "Above 4.0 GB of memory for 5 minutes.");
+        assert_eq!(req.extension_id, "ext.a");
+        assert_eq!(req.actions[0].command_id, "show-processes");
+
+        let host = Breach {
+            extension_id: None,
+            ..breach
+        };
+        assert!(build_alert_notification(&host).actions.is_empty());
+    }
+
+    #[test]
+    fn totals_group_like_the_list_view() {
+        let proc = |pid, name: &str, mem| RawProcess {
+            pid,
+            parent_pid: None,
+            name: name.into(),
+            cpu_percent: 1.5,
+            memory_bytes: mem,
+            exe_path: String::new(),
+            owner: String::new(),
+        };
+        let mut totals = totals_by_app(&[proc(1, "a", 10), proc(2, "a", 5), proc(3, "b", 1)]);
+        totals.sort_by(|x, y| x.app_name.cmp(&y.app_name));
+        assert_eq!(totals[0].app_name, "a");
+        assert_eq!(totals[0].memory_bytes, 15);
+        assert_eq!(totals[0].cpu, 3.0);
+        assert_eq!(totals.len(), 2);
+    }
+}
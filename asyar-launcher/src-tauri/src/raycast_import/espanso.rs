@@ -0,0 +1,378 @@
+//! Espanso match-file parsing.
+//!
+//! Espanso keeps snippets in YAML files (`match/base.yml` plus any number of
+//! siblings). Each file has a `matches` list and an optional `global_vars`
+//! list; a match's `replace` text refers to variables as `{{name}}`, looked
+//! up in the match's own `vars` first and then in `global_vars` from any
+//! file (Espanso's own resolution order).
+//!
+//! Only variable types with an Asyar placeholder equivalent are translated.
+//! Everything else is left in the text literally and counted in
+//! [`SkippedCounts::placeholders`]; matches Asyar cannot represent at all
+//! (images, forms, HTML) are dropped and counted in
+//! [`SkippedCounts::snippets`].
+
+use super::{ImportBundle, ImportSnippet, SkippedCounts, SourceFormat};
+use crate::error::AppError;
+use crate::snippets::{trigger_regex, SnippetExpandMode, SnippetTriggerKind};
+use serde_json::Value;
+
+/// Cheap content sniff used by [`super::parse_export`]: valid UTF-8 YAML
+/// whose top level is a map with `matches` or `global_vars`.
+pub(super) fn parse_document(bytes: &[u8]) -> Option<Value> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    let value: Value = serde_yaml_ng::from_str(text).ok()?;
+    let obj = value.as_object()?;
+    (obj.contains_key("matches") || obj.contains_key("global_vars")).then_some(value)
+}
+
+/// Parse one or more Espanso match files into a single bundle. Global vars
+/// are collected across all files before any match is translated.
+pub fn parse_files(files: &[Vec<u8>]) -> Result<ImportBundle, AppError> {
+    let mut documents = Vec::with_capacity(files.len());
+    for bytes in files {
+        let text = std::str::from_utf8(bytes)
+            .map_err(|_| AppError::Validation("Espanso file is not valid UTF-8".to_string()))?;
+        let value: Value = serde_yaml_ng::from_str(text)
+            .map_err(|e| AppError::Validation(format!("Invalid Espanso YAML: {e}")))?;
+        documents.push(value);
+    }
+    Ok(bundle_from_documents(&documents))
+}
+
+pub(super) fn bundle_from_documents(documents: &[Value]) -> ImportBundle {
+    let mut bundle = ImportBundle {
+        source: SourceFormat::Espanso,
+        snippets: Vec::new(),
+        portals: Vec::new(),
+        shortcuts: Vec::new(),
+        aliases: Vec::new(),
+        skipped: SkippedCounts::default(),
+    };
+
+    let global_vars: Vec<&Value> = documents
+        .iter()
+        .filter_map(|d| d.get("global_vars").and_then(|v| v.as_array()))
+        .flatten()
+        .collect();
+
+    for doc in documents {
+        let Some(matches) = doc.get("matches").and_then(|v| v.as_array()) else {
+            continue;
+        };
+        for m in matches {
+            translate_match(m, &global_vars, &mut bundle);
+        }
+    }
+    bundle
+}
+
+fn translate_match(m: &Value, global_vars: &[&Value], bundle: &mut ImportBundle) {
+    // `replace` is the plain-text body; `markdown` is imported as its source
+    // text. Image, form and HTML matches have no Asyar representation.
+    let Some(body) = m
+        .get("replace")
+        .or_else(|| m.get("markdown"))
+        .and_then(|v| v.as_str())
+    else {
+        bundle.skipped.snippets += 1;
+        return;
+    };
+
+    let local_vars: Vec<&Value> = m
+        .get("vars")
+        .and_then(|v| v.as_array())
+        .map(|a| a.iter().collect())
+        .unwrap_or_default();
+
+    let expand_mode = if m.get("word").and_then(|v| v.as_bool()) == Some(true) {
+        SnippetExpandMode::Delimiter
+    } else {
+        SnippetExpandMode::Immediate
+    };
+
+    let (keywords, trigger, regex_groups) =
+        if let Some(regex) = m.get("regex").and_then(|v| v.as_str()) {
+            (
+                vec![translate_regex(regex)],
+                SnippetTriggerKind::Regex,
+                named_groups(regex),
+            )
+        } else {
+            let mut keywords: Vec<String> = Vec::new();
+            if let Some(t) = m.get("trigger").and_then(|v| v.as_str()) {
+                keywords.push(t.to_string());
+            }
+            if let Some(ts) = m.get("triggers").and_then(|v| v.as_array()) {
+                keywords.extend(ts.iter().filter_map(|t| t.as_str()).map(str::to_string));
+            }
+            (keywords, SnippetTriggerKind::Literal, Vec::new())
+        };
+    if keywords.is_empty() {
+        bundle.skipped.snippets += 1;
+        return;
+    }
+    // Espanso's engine accepts constructs the `regex` crate does not
+    // (lookaround, backreferences); such a trigger could never fire here.
+    if trigger == SnippetTriggerKind::Regex && trigger_regex(&keywords[0]).is_err() {
+        bundle.skipped.snippets += 1;
+        return;
+    }
+
+    let mut untranslated = 0u32;
+    let expansion = translate_body(
+        body,
+        &local_vars,
+        global_vars,
+        &regex_groups,
+        &mut untranslated,
+    );
+    bundle.skipped.placeholders += untranslated;
+
+    let label = m.get("label").and_then(|v| v.as_str());
+    for keyword in keywords {
+        bundle.snippets.push(ImportSnippet {
+            name: label.map(str::to_string).unwrap_or_else(|| keyword.clone()),
+            keyword: Some(keyword),
+            expansion: expansion.clone(),
+            pinned: false,
+            created_at: None,
+            trigger,
+            expand_mode,
+        });
+    }
+}
+
+/// Espanso regex triggers use Python-style `(?P<name>...)` groups, which the
+/// `regex` crate accepts verbatim; only the end anchor needs stripping since
+/// Asyar anchors every regex trigger itself.
+fn translate_regex(regex: &str) -> String {
+    regex.strip_suffix('$').unwrap_or(regex).to_string()
+}
+
+fn named_groups(regex: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = regex;
+    while let Some(start) = rest.find("(?P<").or_else(|| rest.find("(?<")) {
+        let after = &rest[start..];
+        let open = after.find('<').map(|i| i + 1).unwrap_or(after.len());
+        let Some(close) = after[open..].find('>') else {
+            break;
+        };
+        names.push(after[open..open + close].to_string());
+        rest = &after[open + close..];
+    }
+    names
+}
+
+/// Rewrite `{{var}}` references and the `$|$` cursor hint.
+fn translate_body(
+    body: &str,
+    local_vars: &[&Value],
+    global_vars: &[&Value],
+    regex_groups: &[String],
+    untranslated: &mut u32,
+) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut rest = body;
+    loop {
+        let next_var = rest.find("{{");
+        let next_cursor = rest.find("$|$");
+        match (next_var, next_cursor) {
+            (Some(v), c) if c.is_none_or(|c| v < c) => {
+                out.push_str(&rest[..v]);
+                let after = &rest[v + 2..];
+                let Some(end) = after.find("}}") else {
+                    out.push_str(&rest[v..]);
+                    return out;
+                };
+                let name = after[..end].trim();
+                match resolve_var(name, local_vars, global_vars, regex_groups) {
+                    Some(replacement) => out.push_str(&replacement),
+                    None => {
+                        *untranslated += 1;
+                        out.push_str(&rest[v..v + 2 + end + 2]);
+                    }
+                }
+                rest = &after[end + 2..];
+            }
+            (_, Some(c)) => {
+                // Asyar has no cursor-position concept; drop the hint.
+                out.push_str(&rest[..c]);
+                *untranslated += 1;
+                rest = &rest[c + 3..];
+            }
+            _ => {
+                out.push_str(rest);
+                return out;
+            }
+        }
+    }
+}
+
+fn resolve_var(
+    name: &str,
+    local_vars: &[&Value],
+    global_vars: &[&Value],
+    regex_groups: &[String],
+) -> Option<String> {
+    if regex_groups.iter().any(|g| g == name) {
+        return Some(format!("${{{name}}}"));
+    }
+    let var = local_vars
+        .iter()
+        .chain(global_vars.iter())
+        .find(|v| v.get("name").and_then(|n| n.as_str()) == Some(name))?;
+    let params = var.get("params");
+    match var.get("type").and_then(|t| t.as_str())? {
+        "clipboard" => Some("{Clipboard Text}".to_string()),
+        "date" => {
+            let format = params
+                .and_then(|p| p.get("format"))
+                .and_then(|f| f.as_str())
+                .unwrap_or("%x");
+            Some(date_token_for_strftime(format).to_string())
+        }
+        "echo" => params
+            .and_then(|p| p.get("echo"))
+            .and_then(|e| e.as_str())
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
+/// Map a strftime format onto the closest fixed Asyar date token. Asyar's
+/// date placeholders are not configurable, so the layout is approximated.
+pub(super) fn date_token_for_strftime(format: &str) -> &'static str {
+    let mut date = false;
+    let mut time = false;
+    let mut weekday = false;
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        match chars.next() {
+            Some('A' | 'a' | 'u' | 'w') => weekday = true,
+            Some('Y' | 'y' | 'm' | 'd' | 'e' | 'B' | 'b' | 'h' | 'j' | 'D' | 'F' | 'x') => {
+                date = true
+            }
+            Some('H' | 'I' | 'M' | 'S' | 'p' | 'P' | 'T' | 'R' | 'r' | 'X' | 'l' | 'k') => {
+                time = true
+            }
+            Some('c') => {
+                date = true;
+                time = true;
+            }
+            _ => {}
+        }
+    }
+    match (date, time, weekday) {
+        (true, true, _) => "{Date & Time}",
+        (false, true, _) => "{Time}",
+        (false, false, true) => "{Weekday}",
+        _ => "{Date}",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE_YML: &[u8] = include_bytes!("fixtures/espanso_base.yml");
+
+    #[test]
+    fn parses_triggers_vars_and_cursor() {
+        let b = parse_files(&[BASE_YML.to_vec()]).unwrap();
+        assert_eq!(b.source, SourceFormat::Espanso);
+
+        let sig = b.snippets.iter().find(|s| s.name == "Signature").unwrap();
+        assert_eq!(sig.keyword.as_deref(), Some(":sig"));
+        assert_eq!(sig.expansion, "Best,\nJane\n");
+        assert_eq!(sig.expand_mode, SnippetExpandMode::Delimiter);
+
+        let date = b
+            .snippets
+            .iter()
+            .find(|s| s.keyword.as_deref() == Some(":date"))
+            .unwrap();
+        assert_eq!(date.expansion, "Today is {Date}");
+
+        let paste = b
+            .snippets
+            .iter()
+            .find(|s| s.keyword.as_deref() == Some(":paste"))
+            .unwrap();
+        // Cursor hint dropped, clipboard var from global_vars translated.
+        assert_eq!(paste.expansion, "<{Clipboard Text}>");
+    }
+
+    #[test]
+    fn multiple_triggers_fan_out_into_separate_snippets() {
+        let b = parse_files(&[BASE_YML.to_vec()]).unwrap();
+        let hello: Vec<_> = b
+            .snippets
+            .iter()
+            .filter(|s| s.expansion == "Hello there")
+            .filter_map(|s| s.keyword.as_deref())
+            .collect();
+        assert_eq!(hello, vec![":hi", ":hello"]);
+    }
+
+    #[test]
+    fn regex_match_becomes_regex_snippet_with_named_capture() {
+        let b = parse_files(&[BASE_YML.to_vec()]).unwrap();
+        let greet = b
+            .snippets
+            .iter()
+            .find(|s| s.trigger == SnippetTriggerKind::Regex)
+            .unwrap();
+        assert_eq!(greet.keyword.as_deref(), Some(":greet\\((?P<person>.*)\\)"));
+        assert_eq!(greet.expansion, "Hi ${person}!");
+    }
+
+    #[test]
+    fn regex_the_engine_cannot_compile_is_skipped() {
+        let yml = br#"
+matches:
+  - regex: "(?<=\\s)btw"
+    replace: "by the way"
+  - regex: ":n(?P<n>\\d+)"
+    replace: "number ${n}"
+"#;
+        let b = parse_files(&[yml.to_vec()]).unwrap();
+        assert_eq!(b.skipped.snippets, 1);
+        assert_eq!(b.snippets.len(), 1);
+        assert_eq!(b.snippets[0].keyword.as_deref(), Some(":n(?P<n>\\d+)"));
+    }
+
+    #[test]
+    fn untranslatable_features_are_counted() {
+        let b = parse_files(&[BASE_YML.to_vec()]).unwrap();
+        // image match + form match
+        assert_eq!(b.skipped.snippets, 2);
+        // shell var + cursor hint
+        assert_eq!(b.skipped.placeholders, 2);
+        let shell = b
+            .snippets
+            .iter()
+            .find(|s| s.keyword.as_deref() == Some(":ip"))
+            .unwrap();
+        assert_eq!(shell.expansion, "IP: {{output}}");
+    }
+
+    #[test]
+    fn strftime_formats_map_to_closest_token() {
+        assert_eq!(date_token_for_strftime("%Y-%m-%d"), "{Date}");
+        assert_eq!(date_token_for_strftime("%H:%M"), "{Time}");
+        assert_eq!(date_token_for_strftime("%d/%m/%Y %H:%M"), "{Date & Time}");
+        assert_eq!(date_token_for_strftime("%A"), "{Weekday}");
+    }
+
+    #[test]
+    fn detection_requires_matches_or_global_vars() {
+        assert!(parse_document(BASE_YML).is_some());
+        assert!(parse_document(b"foo: bar\n").is_none());
+        assert!(parse_document(&[0xff, 0xfe, 0x00]).is_none());
+    }
+}
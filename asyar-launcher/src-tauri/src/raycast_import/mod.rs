@@ -1,4 +1,4 @@
-//! Raycast export parsing for the "Import from Raycast" built-in feature.
+//! Export parsing for the "Import from Raycast" built-in feature.
 //!
 //! Supported inputs:
 //! - Raycast X `.rayconfig` (gzip → JSON envelope → hex payload, optionally
@@ -6,12 +6,18 @@
 //! - Classic Raycast 1.x `.rayconfig` (gzip JSON, or 16-byte IV +
 //!   AES-256-CBC with a sha256(password) key wrapping the gzip stream)
 //! - Plain JSON files from Raycast's "Export Snippets" / "Export Quicklinks"
+//! - Espanso YAML match files (see [`espanso`])
+//! - TextExpander `.textexpander` snippet groups (see [`textexpander`])
 //!
 //! Everything returned is normalized into an [`ImportBundle`] of items ready
 //! to insert through Asyar's existing snippet/portal/shortcut paths.
 
+pub mod espanso;
+pub mod textexpander;
+
 use crate::error::AppError;
 use crate::search_engine::models::Application;
+use crate::snippets::{SnippetExpandMode, SnippetTriggerKind};
 use serde::{Deserialize, Serialize};
 
 /// A snippet candidate, already translated to Asyar placeholder tokens.
@@ -26,6 +32,12 @@ pub struct ImportSnippet {
     /// Epoch milliseconds parsed from the export's ISO timestamp, when present.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub created_at: Option<f64>,
+    /// Espanso `regex` matches import as regex-triggered snippets.
+    #[serde(default)]
+    pub trigger: SnippetTriggerKind,
+    /// Espanso `word: true` matches wait for a delimiter.
+    #[serde(default)]
+    pub expand_mode: SnippetExpandMode,
 }
 
 /// A portal candidate (Raycast quicklink), URL translated to `{query}` tokens.
@@ -95,6 +107,16 @@ pub struct SkippedCounts {
     /// (must be 1-10 lowercase letters/digits), plus app aliases whose
     /// application is not present in Asyar's index.
     pub aliases: u32,
+    /// Snippets with no text body Asyar can expand: Espanso image, form and
+    /// HTML matches, TextExpander script snippets.
+    #[serde(default)]
+    pub snippets: u32,
+    /// Variables and macros with no Asyar placeholder equivalent (Espanso
+    /// shell/script/choice vars, TextExpander fill-ins, cursor markers).
+    /// They are left in the expansion text, except cursor markers, which
+    /// are dropped.
+    #[serde(default)]
+    pub placeholders: u32,
 }
 
 /// Which file format the parser detected.
@@ -105,6 +127,8 @@ pub enum SourceFormat {
     RayconfigClassic,
     SnippetsJson,
     QuicklinksJson,
+    Espanso,
+    TextExpander,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -131,8 +155,14 @@ pub enum ParseOutcome {
     WrongPassword,
 }
 
-/// Parse any supported Raycast export file.
+/// Parse any supported export file, detecting the format from its contents.
 pub fn parse_export(bytes: &[u8], password: Option<&str>) -> Result<ParseOutcome, AppError> {
+    if textexpander::is_plist(bytes) {
+        return Ok(ParseOutcome::Ok {
+            bundle: textexpander::parse(bytes)?,
+        });
+    }
+
     if is_gzip(bytes) {
         let json = gunzip(bytes)?;
         let value: serde_json::Value = serde_json::from_slice(&json)
@@ -162,6 +192,13 @@ pub fn parse_export(bytes: &[u8], password: Option<&str>) -> Result<ParseOutcome
         ));
     }
 
+    // YAML is checked after JSON (every JSON document is also YAML).
+    if let Some(document) = espanso::parse_document(bytes) {
+        return Ok(ParseOutcome::Ok {
+            bundle: espanso::bundle_from_documents(&[document]),
+        });
+    }
+
     // Not gzip, not JSON: classic encrypted rayconfig (IV + AES-256-CBC).
     parse_classic_encrypted(bytes, password)
 }
@@ -385,6 +422,8 @@ fn bundle_from_x_categories(categories: &serde_json::Value) -> Result<ImportBund
                     .get("createdAt")
                     .and_then(|v| v.as_str())
                     .and_then(iso_to_epoch_ms),
+                trigger: SnippetTriggerKind::Literal,
+                expand_mode: SnippetExpandMode::Immediate,
             });
         }
     }
@@ -576,6 +615,8 @@ fn parse_classic(value: &serde_json::Value) -> Result<ImportBundle, AppError> {
                     .get("createdAt")
                     .and_then(|v| v.as_str())
                     .and_then(iso_to_epoch_ms),
+                trigger: SnippetTriggerKind::Literal,
+                expand_mode: SnippetExpandMode::Immediate,
             });
         }
     }
@@ -630,6 +671,8 @@ fn parse_plain_json(items: &[serde_json::Value]) -> Result<ImportBundle, AppErro
                     expansion: translate_placeholders(text, false),
                     pinned: false,
                     created_at: None,
+                    trigger: SnippetTriggerKind::Literal,
+                    expand_mode: SnippetExpandMode::Immediate,
                 })
             })
             .collect();
@@ -1049,6 +1092,20 @@ mod tests {
 
     // ---- Errors ----
 
+    #[test]
+    fn espanso_and_textexpander_files_are_detected() {
+        let yml = include_bytes!("fixtures/espanso_base.yml");
+        assert_eq!(
+            bundle(parse_export(yml, None).unwrap()).source,
+            SourceFormat::Espanso
+        );
+        let te = include_bytes!("fixtures/group.textexpander");
+        assert_eq!(
+            bundle(parse_export(te, None).unwrap()).source,
+            SourceFormat::TextExpander
+        );
+    }
+
     #[test]
     fn garbage_input_is_an_error() {
         assert!(parse_export(b"definitely not a rayconfig", None).is_err());
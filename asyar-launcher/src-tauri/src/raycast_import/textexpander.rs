@@ -0,0 +1,297 @@
+//! TextExpander `.textexpander` group parsing.
+//!
+//! A group export is a property list (XML or binary) whose `snippetsTE2`
+//! array holds one dictionary per snippet: `abbreviation`, `label`,
+//! `plainText`, `snippetType` and `creationDate`. Older exports use a
+//! `snippets` key with the same shape.
+//!
+//! `snippetType` 0 is plain text and 1 is formatted text (imported from its
+//! `plainText` rendition). Script snippets (AppleScript, shell, JavaScript)
+//! are counted in [`SkippedCounts::snippets`]. Macros with no Asyar
+//! placeholder equivalent (fill-ins, key presses, nested snippets, date
+//! math, the `%|` cursor) are counted in [`SkippedCounts::placeholders`].
+
+use super::{ImportBundle, ImportSnippet, SkippedCounts, SourceFormat};
+use crate::error::AppError;
+use crate::snippets::{SnippetExpandMode, SnippetTriggerKind};
+
+const SNIPPET_TYPE_PLAIN: i64 = 0;
+const SNIPPET_TYPE_FORMATTED: i64 = 1;
+
+/// True when `bytes` look like a property list: the binary magic, or an XML
+/// document with a `<plist` root.
+pub(super) fn is_plist(bytes: &[u8]) -> bool {
+    if bytes.starts_with(b"bplist") {
+        return true;
+    }
+    let head = &bytes[..bytes.len().min(512)];
+    std::str::from_utf8(head)
+        .is_ok_and(|s| s.trim_start().starts_with("<?xml") && s.contains("<plist"))
+}
+
+pub fn parse(bytes: &[u8]) -> Result<ImportBundle, AppError> {
+    let value = plist::Value::from_reader(std::io::Cursor::new(bytes))
+        .map_err(|e| AppError::Validation(format!("Invalid TextExpander file: {e}")))?;
+    let root = value.as_dictionary().ok_or_else(|| {
+        AppError::Validation("TextExpander file has no top-level dictionary".to_string())
+    })?;
+    let snippets = root
+        .get("snippetsTE2")
+        .or_else(|| root.get("snippets"))
+        .and_then(plist::Value::as_array)
+        .ok_or_else(|| {
+            AppError::Validation("File is not a TextExpander snippet group".to_string())
+        })?;
+
+    let mut bundle = ImportBundle {
+        source: SourceFormat::TextExpander,
+        snippets: Vec::new(),
+        portals: Vec::new(),
+        shortcuts: Vec::new(),
+        aliases: Vec::new(),
+        skipped: SkippedCounts::default(),
+    };
+
+    for entry in snippets {
+        let Some(dict) = entry.as_dictionary() else {
+            continue;
+        };
+        let snippet_type = dict
+            .get("snippetType")
+            .and_then(plist::Value::as_signed_integer)
+            .unwrap_or(SNIPPET_TYPE_PLAIN);
+        let text = dict.get("plainText").and_then(plist::Value::as_string);
+        let (SNIPPET_TYPE_PLAIN | SNIPPET_TYPE_FORMATTED, Some(text)) = (snippet_type, text) else {
+            bundle.skipped.snippets += 1;
+            continue;
+        };
+
+        let keyword = dict
+            .get("abbreviation")
+            .and_then(plist::Value::as_string)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string);
+        let name = dict
+            .get("label")
+            .and_then(plist::Value::as_string)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .or_else(|| keyword.clone())
+            .unwrap_or_else(|| "TextExpander snippet".to_string());
+        let created_at = dict
+            .get("creationDate")
+            .and_then(plist::Value::as_date)
+            .and_then(|d| {
+                std::time::SystemTime::from(d)
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .ok()
+            })
+            .map(|d| d.as_millis() as f64);
+
+        let mut untranslated = 0u32;
+        let expansion = translate_macros(text, &mut untranslated);
+        bundle.skipped.placeholders += untranslated;
+
+        bundle.snippets.push(ImportSnippet {
+            name,
+            keyword,
+            expansion,
+            pinned: false,
+            created_at,
+            trigger: SnippetTriggerKind::Literal,
+            expand_mode: SnippetExpandMode::Immediate,
+        });
+    }
+
+    Ok(bundle)
+}
+
+/// Single-letter date/time macros (`%Y`, `%m`, `%H`, ...). A run of them
+/// separated by short punctuation collapses into one Asyar date token.
+fn is_date_macro(c: char) -> bool {
+    matches!(
+        c,
+        'Y' | 'y'
+            | 'm'
+            | 'd'
+            | 'e'
+            | 'B'
+            | 'b'
+            | 'A'
+            | 'a'
+            | 'H'
+            | 'I'
+            | 'M'
+            | 'S'
+            | 'p'
+            | 'h'
+            | 'k'
+            | 'l'
+    )
+}
+
+/// Rewrite TextExpander `%` macros into Asyar placeholder tokens.
+fn translate_macros(text: &str, untranslated: &mut u32) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] != '%' || i + 1 >= chars.len() {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        let rest: String = chars[i + 1..].iter().collect();
+        if rest.starts_with("clipboard") {
+            out.push_str("{Clipboard Text}");
+            i += 1 + "clipboard".len();
+        } else if rest.starts_with('%') {
+            out.push('%');
+            i += 2;
+        } else if rest.starts_with('|') {
+            // Asyar has no cursor-position concept; drop the marker.
+            *untranslated += 1;
+            i += 2;
+        } else if is_date_macro(chars[i + 1]) {
+            let (end, format) = date_run(&chars, i);
+            out.push_str(super::espanso::date_token_for_strftime(&format));
+            i = end;
+        } else if let Some(len) = delimited_macro_len(&rest) {
+            // %fill:…%, %key:…%, %snippet:…%, %@+1D… and friends: keep the
+            // text so the user can see what was there, and report it.
+            *untranslated += 1;
+            out.push('%');
+            out.extend(&chars[i + 1..i + 1 + len]);
+            i += 1 + len;
+        } else {
+            out.push('%');
+            i += 1;
+        }
+    }
+    out
+}
+
+/// Consume a run of date macros starting at `start` (which points at `%`),
+/// allowing up to two separator characters between macros. Returns the
+/// index after the run and the run itself as a strftime string.
+fn date_run(chars: &[char], start: usize) -> (usize, String) {
+    let mut format = String::new();
+    let mut i = start;
+    loop {
+        format.push('%');
+        format.push(chars[i + 1]);
+        i += 2;
+        let mut j = i;
+        while j < chars.len() && j - i < 2 && is_date_separator(chars[j]) {
+            j += 1;
+        }
+        if j + 1 < chars.len() && chars[j] == '%' && is_date_macro(chars[j + 1]) {
+            format.extend(&chars[i..j]);
+            i = j;
+        } else {
+            return (i, format);
+        }
+    }
+}
+
+fn is_date_separator(c: char) -> bool {
+    matches!(c, '/' | '-' | '.' | ':' | ',' | ' ')
+}
+
+/// Length in chars (after the leading `%`) of a `%name:…%`-style or `%@…`
+/// macro. Chars, not bytes: the caller indexes a `Vec<char>`.
+fn delimited_macro_len(rest: &str) -> Option<usize> {
+    const NAMED: [&str; 8] = [
+        "fill",
+        "key:",
+        "snippet:",
+        "filltext",
+        "fillarea",
+        "fillpopup",
+        "fillpart",
+        "fillend",
+    ];
+    if NAMED.iter().any(|p| rest.starts_with(p)) {
+        return rest.find('%').map(|end| rest[..end].chars().count() + 1);
+    }
+    if let Some(date_math) = rest.strip_prefix('@') {
+        // %@+1D, %@-2W, ... then the date macro it modifies.
+        let len = date_math
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '+' || c == '-'))
+            .unwrap_or(date_math.len());
+        return Some(1 + len);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GROUP: &[u8] = include_bytes!("fixtures/group.textexpander");
+
+    #[test]
+    fn parses_plain_and_formatted_and_skips_scripts() {
+        assert!(is_plist(GROUP));
+        let b = parse(GROUP).unwrap();
+        assert_eq!(b.source, SourceFormat::TextExpander);
+        assert_eq!(b.snippets.len(), 3);
+        // AppleScript + shell script snippets
+        assert_eq!(b.skipped.snippets, 2);
+
+        let addr = &b.snippets[0];
+        assert_eq!(addr.name, "Address");
+        assert_eq!(addr.keyword.as_deref(), Some(";addr"));
+        assert_eq!(addr.expansion, "123 Main St");
+        // 2020-01-02T03:04:05Z
+        assert_eq!(addr.created_at, Some(1577934245000.0));
+
+        // No label: name falls back to the abbreviation.
+        assert_eq!(b.snippets[2].name, ";rich");
+    }
+
+    #[test]
+    fn translates_clipboard_dates_and_counts_the_rest() {
+        let b = parse(GROUP).unwrap();
+        let memo = &b.snippets[1];
+        assert_eq!(
+            memo.expansion,
+            "On {Date} at {Time}: {Clipboard Text} %fill:note% 100%"
+        );
+        // %fill:note% + %| cursor
+        assert_eq!(b.skipped.placeholders, 2);
+    }
+
+    #[test]
+    fn weekday_and_date_math_macros() {
+        let mut n = 0;
+        assert_eq!(translate_macros("%A", &mut n), "{Weekday}");
+        assert_eq!(translate_macros("%A, %B %e, %Y", &mut n), "{Date}");
+        assert_eq!(n, 0);
+        assert_eq!(
+            translate_macros("due %@+1D%m/%d", &mut n),
+            "due %@+1D{Date}"
+        );
+        assert_eq!(n, 1);
+    }
+
+    #[test]
+    fn non_ascii_fill_ins_are_kept_whole() {
+        let mut n = 0;
+        assert_eq!(
+            translate_macros("order %fill:café% please", &mut n),
+            "order %fill:café% please"
+        );
+        assert_eq!(
+            translate_macros("%fill:naïve crème%", &mut n),
+            "%fill:naïve crème%"
+        );
+        assert_eq!(n, 2);
+    }
+
+    #[test]
+    fn non_plist_input_is_not_detected() {
+        assert!(!is_plist(b"matches:\n  - trigger: x\n"));
+        assert!(!is_plist(b"[]"));
+    }
+}
@@ -0,0 +1,275 @@
+//! Turns the ANSI escapes in captured run output into styled spans RunView
+//! can render.
+//!
+//! Only SGR (`ESC [ … m`) affects the result: colours (16, 256 and 24-bit),
+//! bold, dim, italic, underline and strikethrough. Every other CSI and OSC
+//! sequence is dropped, as are control characters other than tab. A carriage
+//! return starts the line over, so a progress bar that redraws itself shows
+//! only its last state.
+//!
+//! Style carries from one line to the next within a call, the way a
+//! terminal would render them.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnsiStyle {
+    /// CSS hex colour, e.g. `#cd3131`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fg: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bg: Option<String>,
+    #[serde(skip_serializing_if = "is_false")]
+    pub bold: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    pub dim: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    pub italic: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    pub underline: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    pub strikethrough: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnsiSpan {
+    pub text: String,
+    #[serde(flatten)]
+    pub style: AnsiStyle,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// The xterm palette for SGR 30–37 / 90–97 and the first 16 of 256.
+const BASIC_COLORS: [&str; 16] = [
+    "#000000", "#cd3131", "#0dbc79", "#e5e510", "#2472c8", "#bc3fbc", "#11a8cd", "#e5e5e5",
+    "#666666", "#f14c4c", "#23d18b", "#f5f543", "#3b8eea", "#d670d6", "#29b8db", "#ffffff",
+];
+
+/// Styled spans for each line, in order. A line with no visible text yields
+/// an empty list.
+pub fn render_lines(lines: &[String]) -> Vec<Vec<AnsiSpan>> {
+    let mut style = AnsiStyle::default();
+    lines
+        .iter()
+        .map(|line| render_line(line, &mut style))
+        .collect()
+}
+
+/// `line` with every escape sequence and control character removed.
+pub fn strip(line: &str) -> String {
+    render_line(line, &mut AnsiStyle::default())
+        .into_iter()
+        .map(|span| span.text)
+        .collect()
+}
+
+fn render_line(line: &str, style: &mut AnsiStyle) -> Vec<AnsiSpan> {
+    let mut spans: Vec<AnsiSpan> = Vec::new();
+    let mut text = String::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                Some('[') => {
+                    let mut params = String::new();
+                    let mut final_byte = None;
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            final_byte = Some(c);
+                            break;
+                        }
+                        params.push(c);
+                    }
+                    if final_byte == Some('m') {
+                        flush(&mut spans, &mut text, style);
+                        apply_sgr(style, &params);
+                    }
+                }
+                Some(']') => {
+                    // OSC runs to BEL or ST (`ESC \`).
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\r' => {
+                if chars.peek().is_some() {
+                    spans.clear();
+                    text.clear();
+                }
+            }
+            '\t' => text.push(c),
+            c if c.is_control() => {}
+            c => text.push(c),
+        }
+    }
+    flush(&mut spans, &mut text, style);
+    spans
+}
+
+fn flush(spans: &mut Vec<AnsiSpan>, text: &mut String, style: &AnsiStyle) {
+    if text.is_empty() {
+        return;
+    }
+    match spans.last_mut() {
+        Some(last) if last.style == *style => last.text.push_str(text),
+        _ => spans.push(AnsiSpan {
+            text: text.clone(),
+            style: style.clone(),
+        }),
+    }
+    text.clear();
+}
+
+fn apply_sgr(style: &mut AnsiStyle, params: &str) {
+    // `ESC[m` is a reset; `:` is the ITU spelling of the `;` separator.
+    let codes: Vec<u32> = params
+        .split([';', ':'])
+        .map(|p| p.parse().unwrap_or(0))
+        .collect();
+    let mut i = 0;
+    while i < codes.len() {
+        match codes[i] {
+            0 => *style = AnsiStyle::default(),
+            1 => style.bold = true,
+            2 => style.dim = true,
+            3 => style.italic = true,
+            4 => style.underline = true,
+            9 => style.strikethrough = true,
+            22 => {
+                style.bold = false;
+                style.dim = false;
+            }
+            23 => style.italic = false,
+            24 => style.underline = false,
+            29 => style.strikethrough = false,
+            n @ 30..=37 => style.fg = Some(BASIC_COLORS[(n - 30) as usize].to_string()),
+            n @ 90..=97 => style.fg = Some(BASIC_COLORS[(n - 90 + 8) as usize].to_string()),
+            n @ 40..=47 => style.bg = Some(BASIC_COLORS[(n - 40) as usize].to_string()),
+            n @ 100..=107 => style.bg = Some(BASIC_COLORS[(n - 100 + 8) as usize].to_string()),
+            39 => style.fg = None,
+            49 => style.bg = None,
+            n @ (38 | 48) => {
+                let (color, used) = extended_color(&codes[i + 1..]);
+                i += used;
+                if n == 38 {
+                    style.fg = color;
+                } else {
+                    style.bg = color;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+/// Parse the tail of a `38;…` / `48;…` sequence: `5;N` or `2;R;G;B`.
+/// Returns the colour and how many codes it consumed.
+fn extended_color(rest: &[u32]) -> (Option<String>, usize) {
+    match rest {
+        [5, n, ..] => (Some(color_256(*n)), 2),
+        [2, r, g, b, ..] => (
+            Some(format!(
+                "#{:02x}{:02x}{:02x}",
+                (*r).min(255),
+                (*g).min(255),
+                (*b).min(255)
+            )),
+            4,
+        ),
+        _ => (None, rest.len()),
+    }
+}
+
+fn color_256(n: u32) -> String {
+    match n {
+        0..=15 => BASIC_COLORS[n as usize].to_string(),
+        16..=231 => {
+            let n = n - 16;
+            let level = |v: u32| if v == 0 { 0 } else { 55 + v * 40 };
+            format!(
+                "#{:02x}{:02x}{:02x}",
+                level(n / 36),
+                level((n / 6) % 6),
+                level(n % 6)
+            )
+        }
+        232..=255 => {
+            let v = 8 + (n - 232) * 10;
+            format!("#{v:02x}{v:02x}{v:02x}")
+        }
+        _ => BASIC_COLORS[7].to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(raw: &[&str]) -> Vec<String> {
+        raw.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn plain_text_is_one_unstyled_span() {
+        let rendered = render_lines(&lines(&["hello\tworld"]));
+        assert_eq!(
+            rendered,
+            vec![vec![AnsiSpan {
+                text: "hello\tworld".to_string(),
+                style: AnsiStyle::default(),
+            }]]
+        );
+    }
+
+    #[test]
+    fn sgr_codes_split_the_line_into_styled_spans() {
+        let rendered = render_lines(&lines(&["ok \x1b[1;32mPASS\x1b[0m done"]));
+        let spans = &rendered[0];
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[1].text, "PASS");
+        assert!(spans[1].style.bold);
+        assert_eq!(spans[1].style.fg.as_deref(), Some("#0dbc79"));
+        assert_eq!(spans[2].style, AnsiStyle::default());
+    }
+
+    #[test]
+    fn style_carries_across_lines_until_reset() {
+        let rendered = render_lines(&lines(&["\x1b[31mred", "still red\x1b[m", "plain"]));
+        assert_eq!(rendered[1][0].style.fg.as_deref(), Some("#cd3131"));
+        assert_eq!(rendered[2][0].style, AnsiStyle::default());
+    }
+
+    #[test]
+    fn extended_colours_resolve_to_hex() {
+        let rendered = render_lines(&lines(&[
+            "\x1b[38;5;196mA\x1b[38;5;244mB\x1b[48;2;1;2;300mC",
+        ]));
+        let spans = &rendered[0];
+        assert_eq!(spans[0].style.fg.as_deref(), Some("#ff0000"));
+        assert_eq!(spans[1].style.fg.as_deref(), Some("#808080"));
+        assert_eq!(spans[2].style.bg.as_deref(), Some("#0102ff"));
+    }
+
+    #[test]
+    fn other_escapes_and_carriage_returns_are_dropped() {
+        assert_eq!(strip("\x1b]0;title\x07\x1b[2Kabc\x1b[1Gdef"), "abcdef");
+        assert_eq!(strip("10%\r50%\r100%"), "100%");
+        assert_eq!(strip("done\r"), "done");
+        assert_eq!(strip("bell\x07"), "bell");
+    }
+}
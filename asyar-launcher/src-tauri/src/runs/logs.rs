@@ -0,0 +1,675 @@
+//! Full on-disk output logs for runs.
+//!
+//! [`super::OutputBuffer`] keeps the last lines of a run in memory for
+//! RunView; this keeps every line. While a run writes, its output goes to
+//! `<id>.log` under the run-log directory. When the run finishes the file is
+//! gzip-compressed to `<id>.log.gz` and indexed in
+//! [`crate::storage::run_logs`], which also holds the inputs the run was
+//! started with so it can be re-run.
+//!
+//! Each log stops growing at [`MAX_LOG_BYTES_PER_RUN`]; the retention job
+//! then trims the whole set to [`RETENTION_MAX_AGE_MILLIS`] and
+//! [`RETENTION_MAX_STORED_BYTES`].
+
+use crate::error::AppError;
+use crate::runs::registry::now_millis;
+use crate::storage::{run_logs, DataStore};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Uncompressed output kept per run. Anything past it is dropped and the log
+/// ends with a [`TRUNCATION_MARKER`] line.
+pub const MAX_LOG_BYTES_PER_RUN: u64 = 16 * 1024 * 1024;
+/// Finished logs older than this are deleted.
+pub const RETENTION_MAX_AGE_MILLIS: i64 = 30 * 24 * 60 * 60 * 1000;
+/// Compressed bytes kept across all finished logs; the oldest go first.
+pub const RETENTION_MAX_STORED_BYTES: i64 = 256 * 1024 * 1024;
+/// Upper bound on lines returned by one [`RunLogStore::read_page`] call.
+pub const MAX_PAGE_LINES: usize = 1_000;
+/// Upper bound on matches returned by one [`RunLogStore::search`] call.
+pub const MAX_SEARCH_MATCHES: usize = 500;
+
+pub const TRUNCATION_MARKER: &str = "[asyar] output truncated: run log reached its size cap";
+
+const RETENTION_STARTUP_DELAY: Duration = Duration::from_secs(5 * 60);
+const RETENTION_PERIOD: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Size figures for a log that was just compressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogStats {
+    pub raw_bytes: u64,
+    pub stored_bytes: u64,
+    pub line_count: u64,
+    pub truncated: bool,
+}
+
+/// One page of a run's log.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunLogPage {
+    pub run_id: String,
+    /// Zero-based line number of `lines[0]`.
+    pub offset: usize,
+    pub lines: Vec<String>,
+    /// Offset of the next page, or `None` when this page reached the end.
+    pub next_offset: Option<usize>,
+    /// `false` while the run is still writing — later pages may appear.
+    pub finished: bool,
+}
+
+/// A log line matching a search.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunLogMatch {
+    pub run_id: String,
+    /// Zero-based, usable as a [`RunLogStore::read_page`] offset.
+    pub line_number: usize,
+    pub line: String,
+}
+
+struct LiveLog {
+    file: BufWriter<File>,
+    raw_bytes: u64,
+    line_count: u64,
+    truncated: bool,
+}
+
+/// Writes, compresses, reads and deletes run log files. Cheap to clone —
+/// clones share the set of logs being written.
+#[derive(Clone)]
+pub struct RunLogStore {
+    dir: PathBuf,
+    live: Arc<Mutex<HashMap<String, LiveLog>>>,
+}
+
+impl RunLogStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            live: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Starts capturing `run_id`'s output, replacing any earlier log with
+    /// the same id.
+    pub fn open(&self, run_id: &str) -> Result<(), AppError> {
+        let (live_path, archive_path) = self.paths(run_id)?;
+        std::fs::create_dir_all(&self.dir)?;
+        remove_if_exists(&archive_path)?;
+        let file = File::create(live_path)?;
+        self.lock()?.insert(
+            run_id.to_string(),
+            LiveLog {
+                file: BufWriter::new(file),
+                raw_bytes: 0,
+                line_count: 0,
+                truncated: false,
+            },
+        );
+        Ok(())
+    }
+
+    /// Appends one line. A run that was never opened is ignored, as is
+    /// everything after the run reaches [`MAX_LOG_BYTES_PER_RUN`].
+    pub fn append(&self, run_id: &str, line: &str) -> Result<(), AppError> {
+        let mut live = self.lock()?;
+        let Some(log) = live.get_mut(run_id) else {
+            return Ok(());
+        };
+        if log.truncated {
+            return Ok(());
+        }
+        let len = line.len() as u64 + 1;
+        if log.raw_bytes + len > MAX_LOG_BYTES_PER_RUN {
+            writeln!(log.file, "{TRUNCATION_MARKER}")?;
+            log.raw_bytes += TRUNCATION_MARKER.len() as u64 + 1;
+            log.line_count += 1;
+            log.truncated = true;
+            return Ok(());
+        }
+        writeln!(log.file, "{line}")?;
+        log.raw_bytes += len;
+        log.line_count += 1;
+        Ok(())
+    }
+
+    /// Stops capturing and compresses the log. `None` when the run was never
+    /// opened.
+    pub fn finish(&self, run_id: &str) -> Result<Option<LogStats>, AppError> {
+        let Some(mut log) = self.lock()?.remove(run_id) else {
+            return Ok(None);
+        };
+        log.file.flush()?;
+        drop(log.file);
+        let (live_path, archive_path) = self.paths(run_id)?;
+        let stored_bytes = compress(&live_path, &archive_path)?;
+        Ok(Some(LogStats {
+            raw_bytes: log.raw_bytes,
+            stored_bytes,
+            line_count: log.line_count,
+            truncated: log.truncated,
+        }))
+    }
+
+    /// Compresses a log left uncompressed by a run Asyar quit during. `None`
+    /// when there is no such file, or the run is still being written.
+    pub fn recover(&self, run_id: &str) -> Result<Option<LogStats>, AppError> {
+        if self.lock()?.contains_key(run_id) {
+            return Ok(None);
+        }
+        let (live_path, archive_path) = self.paths(run_id)?;
+        if !live_path.exists() {
+            return Ok(None);
+        }
+        let mut raw_bytes = 0;
+        let mut line_count = 0;
+        let mut last_line = String::new();
+        for line in lossy_lines(BufReader::new(File::open(&live_path)?)) {
+            let line = line?;
+            raw_bytes += line.len() as u64 + 1;
+            line_count += 1;
+            last_line = line;
+        }
+        let stored_bytes = compress(&live_path, &archive_path)?;
+        Ok(Some(LogStats {
+            raw_bytes,
+            stored_bytes,
+            line_count,
+            truncated: last_line == TRUNCATION_MARKER,
+        }))
+    }
+
+    /// Reads up to `limit` lines starting at line `offset`, from the
+    /// compressed log of a finished run or the live log of a running one.
+    pub fn read_page(
+        &self,
+        run_id: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<RunLogPage, AppError> {
+        let limit = limit.clamp(1, MAX_PAGE_LINES);
+        let (live_path, archive_path) = self.paths(run_id)?;
+        let (reader, finished): (Box<dyn BufRead>, bool) = if archive_path.exists() {
+            let file = File::open(&archive_path)?;
+            (Box::new(BufReader::new(GzDecoder::new(file))), true)
+        } else {
+            if let Some(log) = self.lock()?.get_mut(run_id) {
+                log.file.flush()?;
+            }
+            match File::open(&live_path) {
+                Ok(file) => (Box::new(BufReader::new(file)), false),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    return Err(AppError::NotFound(format!("no log for run {run_id}")));
+                }
+                Err(e) => return Err(e.into()),
+            }
+        };
+
+        let mut page = Vec::with_capacity(limit);
+        let mut more = false;
+        for line in lossy_lines(reader).skip(offset).take(limit + 1) {
+            if page.len() == limit {
+                more = true;
+                break;
+            }
+            page.push(line?);
+        }
+        let next_offset = more.then_some(offset + page.len());
+        Ok(RunLogPage {
+            run_id: run_id.to_string(),
+            offset,
+            lines: page,
+            next_offset,
+            finished,
+        })
+    }
+
+    /// Case-insensitive substring search over the compressed logs of
+    /// `run_ids`, in the order given. Runs without a log are skipped.
+    pub fn search(
+        &self,
+        run_ids: &[String],
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<RunLogMatch>, AppError> {
+        let needle = query.trim().to_lowercase();
+        if needle.is_empty() {
+            return Err(AppError::Validation("search query is empty".into()));
+        }
+        let limit = limit.clamp(1, MAX_SEARCH_MATCHES);
+        let mut matches = Vec::new();
+        for run_id in run_ids {
+            let (_, archive_path) = self.paths(run_id)?;
+            let Ok(file) = File::open(&archive_path) else {
+                continue;
+            };
+            let reader = BufReader::new(GzDecoder::new(file));
+            for (line_number, line) in lossy_lines(reader).enumerate() {
+                let line = line?;
+                if !line.to_lowercase().contains(&needle) {
+                    continue;
+                }
+                matches.push(RunLogMatch {
+                    run_id: run_id.clone(),
+                    line_number,
+                    line,
+                });
+                if matches.len() == limit {
+                    return Ok(matches);
+                }
+            }
+        }
+        Ok(matches)
+    }
+
+    /// Deletes a run's log files. Unknown ids are a no-op.
+    pub fn remove(&self, run_id: &str) -> Result<(), AppError> {
+        self.lock()?.remove(run_id);
+        let (live_path, archive_path) = self.paths(run_id)?;
+        remove_if_exists(&live_path)?;
+        remove_if_exists(&archive_path)
+    }
+
+    fn paths(&self, run_id: &str) -> Result<(PathBuf, PathBuf), AppError> {
+        // Run ids come from callers (extensions included) and end up in a
+        // file name.
+        let safe = !run_id.is_empty()
+            && run_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !safe {
+            return Err(AppError::Validation(format!(
+                "run id can't name a log file: {run_id}"
+            )));
+        }
+        Ok((
+            self.dir.join(format!("{run_id}.log")),
+            self.dir.join(format!("{run_id}.log.gz")),
+        ))
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, LiveLog>>, AppError> {
+        self.live.lock().map_err(|_| AppError::Lock)
+    }
+}
+
+/// Starts capturing a run and records the inputs it was started with.
+pub fn start_run_log(
+    store: &RunLogStore,
+    conn: &rusqlite::Connection,
+    run_id: &str,
+    started_at: i64,
+    inputs: Option<&serde_json::Value>,
+) -> Result<(), AppError> {
+    store.open(run_id)?;
+    run_logs::insert_started(conn, run_id, started_at, inputs)
+}
+
+/// Compresses a finished run's log and records its size.
+pub fn finish_run_log(
+    store: &RunLogStore,
+    conn: &rusqlite::Connection,
+    run_id: &str,
+) -> Result<(), AppError> {
+    if let Some(stats) = store.finish(run_id)? {
+        index_finished(conn, run_id, stats)?;
+    }
+    Ok(())
+}
+
+/// Deletes every finished log and its index row. Logs still being written
+/// are left alone.
+pub fn clear_run_logs(store: &RunLogStore, conn: &rusqlite::Connection) -> Result<(), AppError> {
+    for record in run_logs::list_finished(conn)? {
+        store.remove(&record.run_id)?;
+        run_logs::delete(conn, &record.run_id)?;
+    }
+    Ok(())
+}
+
+/// Searches finished logs, most recently finished first.
+pub fn search_run_logs(
+    store: &RunLogStore,
+    conn: &rusqlite::Connection,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<RunLogMatch>, AppError> {
+    let ids: Vec<String> = run_logs::list_finished(conn)?
+        .into_iter()
+        .map(|r| r.run_id)
+        .collect();
+    store.search(&ids, query, limit)
+}
+
+/// Ids of the finished logs retention should delete, given `records` most
+/// recently finished first: anything finished before `now - max_age`, then
+/// the oldest until the rest fits in `max_stored_bytes`.
+pub fn expired_logs(
+    records: &[run_logs::RunLogRecord],
+    now: i64,
+    max_age_millis: i64,
+    max_stored_bytes: i64,
+) -> Vec<String> {
+    let cutoff = now - max_age_millis;
+    let mut kept_bytes = 0;
+    records
+        .iter()
+        .filter(|r| {
+            let too_old = r.finished_at.is_some_and(|at| at < cutoff);
+            kept_bytes += r.stored_bytes;
+            too_old || kept_bytes > max_stored_bytes
+        })
+        .map(|r| r.run_id.clone())
+        .collect()
+}
+
+/// One retention pass: finish logs orphaned by a quit, then delete expired
+/// ones. Returns how many logs were deleted.
+pub fn enforce_retention(
+    store: &RunLogStore,
+    conn: &rusqlite::Connection,
+    now: i64,
+) -> Result<usize, AppError> {
+    for run_id in run_logs::list_unfinished_ids(conn)? {
+        if store.lock()?.contains_key(&run_id) {
+            continue;
+        }
+        match store.recover(&run_id)? {
+            Some(stats) => index_finished(conn, &run_id, stats)?,
+            None => run_logs::delete(conn, &run_id)?,
+        }
+    }
+
+    let expired = expired_logs(
+        &run_logs::list_finished(conn)?,
+        now,
+        RETENTION_MAX_AGE_MILLIS,
+        RETENTION_MAX_STORED_BYTES,
+    );
+    for run_id in &expired {
+        store.remove(run_id)?;
+        run_logs::delete(conn, run_id)?;
+    }
+    Ok(expired.len())
+}
+
+/// Scheduler job: apply run-log retention five minutes after launch, then
+/// every six hours.
+pub fn retention_job(store: RunLogStore, db: DataStore) -> crate::scheduler::Job {
+    crate::scheduler::Job::fixed_interval(
+        "run-log-retention",
+        RETENTION_STARTUP_DELAY,
+        RETENTION_PERIOD,
+        move || {
+            let store = store.clone();
+            let db = db.clone();
+            async move {
+                let conn = db.conn()?;
+                let deleted = enforce_retention(&store, &conn, now_millis())?;
+                if deleted > 0 {
+                    log::info!("[runs] deleted {deleted} expired run logs");
+                }
+                Ok::<(), AppError>(())
+            }
+        },
+    )
+}
+
+fn index_finished(
+    conn: &rusqlite::Connection,
+    run_id: &str,
+    stats: LogStats,
+) -> Result<(), AppError> {
+    run_logs::mark_finished(
+        conn,
+        run_id,
+        now_millis(),
+        stats.raw_bytes as i64,
+        stats.stored_bytes as i64,
+        stats.line_count as i64,
+        stats.truncated,
+    )
+}
+
+/// Gzips `live` into `archive`, removes `live`, and returns the compressed
+/// size.
+fn compress(live: &Path, archive: &Path) -> Result<u64, AppError> {
+    let mut input = File::open(live)?;
+    let mut encoder = GzEncoder::new(
+        BufWriter::new(File::create(archive)?),
+        Compression::default(),
+    );
+    std::io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.flush()?;
+    std::fs::remove_file(live)?;
+    Ok(std::fs::metadata(archive)?.len())
+}
+
+fn remove_if_exists(path: &Path) -> Result<(), AppError> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Lines without their terminator; invalid UTF-8 is replaced rather than
+/// ending the read, since scripts print whatever bytes they like.
+fn lossy_lines<R: BufRead>(mut reader: R) -> impl Iterator<Item = Result<String, AppError>> {
+    std::iter::from_fn(move || {
+        let mut buf = Vec::new();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => None,
+            Ok(_) => {
+                if buf.last() == Some(&b'\n') {
+                    buf.pop();
+                }
+                Some(Ok(String::from_utf8_lossy(&buf).into_owned()))
+            }
+            Err(e) => Some(Err(e.into())),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    fn temp_store(name: &str) -> RunLogStore {
+        let dir =
+            std::env::temp_dir().join(format!("asyar_run_logs_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        RunLogStore::new(dir)
+    }
+
+    fn setup_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_logs::init_table(&conn).unwrap();
+        conn
+    }
+
+    fn record(id: &str, finished_at: i64, stored_bytes: i64) -> run_logs::RunLogRecord {
+        run_logs::RunLogRecord {
+            run_id: id.to_string(),
+            inputs: None,
+            started_at: finished_at,
+            finished_at: Some(finished_at),
+            raw_bytes: stored_bytes,
+            stored_bytes,
+            line_count: 1,
+            truncated: false,
+        }
+    }
+
+    #[test]
+    fn finished_log_is_compressed_and_paged() {
+        let store = temp_store("paged");
+        store.open("run-1").unwrap();
+        for i in 0..25 {
+            store.append("run-1", &format!("line {i}")).unwrap();
+        }
+
+        let live = store.read_page("run-1", 0, 10).unwrap();
+        assert!(!live.finished);
+        assert_eq!(live.lines.len(), 10);
+
+        let stats = store.finish("run-1").unwrap().unwrap();
+        assert_eq!(stats.line_count, 25);
+        assert!(!stats.truncated);
+        assert!(stats.stored_bytes > 0);
+        assert!(store.dir.join("run-1.log.gz").exists());
+        assert!(!store.dir.join("run-1.log").exists());
+
+        let first = store.read_page("run-1", 0, 10).unwrap();
+        assert!(first.finished);
+        assert_eq!(first.lines[0], "line 0");
+        assert_eq!(first.next_offset, Some(10));
+
+        let last = store.read_page("run-1", 20, 10).unwrap();
+        assert_eq!(
+            last.lines,
+            vec!["line 20", "line 21", "line 22", "line 23", "line 24"]
+        );
+        assert_eq!(last.next_offset, None);
+
+        let exact = store.read_page("run-1", 15, 10).unwrap();
+        assert_eq!(exact.lines.len(), 10);
+        assert_eq!(exact.next_offset, None);
+    }
+
+    #[test]
+    fn log_stops_at_the_per_run_cap() {
+        let store = temp_store("cap");
+        store.open("big").unwrap();
+        let line = "x".repeat(1024 * 1024 - 1);
+        for _ in 0..20 {
+            store.append("big", &line).unwrap();
+        }
+        let stats = store.finish("big").unwrap().unwrap();
+        assert!(stats.truncated);
+        assert!(stats.raw_bytes <= MAX_LOG_BYTES_PER_RUN + TRUNCATION_MARKER.len() as u64 + 1);
+        assert_eq!(stats.line_count, 17);
+        assert!(stats.stored_bytes < stats.raw_bytes);
+
+        let tail = store.read_page("big", 16, 10).unwrap();
+        assert_eq!(tail.lines, vec![TRUNCATION_MARKER.to_string()]);
+    }
+
+    #[test]
+    fn search_finds_lines_case_insensitively_in_order() {
+        let store = temp_store("search");
+        for (id, lines) in [
+            ("a", vec!["Build OK", "deploy FAILED: timeout"]),
+            ("b", vec!["nothing here", "retry failed again"]),
+        ] {
+            store.open(id).unwrap();
+            for line in lines {
+                store.append(id, line).unwrap();
+            }
+            store.finish(id).unwrap();
+        }
+
+        let ids = vec!["b".to_string(), "a".to_string(), "missing".to_string()];
+        let hits = store.search(&ids, "failed", 10).unwrap();
+        assert_eq!(
+            hits,
+            vec![
+                RunLogMatch {
+                    run_id: "b".into(),
+                    line_number: 1,
+                    line: "retry failed again".into(),
+                },
+                RunLogMatch {
+                    run_id: "a".into(),
+                    line_number: 1,
+                    line: "deploy FAILED: timeout".into(),
+                },
+            ]
+        );
+        assert_eq!(store.search(&ids, "failed", 1).unwrap().len(), 1);
+        assert!(store.search(&ids, "  ", 10).is_err());
+    }
+
+    #[test]
+    fn unsafe_run_ids_are_rejected() {
+        let store = temp_store("ids");
+        assert!(store.open("../escape").is_err());
+        assert!(store.read_page("a/b", 0, 10).is_err());
+        assert!(matches!(
+            store.read_page("never-opened", 0, 10),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn expired_logs_drops_old_then_oversized() {
+        let records = vec![
+            record("newest", 1_000, 40),
+            record("middle", 900, 40),
+            record("oldest-fits", 800, 10),
+            record("too-old", 100, 1),
+        ];
+        assert_eq!(
+            expired_logs(&records, 1_000, 500, 100),
+            vec!["too-old".to_string()]
+        );
+        assert_eq!(
+            expired_logs(&records, 1_000, 500, 60),
+            vec![
+                "middle".to_string(),
+                "oldest-fits".to_string(),
+                "too-old".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn retention_recovers_orphans_and_deletes_expired() {
+        let store = temp_store("retention");
+        let conn = setup_db();
+
+        start_run_log(&store, &conn, "old", 0, None).unwrap();
+        store.append("old", "done long ago").unwrap();
+        finish_run_log(&store, &conn, "old").unwrap();
+        run_logs::mark_finished(&conn, "old", 0, 14, 30, 1, false).unwrap();
+
+        // A log left behind by a quit: file on disk, row never finished.
+        start_run_log(&store, &conn, "orphan", 5, None).unwrap();
+        store.append("orphan", "half way").unwrap();
+        drop(store.lock().unwrap().remove("orphan"));
+
+        start_run_log(&store, &conn, "running", 10, None).unwrap();
+
+        let now = RETENTION_MAX_AGE_MILLIS + 1_000;
+        assert_eq!(enforce_retention(&store, &conn, now).unwrap(), 1);
+        assert_eq!(run_logs::get(&conn, "old").unwrap(), None);
+        assert!(!store.dir.join("old.log.gz").exists());
+
+        let orphan = run_logs::get(&conn, "orphan").unwrap().unwrap();
+        assert_eq!(orphan.line_count, 1);
+        assert!(orphan.finished_at.is_some());
+        assert_eq!(
+            store.read_page("orphan", 0, 10).unwrap().lines,
+            vec!["half way"]
+        );
+
+        assert_eq!(
+            run_logs::get(&conn, "running")
+                .unwrap()
+                .unwrap()
+                .finished_at,
+            None
+        );
+        clear_run_logs(&store, &conn).unwrap();
+        assert_eq!(run_logs::get(&conn, "orphan").unwrap(), None);
+        assert!(run_logs::get(&conn, "running").unwrap().is_some());
+    }
+}
@@ -1,9 +1,12 @@
+pub mod ansi;
 pub mod buckets;
+pub mod logs;
 pub mod output_buffer;
 pub mod registry;
 pub mod types;
 
 pub use buckets::{upsert_run_bucket, RunBucketKind};
+pub use logs::RunLogStore;
 pub use output_buffer::OutputBuffer;
 pub use registry::RunRegistry;
 pub use types::{Run, RunKind, RunStatus};
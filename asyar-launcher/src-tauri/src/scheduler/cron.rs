@@ -0,0 +1,370 @@
+//! Five-field cron expressions for [`super::Cadence::Cron`].
+//!
+//! `minute hour day-of-month month day-of-week`. Each field is `*`, a number,
+//! a range `a-b`, a step `*/n` or `a-b/n`, or a comma list of those. Months
+//! and weekdays also take three-letter names, and weekday 0 and 7 are both
+//! Sunday. `DOW#N` picks the Nth such weekday of the month, so `mon#1` is the
+//! first Monday. As in classic cron, when both day fields are restricted a day
+//! that matches either one fires. `@hourly`, `@daily`, `@weekly`, `@monthly` and
+//! `@yearly` are accepted as shorthands.
+
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveDate, TimeZone};
+
+use crate::error::AppError;
+
+/// How many days [`CronSchedule::next_after`] searches before giving up —
+/// enough to reach the next 29 February across a skipped century leap year.
+const SEARCH_DAYS: i64 = 366 * 9;
+
+const MONTH_NAMES: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// A parsed cron expression, one bitset per field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// `DOW#N` entries: bit `weekday * 5 + (N - 1)`.
+    nth_weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, AppError> {
+        let invalid = |reason: String| {
+            AppError::Validation(format!("Invalid cron expression '{expression}': {reason}"))
+        };
+
+        let trimmed = expression.trim();
+        let expanded = match trimmed.to_ascii_lowercase().as_str() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            _ => trimmed,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(invalid(format!("expected 5 fields, got {}", fields.len())));
+        };
+
+        let field = |raw: &str, name: &str, min: u32, max: u32, names: &[&str], first: u32| {
+            parse_field(raw, min, max, names, first).map_err(|e| invalid(format!("{name}: {e}")))
+        };
+        let minutes = field(minute, "minute", 0, 59, &[], 0)?;
+        let hours = field(hour, "hour", 0, 23, &[], 0)?;
+        let days = field(day, "day of month", 1, 31, &[], 0)?;
+        let months = field(month, "month", 1, 12, MONTH_NAMES, 1)?;
+        let (weekdays, nth_weekdays) =
+            parse_weekdays(weekday).map_err(|e| invalid(format!("day of week: {e}")))?;
+
+        let schedule = Self {
+            expression: trimmed.to_string(),
+            minutes,
+            hours,
+            days,
+            months,
+            weekdays,
+            nth_weekdays,
+            days_restricted: !day.starts_with('*'),
+            weekdays_restricted: !weekday.starts_with('*'),
+        };
+        if !schedule.can_fire() {
+            return Err(invalid("no month has that day".to_string()));
+        }
+        Ok(schedule)
+    }
+
+    /// The expression as written.
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// The first matching minute strictly after `after`, in `after`'s zone.
+    /// Wall times a daylight-saving jump skips are skipped too; a time that
+    /// occurs twice fires on its first occurrence only.
+    pub fn next_after<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let timezone = after.timezone();
+        let start = after.naive_local().date();
+        for offset in 0..SEARCH_DAYS {
+            let date = start + ChronoDuration::days(offset);
+            if !self.matches_day(date) {
+                continue;
+            }
+            for hour in bits(self.hours, 24) {
+                for minute in bits(self.minutes, 60) {
+                    let Some(naive) = date.and_hms_opt(hour, minute, 0) else {
+                        continue;
+                    };
+                    let Some(candidate) = timezone.from_local_datetime(&naive).earliest() else {
+                        continue;
+                    };
+                    if candidate > *after {
+                        return Some(candidate);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << date.day()) != 0;
+        let dow = date.weekday().num_days_from_sunday();
+        let nth = (date.day() - 1) / 7;
+        let weekday =
+            self.weekdays & (1 << dow) != 0 || self.nth_weekdays & (1 << (dow * 5 + nth)) != 0;
+        if self.days_restricted && self.weekdays_restricted {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+
+    /// Rejects day-of-month/month combinations no calendar has, like `31 2`,
+    /// which would otherwise search for years and find nothing.
+    fn can_fire(&self) -> bool {
+        if self.weekdays_restricted {
+            return true;
+        }
+        const LONGEST: [u32; 13] = [0, 31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+        bits(self.months, 13)
+            .any(|month| bits(self.days, 32).any(|day| day <= LONGEST[month as usize]))
+    }
+}
+
+fn bits(set: u64, width: u32) -> impl Iterator<Item = u32> {
+    (0..width).filter(move |bit| set & (1 << bit) != 0)
+}
+
+/// The day-of-week field as `(weekdays, nth_weekdays)`, Sunday folded to 0.
+fn parse_weekdays(raw: &str) -> Result<(u64, u64), String> {
+    let fold = |set: u64| (set & !(1 << 7)) | (set >> 7);
+    let mut plain = Vec::new();
+    let mut nth_weekdays = 0u64;
+    for item in raw.split(',') {
+        let Some((days, n)) = item.split_once('#') else {
+            plain.push(item);
+            continue;
+        };
+        let n: u32 = n
+            .parse()
+            .ok()
+            .filter(|n| (1..=5).contains(n))
+            .ok_or_else(|| format!("'#{n}' must be #1 to #5"))?;
+        let days = fold(parse_field(days, 0, 7, WEEKDAY_NAMES, 0)?);
+        for day in bits(days, 7) {
+            nth_weekdays |= 1 << (day * 5 + n - 1);
+        }
+    }
+    let weekdays = if plain.is_empty() {
+        0
+    } else {
+        fold(parse_field(&plain.join(","), 0, 7, WEEKDAY_NAMES, 0)?)
+    };
+    Ok((weekdays, nth_weekdays))
+}
+
+/// One field as a bitset: bit `n` set means value `n` matches. `names`
+/// spell the values from `first` upwards.
+fn parse_field(raw: &str, min: u32, max: u32, names: &[&str], first: u32) -> Result<u64, String> {
+    let value = |token: &str| -> Result<u32, String> {
+        let parsed = match token.parse::<u32>() {
+            Ok(n) => n,
+            Err(_) => names
+                .iter()
+                .position(|name| name.eq_ignore_ascii_case(token))
+                .map(|index| index as u32 + first)
+                .ok_or_else(|| format!("'{token}' is not a value"))?,
+        };
+        if parsed < min || parsed > max {
+            return Err(format!("{parsed} is outside {min}-{max}"));
+        }
+        Ok(parsed)
+    };
+
+    let mut set = 0u64;
+    for item in raw.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("'{step}' is not a step"))?;
+                (range, Some(step))
+            }
+            None => (item, None),
+        };
+        let (low, high) = if range == "*" {
+            (min, max)
+        } else if let Some((low, high)) = range.split_once('-') {
+            (value(low)?, value(high)?)
+        } else {
+            let single = value(range)?;
+            (single, if step.is_some() { max } else { single })
+        };
+        if low > high {
+            return Err(format!("range {low}-{high} runs backwards"));
+        }
+        for n in (low..=high).step_by(step.unwrap_or(1) as usize) {
+            set |= 1 << n;
+        }
+    }
+    Ok(set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use chrono_tz::Europe::Berlin;
+
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    fn next(expression: &str, after: DateTime<Utc>) -> DateTime<Utc> {
+        CronSchedule::parse(expression)
+            .unwrap()
+            .next_after(&after)
+            .unwrap()
+    }
+
+    // 1. Lists, ranges and steps
+    #[test]
+    fn fields_accept_lists_ranges_and_steps() {
+        let schedule = CronSchedule::parse("*/15 9-17/4 1,15 * *").unwrap();
+        assert_eq!(
+            bits(schedule.minutes, 60).collect::<Vec<_>>(),
+            [0, 15, 30, 45]
+        );
+        assert_eq!(bits(schedule.hours, 24).collect::<Vec<_>>(), [9, 13, 17]);
+        assert_eq!(bits(schedule.days, 32).collect::<Vec<_>>(), [1, 15]);
+        assert_eq!(schedule.expression(), "*/15 9-17/4 1,15 * *");
+    }
+
+    // 2. Names and Sunday as 7
+    #[test]
+    fn names_and_sunday_seven_are_understood() {
+        let named = CronSchedule::parse("0 9 * jan-mar MON-fri").unwrap();
+        let numeric = CronSchedule::parse("0 9 * 1-3 1-5").unwrap();
+        assert_eq!(named.months, numeric.months);
+        assert_eq!(named.weekdays, numeric.weekdays);
+        assert_eq!(
+            CronSchedule::parse("0 0 * * 7").unwrap().weekdays,
+            CronSchedule::parse("0 0 * * sun").unwrap().weekdays
+        );
+    }
+
+    // 3. Nth weekday of the month
+    #[test]
+    fn nth_weekday_picks_that_week_only() {
+        // The first Monday of June 2026 is the 1st, of July the 6th.
+        assert_eq!(
+            next("0 9 * * mon#1", utc(2026, 5, 20, 0, 0)),
+            utc(2026, 6, 1, 9, 0)
+        );
+        assert_eq!(
+            next("0 9 * * mon#1", utc(2026, 6, 1, 9, 0)),
+            utc(2026, 7, 6, 9, 0)
+        );
+        // Last-week Fridays need a fifth one: May 2026 has one on the 29th.
+        assert_eq!(
+            next("0 9 * * 5#5", utc(2026, 5, 1, 0, 0)),
+            utc(2026, 5, 29, 9, 0)
+        );
+        assert!(CronSchedule::parse("0 9 * * mon#6").is_err());
+    }
+
+    // 4. Malformed expressions
+    #[test]
+    fn malformed_expressions_are_validation_errors() {
+        for bad in [
+            "",
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "5-1 * * * *",
+            "*/0 * * * *",
+            "* * * foo *",
+            "0 0 31 2 *",
+        ] {
+            assert!(
+                matches!(CronSchedule::parse(bad), Err(AppError::Validation(_))),
+                "{bad:?} should be rejected"
+            );
+        }
+    }
+
+    // 5. Next fire
+    #[test]
+    fn next_after_finds_the_following_match() {
+        assert_eq!(
+            next("*/15 * * * *", utc(2026, 5, 4, 10, 7)),
+            utc(2026, 5, 4, 10, 15)
+        );
+        assert_eq!(
+            next("*/15 * * * *", utc(2026, 5, 4, 10, 15)),
+            utc(2026, 5, 4, 10, 30)
+        );
+        assert_eq!(
+            next("@daily", utc(2026, 12, 31, 23, 59)),
+            utc(2027, 1, 1, 0, 0)
+        );
+        assert_eq!(
+            next("0 12 29 2 *", utc(2026, 3, 1, 0, 0)),
+            utc(2028, 2, 29, 12, 0)
+        );
+    }
+
+    // 6. Either day field may match when both are restricted
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // 1 June 2026 is a Monday; the next Friday is the 5th.
+        let schedule = "0 8 13 * fri";
+        assert_eq!(next(schedule, utc(2026, 6, 1, 0, 0)), utc(2026, 6, 5, 8, 0));
+        assert_eq!(
+            next(schedule, utc(2026, 6, 12, 9, 0)),
+            utc(2026, 6, 13, 8, 0)
+        );
+    }
+
+    // 7. Wall-clock time in a zone, across daylight saving
+    #[test]
+    fn next_after_follows_wall_time_across_dst() {
+        let schedule = CronSchedule::parse("30 2 * * *").unwrap();
+        let at = |dt: DateTime<Utc>| schedule.next_after(&dt.with_timezone(&Berlin)).unwrap();
+
+        // 09:00 Berlin is 07:00 UTC in summer.
+        let summer = CronSchedule::parse("0 9 * * *").unwrap();
+        let fired = summer
+            .next_after(&utc(2026, 7, 1, 12, 0).with_timezone(&Berlin))
+            .unwrap();
+        assert_eq!(fired.with_timezone(&Utc), utc(2026, 7, 2, 7, 0));
+
+        // 29 March 2026 has no 02:30 in Berlin: the next one is the 30th.
+        assert_eq!(
+            at(utc(2026, 3, 28, 12, 0)).with_timezone(&Utc),
+            utc(2026, 3, 30, 0, 30)
+        );
+        // 25 October 2026 has two: only the first (summer time) fires.
+        let first = at(utc(2026, 10, 24, 12, 0));
+        assert_eq!(first.with_timezone(&Utc), utc(2026, 10, 25, 0, 30));
+        assert_eq!(
+            at(first.with_timezone(&Utc)).with_timezone(&Utc),
+            utc(2026, 10, 26, 1, 30)
+        );
+    }
+}
@@ -0,0 +1,717 @@
+//! Central scheduler for long-lived background jobs.
+//!
+//! Replaces the per-feature copy-pasted spawn-loop daemons (app-update check,
+//! shell/notification GC, extension auto-update) with one registry. A job is a
+//! stable id + a [`Cadence`] strategy + a command closure; the registry owns one
+//! supervisor task per job so they can be enumerated and cancelled from a single
+//! place. Adding a periodic job is one [`Scheduler::register`] call.
+//!
+//! Supervisors wait on the wall clock rather than a monotonic timer, so a fire
+//! that passed while the machine slept is noticed on wake. Once a store is
+//! attached, each job's last run and next fire are kept in
+//! [`crate::storage::scheduler_jobs`]: a cron job that came due while Asyar was
+//! quit is handled by its [`CatchUp`] policy at the next launch instead of
+//! being lost.
+
+pub mod cron;
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Duration as ChronoDuration, TimeZone, Utc};
+use serde::Serialize;
+use tauri::async_runtime::{self, JoinHandle};
+
+use crate::error::AppError;
+use crate::storage::scheduler_jobs::{self, JobRecord};
+use crate::storage::DataStore;
+
+pub use cron::CronSchedule;
+
+/// A fire this late still counts as on time; later than this it was missed
+/// and the job's [`CatchUp`] policy decides what happens.
+const ON_TIME_GRACE: Duration = Duration::from_secs(90);
+
+/// Longest single sleep. The wall clock is re-read between naps, so a fire
+/// that passed during system sleep runs within this long of waking; kept at a
+/// minute so idle jobs stay a negligible source of wakeups.
+const MAX_NAP: Duration = Duration::from_secs(60);
+
+/// Upper bound on back-to-back runs under [`CatchUp::RunAll`].
+pub const MAX_CATCH_UP_RUNS: u32 = 24;
+
+/// How often a job runs. A strategy — extend with new variants as new
+/// cadences are needed without touching the supervisor loop.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cadence {
+    /// Wait `startup_delay` after launch, then run every `period`.
+    FixedInterval {
+        startup_delay: Duration,
+        period: Duration,
+    },
+    /// Every match of `schedule`, read as wall-clock time in `timezone`.
+    Cron {
+        schedule: CronSchedule,
+        timezone: chrono_tz::Tz,
+    },
+    /// Once at `at`, and never again after that run has been recorded.
+    Once { at: DateTime<Utc> },
+}
+
+impl Cadence {
+    /// The first fire strictly after `after`, or `None` when nothing is left.
+    fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::FixedInterval { period, .. } => Some(after + to_chrono(*period)),
+            Self::Cron { schedule, timezone } => schedule
+                .next_after(&after.with_timezone(timezone))
+                .map(|at| at.with_timezone(&Utc)),
+            Self::Once { at } => (*at > after).then_some(*at),
+        }
+    }
+
+    /// How many fires fell between `due` and `now`, both included, counting
+    /// no further than `cap`.
+    fn fires_between(&self, due: DateTime<Utc>, now: DateTime<Utc>, cap: u32) -> u32 {
+        let mut count = 0;
+        let mut fire = Some(due);
+        while let Some(at) = fire {
+            if at > now || count >= cap {
+                break;
+            }
+            count += 1;
+            fire = self.next_after(at);
+        }
+        count
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::FixedInterval { period, .. } => format!("every {}s", period.as_secs()),
+            Self::Cron { schedule, timezone } => {
+                format!("cron '{}' ({})", schedule.expression(), timezone.name())
+            }
+            Self::Once { at } => format!("once at {}", at.to_rfc3339()),
+        }
+    }
+}
+
+/// What a job does about fires it missed because Asyar was quit or the
+/// machine was asleep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CatchUp {
+    /// Drop them and wait for the next fire.
+    Skip,
+    /// Run once, however many were missed.
+    #[default]
+    RunOnce,
+    /// Run once per missed fire, up to [`MAX_CATCH_UP_RUNS`].
+    RunAll,
+}
+
+impl CatchUp {
+    fn runs(self, missed: u32) -> u32 {
+        match self {
+            Self::Skip => 0,
+            Self::RunOnce => missed.min(1),
+            Self::RunAll => missed.min(MAX_CATCH_UP_RUNS),
+        }
+    }
+}
+
+/// What a job's work may return: `()` for work that deals with its own
+/// failures, or a `Result` whose error becomes the job's `last_error`.
+pub trait JobOutcome {
+    fn into_outcome(self) -> Result<(), String>;
+}
+
+impl JobOutcome for () {
+    fn into_outcome(self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl<E: Display> JobOutcome for Result<(), E> {
+    fn into_outcome(self) -> Result<(), String> {
+        self.map_err(|e| e.to_string())
+    }
+}
+
+type BoxFuture = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+type JobFn = Box<dyn Fn() -> BoxFuture + Send + Sync>;
+
+/// A registered background job: a stable id, a cadence, and the work to run
+/// each tick.
+pub struct Job {
+    id: String,
+    cadence: Cadence,
+    jitter: Duration,
+    catch_up: CatchUp,
+    run: JobFn,
+}
+
+impl Job {
+    fn new<F, Fut>(id: impl Into<String>, cadence: Cadence, run: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: JobOutcome,
+    {
+        Self {
+            id: id.into(),
+            cadence,
+            jitter: Duration::ZERO,
+            catch_up: CatchUp::default(),
+            run: Box::new(move || {
+                let work = run();
+                Box::pin(async move { work.await.into_outcome() })
+            }),
+        }
+    }
+
+    /// Build a fixed-interval job. `run` is invoked once per tick; it captures
+    /// whatever state the work needs (an `AppHandle`, a registry clone, …).
+    pub fn fixed_interval<F, Fut>(
+        id: impl Into<String>,
+        startup_delay: Duration,
+        period: Duration,
+        run: F,
+    ) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: JobOutcome,
+    {
+        Self::new(
+            id,
+            Cadence::FixedInterval {
+                startup_delay,
+                period,
+            },
+            run,
+        )
+    }
+
+    /// Build a job that runs on every match of the five-field cron
+    /// `expression`, read as local time in `timezone`.
+    pub fn cron<F, Fut>(
+        id: impl Into<String>,
+        expression: &str,
+        timezone: chrono_tz::Tz,
+        run: F,
+    ) -> Result<Self, AppError>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: JobOutcome,
+    {
+        let schedule = CronSchedule::parse(expression)?;
+        Ok(Self::new(id, Cadence::Cron { schedule, timezone }, run))
+    }
+
+    /// Build a job that runs once at `at`. With a store attached, a job that
+    /// already ran stays done across restarts.
+    pub fn once<F, Fut>(id: impl Into<String>, at: DateTime<Utc>, run: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: JobOutcome,
+    {
+        Self::new(id, Cadence::Once { at }, run)
+    }
+
+    /// Delay every fire by a random amount up to `jitter`, so jobs sharing a
+    /// schedule don't all wake at the same instant.
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// What to do about missed fires. Defaults to [`CatchUp::RunOnce`].
+    pub fn with_catch_up(mut self, catch_up: CatchUp) -> Self {
+        self.catch_up = catch_up;
+        self
+    }
+}
+
+/// One job's state as reported by [`get_scheduler_snapshot`]. Times are Unix
+/// millis.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobSnapshot {
+    pub id: String,
+    pub cadence: String,
+    pub next_run_at: Option<i64>,
+    pub last_run_at: Option<i64>,
+    pub last_duration_ms: Option<i64>,
+    pub last_error: Option<String>,
+    pub running: bool,
+}
+
+struct Entry {
+    handle: JoinHandle<()>,
+    state: Arc<Mutex<JobSnapshot>>,
+}
+
+/// Registry of background jobs. One supervisor task per job, tracked by id.
+#[derive(Default)]
+pub struct Scheduler {
+    tasks: Mutex<HashMap<String, Entry>>,
+    store: RwLock<Option<DataStore>>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Persist run state from now on. Call before registering jobs: a job
+    /// reads its stored state once, when it is registered.
+    pub fn attach_store(&self, store: DataStore) {
+        if let Ok(mut slot) = self.store.write() {
+            *slot = Some(store);
+        }
+    }
+
+    /// Spawn a job's supervisor. Idempotent per id: re-registering the same id
+    /// aborts the previous supervisor first.
+    pub fn register(&self, job: Job) {
+        let Job {
+            id,
+            cadence,
+            jitter,
+            catch_up,
+            run,
+        } = job;
+        let store = self.store.read().ok().and_then(|slot| slot.clone());
+        let record = store.as_ref().and_then(|store| load_record(store, &id));
+        let first = first_due(&cadence, record.as_ref(), Utc::now());
+
+        let state = Arc::new(Mutex::new(JobSnapshot {
+            id: id.clone(),
+            cadence: cadence.describe(),
+            next_run_at: first.map(|at| at.timestamp_millis()),
+            last_run_at: record.as_ref().and_then(|r| r.last_run_at),
+            last_duration_ms: record.as_ref().and_then(|r| r.last_duration_ms),
+            last_error: record.and_then(|r| r.last_error),
+            running: false,
+        }));
+        let supervisor = Supervisor {
+            id: id.clone(),
+            cadence,
+            jitter,
+            catch_up,
+            run,
+            store,
+            state: state.clone(),
+        };
+        supervisor.save_next(first);
+
+        // tauri's runtime (not raw tokio::spawn): setup_app can run before a
+        // Tokio reactor is attached.
+        let handle = async_runtime::spawn(supervisor.supervise(first));
+        if let Ok(mut tasks) = self.tasks.lock() {
+            if let Some(previous) = tasks.insert(id, Entry { handle, state }) {
+                previous.handle.abort();
+            }
+        }
+    }
+
+    /// Stop and forget every job whose id starts with `prefix`; returns the
+    /// ids removed. Persisted run state is kept, so a job registered again
+    /// under the same id picks up where it left off.
+    pub fn unregister_prefix(&self, prefix: &str) -> Vec<String> {
+        let Ok(mut tasks) = self.tasks.lock() else {
+            return Vec::new();
+        };
+        let ids: Vec<String> = tasks
+            .keys()
+            .filter(|id| id.starts_with(prefix))
+            .cloned()
+            .collect();
+        for id in &ids {
+            if let Some(entry) = tasks.remove(id) {
+                entry.handle.abort();
+            }
+        }
+        ids
+    }
+
+    /// One job's state, if a job with that id is registered.
+    pub fn job(&self, id: &str) -> Option<JobSnapshot> {
+        let tasks = self.tasks.lock().ok()?;
+        let state = tasks.get(id)?.state.lock().ok()?;
+        Some(state.clone())
+    }
+
+    /// Every registered job's state, sorted by id.
+    pub fn snapshot(&self) -> Vec<JobSnapshot> {
+        let Ok(tasks) = self.tasks.lock() else {
+            return Vec::new();
+        };
+        let mut jobs: Vec<JobSnapshot> = tasks
+            .values()
+            .filter_map(|entry| entry.state.lock().ok().map(|state| state.clone()))
+            .collect();
+        jobs.sort_unstable_by(|a, b| a.id.cmp(&b.id));
+        jobs
+    }
+}
+
+fn load_record(store: &DataStore, id: &str) -> Option<JobRecord> {
+    match store.conn().and_then(|conn| scheduler_jobs::get(&conn, id)) {
+        Ok(record) => record,
+        Err(e) => {
+            log::warn!("[scheduler] failed to load state for {id}: {e}");
+            None
+        }
+    }
+}
+
+/// When a freshly registered job first comes due. Interval jobs count from
+/// launch. A cron job resumes its stored fire if that already passed, so the
+/// catch-up policy sees what was missed. A one-shot job that already ran is
+/// done.
+fn first_due(
+    cadence: &Cadence,
+    record: Option<&JobRecord>,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    match cadence {
+        Cadence::FixedInterval { startup_delay, .. } => Some(now + to_chrono(*startup_delay)),
+        Cadence::Cron { .. } => record
+            .and_then(|r| r.next_run_at)
+            .and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+            .filter(|stored| *stored <= now)
+            .or_else(|| cadence.next_after(now)),
+        Cadence::Once { at } => {
+            let ran = record
+                .and_then(|r| r.last_run_at)
+                .is_some_and(|last| last >= at.timestamp_millis());
+            (!ran).then_some(*at)
+        }
+    }
+}
+
+/// How many times to run for a fire planned at `fire_at` (jitter included)
+/// whose schedule slot was `due`, now that the clock reads `now`.
+fn runs_for(
+    cadence: &Cadence,
+    catch_up: CatchUp,
+    due: DateTime<Utc>,
+    fire_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> u32 {
+    if now - fire_at <= to_chrono(ON_TIME_GRACE) {
+        return 1;
+    }
+    catch_up.runs(cadence.fires_between(due, now, MAX_CATCH_UP_RUNS))
+}
+
+/// Durations beyond a century are clamped so adding them to a date cannot
+/// overflow.
+fn to_chrono(duration: Duration) -> ChronoDuration {
+    let century = ChronoDuration::days(36_500);
+    ChronoDuration::from_std(duration).map_or(century, |d| d.min(century))
+}
+
+fn jitter_offset(jitter: Duration) -> ChronoDuration {
+    let max_ms = u64::try_from(jitter.as_millis()).unwrap_or(u64::MAX);
+    if max_ms == 0 {
+        return ChronoDuration::zero();
+    }
+    to_chrono(Duration::from_millis(rand::random_range(0..=max_ms)))
+}
+
+/// Sleep until the wall clock reaches `at`, in naps of at most [`MAX_NAP`].
+async fn sleep_until(at: DateTime<Utc>) {
+    while let Ok(left) = (at - Utc::now()).to_std() {
+        if left.is_zero() {
+            return;
+        }
+        tokio::time::sleep(left.min(MAX_NAP)).await;
+    }
+}
+
+struct Supervisor {
+    id: String,
+    cadence: Cadence,
+    jitter: Duration,
+    catch_up: CatchUp,
+    run: JobFn,
+    store: Option<DataStore>,
+    state: Arc<Mutex<JobSnapshot>>,
+}
+
+impl Supervisor {
+    async fn supervise(self, mut due: Option<DateTime<Utc>>) {
+        while let Some(slot) = due {
+            let fire_at = slot + jitter_offset(self.jitter);
+            sleep_until(fire_at).await;
+
+            let now = Utc::now();
+            let runs = runs_for(&self.cadence, self.catch_up, slot, fire_at, now);
+            if now - fire_at > to_chrono(ON_TIME_GRACE) {
+                log::info!(
+                    "[scheduler] {} was due at {}; catching up with {runs} run(s)",
+                    self.id,
+                    slot.to_rfc3339()
+                );
+            }
+            for _ in 0..runs {
+                self.run_once().await;
+            }
+
+            due = self.cadence.next_after(Utc::now());
+            self.save_next(due);
+        }
+    }
+
+    async fn run_once(&self) {
+        self.update(|state| state.running = true);
+        let started_at = Utc::now().timestamp_millis();
+        let clock = Instant::now();
+
+        // Its own task, so a panicking run is recorded instead of taking the
+        // supervisor down with it.
+        let outcome = match async_runtime::spawn((self.run)()).await {
+            Ok(outcome) => outcome,
+            Err(e) => Err(format!("job panicked: {e}")),
+        };
+        let duration_ms = clock.elapsed().as_millis() as i64;
+        if let Err(e) = &outcome {
+            log::warn!("[scheduler] {} failed: {e}", self.id);
+        }
+        let error = outcome.err();
+
+        if let Some(store) = &self.store {
+            let saved = store.conn().and_then(|conn| {
+                scheduler_jobs::record_run(
+                    &conn,
+                    &self.id,
+                    started_at,
+                    duration_ms,
+                    error.as_deref(),
+                )
+            });
+            if let Err(e) = saved {
+                log::warn!("[scheduler] failed to record run of {}: {e}", self.id);
+            }
+        }
+        self.update(|state| {
+            state.running = false;
+            state.last_run_at = Some(started_at);
+            state.last_duration_ms = Some(duration_ms);
+            state.last_error = error;
+        });
+    }
+
+    fn save_next(&self, next: Option<DateTime<Utc>>) {
+        let next_ms = next.map(|at| at.timestamp_millis());
+        self.update(|state| state.next_run_at = next_ms);
+        if let Some(store) = &self.store {
+            let saved = store
+                .conn()
+                .and_then(|conn| scheduler_jobs::set_next_run(&conn, &self.id, next_ms));
+            if let Err(e) = saved {
+                log::warn!("[scheduler] failed to save next run of {}: {e}", self.id);
+            }
+        }
+    }
+
+    fn update(&self, change: impl FnOnce(&mut JobSnapshot)) {
+        if let Ok(mut state) = self.state.lock() {
+            change(&mut state);
+        }
+    }
+}
+
+/// Debug/observability command: every registered background job with its
+/// next fire, last run, last duration and last error.
+#[tauri::command]
+pub fn get_scheduler_snapshot(scheduler: tauri::State<'_, Scheduler>) -> Vec<JobSnapshot> {
+    scheduler.snapshot()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A job whose first tick is an hour out, so the work never runs during a
+    /// unit test — we only assert the registry bookkeeping.
+    fn idle_job(id: &'static str) -> Job {
+        Job::fixed_interval(
+            id,
+            Duration::from_secs(3600),
+            Duration::from_secs(3600),
+            || async {},
+        )
+    }
+
+    fn ids(scheduler: &Scheduler) -> Vec<String> {
+        scheduler.snapshot().into_iter().map(|job| job.id).collect()
+    }
+
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    fn daily_nine() -> Cadence {
+        Cadence::Cron {
+            schedule: CronSchedule::parse("0 9 * * *").unwrap(),
+            timezone: chrono_tz::UTC,
+        }
+    }
+
+    #[test]
+    fn register_tracks_job_ids_sorted() {
+        let scheduler = Scheduler::new();
+        scheduler.register(idle_job("b-job"));
+        scheduler.register(idle_job("a-job"));
+        assert_eq!(ids(&scheduler), vec!["a-job", "b-job"]);
+    }
+
+    #[test]
+    fn re_registering_same_id_keeps_one_entry() {
+        let scheduler = Scheduler::new();
+        scheduler.register(idle_job("dup"));
+        scheduler.register(idle_job("dup"));
+        assert_eq!(ids(&scheduler), vec!["dup"]);
+    }
+
+    #[test]
+    fn unregister_prefix_removes_only_matching_jobs() {
+        let scheduler = Scheduler::new();
+        scheduler.register(idle_job("ext:a::one"));
+        scheduler.register(idle_job("ext:a::two"));
+        scheduler.register(idle_job("ext:b::one"));
+
+        let mut removed = scheduler.unregister_prefix("ext:a::");
+        removed.sort();
+        assert_eq!(removed, vec!["ext:a::one", "ext:a::two"]);
+        assert_eq!(ids(&scheduler), vec!["ext:b::one"]);
+        assert!(scheduler.job("ext:a::one").is_none());
+        assert!(scheduler.job("ext:b::one").is_some());
+    }
+
+    #[test]
+    fn snapshot_reports_cadence_and_next_fire() {
+        let scheduler = Scheduler::new();
+        let before = Utc::now().timestamp_millis();
+        scheduler.register(idle_job("idle"));
+        scheduler.register(
+            Job::cron("nightly", "0 3 * * *", chrono_tz::UTC, || async {
+                Ok::<(), AppError>(())
+            })
+            .unwrap(),
+        );
+
+        let jobs = scheduler.snapshot();
+        assert_eq!(jobs[0].id, "idle");
+        assert_eq!(jobs[0].cadence, "every 3600s");
+        assert!(jobs[0].next_run_at.unwrap() >= before + 3_600_000);
+        assert_eq!(jobs[1].cadence, "cron '0 3 * * *' (UTC)");
+        assert!(jobs[1].next_run_at.unwrap() > before);
+        assert_eq!(jobs[1].last_run_at, None);
+        assert!(!jobs[1].running);
+    }
+
+    #[test]
+    fn invalid_cron_expression_is_rejected_up_front() {
+        let job = Job::cron("bad", "every day", chrono_tz::UTC, || async {});
+        assert!(matches!(job, Err(AppError::Validation(_))));
+    }
+
+    #[test]
+    fn catch_up_policy_bounds_the_runs() {
+        assert_eq!(CatchUp::Skip.runs(5), 0);
+        assert_eq!(CatchUp::RunOnce.runs(5), 1);
+        assert_eq!(CatchUp::RunOnce.runs(0), 0);
+        assert_eq!(CatchUp::RunAll.runs(5), 5);
+        assert_eq!(CatchUp::RunAll.runs(100), MAX_CATCH_UP_RUNS);
+    }
+
+    #[test]
+    fn runs_for_counts_missed_fires_only_past_the_grace_window() {
+        let cadence = daily_nine();
+        let due = utc(2026, 5, 1, 9, 0);
+
+        let on_time = due + ChronoDuration::seconds(30);
+        assert_eq!(runs_for(&cadence, CatchUp::Skip, due, due, on_time), 1);
+
+        // Quit for three days: the 1st through the 4th at 09:00 were missed.
+        let later = utc(2026, 5, 4, 10, 0);
+        assert_eq!(cadence.fires_between(due, later, MAX_CATCH_UP_RUNS), 4);
+        assert_eq!(runs_for(&cadence, CatchUp::Skip, due, due, later), 0);
+        assert_eq!(runs_for(&cadence, CatchUp::RunOnce, due, due, later), 1);
+        assert_eq!(runs_for(&cadence, CatchUp::RunAll, due, due, later), 4);
+    }
+
+    #[test]
+    fn first_due_resumes_an_overdue_cron_fire() {
+        let now = utc(2026, 5, 4, 10, 0);
+        let stored = JobRecord {
+            next_run_at: Some(utc(2026, 5, 2, 9, 0).timestamp_millis()),
+            ..JobRecord::default()
+        };
+        assert_eq!(
+            first_due(&daily_nine(), Some(&stored), now),
+            Some(utc(2026, 5, 2, 9, 0))
+        );
+        assert_eq!(
+            first_due(&daily_nine(), None, now),
+            Some(utc(2026, 5, 5, 9, 0))
+        );
+    }
+
+    #[test]
+    fn first_due_skips_a_one_shot_that_already_ran() {
+        let at = utc(2026, 5, 1, 9, 0);
+        let once = Cadence::Once { at };
+        let now = utc(2026, 5, 4, 10, 0);
+        assert_eq!(first_due(&once, None, now), Some(at));
+
+        let ran = JobRecord {
+            last_run_at: Some(at.timestamp_millis() + 500),
+            ..JobRecord::default()
+        };
+        assert_eq!(first_due(&once, Some(&ran), now), None);
+        assert_eq!(once.next_after(at), None);
+    }
+
+    #[test]
+    fn runs_persist_their_outcome() {
+        let store = crate::storage::create_test_store();
+        let scheduler = Scheduler::new();
+        scheduler.attach_store(store.clone());
+        scheduler.register(Job::once("fail-once", Utc::now(), || async {
+            Err::<(), _>("disk full")
+        }));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let record = loop {
+            let record = load_record(&store, "fail-once");
+            if record.as_ref().is_some_and(|r| r.last_run_at.is_some()) {
+                break record.unwrap();
+            }
+            assert!(Instant::now() < deadline, "job never ran");
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        assert_eq!(record.last_error.as_deref(), Some("disk full"));
+        assert!(record.last_duration_ms.is_some());
+
+        let snapshot = loop {
+            let job = scheduler.snapshot().remove(0);
+            if job.next_run_at.is_none() {
+                break job;
+            }
+            assert!(Instant::now() < deadline, "one-shot never finished");
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        assert_eq!(snapshot.last_error.as_deref(), Some("disk full"));
+    }
+}
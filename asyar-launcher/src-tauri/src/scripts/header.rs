@@ -1,16 +1,17 @@
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::extensions::CommandArgument;
+use crate::extensions::{CommandArgument, CommandArgumentType, DropdownOption};
 
 /// Lowest legal refreshTime; Raycast parity.
 pub const MIN_REFRESH_TIME_SECONDS: u64 = 10;
 
 /// Execution mode declared by `# @asyar.mode <value>`. Mirrors Raycast's
 /// `mode` script directive. `Compact` is the default when no directive is
-/// present and matches today's "run-once, show subtitle on the row while
-/// active" behavior. Only `Inline` is fully wired in this iteration; the
-/// other variants are accepted and stored for forward compatibility.
+/// present and matches the "run-once, show subtitle on the row while
+/// active" behavior. `Silent` reports the last output line in a HUD,
+/// `FullOutput` opens the run's output in RunView, and `Inline` ticks on
+/// `refreshTime` (see `inline_scheduler`).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub enum ScriptMode {
@@ -21,6 +22,45 @@ pub enum ScriptMode {
     Inline,
 }
 
+/// Launcher state a script asks for with `# @asyar.env <names>`, handed to
+/// it as an environment variable when it runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScriptEnv {
+    /// `ASYAR_SELECTION` — the text selected in the frontmost app.
+    Selection,
+    /// `ASYAR_CLIPBOARD` — the clipboard's text.
+    Clipboard,
+}
+
+/// Argument JSON in Raycast's `@raycast.argumentN` shape. Keys Raycast
+/// documents that Asyar has no use for are ignored rather than rejected.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RaycastArgument {
+    #[serde(rename = "type")]
+    argument_type: RaycastArgumentType,
+    #[serde(default)]
+    placeholder: Option<String>,
+    #[serde(default)]
+    optional: bool,
+    #[serde(default)]
+    percent_encoded: bool,
+    /// Pre-`password` spelling of a masked text field.
+    #[serde(default)]
+    secure: bool,
+    #[serde(default)]
+    data: Option<Vec<DropdownOption>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum RaycastArgumentType {
+    Text,
+    Password,
+    Dropdown,
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ParsedScriptHeader {
@@ -45,6 +85,13 @@ pub struct ParsedScriptHeader {
     /// surface a one-time diagnostic to the user.
     #[serde(default)]
     pub refresh_time_clamped: bool,
+    /// Names of arguments whose value is percent-encoded before it reaches
+    /// the script (Raycast's `percentEncoded`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub percent_encoded: Vec<String>,
+    /// From `@asyar.env`, in declaration order without duplicates.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<ScriptEnv>,
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -61,9 +108,23 @@ pub enum HeaderError {
     InvalidRequireAnyOf { line: usize, message: String },
     #[error("invalid refreshTime '{value}' (expected <N><s|m|h|d>, e.g. 30s or 5m)")]
     InvalidRefreshTime { value: String },
+    #[error("invalid env value '{value}' (expected selection | clipboard)")]
+    InvalidEnv { value: String },
+}
+
+/// Strip a `@asyar.<name> ` directive, or its `@raycast.<name> ` spelling.
+fn directive<'a>(body: &'a str, name: &str) -> Option<&'a str> {
+    ["@asyar.", "@raycast."].iter().find_map(|prefix| {
+        body.strip_prefix(prefix)?
+            .strip_prefix(name)?
+            .strip_prefix(' ')
+    })
 }
 
 /// Parse Raycast-compatible script metadata headers from the top of a script file.
+/// `@raycast.*` spellings of title, icon, mode, refreshTime and `argumentN`
+/// are read alongside the `@asyar.*` ones; other Raycast directives are
+/// ignored.
 pub fn parse_header(content: &str) -> Result<ParsedScriptHeader, HeaderError> {
     let mut header = ParsedScriptHeader::default();
     let mut seen_argument_indices: std::collections::HashSet<u32> = Default::default();
@@ -87,12 +148,22 @@ pub fn parse_header(content: &str) -> Result<ParsedScriptHeader, HeaderError> {
         // Strip leading '#' and any whitespace
         let body = line.trim_start_matches('#').trim_start();
 
-        if let Some(value) = body.strip_prefix("@asyar.title ") {
+        if let Some(value) = directive(body, "title") {
             header.title = Some(value.trim().to_string());
-        } else if let Some(value) = body.strip_prefix("@asyar.icon ") {
+        } else if let Some(value) = directive(body, "icon") {
             header.icon = Some(value.trim().to_string());
-        } else if let Some(value) = body.strip_prefix("@asyar.mode ") {
+        } else if let Some(value) = directive(body, "mode") {
             header.mode = parse_mode(value.trim())?;
+        } else if let Some(value) = body.strip_prefix("@asyar.env ") {
+            for name in value.split(|c: char| c == ',' || c.is_whitespace()) {
+                if name.is_empty() {
+                    continue;
+                }
+                let env = parse_env(name)?;
+                if !header.env.contains(&env) {
+                    header.env.push(env);
+                }
+            }
         } else if let Some(value) = body.strip_prefix("@asyar.requireAnyOf ") {
             let group = serde_json::from_str::<Vec<String>>(value.trim()).map_err(|e| {
                 HeaderError::InvalidRequireAnyOf {
@@ -101,27 +172,12 @@ pub fn parse_header(content: &str) -> Result<ParsedScriptHeader, HeaderError> {
                 }
             })?;
             require_any_of = Some((line_no, group));
-        } else if let Some(value) = body.strip_prefix("@asyar.refreshTime ") {
+        } else if let Some(value) = directive(body, "refreshTime") {
             let (secs, clamped) = parse_refresh_time(value.trim())?;
             header.refresh_time_seconds = Some(secs);
             header.refresh_time_clamped = clamped;
         } else if let Some(rest) = body.strip_prefix("@asyar.argument:") {
-            // Read digits for the index
-            let digit_end = rest
-                .find(|c: char| !c.is_ascii_digit())
-                .unwrap_or(rest.len());
-            let digits = &rest[..digit_end];
-            let index: u32 = digits.parse().unwrap_or(0);
-
-            if index == 0 || index > 3 {
-                return Err(HeaderError::InvalidArgumentIndex { index });
-            }
-
-            if !seen_argument_indices.insert(index) {
-                return Err(HeaderError::DuplicateArgumentIndex { index });
-            }
-
-            let json_str = rest[digit_end..].trim_start();
+            let (index, json_str) = argument_index(rest, &mut seen_argument_indices)?;
             let parsed = serde_json::from_str::<CommandArgument>(json_str).map_err(|e| {
                 HeaderError::InvalidArgumentJson {
                     line: line_no,
@@ -130,6 +186,17 @@ pub fn parse_header(content: &str) -> Result<ParsedScriptHeader, HeaderError> {
             })?;
 
             argument_pairs.push((index, parsed));
+        } else if let Some(rest) = body.strip_prefix("@raycast.argument") {
+            let (index, json_str) = argument_index(rest, &mut seen_argument_indices)?;
+            let parsed = serde_json::from_str::<RaycastArgument>(json_str).map_err(|e| {
+                HeaderError::InvalidArgumentJson {
+                    line: line_no,
+                    message: e.to_string(),
+                }
+            })?;
+
+            let argument = raycast_argument(index, parsed, &mut header.percent_encoded);
+            argument_pairs.push((index, argument));
         }
         // Other comment content is silently ignored
     }
@@ -147,6 +214,65 @@ pub fn parse_header(content: &str) -> Result<ParsedScriptHeader, HeaderError> {
     Ok(header)
 }
 
+/// Read the `N` of an argument directive (1–3, unique per script) and
+/// return it with the JSON that follows.
+fn argument_index<'a>(
+    rest: &'a str,
+    seen: &mut std::collections::HashSet<u32>,
+) -> Result<(u32, &'a str), HeaderError> {
+    let digit_end = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let index: u32 = rest[..digit_end].parse().unwrap_or(0);
+
+    if index == 0 || index > 3 {
+        return Err(HeaderError::InvalidArgumentIndex { index });
+    }
+    if !seen.insert(index) {
+        return Err(HeaderError::DuplicateArgumentIndex { index });
+    }
+    Ok((index, rest[digit_end..].trim_start()))
+}
+
+/// Map a Raycast argument onto the launcher's argument model. Raycast
+/// arguments are positional and unnamed, so the name is `argumentN` and the
+/// placeholder is what the chip shows.
+fn raycast_argument(
+    index: u32,
+    raw: RaycastArgument,
+    percent_encoded: &mut Vec<String>,
+) -> CommandArgument {
+    let name = format!("argument{index}");
+    if raw.percent_encoded {
+        percent_encoded.push(name.clone());
+    }
+    let argument_type = match raw.argument_type {
+        RaycastArgumentType::Text if raw.secure => CommandArgumentType::Password,
+        RaycastArgumentType::Text => CommandArgumentType::Text,
+        RaycastArgumentType::Password => CommandArgumentType::Password,
+        RaycastArgumentType::Dropdown => CommandArgumentType::Dropdown,
+    };
+    CommandArgument {
+        name,
+        argument_type,
+        placeholder: raw.placeholder,
+        required: Some(!raw.optional),
+        default: None,
+        data: raw.data,
+        seed: None,
+    }
+}
+
+fn parse_env(value: &str) -> Result<ScriptEnv, HeaderError> {
+    match value {
+        "selection" => Ok(ScriptEnv::Selection),
+        "clipboard" => Ok(ScriptEnv::Clipboard),
+        other => Err(HeaderError::InvalidEnv {
+            value: other.to_string(),
+        }),
+    }
+}
+
 fn parse_mode(value: &str) -> Result<ScriptMode, HeaderError> {
     match value {
         "silent" => Ok(ScriptMode::Silent),
@@ -222,6 +348,8 @@ mod tests {
                 mode: ScriptMode::Compact,
                 refresh_time_seconds: None,
                 refresh_time_clamped: false,
+                percent_encoded: vec![],
+                env: vec![],
             }
         );
     }
@@ -455,6 +583,102 @@ mod tests {
         );
     }
 
+    // ---- Raycast compatibility ---------------------------------------------
+
+    #[test]
+    fn raycast_directives_are_read_as_aliases() {
+        let content = concat!(
+            "#!/bin/bash\n",
+            "# Required parameters:\n",
+            "# @raycast.schemaVersion 1\n",
+            "# @raycast.title Battery\n",
+            "# @raycast.mode inline\n",
+            "# @raycast.refreshTime 1m\n",
+            "# @raycast.icon 🔋\n",
+            "# @raycast.packageName System\n",
+        );
+        let result = parse_header(content).unwrap();
+        assert_eq!(result.title, Some("Battery".to_string()));
+        assert_eq!(result.icon, Some("🔋".to_string()));
+        assert_eq!(result.mode, ScriptMode::Inline);
+        assert_eq!(result.refresh_time_seconds, Some(60));
+    }
+
+    #[test]
+    fn raycast_arguments_map_onto_command_arguments() {
+        let content = concat!(
+            "# @raycast.title Search\n",
+            "# @raycast.argument1 { \"type\": \"text\", \"placeholder\": \"Query\", ",
+            "\"percentEncoded\": true }\n",
+            "# @raycast.argument2 { \"type\": \"text\", \"placeholder\": \"Token\", ",
+            "\"secure\": true, \"optional\": true }\n",
+            "# @raycast.argument3 { \"type\": \"dropdown\", \"placeholder\": \"Engine\", ",
+            "\"data\": [{ \"title\": \"Google\", \"value\": \"google\" }] }\n",
+        );
+        let result = parse_header(content).unwrap();
+        let names: Vec<&str> = result.arguments.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["argument1", "argument2", "argument3"]);
+
+        let query = &result.arguments[0];
+        assert_eq!(query.argument_type, CommandArgumentType::Text);
+        assert_eq!(query.placeholder.as_deref(), Some("Query"));
+        assert_eq!(query.required, Some(true));
+
+        let token = &result.arguments[1];
+        assert_eq!(token.argument_type, CommandArgumentType::Password);
+        assert_eq!(token.required, Some(false));
+
+        let engine = &result.arguments[2];
+        assert_eq!(engine.argument_type, CommandArgumentType::Dropdown);
+        assert_eq!(
+            engine.data.as_ref().map(|d| d[0].value.as_str()),
+            Some("google")
+        );
+
+        assert_eq!(result.percent_encoded, vec!["argument1".to_string()]);
+    }
+
+    #[test]
+    fn raycast_and_asyar_arguments_share_one_index_space() {
+        let content = concat!(
+            "# @asyar.argument:1 { \"name\": \"q\", \"type\": \"text\" }\n",
+            "# @raycast.argument1 { \"type\": \"text\", \"placeholder\": \"Query\" }\n",
+        );
+        let err = parse_header(content).unwrap_err();
+        assert_eq!(err, HeaderError::DuplicateArgumentIndex { index: 1 });
+    }
+
+    #[test]
+    fn raycast_argument_with_unknown_type_rejected() {
+        let content = "# @raycast.argument1 { \"type\": \"number\" }\n";
+        let err = parse_header(content).unwrap_err();
+        assert!(matches!(
+            err,
+            HeaderError::InvalidArgumentJson { line: 1, .. }
+        ));
+    }
+
+    // ---- env ---------------------------------------------------------------
+
+    #[test]
+    fn env_names_parsed_in_order_without_duplicates() {
+        let content = "# @asyar.env clipboard, selection clipboard\n";
+        let result = parse_header(content).unwrap();
+        assert_eq!(result.env, vec![ScriptEnv::Clipboard, ScriptEnv::Selection]);
+    }
+
+    #[test]
+    fn env_unknown_name_rejected() {
+        let content = "# @asyar.env selection home\n";
+        let err = parse_header(content).unwrap_err();
+        assert_eq!(
+            err,
+            HeaderError::InvalidEnv {
+                value: "home".to_string()
+            }
+        );
+    }
+
     // ---- refreshTime -------------------------------------------------------
 
     #[test]
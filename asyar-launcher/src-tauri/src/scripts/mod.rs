@@ -3,7 +3,7 @@ pub mod inline_scheduler;
 pub mod scanner;
 pub mod watcher;
 
-pub use header::{parse_header, HeaderError, ParsedScriptHeader, ScriptMode};
+pub use header::{parse_header, HeaderError, ParsedScriptHeader, ScriptEnv, ScriptMode};
 pub use inline_scheduler::{
     clear_inline_scripts, set_inline_scripts, InlineSchedulerState, InlineScriptSpec,
     InlineTickPayload, SetInlineScriptsOutcome,
@@ -504,9 +504,14 @@ mod bindings_export {
             .register::<crate::ai::types::ChatStreamEvent>()
             .register::<crate::ai::types::StreamEventPayload>()
             .register::<crate::ai::types::ChatStreamEventPayload>()
+            .register::<crate::ai::types::TokenUsage>()
+            .register::<crate::ai::routing::RoutingPolicy>()
+            .register::<crate::ai::routing::AttemptOutcome>()
             .register::<crate::agents::editor::AgentProviderDescriptor>()
             .register::<crate::agents::runner::AgentRunConfig>()
             .register::<crate::agents::runner::AgentStreamEvent>()
+            .register::<crate::agents::runner::SilentRunOutput>()
+            .register::<crate::storage::agents::SpendGroup>()
             .register::<crate::system_actions::SystemAction>()
             .register::<crate::launcher_placement::LauncherPlacement>()
             .register::<crate::launcher_placement::LauncherMonitorChoice>()
@@ -8,8 +8,12 @@ use tauri::{AppHandle, Emitter};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::Command;
 
+pub mod pty;
 pub mod scheduler;
 
+use pty::PtySession;
+pub use pty::{PtySize, ShellSignal};
+
 #[derive(Clone, Debug)]
 pub struct ShellEntry {
     pub pid: u32,
@@ -19,6 +23,8 @@ pub struct ShellEntry {
     pub started_at: u64,
     pub finished: bool,
     pub exit_code: Option<i32>,
+    /// Running under a pseudo-terminal rather than pipes.
+    pub pty: bool,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -29,6 +35,8 @@ pub struct ShellDescriptor {
     pub args: Vec<String>,
     pub pid: u32,
     pub started_at: u64,
+    /// Output arrives as base64 terminal bytes on the `pty` stream.
+    pub pty: bool,
 }
 
 impl ShellDescriptor {
@@ -39,6 +47,7 @@ impl ShellDescriptor {
             args: entry.args.clone(),
             pid: entry.pid,
             started_at: entry.started_at,
+            pty: entry.pty,
         }
     }
 }
@@ -46,6 +55,7 @@ impl ShellDescriptor {
 pub struct ShellProcessRegistry {
     entries: Arc<Mutex<HashMap<String, ShellEntry>>>,
     stdin_writers: Arc<tokio::sync::Mutex<HashMap<String, tokio::process::ChildStdin>>>,
+    ptys: Arc<Mutex<HashMap<String, PtySession>>>,
 }
 
 impl Clone for ShellProcessRegistry {
@@ -53,6 +63,7 @@ impl Clone for ShellProcessRegistry {
         Self {
             entries: Arc::clone(&self.entries),
             stdin_writers: Arc::clone(&self.stdin_writers),
+            ptys: Arc::clone(&self.ptys),
         }
     }
 }
@@ -68,6 +79,7 @@ impl ShellProcessRegistry {
         Self {
             entries: Arc::new(Mutex::new(HashMap::new())),
             stdin_writers: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            ptys: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -90,17 +102,61 @@ impl ShellProcessRegistry {
                 started_at: now_millis(),
                 finished: false,
                 exit_code: None,
+                pty: false,
             },
         );
         Ok(())
     }
 
+    /// Attaches a terminal to an entry [`Self::register_spawn`] created.
+    pub(crate) fn register_pty(&self, spawn_id: &str, session: PtySession) -> Result<(), AppError> {
+        let mut map = self.entries.lock().map_err(|_| AppError::Lock)?;
+        if let Some(entry) = map.get_mut(spawn_id) {
+            entry.pty = true;
+        }
+        drop(map);
+        let mut ptys = self.ptys.lock().map_err(|_| AppError::Lock)?;
+        ptys.insert(spawn_id.to_string(), session);
+        Ok(())
+    }
+
+    /// Hangs up the spawn's terminal, if it has one.
+    pub fn remove_pty(&self, spawn_id: &str) {
+        if let Ok(mut map) = self.ptys.lock() {
+            map.remove(spawn_id);
+        }
+    }
+
+    /// Runs `f` on the spawn's terminal. `None` when the spawn has none —
+    /// piped, finished or unknown.
+    fn with_pty<T>(
+        &self,
+        spawn_id: &str,
+        f: impl FnOnce(&mut PtySession) -> Result<T, AppError>,
+    ) -> Result<Option<T>, AppError> {
+        let mut map = self.ptys.lock().map_err(|_| AppError::Lock)?;
+        map.get_mut(spawn_id).map(f).transpose()
+    }
+
+    pub fn resize_pty(&self, spawn_id: &str, size: PtySize) -> Result<(), AppError> {
+        size.validate()?;
+        self.with_pty(spawn_id, |session| session.resize(size))?
+            .ok_or_else(|| not_a_terminal(spawn_id))
+    }
+
     pub async fn register_stdin(&self, spawn_id: &str, stdin: tokio::process::ChildStdin) {
         let mut map = self.stdin_writers.lock().await;
         map.insert(spawn_id.to_string(), stdin);
     }
 
+    /// For a PTY session the bytes are typed into the terminal.
     pub async fn write_stdin(&self, spawn_id: &str, data: &[u8]) -> Result<(), AppError> {
+        if self
+            .with_pty(spawn_id, |session| session.write(data))?
+            .is_some()
+        {
+            return Ok(());
+        }
         let mut map = self.stdin_writers.lock().await;
         if let Some(stdin) = map.get_mut(spawn_id) {
             stdin
@@ -120,7 +176,15 @@ impl ShellProcessRegistry {
         }
     }
 
+    /// A terminal has no stdin to close, so a PTY session is sent Ctrl-D,
+    /// which the terminal reads as end of input.
     pub async fn close_stdin(&self, spawn_id: &str) -> Result<(), AppError> {
+        if self
+            .with_pty(spawn_id, |session| session.write(b"\x04"))?
+            .is_some()
+        {
+            return Ok(());
+        }
         let mut map = self.stdin_writers.lock().await;
         if let Some(mut stdin) = map.remove(spawn_id) {
             let _ = stdin.shutdown().await;
@@ -180,6 +244,7 @@ impl ShellProcessRegistry {
 
     pub fn remove(&self, spawn_id: &str) -> Result<Option<ShellEntry>, AppError> {
         self.remove_stdin_sync(spawn_id);
+        self.remove_pty(spawn_id);
         let mut map = self.entries.lock().map_err(|_| AppError::Lock)?;
         Ok(map.remove(spawn_id))
     }
@@ -198,6 +263,7 @@ impl ShellProcessRegistry {
         let mut pids = Vec::with_capacity(victim_ids.len());
         for sid in victim_ids {
             self.remove_stdin_sync(&sid);
+            self.remove_pty(&sid);
             if let Some(entry) = map.remove(&sid) {
                 pids.push(entry.pid);
             }
@@ -250,25 +316,37 @@ pub(crate) fn prune_finished_entries(
     n
 }
 
+fn not_a_terminal(spawn_id: &str) -> AppError {
+    AppError::Validation(format!(
+        "spawnId \"{spawn_id}\" is not a running terminal session"
+    ))
+}
+
+/// Guardrail: libc::kill treats pid ≤ 0 as broadcast (0 = process group,
+/// -1 = every process the caller can signal). A u32 cast to i32 becomes
+/// negative for pid > i32::MAX, so reject those before syscalling. Real
+/// tokio-spawned PIDs never reach that range on any supported OS, so a
+/// rejection here only fires on fake test pids or outright bugs.
+#[cfg(unix)]
+fn signalable_pid(pid: u32) -> Option<i32> {
+    let Ok(signed) = i32::try_from(pid) else {
+        log::warn!("[shell] refusing to signal out-of-range pid {pid}");
+        return None;
+    };
+    if signed <= 0 {
+        log::warn!("[shell] refusing to signal non-positive pid {signed}");
+        return None;
+    }
+    Some(signed)
+}
+
 fn kill_pid(pid: u32) {
     #[cfg(unix)]
     {
-        // Guardrail: libc::kill treats pid ≤ 0 as broadcast (0 = process
-        // group, -1 = every process the caller can signal). A u32 cast to
-        // i32 becomes negative for pid > i32::MAX, so reject those before
-        // syscalling. Real tokio-spawned PIDs never reach that range on
-        // any supported OS, so a rejection here only fires on fake test
-        // pids or outright bugs.
-        let Ok(signed) = i32::try_from(pid) else {
-            log::warn!("[shell] refusing to signal out-of-range pid {pid}");
-            return;
-        };
-        if signed <= 0 {
-            log::warn!("[shell] refusing to signal non-positive pid {signed}");
-            return;
-        }
-        unsafe {
-            libc::kill(signed, libc::SIGKILL);
+        if let Some(signed) = signalable_pid(pid) {
+            unsafe {
+                libc::kill(signed, libc::SIGKILL);
+            }
         }
     }
     #[cfg(windows)]
@@ -313,6 +391,36 @@ pub struct ShellErrorPayload {
     pub message: String,
 }
 
+const ENV_PREFIX: &str = "ASYAR_";
+
+/// Only `ASYAR_`-prefixed names are let through. Anything else could
+/// override loader or interpreter variables (`LD_PRELOAD`, `PATH`,
+/// `NODE_OPTIONS`, …) and run code the trusted binary never asked for.
+/// Values may be anything but NUL.
+pub fn validate_env(env: &HashMap<String, String>) -> Result<(), AppError> {
+    for (name, value) in env {
+        let suffix_ok = name.strip_prefix(ENV_PREFIX).is_some_and(|rest| {
+            !rest.is_empty()
+                && rest
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+        });
+        if !suffix_ok {
+            return Err(AppError::Validation(format!(
+                "Environment variable \"{name}\" is not allowed; names must start with \
+                 {ENV_PREFIX} and use only A-Z, 0-9 and _."
+            )));
+        }
+        if value.contains('\0') {
+            return Err(AppError::Validation(format!(
+                "Environment variable \"{name}\" contains a NUL byte."
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// `env` is added on top of the launcher's own environment.
 pub fn spawn(
     app: AppHandle,
     shell_registry: &ShellProcessRegistry,
@@ -320,10 +428,13 @@ pub fn spawn(
     extension_id: String,
     program: String,
     args: Vec<String>,
+    env: HashMap<String, String>,
 ) -> Result<(), AppError> {
+    validate_env(&env)?;
     let mut child_process = std::process::Command::new(&program);
     child_process
         .args(&args)
+        .envs(&env)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
@@ -464,6 +575,65 @@ pub fn kill(shell_registry: &ShellProcessRegistry, spawn_id: &str) -> Result<(),
     Ok(())
 }
 
+/// Delivers `signal` to a spawn. On a terminal it reaches the foreground
+/// job, as if typed, so a shell's child is interrupted rather than the
+/// shell. A finished spawn is left alone: its pid may already belong to an
+/// unrelated process.
+pub fn signal(
+    shell_registry: &ShellProcessRegistry,
+    spawn_id: &str,
+    entry: &ShellEntry,
+    signal: ShellSignal,
+) -> Result<(), AppError> {
+    if entry.finished {
+        return Ok(());
+    }
+    #[cfg(unix)]
+    {
+        let signum = match signal {
+            ShellSignal::Interrupt => libc::SIGINT,
+            ShellSignal::Terminate => libc::SIGTERM,
+        };
+        let group = shell_registry
+            .with_pty(spawn_id, |session| Ok(session.foreground_group()))?
+            .flatten()
+            .filter(|pgid| *pgid > 0);
+        let target = match group {
+            Some(pgid) => -pgid,
+            None => match signalable_pid(entry.pid) {
+                Some(pid) => pid,
+                None => return Ok(()),
+            },
+        };
+        if unsafe { libc::kill(target, signum) } != 0 {
+            return Err(AppError::Other(format!(
+                "Failed to signal spawnId \"{spawn_id}\": {}",
+                std::io::Error::last_os_error()
+            )));
+        }
+        Ok(())
+    }
+    #[cfg(windows)]
+    {
+        // Windows has no signals. ConPTY turns a typed Ctrl-C into the
+        // console's interrupt; SIGTERM has no gentler equivalent than
+        // terminating.
+        match signal {
+            ShellSignal::Interrupt => shell_registry
+                .with_pty(spawn_id, |session| session.write(b"\x03"))?
+                .ok_or_else(|| {
+                    AppError::Validation(
+                        "SIGINT needs a terminal session on Windows; spawn with `pty`.".to_string(),
+                    )
+                }),
+            ShellSignal::Terminate => {
+                kill_pid(entry.pid);
+                Ok(())
+            }
+        }
+    }
+}
+
 pub async fn resolve_path(program: &str) -> Result<String, AppError> {
     let cmd = if cfg!(windows) { "where" } else { "which" };
     let mut command = Command::new(cmd);
@@ -493,6 +663,51 @@ pub async fn resolve_path(program: &str) -> Result<String, AppError> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn validate_env_accepts_ordinary_variables() {
+        let env = HashMap::from([
+            (
+                "ASYAR_SELECTION".to_string(),
+                "line one\nline two".to_string(),
+            ),
+            ("ASYAR_CLIPBOARD".to_string(), String::new()),
+        ]);
+        assert!(validate_env(&env).is_ok());
+    }
+
+    #[test]
+    fn validate_env_rejects_bad_names_and_nul_values() {
+        for (name, value) in [
+            ("", "x"),
+            ("ASYAR_", "x"),
+            ("ASYAR_A=B", "x"),
+            ("asyar_lower", "x"),
+            ("ASYAR_OK", "a\0b"),
+        ] {
+            let env = HashMap::from([(name.to_string(), value.to_string())]);
+            assert!(
+                matches!(validate_env(&env), Err(AppError::Validation(_))),
+                "expected {name:?}={value:?} to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn validate_env_rejects_loader_and_interpreter_overrides() {
+        for name in [
+            "LD_PRELOAD",
+            "DYLD_INSERT_LIBRARIES",
+            "PATH",
+            "NODE_OPTIONS",
+        ] {
+            let env = HashMap::from([(name.to_string(), "/tmp/evil".to_string())]);
+            assert!(
+                matches!(validate_env(&env), Err(AppError::Validation(_))),
+                "expected {name} to be rejected"
+            );
+        }
+    }
+
     fn register_entry(reg: &ShellProcessRegistry, spawn_id: &str, ext: &str, pid: u32) {
         reg.register_spawn(spawn_id, ext, "/bin/echo", &["hello".to_string()], pid)
             .expect("register ok");
@@ -606,6 +821,7 @@ mod tests {
                 started_at: 0,
                 finished: true,
                 exit_code: Some(0),
+                pty: false,
             },
         );
         map.insert(
@@ -618,6 +834,7 @@ mod tests {
                 started_at: 500_000,
                 finished: true,
                 exit_code: Some(0),
+                pty: false,
             },
         );
         map.insert(
@@ -630,6 +847,7 @@ mod tests {
                 started_at: 0,
                 finished: false,
                 exit_code: None,
+                pty: false,
             },
         );
         let dropped = prune_finished_entries(&mut map, 600_000, 600_000);
@@ -652,6 +870,7 @@ mod tests {
                 started_at: 0,
                 finished: false,
                 exit_code: None,
+                pty: false,
             },
         );
         map.insert(
@@ -664,6 +883,7 @@ mod tests {
                 started_at: 590_000,
                 finished: true,
                 exit_code: Some(0),
+                pty: false,
             },
         );
         let dropped = prune_finished_entries(&mut map, 600_000, 600_000);
@@ -684,6 +904,7 @@ mod tests {
                 started_at: 0,
                 finished: false,
                 exit_code: None,
+                pty: false,
             },
         );
         let dropped = prune_finished_entries(&mut map, 1, 1_000_000);
@@ -709,6 +930,16 @@ mod tests {
         assert!(entry.started_at >= before && entry.started_at <= after);
     }
 
+    #[test]
+    fn resize_pty_rejects_piped_spawns() {
+        let reg = ShellProcessRegistry::new();
+        register_entry(&reg, "s1", "ext-a", 100);
+        let err = reg
+            .resize_pty("s1", PtySize { cols: 80, rows: 24 })
+            .unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)), "got: {err:?}");
+    }
+
     #[tokio::test]
     async fn write_stdin_returns_not_found_for_unregistered_spawn() {
         let reg = ShellProcessRegistry::new();
@@ -768,4 +999,63 @@ mod tests {
         let status = child.wait().await.unwrap();
         assert!(status.success());
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn pty_session_takes_typed_input_resizes_and_ends_on_ctrl_d() {
+        use portable_pty::{native_pty_system, CommandBuilder};
+        use std::io::Read;
+
+        let reg = ShellProcessRegistry::new();
+        let pair = native_pty_system()
+            .openpty(portable_pty::PtySize {
+                rows: 24,
+                cols: 80,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .expect("open pty");
+        let mut child = pair
+            .slave
+            .spawn_command(CommandBuilder::new("cat"))
+            .expect("spawn cat");
+        drop(pair.slave);
+        let mut reader = pair.master.try_clone_reader().expect("reader");
+        let writer = pair.master.take_writer().expect("writer");
+        let pid = child.process_id().expect("pid");
+
+        reg.register_spawn("spawn-tty", "ext-a", "cat", &[], pid)
+            .unwrap();
+        reg.register_pty("spawn-tty", PtySession::new(pair.master, writer))
+            .unwrap();
+        assert!(reg.get("spawn-tty", "ext-a").unwrap().unwrap().pty);
+
+        reg.write_stdin("spawn-tty", b"hello tty\n")
+            .await
+            .expect("write ok");
+        let mut seen = String::new();
+        let mut buf = [0u8; 256];
+        while !seen.contains("hello tty") {
+            let n = reader.read(&mut buf).expect("read terminal");
+            assert!(n > 0, "terminal closed before echoing input");
+            seen.push_str(&String::from_utf8_lossy(&buf[..n]));
+        }
+
+        reg.resize_pty(
+            "spawn-tty",
+            PtySize {
+                cols: 120,
+                rows: 40,
+            },
+        )
+        .expect("resize ok");
+
+        // Ctrl-D at the start of a line is end of input for cat.
+        reg.close_stdin("spawn-tty").await.expect("close ok");
+        assert!(child.wait().expect("wait").success());
+        reg.remove_pty("spawn-tty");
+        assert!(reg
+            .resize_pty("spawn-tty", PtySize { cols: 80, rows: 24 })
+            .is_err());
+    }
 }
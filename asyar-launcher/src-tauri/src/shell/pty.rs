@@ -0,0 +1,248 @@
+//! Spawning under a pseudo-terminal, for programs that check `isatty` or
+//! draw a full-screen UI: ssh password prompts, `top`, language REPLs.
+//!
+//! A PTY session shares the piped spawn's registry entry, events and
+//! lifecycle; only the I/O differs. The child's stdout and stderr arrive
+//! merged on the one terminal, as raw bytes in `asyar:shell:chunk` events
+//! with stream `"pty"` and base64 `data` — never split into lines, since
+//! escape sequences and partial UTF-8 can straddle any boundary.
+
+use super::{
+    validate_env, ShellChunkPayload, ShellDonePayload, ShellErrorPayload, ShellProcessRegistry,
+};
+use crate::error::AppError;
+use base64::Engine;
+use portable_pty::{native_pty_system, CommandBuilder, MasterPty};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// How long output may keep draining after the child exits before `done` is
+/// sent anyway. A background grandchild that inherited the terminal would
+/// otherwise hold it open forever.
+const DRAIN_GRACE: Duration = Duration::from_secs(2);
+
+const READ_BUFFER_BYTES: usize = 8192;
+
+/// Terminal dimensions in character cells.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PtySize {
+    pub cols: u16,
+    pub rows: u16,
+}
+
+impl PtySize {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.cols == 0 || self.rows == 0 {
+            return Err(AppError::Validation(format!(
+                "Terminal size must be at least 1x1, got {}x{}.",
+                self.cols, self.rows
+            )));
+        }
+        Ok(())
+    }
+
+    fn to_portable(self) -> portable_pty::PtySize {
+        portable_pty::PtySize {
+            rows: self.rows,
+            cols: self.cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        }
+    }
+}
+
+/// Signals an extension may deliver. Deliberately short: SIGKILL is what
+/// `abort()` already does, and nothing else is needed to stop a program.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShellSignal {
+    #[serde(rename = "SIGINT")]
+    Interrupt,
+    #[serde(rename = "SIGTERM")]
+    Terminate,
+}
+
+/// The master side of a live session. Dropping it hangs up the terminal,
+/// which sends the child SIGHUP.
+pub(crate) struct PtySession {
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+}
+
+impl PtySession {
+    pub(crate) fn new(master: Box<dyn MasterPty + Send>, writer: Box<dyn Write + Send>) -> Self {
+        Self { master, writer }
+    }
+
+    pub(crate) fn write(&mut self, data: &[u8]) -> Result<(), AppError> {
+        self.writer
+            .write_all(data)
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| AppError::Other(format!("Failed to write to terminal: {e}")))
+    }
+
+    pub(crate) fn resize(&self, size: PtySize) -> Result<(), AppError> {
+        self.master
+            .resize(size.to_portable())
+            .map_err(|e| AppError::Other(format!("Failed to resize terminal: {e}")))
+    }
+
+    /// The terminal's foreground process group — the job a Ctrl-C typed
+    /// into it would reach, which may be a grandchild of the shell we
+    /// spawned.
+    #[cfg(unix)]
+    pub(crate) fn foreground_group(&self) -> Option<i32> {
+        self.master.process_group_leader()
+    }
+}
+
+/// Same contract as [`super::spawn`], under a terminal of `size`. `TERM` is
+/// always `xterm-256color`: `env` only carries `ASYAR_`-prefixed names.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn(
+    app: AppHandle,
+    shell_registry: &ShellProcessRegistry,
+    spawn_id: String,
+    extension_id: String,
+    program: String,
+    args: Vec<String>,
+    env: HashMap<String, String>,
+    size: PtySize,
+) -> Result<(), AppError> {
+    validate_env(&env)?;
+    size.validate()?;
+
+    let pair = native_pty_system()
+        .openpty(size.to_portable())
+        .map_err(|e| AppError::Other(format!("Failed to open a terminal: {e}")))?;
+
+    let mut command = CommandBuilder::new(&program);
+    command.args(&args);
+    command.env("TERM", "xterm-256color");
+    for (name, value) in &env {
+        command.env(name, value);
+    }
+    if let Ok(cwd) = std::env::current_dir() {
+        command.cwd(cwd);
+    }
+
+    let mut child = pair
+        .slave
+        .spawn_command(command)
+        .map_err(|e| AppError::Other(format!("Failed to spawn {program}: {e}")))?;
+    // The child holds its own copy; ours would keep the terminal open after
+    // it exits and the reader would never see EOF.
+    drop(pair.slave);
+
+    let pid = child
+        .process_id()
+        .ok_or_else(|| AppError::Other("Failed to capture process ID".to_string()))?;
+    let reader = pair
+        .master
+        .try_clone_reader()
+        .map_err(|e| AppError::Other(format!("Failed to read from terminal: {e}")))?;
+    let writer = pair
+        .master
+        .take_writer()
+        .map_err(|e| AppError::Other(format!("Failed to write to terminal: {e}")))?;
+
+    shell_registry.register_spawn(&spawn_id, &extension_id, &program, &args, pid)?;
+    shell_registry.register_pty(&spawn_id, PtySession::new(pair.master, writer))?;
+
+    let read_task = tokio::task::spawn_blocking({
+        let app = app.clone();
+        let spawn_id = spawn_id.clone();
+        move || pump_output(&app, &spawn_id, reader)
+    });
+    let wait_task = tokio::task::spawn_blocking(move || child.wait());
+
+    let registry = shell_registry.clone();
+    tokio::spawn(async move {
+        let status = wait_task.await;
+        let _ = tokio::time::timeout(DRAIN_GRACE, read_task).await;
+        registry.remove_pty(&spawn_id);
+
+        match status {
+            Ok(Ok(status)) => {
+                let exit_code = i32::try_from(status.exit_code()).ok();
+                let _ = registry.mark_finished(&spawn_id, exit_code);
+                let _ = app.emit(
+                    "asyar:shell:done",
+                    ShellDonePayload {
+                        spawn_id,
+                        exit_code,
+                    },
+                );
+            }
+            Ok(Err(e)) => emit_error(&app, &registry, spawn_id, e.to_string()),
+            Err(e) => emit_error(&app, &registry, spawn_id, e.to_string()),
+        }
+    });
+
+    Ok(())
+}
+
+fn emit_error(app: &AppHandle, registry: &ShellProcessRegistry, spawn_id: String, message: String) {
+    let _ = registry.mark_finished(&spawn_id, None);
+    let _ = app.emit("asyar:shell:error", ShellErrorPayload { spawn_id, message });
+}
+
+/// Forwards terminal output until the terminal closes. Linux reports that
+/// as EIO rather than EOF, so any read error ends the session.
+fn pump_output(app: &AppHandle, spawn_id: &str, mut reader: Box<dyn Read + Send>) {
+    let mut buf = [0u8; READ_BUFFER_BYTES];
+    loop {
+        match reader.read(&mut buf) {
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                let _ = app.emit(
+                    "asyar:shell:chunk",
+                    ShellChunkPayload {
+                        spawn_id: spawn_id.to_string(),
+                        stream: "pty".to_string(),
+                        data: encode_chunk(&buf[..n]),
+                    },
+                );
+            }
+        }
+    }
+}
+
+fn encode_chunk(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pty_size_rejects_empty_dimensions() {
+        assert!(PtySize { cols: 80, rows: 24 }.validate().is_ok());
+        for size in [PtySize { cols: 0, rows: 24 }, PtySize { cols: 80, rows: 0 }] {
+            assert!(matches!(size.validate(), Err(AppError::Validation(_))));
+        }
+    }
+
+    #[test]
+    fn signals_use_their_conventional_names_on_the_wire() {
+        let parsed: ShellSignal = serde_json::from_str("\"SIGINT\"").unwrap();
+        assert_eq!(parsed, ShellSignal::Interrupt);
+        let parsed: ShellSignal = serde_json::from_str("\"SIGTERM\"").unwrap();
+        assert_eq!(parsed, ShellSignal::Terminate);
+        assert!(serde_json::from_str::<ShellSignal>("\"SIGKILL\"").is_err());
+    }
+
+    #[test]
+    fn chunks_survive_split_utf8_and_escapes() {
+        // "é" split across two reads must round-trip byte for byte.
+        let encoded = encode_chunk(b"\x1b[1m\xc3");
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .unwrap();
+        assert_eq!(decoded, b"\x1b[1m\xc3");
+    }
+}
@@ -2,8 +2,12 @@ use tauri::AppHandle;
 #[allow(unused_imports)]
 use tauri::{Emitter, Manager};
 
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::application::service::FrontmostApplication;
+
 pub type ExtensionId = String;
 pub type ShortcodeMap = HashMap<String, String>;
 pub type ContributedSnippets = HashMap<ExtensionId, ShortcodeMap>;
@@ -62,6 +66,294 @@ pub fn merge_active_snippets(
     merged
 }
 
+/// How a snippet's keyword is compared against the typed buffer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SnippetTriggerKind {
+    /// The keyword must appear verbatim at the end of the buffer.
+    #[default]
+    Literal,
+    /// The keyword is a regex anchored to the end of the buffer. Capture
+    /// groups are available to the expansion as `$1`, `${name}`, etc.
+    Regex,
+}
+
+/// When a matched snippet fires.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SnippetExpandMode {
+    /// Expand as soon as the keyword is complete.
+    #[default]
+    Immediate,
+    /// Wait for a delimiter (space or punctuation) after the keyword. The
+    /// delimiter is consumed and re-emitted after the expansion, so the user
+    /// keeps the character they typed. Regex triggers with open-ended
+    /// captures (`\w+`) need this mode — otherwise they fire on the first
+    /// matching character.
+    Delimiter,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SnippetAppScopeMode {
+    /// Only expand while one of `apps` is frontmost.
+    Include,
+    /// Expand everywhere except while one of `apps` is frontmost.
+    Exclude,
+}
+
+/// Restricts a snippet to (or away from) a set of applications.
+///
+/// Entries are matched case-insensitively against the frontmost app's
+/// bundle id, display name, or executable path / file name, so a rule can
+/// say `com.apple.Terminal`, `Terminal`, or `code.exe` interchangeably.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetAppScope {
+    pub mode: SnippetAppScopeMode,
+    #[serde(default)]
+    pub apps: Vec<String>,
+}
+
+impl SnippetAppScope {
+    /// `frontmost` is `None` when the platform cannot tell (Linux today, or a
+    /// failed lookup). Include-lists fail closed, exclude-lists fail open.
+    pub fn allows(&self, frontmost: Option<&FrontmostApplication>) -> bool {
+        let Some(app) = frontmost else {
+            return self.mode == SnippetAppScopeMode::Exclude;
+        };
+        let listed = self.apps.iter().any(|entry| app_matches(entry, app));
+        match self.mode {
+            SnippetAppScopeMode::Include => listed,
+            SnippetAppScopeMode::Exclude => !listed,
+        }
+    }
+}
+
+fn app_matches(entry: &str, app: &FrontmostApplication) -> bool {
+    let entry = entry.trim();
+    if entry.is_empty() {
+        return false;
+    }
+    if app.name.eq_ignore_ascii_case(entry) {
+        return true;
+    }
+    if app
+        .bundle_id
+        .as_deref()
+        .is_some_and(|id| id.eq_ignore_ascii_case(entry))
+    {
+        return true;
+    }
+    app.path.as_deref().is_some_and(|path| {
+        if path.eq_ignore_ascii_case(entry) {
+            return true;
+        }
+        let p = std::path::Path::new(path);
+        p.file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.eq_ignore_ascii_case(entry))
+            || p.file_stem()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.eq_ignore_ascii_case(entry))
+    })
+}
+
+/// A user snippet as synced from the frontend, with its matching options.
+/// Plain `(keyword, expansion)` pairs from `sync_snippets_to_rust` behave like
+/// a `Literal` + `Immediate` rule with no app scope.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetRule {
+    /// Id of the stored snippet, so compile failures can name it.
+    #[serde(default)]
+    pub id: String,
+    pub keyword: String,
+    pub expansion: String,
+    #[serde(default)]
+    pub trigger: SnippetTriggerKind,
+    #[serde(default)]
+    pub expand_mode: SnippetExpandMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_scope: Option<SnippetAppScope>,
+}
+
+/// Compiles a regex trigger the way the listener matches it: anchored to the
+/// end of the typed buffer.
+pub fn trigger_regex(keyword: &str) -> Result<Regex, regex::Error> {
+    Regex::new(&format!("(?:{keyword})$"))
+}
+
+/// A [`SnippetRule`] with its regex compiled once at sync time rather than
+/// on every keystroke.
+#[derive(Debug, Clone)]
+pub struct CompiledSnippetRule {
+    pub rule: SnippetRule,
+    regex: Option<Regex>,
+}
+
+impl CompiledSnippetRule {
+    pub fn compile(rule: SnippetRule) -> Result<Self, String> {
+        if rule.keyword.is_empty() {
+            return Err("Snippet keyword must not be empty".to_string());
+        }
+        let regex = match rule.trigger {
+            SnippetTriggerKind::Literal => None,
+            SnippetTriggerKind::Regex => Some(
+                trigger_regex(&rule.keyword)
+                    .map_err(|e| format!("Invalid snippet regex \"{}\": {e}", rule.keyword))?,
+            ),
+        };
+        Ok(Self { rule, regex })
+    }
+
+    /// Matches this rule against the end of `buf`, ignoring app scope.
+    fn match_at_end(&self, buf: &str) -> Option<SnippetExpansion> {
+        let (body, delimiter) = match self.rule.expand_mode {
+            SnippetExpandMode::Immediate => (buf, None),
+            SnippetExpandMode::Delimiter => {
+                let last = buf.chars().last()?;
+                if !is_expansion_delimiter(last) {
+                    return None;
+                }
+                (&buf[..buf.len() - last.len_utf8()], Some(last))
+            }
+        };
+
+        let (matched_len, mut expansion) = match &self.regex {
+            None => {
+                if !body.ends_with(self.rule.keyword.as_str()) {
+                    return None;
+                }
+                (
+                    self.rule.keyword.chars().count(),
+                    self.rule.expansion.clone(),
+                )
+            }
+            Some(re) => {
+                let caps = re.captures(body)?;
+                let whole = caps.get(0)?;
+                if whole.as_str().is_empty() {
+                    return None;
+                }
+                let mut out = String::new();
+                caps.expand(&self.rule.expansion, &mut out);
+                (whole.as_str().chars().count(), out)
+            }
+        };
+
+        let keyword_len = match delimiter {
+            Some(d) => {
+                expansion.push(d);
+                matched_len + 1
+            }
+            None => matched_len,
+        };
+        Some(SnippetExpansion {
+            keyword_len,
+            expansion,
+        })
+    }
+}
+
+/// A rule [`compile_rules`] left out, keyed by the snippet it came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetRuleError {
+    pub snippet_id: String,
+    pub message: String,
+}
+
+/// Compiles each rule on its own. A broken regex drops only its own snippet;
+/// the rest still expand, and the failures come back by snippet id.
+pub fn compile_rules(rules: Vec<SnippetRule>) -> (Vec<CompiledSnippetRule>, Vec<SnippetRuleError>) {
+    let mut compiled = Vec::with_capacity(rules.len());
+    let mut errors = Vec::new();
+    for rule in rules {
+        let snippet_id = rule.id.clone();
+        match CompiledSnippetRule::compile(rule) {
+            Ok(c) => compiled.push(c),
+            Err(message) => errors.push(SnippetRuleError {
+                snippet_id,
+                message,
+            }),
+        }
+    }
+    (compiled, errors)
+}
+
+/// Characters that complete a `Delimiter`-mode snippet. Return and Tab are
+/// not listed: both listeners clear the buffer on those keys before matching.
+pub(crate) fn is_expansion_delimiter(c: char) -> bool {
+    c.is_whitespace() || (c.is_ascii_punctuation() && c != '_')
+}
+
+/// The payload of an `expand-snippet` event: how many typed characters to
+/// erase and what to paste in their place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnippetExpansion {
+    pub keyword_len: usize,
+    pub expansion: String,
+}
+
+/// Finds the snippet to expand for the current buffer tail.
+///
+/// User rules are tried first (in sync order), then the flat literal map of
+/// legacy pairs and extension contributions. `frontmost` is only called when
+/// a rule with an app scope matches, and at most once per keystroke — the
+/// platform lookup is too costly to run on every key press.
+pub(crate) fn resolve_expansion_at_end<F>(
+    buf: &str,
+    rules: &[CompiledSnippetRule],
+    literals: &ShortcodeMap,
+    frontmost: F,
+) -> Option<SnippetExpansion>
+where
+    F: FnOnce() -> Option<FrontmostApplication>,
+{
+    let mut frontmost = Some(frontmost);
+    let mut cached: Option<Option<FrontmostApplication>> = None;
+
+    for rule in rules {
+        let Some(hit) = rule.match_at_end(buf) else {
+            continue;
+        };
+        if let Some(scope) = &rule.rule.app_scope {
+            let app = cached.get_or_insert_with(|| frontmost.take().and_then(|f| f()));
+            if !scope.allows(app.as_ref()) {
+                continue;
+            }
+        }
+        return Some(hit);
+    }
+
+    literals.iter().find_map(|(keyword, expansion)| {
+        buf.ends_with(keyword.as_str()).then(|| SnippetExpansion {
+            keyword_len: keyword.chars().count(),
+            expansion: expansion.clone(),
+        })
+    })
+}
+
+/// True when a completed `:shortcode:` candidate is already served by a
+/// snippet, so the listener must not report it as a miss.
+pub(crate) fn is_known_shortcode(
+    candidate: &str,
+    rules: &[CompiledSnippetRule],
+    literals: &ShortcodeMap,
+) -> bool {
+    literals.contains_key(candidate)
+        || rules
+            .iter()
+            .any(|r| r.rule.trigger == SnippetTriggerKind::Literal && r.rule.keyword == candidate)
+}
+
+/// Looks up the frontmost app for app-scoped snippets. Errors (unsupported
+/// platform, failed lookup) collapse to `None`; see [`SnippetAppScope::allows`].
+pub(crate) fn frontmost_for_scope() -> Option<FrontmostApplication> {
+    crate::application::get_frontmost_application().ok()
+}
+
 #[cfg(not(target_os = "macos"))]
 use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 
@@ -152,21 +444,28 @@ pub fn start_listener(app_handle: AppHandle) {
                                     &contributed_guard,
                                 )
                             };
-                            for (keyword, expansion) in merged.iter() {
-                                if current.ends_with(keyword.as_str()) {
-                                    let kw_len = keyword.chars().count();
-                                    let exp = expansion.clone();
-                                    buffer.clear();
-                                    let _ = app_handle.emit_to(
-                                        crate::SPOTLIGHT_LABEL,
-                                        "expand-snippet",
-                                        serde_json::json!({
-                                            "keywordLen": kw_len,
-                                            "expansion": exp
-                                        }),
-                                    );
-                                    return;
-                                }
+                            let rules = std::sync::Arc::clone(
+                                &state
+                                    .snippet_rules
+                                    .lock()
+                                    .unwrap_or_else(|p: std::sync::PoisonError<_>| p.into_inner()),
+                            );
+                            if let Some(hit) = crate::snippets::resolve_expansion_at_end(
+                                &current,
+                                &rules,
+                                &merged,
+                                crate::snippets::frontmost_for_scope,
+                            ) {
+                                buffer.clear();
+                                let _ = app_handle.emit_to(
+                                    crate::SPOTLIGHT_LABEL,
+                                    "expand-snippet",
+                                    serde_json::json!({
+                                        "keywordLen": hit.keyword_len,
+                                        "expansion": hit.expansion
+                                    }),
+                                );
+                                return;
                             }
                             let triggers = {
                                 if let Ok(guard) = state.shortcode_triggers.lock() {
@@ -182,7 +481,9 @@ pub fn start_listener(app_handle: AppHandle) {
                                             &current, &trigger,
                                         )
                                     {
-                                        if !merged.contains_key(&candidate) {
+                                        if !crate::snippets::is_known_shortcode(
+                                            &candidate, &rules, &merged,
+                                        ) {
                                             let _ = app_handle.emit_to(
                                                 crate::SPOTLIGHT_LABEL,
                                                 "shortcode-miss",
@@ -326,6 +627,130 @@ mod tests {
         assert_eq!(merged.get(":bye:"), Some(&"BYE".to_string()));
     }
 
+    fn rule(keyword: &str, expansion: &str) -> super::SnippetRule {
+        super::SnippetRule {
+            id: keyword.to_string(),
+            keyword: keyword.to_string(),
+            expansion: expansion.to_string(),
+            trigger: super::SnippetTriggerKind::Literal,
+            expand_mode: super::SnippetExpandMode::Immediate,
+            app_scope: None,
+        }
+    }
+
+    fn compile_ok(rules: Vec<super::SnippetRule>) -> Vec<super::CompiledSnippetRule> {
+        let (compiled, errors) = super::compile_rules(rules);
+        assert!(errors.is_empty(), "{errors:?}");
+        compiled
+    }
+
+    fn app(name: &str, bundle_id: Option<&str>) -> super::FrontmostApplication {
+        super::FrontmostApplication {
+            name: name.to_string(),
+            bundle_id: bundle_id.map(str::to_string),
+            path: None,
+            window_title: None,
+        }
+    }
+
+    #[test]
+    fn regex_rule_expands_capture_groups_on_delimiter() {
+        let mut r = rule(";mail(\\w+)", "$1@company.com");
+        r.trigger = super::SnippetTriggerKind::Regex;
+        r.expand_mode = super::SnippetExpandMode::Delimiter;
+        let rules = compile_ok(vec![r]);
+        let none = std::collections::HashMap::new();
+
+        assert_eq!(
+            super::resolve_expansion_at_end("hi ;mailjohn", &rules, &none, || None),
+            None
+        );
+        let hit = super::resolve_expansion_at_end("hi ;mailjohn ", &rules, &none, || None).unwrap();
+        assert_eq!(hit.expansion, "john@company.com ");
+        assert_eq!(hit.keyword_len, ";mailjohn ".chars().count());
+    }
+
+    #[test]
+    fn delimiter_mode_waits_for_a_delimiter() {
+        let mut r = rule(";sig", "Kind regards");
+        r.expand_mode = super::SnippetExpandMode::Delimiter;
+        let rules = compile_ok(vec![r]);
+        let none = std::collections::HashMap::new();
+
+        assert!(super::resolve_expansion_at_end(";sig", &rules, &none, || None).is_none());
+        let hit = super::resolve_expansion_at_end(";sig.", &rules, &none, || None).unwrap();
+        assert_eq!(hit.expansion, "Kind regards.");
+        assert_eq!(hit.keyword_len, 5);
+    }
+
+    #[test]
+    fn app_scope_include_and_exclude() {
+        let mut inc = rule(";a", "A");
+        inc.app_scope = Some(super::SnippetAppScope {
+            mode: super::SnippetAppScopeMode::Include,
+            apps: vec!["com.apple.mail".into()],
+        });
+        let rules = compile_ok(vec![inc]);
+        let none = std::collections::HashMap::new();
+
+        let mail = || Some(app("Mail", Some("com.apple.mail")));
+        let term = || Some(app("Terminal", Some("com.apple.Terminal")));
+        assert!(super::resolve_expansion_at_end(";a", &rules, &none, mail).is_some());
+        assert!(super::resolve_expansion_at_end(";a", &rules, &none, term).is_none());
+        // Unknown frontmost app: include-lists fail closed.
+        assert!(super::resolve_expansion_at_end(";a", &rules, &none, || None).is_none());
+
+        let scope = super::SnippetAppScope {
+            mode: super::SnippetAppScopeMode::Exclude,
+            apps: vec!["terminal".into()],
+        };
+        assert!(!scope.allows(Some(&app("Terminal", None))));
+        assert!(scope.allows(Some(&app("Mail", None))));
+        assert!(scope.allows(None));
+    }
+
+    #[test]
+    fn scoped_rule_that_is_filtered_out_falls_back_to_literals() {
+        let mut scoped = rule(";x", "scoped");
+        scoped.app_scope = Some(super::SnippetAppScope {
+            mode: super::SnippetAppScopeMode::Include,
+            apps: vec!["Mail".into()],
+        });
+        let rules = compile_ok(vec![scoped]);
+        let mut literals = std::collections::HashMap::new();
+        literals.insert(";x".to_string(), "global".to_string());
+
+        let hit = super::resolve_expansion_at_end(";x", &rules, &literals, || None).unwrap();
+        assert_eq!(hit.expansion, "global");
+    }
+
+    #[test]
+    fn invalid_regex_drops_only_its_own_rule() {
+        let mut bad = rule("(oops", "x");
+        bad.id = "snip-bad".into();
+        bad.trigger = super::SnippetTriggerKind::Regex;
+        let mut good = rule(";n(\\d+)", "#$1");
+        good.trigger = super::SnippetTriggerKind::Regex;
+
+        let (rules, errors) = super::compile_rules(vec![bad, good, rule(";sig", "S")]);
+        assert_eq!(rules.len(), 2);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].snippet_id, "snip-bad");
+        assert!(errors[0].message.contains("(oops"));
+
+        let none = std::collections::HashMap::new();
+        let hit = super::resolve_expansion_at_end(";n42", &rules, &none, || None).unwrap();
+        assert_eq!(hit.expansion, "#42");
+    }
+
+    #[test]
+    fn literal_rule_counts_as_known_shortcode() {
+        let rules = compile_ok(vec![rule(":sig:", "S")]);
+        let none = std::collections::HashMap::new();
+        assert!(super::is_known_shortcode(":sig:", &rules, &none));
+        assert!(!super::is_known_shortcode(":nope:", &rules, &none));
+    }
+
     #[test]
     fn pattern_shape_rejects_malformed_keys() {
         assert!(super::is_valid_shortcode_key(":party:", ":"));
@@ -0,0 +1,228 @@
+//! Audit log of every `shell-exec`, `fs-read` and `fs-write` call — the
+//! built-in counterpart of [`super::mcp_audit`].
+
+use crate::error::AppError;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// How a sandboxed tool call ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditOutcome {
+    /// The call ran to completion.
+    Ok,
+    /// The agent's policy refused it before anything ran.
+    Denied,
+    /// It was allowed but failed (spawn error, timeout, I/O error).
+    Failed,
+    /// A write in dry-run mode; nothing touched the disk.
+    DryRun,
+    /// A write the user declined (or didn't answer) in confirm mode.
+    Declined,
+}
+
+impl AuditOutcome {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Denied => "denied",
+            Self::Failed => "failed",
+            Self::DryRun => "dryRun",
+            Self::Declined => "declined",
+        }
+    }
+
+    fn parse(raw: &str) -> Self {
+        match raw {
+            "ok" => Self::Ok,
+            "denied" => Self::Denied,
+            "dryRun" => Self::DryRun,
+            "declined" => Self::Declined,
+            _ => Self::Failed,
+        }
+    }
+}
+
+/// A single audit log entry (read from DB).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentToolAuditRow {
+    /// SQLite autoincrement id.
+    pub id: i64,
+    pub tool_id: String,
+    /// `None` when the tool was invoked from the launcher rather than an agent.
+    pub agent_id: Option<String>,
+    /// Unix millis.
+    pub called_at: i64,
+    pub outcome: AuditOutcome,
+    /// Why the call was denied or failed; `None` otherwise.
+    pub detail: Option<String>,
+    /// Truncated representation of the tool arguments (max 200 chars).
+    pub args_summary: String,
+}
+
+/// Input for creating a new audit entry (id is assigned by SQLite).
+#[derive(Debug, Clone)]
+pub struct NewAgentToolAuditEntry {
+    pub tool_id: String,
+    pub agent_id: Option<String>,
+    pub called_at: i64,
+    pub outcome: AuditOutcome,
+    pub detail: Option<String>,
+    pub args_summary: String,
+}
+
+/// Idempotent: creates the agent_tool_audit table and its indexes if missing.
+pub fn init_table(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS agent_tool_audit (
+            id           INTEGER PRIMARY KEY AUTOINCREMENT,
+            tool_id      TEXT    NOT NULL,
+            agent_id     TEXT,
+            called_at    INTEGER NOT NULL,
+            outcome      TEXT    NOT NULL,
+            detail       TEXT,
+            args_summary TEXT    NOT NULL DEFAULT ''
+        );
+        CREATE INDEX IF NOT EXISTS idx_agent_tool_audit_called_at
+            ON agent_tool_audit(called_at DESC);
+        CREATE INDEX IF NOT EXISTS idx_agent_tool_audit_agent_id
+            ON agent_tool_audit(agent_id);",
+    )
+    .map_err(|e| AppError::Database(format!("Failed to init agent_tool_audit table: {e}")))?;
+    Ok(())
+}
+
+/// Insert a new audit entry and return its assigned row id.
+pub fn insert_entry(conn: &Connection, entry: &NewAgentToolAuditEntry) -> Result<i64, AppError> {
+    conn.execute(
+        "INSERT INTO agent_tool_audit
+            (tool_id, agent_id, called_at, outcome, detail, args_summary)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            entry.tool_id,
+            entry.agent_id,
+            entry.called_at,
+            entry.outcome.as_str(),
+            entry.detail,
+            entry.args_summary,
+        ],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Return audit entries ordered by `called_at` descending, newest first.
+/// When `agent_id` is `Some`, only that agent's calls are returned.
+pub fn list_recent(
+    conn: &Connection,
+    agent_id: Option<&str>,
+    limit: u32,
+) -> Result<Vec<AgentToolAuditRow>, AppError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, tool_id, agent_id, called_at, outcome, detail, args_summary
+             FROM agent_tool_audit
+             WHERE ?1 IS NULL OR agent_id = ?1
+             ORDER BY called_at DESC, id DESC
+             LIMIT ?2",
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let rows = stmt
+        .query_map(params![agent_id, limit], |row| {
+            Ok(AgentToolAuditRow {
+                id: row.get(0)?,
+                tool_id: row.get(1)?,
+                agent_id: row.get(2)?,
+                called_at: row.get(3)?,
+                outcome: AuditOutcome::parse(&row.get::<_, String>(4)?),
+                detail: row.get(5)?,
+                args_summary: row.get(6)?,
+            })
+        })
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))
+}
+
+/// Delete all audit entries with `called_at < cutoff_millis`.
+/// Returns the number of rows deleted.
+pub fn purge_older_than(conn: &Connection, cutoff_millis: i64) -> Result<usize, AppError> {
+    conn.execute(
+        "DELETE FROM agent_tool_audit WHERE called_at < ?1",
+        params![cutoff_millis],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_conn() -> Connection {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        init_table(&conn).unwrap();
+        conn
+    }
+
+    fn entry(
+        agent_id: Option<&str>,
+        called_at: i64,
+        outcome: AuditOutcome,
+    ) -> NewAgentToolAuditEntry {
+        NewAgentToolAuditEntry {
+            tool_id: "shell-exec".to_string(),
+            agent_id: agent_id.map(str::to_string),
+            called_at,
+            outcome,
+            detail: None,
+            args_summary: r#"{"command":"ls"}"#.to_string(),
+        }
+    }
+
+    #[test]
+    fn insert_entry_persists_full_row() {
+        let conn = make_conn();
+        let mut e = entry(Some("a1"), 5000, AuditOutcome::Denied);
+        e.detail = Some("'rm' is not in this agent's allowed commands".to_string());
+        let id = insert_entry(&conn, &e).unwrap();
+
+        let rows = list_recent(&conn, None, 10).unwrap();
+        assert_eq!(
+            rows,
+            vec![AgentToolAuditRow {
+                id,
+                tool_id: "shell-exec".to_string(),
+                agent_id: Some("a1".to_string()),
+                called_at: 5000,
+                outcome: AuditOutcome::Denied,
+                detail: e.detail.clone(),
+                args_summary: e.args_summary.clone(),
+            }]
+        );
+    }
+
+    #[test]
+    fn list_recent_filters_by_agent_newest_first() {
+        let conn = make_conn();
+        insert_entry(&conn, &entry(Some("a1"), 1000, AuditOutcome::Ok)).unwrap();
+        insert_entry(&conn, &entry(None, 2000, AuditOutcome::Ok)).unwrap();
+        insert_entry(&conn, &entry(Some("a1"), 3000, AuditOutcome::DryRun)).unwrap();
+
+        let rows = list_recent(&conn, Some("a1"), 10).unwrap();
+        let times: Vec<_> = rows.iter().map(|r| r.called_at).collect();
+        assert_eq!(times, [3000, 1000]);
+        assert_eq!(rows[0].outcome, AuditOutcome::DryRun);
+        assert_eq!(list_recent(&conn, None, 2).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn purge_older_than_drops_old_rows_only() {
+        let conn = make_conn();
+        insert_entry(&conn, &entry(Some("a1"), 1000, AuditOutcome::Ok)).unwrap();
+        insert_entry(&conn, &entry(Some("a1"), 5000, AuditOutcome::Ok)).unwrap();
+
+        assert_eq!(purge_older_than(&conn, 3000).unwrap(), 1);
+        assert_eq!(list_recent(&conn, None, 10).unwrap()[0].called_at, 5000);
+    }
+}
@@ -0,0 +1,174 @@
+//! Per-agent limits on the shell and file-system tools. One row per agent
+//! that has customised its policy; agents without a row run under
+//! [`AgentToolPolicy::default`]. Enforcement lives in
+//! [`crate::agents::tool_policy`]; this module only persists.
+
+use crate::error::AppError;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Default cap on captured command output and file reads, per stream.
+pub const DEFAULT_MAX_OUTPUT_BYTES: u32 = 64 * 1024;
+/// Upper bound a policy may raise the output cap to.
+pub const MAX_OUTPUT_BYTES_LIMIT: u32 = 4 * 1024 * 1024;
+/// Default wall-clock limit for one `shell-exec` call.
+pub const DEFAULT_TIMEOUT_SECS: u32 = 60;
+/// Upper bound a policy may raise the timeout to.
+pub const TIMEOUT_SECS_LIMIT: u32 = 600;
+
+/// What `fs-write` does once a path passes the policy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum WriteMode {
+    /// Write straight away.
+    #[default]
+    Direct,
+    /// Report what would be written without touching the disk.
+    DryRun,
+    /// Ask the user and write only if they approve.
+    Confirm,
+}
+
+/// An empty list means "no restriction" for every list field; the
+/// credential and OS deny-list in [`crate::files_scope`] applies regardless.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AgentToolPolicy {
+    /// Executables `shell-exec` may run — bare names resolved on `PATH`
+    /// (`git`) or absolute paths, matched exactly.
+    pub allowed_commands: Vec<String>,
+    /// Directories `shell-exec` may run in (`~` is expanded). The first one
+    /// is used when the model doesn't pass a `cwd`.
+    pub working_dirs: Vec<String>,
+    /// `files:read`-style globs `fs-read` may open.
+    pub read_paths: Vec<String>,
+    /// `files:read`-style globs `fs-write` may write to.
+    pub write_paths: Vec<String>,
+    pub max_output_bytes: u32,
+    pub timeout_secs: u32,
+    pub write_mode: WriteMode,
+}
+
+impl Default for AgentToolPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_commands: Vec::new(),
+            working_dirs: Vec::new(),
+            read_paths: Vec::new(),
+            write_paths: Vec::new(),
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            write_mode: WriteMode::Direct,
+        }
+    }
+}
+
+pub fn init_table(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS agent_tool_policies (
+            agent_id   TEXT    PRIMARY KEY,
+            policy     TEXT    NOT NULL,
+            updated_at INTEGER NOT NULL,
+            FOREIGN KEY (agent_id) REFERENCES agents(id) ON DELETE CASCADE
+        );",
+    )
+    .map_err(|e| AppError::Database(format!("Failed to init agent_tool_policies table: {e}")))?;
+    Ok(())
+}
+
+/// The stored policy for `agent_id`, or `None` when it runs on the default.
+pub fn get(conn: &Connection, agent_id: &str) -> Result<Option<AgentToolPolicy>, AppError> {
+    let raw: Option<String> = conn
+        .query_row(
+            "SELECT policy FROM agent_tool_policies WHERE agent_id = ?1",
+            params![agent_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    raw.map(|json| {
+        serde_json::from_str(&json)
+            .map_err(|e| AppError::Database(format!("deserialize agent tool policy: {e}")))
+    })
+    .transpose()
+}
+
+pub fn set(
+    conn: &Connection,
+    agent_id: &str,
+    policy: &AgentToolPolicy,
+    updated_at: i64,
+) -> Result<(), AppError> {
+    let json = serde_json::to_string(policy)
+        .map_err(|e| AppError::Database(format!("serialize agent tool policy: {e}")))?;
+    conn.execute(
+        "INSERT INTO agent_tool_policies (agent_id, policy, updated_at)
+         VALUES (?1, ?2, ?3)
+         ON CONFLICT(agent_id) DO UPDATE SET policy = ?2, updated_at = ?3",
+        params![agent_id, json, updated_at],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Drops the agent's policy. Production runs with foreign keys off, so
+/// deleting an agent calls this rather than relying on the cascade.
+pub fn remove(conn: &Connection, agent_id: &str) -> Result<bool, AppError> {
+    let n = conn
+        .execute(
+            "DELETE FROM agent_tool_policies WHERE agent_id = ?1",
+            params![agent_id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(n > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_conn() -> Connection {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        init_table(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn get_is_none_until_a_policy_is_set() {
+        let conn = make_conn();
+        assert_eq!(get(&conn, "a1").unwrap(), None);
+    }
+
+    #[test]
+    fn set_round_trips_and_overwrites() {
+        let conn = make_conn();
+        let mut policy = AgentToolPolicy {
+            allowed_commands: vec!["git".into()],
+            write_mode: WriteMode::Confirm,
+            ..AgentToolPolicy::default()
+        };
+        set(&conn, "a1", &policy, 1).unwrap();
+        assert_eq!(get(&conn, "a1").unwrap(), Some(policy.clone()));
+
+        policy.timeout_secs = 5;
+        set(&conn, "a1", &policy, 2).unwrap();
+        assert_eq!(get(&conn, "a1").unwrap().unwrap().timeout_secs, 5);
+    }
+
+    #[test]
+    fn missing_fields_fall_back_to_defaults() {
+        let policy: AgentToolPolicy = serde_json::from_str(r#"{"readPaths":["~/**"]}"#).unwrap();
+        assert_eq!(policy.read_paths, vec!["~/**".to_string()]);
+        assert_eq!(policy.max_output_bytes, DEFAULT_MAX_OUTPUT_BYTES);
+        assert_eq!(policy.write_mode, WriteMode::Direct);
+    }
+
+    #[test]
+    fn remove_reports_whether_a_row_existed() {
+        let conn = make_conn();
+        set(&conn, "a1", &AgentToolPolicy::default(), 1).unwrap();
+        assert!(remove(&conn, "a1").unwrap());
+        assert!(!remove(&conn, "a1").unwrap());
+        assert_eq!(get(&conn, "a1").unwrap(), None);
+    }
+}
@@ -0,0 +1,356 @@
+//! Background triggers for agents — "every weekday at 9:00", "when a file
+//! lands in ~/Downloads", "when the clipboard matches a pattern".
+//!
+//! Rows survive restart like [`super::timers`]: a schedule trigger keeps its
+//! next due time in `next_fire_at`, so one that came due while Asyar was
+//! quit still fires (once) at the next boot. Evaluation lives in
+//! [`crate::agents::triggers`]; this module only persists.
+
+use crate::error::AppError;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// What makes a trigger fire. Stored as JSON in the `spec` column.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TriggerSpec {
+    /// Local wall-clock time `HH:MM` on the given ISO weekdays (1 = Monday
+    /// … 7 = Sunday). An empty list means every day.
+    Schedule { weekdays: Vec<u8>, time: String },
+    /// A new file appears directly inside `directory` (`~` is expanded).
+    FileCreated { directory: String },
+    /// Copied text matches the regular expression `pattern`.
+    ClipboardMatch { pattern: String },
+}
+
+/// Where the agent's answer goes once a triggered run finishes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "lowercase")]
+pub enum TriggerOutput {
+    Note,
+    Notification,
+    Hud,
+}
+
+impl TriggerOutput {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Note => "note",
+            Self::Notification => "notification",
+            Self::Hud => "hud",
+        }
+    }
+
+    fn parse(raw: &str) -> Self {
+        match raw {
+            "note" => Self::Note,
+            "hud" => Self::Hud,
+            _ => Self::Notification,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentTrigger {
+    pub id: String,
+    pub agent_id: String,
+    pub spec: TriggerSpec,
+    /// Sent as the user message; the event's details (file path, copied
+    /// text) are appended to it.
+    #[serde(default)]
+    pub prompt: String,
+    pub output: TriggerOutput,
+    pub enabled: bool,
+    /// Unix millis of the next scheduled fire; `None` for event triggers.
+    #[serde(default)]
+    pub next_fire_at: Option<i64>,
+    #[serde(default)]
+    pub last_fired_at: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+pub fn init_table(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS agent_triggers (
+            id            TEXT    PRIMARY KEY,
+            agent_id      TEXT    NOT NULL,
+            spec          TEXT    NOT NULL,
+            prompt        TEXT    NOT NULL DEFAULT '',
+            output        TEXT    NOT NULL DEFAULT 'notification',
+            enabled       INTEGER NOT NULL DEFAULT 1,
+            next_fire_at  INTEGER,
+            last_fired_at INTEGER,
+            created_at    INTEGER NOT NULL,
+            updated_at    INTEGER NOT NULL,
+            FOREIGN KEY (agent_id) REFERENCES agents(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_agent_triggers_agent
+            ON agent_triggers(agent_id);
+        CREATE INDEX IF NOT EXISTS idx_agent_triggers_next_fire
+            ON agent_triggers(enabled, next_fire_at);",
+    )
+    .map_err(|e| AppError::Database(format!("Failed to init agent_triggers table: {e}")))?;
+    Ok(())
+}
+
+const TRIGGER_COLUMNS: &str = "id, agent_id, spec, prompt, output, enabled, next_fire_at, \
+     last_fired_at, created_at, updated_at";
+
+fn trigger_from_row(row: &rusqlite::Row) -> rusqlite::Result<AgentTrigger> {
+    let spec: String = row.get(2)?;
+    let output: String = row.get(4)?;
+    Ok(AgentTrigger {
+        id: row.get(0)?,
+        agent_id: row.get(1)?,
+        spec: serde_json::from_str(&spec).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        prompt: row.get(3)?,
+        output: TriggerOutput::parse(&output),
+        enabled: row.get::<_, i64>(5)? != 0,
+        next_fire_at: row.get(6)?,
+        last_fired_at: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
+/// Inserts `trigger`, or replaces every field of the row with its id.
+pub fn upsert(conn: &Connection, trigger: &AgentTrigger) -> Result<(), AppError> {
+    let spec = serde_json::to_string(&trigger.spec)
+        .map_err(|e| AppError::Other(format!("Failed to serialize trigger spec: {e}")))?;
+    conn.execute(
+        "INSERT INTO agent_triggers
+            (id, agent_id, spec, prompt, output, enabled, next_fire_at, last_fired_at,
+             created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+         ON CONFLICT(id) DO UPDATE SET
+            agent_id = excluded.agent_id,
+            spec = excluded.spec,
+            prompt = excluded.prompt,
+            output = excluded.output,
+            enabled = excluded.enabled,
+            next_fire_at = excluded.next_fire_at,
+            last_fired_at = excluded.last_fired_at,
+            updated_at = excluded.updated_at",
+        params![
+            trigger.id,
+            trigger.agent_id,
+            spec,
+            trigger.prompt,
+            trigger.output.as_str(),
+            trigger.enabled as i64,
+            trigger.next_fire_at,
+            trigger.last_fired_at,
+            trigger.created_at,
+            trigger.updated_at,
+        ],
+    )
+    .map_err(|e| AppError::Database(format!("Failed to save agent trigger: {e}")))?;
+    Ok(())
+}
+
+/// Triggers oldest first; all of them, or only `agent_id`'s.
+pub fn list(conn: &Connection, agent_id: Option<&str>) -> Result<Vec<AgentTrigger>, AppError> {
+    let filter = if agent_id.is_some() {
+        "WHERE agent_id = ?1"
+    } else {
+        ""
+    };
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {TRIGGER_COLUMNS} FROM agent_triggers {filter} ORDER BY created_at, rowid"
+        ))
+        .map_err(|e| AppError::Database(format!("Failed to prepare query: {e}")))?;
+    let rows = match agent_id {
+        None => stmt.query_map([], trigger_from_row),
+        Some(agent_id) => stmt.query_map(params![agent_id], trigger_from_row),
+    }
+    .map_err(|e| AppError::Database(format!("Failed to query agent triggers: {e}")))?;
+    Ok(rows.filter_map(Result::ok).collect())
+}
+
+/// Enabled triggers only — the set the evaluator watches.
+pub fn list_enabled(conn: &Connection) -> Result<Vec<AgentTrigger>, AppError> {
+    Ok(list(conn, None)?
+        .into_iter()
+        .filter(|trigger| trigger.enabled)
+        .collect())
+}
+
+/// Enabled schedule triggers whose `next_fire_at` is at or before `now_ms`.
+pub fn due(conn: &Connection, now_ms: i64) -> Result<Vec<AgentTrigger>, AppError> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {TRIGGER_COLUMNS} FROM agent_triggers
+             WHERE enabled = 1 AND next_fire_at IS NOT NULL AND next_fire_at <= ?1
+             ORDER BY next_fire_at"
+        ))
+        .map_err(|e| AppError::Database(format!("Failed to prepare query: {e}")))?;
+    let rows = stmt
+        .query_map(params![now_ms], trigger_from_row)
+        .map_err(|e| AppError::Database(format!("Failed to query due triggers: {e}")))?;
+    Ok(rows.filter_map(Result::ok).collect())
+}
+
+pub fn get(conn: &Connection, id: &str) -> Result<Option<AgentTrigger>, AppError> {
+    match conn.query_row(
+        &format!("SELECT {TRIGGER_COLUMNS} FROM agent_triggers WHERE id = ?1"),
+        params![id],
+        trigger_from_row,
+    ) {
+        Ok(trigger) => Ok(Some(trigger)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(AppError::Database(format!(
+            "Failed to get agent trigger: {e}"
+        ))),
+    }
+}
+
+/// Records a fire and the schedule's next due time (`None` for event
+/// triggers).
+pub fn mark_fired(
+    conn: &Connection,
+    id: &str,
+    fired_at: i64,
+    next_fire_at: Option<i64>,
+) -> Result<(), AppError> {
+    conn.execute(
+        "UPDATE agent_triggers SET last_fired_at = ?1, next_fire_at = ?2 WHERE id = ?3",
+        params![fired_at, next_fire_at, id],
+    )
+    .map_err(|e| AppError::Database(format!("Failed to mark trigger fired: {e}")))?;
+    Ok(())
+}
+
+/// Deletes a trigger. Returns whether it existed.
+pub fn remove(conn: &Connection, id: &str) -> Result<bool, AppError> {
+    let deleted = conn
+        .execute("DELETE FROM agent_triggers WHERE id = ?1", params![id])
+        .map_err(|e| AppError::Database(format!("Failed to delete agent trigger: {e}")))?;
+    Ok(deleted > 0)
+}
+
+/// Deletes every trigger of `agent_id`; the foreign key's cascade only
+/// applies where `foreign_keys` is on, which production leaves off.
+pub fn remove_for_agent(conn: &Connection, agent_id: &str) -> Result<(), AppError> {
+    conn.execute(
+        "DELETE FROM agent_triggers WHERE agent_id = ?1",
+        params![agent_id],
+    )
+    .map_err(|e| AppError::Database(format!("Failed to delete agent triggers: {e}")))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_table(&conn).unwrap();
+        conn
+    }
+
+    fn trigger(id: &str, agent_id: &str, spec: TriggerSpec, next: Option<i64>) -> AgentTrigger {
+        AgentTrigger {
+            id: id.to_string(),
+            agent_id: agent_id.to_string(),
+            spec,
+            prompt: "Summarize".to_string(),
+            output: TriggerOutput::Note,
+            enabled: true,
+            next_fire_at: next,
+            last_fired_at: None,
+            created_at: 1,
+            updated_at: 1,
+        }
+    }
+
+    fn schedule() -> TriggerSpec {
+        TriggerSpec::Schedule {
+            weekdays: vec![1, 2, 3, 4, 5],
+            time: "09:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn upsert_round_trips_and_replaces() {
+        let conn = setup();
+        let mut t = trigger("t1", "a1", schedule(), Some(100));
+        upsert(&conn, &t).unwrap();
+        assert_eq!(get(&conn, "t1").unwrap().unwrap(), t);
+
+        t.spec = TriggerSpec::ClipboardMatch {
+            pattern: "^https://".to_string(),
+        };
+        t.output = TriggerOutput::Hud;
+        t.next_fire_at = None;
+        upsert(&conn, &t).unwrap();
+        assert_eq!(get(&conn, "t1").unwrap().unwrap(), t);
+        assert_eq!(list(&conn, None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn spec_serializes_with_a_type_tag() {
+        let json = serde_json::to_value(TriggerSpec::FileCreated {
+            directory: "~/Downloads".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"type": "fileCreated", "directory": "~/Downloads"})
+        );
+    }
+
+    #[test]
+    fn due_returns_only_enabled_schedules_that_came_due() {
+        let conn = setup();
+        upsert(&conn, &trigger("past", "a1", schedule(), Some(50))).unwrap();
+        upsert(&conn, &trigger("future", "a1", schedule(), Some(500))).unwrap();
+        let mut off = trigger("off", "a1", schedule(), Some(10));
+        off.enabled = false;
+        upsert(&conn, &off).unwrap();
+        let clip = TriggerSpec::ClipboardMatch {
+            pattern: "x".to_string(),
+        };
+        upsert(&conn, &trigger("event", "a1", clip, None)).unwrap();
+
+        let ids: Vec<_> = due(&conn, 100).unwrap().into_iter().map(|t| t.id).collect();
+        assert_eq!(ids, ["past"]);
+        assert_eq!(list_enabled(&conn).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn mark_fired_records_the_fire_and_reschedules() {
+        let conn = setup();
+        upsert(&conn, &trigger("t1", "a1", schedule(), Some(50))).unwrap();
+        mark_fired(&conn, "t1", 60, Some(900)).unwrap();
+
+        let t = get(&conn, "t1").unwrap().unwrap();
+        assert_eq!(t.last_fired_at, Some(60));
+        assert_eq!(t.next_fire_at, Some(900));
+        assert!(due(&conn, 100).unwrap().is_empty());
+    }
+
+    #[test]
+    fn list_filters_by_agent_and_removal_by_id_or_agent() {
+        let conn = setup();
+        upsert(&conn, &trigger("t1", "a1", schedule(), None)).unwrap();
+        upsert(&conn, &trigger("t2", "a2", schedule(), None)).unwrap();
+
+        let ids: Vec<_> = list(&conn, Some("a2"))
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(ids, ["t2"]);
+        assert!(remove(&conn, "t1").unwrap());
+        assert!(!remove(&conn, "t1").unwrap());
+        remove_for_agent(&conn, "a2").unwrap();
+        assert!(list(&conn, None).unwrap().is_empty());
+    }
+}
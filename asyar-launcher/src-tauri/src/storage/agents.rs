@@ -1,5 +1,7 @@
+use crate::ai::routing::{AttemptOutcome, RoutingPolicy};
+use crate::ai::types::TokenUsage;
 use crate::error::AppError;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 
 /// Where the silent-AI dispatcher pulls the input from before it calls the
@@ -112,6 +114,18 @@ pub struct AgentRow {
     pub cache_responses: bool,
     /// Delimiter/trigger pattern (e.g. ":", ";") for shortcode miss event activation.
     pub shortcode_trigger: String,
+    /// Spend cap in USD per calendar month (local time). Runs are refused
+    /// once priced usage this month reaches it; `None` means no cap.
+    #[serde(default)]
+    pub monthly_budget_usd: Option<f64>,
+    /// Retry and fallback rules applied when a provider request fails
+    /// (stored as JSON object in SQLite).
+    #[serde(default)]
+    pub routing: RoutingPolicy,
+    /// JSON Schema the final answer must satisfy; `None` means free text
+    /// (stored as JSON TEXT in SQLite).
+    #[serde(default)]
+    pub output_schema: Option<serde_json::Value>,
     pub created_at: Option<i64>,
     pub updated_at: Option<i64>,
 }
@@ -123,6 +137,11 @@ pub struct ThreadRow {
     pub id: String,
     pub agent_id: String,
     pub title: Option<String>,
+    /// Last message of the selected branch; the conversation shown and sent
+    /// to the model is the path from the root down to it. `None` until the
+    /// first message lands.
+    #[serde(default)]
+    pub current_leaf_id: Option<String>,
     pub created_at: Option<i64>,
     pub updated_at: Option<i64>,
 }
@@ -147,12 +166,40 @@ pub struct MessageRow {
     pub content: serde_json::Value,
     pub created_at: i64,
     pub run_id: Option<String>,
+    /// The message this one follows. Messages sharing a parent are sibling
+    /// branches — an edited prompt or a regenerated answer; `None` for the
+    /// first message of a branch that starts at the thread's root.
+    #[serde(default)]
+    pub parent_id: Option<String>,
 }
 
-/// Idempotent: creates the agents, threads, and messages tables and their
-/// indexes if missing. Also patches in the silent-AI columns (`silent`,
-/// `input_source`, `output_action`) for installs whose `agents` table
-/// predates them — mirrors the `runs_history.subject_id` / `tail_output`
+/// Summary of a thread's older messages, written when the thread outgrew its
+/// model's context window. It stands in for every message on the path up to
+/// and including `through_message_id`; on a branch that doesn't contain
+/// that message it doesn't apply.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadSummary {
+    pub text: String,
+    pub through_message_id: String,
+}
+
+/// A message on a thread's selected path, with the ids of every branch that
+/// forks at the same point (itself included, oldest first).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathMessage {
+    #[serde(flatten)]
+    pub message: MessageRow,
+    pub sibling_ids: Vec<String>,
+}
+
+/// Idempotent: creates the agents, threads, messages, message_usage and
+/// agent_run_attempts tables and their indexes if missing. Also patches in
+/// the silent-AI columns (`silent`, `input_source`, `output_action`) and
+/// later additions such as `monthly_budget_usd`, `routing_policy`,
+/// `output_schema` and the message-branch columns for installs whose tables
+/// predate them — mirrors the `runs_history.subject_id` / `tail_output`
 /// ALTER TABLE guard pattern.
 pub fn init_table(conn: &Connection) -> Result<(), AppError> {
     conn.execute_batch(
@@ -169,6 +216,9 @@ pub fn init_table(conn: &Connection) -> Result<(), AppError> {
             output_action   TEXT    NOT NULL DEFAULT 'replaceSelection',
             cache_responses INTEGER NOT NULL DEFAULT 0,
             shortcode_trigger TEXT  NOT NULL DEFAULT ':',
+            monthly_budget_usd REAL,
+            routing_policy  TEXT    NOT NULL DEFAULT '{}',
+            output_schema   TEXT,
             created_at      INTEGER NOT NULL,
             updated_at      INTEGER NOT NULL
         );
@@ -177,6 +227,9 @@ pub fn init_table(conn: &Connection) -> Result<(), AppError> {
             id          TEXT    PRIMARY KEY,
             agent_id    TEXT    NOT NULL,
             title       TEXT,
+            current_leaf_id TEXT,
+            context_summary TEXT,
+            context_summary_through TEXT,
             created_at  INTEGER NOT NULL,
             updated_at  INTEGER NOT NULL,
             FOREIGN KEY (agent_id) REFERENCES agents(id) ON DELETE CASCADE
@@ -192,11 +245,51 @@ pub fn init_table(conn: &Connection) -> Result<(), AppError> {
             content     TEXT    NOT NULL,
             created_at  INTEGER NOT NULL,
             run_id      TEXT,
+            parent_id   TEXT,
             FOREIGN KEY (thread_id) REFERENCES threads(id) ON DELETE CASCADE
         );
 
         CREATE INDEX IF NOT EXISTS idx_messages_thread_created
-            ON messages(thread_id, created_at);",
+            ON messages(thread_id, created_at);
+
+        CREATE TABLE IF NOT EXISTS message_usage (
+            id                TEXT    PRIMARY KEY,
+            message_id        TEXT,
+            thread_id         TEXT,
+            run_id            TEXT,
+            agent_id          TEXT    NOT NULL,
+            provider_id       TEXT    NOT NULL,
+            model_id          TEXT    NOT NULL,
+            input_tokens      INTEGER NOT NULL DEFAULT 0,
+            output_tokens     INTEGER NOT NULL DEFAULT 0,
+            cached_tokens     INTEGER NOT NULL DEFAULT 0,
+            reasoning_tokens  INTEGER NOT NULL DEFAULT 0,
+            cost_usd          REAL,
+            created_at        INTEGER NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_message_usage_agent_created
+            ON message_usage(agent_id, created_at);
+        CREATE INDEX IF NOT EXISTS idx_message_usage_created
+            ON message_usage(created_at);
+
+        CREATE TABLE IF NOT EXISTS agent_run_attempts (
+            id           TEXT    PRIMARY KEY,
+            run_id       TEXT,
+            agent_id     TEXT    NOT NULL,
+            turn         INTEGER NOT NULL,
+            attempt      INTEGER NOT NULL,
+            provider_id  TEXT    NOT NULL,
+            model_id     TEXT    NOT NULL,
+            outcome      TEXT    NOT NULL,
+            status_code  INTEGER,
+            error        TEXT,
+            started_at   INTEGER NOT NULL,
+            ended_at     INTEGER NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_agent_run_attempts_run
+            ON agent_run_attempts(run_id, attempt);",
     )
     .map_err(|e| AppError::Database(format!("Failed to init agents tables: {e}")))?;
 
@@ -243,6 +336,72 @@ pub fn init_table(conn: &Connection) -> Result<(), AppError> {
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
     }
+    if !cols.contains(&"monthly_budget_usd".to_string()) {
+        conn.execute("ALTER TABLE agents ADD COLUMN monthly_budget_usd REAL", [])
+            .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    if !cols.contains(&"routing_policy".to_string()) {
+        conn.execute(
+            "ALTER TABLE agents ADD COLUMN routing_policy TEXT NOT NULL DEFAULT '{}'",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    if !cols.contains(&"output_schema".to_string()) {
+        conn.execute("ALTER TABLE agents ADD COLUMN output_schema TEXT", [])
+            .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+
+    let message_cols: Vec<String> = conn
+        .prepare("PRAGMA table_info(messages)")
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .query_map([], |row| row.get::<_, String>(1))
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .filter_map(Result::ok)
+        .collect();
+    if !message_cols.contains(&"parent_id".to_string()) {
+        // Threads written before branching were a flat, append-only list:
+        // chain each message to the one before it and select the newest as
+        // the leaf, so every old thread becomes a single branch.
+        conn.execute_batch(
+            "ALTER TABLE messages ADD COLUMN parent_id TEXT;
+             ALTER TABLE threads ADD COLUMN current_leaf_id TEXT;
+             UPDATE messages SET parent_id = (
+                 SELECT p.id FROM messages p
+                 WHERE p.thread_id = messages.thread_id
+                   AND (p.created_at < messages.created_at
+                        OR (p.created_at = messages.created_at AND p.rowid < messages.rowid))
+                 ORDER BY p.created_at DESC, p.rowid DESC
+                 LIMIT 1
+             );
+             UPDATE threads SET current_leaf_id = (
+                 SELECT m.id FROM messages m
+                 WHERE m.thread_id = threads.id
+                 ORDER BY m.created_at DESC, m.rowid DESC
+                 LIMIT 1
+             );",
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    let thread_cols: Vec<String> = conn
+        .prepare("PRAGMA table_info(threads)")
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .query_map([], |row| row.get::<_, String>(1))
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .filter_map(Result::ok)
+        .collect();
+    if !thread_cols.contains(&"context_summary".to_string()) {
+        conn.execute_batch(
+            "ALTER TABLE threads ADD COLUMN context_summary TEXT;
+             ALTER TABLE threads ADD COLUMN context_summary_through TEXT;",
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_messages_parent ON messages(parent_id)",
+        [],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
     Ok(())
 }
 
@@ -250,11 +409,18 @@ pub fn init_table(conn: &Connection) -> Result<(), AppError> {
 pub fn insert_agent(conn: &Connection, agent: &AgentRow) -> Result<(), AppError> {
     let tool_json = serde_json::to_string(&agent.tool_selection)
         .map_err(|e| AppError::Database(format!("serialize tool_selection: {e}")))?;
+    let routing_json = serde_json::to_string(&agent.routing)
+        .map_err(|e| AppError::Database(format!("serialize routing_policy: {e}")))?;
+    let schema_json = agent
+        .output_schema
+        .as_ref()
+        .map(|schema| schema.to_string());
     conn.execute(
         "INSERT INTO agents (id, name, description, system_prompt, provider_id, model_id,
                              tool_selection, silent, input_source, output_action, cache_responses,
-                             shortcode_trigger, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                             shortcode_trigger, monthly_budget_usd, routing_policy,
+                             output_schema, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
         params![
             agent.id,
             agent.name,
@@ -268,6 +434,9 @@ pub fn insert_agent(conn: &Connection, agent: &AgentRow) -> Result<(), AppError>
             agent.output_action.as_str(),
             agent.cache_responses as i64,
             agent.shortcode_trigger,
+            agent.monthly_budget_usd,
+            routing_json,
+            schema_json,
             agent.created_at,
             agent.updated_at,
         ],
@@ -280,10 +449,18 @@ pub fn insert_agent(conn: &Connection, agent: &AgentRow) -> Result<(), AppError>
 pub fn update_agent(conn: &Connection, agent: &AgentRow) -> Result<(), AppError> {
     let tool_json = serde_json::to_string(&agent.tool_selection)
         .map_err(|e| AppError::Database(format!("serialize tool_selection: {e}")))?;
+    let routing_json = serde_json::to_string(&agent.routing)
+        .map_err(|e| AppError::Database(format!("serialize routing_policy: {e}")))?;
+    let schema_json = agent
+        .output_schema
+        .as_ref()
+        .map(|schema| schema.to_string());
     conn.execute(
         "UPDATE agents SET name=?2, description=?3, system_prompt=?4, provider_id=?5,
          model_id=?6, tool_selection=?7, silent=?8, input_source=?9, output_action=?10,
-         cache_responses=?11, shortcode_trigger=?12, updated_at=?13 WHERE id=?1",
+         cache_responses=?11, shortcode_trigger=?12, monthly_budget_usd=?13,
+         routing_policy=?14, output_schema=?15, updated_at=?16
+         WHERE id=?1",
         params![
             agent.id,
             agent.name,
@@ -297,6 +474,9 @@ pub fn update_agent(conn: &Connection, agent: &AgentRow) -> Result<(), AppError>
             agent.output_action.as_str(),
             agent.cache_responses as i64,
             agent.shortcode_trigger,
+            agent.monthly_budget_usd,
+            routing_json,
+            schema_json,
             agent.updated_at,
         ],
     )
@@ -312,13 +492,22 @@ pub fn delete_agent(conn: &Connection, id: &str) -> Result<(), AppError> {
     Ok(())
 }
 
+fn parse_output_schema(json: Option<String>) -> Result<Option<serde_json::Value>, AppError> {
+    json.map(|json| {
+        serde_json::from_str(&json)
+            .map_err(|e| AppError::Database(format!("deserialize output_schema: {e}")))
+    })
+    .transpose()
+}
+
 /// Return all agents ordered by `created_at` ascending.
 pub fn list_agents(conn: &Connection) -> Result<Vec<AgentRow>, AppError> {
     let mut stmt = conn
         .prepare(
             "SELECT id, name, description, system_prompt, provider_id, model_id,
                     tool_selection, silent, input_source, output_action, cache_responses,
-                    shortcode_trigger, created_at, updated_at
+                    shortcode_trigger, monthly_budget_usd, routing_policy, output_schema,
+                    created_at, updated_at
              FROM agents
              ORDER BY created_at ASC",
         )
@@ -339,8 +528,11 @@ pub fn list_agents(conn: &Connection) -> Result<Vec<AgentRow>, AppError> {
                 row.get::<_, String>(9)?,
                 row.get::<_, i64>(10)?,
                 row.get::<_, String>(11)?,
-                row.get::<_, Option<i64>>(12)?,
-                row.get::<_, Option<i64>>(13)?,
+                row.get::<_, Option<f64>>(12)?,
+                row.get::<_, String>(13)?,
+                row.get::<_, Option<String>>(14)?,
+                row.get::<_, Option<i64>>(15)?,
+                row.get::<_, Option<i64>>(16)?,
             ))
         })
         .map_err(|e| AppError::Database(e.to_string()))?;
@@ -360,11 +552,17 @@ pub fn list_agents(conn: &Connection) -> Result<Vec<AgentRow>, AppError> {
             output_str,
             cache_resp_int,
             shortcode_trigger,
+            monthly_budget_usd,
+            routing_json,
+            schema_json,
             created_at,
             updated_at,
         ) = row.map_err(|e| AppError::Database(e.to_string()))?;
         let tool_selection = serde_json::from_str::<Vec<String>>(&tool_json)
             .map_err(|e| AppError::Database(format!("deserialize tool_selection: {e}")))?;
+        let routing = serde_json::from_str::<RoutingPolicy>(&routing_json)
+            .map_err(|e| AppError::Database(format!("deserialize routing_policy: {e}")))?;
+        let output_schema = parse_output_schema(schema_json)?;
         agents.push(AgentRow {
             id,
             name,
@@ -378,6 +576,9 @@ pub fn list_agents(conn: &Connection) -> Result<Vec<AgentRow>, AppError> {
             output_action: SilentOutputAction::parse(&output_str),
             cache_responses: cache_resp_int != 0,
             shortcode_trigger,
+            monthly_budget_usd,
+            routing,
+            output_schema,
             created_at,
             updated_at,
         });
@@ -391,7 +592,8 @@ pub fn get_agent(conn: &Connection, id: &str) -> Result<Option<AgentRow>, AppErr
         .prepare(
             "SELECT id, name, description, system_prompt, provider_id, model_id,
                     tool_selection, silent, input_source, output_action, cache_responses,
-                    shortcode_trigger, created_at, updated_at
+                    shortcode_trigger, monthly_budget_usd, routing_policy, output_schema,
+                    created_at, updated_at
              FROM agents
              WHERE id = ?1",
         )
@@ -412,8 +614,11 @@ pub fn get_agent(conn: &Connection, id: &str) -> Result<Option<AgentRow>, AppErr
                 row.get::<_, String>(9)?,
                 row.get::<_, i64>(10)?,
                 row.get::<_, String>(11)?,
-                row.get::<_, Option<i64>>(12)?,
-                row.get::<_, Option<i64>>(13)?,
+                row.get::<_, Option<f64>>(12)?,
+                row.get::<_, String>(13)?,
+                row.get::<_, Option<String>>(14)?,
+                row.get::<_, Option<i64>>(15)?,
+                row.get::<_, Option<i64>>(16)?,
             ))
         })
         .map_err(|e| AppError::Database(e.to_string()))?;
@@ -434,11 +639,17 @@ pub fn get_agent(conn: &Connection, id: &str) -> Result<Option<AgentRow>, AppErr
                 output_str,
                 cache_resp_int,
                 shortcode_trigger,
+                monthly_budget_usd,
+                routing_json,
+                schema_json,
                 created_at,
                 updated_at,
             ) = row.map_err(|e| AppError::Database(e.to_string()))?;
             let tool_selection = serde_json::from_str::<Vec<String>>(&tool_json)
                 .map_err(|e| AppError::Database(format!("deserialize tool_selection: {e}")))?;
+            let routing = serde_json::from_str::<RoutingPolicy>(&routing_json)
+                .map_err(|e| AppError::Database(format!("deserialize routing_policy: {e}")))?;
+            let output_schema = parse_output_schema(schema_json)?;
             Ok(Some(AgentRow {
                 id,
                 name,
@@ -452,6 +663,9 @@ pub fn get_agent(conn: &Connection, id: &str) -> Result<Option<AgentRow>, AppErr
                 output_action: SilentOutputAction::parse(&output_str),
                 cache_responses: cache_resp_int != 0,
                 shortcode_trigger,
+                monthly_budget_usd,
+                routing,
+                output_schema,
                 created_at,
                 updated_at,
             }))
@@ -462,12 +676,13 @@ pub fn get_agent(conn: &Connection, id: &str) -> Result<Option<AgentRow>, AppErr
 /// Insert a new thread row.
 pub fn insert_thread(conn: &Connection, thread: &ThreadRow) -> Result<(), AppError> {
     conn.execute(
-        "INSERT INTO threads (id, agent_id, title, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5)",
+        "INSERT INTO threads (id, agent_id, title, current_leaf_id, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
         params![
             thread.id,
             thread.agent_id,
             thread.title,
+            thread.current_leaf_id,
             thread.created_at,
             thread.updated_at,
         ],
@@ -480,7 +695,7 @@ pub fn insert_thread(conn: &Connection, thread: &ThreadRow) -> Result<(), AppErr
 pub fn get_thread(conn: &Connection, id: &str) -> Result<Option<ThreadRow>, AppError> {
     let mut stmt = conn
         .prepare(
-            "SELECT id, agent_id, title, created_at, updated_at
+            "SELECT id, agent_id, title, current_leaf_id, created_at, updated_at
              FROM threads
              WHERE id = ?1",
         )
@@ -492,8 +707,9 @@ pub fn get_thread(conn: &Connection, id: &str) -> Result<Option<ThreadRow>, AppE
                 id: row.get(0)?,
                 agent_id: row.get(1)?,
                 title: row.get(2)?,
-                created_at: row.get(3)?,
-                updated_at: row.get(4)?,
+                current_leaf_id: row.get(3)?,
+                created_at: row.get(4)?,
+                updated_at: row.get(5)?,
             })
         })
         .map_err(|error| AppError::Database(error.to_string()))?;
@@ -634,7 +850,7 @@ pub fn list_threads_for_agent(
 ) -> Result<Vec<ThreadRow>, AppError> {
     let mut stmt = conn
         .prepare(
-            "SELECT id, agent_id, title, created_at, updated_at
+            "SELECT id, agent_id, title, current_leaf_id, created_at, updated_at
              FROM threads
              WHERE agent_id = ?1
              ORDER BY updated_at DESC",
@@ -643,41 +859,58 @@ pub fn list_threads_for_agent(
 
     let rows = stmt
         .query_map(params![agent_id], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, Option<String>>(2)?,
-                row.get::<_, Option<i64>>(3)?,
-                row.get::<_, Option<i64>>(4)?,
-            ))
+            Ok(ThreadRow {
+                id: row.get(0)?,
+                agent_id: row.get(1)?,
+                title: row.get(2)?,
+                current_leaf_id: row.get(3)?,
+                created_at: row.get(4)?,
+                updated_at: row.get(5)?,
+            })
         })
         .map_err(|e| AppError::Database(e.to_string()))?;
 
     let mut threads = Vec::new();
     for row in rows {
-        let (id, agent_id, title, created_at, updated_at) =
-            row.map_err(|e| AppError::Database(e.to_string()))?;
-        threads.push(ThreadRow {
-            id,
-            agent_id,
-            title,
-            created_at,
-            updated_at,
-        });
+        threads.push(row.map_err(|e| AppError::Database(e.to_string()))?);
     }
     Ok(threads)
 }
 
-/// Insert a new message row.
+/// Append a message to its thread's selected branch: a row without
+/// `parent_id` follows the thread's current leaf. Either way the message
+/// becomes the new leaf.
 pub fn insert_message(conn: &Connection, msg: &MessageRow) -> Result<(), AppError> {
+    let parent_id = match &msg.parent_id {
+        Some(parent_id) => Some(parent_id.clone()),
+        None => conn
+            .query_row(
+                "SELECT current_leaf_id FROM threads WHERE id = ?1",
+                params![msg.thread_id],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .optional()
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .flatten(),
+    };
+    insert_message_after(conn, msg, parent_id.as_deref())
+}
+
+/// Insert `msg` as a child of `parent_id` (`None`: the thread's root) and
+/// select it as the thread's leaf. Used directly to fork a sibling branch.
+pub fn insert_message_after(
+    conn: &Connection,
+    msg: &MessageRow,
+    parent_id: Option<&str>,
+) -> Result<(), AppError> {
     let role_str = serde_json::to_string(&msg.role)
         .map_err(|e| AppError::Database(format!("serialize role: {e}")))?;
     let role_str = role_str.trim_matches('"').to_string();
     let content_json = serde_json::to_string(&msg.content)
         .map_err(|e| AppError::Database(format!("serialize content: {e}")))?;
     conn.execute(
-        "INSERT INTO messages (id, thread_id, role, content, created_at, run_id)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        "INSERT INTO messages (id, thread_id, role, content, created_at, run_id, parent_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
         params![
             msg.id,
             msg.thread_id,
@@ -685,55 +918,498 @@ pub fn insert_message(conn: &Connection, msg: &MessageRow) -> Result<(), AppErro
             content_json,
             msg.created_at,
             msg.run_id,
+            parent_id,
         ],
     )
     .map_err(|e| AppError::Database(e.to_string()))?;
+    conn.execute(
+        "UPDATE threads SET current_leaf_id = ?1 WHERE id = ?2",
+        params![msg.id, msg.thread_id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
     Ok(())
 }
 
-/// Return all messages for the given thread ordered by `created_at` ascending.
+const MESSAGE_COLUMNS: &str = "id, thread_id, role, content, created_at, run_id, parent_id";
+
+fn message_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<MessageRow> {
+    let role_str: String = row.get(2)?;
+    let content_json: String = row.get(3)?;
+    let role = serde_json::from_str::<MessageRole>(&format!("\"{role_str}\"")).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+    let content = serde_json::from_str::<serde_json::Value>(&content_json).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+    Ok(MessageRow {
+        id: row.get(0)?,
+        thread_id: row.get(1)?,
+        role,
+        content,
+        created_at: row.get(4)?,
+        run_id: row.get(5)?,
+        parent_id: row.get(6)?,
+    })
+}
+
+/// Return all messages for the given thread, on every branch, ordered by
+/// `created_at` ascending.
 pub fn list_messages_for_thread(
     conn: &Connection,
     thread_id: &str,
 ) -> Result<Vec<MessageRow>, AppError> {
     let mut stmt = conn
-        .prepare(
-            "SELECT id, thread_id, role, content, created_at, run_id
+        .prepare(&format!(
+            "SELECT {MESSAGE_COLUMNS}
              FROM messages
              WHERE thread_id = ?1
-             ORDER BY created_at ASC",
+             ORDER BY created_at ASC, rowid ASC"
+        ))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let rows = stmt
+        .query_map(params![thread_id], message_from_row)
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    rows.collect::<Result<_, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))
+}
+
+/// Return a single message by id, or `None` if not found.
+pub fn get_message(conn: &Connection, id: &str) -> Result<Option<MessageRow>, AppError> {
+    conn.query_row(
+        &format!("SELECT {MESSAGE_COLUMNS} FROM messages WHERE id = ?1"),
+        params![id],
+        message_from_row,
+    )
+    .optional()
+    .map_err(|e| AppError::Database(e.to_string()))
+}
+
+/// The thread's selected branch: root first, ending at `current_leaf_id`.
+pub fn list_thread_path(conn: &Connection, thread_id: &str) -> Result<Vec<MessageRow>, AppError> {
+    let mut stmt = conn
+        .prepare(
+            "WITH RECURSIVE path(id, depth) AS (
+                 SELECT current_leaf_id, 0 FROM threads
+                 WHERE id = ?1 AND current_leaf_id IS NOT NULL
+                 UNION ALL
+                 SELECT m.parent_id, path.depth + 1
+                 FROM messages m JOIN path ON m.id = path.id
+                 WHERE m.parent_id IS NOT NULL
+             )
+             SELECT m.id, m.thread_id, m.role, m.content, m.created_at, m.run_id, m.parent_id
+             FROM path JOIN messages m ON m.id = path.id
+             ORDER BY path.depth DESC",
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let rows = stmt
+        .query_map(params![thread_id], message_from_row)
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    rows.collect::<Result<_, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))
+}
+
+/// Ids of the messages that fork where `message` does, itself included,
+/// oldest first.
+pub fn list_sibling_ids(conn: &Connection, message: &MessageRow) -> Result<Vec<String>, AppError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id FROM messages
+             WHERE thread_id = ?1 AND parent_id IS ?2
+             ORDER BY created_at ASC, rowid ASC",
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let rows = stmt
+        .query_map(params![message.thread_id, message.parent_id], |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    rows.collect::<Result<_, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))
+}
+
+/// The selected path with each message's sibling branches, for the chat view.
+pub fn list_thread_path_with_siblings(
+    conn: &Connection,
+    thread_id: &str,
+) -> Result<Vec<PathMessage>, AppError> {
+    list_thread_path(conn, thread_id)?
+        .into_iter()
+        .map(|message| {
+            let sibling_ids = list_sibling_ids(conn, &message)?;
+            Ok(PathMessage {
+                message,
+                sibling_ids,
+            })
+        })
+        .collect()
+}
+
+/// Point the thread's selected branch at `leaf_id`, or at the root when
+/// `None` (the next message then starts a new top-level branch).
+pub fn set_current_leaf(
+    conn: &Connection,
+    thread_id: &str,
+    leaf_id: Option<&str>,
+) -> Result<(), AppError> {
+    let rows = conn
+        .execute(
+            "UPDATE threads SET current_leaf_id = ?1 WHERE id = ?2",
+            params![leaf_id, thread_id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "thread '{thread_id}' not found"
+        )));
+    }
+    Ok(())
+}
+
+pub fn get_thread_summary(
+    conn: &Connection,
+    thread_id: &str,
+) -> Result<Option<ThreadSummary>, AppError> {
+    let row = conn
+        .query_row(
+            "SELECT context_summary, context_summary_through FROM threads WHERE id = ?1",
+            params![thread_id],
+            |row| {
+                Ok((
+                    row.get::<_, Option<String>>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(match row {
+        Some((Some(text), Some(through_message_id))) => Some(ThreadSummary {
+            text,
+            through_message_id,
+        }),
+        _ => None,
+    })
+}
+
+/// Replace the thread's summary. A thread keeps one: compacting another
+/// branch overwrites it, and the branch it came from falls back to sending
+/// its full history until it is compacted again.
+pub fn set_thread_summary(
+    conn: &Connection,
+    thread_id: &str,
+    summary: &ThreadSummary,
+) -> Result<(), AppError> {
+    let rows = conn
+        .execute(
+            "UPDATE threads SET context_summary = ?1, context_summary_through = ?2 WHERE id = ?3",
+            params![summary.text, summary.through_message_id, thread_id],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "thread '{thread_id}' not found"
+        )));
+    }
+    Ok(())
+}
+
+/// Follow the newest child from `message_id` down to a leaf, so switching
+/// to a branch resumes where it was last extended.
+pub fn newest_leaf_under(conn: &Connection, message_id: &str) -> Result<String, AppError> {
+    let mut leaf = message_id.to_string();
+    loop {
+        let child = conn
+            .query_row(
+                "SELECT id FROM messages
+                 WHERE parent_id = ?1
+                 ORDER BY created_at DESC, rowid DESC
+                 LIMIT 1",
+                params![leaf],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        match child {
+            Some(child) => leaf = child,
+            None => return Ok(leaf),
+        }
+    }
+}
+
+/// Token usage and estimated cost of one provider response. `message_id` and
+/// `thread_id` are `None` for silent runs, which persist no messages but
+/// still count towards spend. Rows carry no foreign keys on purpose: spend
+/// history must survive deleting the thread or agent it came from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageRow {
+    pub id: String,
+    pub message_id: Option<String>,
+    pub thread_id: Option<String>,
+    pub run_id: Option<String>,
+    pub agent_id: String,
+    /// The provider connection that served the response — the agent's own
+    /// or a routing fallback. May be a named connection id such as
+    /// `custom_60f9a975`, never the engine type.
+    pub provider_id: String,
+    pub model_id: String,
+    #[serde(flatten)]
+    pub usage: TokenUsage,
+    /// `None` when the model isn't in `ai::pricing`'s table.
+    pub cost_usd: Option<f64>,
+    pub created_at: i64,
+}
+
+/// Insert one usage row.
+pub fn insert_usage(conn: &Connection, row: &UsageRow) -> Result<(), AppError> {
+    conn.execute(
+        "INSERT INTO message_usage (id, message_id, thread_id, run_id, agent_id, provider_id,
+                                    model_id, input_tokens, output_tokens, cached_tokens,
+                                    reasoning_tokens, cost_usd, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            row.id,
+            row.message_id,
+            row.thread_id,
+            row.run_id,
+            row.agent_id,
+            row.provider_id,
+            row.model_id,
+            row.usage.input_tokens,
+            row.usage.output_tokens,
+            row.usage.cached_tokens,
+            row.usage.reasoning_tokens,
+            row.cost_usd,
+            row.created_at,
+        ],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
 
+/// Return the usage rows recorded for a thread, oldest first.
+pub fn list_usage_for_thread(
+    conn: &Connection,
+    thread_id: &str,
+) -> Result<Vec<UsageRow>, AppError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, message_id, thread_id, run_id, agent_id, provider_id, model_id,
+                    input_tokens, output_tokens, cached_tokens, reasoning_tokens, cost_usd,
+                    created_at
+             FROM message_usage
+             WHERE thread_id = ?1
+             ORDER BY created_at ASC",
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
     let rows = stmt
         .query_map(params![thread_id], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, String>(2)?,
-                row.get::<_, String>(3)?,
-                row.get::<_, i64>(4)?,
-                row.get::<_, Option<String>>(5)?,
-            ))
+            Ok(UsageRow {
+                id: row.get(0)?,
+                message_id: row.get(1)?,
+                thread_id: row.get(2)?,
+                run_id: row.get(3)?,
+                agent_id: row.get(4)?,
+                provider_id: row.get(5)?,
+                model_id: row.get(6)?,
+                usage: TokenUsage {
+                    input_tokens: row.get(7)?,
+                    output_tokens: row.get(8)?,
+                    cached_tokens: row.get(9)?,
+                    reasoning_tokens: row.get(10)?,
+                },
+                cost_usd: row.get(11)?,
+                created_at: row.get(12)?,
+            })
         })
         .map_err(|e| AppError::Database(e.to_string()))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))
+}
 
-    let mut messages = Vec::new();
-    for row in rows {
-        let (id, thread_id, role_str, content_json, created_at, run_id) =
-            row.map_err(|e| AppError::Database(e.to_string()))?;
-        let role = serde_json::from_str::<MessageRole>(&format!("\"{role_str}\""))
-            .map_err(|e| AppError::Database(format!("deserialize role '{role_str}': {e}")))?;
-        let content = serde_json::from_str::<serde_json::Value>(&content_json)
-            .map_err(|e| AppError::Database(format!("deserialize content: {e}")))?;
-        messages.push(MessageRow {
-            id,
-            thread_id,
-            role,
-            content,
-            created_at,
-            run_id,
-        });
+/// Priced spend in USD for `agent_id` since `since_ms`. Unpriced rows count
+/// as zero.
+pub fn agent_spend_since(
+    conn: &Connection,
+    agent_id: &str,
+    since_ms: i64,
+) -> Result<f64, AppError> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(cost_usd), 0.0) FROM message_usage
+         WHERE agent_id = ?1 AND created_at >= ?2",
+        params![agent_id, since_ms],
+        |row| row.get(0),
+    )
+    .map_err(|e| AppError::Database(e.to_string()))
+}
+
+/// The dimension [`spend_summary`] groups by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum SpendGroup {
+    /// Local calendar day, keyed `YYYY-MM-DD`.
+    Day,
+    Agent,
+    Provider,
+    Model,
+    /// Silent-run usage has no thread and is grouped under `""`.
+    Thread,
+}
+
+impl SpendGroup {
+    fn key_sql(self) -> &'static str {
+        match self {
+            SpendGroup::Day => "strftime('%Y-%m-%d', created_at / 1000, 'unixepoch', 'localtime')",
+            SpendGroup::Agent => "agent_id",
+            SpendGroup::Provider => "provider_id",
+            SpendGroup::Model => "model_id",
+            SpendGroup::Thread => "COALESCE(thread_id, '')",
+        }
     }
-    Ok(messages)
+}
+
+/// One group of [`spend_summary`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendRow {
+    pub key: String,
+    pub responses: u32,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cached_tokens: u64,
+    pub reasoning_tokens: u64,
+    /// Sum over priced responses only.
+    pub cost_usd: f64,
+    /// Responses whose model has no price; their tokens are still counted.
+    pub unpriced_responses: u32,
+}
+
+/// Token and cost totals in `[since_ms, until_ms)` grouped by `group`. Days
+/// come back in chronological order, every other grouping by cost, highest
+/// first.
+pub fn spend_summary(
+    conn: &Connection,
+    group: SpendGroup,
+    since_ms: Option<i64>,
+    until_ms: Option<i64>,
+) -> Result<Vec<SpendRow>, AppError> {
+    let order = if group == SpendGroup::Day {
+        "key ASC"
+    } else {
+        "cost DESC, key ASC"
+    };
+    let sql = format!(
+        "SELECT {key} AS key, COUNT(*), SUM(input_tokens), SUM(output_tokens),
+                SUM(cached_tokens), SUM(reasoning_tokens),
+                COALESCE(SUM(cost_usd), 0.0) AS cost,
+                SUM(CASE WHEN cost_usd IS NULL THEN 1 ELSE 0 END)
+         FROM message_usage
+         WHERE created_at >= ?1 AND created_at < ?2
+         GROUP BY key
+         ORDER BY {order}",
+        key = group.key_sql(),
+    );
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let rows = stmt
+        .query_map(
+            params![since_ms.unwrap_or(0), until_ms.unwrap_or(i64::MAX)],
+            |row| {
+                Ok(SpendRow {
+                    key: row.get(0)?,
+                    responses: row.get(1)?,
+                    input_tokens: row.get::<_, i64>(2)? as u64,
+                    output_tokens: row.get::<_, i64>(3)? as u64,
+                    cached_tokens: row.get::<_, i64>(4)? as u64,
+                    reasoning_tokens: row.get::<_, i64>(5)? as u64,
+                    cost_usd: row.get(6)?,
+                    unpriced_responses: row.get(7)?,
+                })
+            },
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))
+}
+
+/// One provider request made during an agent run, successful or not.
+/// `run_id` is `None` for silent runs. Like usage rows, attempts carry no
+/// foreign keys so a run's routing history outlives its thread.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttemptRow {
+    pub id: String,
+    pub run_id: Option<String>,
+    pub agent_id: String,
+    /// 0-based loop turn the attempt belongs to.
+    pub turn: u32,
+    /// 1-based position across the whole run.
+    pub attempt: u32,
+    pub provider_id: String,
+    pub model_id: String,
+    pub outcome: AttemptOutcome,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+    pub started_at: i64,
+    pub ended_at: i64,
+}
+
+/// Insert one attempt row.
+pub fn insert_attempt(conn: &Connection, row: &AttemptRow) -> Result<(), AppError> {
+    conn.execute(
+        "INSERT INTO agent_run_attempts (id, run_id, agent_id, turn, attempt, provider_id,
+                                         model_id, outcome, status_code, error, started_at,
+                                         ended_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            row.id,
+            row.run_id,
+            row.agent_id,
+            row.turn,
+            row.attempt,
+            row.provider_id,
+            row.model_id,
+            row.outcome.as_str(),
+            row.status_code,
+            row.error,
+            row.started_at,
+            row.ended_at,
+        ],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Return a run's attempts in the order they were made.
+pub fn list_attempts_for_run(conn: &Connection, run_id: &str) -> Result<Vec<AttemptRow>, AppError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, run_id, agent_id, turn, attempt, provider_id, model_id, outcome,
+                    status_code, error, started_at, ended_at
+             FROM agent_run_attempts
+             WHERE run_id = ?1
+             ORDER BY attempt ASC",
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let rows = stmt
+        .query_map(params![run_id], |row| {
+            Ok(AttemptRow {
+                id: row.get(0)?,
+                run_id: row.get(1)?,
+                agent_id: row.get(2)?,
+                turn: row.get(3)?,
+                attempt: row.get(4)?,
+                provider_id: row.get(5)?,
+                model_id: row.get(6)?,
+                outcome: AttemptOutcome::parse(&row.get::<_, String>(7)?),
+                status_code: row.get(8)?,
+                error: row.get(9)?,
+                started_at: row.get(10)?,
+                ended_at: row.get(11)?,
+            })
+        })
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))
 }
@@ -1,9 +1,14 @@
+use crate::ai::routing::{AttemptOutcome, RouteTarget, RoutingPolicy};
+use crate::ai::types::TokenUsage;
 #[allow(unused_imports)]
 use crate::storage::agents::{
-    delete_agent, delete_thread, find_run_origin, get_agent, init_table, insert_agent,
-    insert_message, insert_thread, list_agents, list_messages_for_thread, list_threads_for_agent,
-    update_agent, AgentRow, MessageRole, MessageRow, SilentInputSource, SilentOutputAction,
-    ThreadRow,
+    agent_spend_since, delete_agent, delete_thread, find_run_origin, get_agent, get_thread,
+    get_thread_summary, init_table, insert_agent, insert_attempt, insert_message,
+    insert_message_after, insert_thread, insert_usage, list_agents, list_attempts_for_run,
+    list_messages_for_thread, list_sibling_ids, list_thread_path, list_threads_for_agent,
+    list_usage_for_thread, newest_leaf_under, set_current_leaf, set_thread_summary, spend_summary,
+    update_agent, AgentRow, AttemptRow, MessageRole, MessageRow, SilentInputSource,
+    SilentOutputAction, SpendGroup, ThreadRow, ThreadSummary, UsageRow,
 };
 use rusqlite::Connection;
 
@@ -28,6 +33,9 @@ fn agent(id: &str, created_at: i64) -> AgentRow {
         output_action: SilentOutputAction::ReplaceSelection,
         cache_responses: false,
         shortcode_trigger: ":".to_string(),
+        monthly_budget_usd: None,
+        routing: Default::default(),
+        output_schema: None,
         created_at: Some(created_at),
         updated_at: Some(created_at),
     }
@@ -38,6 +46,7 @@ fn thread(id: &str, agent_id: &str, updated_at: i64) -> ThreadRow {
         id: id.to_string(),
         agent_id: agent_id.to_string(),
         title: Some(format!("Thread {id}")),
+        current_leaf_id: None,
         created_at: Some(updated_at),
         updated_at: Some(updated_at),
     }
@@ -51,6 +60,7 @@ fn message(id: &str, thread_id: &str, role: MessageRole, created_at: i64) -> Mes
         content: serde_json::json!({"text": "hello"}),
         created_at,
         run_id: None,
+        parent_id: None,
     }
 }
 
@@ -229,6 +239,81 @@ fn messages_role_round_trips() {
     assert!(matches!(msgs[2].role, MessageRole::Tool));
 }
 
+fn path_ids(conn: &Connection, thread_id: &str) -> Vec<String> {
+    list_thread_path(conn, thread_id)
+        .unwrap()
+        .into_iter()
+        .map(|m| m.id)
+        .collect()
+}
+
+#[test]
+fn messages_chain_onto_the_leaf_and_fork_into_siblings() {
+    let conn = make_conn();
+    insert_agent(&conn, &agent("a1", 1000)).unwrap();
+    insert_thread(&conn, &thread("t1", "a1", 1000)).unwrap();
+
+    insert_message(&conn, &message("m1", "t1", MessageRole::User, 1000)).unwrap();
+    insert_message(&conn, &message("m2", "t1", MessageRole::Assistant, 2000)).unwrap();
+    insert_message(&conn, &message("m3", "t1", MessageRole::User, 3000)).unwrap();
+    assert_eq!(path_ids(&conn, "t1"), vec!["m1", "m2", "m3"]);
+
+    // Regenerate the first answer: a sibling of m2 becomes the leaf.
+    let regenerated = message("m2b", "t1", MessageRole::Assistant, 4000);
+    insert_message_after(&conn, &regenerated, Some("m1")).unwrap();
+    assert_eq!(path_ids(&conn, "t1"), vec!["m1", "m2b"]);
+    assert_eq!(
+        get_thread(&conn, "t1")
+            .unwrap()
+            .unwrap()
+            .current_leaf_id
+            .as_deref(),
+        Some("m2b")
+    );
+    let m2b = list_thread_path(&conn, "t1").unwrap().pop().unwrap();
+    assert_eq!(list_sibling_ids(&conn, &m2b).unwrap(), vec!["m2", "m2b"]);
+
+    // The full history survives: switching back resumes the old branch.
+    assert_eq!(list_messages_for_thread(&conn, "t1").unwrap().len(), 4);
+    assert_eq!(newest_leaf_under(&conn, "m2").unwrap(), "m3");
+    set_current_leaf(&conn, "t1", Some("m3")).unwrap();
+    assert_eq!(path_ids(&conn, "t1"), vec!["m1", "m2", "m3"]);
+}
+
+#[test]
+fn thread_summary_round_trips_and_requires_the_thread() {
+    let conn = make_conn();
+    insert_agent(&conn, &agent("a1", 1000)).unwrap();
+    insert_thread(&conn, &thread("t1", "a1", 1000)).unwrap();
+    assert_eq!(get_thread_summary(&conn, "t1").unwrap(), None);
+
+    let summary = ThreadSummary {
+        text: "The user is planning a trip.".to_string(),
+        through_message_id: "m4".to_string(),
+    };
+    set_thread_summary(&conn, "t1", &summary).unwrap();
+    assert_eq!(
+        get_thread_summary(&conn, "t1").unwrap(),
+        Some(summary.clone())
+    );
+    assert!(set_thread_summary(&conn, "missing", &summary).is_err());
+}
+
+#[test]
+fn root_messages_are_siblings_of_each_other() {
+    let conn = make_conn();
+    insert_agent(&conn, &agent("a1", 1000)).unwrap();
+    insert_thread(&conn, &thread("t1", "a1", 1000)).unwrap();
+
+    insert_message(&conn, &message("m1", "t1", MessageRole::User, 1000)).unwrap();
+    insert_message_after(&conn, &message("m1b", "t1", MessageRole::User, 2000), None).unwrap();
+
+    assert_eq!(path_ids(&conn, "t1"), vec!["m1b"]);
+    let m1b = list_thread_path(&conn, "t1").unwrap().pop().unwrap();
+    assert_eq!(list_sibling_ids(&conn, &m1b).unwrap(), vec!["m1", "m1b"]);
+    assert!(set_current_leaf(&conn, "missing", None).is_err());
+}
+
 #[test]
 fn messages_content_json_round_trips() {
     let conn = make_conn();
@@ -246,6 +331,7 @@ fn messages_content_json_round_trips() {
         content: expected.clone(),
         created_at: 1000,
         run_id: None,
+        parent_id: None,
     };
     insert_message(&conn, &msg).unwrap();
 
@@ -426,3 +512,166 @@ fn list_agents_returns_silent_fields() {
     assert_eq!(rows[0].input_source, SilentInputSource::None);
     assert_eq!(rows[0].output_action, SilentOutputAction::Paste);
 }
+
+fn usage(
+    id: &str,
+    agent_id: &str,
+    thread_id: Option<&str>,
+    model_id: &str,
+    cost_usd: Option<f64>,
+    created_at: i64,
+) -> UsageRow {
+    UsageRow {
+        id: id.to_string(),
+        message_id: None,
+        thread_id: thread_id.map(str::to_string),
+        run_id: None,
+        agent_id: agent_id.to_string(),
+        provider_id: "openai".to_string(),
+        model_id: model_id.to_string(),
+        usage: TokenUsage {
+            input_tokens: 100,
+            output_tokens: 10,
+            cached_tokens: 40,
+            reasoning_tokens: 0,
+        },
+        cost_usd,
+        created_at,
+    }
+}
+
+#[test]
+fn monthly_budget_round_trips() {
+    let conn = make_conn();
+    let mut a = agent("a1", 1000);
+    a.monthly_budget_usd = Some(12.5);
+    insert_agent(&conn, &a).unwrap();
+    assert_eq!(
+        get_agent(&conn, "a1").unwrap().unwrap().monthly_budget_usd,
+        Some(12.5)
+    );
+
+    a.monthly_budget_usd = None;
+    update_agent(&conn, &a).unwrap();
+    assert_eq!(list_agents(&conn).unwrap()[0].monthly_budget_usd, None);
+}
+
+#[test]
+fn usage_survives_thread_delete_and_sums_by_group() {
+    let conn = make_conn();
+    insert_agent(&conn, &agent("a1", 1000)).unwrap();
+    insert_thread(&conn, &thread("t1", "a1", 1000)).unwrap();
+    insert_usage(
+        &conn,
+        &usage("u1", "a1", Some("t1"), "gpt-4o", Some(0.5), 1_000),
+    )
+    .unwrap();
+    insert_usage(
+        &conn,
+        &usage("u2", "a1", Some("t1"), "gpt-4o", Some(0.25), 2_000),
+    )
+    .unwrap();
+    insert_usage(&conn, &usage("u3", "a2", None, "my-local", None, 3_000)).unwrap();
+
+    assert_eq!(list_usage_for_thread(&conn, "t1").unwrap().len(), 2);
+    delete_thread(&conn, "t1").unwrap();
+
+    let by_agent = spend_summary(&conn, SpendGroup::Agent, None, None).unwrap();
+    assert_eq!(by_agent.len(), 2);
+    assert_eq!(by_agent[0].key, "a1");
+    assert_eq!(by_agent[0].responses, 2);
+    assert_eq!(by_agent[0].input_tokens, 200);
+    assert_eq!(by_agent[0].cached_tokens, 80);
+    assert!((by_agent[0].cost_usd - 0.75).abs() < 1e-9);
+    assert_eq!(by_agent[1].key, "a2");
+    assert_eq!(by_agent[1].unpriced_responses, 1);
+
+    let by_thread = spend_summary(&conn, SpendGroup::Thread, Some(2_000), None).unwrap();
+    let keys: Vec<&str> = by_thread.iter().map(|row| row.key.as_str()).collect();
+    assert_eq!(keys, vec!["t1", ""]);
+
+    assert!((agent_spend_since(&conn, "a1", 1_500).unwrap() - 0.25).abs() < 1e-9);
+    assert_eq!(agent_spend_since(&conn, "nobody", 0).unwrap(), 0.0);
+}
+
+#[test]
+fn routing_policy_round_trips() {
+    let conn = make_conn();
+    let mut a = agent("a1", 1000);
+    a.routing = RoutingPolicy {
+        fallbacks: vec![RouteTarget {
+            provider_id: "anthropic".to_string(),
+            model_id: "claude-sonnet-4".to_string(),
+        }],
+        max_retries: 2,
+        offline_fallback: Some(RouteTarget {
+            provider_id: "ollama".to_string(),
+            model_id: "llama3.2".to_string(),
+        }),
+    };
+    insert_agent(&conn, &a).unwrap();
+    assert_eq!(get_agent(&conn, "a1").unwrap().unwrap().routing, a.routing);
+
+    a.routing = RoutingPolicy::default();
+    update_agent(&conn, &a).unwrap();
+    assert_eq!(
+        list_agents(&conn).unwrap()[0].routing,
+        RoutingPolicy::default()
+    );
+}
+
+#[test]
+fn output_schema_round_trips() {
+    let conn = make_conn();
+    let mut a = agent("a1", 1000);
+    a.output_schema = Some(serde_json::json!({
+        "type": "object",
+        "required": ["name"],
+    }));
+    insert_agent(&conn, &a).unwrap();
+    assert_eq!(
+        get_agent(&conn, "a1").unwrap().unwrap().output_schema,
+        a.output_schema
+    );
+
+    a.output_schema = None;
+    update_agent(&conn, &a).unwrap();
+    assert_eq!(list_agents(&conn).unwrap()[0].output_schema, None);
+}
+
+#[test]
+fn attempts_list_in_run_order_and_survive_thread_delete() {
+    let conn = make_conn();
+    insert_agent(&conn, &agent("a1", 1000)).unwrap();
+    insert_thread(&conn, &thread("t1", "a1", 1000)).unwrap();
+    for (attempt, outcome, status_code) in [
+        (2, AttemptOutcome::Succeeded, None),
+        (1, AttemptOutcome::RateLimited, Some(429)),
+    ] {
+        insert_attempt(
+            &conn,
+            &AttemptRow {
+                id: format!("at{attempt}"),
+                run_id: Some("r1".to_string()),
+                agent_id: "a1".to_string(),
+                turn: 0,
+                attempt,
+                provider_id: "openai".to_string(),
+                model_id: "gpt-4o".to_string(),
+                outcome,
+                status_code,
+                error: None,
+                started_at: 1000,
+                ended_at: 1001,
+            },
+        )
+        .unwrap();
+    }
+    delete_thread(&conn, "t1").unwrap();
+
+    let attempts = list_attempts_for_run(&conn, "r1").unwrap();
+    assert_eq!(attempts.len(), 2);
+    assert_eq!(attempts[0].outcome, AttemptOutcome::RateLimited);
+    assert_eq!(attempts[0].status_code, Some(429));
+    assert_eq!(attempts[1].outcome, AttemptOutcome::Succeeded);
+}
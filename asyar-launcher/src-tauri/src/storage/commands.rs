@@ -105,13 +105,17 @@ pub fn clipboard_record_capture(
         .map(|p| p.join("icon_cache"))
         .unwrap_or_else(|_| std::path::PathBuf::from("/tmp/asyar_icon_cache"));
     let conn = store.conn()?;
-    super::clipboard::record_capture_with_fts(
+    let result = super::clipboard::record_capture_with_fts(
         &conn,
         &item,
         Some(&cache_dir),
         keystore.master_key(),
         fts.inner(),
-    )
+    )?;
+    if let Some(text) = crate::agents::triggers::clipboard_trigger_text(&item) {
+        crate::agents::triggers::on_clipboard_text(&app, &conn, text);
+    }
+    Ok(result)
 }
 
 #[tauri::command]
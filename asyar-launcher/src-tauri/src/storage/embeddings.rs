@@ -0,0 +1,381 @@
+//! Encrypted vector store for the semantic index, plus its in-memory k-NN.
+//!
+//! Vectors are derived from note, clipboard and snippet plaintext and leak
+//! meaning to anyone who can read them, so on disk they get the same
+//! `cipher::encrypt` treatment as the content they came from. Like
+//! `notes_fts`/`clipboard_fts`, queries never touch the ciphertext: the rows
+//! are decrypted once at startup into [`VectorIndex`] and kept in sync by
+//! `ai::semantic_index`.
+//!
+//! `content_hash` is an HMAC of the embedded text under the master key (the
+//! same construction as `clipboard_items.content_hash`), so re-indexing can
+//! skip unchanged documents without storing their plaintext.
+
+use crate::crypto::cipher;
+use crate::error::AppError;
+use base64::Engine;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Which table a vector was computed from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum EmbeddingSource {
+    Note,
+    Clipboard,
+    Snippet,
+}
+
+impl EmbeddingSource {
+    pub const ALL: [EmbeddingSource; 3] = [Self::Note, Self::Clipboard, Self::Snippet];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Note => "note",
+            Self::Clipboard => "clipboard",
+            Self::Snippet => "snippet",
+        }
+    }
+
+    fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "note" => Some(Self::Note),
+            "clipboard" => Some(Self::Clipboard),
+            "snippet" => Some(Self::Snippet),
+            _ => None,
+        }
+    }
+}
+
+/// A document identity inside the index.
+pub type DocKey = (EmbeddingSource, String);
+
+pub fn init_table(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS embeddings (
+            source TEXT NOT NULL,
+            item_id TEXT NOT NULL,
+            model TEXT NOT NULL,
+            content_hash BLOB NOT NULL,
+            vector TEXT NOT NULL,
+            updated_at REAL NOT NULL,
+            PRIMARY KEY (source, item_id)
+        );",
+    )
+    .map_err(|e| AppError::Database(format!("Failed to init embeddings table: {e}")))?;
+    Ok(())
+}
+
+fn encode_vector(vector: &[f32], master_key: &[u8; 32]) -> Result<String, AppError> {
+    let bytes: Vec<u8> = vector.iter().flat_map(|f| f.to_le_bytes()).collect();
+    let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+    cipher::encrypt(&encoded, master_key)
+}
+
+fn decode_vector(stored: &str, master_key: &[u8; 32]) -> Option<Vec<f32>> {
+    let encoded = cipher::decrypt(stored, master_key).ok()?;
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .ok()?;
+    if bytes.len() % 4 != 0 {
+        return None;
+    }
+    Some(
+        bytes
+            .chunks_exact(4)
+            .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect(),
+    )
+}
+
+pub fn upsert(
+    conn: &Connection,
+    (source, item_id): &DocKey,
+    model: &str,
+    content_hash: &[u8],
+    vector: &[f32],
+    updated_at: f64,
+    master_key: &[u8; 32],
+) -> Result<(), AppError> {
+    conn.execute(
+        "INSERT OR REPLACE INTO embeddings (source, item_id, model, content_hash, vector, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            source.as_str(),
+            item_id,
+            model,
+            content_hash,
+            encode_vector(vector, master_key)?,
+            updated_at,
+        ],
+    )
+    .map_err(|e| AppError::Database(format!("Failed to upsert embedding: {e}")))?;
+    Ok(())
+}
+
+pub fn remove(conn: &Connection, source: EmbeddingSource, item_id: &str) -> Result<(), AppError> {
+    conn.execute(
+        "DELETE FROM embeddings WHERE source = ?1 AND item_id = ?2",
+        params![source.as_str(), item_id],
+    )
+    .map_err(|e| AppError::Database(format!("Failed to delete embedding: {e}")))?;
+    Ok(())
+}
+
+/// Drop every vector computed by a model other than `model`. Returns the
+/// number of rows removed.
+pub fn remove_other_models(conn: &Connection, model: &str) -> Result<usize, AppError> {
+    conn.execute("DELETE FROM embeddings WHERE model != ?1", params![model])
+        .map_err(|e| AppError::Database(format!("Failed to prune embeddings: {e}")))
+}
+
+/// Stored content hashes for `model`, keyed by document.
+pub fn hashes(conn: &Connection, model: &str) -> Result<HashMap<DocKey, Vec<u8>>, AppError> {
+    let mut stmt = conn
+        .prepare("SELECT source, item_id, content_hash FROM embeddings WHERE model = ?1")
+        .map_err(|e| AppError::Database(format!("Failed to prepare query: {e}")))?;
+    let rows = stmt
+        .query_map(params![model], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Vec<u8>>(2)?,
+            ))
+        })
+        .map_err(|e| AppError::Database(format!("Failed to query embeddings: {e}")))?;
+    Ok(rows
+        .filter_map(|r| r.ok())
+        .filter_map(|(source, id, hash)| Some(((EmbeddingSource::parse(&source)?, id), hash)))
+        .collect())
+}
+
+/// Decrypt every vector for `model`. Rows that fail to decrypt are skipped;
+/// the next sync sees them as missing and re-embeds.
+pub fn load_all(
+    conn: &Connection,
+    model: &str,
+    master_key: &[u8; 32],
+) -> Result<Vec<(DocKey, Vec<f32>)>, AppError> {
+    let mut stmt = conn
+        .prepare("SELECT source, item_id, vector FROM embeddings WHERE model = ?1")
+        .map_err(|e| AppError::Database(format!("Failed to prepare query: {e}")))?;
+    let rows = stmt
+        .query_map(params![model], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|e| AppError::Database(format!("Failed to query embeddings: {e}")))?;
+    Ok(rows
+        .filter_map(|r| r.ok())
+        .filter_map(|(source, id, vector)| {
+            Some((
+                (EmbeddingSource::parse(&source)?, id),
+                decode_vector(&vector, master_key)?,
+            ))
+        })
+        .collect())
+}
+
+/// Brute-force cosine k-NN over unit-normalised vectors. A launcher's worth
+/// of notes, clipboard history and snippets is a few thousand documents at
+/// most; a linear scan of that is sub-millisecond and needs no index
+/// maintenance, so an ANN structure would be all cost and no benefit.
+#[derive(Default)]
+pub struct VectorIndex {
+    vectors: RwLock<HashMap<DocKey, Vec<f32>>>,
+}
+
+impl VectorIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert or replace a vector. Normalises on the way in.
+    pub fn upsert(&self, key: DocKey, mut vector: Vec<f32>) -> Result<(), AppError> {
+        crate::ai::embeddings::normalize(&mut vector);
+        self.vectors
+            .write()
+            .map_err(|_| AppError::Lock)?
+            .insert(key, vector);
+        Ok(())
+    }
+
+    pub fn remove(&self, key: &DocKey) -> Result<(), AppError> {
+        self.vectors
+            .write()
+            .map_err(|_| AppError::Lock)?
+            .remove(key);
+        Ok(())
+    }
+
+    pub fn clear(&self) -> Result<(), AppError> {
+        self.vectors.write().map_err(|_| AppError::Lock)?.clear();
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.vectors.read().map(|v| v.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The `k` nearest documents to `query` restricted to `sources`, best
+    /// first, with their cosine similarity. Vectors whose dimension differs
+    /// from the query's are ignored.
+    pub fn knn(
+        &self,
+        query: &[f32],
+        k: usize,
+        sources: &[EmbeddingSource],
+    ) -> Result<Vec<(DocKey, f32)>, AppError> {
+        let mut query = query.to_vec();
+        crate::ai::embeddings::normalize(&mut query);
+        let vectors = self.vectors.read().map_err(|_| AppError::Lock)?;
+        let mut scored: Vec<(DocKey, f32)> = vectors
+            .iter()
+            .filter(|((source, _), v)| sources.contains(source) && v.len() == query.len())
+            .map(|(key, v)| (key.clone(), crate::ai::embeddings::dot(&query, v)))
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0 .1.cmp(&b.0 .1)));
+        scored.truncate(k);
+        Ok(scored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_table(&conn).unwrap();
+        conn
+    }
+
+    fn test_key() -> [u8; 32] {
+        let mut k = [0u8; 32];
+        for (i, b) in k.iter_mut().enumerate() {
+            *b = (i * 23) as u8;
+        }
+        k
+    }
+
+    #[test]
+    fn vectors_round_trip_encrypted() {
+        let conn = setup();
+        let key = test_key();
+        upsert(
+            &conn,
+            &(EmbeddingSource::Note, "n1".to_string()),
+            "ollama:m",
+            b"hash",
+            &[0.25, -1.5, 3.0],
+            1.0,
+            &key,
+        )
+        .unwrap();
+
+        let raw: String = conn
+            .query_row("SELECT vector FROM embeddings", [], |r| r.get(0))
+            .unwrap();
+        assert!(cipher::is_encrypted_value(&raw));
+
+        let all = load_all(&conn, "ollama:m", &key).unwrap();
+        assert_eq!(
+            all,
+            vec![(
+                (EmbeddingSource::Note, "n1".to_string()),
+                vec![0.25, -1.5, 3.0]
+            )]
+        );
+        assert_eq!(
+            hashes(&conn, "ollama:m").unwrap()[&(EmbeddingSource::Note, "n1".to_string())],
+            b"hash".to_vec()
+        );
+    }
+
+    #[test]
+    fn other_models_are_filtered_and_prunable() {
+        let conn = setup();
+        let key = test_key();
+        upsert(
+            &conn,
+            &(EmbeddingSource::Note, "a".into()),
+            "old",
+            b"h",
+            &[1.0],
+            1.0,
+            &key,
+        )
+        .unwrap();
+        upsert(
+            &conn,
+            &(EmbeddingSource::Snippet, "b".into()),
+            "new",
+            b"h",
+            &[1.0],
+            1.0,
+            &key,
+        )
+        .unwrap();
+
+        assert_eq!(load_all(&conn, "new", &key).unwrap().len(), 1);
+        assert_eq!(remove_other_models(&conn, "new").unwrap(), 1);
+        assert!(hashes(&conn, "old").unwrap().is_empty());
+
+        remove(&conn, EmbeddingSource::Snippet, "b").unwrap();
+        assert!(load_all(&conn, "new", &key).unwrap().is_empty());
+    }
+
+    #[test]
+    fn wrong_key_rows_are_skipped() {
+        let conn = setup();
+        upsert(
+            &conn,
+            &(EmbeddingSource::Note, "a".into()),
+            "m",
+            b"h",
+            &[1.0],
+            1.0,
+            &test_key(),
+        )
+        .unwrap();
+        assert!(load_all(&conn, "m", &[0u8; 32]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn knn_ranks_by_cosine_and_filters_sources() {
+        let index = VectorIndex::new();
+        index
+            .upsert((EmbeddingSource::Note, "x".into()), vec![10.0, 0.0])
+            .unwrap();
+        index
+            .upsert((EmbeddingSource::Note, "xy".into()), vec![1.0, 1.0])
+            .unwrap();
+        index
+            .upsert((EmbeddingSource::Clipboard, "y".into()), vec![0.0, 2.0])
+            .unwrap();
+        index
+            .upsert((EmbeddingSource::Snippet, "3d".into()), vec![1.0, 0.0, 0.0])
+            .unwrap();
+
+        let hits = index.knn(&[1.0, 0.1], 3, &EmbeddingSource::ALL).unwrap();
+        let ids: Vec<&str> = hits.iter().map(|((_, id), _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["x", "xy", "y"]);
+        assert!(hits[0].1 > 0.99);
+
+        let notes_only = index.knn(&[0.0, 1.0], 5, &[EmbeddingSource::Note]).unwrap();
+        assert_eq!(notes_only.len(), 2);
+        assert_eq!(notes_only[0].0 .1, "xy");
+
+        index.remove(&(EmbeddingSource::Note, "x".into())).unwrap();
+        assert_eq!(index.len(), 3);
+    }
+}
@@ -1,7 +1,8 @@
 //! Launcher-wide MCP feature settings. Singleton row, schema migrated via
-//! `init_table` like every other storage module. Currently holds only
-//! `strict_mode` — when on, every MCP tool call prompts the user on first
-//! use, regardless of whether the tool name matches a read-only prefix.
+//! `init_table` like every other storage module. Holds `strict_mode` — when
+//! on, every MCP tool call prompts the user on first use, regardless of
+//! whether the tool name matches a read-only prefix — and `host_enabled`,
+//! the opt-in for serving Asyar's own tools over MCP (see `mcp::host`).
 
 use crate::error::AppError;
 use rusqlite::{params, Connection, OptionalExtension};
@@ -18,6 +19,21 @@ pub(crate) fn init_table(conn: &Connection) -> Result<(), AppError> {
          INSERT OR IGNORE INTO mcp_settings (id, strict_mode) VALUES (1, 0);",
     )
     .map_err(|e| AppError::Database(format!("Failed to init mcp_settings table: {e}")))?;
+
+    let cols: Vec<String> = conn
+        .prepare("PRAGMA table_info(mcp_settings)")
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .query_map([], |row| row.get::<_, String>(1))
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .filter_map(Result::ok)
+        .collect();
+    if !cols.contains(&"host_enabled".to_string()) {
+        conn.execute(
+            "ALTER TABLE mcp_settings ADD COLUMN host_enabled INTEGER NOT NULL DEFAULT 0",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
     Ok(())
 }
 
@@ -45,6 +61,29 @@ pub fn set_strict_mode(conn: &Connection, enabled: bool) -> Result<(), AppError>
     Ok(())
 }
 
+/// Whether Asyar serves its tools to external MCP clients. Off until the
+/// user turns it on.
+pub fn get_host_enabled(conn: &Connection) -> Result<bool, AppError> {
+    let value: Option<i64> = conn
+        .query_row(
+            "SELECT host_enabled FROM mcp_settings WHERE id = 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(value.unwrap_or(0) != 0)
+}
+
+pub fn set_host_enabled(conn: &Connection, enabled: bool) -> Result<(), AppError> {
+    conn.execute(
+        "UPDATE mcp_settings SET host_enabled = ?1 WHERE id = 1",
+        params![if enabled { 1 } else { 0 }],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -78,4 +117,30 @@ mod tests {
         // The row's value must survive re-init (INSERT OR IGNORE preserves it).
         assert!(get_strict_mode(&conn).unwrap());
     }
+
+    #[test]
+    fn host_enabled_defaults_off_and_round_trips() {
+        let conn = make_conn();
+        assert!(!get_host_enabled(&conn).unwrap());
+        set_host_enabled(&conn, true).unwrap();
+        assert!(get_host_enabled(&conn).unwrap());
+        // Independent of strict mode.
+        assert!(!get_strict_mode(&conn).unwrap());
+    }
+
+    #[test]
+    fn init_adds_host_enabled_to_an_existing_table() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE mcp_settings (
+                id          INTEGER PRIMARY KEY CHECK (id = 1),
+                strict_mode INTEGER NOT NULL DEFAULT 0
+             );
+             INSERT INTO mcp_settings (id, strict_mode) VALUES (1, 1);",
+        )
+        .unwrap();
+        init_table(&conn).unwrap();
+        assert!(!get_host_enabled(&conn).unwrap());
+        assert!(get_strict_mode(&conn).unwrap());
+    }
 }
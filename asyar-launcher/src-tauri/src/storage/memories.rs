@@ -0,0 +1,345 @@
+//! Long-term memory for agents: short facts the AI was asked to remember
+//! about the user, as opposed to the documents in `notes`.
+//!
+//! A memory is either global (`agent_id` is `NULL`, visible to every agent)
+//! or scoped to one agent. `content` is encrypted under the master key like
+//! note bodies, so search happens here in Rust over the decrypted rows
+//! rather than in SQL; the store is small enough for that to stay cheap.
+
+use crate::crypto::cipher;
+use crate::error::AppError;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Memory {
+    pub id: String,
+    /// `None` for a global memory.
+    pub agent_id: Option<String>,
+    pub content: String,
+    /// The thread the memory was saved from, when it came from a chat.
+    pub source_thread_id: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+pub fn init_table(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS memories (
+            id               TEXT    PRIMARY KEY,
+            agent_id         TEXT,
+            content          TEXT    NOT NULL,
+            source_thread_id TEXT,
+            created_at       INTEGER NOT NULL,
+            updated_at       INTEGER NOT NULL,
+            FOREIGN KEY (agent_id) REFERENCES agents(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_memories_agent
+            ON memories(agent_id, updated_at DESC);",
+    )
+    .map_err(|e| AppError::Database(format!("Failed to init memories table: {e}")))?;
+    Ok(())
+}
+
+const MEMORY_COLUMNS: &str = "id, agent_id, content, source_thread_id, created_at, updated_at";
+
+fn memory_from_row(row: &rusqlite::Row, master_key: &[u8; 32]) -> rusqlite::Result<Memory> {
+    let raw: String = row.get(2)?;
+    Ok(Memory {
+        id: row.get(0)?,
+        agent_id: row.get(1)?,
+        content: cipher::decrypt(&raw, master_key).unwrap_or_default(),
+        source_thread_id: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+/// Saves `memory`, unless the same scope already holds the same fact
+/// (compared case- and whitespace-insensitively); then that entry is
+/// refreshed instead. Returns the id of the stored entry.
+pub fn save(conn: &Connection, memory: &Memory, master_key: &[u8; 32]) -> Result<String, AppError> {
+    let normalized = normalize(&memory.content);
+    if let Some(existing) = list(conn, Some(memory.agent_id.as_deref()), master_key)?
+        .into_iter()
+        .find(|existing| normalize(&existing.content) == normalized)
+    {
+        conn.execute(
+            "UPDATE memories SET updated_at = ?1 WHERE id = ?2",
+            params![memory.updated_at, existing.id],
+        )
+        .map_err(|e| AppError::Database(format!("Failed to refresh memory: {e}")))?;
+        return Ok(existing.id);
+    }
+    let encrypted = cipher::encrypt(&memory.content, master_key)?;
+    conn.execute(
+        "INSERT INTO memories (id, agent_id, content, source_thread_id, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            memory.id,
+            memory.agent_id,
+            encrypted,
+            memory.source_thread_id,
+            memory.created_at,
+            memory.updated_at,
+        ],
+    )
+    .map_err(|e| AppError::Database(format!("Failed to save memory: {e}")))?;
+    Ok(memory.id.clone())
+}
+
+/// Memories newest first. `scope` of `None` lists everything;
+/// `Some(None)` only global entries; `Some(Some(id))` only that agent's.
+pub fn list(
+    conn: &Connection,
+    scope: Option<Option<&str>>,
+    master_key: &[u8; 32],
+) -> Result<Vec<Memory>, AppError> {
+    let filter = if scope.is_some() {
+        "WHERE agent_id IS ?1"
+    } else {
+        ""
+    };
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {MEMORY_COLUMNS} FROM memories {filter}
+             ORDER BY updated_at DESC, rowid DESC"
+        ))
+        .map_err(|e| AppError::Database(format!("Failed to prepare query: {e}")))?;
+    let from_row = |row: &rusqlite::Row| memory_from_row(row, master_key);
+    let rows = match scope {
+        None => stmt.query_map([], from_row),
+        Some(agent_id) => stmt.query_map(params![agent_id], from_row),
+    }
+    .map_err(|e| AppError::Database(format!("Failed to query memories: {e}")))?;
+    Ok(rows.filter_map(Result::ok).collect())
+}
+
+/// Everything `agent_id` can recall: the global memories plus its own,
+/// newest first. A caller with no agent sees only the global ones.
+pub fn visible_to(
+    conn: &Connection,
+    agent_id: Option<&str>,
+    master_key: &[u8; 32],
+) -> Result<Vec<Memory>, AppError> {
+    let mut memories = list(conn, Some(None), master_key)?;
+    if let Some(agent_id) = agent_id {
+        memories.extend(list(conn, Some(Some(agent_id)), master_key)?);
+        memories.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    }
+    Ok(memories)
+}
+
+/// The memories visible to `agent_id` that share the most words with
+/// `query`, best match first and newest first among equals. Memories
+/// sharing no word with the query are left out.
+pub fn search(
+    conn: &Connection,
+    agent_id: Option<&str>,
+    query: &str,
+    limit: usize,
+    master_key: &[u8; 32],
+) -> Result<Vec<Memory>, AppError> {
+    let terms = terms(query);
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut scored: Vec<(usize, Memory)> = visible_to(conn, agent_id, master_key)?
+        .into_iter()
+        .filter_map(|memory| {
+            let score = terms(&memory.content).intersection(&terms).count();
+            (score > 0).then_some((score, memory))
+        })
+        .collect();
+    // Stable, so equal scores keep `visible_to`'s newest-first order.
+    scored.sort_by(|a, b| b.0.cmp(&a.0));
+    Ok(scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, memory)| memory)
+        .collect())
+}
+
+pub fn get(conn: &Connection, id: &str, master_key: &[u8; 32]) -> Result<Option<Memory>, AppError> {
+    match conn.query_row(
+        &format!("SELECT {MEMORY_COLUMNS} FROM memories WHERE id = ?1"),
+        params![id],
+        |row| memory_from_row(row, master_key),
+    ) {
+        Ok(memory) => Ok(Some(memory)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(AppError::Database(format!("Failed to get memory: {e}"))),
+    }
+}
+
+/// Deletes a memory. Returns whether it existed.
+pub fn remove(conn: &Connection, id: &str) -> Result<bool, AppError> {
+    let deleted = conn
+        .execute("DELETE FROM memories WHERE id = ?1", params![id])
+        .map_err(|e| AppError::Database(format!("Failed to delete memory: {e}")))?;
+    Ok(deleted > 0)
+}
+
+/// Deletes every memory scoped to `agent_id`. Global memories stay. Needed
+/// because production connections run with `foreign_keys` off, so the
+/// table's `ON DELETE CASCADE` never fires.
+pub fn remove_for_agent(conn: &Connection, agent_id: &str) -> Result<(), AppError> {
+    conn.execute(
+        "DELETE FROM memories WHERE agent_id = ?1",
+        params![agent_id],
+    )
+    .map_err(|e| AppError::Database(format!("Failed to delete agent memories: {e}")))?;
+    Ok(())
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Words that carry no meaning on their own and would make every memory
+/// match every query.
+const STOP_WORDS: &[&str] = &[
+    "about", "and", "are", "but", "can", "did", "does", "for", "from", "had", "has", "have", "her",
+    "his", "how", "into", "its", "not", "of", "our", "she", "that", "the", "their", "them", "they",
+    "this", "was", "what", "when", "where", "which", "who", "why", "will", "with", "you", "your",
+];
+
+fn terms(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() > 2)
+        .map(str::to_lowercase)
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_table(&conn).unwrap();
+        conn
+    }
+
+    fn test_key() -> [u8; 32] {
+        let mut k = [0u8; 32];
+        for (i, b) in k.iter_mut().enumerate() {
+            *b = (i * 29) as u8;
+        }
+        k
+    }
+
+    fn memory(id: &str, agent_id: Option<&str>, content: &str, at: i64) -> Memory {
+        Memory {
+            id: id.to_string(),
+            agent_id: agent_id.map(str::to_string),
+            content: content.to_string(),
+            source_thread_id: None,
+            created_at: at,
+            updated_at: at,
+        }
+    }
+
+    #[test]
+    fn content_is_stored_encrypted_and_read_back() {
+        let conn = setup();
+        let key = test_key();
+        save(&conn, &memory("m1", None, "Prefers metric units", 1), &key).unwrap();
+
+        let raw: String = conn
+            .query_row("SELECT content FROM memories", [], |r| r.get(0))
+            .unwrap();
+        assert!(cipher::is_encrypted_value(&raw));
+        assert_eq!(
+            get(&conn, "m1", &key).unwrap().unwrap().content,
+            "Prefers metric units"
+        );
+    }
+
+    #[test]
+    fn saving_a_known_fact_refreshes_it_instead_of_duplicating() {
+        let conn = setup();
+        let key = test_key();
+        save(&conn, &memory("m1", None, "Lives in Lyon", 1), &key).unwrap();
+        let id = save(&conn, &memory("m2", None, "  lives in  LYON ", 5), &key).unwrap();
+
+        assert_eq!(id, "m1");
+        let all = list(&conn, None, &key).unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].updated_at, 5);
+        // The same fact under another scope is a separate memory.
+        save(&conn, &memory("m3", Some("a1"), "Lives in Lyon", 6), &key).unwrap();
+        assert_eq!(list(&conn, None, &key).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn agents_see_global_memories_and_only_their_own() {
+        let conn = setup();
+        let key = test_key();
+        save(&conn, &memory("g", None, "global", 1), &key).unwrap();
+        save(&conn, &memory("a", Some("a1"), "first agent", 2), &key).unwrap();
+        save(&conn, &memory("b", Some("a2"), "second agent", 3), &key).unwrap();
+
+        let ids = |memories: Vec<Memory>| memories.into_iter().map(|m| m.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(visible_to(&conn, Some("a1"), &key).unwrap()),
+            ["a", "g"]
+        );
+        assert_eq!(ids(visible_to(&conn, None, &key).unwrap()), ["g"]);
+        assert_eq!(ids(list(&conn, Some(Some("a2")), &key).unwrap()), ["b"]);
+    }
+
+    #[test]
+    fn search_ranks_by_shared_words_and_skips_unrelated_entries() {
+        let conn = setup();
+        let key = test_key();
+        save(
+            &conn,
+            &memory("diet", None, "The user is vegetarian", 1),
+            &key,
+        )
+        .unwrap();
+        save(
+            &conn,
+            &memory("trip", None, "Planning a trip to Japan in April", 2),
+            &key,
+        )
+        .unwrap();
+        save(
+            &conn,
+            &memory("food", None, "Favourite food in Japan is ramen", 3),
+            &key,
+        )
+        .unwrap();
+
+        let found = search(
+            &conn,
+            None,
+            "What food should I try on my Japan trip?",
+            5,
+            &key,
+        )
+        .unwrap();
+        let ids: Vec<_> = found.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["food", "trip"]);
+        assert!(search(&conn, None, "the and of", 5, &key)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn remove_reports_whether_the_memory_existed() {
+        let conn = setup();
+        let key = test_key();
+        save(&conn, &memory("m1", None, "fact", 1), &key).unwrap();
+        assert!(remove(&conn, "m1").unwrap());
+        assert!(!remove(&conn, "m1").unwrap());
+    }
+}
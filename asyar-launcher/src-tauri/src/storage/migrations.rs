@@ -35,6 +35,81 @@ pub const MIGRATIONS: &[Migration] = &[
         name: "walkthrough",
         up: |conn| super::walkthrough::init_table(conn),
     },
+    Migration {
+        version: 3,
+        name: "snippet_matching_options",
+        up: |conn| {
+            conn.execute_batch(
+                "ALTER TABLE snippets ADD COLUMN trigger_kind TEXT;
+                 ALTER TABLE snippets ADD COLUMN expand_mode TEXT;
+                 ALTER TABLE snippets ADD COLUMN app_scope TEXT;",
+            )
+            .map_err(|e| AppError::Database(format!("Failed to add snippet matching columns: {e}")))
+        },
+    },
+    Migration {
+        version: 4,
+        name: "embeddings",
+        up: |conn| super::embeddings::init_table(conn),
+    },
+    Migration {
+        version: 5,
+        name: "agent_usage_and_budget",
+        up: |conn| super::agents::init_table(conn),
+    },
+    Migration {
+        version: 6,
+        name: "agent_routing",
+        up: |conn| super::agents::init_table(conn),
+    },
+    Migration {
+        version: 7,
+        name: "agent_output_schema",
+        up: |conn| super::agents::init_table(conn),
+    },
+    Migration {
+        version: 8,
+        name: "message_branches",
+        up: |conn| super::agents::init_table(conn),
+    },
+    Migration {
+        version: 9,
+        name: "thread_context_summary",
+        up: |conn| super::agents::init_table(conn),
+    },
+    Migration {
+        version: 10,
+        name: "memories",
+        up: |conn| super::memories::init_table(conn),
+    },
+    Migration {
+        version: 11,
+        name: "agent_triggers",
+        up: |conn| super::agent_triggers::init_table(conn),
+    },
+    Migration {
+        version: 12,
+        name: "agent_tool_sandbox",
+        up: |conn| {
+            super::agent_tool_policies::init_table(conn)?;
+            super::agent_tool_audit::init_table(conn)
+        },
+    },
+    Migration {
+        version: 13,
+        name: "scheduler_jobs",
+        up: |conn| super::scheduler_jobs::init_table(conn),
+    },
+    Migration {
+        version: 14,
+        name: "run_logs",
+        up: |conn| super::run_logs::init_table(conn),
+    },
+    Migration {
+        version: 15,
+        name: "process_alerts",
+        up: |conn| super::process_alerts::init_table(conn),
+    },
 ];
 
 /// Bring `conn` up to the newest ledger version. Idempotent.
@@ -137,12 +212,17 @@ mod tests {
     /// Every table `asyar_data.db` is expected to hold after a full ledger run.
     /// Sorted; compared as a whole so an accidental add or drop fails loudly.
     const EXPECTED_TABLES: &[&str] = &[
+        "agent_run_attempts",
+        "agent_tool_audit",
+        "agent_tool_policies",
+        "agent_triggers",
         "agents",
         "clipboard_items",
         "cloud_sync_cursor",
         "cloud_sync_e2ee_local",
         "cloud_sync_items_journal",
         "command_arg_defaults",
+        "embeddings",
         "extension_cache",
         "extension_onboarding",
         "extension_preferences",
@@ -156,10 +236,15 @@ mod tests {
         "mcp_permissions",
         "mcp_servers",
         "mcp_settings",
+        "memories",
+        "message_usage",
         "messages",
         "notes",
         "oauth_tokens",
+        "process_alerts",
+        "run_logs",
         "runs_history",
+        "scheduler_jobs",
         "script_directories",
         "searchbar_accessory_state",
         "shell_trusted_binaries",
@@ -298,6 +383,210 @@ mod tests {
         assert_eq!(user_version(&conn), MIGRATIONS.last().unwrap().version);
     }
 
+    #[test]
+    fn v2_db_gains_snippet_matching_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE snippets (
+                id TEXT PRIMARY KEY,
+                keyword TEXT,
+                expansion TEXT NOT NULL,
+                name TEXT NOT NULL,
+                created_at REAL NOT NULL,
+                pinned INTEGER NOT NULL DEFAULT 0,
+                redacted_kinds TEXT
+            );
+            PRAGMA user_version = 2;",
+        )
+        .unwrap();
+
+        run_ledger(&conn, MIGRATIONS).unwrap();
+
+        let columns = column_names(&conn, "snippets");
+        for column in ["trigger_kind", "expand_mode", "app_scope"] {
+            assert!(columns.contains(&column.to_string()), "missing {column}");
+        }
+    }
+
+    #[test]
+    fn v4_db_gains_agent_budget_and_usage_table() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE agents (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                description TEXT,
+                system_prompt TEXT NOT NULL,
+                provider_id TEXT NOT NULL,
+                model_id TEXT NOT NULL,
+                tool_selection TEXT NOT NULL DEFAULT '[]',
+                silent INTEGER NOT NULL DEFAULT 0,
+                input_source TEXT NOT NULL DEFAULT 'argument',
+                output_action TEXT NOT NULL DEFAULT 'replaceSelection',
+                cache_responses INTEGER NOT NULL DEFAULT 0,
+                shortcode_trigger TEXT NOT NULL DEFAULT ':',
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+            PRAGMA user_version = 4;",
+        )
+        .unwrap();
+
+        run_ledger(&conn, MIGRATIONS).unwrap();
+
+        assert!(column_names(&conn, "agents").contains(&"monthly_budget_usd".to_string()));
+        assert!(names_of(&conn, "table").contains(&"message_usage".to_string()));
+    }
+
+    #[test]
+    fn v5_db_gains_routing_policy_and_attempts_table() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE agents (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                description TEXT,
+                system_prompt TEXT NOT NULL,
+                provider_id TEXT NOT NULL,
+                model_id TEXT NOT NULL,
+                tool_selection TEXT NOT NULL DEFAULT '[]',
+                silent INTEGER NOT NULL DEFAULT 0,
+                input_source TEXT NOT NULL DEFAULT 'argument',
+                output_action TEXT NOT NULL DEFAULT 'replaceSelection',
+                cache_responses INTEGER NOT NULL DEFAULT 0,
+                shortcode_trigger TEXT NOT NULL DEFAULT ':',
+                monthly_budget_usd REAL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+            INSERT INTO agents (id, name, system_prompt, provider_id, model_id,
+                                created_at, updated_at)
+            VALUES ('a1', 'Old', '', 'openai', 'gpt-4o', 1, 1);
+            PRAGMA user_version = 5;",
+        )
+        .unwrap();
+
+        run_ledger(&conn, MIGRATIONS).unwrap();
+
+        assert!(names_of(&conn, "table").contains(&"agent_run_attempts".to_string()));
+        let agent = crate::storage::agents::get_agent(&conn, "a1")
+            .unwrap()
+            .unwrap();
+        assert_eq!(agent.routing, crate::ai::routing::RoutingPolicy::default());
+    }
+
+    #[test]
+    fn v6_db_gains_output_schema() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE agents (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                description TEXT,
+                system_prompt TEXT NOT NULL,
+                provider_id TEXT NOT NULL,
+                model_id TEXT NOT NULL,
+                tool_selection TEXT NOT NULL DEFAULT '[]',
+                silent INTEGER NOT NULL DEFAULT 0,
+                input_source TEXT NOT NULL DEFAULT 'argument',
+                output_action TEXT NOT NULL DEFAULT 'replaceSelection',
+                cache_responses INTEGER NOT NULL DEFAULT 0,
+                shortcode_trigger TEXT NOT NULL DEFAULT ':',
+                monthly_budget_usd REAL,
+                routing_policy TEXT NOT NULL DEFAULT '{}',
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+            INSERT INTO agents (id, name, system_prompt, provider_id, model_id,
+                                created_at, updated_at)
+            VALUES ('a1', 'Old', '', 'openai', 'gpt-4o', 1, 1);
+            PRAGMA user_version = 6;",
+        )
+        .unwrap();
+
+        run_ledger(&conn, MIGRATIONS).unwrap();
+
+        let agent = crate::storage::agents::get_agent(&conn, "a1")
+            .unwrap()
+            .unwrap();
+        assert_eq!(agent.output_schema, None);
+    }
+
+    #[test]
+    fn v7_threads_become_single_branches() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE threads (
+                id TEXT PRIMARY KEY,
+                agent_id TEXT NOT NULL,
+                title TEXT,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE messages (
+                id TEXT PRIMARY KEY,
+                thread_id TEXT NOT NULL,
+                role TEXT NOT NULL,
+                content TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                run_id TEXT
+            );
+            INSERT INTO threads (id, agent_id, created_at, updated_at) VALUES ('t1', 'a1', 1, 1);
+            INSERT INTO messages (id, thread_id, role, content, created_at) VALUES
+                ('m1', 't1', 'user', '{\"text\":\"hi\"}', 10),
+                ('m2', 't1', 'assistant', '{\"text\":\"hello\"}', 20),
+                ('m3', 't1', 'user', '{\"text\":\"again\"}', 20);
+            PRAGMA user_version = 7;",
+        )
+        .unwrap();
+
+        run_ledger(&conn, MIGRATIONS).unwrap();
+
+        let thread = crate::storage::agents::get_thread(&conn, "t1")
+            .unwrap()
+            .unwrap();
+        assert_eq!(thread.current_leaf_id.as_deref(), Some("m3"));
+        let path: Vec<(String, Option<String>)> =
+            crate::storage::agents::list_thread_path(&conn, "t1")
+                .unwrap()
+                .into_iter()
+                .map(|m| (m.id, m.parent_id))
+                .collect();
+        assert_eq!(
+            path,
+            vec![
+                ("m1".to_string(), None),
+                ("m2".to_string(), Some("m1".to_string())),
+                ("m3".to_string(), Some("m2".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn v8_threads_gain_an_empty_context_summary() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE threads (
+                id TEXT PRIMARY KEY,
+                agent_id TEXT NOT NULL,
+                title TEXT,
+                current_leaf_id TEXT,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+            INSERT INTO threads (id, agent_id, created_at, updated_at) VALUES ('t1', 'a1', 1, 1);
+            PRAGMA user_version = 8;",
+        )
+        .unwrap();
+
+        run_ledger(&conn, MIGRATIONS).unwrap();
+
+        assert_eq!(
+            crate::storage::agents::get_thread_summary(&conn, "t1").unwrap(),
+            None
+        );
+    }
+
     #[test]
     fn run_twice_changes_nothing() {
         let conn = Connection::open_in_memory().unwrap();
@@ -1,3 +1,6 @@
+pub mod agent_tool_audit;
+pub mod agent_tool_policies;
+pub mod agent_triggers;
 pub mod agents;
 pub mod clipboard;
 pub mod clipboard_fts;
@@ -5,6 +8,7 @@ pub mod cloud_sync_e2ee_local;
 pub mod cloud_sync_state;
 pub mod command_arg_defaults;
 pub mod commands;
+pub mod embeddings;
 pub mod extension_cache;
 pub mod extension_kv;
 pub mod extension_preferences;
@@ -15,10 +19,14 @@ pub mod mcp_audit;
 pub mod mcp_permissions;
 pub mod mcp_servers;
 pub mod mcp_settings;
+pub mod memories;
 pub mod migrations;
 pub mod notes;
 pub mod notes_fts;
+pub mod process_alerts;
+pub mod run_logs;
 pub mod runs_history;
+pub mod scheduler_jobs;
 pub mod script_directories;
 pub mod searchbar_accessory;
 pub mod shell;
@@ -0,0 +1,208 @@
+//! Resource threshold alerts for [`crate::process_manager::sampler`].
+//!
+//! One row per (owner, alert id). The launcher's own alerts are stored with
+//! an empty `extension_id`, so the pair stays a plain primary key.
+
+use crate::error::AppError;
+use crate::process_manager::history::{ResourceAlert, ResourceMetric};
+use rusqlite::{params, Connection};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredAlert {
+    /// `None` for alerts the launcher itself set.
+    pub extension_id: Option<String>,
+    pub alert: ResourceAlert,
+}
+
+pub fn init_table(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS process_alerts (
+            extension_id   TEXT    NOT NULL,
+            id             TEXT    NOT NULL,
+            app_name       TEXT,
+            metric         TEXT    NOT NULL,
+            threshold      REAL    NOT NULL,
+            sustained_secs INTEGER NOT NULL,
+            enabled        INTEGER NOT NULL,
+            command_id     TEXT,
+            PRIMARY KEY (extension_id, id)
+        );",
+    )
+    .map_err(|e| AppError::Database(format!("Failed to init process_alerts table: {e}")))?;
+    Ok(())
+}
+
+fn owner_key(extension_id: Option<&str>) -> &str {
+    extension_id.unwrap_or("")
+}
+
+pub fn list_all(conn: &Connection) -> Result<Vec<StoredAlert>, AppError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT extension_id, id, app_name, metric, threshold, sustained_secs, enabled,
+                    command_id
+             FROM process_alerts ORDER BY extension_id, id",
+        )
+        .map_err(|e| AppError::Database(format!("Failed to list process alerts: {e}")))?;
+    let rows = stmt
+        .query_map([], |row| {
+            let extension_id: String = row.get(0)?;
+            let metric: String = row.get(3)?;
+            Ok((
+                extension_id,
+                metric,
+                ResourceAlert {
+                    id: row.get(1)?,
+                    app_name: row.get(2)?,
+                    metric: ResourceMetric::Cpu,
+                    threshold: row.get(4)?,
+                    sustained_secs: row.get(5)?,
+                    enabled: row.get::<_, i64>(6)? != 0,
+                    command_id: row.get(7)?,
+                },
+            ))
+        })
+        .map_err(|e| AppError::Database(format!("Failed to list process alerts: {e}")))?;
+
+    let mut alerts = Vec::new();
+    for row in rows {
+        let (extension_id, metric, mut alert) =
+            row.map_err(|e| AppError::Database(format!("Failed to read process alert: {e}")))?;
+        // A metric this build doesn't know came from a newer one; skip it
+        // rather than guess.
+        let Some(metric) = ResourceMetric::parse(&metric) else {
+            continue;
+        };
+        alert.metric = metric;
+        alerts.push(StoredAlert {
+            extension_id: (!extension_id.is_empty()).then_some(extension_id),
+            alert,
+        });
+    }
+    Ok(alerts)
+}
+
+/// Insert or replace the owner's alert with the same id.
+pub fn upsert(
+    conn: &Connection,
+    extension_id: Option<&str>,
+    alert: &ResourceAlert,
+) -> Result<(), AppError> {
+    conn.execute(
+        "INSERT INTO process_alerts
+            (extension_id, id, app_name, metric, threshold, sustained_secs, enabled, command_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+         ON CONFLICT(extension_id, id) DO UPDATE SET
+            app_name = excluded.app_name,
+            metric = excluded.metric,
+            threshold = excluded.threshold,
+            sustained_secs = excluded.sustained_secs,
+            enabled = excluded.enabled,
+            command_id = excluded.command_id",
+        params![
+            owner_key(extension_id),
+            alert.id,
+            alert.app_name,
+            alert.metric.as_str(),
+            alert.threshold,
+            alert.sustained_secs,
+            alert.enabled as i64,
+            alert.command_id,
+        ],
+    )
+    .map_err(|e| AppError::Database(format!("Failed to save process alert: {e}")))?;
+    Ok(())
+}
+
+/// Returns whether a row was removed.
+pub fn delete(conn: &Connection, extension_id: Option<&str>, id: &str) -> Result<bool, AppError> {
+    let n = conn
+        .execute(
+            "DELETE FROM process_alerts WHERE extension_id = ?1 AND id = ?2",
+            params![owner_key(extension_id), id],
+        )
+        .map_err(|e| AppError::Database(format!("Failed to delete process alert: {e}")))?;
+    Ok(n > 0)
+}
+
+pub fn delete_for_extension(conn: &Connection, extension_id: &str) -> Result<usize, AppError> {
+    conn.execute(
+        "DELETE FROM process_alerts WHERE extension_id = ?1",
+        params![extension_id],
+    )
+    .map_err(|e| AppError::Database(format!("Failed to clear process alerts: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_table(&conn).unwrap();
+        conn
+    }
+
+    fn alert(id: &str, threshold: f64) -> ResourceAlert {
+        ResourceAlert {
+            id: id.into(),
+            app_name: Some("Google Chrome".into()),
+            metric: ResourceMetric::Memory,
+            threshold,
+            sustained_secs: 300,
+            enabled: true,
+            command_id: Some("show".into()),
+        }
+    }
+
+    #[test]
+    fn upsert_round_trips_and_replaces_by_owner_and_id() {
+        let conn = setup();
+        upsert(&conn, Some("ext.a"), &alert("mem", 1.0)).unwrap();
+        upsert(&conn, Some("ext.a"), &alert("mem", 2.0)).unwrap();
+        upsert(&conn, None, &alert("mem", 3.0)).unwrap();
+
+        let all = list_all(&conn).unwrap();
+        assert_eq!(
+            all,
+            vec![
+                StoredAlert {
+                    extension_id: None,
+                    alert: alert("mem", 3.0),
+                },
+                StoredAlert {
+                    extension_id: Some("ext.a".into()),
+                    alert: alert("mem", 2.0),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn delete_is_scoped_to_the_owner() {
+        let conn = setup();
+        upsert(&conn, Some("ext.a"), &alert("mem", 1.0)).unwrap();
+        assert!(!delete(&conn, Some("ext.b"), "mem").unwrap());
+        assert!(delete(&conn, Some("ext.a"), "mem").unwrap());
+        assert!(list_all(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn delete_for_extension_leaves_other_owners() {
+        let conn = setup();
+        upsert(&conn, Some("ext.a"), &alert("one", 1.0)).unwrap();
+        upsert(&conn, Some("ext.a"), &alert("two", 1.0)).unwrap();
+        upsert(&conn, None, &alert("one", 1.0)).unwrap();
+        assert_eq!(delete_for_extension(&conn, "ext.a").unwrap(), 2);
+        assert_eq!(list_all(&conn).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn unknown_metrics_are_skipped() {
+        let conn = setup();
+        upsert(&conn, None, &alert("mem", 1.0)).unwrap();
+        conn.execute("UPDATE process_alerts SET metric = 'disk'", [])
+            .unwrap();
+        assert!(list_all(&conn).unwrap().is_empty());
+    }
+}
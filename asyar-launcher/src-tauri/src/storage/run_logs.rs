@@ -0,0 +1,243 @@
+//! Index of the on-disk run logs kept by [`crate::runs::logs::RunLogStore`].
+//!
+//! One row per captured run. The row is written when the run starts (so the
+//! inputs needed to re-run it survive a crash) and completed with the log's
+//! size once the run finishes and its log is compressed. Retention reads the
+//! finished rows oldest first and deletes file and row together.
+
+use crate::error::AppError;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunLogRecord {
+    pub run_id: String,
+    /// Whatever the run was started with, as JSON — enough for the caller
+    /// that started it to start it again. `None` for runs with no replayable
+    /// inputs.
+    pub inputs: Option<serde_json::Value>,
+    /// Unix millis.
+    pub started_at: i64,
+    /// Unix millis; `None` while the run is still writing.
+    pub finished_at: Option<i64>,
+    /// Uncompressed size of the captured output.
+    pub raw_bytes: i64,
+    /// Size of the compressed file on disk.
+    pub stored_bytes: i64,
+    pub line_count: i64,
+    /// `true` when output went past the per-run cap and the rest was dropped.
+    pub truncated: bool,
+}
+
+pub fn init_table(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS run_logs (
+            run_id       TEXT    PRIMARY KEY,
+            inputs       TEXT,
+            started_at   INTEGER NOT NULL,
+            finished_at  INTEGER,
+            raw_bytes    INTEGER NOT NULL DEFAULT 0,
+            stored_bytes INTEGER NOT NULL DEFAULT 0,
+            line_count   INTEGER NOT NULL DEFAULT 0,
+            truncated    INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE INDEX IF NOT EXISTS idx_run_logs_finished_at
+            ON run_logs(finished_at);",
+    )
+    .map_err(|e| AppError::Database(format!("Failed to init run_logs table: {e}")))?;
+    Ok(())
+}
+
+/// Records a run whose log has just been opened. Re-inserting an id
+/// replaces the previous row.
+pub fn insert_started(
+    conn: &Connection,
+    run_id: &str,
+    started_at: i64,
+    inputs: Option<&serde_json::Value>,
+) -> Result<(), AppError> {
+    let inputs = inputs.map(|v| v.to_string());
+    conn.execute(
+        "INSERT OR REPLACE INTO run_logs (run_id, inputs, started_at) VALUES (?1, ?2, ?3)",
+        params![run_id, inputs, started_at],
+    )
+    .map_err(|e| AppError::Database(format!("Failed to insert run log: {e}")))?;
+    Ok(())
+}
+
+/// Completes a run's row once its log is compressed. A run with no row
+/// (its start was never recorded) gets one.
+#[allow(clippy::too_many_arguments)]
+pub fn mark_finished(
+    conn: &Connection,
+    run_id: &str,
+    finished_at: i64,
+    raw_bytes: i64,
+    stored_bytes: i64,
+    line_count: i64,
+    truncated: bool,
+) -> Result<(), AppError> {
+    conn.execute(
+        "INSERT INTO run_logs
+            (run_id, started_at, finished_at, raw_bytes, stored_bytes, line_count, truncated)
+         VALUES (?1, ?2, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(run_id) DO UPDATE SET
+            finished_at = excluded.finished_at,
+            raw_bytes = excluded.raw_bytes,
+            stored_bytes = excluded.stored_bytes,
+            line_count = excluded.line_count,
+            truncated = excluded.truncated",
+        params![
+            run_id,
+            finished_at,
+            raw_bytes,
+            stored_bytes,
+            line_count,
+            truncated as i64
+        ],
+    )
+    .map_err(|e| AppError::Database(format!("Failed to finish run log: {e}")))?;
+    Ok(())
+}
+
+pub fn get(conn: &Connection, run_id: &str) -> Result<Option<RunLogRecord>, AppError> {
+    let mut stmt = conn
+        .prepare(&format!("{SELECT} WHERE run_id = ?1"))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let mut rows = stmt
+        .query_map(params![run_id], from_row)
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    rows.next()
+        .transpose()
+        .map_err(|e| AppError::Database(format!("Failed to get run log: {e}")))
+}
+
+/// Finished logs, most recently finished first.
+pub fn list_finished(conn: &Connection) -> Result<Vec<RunLogRecord>, AppError> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{SELECT} WHERE finished_at IS NOT NULL ORDER BY finished_at DESC"
+        ))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let rows = stmt
+        .query_map([], from_row)
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(format!("Failed to list run logs: {e}")))
+}
+
+/// Ids of runs whose log was never finished — Asyar quit while they ran.
+pub fn list_unfinished_ids(conn: &Connection) -> Result<Vec<String>, AppError> {
+    let mut stmt = conn
+        .prepare("SELECT run_id FROM run_logs WHERE finished_at IS NULL")
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let rows = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(format!("Failed to list unfinished run logs: {e}")))
+}
+
+/// Deleting an unknown id is a no-op.
+pub fn delete(conn: &Connection, run_id: &str) -> Result<(), AppError> {
+    conn.execute("DELETE FROM run_logs WHERE run_id = ?1", params![run_id])
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+pub fn delete_all(conn: &Connection) -> Result<(), AppError> {
+    conn.execute("DELETE FROM run_logs", [])
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+const SELECT: &str = "SELECT run_id, inputs, started_at, finished_at, raw_bytes, stored_bytes,
+        line_count, truncated
+ FROM run_logs";
+
+fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<RunLogRecord> {
+    let inputs: Option<String> = row.get(1)?;
+    Ok(RunLogRecord {
+        run_id: row.get(0)?,
+        // A row whose inputs no longer parse just can't be re-run.
+        inputs: inputs.and_then(|s| serde_json::from_str(&s).ok()),
+        started_at: row.get(2)?,
+        finished_at: row.get(3)?,
+        raw_bytes: row.get(4)?,
+        stored_bytes: row.get(5)?,
+        line_count: row.get(6)?,
+        truncated: row.get::<_, i64>(7)? != 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_table(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn started_then_finished_round_trips() {
+        let conn = setup();
+        let inputs = json!({ "program": "/bin/echo", "args": ["hi"] });
+        insert_started(&conn, "r1", 100, Some(&inputs)).unwrap();
+
+        let started = get(&conn, "r1").unwrap().unwrap();
+        assert_eq!(started.inputs, Some(inputs.clone()));
+        assert_eq!(started.finished_at, None);
+        assert_eq!(list_unfinished_ids(&conn).unwrap(), vec!["r1".to_string()]);
+
+        mark_finished(&conn, "r1", 250, 4096, 512, 80, true).unwrap();
+        assert_eq!(
+            get(&conn, "r1").unwrap().unwrap(),
+            RunLogRecord {
+                run_id: "r1".to_string(),
+                inputs: Some(inputs),
+                started_at: 100,
+                finished_at: Some(250),
+                raw_bytes: 4096,
+                stored_bytes: 512,
+                line_count: 80,
+                truncated: true,
+            }
+        );
+        assert!(list_unfinished_ids(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn mark_finished_without_a_start_creates_the_row() {
+        let conn = setup();
+        mark_finished(&conn, "orphan", 300, 10, 8, 1, false).unwrap();
+        let record = get(&conn, "orphan").unwrap().unwrap();
+        assert_eq!(record.started_at, 300);
+        assert_eq!(record.inputs, None);
+    }
+
+    #[test]
+    fn list_finished_is_newest_first_and_skips_running() {
+        let conn = setup();
+        insert_started(&conn, "old", 1, None).unwrap();
+        mark_finished(&conn, "old", 10, 1, 1, 1, false).unwrap();
+        insert_started(&conn, "new", 2, None).unwrap();
+        mark_finished(&conn, "new", 20, 1, 1, 1, false).unwrap();
+        insert_started(&conn, "running", 3, None).unwrap();
+
+        let ids: Vec<String> = list_finished(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.run_id)
+            .collect();
+        assert_eq!(ids, vec!["new".to_string(), "old".to_string()]);
+
+        delete(&conn, "new").unwrap();
+        assert_eq!(get(&conn, "new").unwrap(), None);
+        delete_all(&conn).unwrap();
+        assert_eq!(get(&conn, "running").unwrap(), None);
+    }
+}
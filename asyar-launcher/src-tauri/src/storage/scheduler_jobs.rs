@@ -0,0 +1,145 @@
+//! Last-run / next-run bookkeeping for [`crate::scheduler`] jobs.
+//!
+//! One row per job id. The scheduler writes it after every run and every
+//! reschedule, and reads it once at registration: a cron job whose
+//! `next_run_at` passed while Asyar was quit is caught up according to its
+//! policy, and a one-shot job that already ran stays done.
+
+use crate::error::AppError;
+use rusqlite::{params, Connection};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JobRecord {
+    pub id: String,
+    /// Unix millis the last run started.
+    pub last_run_at: Option<i64>,
+    /// Unix millis of the next planned fire; `None` once a job has nothing
+    /// left to run.
+    pub next_run_at: Option<i64>,
+    pub last_duration_ms: Option<i64>,
+    /// The last run's error, or `None` when it succeeded.
+    pub last_error: Option<String>,
+}
+
+pub fn init_table(conn: &Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS scheduler_jobs (
+            id               TEXT    PRIMARY KEY,
+            last_run_at      INTEGER,
+            next_run_at      INTEGER,
+            last_duration_ms INTEGER,
+            last_error       TEXT
+        );",
+    )
+    .map_err(|e| AppError::Database(format!("Failed to init scheduler_jobs table: {e}")))?;
+    Ok(())
+}
+
+pub fn get(conn: &Connection, id: &str) -> Result<Option<JobRecord>, AppError> {
+    match conn.query_row(
+        "SELECT id, last_run_at, next_run_at, last_duration_ms, last_error
+         FROM scheduler_jobs WHERE id = ?1",
+        params![id],
+        |row| {
+            Ok(JobRecord {
+                id: row.get(0)?,
+                last_run_at: row.get(1)?,
+                next_run_at: row.get(2)?,
+                last_duration_ms: row.get(3)?,
+                last_error: row.get(4)?,
+            })
+        },
+    ) {
+        Ok(record) => Ok(Some(record)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(AppError::Database(format!(
+            "Failed to get scheduler job: {e}"
+        ))),
+    }
+}
+
+/// Records a finished run; the planned next fire is left alone.
+pub fn record_run(
+    conn: &Connection,
+    id: &str,
+    started_at: i64,
+    duration_ms: i64,
+    error: Option<&str>,
+) -> Result<(), AppError> {
+    conn.execute(
+        "INSERT INTO scheduler_jobs (id, last_run_at, last_duration_ms, last_error)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(id) DO UPDATE SET
+            last_run_at = excluded.last_run_at,
+            last_duration_ms = excluded.last_duration_ms,
+            last_error = excluded.last_error",
+        params![id, started_at, duration_ms, error],
+    )
+    .map_err(|e| AppError::Database(format!("Failed to record scheduler run: {e}")))?;
+    Ok(())
+}
+
+/// Moves a job's next planned fire without touching its run history.
+pub fn set_next_run(conn: &Connection, id: &str, next_run_at: Option<i64>) -> Result<(), AppError> {
+    conn.execute(
+        "INSERT INTO scheduler_jobs (id, next_run_at) VALUES (?1, ?2)
+         ON CONFLICT(id) DO UPDATE SET next_run_at = excluded.next_run_at",
+        params![id, next_run_at],
+    )
+    .map_err(|e| AppError::Database(format!("Failed to save scheduler next run: {e}")))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_table(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn unknown_job_has_no_record() {
+        let conn = setup();
+        assert_eq!(get(&conn, "nope").unwrap(), None);
+    }
+
+    #[test]
+    fn record_run_round_trips_and_replaces() {
+        let conn = setup();
+        set_next_run(&conn, "job", Some(200)).unwrap();
+        record_run(&conn, "job", 100, 25, Some("boom")).unwrap();
+        assert_eq!(
+            get(&conn, "job").unwrap().unwrap(),
+            JobRecord {
+                id: "job".to_string(),
+                last_run_at: Some(100),
+                next_run_at: Some(200),
+                last_duration_ms: Some(25),
+                last_error: Some("boom".to_string()),
+            }
+        );
+
+        record_run(&conn, "job", 300, 5, None).unwrap();
+        let record = get(&conn, "job").unwrap().unwrap();
+        assert_eq!(record.last_run_at, Some(300));
+        assert_eq!(record.last_error, None);
+        assert_eq!(record.next_run_at, Some(200));
+    }
+
+    #[test]
+    fn set_next_run_keeps_the_run_history() {
+        let conn = setup();
+        set_next_run(&conn, "fresh", Some(50)).unwrap();
+        assert_eq!(get(&conn, "fresh").unwrap().unwrap().next_run_at, Some(50));
+
+        record_run(&conn, "job", 100, 25, None).unwrap();
+        set_next_run(&conn, "job", Some(400)).unwrap();
+        let record = get(&conn, "job").unwrap().unwrap();
+        assert_eq!(record.next_run_at, Some(400));
+        assert_eq!(record.last_run_at, Some(100));
+        assert_eq!(record.last_duration_ms, Some(25));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::crypto::cipher;
 use crate::error::AppError;
+use crate::snippets::{SnippetAppScope, SnippetExpandMode, SnippetTriggerKind};
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 
@@ -32,6 +33,15 @@ pub struct Snippet {
     /// `expansion` at save time. See [`crate::secret_detection::redact`].
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub redacted_kinds: Option<Vec<String>>,
+    /// Whether `keyword` is matched literally or as an end-anchored regex.
+    #[serde(default)]
+    pub trigger: SnippetTriggerKind,
+    #[serde(default)]
+    pub expand_mode: SnippetExpandMode,
+    /// Optional include/exclude application list. Stored as JSON; plaintext
+    /// for the same reason `keyword` is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_scope: Option<SnippetAppScope>,
 }
 
 fn encode_redacted_kinds(kinds: &Option<Vec<String>>) -> Option<String> {
@@ -46,6 +56,40 @@ fn decode_redacted_kinds(raw: Option<String>) -> Option<Vec<String>> {
         .map(|s| s.split(',').map(|p| p.to_string()).collect())
 }
 
+fn encode_trigger(kind: SnippetTriggerKind) -> &'static str {
+    match kind {
+        SnippetTriggerKind::Literal => "literal",
+        SnippetTriggerKind::Regex => "regex",
+    }
+}
+
+fn decode_trigger(raw: Option<String>) -> SnippetTriggerKind {
+    match raw.as_deref() {
+        Some("regex") => SnippetTriggerKind::Regex,
+        _ => SnippetTriggerKind::Literal,
+    }
+}
+
+fn encode_expand_mode(mode: SnippetExpandMode) -> &'static str {
+    match mode {
+        SnippetExpandMode::Immediate => "immediate",
+        SnippetExpandMode::Delimiter => "delimiter",
+    }
+}
+
+fn decode_expand_mode(raw: Option<String>) -> SnippetExpandMode {
+    match raw.as_deref() {
+        Some("delimiter") => SnippetExpandMode::Delimiter,
+        _ => SnippetExpandMode::Immediate,
+    }
+}
+
+/// A malformed stored scope decodes as "no scope" rather than hiding the row.
+fn decode_app_scope(raw: Option<String>) -> Option<SnippetAppScope> {
+    raw.filter(|s| !s.is_empty())
+        .and_then(|s| serde_json::from_str(&s).ok())
+}
+
 pub fn init_table(conn: &Connection) -> Result<(), AppError> {
     conn.execute_batch(
         "CREATE TABLE IF NOT EXISTS snippets (
@@ -77,15 +121,34 @@ pub fn init_table(conn: &Connection) -> Result<(), AppError> {
     Ok(())
 }
 
+/// Rejects a regex trigger the listener could never compile, so the mistake
+/// surfaces on save rather than as a snippet that silently never fires.
+fn validate_trigger(snippet: &Snippet) -> Result<(), AppError> {
+    if snippet.trigger != SnippetTriggerKind::Regex {
+        return Ok(());
+    }
+    let keyword = snippet.keyword.as_deref().unwrap_or_default();
+    crate::snippets::trigger_regex(keyword)
+        .map(|_| ())
+        .map_err(|e| AppError::Validation(format!("Invalid snippet regex \"{keyword}\": {e}")))
+}
+
 /// Insert or replace a snippet (upsert by id). The `expansion` column
 /// is encrypted under `master_key`; `keyword` stays plaintext because
 /// the global keystroke matcher needs to compare incoming keystrokes
 /// against keywords without decrypting every row on every press.
 pub fn upsert(conn: &Connection, snippet: &Snippet, master_key: &[u8; 32]) -> Result<(), AppError> {
+    validate_trigger(snippet)?;
     let encrypted_expansion = cipher::encrypt(&snippet.expansion, master_key)?;
+    let app_scope = snippet
+        .app_scope
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()?;
     conn.execute(
-        "INSERT OR REPLACE INTO snippets (id, keyword, expansion, name, created_at, pinned, redacted_kinds)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        "INSERT OR REPLACE INTO snippets (id, keyword, expansion, name, created_at, pinned, redacted_kinds,
+                                          trigger_kind, expand_mode, app_scope)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
         params![
             snippet.id,
             snippet.keyword,
@@ -94,6 +157,9 @@ pub fn upsert(conn: &Connection, snippet: &Snippet, master_key: &[u8; 32]) -> Re
             snippet.created_at,
             snippet.pinned as i32,
             encode_redacted_kinds(&snippet.redacted_kinds),
+            encode_trigger(snippet.trigger),
+            encode_expand_mode(snippet.expand_mode),
+            app_scope,
         ],
     )
     .map_err(|e| AppError::Database(format!("Failed to upsert snippet: {e}")))?;
@@ -185,7 +251,8 @@ pub fn clear_all(conn: &Connection) -> Result<(), AppError> {
 pub fn get_all(conn: &Connection, master_key: &[u8; 32]) -> Result<Vec<Snippet>, AppError> {
     let mut stmt = conn
         .prepare(
-            "SELECT id, keyword, expansion, name, created_at, pinned, redacted_kinds
+            "SELECT id, keyword, expansion, name, created_at, pinned, redacted_kinds,
+                    trigger_kind, expand_mode, app_scope
              FROM snippets ORDER BY created_at DESC",
         )
         .map_err(|e| AppError::Database(format!("Failed to prepare query: {e}")))?;
@@ -202,6 +269,9 @@ pub fn get_all(conn: &Connection, master_key: &[u8; 32]) -> Result<Vec<Snippet>,
                 created_at: row.get(4)?,
                 pinned: row.get::<_, i32>(5)? != 0,
                 redacted_kinds: decode_redacted_kinds(redacted_kinds_str),
+                trigger: decode_trigger(row.get(7)?),
+                expand_mode: decode_expand_mode(row.get(8)?),
+                app_scope: decode_app_scope(row.get(9)?),
             })
         })
         .map_err(|e| AppError::Database(format!("Failed to query snippets: {e}")))?
@@ -217,7 +287,8 @@ mod tests {
 
     fn setup() -> Connection {
         let conn = Connection::open_in_memory().unwrap();
-        init_table(&conn).unwrap();
+        // The matching-option columns arrive through the ledger, not here.
+        crate::storage::migrations::run(&conn).unwrap();
         conn
     }
 
@@ -238,6 +309,9 @@ mod tests {
             created_at: 1000.0 + id.parse::<f64>().unwrap_or(0.0),
             pinned: false,
             redacted_kinds: None,
+            trigger: SnippetTriggerKind::Literal,
+            expand_mode: SnippetExpandMode::Immediate,
+            app_scope: None,
         }
     }
 
@@ -257,6 +331,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_matching_options_round_trip() {
+        use crate::snippets::SnippetAppScopeMode;
+        let conn = setup();
+        let key = test_key();
+        let mut s = make_snippet("1", ";mail(\\w+)", "$1@company.com");
+        s.trigger = SnippetTriggerKind::Regex;
+        s.expand_mode = SnippetExpandMode::Delimiter;
+        s.app_scope = Some(SnippetAppScope {
+            mode: SnippetAppScopeMode::Exclude,
+            apps: vec!["com.apple.Terminal".into()],
+        });
+        upsert(&conn, &s, &key).unwrap();
+
+        let items = get_all(&conn, &key).unwrap();
+        assert_eq!(items[0].trigger, SnippetTriggerKind::Regex);
+        assert_eq!(items[0].expand_mode, SnippetExpandMode::Delimiter);
+        assert_eq!(items[0].app_scope, s.app_scope);
+    }
+
+    #[test]
+    fn test_upsert_rejects_invalid_regex_trigger() {
+        let conn = setup();
+        let key = test_key();
+        let mut s = make_snippet("1", "(?<=x)y", "z");
+        s.trigger = SnippetTriggerKind::Regex;
+        assert!(matches!(
+            upsert(&conn, &s, &key),
+            Err(AppError::Validation(_))
+        ));
+        assert!(get_all(&conn, &key).unwrap().is_empty());
+
+        // The same text is fine as a literal keyword.
+        s.trigger = SnippetTriggerKind::Literal;
+        upsert(&conn, &s, &key).unwrap();
+    }
+
     #[test]
     fn test_init_table_idempotent_adds_redacted_kinds() {
         let conn = Connection::open_in_memory().unwrap();
@@ -26,7 +26,7 @@ export type AgentRunConfig = {
 	maxTokens: number,
 };
 
-export type AgentStreamEvent = { type: "user_message_persisted" } | { type: "text_delta"; delta: string; accumulated: string } | { type: "status"; status: string | null } | { type: "assistant_turn_persisted" } | { type: "tool_dispatch"; tool_call_id: string; extension_id: string; tool_id: string; arguments: any } | { type: "tool_dispatch_cancelled"; tool_call_id: string } | { type: "mcp_permission_request"; tool_call_id: string; server_id: string; tool_id: string; agent_id: string } | { type: "mcp_permission_cancelled"; tool_call_id: string } | { type: "error"; message: string } | { type: "completed" } | { type: "cancelled" };
+export type AgentStreamEvent = { type: "user_message_persisted" } | { type: "text_delta"; delta: string; accumulated: string } | { type: "status"; status: string | null } | { type: "assistant_turn_persisted" } | { type: "usage"; usage: TokenUsage; cost_usd: number | null } | { type: "provider_attempt_failed"; provider_id: string; model_id: string; outcome: AttemptOutcome; status_code: number | null; message: string; retry_in_ms: number } | { type: "tool_dispatch"; tool_call_id: string; extension_id: string; tool_id: string; arguments: any } | { type: "tool_dispatch_cancelled"; tool_call_id: string } | { type: "mcp_permission_request"; tool_call_id: string; server_id: string; tool_id: string; agent_id: string } | { type: "mcp_permission_cancelled"; tool_call_id: string } | { type: "context_compacted"; summarized_messages: number } | { type: "structured_output"; value: any } | { type: "sub_agent_started"; tool_call_id: string; agent_id: string; agent_name: string; task: string } | { type: "sub_agent_event"; tool_call_id: string; event: AgentStreamEvent } | { type: "sub_agent_finished"; tool_call_id: string; cancelled: boolean; error: string | null } | { type: "error"; message: string } | { type: "completed" } | { type: "cancelled" };
 
 export type AliasConflict = {
 	objectId: string,
@@ -59,6 +59,9 @@ export type Application = {
 	bundleId?: string | null,
 };
 
+// How one provider request ended. Stored as a short stable string.
+export type AttemptOutcome = "succeeded" | "rateLimited" | "serverError" | "timeout" | "offline" | "failed";
+
 // Category of a calculator answer. Drives icon selection in the UI.
 export type CalcKind = "math" | "unit" | "currency" | "date" | "time" | "base" | "color" | "percent" | "ratio";
 
@@ -79,6 +82,8 @@ export type ChatMessage = {
 	toolCalls: ToolCall[] | null,
 	toolCallId: string | null,
 	providerContext: any[] | null,
+	// Attachments sent after `content`; user messages only.
+	parts: ContentPart[] | null,
 };
 
 export type ChatParams = {
@@ -87,11 +92,16 @@ export type ChatParams = {
 	maxTokens: number,
 	systemPrompt: string | null,
 	tools: ToolDefinition[] | null,
+	/**
+	 *  JSON Schema the reply must match. Engines with a native JSON mode
+	 *  get it in the request; the rest get it as a system-prompt instruction.
+	 */
+	responseSchema?: any | null,
 };
 
-export type ChatStreamEvent = { type: "token"; token: string } | { type: "status"; status: string } | { type: "toolCall"; id: string; name: string; input: any } | { type: "providerContext"; item: any };
+export type ChatStreamEvent = { type: "token"; token: string } | { type: "status"; status: string } | { type: "toolCall"; id: string; name: string; input: any } | { type: "providerContext"; item: any } | { type: "usage"; usage: TokenUsage };
 
-export type ChatStreamEventPayload = { type: "token"; token: string } | { type: "status"; status: string } | { type: "toolCall"; id: string; name: string; input: any } | { type: "providerContext"; item: any } | { type: "done" } | { type: "error"; error: string };
+export type ChatStreamEventPayload = { type: "token"; token: string } | { type: "status"; status: string } | { type: "toolCall"; id: string; name: string; input: any } | { type: "providerContext"; item: any } | { type: "usage"; usage: TokenUsage } | { type: "done" } | { type: "error"; error: string };
 
 export type Command = {
 	id: string,
@@ -123,6 +133,12 @@ export type Command = {
 	isDynamic?: boolean,
 };
 
+/**
+ *  One typed piece of a multimodal message. Each provider builder encodes
+ *  these into its own wire format.
+ */
+export type ContentPart = { type: "text"; text: string } | { type: "image"; mediaType: string; source: ImageSource } | { type: "file"; name: string; mediaType: string; text: string | null; data: string | null };
+
 /**
  *  Represents a search result contributed by a frontend extension.
  *  Sent from TypeScript to Rust for unified ranking.
@@ -194,6 +210,9 @@ export type FileType = "document" | "image" | "code" | "audio-video" | "archive"
  */
 export type HitSource = "local" | "deep";
 
+// Where an image attachment's bytes come from.
+export type ImageSource = { type: "bytes"; data: string } | { type: "clipboardCache"; path: string };
+
 // Index lifecycle state, kebab-case on the wire.
 export type IndexStateKind = "disabled" | "building" | "ready" | "rescanning" | "cap-reached";
 
@@ -304,6 +323,30 @@ export type RankInput = {
  */
 export type ResultPriority = "top";
 
+export type RouteTarget = {
+	providerId: string,
+	modelId: string,
+};
+
+/**
+ *  Per-agent routing. The default policy — no retries, no fallbacks — is
+ *  exactly the pre-routing behaviour, so existing agents are unaffected.
+ */
+export type RoutingPolicy = {
+	// Tried in order once the agent's own provider/model gives up.
+	fallbacks: RouteTarget[],
+	/**
+	 *  Retries per target on rate limits, 5xx, timeouts and connection
+	 *  failures before moving to the next fallback.
+	 */
+	maxRetries: number,
+	/**
+	 *  Ollama connection to switch to as soon as a request can't connect
+	 *  at all, skipping retries and the remaining cloud fallbacks.
+	 */
+	offlineFallback: RouteTarget | null,
+};
+
 export type SearchResult = {
 	objectId: string,
 	name: string,
@@ -327,6 +370,19 @@ export type SearchResult = {
 
 export type SearchableItem = { category: "application" } & Application | { category: "command" } & Command;
 
+/**
+ *  What a silent run hands back: the text its output action applies and,
+ *  for an agent with an output schema, the parsed answer that text was
+ *  rendered from.
+ */
+export type SilentRunOutput = {
+	text: string,
+	structured: any | null,
+};
+
+// The dimension [`spend_summary`] groups by.
+export type SpendGroup = "day" | "agent" | "provider" | "model" | "thread";
+
 export type StreamEventPayload = {
 	streamId: string,
 	event: ChatStreamEventPayload,
@@ -340,6 +396,20 @@ export type TierResult = {
 	tier: number,
 };
 
+/**
+ *  Token counts for one provider response, normalised across engines:
+ *  `input_tokens` includes any prompt tokens served from cache, and
+ *  `output_tokens` includes any reasoning tokens. `cached_tokens` and
+ *  `reasoning_tokens` are the respective subsets, zero when the provider
+ *  doesn't report them.
+ */
+export type TokenUsage = {
+	inputTokens: number,
+	outputTokens: number,
+	cachedTokens: number,
+	reasoningTokens: number,
+};
+
 export type ToolCall = {
 	id: string,
 	name: string,
@@ -7,6 +7,7 @@ vi.mock('./agentService.svelte', () => ({
     getById: vi.fn(),
     listThreads: vi.fn(),
     listMessages: vi.fn(),
+    getThreadSummary: vi.fn(),
   },
 }));
 
@@ -32,7 +33,7 @@ vi.mock('../../lib/ipc/commands', () => ({
 import AgentChatView from './AgentChatView.svelte';
 import { agentService } from './agentService.svelte';
 import { agentsManager } from './agentsManager.svelte';
-import type { MessageDef } from './types';
+import type { PathMessageDef } from './types';
 
 const mockedAgentService = vi.mocked(agentService);
 
@@ -55,6 +56,7 @@ const thread = {
   id: 'thread-1',
   agentId: agent.id,
   title: 'Thread to delete',
+  currentLeafId: null,
   createdAt: 1,
   updatedAt: 1,
 };
@@ -73,6 +75,7 @@ describe('AgentChatView', () => {
     mockedAgentService.getById.mockReturnValue(agent);
     mockedAgentService.listThreads.mockResolvedValue([thread]);
     mockedAgentService.listMessages.mockResolvedValue([]);
+    mockedAgentService.getThreadSummary.mockResolvedValue(null);
     agentsManager.currentAgentId = agent.id;
     agentsManager.currentThreadId = thread.id;
     agentsManager.sending = false;
@@ -102,7 +105,7 @@ describe('AgentChatView', () => {
 
   it('ignores messages that resolve after a newer thread is selected', async () => {
     const otherThread = { ...thread, id: 'thread-2', title: 'Current thread' };
-    const staleMessages = deferred<MessageDef[]>();
+    const staleMessages = deferred<PathMessageDef[]>();
     mockedAgentService.listThreads.mockResolvedValue([thread, otherThread]);
     mockedAgentService.listMessages.mockImplementation((threadId) => {
       if (threadId === thread.id) return staleMessages.promise;
@@ -114,6 +117,8 @@ describe('AgentChatView', () => {
           content: { text: 'Current message' },
           createdAt: 2,
           runId: null,
+          parentId: null,
+          siblingIds: ['message-2'],
         },
       ]);
     });
@@ -134,6 +139,8 @@ describe('AgentChatView', () => {
         content: { text: 'Stale message' },
         createdAt: 1,
         runId: null,
+        parentId: null,
+        siblingIds: ['message-1'],
       },
     ]);
 
@@ -141,6 +148,30 @@ describe('AgentChatView', () => {
     expect(screen.getByText('Current message')).toBeTruthy();
   });
 
+  it('pins the context summary after the last summarized message', async () => {
+    mockedAgentService.listMessages.mockResolvedValue([
+      {
+        id: 'message-1',
+        threadId: thread.id,
+        role: 'user',
+        content: { text: 'Summarized message' },
+        createdAt: 1,
+        runId: null,
+        parentId: null,
+        siblingIds: ['message-1'],
+      },
+    ]);
+    mockedAgentService.getThreadSummary.mockResolvedValue({
+      text: 'The user asked about trains.',
+      throughMessageId: 'message-1',
+    });
+
+    render(AgentChatView);
+
+    await screen.findByText('The user asked about trains.');
+    expect(mockedAgentService.getThreadSummary).toHaveBeenCalledWith(thread.id);
+  });
+
   it('keeps pending scroll callbacks safe after unmount', async () => {
     const callbacks: FrameRequestCallback[] = [];
     vi.stubGlobal(
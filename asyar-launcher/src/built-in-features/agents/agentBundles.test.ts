@@ -0,0 +1,153 @@
+import { beforeEach, describe, expect, it, vi } from 'vitest';
+import type { AgentDef, AgentImportPlan, AgentImportReport } from './types';
+
+vi.mock('@tauri-apps/plugin-dialog', () => ({
+  open: vi.fn(),
+  save: vi.fn(),
+}));
+
+vi.mock('../../lib/ipc/commands', () => ({
+  agentsExportBundle: vi.fn(async () => 1),
+  agentsImportPreview: vi.fn(),
+  agentsImportBundle: vi.fn(),
+}));
+
+vi.mock('../../services/ai/providerRegistry', () => ({
+  providerRegistry: { list: vi.fn(() => []) },
+}));
+
+vi.mock('../../services/feedback/feedbackService.svelte', () => ({
+  feedbackService: { confirmAlert: vi.fn() },
+}));
+
+vi.mock('../../services/log/logService', () => ({
+  logService: { info: vi.fn(), warn: vi.fn() },
+}));
+
+vi.mock('../../services/settings/settingsService.svelte', () => ({
+  settingsService: {
+    getSettings: vi.fn(() => ({ ai: { providers: {}, defaultAgentId: 'default-1' } })),
+  },
+}));
+
+vi.mock('./agentService.svelte', () => ({
+  agentService: { getById: vi.fn() },
+}));
+
+import {
+  bundleFileName,
+  describeImportPlan,
+  exportAgentBundle,
+  importAgentBundle,
+} from './agentBundles';
+import { open, save } from '@tauri-apps/plugin-dialog';
+import * as commands from '../../lib/ipc/commands';
+import { feedbackService } from '../../services/feedback/feedbackService.svelte';
+import { agentService } from './agentService.svelte';
+
+function plan(overrides: Partial<AgentImportPlan> = {}): AgentImportPlan {
+  return {
+    name: 'Digest',
+    providerId: 'openai',
+    modelId: 'gpt-4o',
+    providerMatch: 'matched',
+    toolSelection: [],
+    missingTools: [],
+    missingExtensions: [],
+    missingMcpServers: [],
+    droppedFallbacks: [],
+    agentId: null,
+    ...overrides,
+  };
+}
+
+describe('bundleFileName', () => {
+  it('slugs the agent name', () => {
+    expect(bundleFileName('Daily Digest!')).toBe('daily-digest.agents.json');
+    expect(bundleFileName('***')).toBe('agent.agents.json');
+  });
+});
+
+describe('describeImportPlan', () => {
+  it('is a single line when everything carries over', () => {
+    expect(describeImportPlan(plan())).toBe('• Digest — openai / gpt-4o');
+  });
+
+  it('lists what is remapped or dropped', () => {
+    const text = describeImportPlan(
+      plan({
+        providerMatch: 'defaultAgent',
+        missingExtensions: ['org.example.notes'],
+        missingMcpServers: ['github'],
+        missingTools: ['org.example.notes:lookup', 'mcp:github:search'],
+        droppedFallbacks: ['anthropic'],
+      }),
+    );
+    expect(text).toContain("uses the default agent's model");
+    expect(text).toContain('Missing extensions: org.example.notes');
+    expect(text).toContain('Missing MCP servers: github');
+    expect(text).toContain('Tools dropped: org.example.notes:lookup, mcp:github:search');
+    expect(text).toContain('Fallbacks dropped: anthropic');
+  });
+});
+
+describe('exportAgentBundle', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+    vi.mocked(agentService.getById).mockReturnValue({ id: 'a1', name: 'Digest' } as AgentDef);
+  });
+
+  it('writes the chosen file', async () => {
+    vi.mocked(save).mockResolvedValueOnce('/tmp/digest.agents.json');
+
+    expect(await exportAgentBundle('a1')).toBe(true);
+    expect(save).toHaveBeenCalledWith(
+      expect.objectContaining({ defaultPath: 'digest.agents.json' }),
+    );
+    expect(commands.agentsExportBundle).toHaveBeenCalledWith(['a1'], {}, '/tmp/digest.agents.json');
+  });
+
+  it('does nothing when the save dialog is cancelled', async () => {
+    vi.mocked(save).mockResolvedValueOnce(null);
+
+    expect(await exportAgentBundle('a1')).toBe(false);
+    expect(commands.agentsExportBundle).not.toHaveBeenCalled();
+  });
+});
+
+describe('importAgentBundle', () => {
+  const preview: AgentImportReport = { agents: [plan()] };
+
+  beforeEach(() => {
+    vi.clearAllMocks();
+    vi.mocked(open).mockResolvedValue('/tmp/digest.agents.json');
+    vi.mocked(commands.agentsImportPreview).mockResolvedValue(preview);
+  });
+
+  it('imports after the user confirms the preview', async () => {
+    const imported = { agents: [plan({ agentId: 'new-1' })] };
+    vi.mocked(feedbackService.confirmAlert).mockResolvedValueOnce(true);
+    vi.mocked(commands.agentsImportBundle).mockResolvedValueOnce(imported);
+
+    expect(await importAgentBundle()).toEqual(imported);
+    expect(feedbackService.confirmAlert).toHaveBeenCalledWith(
+      expect.objectContaining({
+        title: 'Import 1 agent?',
+        message: '• Digest — openai / gpt-4o',
+      }),
+    );
+    expect(commands.agentsImportBundle).toHaveBeenCalledWith(
+      '/tmp/digest.agents.json',
+      'default-1',
+      [],
+      {},
+    );
+  });
+
+  it('leaves everything untouched when the user declines', async () => {
+    vi.mocked(feedbackService.confirmAlert).mockResolvedValueOnce(false);
+
+    expect(await importAgentBundle()).toBeNull();
+    expect(commands.agentsImportBundle).not.toHaveBeenCalled();
+  });
+});
@@ -0,0 +1,91 @@
+/**
+ * Agent bundles: export agents to a portable JSON file and import them on
+ * another machine. Rust builds and reads the bundle (provider types only,
+ * never keys or URLs); this module owns the file pickers and the
+ * confirmation that lists what an import will drop or remap.
+ */
+import { open, save } from '@tauri-apps/plugin-dialog';
+import {
+  agentsExportBundle,
+  agentsImportBundle,
+  agentsImportPreview,
+} from '../../lib/ipc/commands';
+import { providerRegistry } from '../../services/ai/providerRegistry';
+import { feedbackService } from '../../services/feedback/feedbackService.svelte';
+import { logService } from '../../services/log/logService';
+import { settingsService } from '../../services/settings/settingsService.svelte';
+import { agentService } from './agentService.svelte';
+import type { AgentImportPlan, AgentImportReport } from './types';
+
+const BUNDLE_FILTERS = [{ name: 'Asyar agent bundle', extensions: ['json'] }];
+
+/** File name suggested for an exported agent: `Daily Digest` → `daily-digest.agents.json`. */
+export function bundleFileName(agentName: string): string {
+  const slug = agentName
+    .toLowerCase()
+    .replace(/[^a-z0-9]+/g, '-')
+    .replace(/^-+|-+$/g, '');
+  return `${slug || 'agent'}.agents.json`;
+}
+
+/** One line per agent, plus an indented line for each thing that won't carry over. */
+export function describeImportPlan(plan: AgentImportPlan): string {
+  const lines = [`• ${plan.name} — ${plan.providerId} / ${plan.modelId}`];
+  if (plan.providerMatch === 'defaultAgent') {
+    lines.push("    Provider not configured here; uses the default agent's model");
+  } else if (plan.providerMatch === 'unavailable') {
+    lines.push('    Provider not configured here; edit the agent before running it');
+  }
+  if (plan.missingExtensions.length > 0) {
+    lines.push(`    Missing extensions: ${plan.missingExtensions.join(', ')}`);
+  }
+  if (plan.missingMcpServers.length > 0) {
+    lines.push(`    Missing MCP servers: ${plan.missingMcpServers.join(', ')}`);
+  }
+  if (plan.missingTools.length > 0) {
+    lines.push(`    Tools dropped: ${plan.missingTools.join(', ')}`);
+  }
+  if (plan.droppedFallbacks.length > 0) {
+    lines.push(`    Fallbacks dropped: ${plan.droppedFallbacks.join(', ')}`);
+  }
+  return lines.join('\n');
+}
+
+export function describeImportReport(report: AgentImportReport): string {
+  return report.agents.map(describeImportPlan).join('\n');
+}
+
+/** Asks where to save, then writes `agentId` as a bundle. Resolves to whether a file was written. */
+export async function exportAgentBundle(agentId: string): Promise<boolean> {
+  const agent = agentService.getById(agentId);
+  if (!agent) return false;
+  const path = await save({
+    defaultPath: bundleFileName(agent.name),
+    filters: BUNDLE_FILTERS,
+  });
+  if (!path) return false;
+  await agentsExportBundle([agentId], settingsService.getSettings().ai.providers, path);
+  logService.info(`[agents] exported '${agent.name}' to ${path}`);
+  return true;
+}
+
+/**
+ * Asks for a bundle, previews the import, and creates the agents once the
+ * user confirms. Resolves to the import report, or `null` when cancelled.
+ */
+export async function importAgentBundle(): Promise<AgentImportReport | null> {
+  const selected = await open({ multiple: false, directory: false, filters: BUNDLE_FILTERS });
+  if (typeof selected !== 'string') return null;
+  const ai = settingsService.getSettings().ai;
+  const providers = providerRegistry.list();
+  const preview = await agentsImportPreview(selected, ai.defaultAgentId, providers, ai.providers);
+  if (preview.agents.length === 0) return null;
+  const count = preview.agents.length;
+  const confirmed = await feedbackService.confirmAlert({
+    title: `Import ${count} agent${count === 1 ? '' : 's'}?`,
+    message: describeImportReport(preview),
+    confirmText: 'Import',
+  });
+  if (!confirmed) return null;
+  return agentsImportBundle(selected, ai.defaultAgentId, providers, ai.providers);
+}
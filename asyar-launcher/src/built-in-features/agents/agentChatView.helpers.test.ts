@@ -19,6 +19,7 @@ const makeThread = (over: Partial<ThreadDef> = {}): ThreadDef => ({
   id: 'thread-1',
   agentId: 'agent-1',
   title: null,
+  currentLeafId: null,
   createdAt: 1000,
   updatedAt: 2000,
   ...over,
@@ -31,6 +32,7 @@ const makeMessage = (over: Partial<MessageDef> = {}): MessageDef => ({
   content: { text: 'Hello' },
   createdAt: 1000,
   runId: null,
+  parentId: null,
   ...over,
 });
 
@@ -254,6 +256,7 @@ describe('resolveThreadId', () => {
     id: 't1',
     agentId: 'agent-a',
     title: null,
+    currentLeafId: null,
     createdAt: 1000,
     updatedAt: 2000,
   };
@@ -261,6 +264,7 @@ describe('resolveThreadId', () => {
     id: 't2',
     agentId: 'agent-a',
     title: null,
+    currentLeafId: null,
     createdAt: 500,
     updatedAt: 1500,
   };
@@ -44,7 +44,7 @@ vi.mock('./agentStreamBridge', () => ({
   }),
 }));
 
-import { runAgent } from './agentLoop';
+import { applySubAgentEvent, runAgent } from './agentLoop';
 import { agentService } from './agentService.svelte';
 import { agentsCancelRun, agentsRunThread } from '../../lib/ipc/commands';
 import { settingsService } from '../../services/settings/settingsService.svelte';
@@ -82,6 +82,9 @@ const agent = {
   outputAction: 'hud' as const,
   cacheResponses: false,
   shortcodeTrigger: ':',
+  monthlyBudgetUsd: null,
+  routing: { fallbacks: [], maxRetries: 0, offlineFallback: null },
+  outputSchema: null,
   createdAt: 1,
   updatedAt: 1,
 };
@@ -130,7 +133,9 @@ describe('runAgent', () => {
       expect(streamMock.options).toBeDefined();
       const emit = streamMock.options?.onEvent;
       emit?.({ type: 'user_message_persisted' });
+      emit?.({ type: 'status', status: 'summarizing' });
       emit?.({ type: 'status', status: 'searching' });
+      emit?.({ type: 'status', status: 'upload: Sending (3/10)' });
       emit?.({ type: 'text_delta', delta: 'Hi', accumulated: 'Hi' });
       emit?.({ type: 'status', status: null });
       emit?.({ type: 'assistant_turn_persisted' });
@@ -155,14 +160,17 @@ describe('runAgent', () => {
       'agent-1',
       'thread-1',
       'hello',
+      [],
       'run-1',
       runConfig,
       expect.any(String),
       expect.anything(),
     );
     expect(onUserMessagePersisted).toHaveBeenCalledOnce();
-    expect(onAssistantStatus).toHaveBeenNthCalledWith(1, 'searching');
-    expect(onAssistantStatus).toHaveBeenNthCalledWith(2, null);
+    expect(onAssistantStatus).toHaveBeenNthCalledWith(1, 'summarizing');
+    expect(onAssistantStatus).toHaveBeenNthCalledWith(2, 'searching');
+    expect(onAssistantStatus).toHaveBeenNthCalledWith(3, 'upload: Sending (3/10)');
+    expect(onAssistantStatus).toHaveBeenNthCalledWith(4, null);
     expect(onAssistantTextDelta).toHaveBeenCalledWith('Hi', 'Hi');
     expect(onAssistantTurnPersisted).toHaveBeenCalledOnce();
     expect(handle.write).toHaveBeenCalledWith('Hi');
@@ -170,6 +178,43 @@ describe('runAgent', () => {
     expect(streamMock.dispose).toHaveBeenCalledOnce();
   });
 
+  it('reports delegated sub-runs without mixing their text into the reply', async () => {
+    const onAssistantTextDelta = vi.fn();
+    const onSubAgentProgress = vi.fn();
+    vi.mocked(agentsRunThread).mockImplementation(async () => {
+      const emit = streamMock.options?.onEvent;
+      emit?.({
+        type: 'sub_agent_started',
+        tool_call_id: 'call-1',
+        agent_id: 'helper',
+        agent_name: 'Helper',
+        task: 'Say hi',
+      });
+      emit?.({
+        type: 'sub_agent_event',
+        tool_call_id: 'call-1',
+        event: { type: 'text_delta', delta: 'Hi', accumulated: 'Hi' },
+      });
+      emit?.({ type: 'sub_agent_finished', tool_call_id: 'call-1', cancelled: false, error: null });
+      emit?.({ type: 'completed' });
+    });
+
+    await runAgent({
+      agentId: 'agent-1',
+      threadId: 'thread-1',
+      userText: 'hello',
+      onAssistantTextDelta,
+      onSubAgentProgress,
+    });
+
+    expect(onAssistantTextDelta).not.toHaveBeenCalled();
+    expect(onSubAgentProgress.mock.calls).toEqual([
+      [[{ toolCallId: 'call-1', agentName: 'Helper', text: '' }]],
+      [[{ toolCallId: 'call-1', agentName: 'Helper', text: 'Hi' }]],
+      [[]],
+    ]);
+  });
+
   it('fails the tracked run and rethrows a Rust command error', async () => {
     vi.mocked(agentsRunThread).mockRejectedValue(new Error('provider unavailable'));
 
@@ -249,6 +294,7 @@ describe('runAgent', () => {
       'agent-1',
       'thread-1',
       'hello',
+      [],
       'run-1',
       runConfig,
       expect.any(String),
@@ -273,6 +319,7 @@ describe('runAgent', () => {
       'agent-1',
       'thread-1',
       'hello',
+      [],
       'run-1',
       expect.objectContaining({
         temperature: 0.2,
@@ -283,3 +330,57 @@ describe('runAgent', () => {
     );
   });
 });
+
+describe('applySubAgentEvent', () => {
+  const started = (id: string, name: string): AgentStreamEvent => ({
+    type: 'sub_agent_started',
+    tool_call_id: id,
+    agent_id: name.toLowerCase(),
+    agent_name: name,
+    task: 'Look it up',
+  });
+  const nested = (id: string, event: AgentStreamEvent): AgentStreamEvent => ({
+    type: 'sub_agent_event',
+    tool_call_id: id,
+    event,
+  });
+
+  it('tracks nested sub-runs by level and clears them as they finish', () => {
+    let runs = applySubAgentEvent([], started('call-1', 'Researcher'));
+    runs = applySubAgentEvent(runs, nested('call-1', started('call-2', 'Summarizer')));
+    runs = applySubAgentEvent(
+      runs,
+      nested('call-1', nested('call-2', { type: 'text_delta', delta: 'Hi', accumulated: 'Hi' })),
+    );
+    expect(runs).toEqual([
+      { toolCallId: 'call-1', agentName: 'Researcher', text: '' },
+      { toolCallId: 'call-2', agentName: 'Summarizer', text: 'Hi' },
+    ]);
+
+    runs = applySubAgentEvent(
+      runs,
+      nested('call-1', {
+        type: 'sub_agent_finished',
+        tool_call_id: 'call-2',
+        cancelled: false,
+        error: null,
+      }),
+    );
+    expect(runs.map((run) => run.agentName)).toEqual(['Researcher']);
+    runs = applySubAgentEvent(runs, {
+      type: 'sub_agent_finished',
+      tool_call_id: 'call-1',
+      cancelled: false,
+      error: null,
+    });
+    expect(runs).toEqual([]);
+  });
+
+  it('ignores text from a sub-run it never saw start', () => {
+    const runs = applySubAgentEvent(
+      [],
+      nested('call-1', { type: 'text_delta', delta: 'Hi', accumulated: 'Hi' }),
+    );
+    expect(runs).toEqual([]);
+  });
+});
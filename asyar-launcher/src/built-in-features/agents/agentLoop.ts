@@ -1,4 +1,4 @@
-import type { AgentStreamEvent } from '../../bindings';
+import type { AgentStreamEvent, ContentPart } from '../../bindings';
 import { extractErrorMessage } from '../../lib/errors';
 import {
   agentsCancelRun,
@@ -7,7 +7,6 @@ import {
   toAgentProviderDescriptors,
 } from '../../lib/ipc/commands';
 import { providerRegistry } from '../../services/ai/providerRegistry';
-import type { ChatStreamStatus } from '../../services/ai/IProviderPlugin';
 import { runService } from '../../services/run/runService.svelte';
 import { settingsService } from '../../services/settings/settingsService.svelte';
 import { agentService } from './agentService.svelte';
@@ -18,11 +17,59 @@ export interface RunAgentInput {
   agentId: string;
   threadId: string;
   userText: string;
+  /** Images and files sent with `userText`; see `agentsAttachmentFromFile`. */
+  attachments?: ContentPart[];
   abortSignal?: AbortSignal;
   onUserMessagePersisted?: () => void;
   onAssistantTextDelta?: (delta: string, accumulated: string) => void;
-  onAssistantStatus?: (status: ChatStreamStatus | null) => void;
+  /** A `ChatStreamStatus`, or free text such as an MCP tool's progress. */
+  onAssistantStatus?: (status: string | null) => void;
   onAssistantTurnPersisted?: () => void;
+  /** Delegated sub-runs in progress, outermost first; empty once they end. */
+  onSubAgentProgress?: (runs: SubAgentProgress[]) => void;
+}
+
+/** A `delegate-to-agent` sub-run as shown while it streams. */
+export interface SubAgentProgress {
+  toolCallId: string;
+  agentName: string;
+  text: string;
+}
+
+/**
+ * Folds a `sub_agent_*` event into `runs`, the chain of sub-runs currently
+ * active. Rust wraps a nested sub-run's events once per level, so the
+ * number of `sub_agent_event` wrappers is the level the event belongs to.
+ */
+export function applySubAgentEvent(
+  runs: SubAgentProgress[],
+  event: AgentStreamEvent,
+): SubAgentProgress[] {
+  let level = 0;
+  let inner = event;
+  let toolCallId = '';
+  while (inner.type === 'sub_agent_event') {
+    level += 1;
+    toolCallId = inner.tool_call_id;
+    inner = inner.event;
+  }
+  switch (inner.type) {
+    case 'sub_agent_started':
+      return [
+        ...runs.slice(0, level),
+        { toolCallId: inner.tool_call_id, agentName: inner.agent_name, text: '' },
+      ];
+    case 'sub_agent_finished':
+      return runs.slice(0, level);
+    case 'text_delta': {
+      const text = inner.accumulated;
+      return runs.map((run, index) =>
+        index === level - 1 && run.toolCallId === toolCallId ? { ...run, text } : run,
+      );
+    }
+    default:
+      return runs;
+  }
 }
 
 async function loadAgent(agentId: string): Promise<AgentDef> {
@@ -37,6 +84,7 @@ function presentEvent(
   input: RunAgentInput,
   event: AgentStreamEvent,
   writeRunOutput: (text: string) => void,
+  subAgents: { current: SubAgentProgress[] },
 ): void {
   switch (event.type) {
     case 'user_message_persisted':
@@ -47,11 +95,17 @@ function presentEvent(
       writeRunOutput(event.delta);
       break;
     case 'status':
-      input.onAssistantStatus?.(event.status === 'searching' ? 'searching' : null);
+      input.onAssistantStatus?.(event.status);
       break;
     case 'assistant_turn_persisted':
       input.onAssistantTurnPersisted?.();
       break;
+    case 'sub_agent_started':
+    case 'sub_agent_event':
+    case 'sub_agent_finished':
+      subAgents.current = applySubAgentEvent(subAgents.current, event);
+      input.onSubAgentProgress?.(subAgents.current);
+      break;
     default:
       break;
   }
@@ -89,6 +143,7 @@ export async function runAgent(input: RunAgentInput): Promise<void> {
   let callerCancelled = false;
   let externallyCancelled = false;
   const streamFailure: { current: Error | null } = { current: null };
+  const subAgents: { current: SubAgentProgress[] } = { current: [] };
 
   const requestRustCancellation = (): void => {
     void agentsCancelRun(streamId).catch(() => undefined);
@@ -111,9 +166,14 @@ export async function runAgent(input: RunAgentInput): Promise<void> {
       onEvent: (event) => {
         if (event.type === 'cancelled') runnerCancelled = true;
         if (event.type === 'error') streamFailure.current = new Error(event.message);
-        presentEvent(input, event, (text) => {
-          void handle.write(text).catch(() => undefined);
-        });
+        presentEvent(
+          input,
+          event,
+          (text) => {
+            void handle.write(text).catch(() => undefined);
+          },
+          subAgents,
+        );
       },
       onBridgeError: (error) => {
         streamFailure.current = error;
@@ -125,6 +185,7 @@ export async function runAgent(input: RunAgentInput): Promise<void> {
       input.agentId,
       input.threadId,
       input.userText,
+      input.attachments ?? [],
       handle.id,
       runConfig,
       streamId,
@@ -9,6 +9,18 @@ import {
   agentsThreadUpdateTitle,
   agentsMessagesList,
   agentsMessageInsert,
+  agentsMessageEdit,
+  agentsThreadSummary,
+  agentsMemoriesList,
+  agentsMemoryDelete,
+  agentsTriggersList,
+  agentsTriggerSave,
+  agentsTriggerDelete,
+  agentsToolPolicyGet,
+  agentsToolPolicySet,
+  agentsToolAuditList,
+  agentsThreadRegenerate,
+  agentsThreadSelectBranch,
   agentsResolveDefault,
   agentsUpsertDefault,
   agentsSeedGrammarFix,
@@ -24,6 +36,13 @@ import type {
   ThreadDef,
   MessageDef,
   MessageInsertInput,
+  PathMessageDef,
+  ThreadSummaryDef,
+  MemoryDef,
+  AgentTriggerDef,
+  AgentTriggerInput,
+  AgentToolPolicy,
+  AgentToolAuditDef,
 } from './types';
 
 // Tracks the most-recently-constructed AgentService instance.
@@ -198,7 +217,8 @@ export class AgentService {
     await agentsThreadUpdateTitle(id, title);
   }
 
-  async listMessages(threadId: string): Promise<MessageDef[]> {
+  /** The thread's selected branch, root first. */
+  async listMessages(threadId: string): Promise<PathMessageDef[]> {
     const result = await agentsMessagesList(threadId);
     if (result === null) throw new Error('Failed to list agent messages');
     return result;
@@ -209,6 +229,83 @@ export class AgentService {
     if (result === null) throw new Error('Failed to insert agent message');
     return result;
   }
+
+  /** Never throws: a missing summary only hides the pinned note. */
+  async getThreadSummary(threadId: string): Promise<ThreadSummaryDef | null> {
+    return agentsThreadSummary(threadId);
+  }
+
+  /** Forks an edited copy of a user message and selects it; re-run the thread afterwards. */
+  async editMessage(threadId: string, messageId: string, text: string): Promise<MessageDef> {
+    const result = await agentsMessageEdit(threadId, messageId, text);
+    if (result === null) throw new Error('Failed to edit agent message');
+    return result;
+  }
+
+  /** Rewinds the selected branch to its last prompt; re-run the thread afterwards. */
+  async rewindForRegenerate(threadId: string): Promise<void> {
+    if (!(await agentsThreadRegenerate(threadId))) {
+      throw new Error('Failed to rewind agent thread');
+    }
+  }
+
+  async selectBranch(threadId: string, messageId: string): Promise<void> {
+    if (!(await agentsThreadSelectBranch(threadId, messageId))) {
+      throw new Error('Failed to switch agent thread branch');
+    }
+  }
+
+  /** Every memory when `agentId` is null, otherwise what that agent can recall. */
+  async listMemories(agentId: string | null = null): Promise<MemoryDef[]> {
+    const result = await agentsMemoriesList(agentId);
+    if (result === null) throw new Error('Failed to list agent memories');
+    return result;
+  }
+
+  async deleteMemory(id: string): Promise<void> {
+    if (!(await agentsMemoryDelete(id))) {
+      throw new Error('Failed to delete agent memory');
+    }
+  }
+
+  /** Every trigger when `agentId` is null, otherwise that agent's. */
+  async listTriggers(agentId: string | null = null): Promise<AgentTriggerDef[]> {
+    const result = await agentsTriggersList(agentId);
+    if (result === null) throw new Error('Failed to list agent triggers');
+    return result;
+  }
+
+  async saveTrigger(input: AgentTriggerInput): Promise<AgentTriggerDef> {
+    const trigger = await agentsTriggerSave(input);
+    if (!trigger) throw new Error('Failed to save agent trigger');
+    return trigger;
+  }
+
+  async deleteTrigger(id: string): Promise<void> {
+    if (!(await agentsTriggerDelete(id))) {
+      throw new Error('Failed to delete agent trigger');
+    }
+  }
+
+  /** The agent's shell/file policy; the default when it never set one. */
+  async getToolPolicy(agentId: string): Promise<AgentToolPolicy> {
+    const policy = await agentsToolPolicyGet(agentId);
+    if (!policy) throw new Error('Failed to load agent tool policy');
+    return policy;
+  }
+
+  async setToolPolicy(agentId: string, policy: AgentToolPolicy): Promise<AgentToolPolicy> {
+    const saved = await agentsToolPolicySet(agentId, policy);
+    if (!saved) throw new Error('Failed to save agent tool policy');
+    return saved;
+  }
+
+  /** Newest shell/file tool calls first — every agent's when `agentId` is null. */
+  async listToolAudit(agentId: string | null = null, limit?: number): Promise<AgentToolAuditDef[]> {
+    const result = await agentsToolAuditList(agentId, limit);
+    if (result === null) throw new Error('Failed to list agent tool audit');
+    return result;
+  }
 }
 
 export const agentService = new AgentService();
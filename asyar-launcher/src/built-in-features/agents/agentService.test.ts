@@ -11,6 +11,18 @@ vi.mock('../../lib/ipc/commands', () => ({
   agentsThreadsList: vi.fn(),
   agentsMessageInsert: vi.fn(),
   agentsMessagesList: vi.fn(),
+  agentsMessageEdit: vi.fn(),
+  agentsThreadSummary: vi.fn(),
+  agentsMemoriesList: vi.fn(),
+  agentsMemoryDelete: vi.fn(),
+  agentsTriggersList: vi.fn(),
+  agentsTriggerSave: vi.fn(),
+  agentsTriggerDelete: vi.fn(),
+  agentsToolPolicyGet: vi.fn(),
+  agentsToolPolicySet: vi.fn(),
+  agentsToolAuditList: vi.fn(),
+  agentsThreadRegenerate: vi.fn(),
+  agentsThreadSelectBranch: vi.fn(),
   agentsResolveDefault: vi.fn(),
   agentsUpsertDefault: vi.fn(),
   agentsSeedGrammarFix: vi.fn(),
@@ -68,6 +80,7 @@ const makeThread = (over: Record<string, unknown> = {}) => ({
   id: 't1',
   agentId: 'a1',
   title: 'My Thread',
+  currentLeafId: null,
   createdAt: 2000,
   updatedAt: 2000,
   ...over,
@@ -80,6 +93,7 @@ const makeMessage = (over: Record<string, unknown> = {}) => ({
   content: { text: 'hi' },
   createdAt: 3000,
   runId: null,
+  parentId: null,
   ...over,
 });
 
@@ -215,6 +229,82 @@ describe('AgentService', () => {
     expect(result).toEqual(msg);
   });
 
+  it('editMessage_forwards_args_to_agentsMessageEdit', async () => {
+    const msg = makeMessage({ id: 'm2', content: { text: 'edited' } });
+    vi.mocked(commands.agentsMessageEdit).mockResolvedValueOnce(msg as never);
+
+    const result = await service.editMessage('t1', 'm1', 'edited');
+
+    expect(commands.agentsMessageEdit).toHaveBeenCalledWith('t1', 'm1', 'edited');
+    expect(result).toEqual(msg);
+  });
+
+  it('selectBranch_throws_when_the_switch_fails', async () => {
+    vi.mocked(commands.agentsThreadSelectBranch).mockResolvedValueOnce(false);
+
+    await expect(service.selectBranch('t1', 'm9')).rejects.toThrow();
+  });
+
+  it('listMemories_scopes_to_the_given_agent', async () => {
+    vi.mocked(commands.agentsMemoriesList).mockResolvedValueOnce([] as never);
+
+    await service.listMemories('a1');
+
+    expect(commands.agentsMemoriesList).toHaveBeenCalledWith('a1');
+  });
+
+  it('deleteMemory_throws_when_the_delete_fails', async () => {
+    vi.mocked(commands.agentsMemoryDelete).mockResolvedValueOnce(false);
+
+    await expect(service.deleteMemory('m1')).rejects.toThrow();
+  });
+
+  it('saveTrigger_passes_the_input_through_and_throws_on_failure', async () => {
+    const input = {
+      agentId: 'a1',
+      spec: { type: 'schedule' as const, weekdays: [1, 2, 3, 4, 5], time: '09:00' },
+      output: 'note' as const,
+    };
+    vi.mocked(commands.agentsTriggerSave).mockResolvedValueOnce({ id: 't1' } as never);
+
+    await expect(service.saveTrigger(input)).resolves.toEqual({ id: 't1' });
+    expect(commands.agentsTriggerSave).toHaveBeenCalledWith(input);
+
+    vi.mocked(commands.agentsTriggerSave).mockResolvedValueOnce(null);
+    await expect(service.saveTrigger(input)).rejects.toThrow();
+  });
+
+  it('deleteTrigger_throws_when_the_delete_fails', async () => {
+    vi.mocked(commands.agentsTriggerDelete).mockResolvedValueOnce(false);
+
+    await expect(service.deleteTrigger('t1')).rejects.toThrow();
+  });
+
+  it('setToolPolicy_passes_the_policy_through_and_throws_on_failure', async () => {
+    const policy = {
+      allowedCommands: ['git'],
+      workingDirs: ['~/Projects'],
+      readPaths: [],
+      writePaths: ['~/Projects/**'],
+      maxOutputBytes: 65536,
+      timeoutSecs: 60,
+      writeMode: 'confirm' as const,
+    };
+    vi.mocked(commands.agentsToolPolicySet).mockResolvedValueOnce(policy);
+
+    await expect(service.setToolPolicy('a1', policy)).resolves.toEqual(policy);
+    expect(commands.agentsToolPolicySet).toHaveBeenCalledWith('a1', policy);
+
+    vi.mocked(commands.agentsToolPolicySet).mockResolvedValueOnce(null);
+    await expect(service.setToolPolicy('a1', policy)).rejects.toThrow();
+  });
+
+  it('listToolAudit_throws_when_the_list_fails', async () => {
+    vi.mocked(commands.agentsToolAuditList).mockResolvedValueOnce(null);
+
+    await expect(service.listToolAudit('a1')).rejects.toThrow();
+  });
+
   it('create_reports_diagnostic_and_rethrows_on_failure', async () => {
     vi.mocked(commands.agentsList).mockResolvedValueOnce([] as never);
     await service.init();
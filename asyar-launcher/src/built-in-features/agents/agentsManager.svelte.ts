@@ -4,9 +4,14 @@ import { logService } from '../../services/log/logService';
 import type { DynamicCommandRegistration } from 'asyar-sdk/contracts';
 import type { AgentService } from './agentService.svelte';
 import { agentService as defaultAgentService } from './agentService.svelte';
-import type { ChatStreamStatus } from '../../services/ai/IProviderPlugin';
+import type { SubAgentProgress } from './agentLoop';
+import type { AgentTriggerFire, AgentWriteConfirmRequest } from './types';
+import type { ContentPart } from '../../bindings';
 
 const AGENTS_EXTENSION_ID = 'agents';
+/** Emitted by Rust when a scheduled or event trigger fires. */
+const AGENT_TRIGGER_FIRE_EVENT = 'asyar:agents:trigger';
+const AGENT_WRITE_CONFIRM_EVENT = 'asyar:agents:confirm-write';
 
 export class AgentsManager {
   currentAgentId = $state<string | null>(null);
@@ -22,7 +27,9 @@ export class AgentsManager {
    * when the turn is persisted (real message takes over).
    */
   streamingText = $state<string>('');
-  streamingStatus = $state<ChatStreamStatus | null>(null);
+  streamingStatus = $state<string | null>(null);
+  /** Sub-agents the streaming turn delegated to, outermost first. */
+  subAgentRuns = $state<SubAgentProgress[]>([]);
   /** True while a `runAgent` invocation is in-flight for the active thread. */
   sending = $state<boolean>(false);
   /**
@@ -31,9 +38,26 @@ export class AgentsManager {
    * via the abortSignal arg.
    */
   activeAbortController = $state<AbortController | null>(null);
+  /**
+   * User message being rewritten. While set, launcher-bar Enter forks an
+   * edited copy of it into a new branch instead of appending a message.
+   */
+  editingMessageId = $state<string | null>(null);
+  /**
+   * Bumped when the thread's selected branch changes without a send (the
+   * branch switcher), so the chat view knows to reload the path.
+   */
+  branchVersion = $state(0);
+  /**
+   * Attachments queued for the next message sent from the chat view — an
+   * MCP resource picked in root search lands here until the user sends.
+   */
+  pendingAttachments = $state<ContentPart[]>([]);
   private service: AgentService;
   private started = false;
   private agentsChangedUnlisten: UnlistenFn | null = null;
+  private triggerFireUnlisten: UnlistenFn | null = null;
+  private writeConfirmUnlisten: UnlistenFn | null = null;
 
   constructor(service?: AgentService) {
     this.service = service ?? defaultAgentService;
@@ -91,6 +115,42 @@ export class AgentsManager {
     } catch (err) {
       logService.warn(`[agents] failed to subscribe to agents:changed: ${err}`);
     }
+
+    // Background triggers: Rust owns the schedule/file/clipboard evaluation
+    // and persists each fire; running the agent needs the provider settings
+    // that live here. Loaded lazily so the run machinery stays out of the
+    // manager's import graph.
+    try {
+      this.triggerFireUnlisten = await listen<AgentTriggerFire>(
+        AGENT_TRIGGER_FIRE_EVENT,
+        (event) => {
+          void import('./triggerDispatch')
+            .then(({ runTriggeredAgent }) => runTriggeredAgent(event.payload))
+            .catch((err) => {
+              logService.warn(`[agents] trigger run failed: ${err}`);
+            });
+        },
+      );
+    } catch (err) {
+      logService.warn(`[agents] failed to subscribe to ${AGENT_TRIGGER_FIRE_EVENT}: ${err}`);
+    }
+
+    // Confirm-mode writes: the `fs-write` call waits in Rust until the
+    // user answers the dialog (or it times out and counts as declined).
+    try {
+      this.writeConfirmUnlisten = await listen<AgentWriteConfirmRequest>(
+        AGENT_WRITE_CONFIRM_EVENT,
+        (event) => {
+          void import('./writeConfirm')
+            .then(({ confirmAgentWrite }) => confirmAgentWrite(event.payload))
+            .catch((err) => {
+              logService.warn(`[agents] write confirmation failed: ${err}`);
+            });
+        },
+      );
+    } catch (err) {
+      logService.warn(`[agents] failed to subscribe to ${AGENT_WRITE_CONFIRM_EVENT}: ${err}`);
+    }
   }
 
   async stop(): Promise<void> {
@@ -100,6 +160,14 @@ export class AgentsManager {
       this.agentsChangedUnlisten();
       this.agentsChangedUnlisten = null;
     }
+    if (this.triggerFireUnlisten) {
+      this.triggerFireUnlisten();
+      this.triggerFireUnlisten = null;
+    }
+    if (this.writeConfirmUnlisten) {
+      this.writeConfirmUnlisten();
+      this.writeConfirmUnlisten = null;
+    }
     await replaceDynamicCommandsBuiltin(AGENTS_EXTENSION_ID, []);
   }
 
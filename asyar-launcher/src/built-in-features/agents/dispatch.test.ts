@@ -21,6 +21,18 @@ vi.mock('../../lib/ipc/commands', () => ({
   agentsThreadCreate: vi.fn(),
   agentsThreadDelete: vi.fn(),
   agentsMessagesList: vi.fn(),
+  agentsMessageEdit: vi.fn(),
+  agentsThreadSummary: vi.fn(),
+  agentsMemoriesList: vi.fn(),
+  agentsMemoryDelete: vi.fn(),
+  agentsTriggersList: vi.fn(),
+  agentsTriggerSave: vi.fn(),
+  agentsTriggerDelete: vi.fn(),
+  agentsToolPolicyGet: vi.fn(),
+  agentsToolPolicySet: vi.fn(),
+  agentsToolAuditList: vi.fn(),
+  agentsThreadRegenerate: vi.fn(),
+  agentsThreadSelectBranch: vi.fn(),
   agentsMessageInsert: vi.fn(),
   agentsBackfillThreadTitles: vi.fn().mockResolvedValue(0),
   agentsGet: vi.fn(),
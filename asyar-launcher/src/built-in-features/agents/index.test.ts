@@ -49,6 +49,8 @@ vi.mock('./agentsManager.svelte', () => ({
     currentThreadId: null,
     sending: false,
     streamingText: '',
+    editingMessageId: null,
+    pendingAttachments: [],
     start: vi.fn().mockResolvedValue(undefined),
     stop: vi.fn().mockResolvedValue(undefined),
     refresh: vi.fn().mockResolvedValue(undefined),
@@ -62,6 +64,7 @@ vi.mock('./agentService.svelte', () => ({
     createThread: vi.fn(),
     deleteThread: vi.fn(),
     listThreads: vi.fn().mockResolvedValue([]),
+    editMessage: vi.fn(),
   },
 }));
 
@@ -77,6 +80,11 @@ vi.mock('./agentChatView.helpers', () => ({
   ensureThread: vi.fn(),
 }));
 
+vi.mock('./agentBundles', () => ({
+  exportAgentBundle: vi.fn(),
+  importAgentBundle: vi.fn(),
+}));
+
 vi.mock('../../services/extension/builtinDynamicDispatchers', () => ({
   registerBuiltinDynamicDispatcher: vi.fn(),
 }));
@@ -131,6 +139,8 @@ describe('AgentsExtension', () => {
     agentsManager.sending = false;
     agentsManager.streamingText = '';
     agentsManager.activeAbortController = null;
+    agentsManager.editingMessageId = null;
+    agentsManager.pendingAttachments = [];
     mockExtensionManager = {
       navigateToView: vi.fn(),
       setActiveViewSubtitle: vi.fn(),
@@ -146,6 +156,7 @@ describe('AgentsExtension', () => {
         id: 'fresh-thread',
         agentId: 'agent-1',
         title: '',
+        currentLeafId: null,
         createdAt: 1,
         updatedAt: 1,
       });
@@ -162,6 +173,62 @@ describe('AgentsExtension', () => {
         }),
       );
     });
+
+    it('sends queued attachments with the next message and clears them', async () => {
+      const attachment = {
+        type: 'file' as const,
+        name: 'today.md',
+        mediaType: 'text/markdown',
+        text: 'notes',
+        data: null,
+      };
+      agentsManager.currentAgentId = 'agent-1';
+      agentsManager.currentThreadId = 'thread-1';
+      agentsManager.pendingAttachments = [attachment];
+      vi.mocked(agentService.listThreads).mockResolvedValue([
+        {
+          id: 'thread-1',
+          agentId: 'agent-1',
+          title: '',
+          currentLeafId: null,
+          createdAt: 1,
+          updatedAt: 1,
+        },
+      ] as never);
+      vi.mocked(runAgent).mockResolvedValue(undefined);
+
+      await agentsExtension.onViewSubmit?.('summarise this');
+
+      expect(runAgent).toHaveBeenCalledWith(
+        expect.objectContaining({ userText: 'summarise this', attachments: [attachment] }),
+      );
+      expect(agentsManager.pendingAttachments).toEqual([]);
+    });
+
+    it('forks the message being edited and re-runs without new text', async () => {
+      agentsManager.currentAgentId = 'agent-1';
+      agentsManager.currentThreadId = 'thread-1';
+      agentsManager.editingMessageId = 'message-1';
+      vi.mocked(agentService.listThreads).mockResolvedValue([
+        {
+          id: 'thread-1',
+          agentId: 'agent-1',
+          title: 'Thread',
+          currentLeafId: 'message-2',
+          createdAt: 1,
+          updatedAt: 1,
+        },
+      ] as never);
+      vi.mocked(runAgent).mockResolvedValue(undefined);
+
+      await agentsExtension.onViewSubmit?.('hello again');
+
+      expect(agentService.editMessage).toHaveBeenCalledWith('thread-1', 'message-1', 'hello again');
+      expect(agentsManager.editingMessageId).toBeNull();
+      expect(runAgent).toHaveBeenCalledWith(
+        expect.objectContaining({ threadId: 'thread-1', userText: '' }),
+      );
+    });
   });
 
   describe('initialize', () => {
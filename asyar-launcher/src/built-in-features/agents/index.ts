@@ -5,6 +5,7 @@ import { agentsManager } from './agentsManager.svelte';
 import { agentService } from './agentService.svelte';
 import { runAgent } from './agentLoop';
 import { ensureThread } from './agentChatView.helpers';
+import { exportAgentBundle, importAgentBundle } from './agentBundles';
 import { actionService } from '../../services/action/actionService.svelte';
 import { logService } from '../../services/log/logService';
 import { contextModeService } from '../../services/context/contextModeService.svelte';
@@ -24,9 +25,14 @@ registerBuiltinDynamicDispatcher('agents', dispatchAgentCommand);
 const ACTION_NEW_AGENT = 'agents:new-agent';
 const ACTION_EDIT_AGENT = 'agents:edit-agent';
 const ACTION_DELETE_AGENT = 'agents:delete-agent';
+const ACTION_EXPORT_AGENT = 'agents:export-agent';
+const ACTION_IMPORT_AGENTS = 'agents:import-agents';
 const ACTION_NEW_THREAD = 'agents:new-thread';
 const ACTION_DELETE_THREAD = 'agents:delete-thread';
 const ACTION_CANCEL_SEND = 'agents:cancel-send';
+const ACTION_REGENERATE = 'agents:regenerate';
+const ACTION_EDIT_LAST_MESSAGE = 'agents:edit-last-message';
+const ACTION_CANCEL_EDIT = 'agents:cancel-edit';
 
 class AgentsExtension implements Extension {
   private extensionManager?: IExtensionManager;
@@ -128,12 +134,32 @@ class AgentsExtension implements Extension {
     }
   }
 
+  private async runExportAgent(): Promise<void> {
+    const agentId = agentsManager.currentAgentId;
+    if (!agentId) return;
+    try {
+      await exportAgentBundle(agentId);
+    } catch (err) {
+      logService.warn(`[agents] export-agent action failed: ${err}`);
+    }
+  }
+
+  private async runImportAgents(): Promise<void> {
+    try {
+      const report = await importAgentBundle();
+      if (report) await agentsManager.refresh();
+    } catch (err) {
+      logService.warn(`[agents] import-agents action failed: ${err}`);
+    }
+  }
+
   private async runNewThread(): Promise<void> {
     const agentId = agentsManager.currentAgentId;
     if (!agentId) return;
     try {
       const thread = await agentService.createThread(agentId, '');
       agentsManager.currentThreadId = thread.id;
+      agentsManager.editingMessageId = null;
     } catch (err) {
       logService.warn(`[agents] new-thread action failed: ${err}`);
     }
@@ -187,12 +213,34 @@ class AgentsExtension implements Extension {
       context: ActionContext.EXTENSION_VIEW,
       execute: async () => this.runDeleteAgent(),
     });
+    actionService.registerAction({
+      id: ACTION_EXPORT_AGENT,
+      label: 'Export Agent',
+      icon: '📤',
+      description: 'Save the selected agent as a portable bundle',
+      category: 'Agents',
+      extensionId: 'agents',
+      context: ActionContext.EXTENSION_VIEW,
+      execute: async () => this.runExportAgent(),
+    });
+    actionService.registerAction({
+      id: ACTION_IMPORT_AGENTS,
+      label: 'Import Agents',
+      icon: '📥',
+      description: 'Create agents from a bundle file',
+      category: 'Agents',
+      extensionId: 'agents',
+      context: ActionContext.EXTENSION_VIEW,
+      execute: async () => this.runImportAgents(),
+    });
   }
 
   private unregisterListViewActions(): void {
     actionService.unregisterAction(ACTION_NEW_AGENT);
     actionService.unregisterAction(ACTION_EDIT_AGENT);
     actionService.unregisterAction(ACTION_DELETE_AGENT);
+    actionService.unregisterAction(ACTION_EXPORT_AGENT);
+    actionService.unregisterAction(ACTION_IMPORT_AGENTS);
   }
 
   private registerChatViewActions(): void {
@@ -227,12 +275,78 @@ class AgentsExtension implements Extension {
       visible: () => agentsManager.sending,
       execute: async () => this.runCancelSend(),
     });
+    actionService.registerAction({
+      id: ACTION_REGENERATE,
+      label: 'Regenerate Response',
+      icon: '🔄',
+      description: 'Answer the last message again, keeping the old answer as a branch',
+      category: 'Agents',
+      extensionId: 'agents',
+      context: ActionContext.EXTENSION_VIEW,
+      visible: () => !agentsManager.sending && agentsManager.currentThreadId !== null,
+      execute: async () => this.runRegenerate(),
+    });
+    actionService.registerAction({
+      id: ACTION_EDIT_LAST_MESSAGE,
+      label: 'Edit Last Message',
+      icon: '✏️',
+      description: 'Rewrite your last message in the search bar and re-run from it',
+      category: 'Agents',
+      extensionId: 'agents',
+      context: ActionContext.EXTENSION_VIEW,
+      visible: () =>
+        !agentsManager.sending &&
+        agentsManager.currentThreadId !== null &&
+        agentsManager.editingMessageId === null,
+      execute: async () => this.runEditLastMessage(),
+    });
+    actionService.registerAction({
+      id: ACTION_CANCEL_EDIT,
+      label: 'Cancel Edit',
+      icon: '↩️',
+      description: 'Stop editing and send the next message as usual',
+      category: 'Agents',
+      extensionId: 'agents',
+      context: ActionContext.EXTENSION_VIEW,
+      visible: () => agentsManager.editingMessageId !== null,
+      execute: async () => {
+        agentsManager.editingMessageId = null;
+      },
+    });
   }
 
   private unregisterChatViewActions(): void {
     actionService.unregisterAction(ACTION_NEW_THREAD);
     actionService.unregisterAction(ACTION_DELETE_THREAD);
     actionService.unregisterAction(ACTION_CANCEL_SEND);
+    actionService.unregisterAction(ACTION_REGENERATE);
+    actionService.unregisterAction(ACTION_EDIT_LAST_MESSAGE);
+    actionService.unregisterAction(ACTION_CANCEL_EDIT);
+  }
+
+  private async runRegenerate(): Promise<void> {
+    const agentId = agentsManager.currentAgentId;
+    const threadId = agentsManager.currentThreadId;
+    if (!agentId || !threadId || agentsManager.sending) return;
+    try {
+      await agentService.rewindForRegenerate(threadId);
+    } catch (err) {
+      logService.warn(`[agents] regenerate failed: ${err}`);
+      return;
+    }
+    await this.submitToThread(agentId, threadId, '');
+  }
+
+  private async runEditLastMessage(): Promise<void> {
+    const threadId = agentsManager.currentThreadId;
+    if (!threadId) return;
+    try {
+      const path = await agentService.listMessages(threadId);
+      const lastPrompt = path.findLast((message) => message.role === 'user');
+      agentsManager.editingMessageId = lastPrompt?.id ?? null;
+    } catch (err) {
+      logService.warn(`[agents] edit last message failed: ${err}`);
+    }
   }
 
   async executeCommand(commandId: string, args?: Record<string, unknown>): Promise<unknown> {
@@ -257,7 +371,8 @@ class AgentsExtension implements Extension {
    * Called when the user types in the launcher search bar while the
    * AgentChatView is active and presses Enter. Routes the query as a new
    * message to the active agent + thread. Mirrors ai-chat's flow so the
-   * search bar is the omnipresent chat input.
+   * search bar is the omnipresent chat input. While a message is being
+   * edited, the query replaces it on a new branch instead.
    */
   async onViewSubmit(query: string): Promise<void> {
     const text = query.trim();
@@ -280,22 +395,38 @@ class AgentsExtension implements Extension {
       return;
     }
 
-    await this.submitToThread(agentId, thread, text);
+    const editingMessageId = agentsManager.editingMessageId;
+    if (editingMessageId) {
+      agentsManager.editingMessageId = null;
+      try {
+        await agentService.editMessage(thread.id, editingMessageId, text);
+      } catch (err) {
+        logService.warn(`[agents] editMessage failed: ${err}`);
+        return;
+      }
+      // The edited prompt is already the branch's leaf; run without new text.
+      await this.submitToThread(agentId, thread.id, '');
+      return;
+    }
+
+    await this.submitToThread(agentId, thread.id, text);
   }
 
-  private async submitToThread(agentId: string, thread: ThreadDef, text: string): Promise<void> {
-    const threadId = thread.id;
+  private async submitToThread(agentId: string, threadId: string, text: string): Promise<void> {
     const controller = new AbortController();
     agentsManager.activeAbortController = controller;
     agentsManager.sending = true;
     agentsManager.streamingText = '';
     agentsManager.streamingStatus = null;
+    const attachments = agentsManager.pendingAttachments;
+    agentsManager.pendingAttachments = [];
 
     try {
       await runAgent({
         agentId,
         threadId,
         userText: text,
+        attachments,
         abortSignal: controller.signal,
         onUserMessagePersisted: () => {
           // Chat view watches `agentsManager.sending` + listens for refresh
@@ -314,6 +445,9 @@ class AgentsExtension implements Extension {
           agentsManager.streamingText = '';
           agentsManager.streamingStatus = null;
         },
+        onSubAgentProgress: (runs) => {
+          agentsManager.subAgentRuns = runs;
+        },
       });
     } catch (err) {
       logService.warn(`[agents] runAgent failed: ${err}`);
@@ -322,6 +456,7 @@ class AgentsExtension implements Extension {
       agentsManager.activeAbortController = null;
       agentsManager.streamingText = '';
       agentsManager.streamingStatus = null;
+      agentsManager.subAgentRuns = [];
     }
   }
 }
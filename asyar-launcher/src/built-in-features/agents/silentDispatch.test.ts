@@ -1,4 +1,5 @@
 import { afterEach, beforeEach, describe, expect, it, vi } from 'vitest';
+import type { AgentStreamEvent } from '../../bindings';
 import type { AgentDef } from './types';
 
 vi.mock('../../services/settings/settingsService.svelte', () => ({
@@ -59,7 +60,13 @@ vi.mock('../../services/log/logService', () => ({
 
 const bridgeMock = vi.hoisted(() => ({
   options: undefined as
-    { streamId: string; agentId: string; onBridgeError?: (error: Error) => void } | undefined,
+    | {
+        streamId: string;
+        agentId: string;
+        onEvent?: (event: AgentStreamEvent) => void;
+        onBridgeError?: (error: Error) => void;
+      }
+    | undefined,
   dispose: vi.fn(),
 }));
 
@@ -104,6 +111,9 @@ function makeAgent(overrides: Partial<AgentDef> = {}): AgentDef {
     outputAction: 'replaceSelection',
     cacheResponses: false,
     shortcodeTrigger: ':',
+    monthlyBudgetUsd: null,
+    routing: { fallbacks: [], maxRetries: 0, offlineFallback: null },
+    outputSchema: null,
     createdAt: 1,
     updatedAt: 1,
     ...overrides,
@@ -135,7 +145,10 @@ describe('dispatchSilentAgentCommand', () => {
       },
     } as never);
     vi.mocked(providerRegistry.list).mockReturnValue(providers as never);
-    vi.mocked(commands.agentsRunSilent).mockResolvedValue('Corrected text');
+    vi.mocked(commands.agentsRunSilent).mockResolvedValue({
+      text: 'Corrected text',
+      structured: null,
+    });
     vi.mocked(commands.agentsCancelRun).mockResolvedValue(undefined);
     vi.mocked(readText).mockResolvedValue('previous clipboard');
     vi.mocked(writeText).mockResolvedValue(undefined);
@@ -224,7 +237,10 @@ describe('dispatchSilentAgentCommand', () => {
       },
     } as never);
     vi.mocked(providerRegistry.list).mockReturnValue(providers as never);
-    vi.mocked(commands.agentsRunSilent).mockResolvedValue('first\nfinal line\n');
+    vi.mocked(commands.agentsRunSilent).mockResolvedValue({
+      text: 'first\nfinal line\n',
+      structured: null,
+    });
     await dispatchSilentAgentCommand({ agentId: 'agent-1', userText: 'hello' });
     expect(spinnerMock.replace).toHaveBeenCalledWith('final line', { spinning: false });
   });
@@ -236,7 +252,7 @@ describe('dispatchSilentAgentCommand', () => {
       outputAction: 'paste',
     });
     vi.mocked(agentService.getById).mockReturnValue(emojiAgent);
-    vi.mocked(commands.agentsRunSilent).mockResolvedValue('🎉');
+    vi.mocked(commands.agentsRunSilent).mockResolvedValue({ text: '🎉', structured: null });
 
     await dispatchSilentAgentCommand({ agentId: 'emoji-agent-uuid', userText: 'party' });
 
@@ -314,7 +330,7 @@ describe('dispatchSilentAgentCommand', () => {
   it('reports a bridge resume failure instead of treating cancellation as success', async () => {
     vi.mocked(commands.agentsRunSilent).mockImplementation(async () => {
       bridgeMock.options?.onBridgeError?.(new Error('resume failed'));
-      return '';
+      return { text: '', structured: null };
     });
 
     await dispatchSilentAgentCommand({ agentId: 'agent-1', userText: 'hello' });
@@ -334,4 +350,24 @@ describe('dispatchSilentAgentCommand', () => {
       expect.objectContaining({ developerDetail: 'Error: cache failed' }),
     );
   });
+
+  it('hands the structured answer to onStructuredOutput', async () => {
+    const onStructuredOutput = vi.fn();
+    vi.mocked(commands.agentsRunSilent).mockResolvedValue({
+      text: '{\n  "name": "Ada"\n}',
+      structured: { name: 'Ada' },
+    });
+
+    await dispatchSilentAgentCommand({ agentId: 'agent-1', userText: 'hello', onStructuredOutput });
+
+    expect(onStructuredOutput).toHaveBeenCalledWith({ name: 'Ada' });
+  });
+
+  it('skips onStructuredOutput for agents without an output schema', async () => {
+    const onStructuredOutput = vi.fn();
+
+    await dispatchSilentAgentCommand({ agentId: 'agent-1', userText: 'hello', onStructuredOutput });
+
+    expect(onStructuredOutput).not.toHaveBeenCalled();
+  });
 });
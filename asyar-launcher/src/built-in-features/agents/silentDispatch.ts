@@ -34,6 +34,8 @@ interface SilentDispatchOptions {
   rawInputLength?: number;
   abortSignal?: AbortSignal;
   onFinalText?: (text: string) => void | Promise<void>;
+  /** Receives the parsed answer of an agent with an output schema. */
+  onStructuredOutput?: (value: unknown) => void | Promise<void>;
 }
 
 export interface SilentDispatchInput extends SilentDispatchOptions {
@@ -127,7 +129,13 @@ async function runSilentAgentCommand(input: SilentDispatchInput): Promise<void>
       return;
     }
 
-    const result = await agentsRunSilent(agent.id, userText, runConfig, streamId, stream.channel);
+    const { text: result, structured } = await agentsRunSilent(
+      agent.id,
+      userText,
+      runConfig,
+      streamId,
+      stream.channel,
+    );
     if (bridgeError) throw bridgeError;
     if (input.abortSignal?.aborted) {
       await spinner.dismiss();
@@ -158,6 +166,7 @@ async function runSilentAgentCommand(input: SilentDispatchInput): Promise<void>
     );
     spinner = null;
     await callFinalText(input, result);
+    if (structured !== null) await input.onStructuredOutput?.(structured);
   } catch (cause) {
     if (input.abortSignal?.aborted) {
       if (spinner) await spinner.dismiss();
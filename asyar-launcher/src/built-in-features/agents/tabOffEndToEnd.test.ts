@@ -118,6 +118,7 @@ const oldThread = {
   id: 'old-thread',
   agentId: 'agent-a',
   title: 'Old thread',
+  currentLeafId: null,
   createdAt: 1000,
   updatedAt: 2000,
 };
@@ -126,6 +127,7 @@ const newThread = {
   id: 'new-thread',
   agentId: 'agent-a',
   title: '',
+  currentLeafId: null,
   createdAt: 3000,
   updatedAt: 3000,
 };
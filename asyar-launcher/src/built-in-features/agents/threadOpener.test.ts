@@ -41,6 +41,7 @@ const thread1 = {
   id: 'thread-1',
   agentId: 'agent-a',
   title: 'First',
+  currentLeafId: null,
   createdAt: 1000,
   updatedAt: 2000,
 };
@@ -48,6 +49,7 @@ const thread2 = {
   id: 'thread-2',
   agentId: 'agent-a',
   title: 'Second',
+  currentLeafId: null,
   createdAt: 500,
   updatedAt: 1500,
 };
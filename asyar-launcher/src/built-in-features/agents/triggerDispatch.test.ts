@@ -0,0 +1,186 @@
+import { beforeEach, describe, expect, it, vi } from 'vitest';
+import type { AgentStreamEvent, SilentRunOutput } from '../../bindings';
+import type { AgentDef, AgentTriggerFire } from './types';
+
+vi.mock('../../services/settings/settingsService.svelte', () => ({
+  settingsService: { getSettings: vi.fn() },
+}));
+
+vi.mock('./agentService.svelte', () => ({
+  agentService: { getById: vi.fn() },
+}));
+
+vi.mock('../../services/ai/providerRegistry', () => ({
+  providerRegistry: { list: vi.fn(() => []) },
+}));
+
+vi.mock('../../lib/ipc/commands', () => ({
+  agentsGet: vi.fn(),
+  agentsRunSilent: vi.fn(),
+  agentsCancelRun: vi.fn(async () => undefined),
+  noteUpsert: vi.fn(async () => undefined),
+  toAgentProviderDescriptors: vi.fn((providers) => providers),
+}));
+
+vi.mock('../../services/feedback/feedbackService.svelte', () => ({
+  feedbackService: {
+    showHUD: vi.fn(async () => undefined),
+    sendBackgroundForSource: vi.fn(async () => 'background-1'),
+  },
+}));
+
+vi.mock('../../services/log/logService', () => ({
+  logService: { info: vi.fn(), warn: vi.fn() },
+}));
+
+const runMock = vi.hoisted(() => ({
+  handle: {
+    id: 'run-1',
+    write: vi.fn(async () => undefined),
+    done: vi.fn(async () => undefined),
+    fail: vi.fn(async () => undefined),
+    cancel: vi.fn(async () => undefined),
+    onCancel: vi.fn(() => () => undefined),
+  },
+}));
+
+vi.mock('../../services/run/runService.svelte', () => ({
+  runService: { startLocal: vi.fn(async () => runMock.handle) },
+}));
+
+const bridgeMock = vi.hoisted(() => ({
+  onEvent: undefined as ((event: AgentStreamEvent) => void) | undefined,
+}));
+
+vi.mock('./agentStreamBridge', () => ({
+  createAgentStreamChannel: vi.fn((options) => {
+    bridgeMock.onEvent = options.onEvent;
+    return { channel: {}, dispose: vi.fn() };
+  }),
+}));
+
+vi.mock('./silentDispatch', () => ({
+  lastNonEmptyLine: (text: string) => text.trim().split('\n').at(-1) ?? '',
+}));
+
+import { runTriggeredAgent } from './triggerDispatch';
+import { agentService } from './agentService.svelte';
+import * as commands from '../../lib/ipc/commands';
+import { feedbackService } from '../../services/feedback/feedbackService.svelte';
+import { runService } from '../../services/run/runService.svelte';
+import { settingsService } from '../../services/settings/settingsService.svelte';
+
+const agent = { id: 'agent-1', name: 'Morning Brief', providerId: 'openai' } as AgentDef;
+
+function fire(overrides: Partial<AgentTriggerFire> = {}): AgentTriggerFire {
+  return {
+    triggerId: 'trigger-1',
+    agentId: 'agent-1',
+    output: 'notification',
+    input: 'Brief me on today',
+    firedAt: 1,
+    ...overrides,
+  };
+}
+
+describe('runTriggeredAgent', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+    vi.mocked(agentService.getById).mockReturnValue(agent);
+    vi.mocked(settingsService.getSettings).mockReturnValue({
+      ai: {
+        providers: { openai: { enabled: true } },
+        defaultAgentId: null,
+        temperature: 0.7,
+        maxTokens: 2048,
+      },
+    } as never);
+  });
+
+  it('tracks the run against the agent and delivers the answer as a notification', async () => {
+    vi.mocked(commands.agentsRunSilent).mockResolvedValueOnce({
+      text: 'Three meetings today.',
+      structured: null,
+    });
+
+    await runTriggeredAgent(fire());
+
+    expect(runService.startLocal).toHaveBeenCalledWith(
+      expect.objectContaining({ kind: 'agent', subjectId: 'cmd_agents_dyn_agent-1' }),
+    );
+    expect(commands.agentsRunSilent).toHaveBeenCalledWith(
+      'agent-1',
+      'Brief me on today',
+      expect.objectContaining({ temperature: 0.7, maxTokens: 2048 }),
+      expect.stringContaining('trigger-agent-agent-1-'),
+      {},
+    );
+    expect(feedbackService.sendBackgroundForSource).toHaveBeenCalledWith('agents', {
+      title: 'Morning Brief',
+      body: 'Three meetings today.',
+    });
+    expect(runMock.handle.write).toHaveBeenCalledWith('Three meetings today.');
+    expect(runMock.handle.done).toHaveBeenCalled();
+  });
+
+  it('writes the answer to a new note', async () => {
+    vi.mocked(commands.agentsRunSilent).mockResolvedValueOnce({
+      text: 'Summary of report.pdf',
+      structured: null,
+    });
+
+    await runTriggeredAgent(fire({ output: 'note' }));
+
+    expect(commands.noteUpsert).toHaveBeenCalledWith(
+      expect.objectContaining({ body: 'Summary of report.pdf', pinned: false }),
+    );
+  });
+
+  it('shows the last line in the HUD', async () => {
+    vi.mocked(commands.agentsRunSilent).mockResolvedValueOnce({
+      text: 'Checked.\nAll good',
+      structured: null,
+    });
+
+    await runTriggeredAgent(fire({ output: 'hud' }));
+
+    expect(feedbackService.showHUD).toHaveBeenCalledWith('All good');
+  });
+
+  it('fails the run instead of delivering an empty answer', async () => {
+    vi.mocked(commands.agentsRunSilent).mockResolvedValueOnce({ text: '  ', structured: null });
+
+    await runTriggeredAgent(fire());
+
+    expect(feedbackService.sendBackgroundForSource).not.toHaveBeenCalled();
+    expect(runMock.handle.fail).toHaveBeenCalledWith('Agent returned empty response');
+    expect(runMock.handle.done).not.toHaveBeenCalled();
+  });
+
+  it('leaves a cancelled run alone', async () => {
+    vi.mocked(commands.agentsRunSilent).mockImplementationOnce(async () => {
+      bridgeMock.onEvent?.({ type: 'cancelled' } as AgentStreamEvent);
+      return { text: '', structured: null };
+    });
+
+    await runTriggeredAgent(fire());
+
+    expect(runMock.handle.fail).not.toHaveBeenCalled();
+    expect(runMock.handle.done).not.toHaveBeenCalled();
+  });
+
+  it('skips a fire while the same trigger is still running', async () => {
+    let finish: (output: SilentRunOutput) => void = () => undefined;
+    vi.mocked(commands.agentsRunSilent).mockImplementationOnce(
+      () => new Promise<SilentRunOutput>((resolve) => (finish = resolve)),
+    );
+
+    const first = runTriggeredAgent(fire());
+    await vi.waitFor(() => expect(commands.agentsRunSilent).toHaveBeenCalledTimes(1));
+    await runTriggeredAgent(fire());
+    finish({ text: 'done', structured: null });
+    await first;
+
+    expect(runService.startLocal).toHaveBeenCalledTimes(1);
+  });
+});
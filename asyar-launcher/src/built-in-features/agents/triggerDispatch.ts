@@ -0,0 +1,157 @@
+/**
+ * Background trigger runs.
+ *
+ * Rust decides when a schedule, file or clipboard trigger fires and emits
+ * `asyar:agents:trigger` (see `agents::triggers`). This adapter runs the
+ * agent silently with the user's provider settings, tracks it as a Run so
+ * it shows up in run history, and delivers the answer to a note, a
+ * notification or the HUD. Unlike hotkey commands it never reads the
+ * selection or pastes: nobody is waiting at the keyboard.
+ */
+import { extractErrorMessage } from '../../lib/errors';
+import {
+  agentsCancelRun,
+  agentsGet,
+  agentsRunSilent,
+  noteUpsert,
+  toAgentProviderDescriptors,
+} from '../../lib/ipc/commands';
+import { providerRegistry } from '../../services/ai/providerRegistry';
+import { feedbackService } from '../../services/feedback/feedbackService.svelte';
+import { logService } from '../../services/log/logService';
+import { runService } from '../../services/run/runService.svelte';
+import { settingsService } from '../../services/settings/settingsService.svelte';
+import { agentService } from './agentService.svelte';
+import { createAgentStreamChannel } from './agentStreamBridge';
+import { lastNonEmptyLine } from './silentDispatch';
+import type { AgentDef, AgentTriggerFire, AgentTriggerOutput } from './types';
+
+/** Trigger ids with a run in flight; a fire arriving meanwhile is dropped. */
+const inFlight = new Set<string>();
+
+/**
+ * Runs the agent for one trigger fire. Failures land on the tracked Run
+ * (and through it the failure list) rather than rejecting: the caller is
+ * an event listener with nobody to report to.
+ */
+export async function runTriggeredAgent(fire: AgentTriggerFire): Promise<void> {
+  if (inFlight.has(fire.triggerId)) {
+    logService.info(`[agent-triggers] ${fire.triggerId} still running; skipping fire`);
+    return;
+  }
+  inFlight.add(fire.triggerId);
+  try {
+    await runTrigger(fire);
+  } finally {
+    inFlight.delete(fire.triggerId);
+  }
+}
+
+async function runTrigger(fire: AgentTriggerFire): Promise<void> {
+  let agent: AgentDef;
+  try {
+    agent = await loadAgent(fire.agentId);
+  } catch (cause) {
+    logService.warn(`[agent-triggers] ${fire.triggerId}: ${extractErrorMessage(cause)}`);
+    return;
+  }
+
+  const handle = await runService.startLocal({
+    label: `${agent.name}: ${fire.input.slice(0, 50)}`,
+    kind: 'agent',
+    cancellable: true,
+    extensionId: 'agents',
+    subjectId: `cmd_agents_dyn_${agent.id}`,
+  });
+  const streamId = `trigger-agent-${agent.id}-${crypto.randomUUID()}`;
+  let cancelled = false;
+  const unsubscribeCancel = handle.onCancel(() => {
+    cancelled = true;
+    void agentsCancelRun(streamId).catch(() => undefined);
+  });
+
+  let dispose: (() => void) | undefined;
+  try {
+    const settings = settingsService.getSettings();
+    const providerConfig = settings.ai.providers[agent.providerId];
+    const runConfig = {
+      providers: toAgentProviderDescriptors(providerRegistry.list()),
+      configs: settings.ai.providers,
+      defaultAgentId: settings.ai.defaultAgentId,
+      temperature: providerConfig?.temperature ?? settings.ai.temperature,
+      maxTokens: providerConfig?.maxTokens ?? settings.ai.maxTokens,
+    };
+    let bridgeError: Error | null = null;
+    const stream = createAgentStreamChannel({
+      streamId,
+      agentId: agent.id,
+      onEvent: (event) => {
+        if (event.type === 'cancelled') cancelled = true;
+      },
+      onBridgeError: (error) => {
+        bridgeError = error;
+      },
+    });
+    dispose = stream.dispose;
+
+    const { text: result } = await agentsRunSilent(
+      agent.id,
+      fire.input,
+      runConfig,
+      streamId,
+      stream.channel,
+    );
+    if (bridgeError) throw bridgeError;
+    if (cancelled) return;
+    if (result.trim().length === 0) throw new Error('Agent returned empty response');
+
+    await handle.write(result);
+    await deliverOutput(fire.output, agent, result);
+    await handle.done();
+  } catch (cause) {
+    if (cancelled) return;
+    const detail = extractErrorMessage(cause);
+    logService.warn(`[agent-triggers] ${agent.name} failed: ${detail}`);
+    await handle.fail(detail);
+  } finally {
+    unsubscribeCancel();
+    dispose?.();
+  }
+}
+
+async function deliverOutput(
+  output: AgentTriggerOutput,
+  agent: AgentDef,
+  result: string,
+): Promise<void> {
+  switch (output) {
+    case 'note': {
+      const now = Date.now();
+      await noteUpsert({
+        id: crypto.randomUUID(),
+        title: `${agent.name} — ${new Date(now).toLocaleString()}`,
+        body: result,
+        createdAt: now,
+        updatedAt: now,
+        pinned: false,
+      });
+      return;
+    }
+    case 'notification':
+      await feedbackService.sendBackgroundForSource('agents', {
+        title: agent.name,
+        body: result,
+      });
+      return;
+    case 'hud':
+      await feedbackService.showHUD(lastNonEmptyLine(result));
+  }
+}
+
+async function loadAgent(agentId: string): Promise<AgentDef> {
+  const cached = agentService.getById(agentId);
+  if (cached) return cached;
+  const fetched = await agentsGet(agentId);
+  if (!fetched) throw new Error(`Agent '${agentId}' not found`);
+  return fetched;
+}
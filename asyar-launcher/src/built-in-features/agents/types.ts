@@ -1,3 +1,5 @@
+import type { AttemptOutcome, RoutingPolicy } from '../../bindings';
+
 export type MessageRole = 'user' | 'assistant' | 'tool';
 
 /**
@@ -33,6 +35,12 @@ export interface AgentDef {
   cacheResponses: boolean;
   /** Trigger delimiter used when inputSource is shortcodeMiss. */
   shortcodeTrigger: string;
+  /** Estimated USD spend cap per calendar month; `null` means unlimited. */
+  monthlyBudgetUsd: number | null;
+  /** Retry and fallback rules applied when a provider request fails. */
+  routing: RoutingPolicy;
+  /** JSON Schema the final answer must match; `null` means free text. */
+  outputSchema: unknown | null;
   createdAt: number | null;
   updatedAt: number | null;
 }
@@ -41,6 +49,8 @@ export interface ThreadDef {
   id: string;
   agentId: string;
   title: string | null;
+  /** Last message of the selected branch; null until the first message. */
+  currentLeafId: string | null;
   createdAt: number | null;
   updatedAt: number | null;
 }
@@ -52,6 +62,153 @@ export interface MessageDef {
   content: unknown;
   createdAt: number;
   runId: string | null;
+  /** The message this one follows; siblings share a parent. */
+  parentId: string | null;
+}
+
+/**
+ * The thread's summary of older messages, sent in their place once the
+ * thread outgrew its model's context window. Applies to a branch only when
+ * `throughMessageId` is on it.
+ */
+export interface ThreadSummaryDef {
+  text: string;
+  throughMessageId: string;
+}
+
+/**
+ * A fact an agent remembered about the user. `agentId` is null for a global
+ * memory shared by every agent.
+ */
+export interface MemoryDef {
+  id: string;
+  agentId: string | null;
+  content: string;
+  sourceThreadId: string | null;
+  createdAt: number;
+  updatedAt: number;
+}
+
+/**
+ * What starts a background run. Schedules use local time on ISO weekdays
+ * (1 = Monday … 7 = Sunday; empty means every day).
+ */
+export type AgentTriggerSpec =
+  | { type: 'schedule'; weekdays: number[]; time: string }
+  | { type: 'fileCreated'; directory: string }
+  | { type: 'clipboardMatch'; pattern: string };
+
+/** Where a triggered run's answer is delivered. */
+export type AgentTriggerOutput = 'note' | 'notification' | 'hud';
+
+export interface AgentTriggerDef {
+  id: string;
+  agentId: string;
+  spec: AgentTriggerSpec;
+  /** User message for the run; the event's details are appended. */
+  prompt: string;
+  output: AgentTriggerOutput;
+  enabled: boolean;
+  nextFireAt: number | null;
+  lastFiredAt: number | null;
+  createdAt: number;
+  updatedAt: number;
+}
+
+/** Creates a trigger when `id` is omitted, otherwise replaces it. */
+export interface AgentTriggerInput {
+  id?: string | null;
+  agentId: string;
+  spec: AgentTriggerSpec;
+  prompt?: string | null;
+  output: AgentTriggerOutput;
+  enabled?: boolean | null;
+}
+
+/** Payload of the `asyar:agents:trigger` event Rust emits when a trigger fires. */
+export interface AgentTriggerFire {
+  triggerId: string;
+  agentId: string;
+  output: AgentTriggerOutput;
+  input: string;
+  firedAt: number;
+}
+
+/** What `fs-write` does once a path passes the policy. */
+export type AgentWriteMode = 'direct' | 'dryRun' | 'confirm';
+
+/**
+ * Per-agent limits on the shell and file tools. Empty lists mean "no
+ * restriction"; credential stores and the launcher's own data stay off
+ * limits regardless.
+ */
+export interface AgentToolPolicy {
+  /** Executables `shell-exec` may run, matched exactly (`git`, `/usr/bin/ls`). */
+  allowedCommands: string[];
+  /** Directories commands may run in; the first is the default. */
+  workingDirs: string[];
+  /** `files:read`-style globs the agent may read. */
+  readPaths: string[];
+  /** `files:read`-style globs the agent may write. */
+  writePaths: string[];
+  maxOutputBytes: number;
+  timeoutSecs: number;
+  writeMode: AgentWriteMode;
+}
+
+export type AgentToolAuditOutcome = 'ok' | 'denied' | 'failed' | 'dryRun' | 'declined';
+
+export interface AgentToolAuditDef {
+  id: number;
+  toolId: string;
+  agentId: string | null;
+  calledAt: number;
+  outcome: AgentToolAuditOutcome;
+  detail: string | null;
+  argsSummary: string;
+}
+
+/** Payload of the `asyar:agents:confirm-write` event for confirm-mode writes. */
+export interface AgentWriteConfirmRequest {
+  id: string;
+  agentId: string | null;
+  path: string;
+  bytes: number;
+  /** Whether the write replaces an existing file. */
+  exists: boolean;
+  preview: string;
+}
+
+/** How an imported agent's provider was resolved on this machine. */
+export type ImportProviderMatch = 'matched' | 'defaultAgent' | 'unavailable';
+
+/** What importing one bundled agent does (preview) or did (import). */
+export interface AgentImportPlan {
+  name: string;
+  providerId: string;
+  modelId: string;
+  providerMatch: ImportProviderMatch;
+  /** The bundled selection minus tools not registered here. */
+  toolSelection: string[];
+  missingTools: string[];
+  /** Tier 2 extensions none of whose tools are registered. */
+  missingExtensions: string[];
+  /** MCP servers with no registered tools. */
+  missingMcpServers: string[];
+  /** Provider types of routing fallbacks dropped for lack of a connection. */
+  droppedFallbacks: string[];
+  /** Id of the created agent; `null` in a preview. */
+  agentId: string | null;
+}
+
+export interface AgentImportReport {
+  agents: AgentImportPlan[];
+}
+
+/** A message on the thread's selected branch, as listed for the chat view. */
+export interface PathMessageDef extends MessageDef {
+  /** Every branch forking at this point, itself included, oldest first. */
+  siblingIds: string[];
 }
 
 export interface AgentCreateInput {
@@ -69,6 +226,11 @@ export interface AgentCreateInput {
   outputAction?: SilentOutputAction;
   cacheResponses?: boolean;
   shortcodeTrigger?: string;
+  monthlyBudgetUsd?: number | null;
+  /** Omit on update to keep the stored policy. */
+  routing?: RoutingPolicy;
+  /** Omit on update to clear the stored schema. */
+  outputSchema?: unknown | null;
 }
 
 export interface AgentUpdateInput extends AgentCreateInput {
@@ -81,3 +243,48 @@ export interface MessageInsertInput {
   content: unknown;
   runId?: string | null;
 }
+
+/** Token usage of one assistant response, as recorded by the agent loop. */
+export interface UsageDef {
+  id: string;
+  messageId: string | null;
+  threadId: string | null;
+  runId: string | null;
+  agentId: string;
+  providerId: string;
+  modelId: string;
+  inputTokens: number;
+  outputTokens: number;
+  cachedTokens: number;
+  reasoningTokens: number;
+  /** `null` when the model has no known price. */
+  costUsd: number | null;
+  createdAt: number;
+}
+
+/** One provider request made during an agent run, including retries and fallbacks. */
+export interface AttemptDef {
+  id: string;
+  runId: string | null;
+  agentId: string;
+  turn: number;
+  attempt: number;
+  providerId: string;
+  modelId: string;
+  outcome: AttemptOutcome;
+  statusCode: number | null;
+  error: string | null;
+  startedAt: number;
+  endedAt: number;
+}
+
+export interface SpendRowDef {
+  key: string;
+  responses: number;
+  inputTokens: number;
+  outputTokens: number;
+  cachedTokens: number;
+  reasoningTokens: number;
+  costUsd: number;
+  unpricedResponses: number;
+}
@@ -0,0 +1,75 @@
+import { beforeEach, describe, expect, it, vi } from 'vitest';
+import type { AgentDef, AgentWriteConfirmRequest } from './types';
+
+vi.mock('../../lib/ipc/commands', () => ({
+  agentsToolWriteResolve: vi.fn(async () => true),
+}));
+
+vi.mock('../../services/feedback/feedbackService.svelte', () => ({
+  feedbackService: { confirmAlert: vi.fn() },
+}));
+
+vi.mock('../../services/log/logService', () => ({
+  logService: { info: vi.fn(), warn: vi.fn() },
+}));
+
+vi.mock('./agentService.svelte', () => ({
+  agentService: { getById: vi.fn() },
+}));
+
+import { confirmAgentWrite } from './writeConfirm';
+import { agentService } from './agentService.svelte';
+import * as commands from '../../lib/ipc/commands';
+import { feedbackService } from '../../services/feedback/feedbackService.svelte';
+
+function request(overrides: Partial<AgentWriteConfirmRequest> = {}): AgentWriteConfirmRequest {
+  return {
+    id: 'write-1',
+    agentId: 'agent-1',
+    path: '/home/u/plan.md',
+    bytes: 5,
+    exists: false,
+    preview: 'hello',
+    ...overrides,
+  };
+}
+
+describe('confirmAgentWrite', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+    vi.mocked(agentService.getById).mockReturnValue({ id: 'agent-1', name: 'Planner' } as AgentDef);
+  });
+
+  it('asks the user and passes the answer back', async () => {
+    vi.mocked(feedbackService.confirmAlert).mockResolvedValueOnce(true);
+
+    await confirmAgentWrite(request());
+
+    expect(feedbackService.confirmAlert).toHaveBeenCalledWith(
+      expect.objectContaining({
+        title: 'Planner wants to create a file',
+        message: expect.stringContaining('/home/u/plan.md'),
+      }),
+    );
+    expect(commands.agentsToolWriteResolve).toHaveBeenCalledWith('write-1', true);
+  });
+
+  it('flags overwrites as dangerous', async () => {
+    vi.mocked(feedbackService.confirmAlert).mockResolvedValueOnce(false);
+
+    await confirmAgentWrite(request({ exists: true }));
+
+    expect(feedbackService.confirmAlert).toHaveBeenCalledWith(
+      expect.objectContaining({ confirmText: 'Overwrite', variant: 'danger' }),
+    );
+    expect(commands.agentsToolWriteResolve).toHaveBeenCalledWith('write-1', false);
+  });
+
+  it('declines when the dialog fails', async () => {
+    vi.mocked(feedbackService.confirmAlert).mockRejectedValueOnce(new Error('no window'));
+
+    await confirmAgentWrite(request());
+
+    expect(commands.agentsToolWriteResolve).toHaveBeenCalledWith('write-1', false);
+  });
+});
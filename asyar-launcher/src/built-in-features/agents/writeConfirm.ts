@@ -0,0 +1,41 @@
+/**
+ * Confirm-mode file writes.
+ *
+ * An agent whose tool policy is in confirm mode can't write a file until
+ * the user says so: Rust holds the `fs-write` call, emits
+ * `asyar:agents:confirm-write`, and waits (two minutes at most) for the
+ * answer sent back here. Any failure along the way answers "no".
+ */
+import { agentsToolWriteResolve } from '../../lib/ipc/commands';
+import { feedbackService } from '../../services/feedback/feedbackService.svelte';
+import { logService } from '../../services/log/logService';
+import { agentService } from './agentService.svelte';
+import type { AgentWriteConfirmRequest } from './types';
+
+/** How much of the content the dialog quotes. */
+const PREVIEW_LINES = 12;
+
+export async function confirmAgentWrite(request: AgentWriteConfirmRequest): Promise<void> {
+  const agentName =
+    (request.agentId ? agentService.getById(request.agentId)?.name : undefined) ?? 'An agent';
+  let approved = false;
+  try {
+    approved = await feedbackService.confirmAlert({
+      title: `${agentName} wants to ${request.exists ? 'overwrite' : 'create'} a file`,
+      message: `${request.path} (${request.bytes} bytes)\n\n${previewOf(request.preview)}`,
+      confirmText: request.exists ? 'Overwrite' : 'Write',
+      variant: request.exists ? 'danger' : undefined,
+    });
+  } catch (err) {
+    logService.warn(`[agents] write confirmation failed: ${err}`);
+  }
+  if ((await agentsToolWriteResolve(request.id, approved)) === false) {
+    logService.info(`[agents] write ${request.id} was no longer waiting for an answer`);
+  }
+}
+
+function previewOf(text: string): string {
+  const lines = text.split('\n');
+  if (lines.length <= PREVIEW_LINES) return text;
+  return `${lines.slice(0, PREVIEW_LINES).join('\n')}\n…`;
+}
@@ -0,0 +1,101 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import type { McpCatalogEntry } from './types';
+
+vi.mock('../../lib/ipc/mcpCommands', () => ({
+  mcpResourceAttachments: vi.fn(),
+  mcpRenderPrompt: vi.fn(),
+}));
+
+vi.mock('../../services/settings/settingsService.svelte', () => ({
+  settingsService: { currentSettings: { ai: { defaultAgentId: 'agent-1' } } },
+}));
+
+vi.mock('../agents/agentService.svelte', () => ({
+  agentService: { agents: [{ id: 'agent-1' }, { id: 'agent-2' }] },
+}));
+
+vi.mock('../agents/agentsManager.svelte', () => ({
+  agentsManager: { pendingAttachments: [] },
+}));
+
+vi.mock('../agents/threadOpener', () => ({
+  openAgentForTab: vi.fn(async () => {}),
+}));
+
+vi.mock('./mcpCatalog.svelte', () => ({
+  mcpCatalog: { getByDynamicId: vi.fn() },
+}));
+
+import { dispatchMcpCommand, promptArguments } from './dispatch';
+import { mcpRenderPrompt, mcpResourceAttachments } from '../../lib/ipc/mcpCommands';
+import { agentsManager } from '../agents/agentsManager.svelte';
+import { openAgentForTab } from '../agents/threadOpener';
+import { mcpCatalog } from './mcpCatalog.svelte';
+
+function entry(overrides: Partial<McpCatalogEntry>): McpCatalogEntry {
+  return {
+    dynamicId: 'res-1',
+    kind: 'resource',
+    serverId: 'files',
+    target: 'file:///notes/today.md',
+    title: 'today.md',
+    description: null,
+    mimeType: 'text/markdown',
+    arguments: [],
+    ...overrides,
+  };
+}
+
+beforeEach(() => {
+  vi.clearAllMocks();
+  agentsManager.pendingAttachments = [];
+});
+
+describe('dispatchMcpCommand', () => {
+  it('queues a resource as an attachment and opens the default agent', async () => {
+    const part = {
+      type: 'file' as const,
+      name: 'today.md',
+      mediaType: 'text/markdown',
+      text: 'notes',
+      data: null,
+    };
+    vi.mocked(mcpCatalog.getByDynamicId).mockReturnValue(entry({}));
+    vi.mocked(mcpResourceAttachments).mockResolvedValue([part]);
+
+    await dispatchMcpCommand('res-1');
+
+    expect(mcpResourceAttachments).toHaveBeenCalledWith('files', 'file:///notes/today.md');
+    expect(agentsManager.pendingAttachments).toEqual([part]);
+    expect(openAgentForTab).toHaveBeenCalledWith('agent-1', '', true);
+  });
+
+  it('renders a prompt with the collected arguments and sends it to a new thread', async () => {
+    vi.mocked(mcpCatalog.getByDynamicId).mockReturnValue(
+      entry({ dynamicId: 'prompt-1', kind: 'prompt', serverId: 'github', target: 'review' }),
+    );
+    vi.mocked(mcpRenderPrompt).mockResolvedValue('Review fn main() {}');
+
+    await dispatchMcpCommand('prompt-1', { arguments: { code: 'fn main() {}', style: '' } });
+
+    expect(mcpRenderPrompt).toHaveBeenCalledWith('github', 'review', { code: 'fn main() {}' });
+    expect(openAgentForTab).toHaveBeenCalledWith('agent-1', 'Review fn main() {}', false);
+  });
+
+  it('throws for an id that is no longer in the catalog', async () => {
+    vi.mocked(mcpCatalog.getByDynamicId).mockReturnValue(undefined);
+
+    await expect(dispatchMcpCommand('gone')).rejects.toThrow("MCP command 'gone' not found");
+    expect(openAgentForTab).not.toHaveBeenCalled();
+  });
+});
+
+describe('promptArguments', () => {
+  it('accepts a flat map and stringifies values', () => {
+    expect(promptArguments({ count: 3, name: 'x', empty: null })).toEqual({
+      count: '3',
+      name: 'x',
+    });
+    expect(promptArguments(undefined)).toEqual({});
+  });
+});
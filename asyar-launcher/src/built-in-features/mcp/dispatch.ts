@@ -0,0 +1,51 @@
+import { mcpRenderPrompt, mcpResourceAttachments } from '../../lib/ipc/mcpCommands';
+import { settingsService } from '../../services/settings/settingsService.svelte';
+import { agentService } from '../agents/agentService.svelte';
+import { agentsManager } from '../agents/agentsManager.svelte';
+import { decideTabDestination } from '../agents/tabRouter';
+import { openAgentForTab } from '../agents/threadOpener';
+import { mcpCatalog } from './mcpCatalog.svelte';
+
+/**
+ * Runs an MCP catalog command picked in root search. A resource is read and
+ * queued as an attachment on the default agent's current thread, where the
+ * user types what to do with it; a prompt is rendered with the arguments the
+ * search bar collected and sent to a new thread.
+ */
+export async function dispatchMcpCommand(
+  dynamicId: string,
+  args?: Record<string, unknown>,
+): Promise<void> {
+  const entry = mcpCatalog.getByDynamicId(dynamicId);
+  if (!entry) {
+    throw new Error(`MCP command '${dynamicId}' not found`);
+  }
+  const agentId = decideTabDestination({
+    defaultAgentId: settingsService.currentSettings.ai.defaultAgentId,
+    agents: agentService.agents,
+  }).agentId;
+
+  if (entry.kind === 'resource') {
+    const parts = await mcpResourceAttachments(entry.serverId, entry.target);
+    agentsManager.pendingAttachments = [...agentsManager.pendingAttachments, ...parts];
+    await openAgentForTab(agentId, '', true);
+    return;
+  }
+
+  const text = await mcpRenderPrompt(entry.serverId, entry.target, promptArguments(args));
+  await openAgentForTab(agentId, text, false);
+}
+
+/** MCP prompt arguments are strings; unset optional arguments are left out. */
+export function promptArguments(args?: Record<string, unknown>): Record<string, string> {
+  const values =
+    (args && typeof args === 'object' && 'arguments' in args
+      ? (args as { arguments: Record<string, unknown> }).arguments
+      : args) ?? {};
+  const result: Record<string, string> = {};
+  for (const [name, value] of Object.entries(values)) {
+    if (value === undefined || value === null || value === '') continue;
+    result[name] = String(value);
+  }
+  return result;
+}
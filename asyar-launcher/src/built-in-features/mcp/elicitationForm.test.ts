@@ -0,0 +1,42 @@
+import { describe, it, expect } from 'vitest';
+import { enumOptions, initialValues, toContent } from './elicitationForm';
+import type { McpElicitationSchema } from './types';
+
+const schema: McpElicitationSchema = {
+  type: 'object',
+  properties: {
+    assignee: { type: 'string', title: 'Assignee' },
+    priority: { type: 'string', enum: ['low', 'high'], enumNames: ['Low', 'High'], default: 'low' },
+    estimate: { type: 'integer' },
+    notify: { type: 'boolean', default: true },
+  },
+  required: ['assignee'],
+};
+
+describe('elicitationForm', () => {
+  it('starts from the schema defaults', () => {
+    expect(initialValues(schema)).toEqual({
+      assignee: '',
+      priority: 'low',
+      estimate: '',
+      notify: true,
+    });
+  });
+
+  it('labels enum options by enumNames', () => {
+    expect(enumOptions(schema.properties.priority)).toEqual([
+      { value: 'low', label: 'Low' },
+      { value: 'high', label: 'High' },
+    ]);
+  });
+
+  it('omits empty fields and parses numbers', () => {
+    expect(
+      toContent(schema, { assignee: ' sam ', priority: 'high', estimate: '3', notify: false }),
+    ).toEqual({ assignee: 'sam', priority: 'high', estimate: 3, notify: false });
+    expect(toContent(schema, { assignee: '', priority: '', estimate: 'x', notify: true })).toEqual({
+      estimate: 'x',
+      notify: true,
+    });
+  });
+});
@@ -0,0 +1,58 @@
+import type { McpElicitationField, McpElicitationSchema } from './types';
+
+/** What a form control holds: text for inputs and selects, a flag for checkboxes. */
+export type ElicitationValue = string | boolean;
+
+/** Starting values: the schema's defaults, else empty (or unchecked). */
+export function initialValues(schema: McpElicitationSchema): Record<string, ElicitationValue> {
+  const values: Record<string, ElicitationValue> = {};
+  for (const [name, field] of Object.entries(schema.properties)) {
+    if (field.type === 'boolean') {
+      values[name] = field.default === true;
+    } else {
+      values[name] = field.default === undefined ? '' : String(field.default);
+    }
+  }
+  return values;
+}
+
+/** The label a field is shown with. */
+export function fieldLabel(name: string, field: McpElicitationField): string {
+  return field.title ?? name;
+}
+
+/** Select options for an `enum` field, labelled by `enumNames` when given. */
+export function enumOptions(field: McpElicitationField): Array<{ value: string; label: string }> {
+  return (field.enum ?? []).map((value, index) => ({
+    value,
+    label: field.enumNames?.[index] ?? value,
+  }));
+}
+
+/**
+ * Turns form values into the content the server asked for. Left-empty
+ * fields are omitted; numbers that don't parse are kept as text so the
+ * launcher's schema check reports them against the right field.
+ */
+export function toContent(
+  schema: McpElicitationSchema,
+  values: Record<string, ElicitationValue>,
+): Record<string, unknown> {
+  const content: Record<string, unknown> = {};
+  for (const [name, field] of Object.entries(schema.properties)) {
+    const value = values[name];
+    if (field.type === 'boolean') {
+      content[name] = value === true;
+      continue;
+    }
+    const text = typeof value === 'string' ? value.trim() : '';
+    if (text === '') continue;
+    if (field.type === 'number' || field.type === 'integer') {
+      const parsed = Number(text);
+      content[name] = Number.isNaN(parsed) ? text : parsed;
+    } else {
+      content[name] = text;
+    }
+  }
+  return content;
+}
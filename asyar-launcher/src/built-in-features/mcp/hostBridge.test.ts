@@ -0,0 +1,80 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+
+vi.mock('@tauri-apps/api/event', () => ({
+  listen: vi.fn(async () => () => {}),
+}));
+
+vi.mock('../../lib/ipc/mcpCommands', () => ({
+  mcpHostResolvePermission: vi.fn(async () => true),
+  mcpHostReportToolResult: vi.fn(async () => true),
+}));
+
+vi.mock('../../lib/ipc/commands', () => ({
+  showWindow: vi.fn(async () => {}),
+}));
+
+vi.mock('../../services/log/logService', () => ({
+  logService: { warn: vi.fn(), error: vi.fn(), info: vi.fn(), debug: vi.fn() },
+}));
+
+vi.mock('../agents/toolDispatch', () => ({
+  invokeExtensionTool: vi.fn(),
+}));
+
+vi.mock('./mcpService.svelte', () => ({
+  mcpService: { requestPermission: vi.fn() },
+}));
+
+import { answerHostPermission, runHostToolDispatch } from './hostBridge';
+import { mcpHostReportToolResult, mcpHostResolvePermission } from '../../lib/ipc/mcpCommands';
+import { showWindow } from '../../lib/ipc/commands';
+import { invokeExtensionTool } from '../agents/toolDispatch';
+import { mcpService } from './mcpService.svelte';
+
+beforeEach(() => {
+  vi.clearAllMocks();
+});
+
+describe('answerHostPermission', () => {
+  it('shows the launcher, asks the user and reports the decision', async () => {
+    vi.mocked(mcpService.requestPermission).mockResolvedValue('allow_always');
+
+    await answerHostPermission({
+      id: 'req-1',
+      serverId: 'asyar',
+      toolId: 'notes-search',
+      agentId: 'mcp-client:Zed',
+      clientName: 'Zed',
+    });
+
+    expect(showWindow).toHaveBeenCalled();
+    expect(mcpService.requestPermission).toHaveBeenCalledWith(
+      'asyar',
+      'notes-search',
+      'mcp-client:Zed',
+    );
+    expect(mcpHostResolvePermission).toHaveBeenCalledWith('req-1', 'allow_always');
+  });
+});
+
+describe('runHostToolDispatch', () => {
+  const dispatch = {
+    id: 'call-1',
+    extensionId: 'org.example.notes',
+    toolId: 'lookup',
+    arguments: { q: 'x' },
+  };
+
+  it('reports the tool result', async () => {
+    vi.mocked(invokeExtensionTool).mockResolvedValue({ hits: 2 });
+    await runHostToolDispatch(dispatch);
+    expect(invokeExtensionTool).toHaveBeenCalledWith('org.example.notes', 'lookup', { q: 'x' });
+    expect(mcpHostReportToolResult).toHaveBeenCalledWith('call-1', { hits: 2 });
+  });
+
+  it('reports a failure as an error message', async () => {
+    vi.mocked(invokeExtensionTool).mockRejectedValue(new Error('worker not mounted'));
+    await runHostToolDispatch(dispatch);
+    expect(mcpHostReportToolResult).toHaveBeenCalledWith('call-1', null, 'worker not mounted');
+  });
+});
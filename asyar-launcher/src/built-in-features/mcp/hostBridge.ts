@@ -0,0 +1,66 @@
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { mcpHostReportToolResult, mcpHostResolvePermission } from '../../lib/ipc/mcpCommands';
+import { showWindow } from '../../lib/ipc/commands';
+import { extractErrorMessage } from '../../lib/errors';
+import { logService } from '../../services/log/logService';
+import { invokeExtensionTool } from '../agents/toolDispatch';
+import { mcpService } from './mcpService.svelte';
+import type { McpHostPermissionRequest, McpHostToolDispatch } from './types';
+
+export const HOST_PERMISSION_EVENT = 'asyar:mcp-host:permission-request';
+export const HOST_TOOL_DISPATCH_EVENT = 'asyar:mcp-host:tool-dispatch';
+
+/** Server id the host's own tools are permissioned and audited under. */
+export const HOST_SERVER_ID = 'asyar';
+/** Agent id prefix for calls made by an external client, e.g. `mcp-client:Zed`. */
+export const CLIENT_AGENT_PREFIX = 'mcp-client:';
+
+/**
+ * A client of Asyar's MCP server wants to call a tool. The launcher may be
+ * hidden while the client runs, so the window is brought up before the
+ * usual permission prompt is shown.
+ */
+export async function answerHostPermission(request: McpHostPermissionRequest): Promise<void> {
+  await showWindow();
+  const decision = await mcpService.requestPermission(
+    request.serverId,
+    request.toolId,
+    request.agentId,
+  );
+  const delivered = await mcpHostResolvePermission(request.id, decision);
+  if (delivered === false) {
+    logService.warn(
+      `[mcp] ${request.clientName} stopped waiting for permission to run ${request.toolId}`,
+    );
+  }
+}
+
+/** Runs an extension tool a client called and hands the outcome back to the host. */
+export async function runHostToolDispatch(dispatch: McpHostToolDispatch): Promise<void> {
+  try {
+    const result = await invokeExtensionTool(
+      dispatch.extensionId,
+      dispatch.toolId,
+      dispatch.arguments,
+    );
+    await mcpHostReportToolResult(dispatch.id, result ?? null);
+  } catch (err) {
+    await mcpHostReportToolResult(dispatch.id, null, extractErrorMessage(err));
+  }
+}
+
+export async function startMcpHostBridge(): Promise<UnlistenFn> {
+  const unlistenPermission = await listen<McpHostPermissionRequest>(
+    HOST_PERMISSION_EVENT,
+    (event) => {
+      void answerHostPermission(event.payload);
+    },
+  );
+  const unlistenDispatch = await listen<McpHostToolDispatch>(HOST_TOOL_DISPATCH_EVENT, (event) => {
+    void runHostToolDispatch(event.payload);
+  });
+  return () => {
+    unlistenPermission();
+    unlistenDispatch();
+  };
+}
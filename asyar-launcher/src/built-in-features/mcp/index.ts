@@ -1,6 +1,13 @@
 import type { Extension, ExtensionContext } from 'asyar-sdk/contracts';
 import { ActionContext } from 'asyar-sdk/contracts';
 import { mcpService } from './mcpService.svelte';
+import { mcpCatalog } from './mcpCatalog.svelte';
+import { dispatchMcpCommand } from './dispatch';
+import { startMcpHostBridge } from './hostBridge';
+import { startMcpServerRequestsBridge } from './serverRequestsBridge';
+import { logService } from '../../services/log/logService';
+import type { UnlistenFn } from '@tauri-apps/api/event';
+import { registerBuiltinDynamicDispatcher } from '../../services/extension/builtinDynamicDispatchers';
 import { actionService } from '../../services/action/actionService.svelte';
 import { viewManager } from '../../services/extension/viewManager.svelte';
 import ManageServersView from './ManageServersView.svelte';
@@ -11,12 +18,16 @@ import ActivityView from './ActivityView.svelte';
 
 export { ManageServersView, ImportServersView, InstallServerView, PermissionsView, ActivityView };
 
+registerBuiltinDynamicDispatcher('mcp', dispatchMcpCommand);
+
 const ACTION_REFRESH = 'mcp:refresh-servers';
 const ACTION_INSTALL = 'mcp:install-server';
 const ACTION_IMPORT = 'mcp:import-servers';
 const ACTION_VIEW_PERMISSIONS = 'mcp:view-permissions';
 const ACTION_VIEW_ACTIVITY = 'mcp:view-activity';
 const ACTION_TOGGLE_STRICT = 'mcp:toggle-strict-mode';
+const ACTION_TOGGLE_HOST = 'mcp:toggle-host';
+const ACTION_COPY_HOST_CONFIG = 'mcp:copy-host-config';
 
 const ALL_ACTIONS = [
   ACTION_REFRESH,
@@ -25,20 +36,45 @@ const ALL_ACTIONS = [
   ACTION_VIEW_PERMISSIONS,
   ACTION_VIEW_ACTIVITY,
   ACTION_TOGGLE_STRICT,
+  ACTION_TOGGLE_HOST,
+  ACTION_COPY_HOST_CONFIG,
 ];
 
+/** The `mcpServers` entry a client needs to reach Asyar over stdio. */
+export function hostClientConfig(stdioCommand: string[]): string {
+  const [command, ...args] = stdioCommand;
+  return JSON.stringify({ mcpServers: { asyar: { command, args } } }, null, 2);
+}
+
 class McpExtension implements Extension {
+  private hostBridgeUnlisten: UnlistenFn | null = null;
+  private serverRequestsUnlisten: UnlistenFn | null = null;
+
   async initialize(_context: ExtensionContext): Promise<void> {
     // no-op — actions are registered per-view in viewActivated below so
     // they only appear in ⌘K while the user is inside an MCP view.
   }
 
   async activate(): Promise<void> {
-    // no-op
+    await mcpCatalog.start();
+    try {
+      this.hostBridgeUnlisten = await startMcpHostBridge();
+    } catch (err) {
+      logService.warn(`[mcp] failed to subscribe to MCP server events: ${err}`);
+    }
+    try {
+      this.serverRequestsUnlisten = await startMcpServerRequestsBridge();
+    } catch (err) {
+      logService.warn(`[mcp] failed to subscribe to sampling and elicitation requests: ${err}`);
+    }
   }
 
   async deactivate(): Promise<void> {
-    // no-op
+    await mcpCatalog.stop();
+    this.hostBridgeUnlisten?.();
+    this.hostBridgeUnlisten = null;
+    this.serverRequestsUnlisten?.();
+    this.serverRequestsUnlisten = null;
   }
 
   async viewActivated(viewId: string): Promise<void> {
@@ -125,6 +161,33 @@ class McpExtension implements Extension {
           await mcpService.setStrictMode(!mcpService.strictMode);
         },
       });
+      actionService.registerAction({
+        id: ACTION_TOGGLE_HOST,
+        label: 'Toggle Asyar MCP Server',
+        icon: '🛰️',
+        description: "Let other MCP clients use Asyar's tools (each call still asks first)",
+        category: 'MCP',
+        extensionId: 'mcp',
+        context: ActionContext.EXTENSION_VIEW,
+        execute: async () => {
+          await mcpService.setHostEnabled(!mcpService.hostStatus?.enabled);
+        },
+      });
+      actionService.registerAction({
+        id: ACTION_COPY_HOST_CONFIG,
+        label: 'Copy Asyar MCP Client Config',
+        icon: '📋',
+        description: 'Copy the config snippet that connects an MCP client to Asyar',
+        category: 'MCP',
+        extensionId: 'mcp',
+        context: ActionContext.EXTENSION_VIEW,
+        execute: async () => {
+          await mcpService.refreshHostStatus();
+          const status = mcpService.hostStatus;
+          if (!status) return;
+          await navigator.clipboard.writeText(hostClientConfig(status.stdioCommand));
+        },
+      });
     }
   }
 
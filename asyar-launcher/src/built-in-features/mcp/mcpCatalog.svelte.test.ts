@@ -0,0 +1,88 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import type { McpCatalogEntry } from './types';
+
+const mockListen = vi.hoisted(() => {
+  const callbacks = new Map<string, () => void>();
+  const listen = vi.fn(async (event: string, cb: () => void) => {
+    callbacks.set(event, cb);
+    return () => callbacks.delete(event);
+  });
+  const fire = (event: string) => callbacks.get(event)?.();
+  return { listen, fire, callbacks };
+});
+
+vi.mock('@tauri-apps/api/event', () => ({ listen: mockListen.listen }));
+
+vi.mock('../../lib/ipc/commands', () => ({
+  replaceDynamicCommandsBuiltin: vi.fn().mockResolvedValue(undefined),
+}));
+
+vi.mock('../../lib/ipc/mcpCommands', () => ({
+  mcpListCatalog: vi.fn(),
+}));
+
+vi.mock('../../services/log/logService', () => ({
+  logService: { warn: vi.fn() },
+}));
+
+import { McpCatalogManager } from './mcpCatalog.svelte';
+import { replaceDynamicCommandsBuiltin } from '../../lib/ipc/commands';
+import { mcpListCatalog } from '../../lib/ipc/mcpCommands';
+
+const prompt: McpCatalogEntry = {
+  dynamicId: 'prompt-abc',
+  kind: 'prompt',
+  serverId: 'github',
+  target: 'review',
+  title: 'Review code',
+  description: null,
+  mimeType: null,
+  arguments: [{ name: 'code', type: 'text', placeholder: 'code', required: true }],
+};
+
+beforeEach(() => {
+  vi.clearAllMocks();
+  mockListen.callbacks.clear();
+});
+
+describe('McpCatalogManager', () => {
+  it('registers catalog entries as dynamic commands with their arguments', async () => {
+    vi.mocked(mcpListCatalog).mockResolvedValue([prompt]);
+    const manager = new McpCatalogManager();
+
+    await manager.start();
+
+    expect(replaceDynamicCommandsBuiltin).toHaveBeenCalledWith('mcp', [
+      {
+        id: 'prompt-abc',
+        name: 'Review code',
+        description: undefined,
+        icon: 'icon:ai-chat',
+        typeLabel: 'MCP Prompt',
+        arguments: prompt.arguments,
+      },
+    ]);
+    expect(manager.getByDynamicId('prompt-abc')).toEqual(prompt);
+  });
+
+  it('re-syncs when a server changes status', async () => {
+    vi.mocked(mcpListCatalog).mockResolvedValueOnce([]).mockResolvedValueOnce([prompt]);
+    const manager = new McpCatalogManager();
+    await manager.start();
+
+    mockListen.fire('mcp:status_changed');
+    await vi.waitFor(() => expect(manager.entries).toEqual([prompt]));
+  });
+
+  it('clears its commands on stop', async () => {
+    vi.mocked(mcpListCatalog).mockResolvedValue([prompt]);
+    const manager = new McpCatalogManager();
+    await manager.start();
+
+    await manager.stop();
+
+    expect(replaceDynamicCommandsBuiltin).toHaveBeenLastCalledWith('mcp', []);
+    expect(manager.entries).toEqual([]);
+    expect(mockListen.callbacks.size).toBe(0);
+  });
+});
@@ -0,0 +1,75 @@
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { DynamicCommandRegistration } from 'asyar-sdk/contracts';
+import { replaceDynamicCommandsBuiltin } from '../../lib/ipc/commands';
+import { mcpListCatalog } from '../../lib/ipc/mcpCommands';
+import { logService } from '../../services/log/logService';
+import type { McpCatalogEntry } from './types';
+
+export const MCP_EXTENSION_ID = 'mcp';
+
+/**
+ * Mirrors the resources and prompts of connected MCP servers into the
+ * launcher as dynamic commands. Rust derives the entries (ids, prompt
+ * arguments); this keeps the registration in step with server status.
+ */
+export class McpCatalogManager {
+  entries = $state<McpCatalogEntry[]>([]);
+  private started = false;
+  private statusUnlisten: UnlistenFn | null = null;
+
+  async start(): Promise<void> {
+    if (this.started) return;
+    this.started = true;
+
+    try {
+      await this.refresh();
+    } catch (err) {
+      logService.warn(`[mcp] initial catalog refresh failed: ${err}`);
+    }
+
+    // A server connecting, restarting or going away changes what it
+    // publishes; the handshake has already listed everything by the time
+    // `connected` fires.
+    try {
+      this.statusUnlisten = await listen('mcp:status_changed', () => {
+        void this.refresh().catch((err) => {
+          logService.warn(`[mcp] catalog refresh on status change failed: ${err}`);
+        });
+      });
+    } catch (err) {
+      logService.warn(`[mcp] failed to subscribe to mcp:status_changed: ${err}`);
+    }
+  }
+
+  async stop(): Promise<void> {
+    if (!this.started) return;
+    this.started = false;
+    if (this.statusUnlisten) {
+      this.statusUnlisten();
+      this.statusUnlisten = null;
+    }
+    this.entries = [];
+    await replaceDynamicCommandsBuiltin(MCP_EXTENSION_ID, []);
+  }
+
+  async refresh(): Promise<void> {
+    const entries = await mcpListCatalog();
+    if (entries === null) return;
+    const regs: DynamicCommandRegistration[] = entries.map((entry) => ({
+      id: entry.dynamicId,
+      name: entry.title,
+      description: entry.description ?? undefined,
+      icon: entry.kind === 'resource' ? 'icon:file-text' : 'icon:ai-chat',
+      typeLabel: entry.kind === 'resource' ? 'MCP Resource' : 'MCP Prompt',
+      arguments: entry.arguments,
+    }));
+    await replaceDynamicCommandsBuiltin(MCP_EXTENSION_ID, regs);
+    this.entries = entries;
+  }
+
+  getByDynamicId(dynamicId: string): McpCatalogEntry | undefined {
+    return this.entries.find((entry) => entry.dynamicId === dynamicId);
+  }
+}
+
+export const mcpCatalog = new McpCatalogManager();
@@ -17,6 +17,10 @@ vi.mock('../../lib/ipc/mcpCommands', () => ({
   mcpDeletePermission: vi.fn(),
   mcpGetStrictMode: vi.fn().mockResolvedValue(false),
   mcpSetStrictMode: vi.fn().mockResolvedValue(true),
+  mcpHostStatus: vi.fn().mockResolvedValue(null),
+  mcpHostSetEnabled: vi.fn(),
+  mcpHostRotateToken: vi.fn(),
+  mcpResolveElicitation: vi.fn(),
 }));
 
 vi.mock('@tauri-apps/api/event', () => ({
@@ -117,6 +121,26 @@ describe('mcpService.refresh', () => {
   });
 });
 
+describe('mcpService host', () => {
+  const status = { enabled: true, url: null, token: 'tok', stdioCommand: ['/bin/asyar', 'mcp'] };
+
+  it('keeps the status returned when toggling', async () => {
+    (cmds.mcpHostSetEnabled as ReturnType<typeof vi.fn>).mockResolvedValue(status);
+    const svc = new McpService();
+    await svc.setHostEnabled(true);
+    expect(cmds.mcpHostSetEnabled).toHaveBeenCalledWith(true);
+    expect(svc.hostStatus).toEqual(status);
+  });
+
+  it('leaves the status alone when the command fails', async () => {
+    (cmds.mcpHostRotateToken as ReturnType<typeof vi.fn>).mockResolvedValue(null);
+    const svc = new McpService();
+    svc.hostStatus = status;
+    await svc.rotateHostToken();
+    expect(svc.hostStatus).toEqual(status);
+  });
+});
+
 describe('mcpService.install', () => {
   it('pushes the new server (verifies refreshServers ran)', async () => {
     const input = makeInput();
@@ -534,3 +558,55 @@ describe('mcpService status event listener', () => {
     expect(svc.servers[0].toolsCount).toBe(2);
   });
 });
+
+describe('mcpService server requests', () => {
+  const request = {
+    id: 'req-1',
+    serverId: 'jira',
+    serverName: 'Jira',
+    message: 'Who should the ticket go to?',
+    requestedSchema: {
+      type: 'object' as const,
+      properties: { assignee: { type: 'string' as const } },
+      required: ['assignee'],
+    },
+  };
+
+  it('keeps the form open with the reason when the answer is rejected', async () => {
+    vi.mocked(cmds.mcpResolveElicitation).mockRejectedValueOnce(
+      new Error("/: missing required property 'assignee'"),
+    );
+    const svc = new McpService();
+    expect(svc.showElicitation(request)).toBe(true);
+    expect(svc.showElicitation({ ...request, id: 'req-2' })).toBe(false);
+
+    await svc.submitElicitation('accept', {});
+    expect(svc.elicitationPrompt?.error).toContain('assignee');
+
+    vi.mocked(cmds.mcpResolveElicitation).mockResolvedValueOnce(true);
+    await svc.submitElicitation('accept', { assignee: 'sam' });
+    expect(cmds.mcpResolveElicitation).toHaveBeenLastCalledWith('req-1', 'accept', {
+      assignee: 'sam',
+    });
+    expect(svc.elicitationPrompt).toBeNull();
+  });
+
+  it('cancels a second sampling request while one is being asked about', async () => {
+    const svc = new McpService();
+    const sampling = {
+      id: 'req-1',
+      serverId: 'github',
+      serverName: 'GitHub',
+      systemPrompt: null,
+      messages: [],
+      maxTokens: 10,
+      needsApproval: true,
+    };
+    const first = svc.requestSampling(sampling);
+    await expect(svc.requestSampling({ ...sampling, id: 'req-2' })).resolves.toBe('cancel');
+
+    svc.handleSamplingDecision('allow_once');
+    await expect(first).resolves.toBe('allow_once');
+    expect(svc.samplingPrompt).toBeNull();
+  });
+});
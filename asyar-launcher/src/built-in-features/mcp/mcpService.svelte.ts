@@ -7,6 +7,10 @@ import type {
   McpToolDescriptor,
   McpPermissionRow,
   McpRuntimeConsentNeeded,
+  McpHostStatus,
+  McpSamplingRequest,
+  McpElicitationRequest,
+  McpElicitationAction,
 } from './types';
 import {
   mcpListServers,
@@ -22,7 +26,12 @@ import {
   mcpDeletePermission,
   mcpGetStrictMode,
   mcpSetStrictMode,
+  mcpHostStatus,
+  mcpHostSetEnabled,
+  mcpHostRotateToken,
+  mcpResolveElicitation,
 } from '../../lib/ipc/mcpCommands';
+import { extractErrorMessage } from '../../lib/errors';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { logService } from '../../services/log/logService';
 import { runtimeService } from '../../services/runtime/runtimeService.svelte';
@@ -49,12 +58,24 @@ export class McpService {
   permissions = $state<McpPermissionRow[]>([]);
   loading = $state<boolean>(false);
   strictMode = $state<boolean>(false);
+  /** Asyar's own MCP server; `null` until first loaded. */
+  hostStatus = $state<McpHostStatus | null>(null);
   permissionPrompt = $state<{
     serverId: string;
     toolId: string;
     agentId: string;
     resolve: (d: 'allow_once' | 'allow_always' | 'never' | 'cancel') => void;
   } | null>(null);
+  /** A server asking to run a completion through the user's AI provider. */
+  samplingPrompt = $state<{
+    request: McpSamplingRequest;
+    resolve: (d: 'allow_once' | 'allow_always' | 'never' | 'cancel') => void;
+  } | null>(null);
+  /** A server asking the user to fill in a form; `error` is the last rejected answer. */
+  elicitationPrompt = $state<{
+    request: McpElicitationRequest;
+    error: string | null;
+  } | null>(null);
   /** Set while `install`/`setEnabled` is waiting on user consent to download a missing runtime. */
   runtimeConsentPrompt = $state<{
     name: string;
@@ -98,7 +119,12 @@ export class McpService {
   async refresh(): Promise<void> {
     this.loading = true;
     try {
-      await Promise.all([this.refreshServers(), this.refreshAudit(), this.refreshStrictMode()]);
+      await Promise.all([
+        this.refreshServers(),
+        this.refreshAudit(),
+        this.refreshStrictMode(),
+        this.refreshHostStatus(),
+      ]);
       if (this.servers.length === 0) {
         await this.detectConfigs();
       }
@@ -119,6 +145,29 @@ export class McpService {
     }
   }
 
+  async refreshHostStatus(): Promise<void> {
+    const status = await mcpHostStatus();
+    if (status !== null) {
+      this.hostStatus = status;
+    }
+  }
+
+  /** Turns Asyar's MCP server on or off; the setting survives restarts. */
+  async setHostEnabled(enabled: boolean): Promise<void> {
+    const status = await mcpHostSetEnabled(enabled);
+    if (status !== null) {
+      this.hostStatus = status;
+    }
+  }
+
+  /** Issues a new client token; configured clients need the new one. */
+  async rotateHostToken(): Promise<void> {
+    const status = await mcpHostRotateToken();
+    if (status !== null) {
+      this.hostStatus = status;
+    }
+  }
+
   async refreshServers(): Promise<void> {
     const result = await mcpListServers();
     if (result !== null) {
@@ -275,6 +324,57 @@ export class McpService {
     if (!p) return;
     p.resolve(decision);
   }
+
+  requestSampling(
+    request: McpSamplingRequest,
+  ): Promise<'allow_once' | 'allow_always' | 'never' | 'cancel'> {
+    // One prompt at a time; a second request while one is open is cancelled.
+    if (this.samplingPrompt) return Promise.resolve('cancel');
+    return new Promise((resolve) => {
+      this.samplingPrompt = {
+        request,
+        resolve: (decision) => {
+          this.samplingPrompt = null;
+          resolve(decision);
+        },
+      };
+    });
+  }
+
+  handleSamplingDecision(decision: 'allow_once' | 'allow_always' | 'never' | 'cancel'): void {
+    this.samplingPrompt?.resolve(decision);
+  }
+
+  /** Shows the form; `false` when another one is already open. */
+  showElicitation(request: McpElicitationRequest): boolean {
+    if (this.elicitationPrompt) return false;
+    this.elicitationPrompt = { request, error: null };
+    return true;
+  }
+
+  /**
+   * Sends the user's answer. Content the server's schema rejects keeps the
+   * form open with the reason; anything else closes it.
+   */
+  async submitElicitation(
+    action: McpElicitationAction,
+    content?: Record<string, unknown>,
+  ): Promise<void> {
+    const p = this.elicitationPrompt;
+    if (!p) return;
+    try {
+      const delivered = await mcpResolveElicitation(p.request.id, action, content);
+      if (!delivered) {
+        logService.warn(`[mcp] ${p.request.serverName} stopped waiting for form input`);
+      }
+    } catch (err) {
+      if (this.elicitationPrompt?.request.id === p.request.id) {
+        this.elicitationPrompt = { ...p, error: extractErrorMessage(err) };
+      }
+      return;
+    }
+    if (this.elicitationPrompt?.request.id === p.request.id) this.elicitationPrompt = null;
+  }
 }
 
 export const mcpService = new McpService();
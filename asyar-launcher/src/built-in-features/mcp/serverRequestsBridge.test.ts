@@ -0,0 +1,128 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+
+vi.mock('@tauri-apps/api/event', () => ({
+  listen: vi.fn(async () => () => {}),
+}));
+
+vi.mock('../../lib/ipc/mcpCommands', () => ({
+  mcpResolveSampling: vi.fn(async () => true),
+  mcpResolveElicitation: vi.fn(async () => true),
+}));
+
+vi.mock('../../lib/ipc/commands', () => ({
+  showWindow: vi.fn(async () => {}),
+}));
+
+vi.mock('../../services/log/logService', () => ({
+  logService: { warn: vi.fn(), error: vi.fn(), info: vi.fn(), debug: vi.fn() },
+}));
+
+vi.mock('../../services/settings/settingsService.svelte', () => ({
+  settingsService: {
+    getSettings: vi.fn(() => ({
+      ai: {
+        temperature: 0.7,
+        providers: { anthropic: { enabled: true, apiKey: 'sk', temperature: 0.2 } },
+      },
+    })),
+  },
+}));
+
+vi.mock('../agents/agentService.svelte', () => ({
+  agentService: { getDefaultAgent: vi.fn() },
+}));
+
+vi.mock('./mcpService.svelte', () => ({
+  mcpService: { requestSampling: vi.fn(), showElicitation: vi.fn() },
+}));
+
+import {
+  answerElicitationRequest,
+  answerSamplingRequest,
+  samplingModelLabel,
+} from './serverRequestsBridge';
+import { mcpResolveElicitation, mcpResolveSampling } from '../../lib/ipc/mcpCommands';
+import { showWindow } from '../../lib/ipc/commands';
+import { agentService } from '../agents/agentService.svelte';
+import { mcpService } from './mcpService.svelte';
+import type { McpElicitationRequest, McpSamplingRequest } from './types';
+
+const sampling: McpSamplingRequest = {
+  id: 'req-1',
+  serverId: 'github',
+  serverName: 'GitHub',
+  systemPrompt: null,
+  messages: [{ role: 'user', text: 'Summarise the issue' }],
+  maxTokens: 100,
+  needsApproval: true,
+};
+
+const form: McpElicitationRequest = {
+  id: 'req-2',
+  serverId: 'jira',
+  serverName: 'Jira',
+  message: 'Who should the ticket go to?',
+  requestedSchema: { type: 'object', properties: { assignee: { type: 'string' } } },
+};
+
+beforeEach(() => {
+  vi.clearAllMocks();
+  vi.mocked(agentService.getDefaultAgent).mockReturnValue({
+    providerId: 'anthropic',
+    modelId: 'claude-test',
+  } as ReturnType<typeof agentService.getDefaultAgent>);
+});
+
+describe('answerSamplingRequest', () => {
+  it('asks the user and answers with the default agent’s route', async () => {
+    vi.mocked(mcpService.requestSampling).mockResolvedValue('allow_always');
+
+    await answerSamplingRequest(sampling);
+
+    expect(showWindow).toHaveBeenCalled();
+    expect(mcpService.requestSampling).toHaveBeenCalledWith(sampling);
+    expect(mcpResolveSampling).toHaveBeenCalledWith('req-1', 'allow_always', {
+      providerId: 'anthropic',
+      modelId: 'claude-test',
+      config: { enabled: true, apiKey: 'sk', temperature: 0.2 },
+      temperature: 0.2,
+    });
+  });
+
+  it('skips the prompt for servers that are already allowed', async () => {
+    await answerSamplingRequest({ ...sampling, needsApproval: false });
+
+    expect(showWindow).not.toHaveBeenCalled();
+    expect(mcpService.requestSampling).not.toHaveBeenCalled();
+    expect(mcpResolveSampling).toHaveBeenCalledWith('req-1', 'allow_once', expect.any(Object));
+  });
+
+  it('sends no route without a default agent', async () => {
+    vi.mocked(agentService.getDefaultAgent).mockReturnValue(null);
+
+    await answerSamplingRequest({ ...sampling, needsApproval: false });
+
+    expect(mcpResolveSampling).toHaveBeenCalledWith('req-1', 'allow_once', null);
+    expect(samplingModelLabel()).toBeNull();
+  });
+});
+
+describe('answerElicitationRequest', () => {
+  it('shows the form', async () => {
+    vi.mocked(mcpService.showElicitation).mockReturnValue(true);
+
+    await answerElicitationRequest(form);
+
+    expect(showWindow).toHaveBeenCalled();
+    expect(mcpService.showElicitation).toHaveBeenCalledWith(form);
+    expect(mcpResolveElicitation).not.toHaveBeenCalled();
+  });
+
+  it('cancels a request while another form is open', async () => {
+    vi.mocked(mcpService.showElicitation).mockReturnValue(false);
+
+    await answerElicitationRequest(form);
+
+    expect(mcpResolveElicitation).toHaveBeenCalledWith('req-2', 'cancel');
+  });
+});
@@ -0,0 +1,73 @@
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { mcpResolveElicitation, mcpResolveSampling } from '../../lib/ipc/mcpCommands';
+import { showWindow } from '../../lib/ipc/commands';
+import { logService } from '../../services/log/logService';
+import { settingsService } from '../../services/settings/settingsService.svelte';
+import { agentService } from '../agents/agentService.svelte';
+import { mcpService } from './mcpService.svelte';
+import type { McpElicitationRequest, McpSamplingRequest, McpSamplingRoute } from './types';
+
+export const SAMPLING_REQUEST_EVENT = 'asyar:mcp:sampling-request';
+export const ELICITATION_REQUEST_EVENT = 'asyar:mcp:elicitation-request';
+
+/** Sampling runs on the default agent's provider and model; `null` when none is set up. */
+export function samplingRoute(): McpSamplingRoute | null {
+  const agent = agentService.getDefaultAgent();
+  if (!agent) return null;
+  const ai = settingsService.getSettings().ai;
+  const config = ai.providers[agent.providerId];
+  if (!config) return null;
+  return {
+    providerId: agent.providerId,
+    modelId: agent.modelId,
+    config,
+    temperature: config.temperature ?? ai.temperature,
+  };
+}
+
+/** How the sampling prompt names the model a request would run on. */
+export function samplingModelLabel(): string | null {
+  const route = samplingRoute();
+  if (!route) return null;
+  return `${route.config.name ?? route.providerId} / ${route.modelId}`;
+}
+
+/**
+ * A connected server wants a completion. Servers the user already allowed
+ * get one straight away; otherwise the launcher is brought up and asked.
+ */
+export async function answerSamplingRequest(request: McpSamplingRequest): Promise<void> {
+  let choice: 'allow_once' | 'allow_always' | 'never' | 'cancel' = 'allow_once';
+  if (request.needsApproval) {
+    await showWindow();
+    choice = await mcpService.requestSampling(request);
+  }
+  const delivered = await mcpResolveSampling(request.id, choice, samplingRoute());
+  if (delivered === false) {
+    logService.warn(`[mcp] ${request.serverName} stopped waiting for a sampling decision`);
+  }
+}
+
+/** A connected server wants the user to fill in a form; one is shown at a time. */
+export async function answerElicitationRequest(request: McpElicitationRequest): Promise<void> {
+  await showWindow();
+  if (!mcpService.showElicitation(request)) {
+    await mcpResolveElicitation(request.id, 'cancel');
+  }
+}
+
+export async function startMcpServerRequestsBridge(): Promise<UnlistenFn> {
+  const unlistenSampling = await listen<McpSamplingRequest>(SAMPLING_REQUEST_EVENT, (event) => {
+    void answerSamplingRequest(event.payload);
+  });
+  const unlistenElicitation = await listen<McpElicitationRequest>(
+    ELICITATION_REQUEST_EVENT,
+    (event) => {
+      void answerElicitationRequest(event.payload);
+    },
+  );
+  return () => {
+    unlistenSampling();
+    unlistenElicitation();
+  };
+}
@@ -1,3 +1,6 @@
+import type { CommandArgument } from 'asyar-sdk/contracts';
+import type { ProviderConfig } from '../../services/ai/IProviderPlugin';
+
 export type McpTransportSpec =
   | {
       kind: 'stdio';
@@ -67,3 +70,135 @@ export interface McpPermissionRow {
   decision: 'allow_once' | 'allow_always' | 'never';
   setAt: number;
 }
+
+/** An entry from a server's `resources/list`. */
+export interface McpResource {
+  uri: string;
+  name: string;
+  title?: string | null;
+  description?: string | null;
+  mimeType?: string | null;
+}
+
+/** A parameterised (RFC 6570) resource URI from `resources/templates/list`. */
+export interface McpResourceTemplate {
+  uriTemplate: string;
+  name: string;
+  title?: string | null;
+  description?: string | null;
+  mimeType?: string | null;
+}
+
+/** One item of a `resources/read` result: `text`, or base64 `blob`. */
+export interface McpResourceContents {
+  uri: string;
+  mimeType?: string | null;
+  text?: string | null;
+  blob?: string | null;
+}
+
+export interface McpPrompt {
+  name: string;
+  title?: string | null;
+  description?: string | null;
+  arguments: { name: string; description?: string | null; required: boolean }[];
+}
+
+export interface McpPromptResult {
+  description?: string | null;
+  messages: { role: string; content: Record<string, unknown> }[];
+}
+
+/** A resource or prompt surfaced as a launcher command (`cmd_mcp_dyn_<dynamicId>`). */
+export interface McpCatalogEntry {
+  dynamicId: string;
+  kind: 'resource' | 'prompt';
+  serverId: string;
+  /** The resource URI or the prompt name. */
+  target: string;
+  title: string;
+  description: string | null;
+  mimeType: string | null;
+  arguments: CommandArgument[];
+}
+
+/** Asyar's own MCP server: whether it's on and how clients reach it. */
+export interface McpHostStatus {
+  enabled: boolean;
+  /** `null` while stopped. */
+  url: string | null;
+  token: string | null;
+  /** Executable and arguments for stdio clients. */
+  stdioCommand: string[];
+}
+
+/** Payload of `asyar:mcp-host:permission-request`. */
+export interface McpHostPermissionRequest {
+  id: string;
+  serverId: string;
+  toolId: string;
+  /** `mcp-client:<client name>`. */
+  agentId: string;
+  clientName: string;
+}
+
+/** Payload of `asyar:mcp-host:tool-dispatch`: a Tier-2 tool call from a client. */
+export interface McpHostToolDispatch {
+  id: string;
+  extensionId: string;
+  toolId: string;
+  arguments: unknown;
+}
+
+/** One message of a sampling request, flattened for the approval dialog. */
+export interface McpSamplingPreviewMessage {
+  role: 'user' | 'assistant';
+  text: string;
+}
+
+/** Payload of `asyar:mcp:sampling-request`: a server asks for a completion. */
+export interface McpSamplingRequest {
+  id: string;
+  serverId: string;
+  serverName: string;
+  systemPrompt: string | null;
+  messages: McpSamplingPreviewMessage[];
+  maxTokens: number;
+  /** `false` when the server is already allowed to sample. */
+  needsApproval: boolean;
+}
+
+/** The provider and model a sampling request runs on. */
+export interface McpSamplingRoute {
+  providerId: string;
+  modelId: string;
+  config: ProviderConfig;
+  temperature: number;
+}
+
+/** One field of an elicitation form. Only primitive types are allowed. */
+export interface McpElicitationField {
+  type: 'string' | 'number' | 'integer' | 'boolean';
+  title?: string;
+  description?: string;
+  enum?: string[];
+  enumNames?: string[];
+  default?: string | number | boolean;
+}
+
+export interface McpElicitationSchema {
+  type: 'object';
+  properties: Record<string, McpElicitationField>;
+  required?: string[];
+}
+
+/** Payload of `asyar:mcp:elicitation-request`: a server asks the user for input. */
+export interface McpElicitationRequest {
+  id: string;
+  serverId: string;
+  serverName: string;
+  message: string;
+  requestedSchema: McpElicitationSchema;
+}
+
+export type McpElicitationAction = 'accept' | 'decline' | 'cancel';
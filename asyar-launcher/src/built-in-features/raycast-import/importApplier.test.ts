@@ -55,7 +55,7 @@ function makeBundle(overrides: Partial<ImportBundle> = {}): ImportBundle {
     portals: [],
     shortcuts: [],
     aliases: [],
-    skipped: { hotkeys: 0, aliases: 0 },
+    skipped: { hotkeys: 0, aliases: 0, snippets: 0, placeholders: 0 },
     ...overrides,
   };
 }
@@ -92,6 +92,32 @@ describe('applyBundle', () => {
     expect(snippetService.syncToRust).toHaveBeenCalledTimes(1);
   });
 
+  it('carries Espanso regex and word options onto the stored snippet', async () => {
+    const bundle = makeBundle({
+      source: 'espanso',
+      snippets: [
+        {
+          name: 'Greet',
+          keyword: ':greet\\((?P<person>.*)\\)',
+          expansion: 'Hi ${person}!',
+          pinned: false,
+          trigger: 'regex',
+          expandMode: 'delimiter',
+        },
+        { name: 'Plain', keyword: ':p', expansion: 'p', pinned: false, trigger: 'literal' },
+      ],
+    });
+
+    await applyBundle(bundle, ALL);
+
+    const first = vi.mocked(snippetStore.add).mock.calls[0][0];
+    expect(first.trigger).toBe('regex');
+    expect(first.expandMode).toBe('delimiter');
+    const second = vi.mocked(snippetStore.add).mock.calls[1][0];
+    expect(second.trigger).toBeUndefined();
+    expect(second.expandMode).toBeUndefined();
+  });
+
   it('skips duplicate snippets by name+expansion', async () => {
     vi.mocked(snippetStore.getAll).mockReturnValue([
       { id: 'x', name: 'Sig', expansion: 'Best, John', createdAt: 1 },
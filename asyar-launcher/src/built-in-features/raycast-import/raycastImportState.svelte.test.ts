@@ -18,7 +18,7 @@ const BUNDLE: ImportBundle = {
   portals: [],
   shortcuts: [],
   aliases: [],
-  skipped: { hotkeys: 2, aliases: 1 },
+  skipped: { hotkeys: 2, aliases: 1, snippets: 0, placeholders: 0 },
 };
 
 describe('RaycastImportState', () => {
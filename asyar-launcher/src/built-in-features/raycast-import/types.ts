@@ -1,7 +1,14 @@
 // Wire types for the `raycast_import_parse` Tauri command
 // (see src-tauri/src/raycast_import/mod.rs — serde camelCase).
+import type { SnippetExpandMode, SnippetTriggerKind } from '../../lib/ipc/commands';
 
-export type SourceFormat = 'rayconfigX' | 'rayconfigClassic' | 'snippetsJson' | 'quicklinksJson';
+export type SourceFormat =
+  | 'rayconfigX'
+  | 'rayconfigClassic'
+  | 'snippetsJson'
+  | 'quicklinksJson'
+  | 'espanso'
+  | 'textExpander';
 
 export interface ImportSnippet {
   name: string;
@@ -9,6 +16,8 @@ export interface ImportSnippet {
   expansion: string;
   pinned: boolean;
   createdAt?: number;
+  trigger?: SnippetTriggerKind;
+  expandMode?: SnippetExpandMode;
 }
 
 export interface ImportPortal {
@@ -41,6 +50,10 @@ export interface ImportAlias {
 export interface SkippedCounts {
   hotkeys: number;
   aliases: number;
+  /** Espanso image/form/HTML matches and TextExpander script snippets. */
+  snippets: number;
+  /** Variables/macros with no Asyar placeholder equivalent. */
+  placeholders: number;
 }
 
 export interface ImportBundle {
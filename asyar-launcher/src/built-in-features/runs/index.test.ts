@@ -11,6 +11,8 @@ vi.mock('../../services/run/runService.svelte', () => ({
     selectedRunId: null,
     combined: [] as Array<{ id: string }>,
     moveSelection: vi.fn(),
+    canRerun: vi.fn().mockReturnValue(false),
+    rerun: vi.fn().mockResolvedValue(true),
   },
 }));
 
@@ -43,6 +45,7 @@ beforeEach(() => {
   vi.clearAllMocks();
   (runService as any).selectedRunId = null;
   (runService as any).combined = [];
+  vi.mocked(runService.canRerun).mockReturnValue(false);
 });
 
 describe('RunsExtension.executeCommand', () => {
@@ -79,6 +82,27 @@ describe('RunsExtension keyboard navigation', () => {
     expect(actionService.unregisterAction).toHaveBeenCalledWith('agents:open-run-in-chat');
   });
 
+  it('run again action reruns the selected run when it can be rerun', async () => {
+    const run = { id: 'r1', kind: 'shell-script', status: 'failed' };
+    (runService as any).combined = [run];
+    (runService as any).selectedRunId = 'r1';
+    await RunsExtension.viewActivated!('runs/RunView');
+
+    const action = vi
+      .mocked(actionService.registerAction)
+      .mock.calls.map(([a]) => a as any)
+      .find((a) => a.id === 'runs:rerun');
+    expect(action.visible()).toBe(false);
+
+    vi.mocked(runService.canRerun).mockReturnValue(true);
+    expect(action.visible()).toBe(true);
+    await action.execute();
+    expect(runService.rerun).toHaveBeenCalledWith(run);
+
+    await RunsExtension.viewDeactivated!('runs/RunView');
+    expect(actionService.unregisterAction).toHaveBeenCalledWith('runs:rerun');
+  });
+
   it('the registered conversation action opens the selected agent run', async () => {
     (runService as any).combined = [{ id: 'agent-run-1', kind: 'agent' }];
     (runService as any).selectedRunId = 'agent-run-1';
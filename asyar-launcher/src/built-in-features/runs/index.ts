@@ -1,4 +1,4 @@
-import type { Extension, ExtensionContext } from 'asyar-sdk/contracts';
+import type { Extension, ExtensionContext, Run } from 'asyar-sdk/contracts';
 import { ActionContext } from 'asyar-sdk/contracts';
 import RunView from './RunView.svelte';
 import { viewManager } from '../../services/extension/viewManager.svelte';
@@ -8,6 +8,7 @@ import { openAgentRunInChat } from '../agents/runNavigation';
 
 const CLEAR_RECENT_ACTION_ID = 'runs:clear-recent';
 const OPEN_AGENT_RUN_IN_CHAT_ACTION_ID = 'agents:open-run-in-chat';
+const RERUN_ACTION_ID = 'runs:rerun';
 
 class RunsExtension implements Extension {
   private inView = false;
@@ -63,6 +64,20 @@ class RunsExtension implements Extension {
       },
     } as ApplicationAction);
 
+    actionService.registerAction({
+      id: RERUN_ACTION_ID,
+      label: 'Run Again',
+      icon: 'icon:refresh',
+      description: 'Start the selected run again with the same inputs',
+      extensionId: 'runs',
+      context: ActionContext.EXTENSION_VIEW,
+      visible: () => this.selectedRerunnableRun() !== null,
+      execute: async () => {
+        const run = this.selectedRerunnableRun();
+        if (run) await runService.rerun(run);
+      },
+    } as ApplicationAction);
+
     // Initial history load might complete after registerAction
     runService.loadHistory().then(() => {
       actionService.refreshFiltered();
@@ -74,6 +89,12 @@ class RunsExtension implements Extension {
     this.inView = false;
     actionService.unregisterAction(CLEAR_RECENT_ACTION_ID);
     actionService.unregisterAction(OPEN_AGENT_RUN_IN_CHAT_ACTION_ID);
+    actionService.unregisterAction(RERUN_ACTION_ID);
+  }
+
+  private selectedRerunnableRun(): Run | null {
+    const selectedRun = runService.combined.find((run) => run.id === runService.selectedRunId);
+    return selectedRun && runService.canRerun(selectedRun) ? selectedRun : null;
   }
 
   private selectedAgentRunId(): string | null {
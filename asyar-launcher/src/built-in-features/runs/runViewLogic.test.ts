@@ -1,5 +1,5 @@
 import { describe, it, expect } from 'vitest';
-import { formatRunSubtitle } from './runViewLogic';
+import { ansiSpanStyle, formatRunSubtitle, hasAnsi } from './runViewLogic';
 import type { Run } from 'asyar-sdk/contracts';
 
 const makeRun = (over: Partial<Run> = {}): Run => ({
@@ -52,3 +52,34 @@ describe('formatRunSubtitle', () => {
     expect(result).toContain('Cancelled');
   });
 });
+
+// ── ANSI output ───────────────────────────────────────────────────────────────
+
+describe('hasAnsi', () => {
+  it('hasAnsi_detects_an_escape_anywhere_in_the_line', () => {
+    expect(hasAnsi('plain output')).toBe(false);
+    expect(hasAnsi('ok \x1b[32mPASS\x1b[0m')).toBe(true);
+  });
+});
+
+describe('ansiSpanStyle', () => {
+  it('ansiSpanStyle_is_empty_for_an_unstyled_span', () => {
+    expect(ansiSpanStyle({ text: 'x' })).toBe('');
+  });
+
+  it('ansiSpanStyle_maps_every_attribute_to_css', () => {
+    const style = ansiSpanStyle({
+      text: 'x',
+      fg: '#cd3131',
+      bg: '#000000',
+      bold: true,
+      italic: true,
+      underline: true,
+      strikethrough: true,
+    });
+    expect(style).toBe(
+      'color: #cd3131; background-color: #000000; font-weight: 600; font-style: italic; ' +
+        'text-decoration: underline line-through',
+    );
+  });
+});
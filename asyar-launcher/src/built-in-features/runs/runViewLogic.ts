@@ -1,4 +1,5 @@
 import type { Run } from 'asyar-sdk/contracts';
+import type { AnsiSpan } from '../../services/run/runService.svelte';
 import { formatElapsed } from '../../components/run/runningSectionLogic';
 
 const ERROR_MESSAGE_MAX = 60;
@@ -31,3 +32,23 @@ export function formatRunSubtitle(run: Run): string {
       return 'Cancelled';
   }
 }
+
+/** Whether `line` carries escape sequences worth asking Rust to render. */
+export function hasAnsi(line: string): boolean {
+  return line.includes('\x1b');
+}
+
+/** Inline CSS for one rendered output span. */
+export function ansiSpanStyle(span: AnsiSpan): string {
+  const rules: string[] = [];
+  if (span.fg) rules.push(`color: ${span.fg}`);
+  if (span.bg) rules.push(`background-color: ${span.bg}`);
+  if (span.bold) rules.push('font-weight: 600');
+  if (span.dim) rules.push('opacity: 0.6');
+  if (span.italic) rules.push('font-style: italic');
+  const lines = [span.underline && 'underline', span.strikethrough && 'line-through'].filter(
+    Boolean,
+  );
+  if (lines.length) rules.push(`text-decoration: ${lines.join(' ')}`);
+  return rules.join('; ');
+}
@@ -4,8 +4,36 @@ vi.mock('../../services/shell/shellService.svelte', () => ({
   shellService: { spawn: vi.fn(async () => ({ streaming: true })) },
 }));
 
+const hud = vi.hoisted(() => ({
+  replace: vi.fn(async () => {}),
+  dismiss: vi.fn(async () => {}),
+}));
+
 vi.mock('../../services/feedback/feedbackService.svelte', () => ({
-  feedbackService: { report: vi.fn(async () => {}) },
+  feedbackService: { report: vi.fn(async () => {}), showHUDSpinning: vi.fn(() => hud) },
+}));
+
+vi.mock('../../services/run/runService.svelte', () => ({
+  runService: {
+    selectedRunId: null,
+    stripAnsi: vi.fn(async (line: string) => line.replace(/\x1b\[[0-9;]*m/g, '')),
+  },
+}));
+
+vi.mock('../../services/selection/selectionService', () => ({
+  selectionService: { getSelectedText: vi.fn(async () => 'selected words') },
+}));
+
+vi.mock('tauri-plugin-clipboard-x-api', () => ({
+  readText: vi.fn(async () => 'copied words'),
+}));
+
+vi.mock('../../services/window/windowService', () => ({
+  windowService: { hide: vi.fn(async () => {}) },
+}));
+
+vi.mock('../../services/log/logService', () => ({
+  logService: { warn: vi.fn() },
 }));
 
 vi.mock('./scriptsManager.svelte', () => ({
@@ -18,6 +46,9 @@ import { dispatchScriptCommand } from './dispatch';
 import { shellService } from '../../services/shell/shellService.svelte';
 import { feedbackService } from '../../services/feedback/feedbackService.svelte';
 import { scriptsManager } from './scriptsManager.svelte';
+import { runService } from '../../services/run/runService.svelte';
+import { windowService } from '../../services/window/windowService';
+import type { ShellSpawnHooks } from '../../services/shell/shellService.svelte';
 import type { ScannedScript } from './types';
 
 const mockScript: ScannedScript = {
@@ -39,8 +70,13 @@ const mockScript: ScannedScript = {
 
 beforeEach(() => {
   vi.clearAllMocks();
+  runService.selectedRunId = null;
 });
 
+function spawnHooks(): ShellSpawnHooks {
+  return vi.mocked(shellService.spawn).mock.calls[0][8] as ShellSpawnHooks;
+}
+
 describe('dispatchScriptCommand', () => {
   it('dispatch_with_known_id_calls_shell_spawn', async () => {
     vi.mocked(scriptsManager.getScriptByDynamicId).mockReturnValue(mockScript);
@@ -55,6 +91,8 @@ describe('dispatchScriptCommand', () => {
       undefined,
       'cmd_scripts_dyn_dyn123',
       'My Script',
+      undefined,
+      expect.any(Object),
     );
     expect(feedbackService.report).not.toHaveBeenCalled();
   });
@@ -133,6 +171,8 @@ describe('dispatchScriptCommand', () => {
       undefined,
       'cmd_scripts_dyn_dyn123',
       'My Script',
+      undefined,
+      expect.any(Object),
     );
   });
 
@@ -159,6 +199,99 @@ describe('dispatchScriptCommand', () => {
       undefined,
       'cmd_scripts_dyn_dyn123',
       'My Script',
+      undefined,
+      expect.any(Object),
     );
   });
+
+  it('dispatch_percent_encodes_the_arguments_the_header_marks', async () => {
+    vi.mocked(scriptsManager.getScriptByDynamicId).mockReturnValue({
+      ...mockScript,
+      header: {
+        ...mockScript.header,
+        arguments: [
+          { name: 'argument1', type: 'text', required: true },
+          { name: 'argument2', type: 'text', required: true },
+        ],
+        percentEncoded: ['argument1'],
+      },
+    });
+
+    await dispatchScriptCommand('dyn123', { argument1: 'a b&c', argument2: 'a b' });
+
+    expect(vi.mocked(shellService.spawn).mock.calls[0][2]).toEqual(['a%20b%26c', 'a b']);
+  });
+
+  it('dispatch_passes_requested_launcher_state_as_env', async () => {
+    vi.mocked(scriptsManager.getScriptByDynamicId).mockReturnValue({
+      ...mockScript,
+      header: { ...mockScript.header, env: ['selection', 'clipboard'] },
+    });
+
+    await dispatchScriptCommand('dyn123', undefined);
+
+    expect(spawnHooks().env).toEqual({
+      ASYAR_SELECTION: 'selected words',
+      ASYAR_CLIPBOARD: 'copied words',
+    });
+  });
+
+  it('dispatch_sets_no_env_when_the_header_requests_none', async () => {
+    vi.mocked(scriptsManager.getScriptByDynamicId).mockReturnValue(mockScript);
+
+    await dispatchScriptCommand('dyn123', undefined);
+
+    expect(spawnHooks().env).toBeUndefined();
+  });
+
+  it('silent_mode_hides_the_launcher_and_shows_the_last_line_in_a_hud', async () => {
+    vi.mocked(scriptsManager.getScriptByDynamicId).mockReturnValue({
+      ...mockScript,
+      header: { ...mockScript.header, mode: 'silent' },
+    });
+
+    await dispatchScriptCommand('dyn123', undefined);
+    expect(windowService.hide).toHaveBeenCalled();
+    expect(feedbackService.showHUDSpinning).toHaveBeenCalledWith('My Script');
+
+    spawnHooks().onExit?.({ ok: true, lastLine: '\x1b[32mDeployed\x1b[0m' });
+    await vi.waitFor(() => expect(hud.replace).toHaveBeenCalledWith('Deployed'));
+  });
+
+  it('silent_mode_reports_a_failure_in_the_hud', async () => {
+    vi.mocked(scriptsManager.getScriptByDynamicId).mockReturnValue({
+      ...mockScript,
+      header: { ...mockScript.header, mode: 'silent' },
+    });
+
+    await dispatchScriptCommand('dyn123', undefined);
+    spawnHooks().onExit?.({ ok: false, lastLine: null, error: 'exit code 1' });
+
+    await vi.waitFor(() =>
+      expect(hud.replace).toHaveBeenCalledWith('⚠️ My Script failed', { durationMs: 3000 }),
+    );
+  });
+
+  it('full_output_mode_returns_run_view_with_the_run_selected', async () => {
+    vi.mocked(scriptsManager.getScriptByDynamicId).mockReturnValue({
+      ...mockScript,
+      header: { ...mockScript.header, mode: 'fullOutput' },
+    });
+    vi.mocked(shellService.spawn).mockImplementationOnce(async (...args: unknown[]) => {
+      (args[8] as ShellSpawnHooks).onRunStarted?.('run-42');
+      return { streaming: true as const };
+    });
+
+    const result = await dispatchScriptCommand('dyn123', undefined);
+
+    expect(result).toEqual({ type: 'view', viewPath: 'runs/RunView' });
+    expect(runService.selectedRunId).toBe('run-42');
+  });
+
+  it('compact_mode_returns_nothing_so_the_launcher_hides', async () => {
+    vi.mocked(scriptsManager.getScriptByDynamicId).mockReturnValue(mockScript);
+
+    expect(await dispatchScriptCommand('dyn123', undefined)).toBeUndefined();
+    expect(feedbackService.showHUDSpinning).not.toHaveBeenCalled();
+  });
 });
@@ -1,13 +1,38 @@
-import { shellService } from '../../services/shell/shellService.svelte';
-import { feedbackService } from '../../services/feedback/feedbackService.svelte';
+import { readText } from 'tauri-plugin-clipboard-x-api';
+import {
+  shellService,
+  type ShellExitResult,
+  type ShellSpawnHooks,
+} from '../../services/shell/shellService.svelte';
+import {
+  feedbackService,
+  type HudSpinnerHandle,
+} from '../../services/feedback/feedbackService.svelte';
+import { runService } from '../../services/run/runService.svelte';
+import { selectionService } from '../../services/selection/selectionService';
+import type { BuiltinDispatchView } from '../../services/extension/builtinDynamicDispatchers';
+import { windowService } from '../../services/window/windowService';
+import { logService } from '../../services/log/logService';
 import { scriptsManager } from './scriptsManager.svelte';
+import type { ScriptEnv } from './types';
 
 const SCRIPTS_EXTENSION_ID = 'scripts';
+const RUN_VIEW = 'runs/RunView';
 
+/** The variable each `@asyar.env` name is passed as. */
+const ENV_VARIABLES: Record<ScriptEnv, string> = {
+  selection: 'ASYAR_SELECTION',
+  clipboard: 'ASYAR_CLIPBOARD',
+};
+
+/**
+ * Run a script with the arguments the launcher collected. A `fullOutput`
+ * script returns RunView, already showing its run, for the caller to open.
+ */
 export async function dispatchScriptCommand(
   dynamicId: string,
   args: Record<string, unknown> | undefined,
-): Promise<void> {
+): Promise<BuiltinDispatchView | void> {
   const script = scriptsManager.getScriptByDynamicId(dynamicId);
   if (!script) {
     feedbackService.report({
@@ -25,21 +50,88 @@ export async function dispatchScriptCommand(
       ? (args as { arguments: Record<string, unknown> }).arguments
       : args) ?? {};
 
+  const percentEncoded = new Set(script.header.percentEncoded ?? []);
   const argsArray = script.header.arguments.map((argSpec) => {
     const value = (argMap as Record<string, unknown>)[argSpec.name];
-    return value !== undefined && value !== null ? String(value) : '';
+    const text = value !== undefined && value !== null ? String(value) : '';
+    return percentEncoded.has(argSpec.name) ? encodeURIComponent(text) : text;
   });
 
   const spawnId = crypto.randomUUID();
   const subjectId = `cmd_scripts_dyn_${dynamicId}`;
   const label = script.displayName;
-  await shellService.spawn(
-    SCRIPTS_EXTENSION_ID,
-    script.absolutePath,
-    argsArray,
-    spawnId,
-    undefined,
-    subjectId,
-    label,
-  );
+
+  const hooks: ShellSpawnHooks = {};
+  if (script.header.env?.length) {
+    hooks.env = await captureEnv(script.header.env);
+  }
+
+  let spinner: HudSpinnerHandle | null = null;
+  if (script.header.mode === 'silent') {
+    try {
+      await windowService.hide();
+    } catch {
+      // The launcher may already be hidden for a global-hotkey invocation.
+    }
+    spinner = feedbackService.showHUDSpinning(label);
+    const hud = spinner;
+    hooks.onExit = (result) => void showSilentResult(hud, label, result);
+  }
+  let runId: string | null = null;
+  hooks.onRunStarted = (id) => {
+    runId = id;
+  };
+
+  try {
+    await shellService.spawn(
+      SCRIPTS_EXTENSION_ID,
+      script.absolutePath,
+      argsArray,
+      spawnId,
+      undefined,
+      subjectId,
+      label,
+      undefined,
+      hooks,
+    );
+  } catch (err) {
+    // Consent denied or the path didn't resolve: nothing ran to report on.
+    await spinner?.dismiss();
+    throw err;
+  }
+
+  if (script.header.mode === 'fullOutput' && runId) {
+    runService.selectedRunId = runId;
+    return { type: 'view', viewPath: RUN_VIEW };
+  }
+}
+
+/** Read what the script asked for. Missing or unreadable state is passed as empty. */
+async function captureEnv(names: ScriptEnv[]): Promise<Record<string, string>> {
+  const env: Record<string, string> = {};
+  for (const name of names) {
+    try {
+      const text =
+        name === 'selection' ? await selectionService.getSelectedText() : await readText();
+      env[ENV_VARIABLES[name]] = text ?? '';
+    } catch (cause) {
+      logService.warn(`[scripts] could not read ${name} for ${ENV_VARIABLES[name]}: ${cause}`);
+      env[ENV_VARIABLES[name]] = '';
+    }
+  }
+  return env;
+}
+
+/** Silent mode: the script's last output line is its result. */
+async function showSilentResult(
+  spinner: HudSpinnerHandle,
+  label: string,
+  result: ShellExitResult,
+): Promise<void> {
+  const line = result.lastLine ? (await runService.stripAnsi(result.lastLine)).trim() : '';
+  if (result.ok) {
+    await spinner.replace(line || `✓ ${label}`);
+  } else {
+    await spinner.replace(`⚠️ ${line || `${label} failed`}`, { durationMs: 3000 });
+  }
 }
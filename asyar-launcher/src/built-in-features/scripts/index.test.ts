@@ -79,6 +79,13 @@ describe('ScriptsExtension', () => {
     expect(dispatchScriptCommand).toHaveBeenCalledWith('some-dynamic-id', args);
   });
 
+  it('executeCommand_returns_the_view_a_full_output_script_opens', async () => {
+    const view = { type: 'view' as const, viewPath: 'runs/RunView' };
+    vi.mocked(dispatchScriptCommand).mockResolvedValueOnce(view);
+
+    expect(await ScriptsExtension.executeCommand('some-dynamic-id')).toEqual(view);
+  });
+
   it('executeCommand_opens_the_script_library', async () => {
     const result = await ScriptsExtension.executeCommand('script-library');
 
@@ -50,8 +50,7 @@ class ScriptsExtension implements Extension {
     if (commandId === 'script-library') {
       return { type: 'view', viewPath: LIBRARY_VIEW };
     }
-    await dispatchScriptCommand(commandId, args);
-    return { type: 'no-view' };
+    return (await dispatchScriptCommand(commandId, args)) ?? { type: 'no-view' };
   }
 
   private registerLibraryActions(): void {
@@ -1,6 +1,7 @@
 import { scriptsManager } from './scriptsManager.svelte';
 import { dispatchScriptCommand } from './dispatch';
 import { commandArgumentsService } from '../../services/search/commandArguments';
+import { viewManager } from '../../services/extension/viewManager.svelte';
 
 /** Run whatever the script library has selected, prompting for arguments
  *  first when the script declares any. Lives outside index.ts so the view
@@ -12,5 +13,6 @@ export async function runSelectedScript(): Promise<void> {
     const entered = await commandArgumentsService.enter(`cmd_scripts_dyn_${script.dynamicId}`);
     if (entered) return;
   }
-  await dispatchScriptCommand(script.dynamicId, undefined);
+  const view = await dispatchScriptCommand(script.dynamicId, undefined);
+  if (view) viewManager.navigateToView(view.viewPath);
 }
@@ -3,12 +3,14 @@ import type { CommandArgument } from 'asyar-sdk/contracts';
 /**
  * Execution mode declared by `# @asyar.mode <value>` in a script header.
  * Mirrors Raycast's `mode` directive. `compact` is the default when the
- * directive is absent. Only `inline` is fully wired today; the rest are
- * accepted-and-stored for forward compatibility with the rest of Raycast's
- * mode taxonomy.
+ * directive is absent; `silent` shows the last output line in a HUD,
+ * `fullOutput` opens the run in RunView, and `inline` ticks on a timer.
  */
 export type ScriptMode = 'silent' | 'compact' | 'fullOutput' | 'inline';
 
+/** Launcher state a script asks for with `# @asyar.env`. */
+export type ScriptEnv = 'selection' | 'clipboard';
+
 export interface ParsedScriptHeader {
   title: string | null;
   icon: string | null;
@@ -20,6 +22,10 @@ export interface ParsedScriptHeader {
   refreshTimeSeconds: number | null;
   /** True iff the declared refreshTime was below 10s and got clamped. */
   refreshTimeClamped: boolean;
+  /** Arguments to percent-encode before they reach the script (Raycast's `percentEncoded`). */
+  percentEncoded?: string[];
+  /** From `@asyar.env`; each is set as `ASYAR_<NAME>` when the script runs. */
+  env?: ScriptEnv[];
 }
 
 export interface ScannedScript {
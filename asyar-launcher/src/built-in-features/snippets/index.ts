@@ -152,6 +152,9 @@ class SnippetsExtension implements Extension {
           keyword: newKeyword,
           expansion: s.expansion,
           createdAt: Date.now(),
+          trigger: s.trigger,
+          expandMode: s.expandMode,
+          appScope: s.appScope,
         };
         snippetStore.add(dup);
         await snippetService.syncToRust();
@@ -163,6 +163,59 @@ describe('syncToRust', () => {
     });
   });
 
+  it('routes snippets with matching options through sync_snippet_rules_to_rust', async () => {
+    mockGetAll.mockReturnValue([
+      { id: '1', keyword: ';addr', expansion: '123 Main St', name: 'Address', createdAt: 0 },
+      {
+        id: '2',
+        keyword: ';mail(\\w+)',
+        expansion: '$1@company.com',
+        name: 'Mail',
+        createdAt: 0,
+        trigger: 'regex',
+        expandMode: 'delimiter',
+      },
+    ]);
+    await snippetService.syncToRust();
+    expect(mockInvoke).toHaveBeenCalledWith('sync_snippets_to_rust', {
+      snippets: [[';addr', '123 Main St']],
+    });
+    expect(mockInvoke).toHaveBeenCalledWith('sync_snippet_rules_to_rust', {
+      rules: [
+        {
+          id: '2',
+          keyword: ';mail(\\w+)',
+          expansion: '$1@company.com',
+          trigger: 'regex',
+          expandMode: 'delimiter',
+          appScope: undefined,
+        },
+      ],
+    });
+  });
+
+  it('warns about each rule Rust could not compile, by snippet name', async () => {
+    mockGetAll.mockReturnValue([
+      {
+        id: '7',
+        keyword: '(?<=x)y',
+        expansion: 'z',
+        name: 'Lookbehind',
+        createdAt: 0,
+        trigger: 'regex',
+      },
+    ]);
+    mockInvoke.mockImplementation(async (cmd: string) =>
+      cmd === 'sync_snippet_rules_to_rust'
+        ? [{ snippetId: '7', message: 'look-around is not supported' }]
+        : undefined,
+    );
+    await snippetService.syncToRust();
+    expect(mockWarn).toHaveBeenCalledWith(
+      'Snippet "Lookbehind" will not expand: look-around is not supported',
+    );
+  });
+
   it('passes an empty array when the store has no snippets', async () => {
     await snippetService.syncToRust();
     expect(mockInvoke).toHaveBeenCalledWith('sync_snippets_to_rust', { snippets: [] });
@@ -1,5 +1,5 @@
 import { writeText } from 'tauri-plugin-clipboard-x-api';
-import { snippetStore } from './snippetStore.svelte';
+import { snippetStore, type Snippet } from './snippetStore.svelte';
 import * as commands from '../../lib/ipc/commands';
 import { createPersistence } from '../../lib/persistence/extensionStore';
 import { logService } from '../../services/log/logService';
@@ -61,11 +61,30 @@ export const snippetService = {
   },
 
   async syncToRust(): Promise<void> {
-    const pairs = snippetStore
-      .getAll()
-      .filter((s) => s.keyword)
+    const withKeyword = snippetStore.getAll().filter((s) => s.keyword);
+    // Snippets with matching options go through the rule path; the rest keep
+    // the plain keyword → expansion map.
+    const hasOptions = (s: Snippet) =>
+      (s.trigger ?? 'literal') !== 'literal' ||
+      (s.expandMode ?? 'immediate') !== 'immediate' ||
+      !!s.appScope;
+    const pairs = withKeyword
+      .filter((s) => !hasOptions(s))
       .map((s) => [s.keyword!, s.expansion] as [string, string]);
     await commands.syncSnippetsToRust(pairs);
+    const rules = withKeyword.filter(hasOptions).map((s) => ({
+      id: s.id,
+      keyword: s.keyword!,
+      expansion: s.expansion,
+      trigger: s.trigger,
+      expandMode: s.expandMode,
+      appScope: s.appScope,
+    }));
+    const failed = (await commands.syncSnippetRulesToRust(rules)) ?? [];
+    for (const { snippetId, message } of failed) {
+      const name = withKeyword.find((s) => s.id === snippetId)?.name ?? snippetId;
+      logService.warn(`Snippet "${name}" will not expand: ${message}`);
+    }
   },
 
   async setEnabled(enabled: boolean): Promise<{ ok: boolean; error?: string }> {
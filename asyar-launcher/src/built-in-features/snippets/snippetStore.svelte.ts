@@ -4,6 +4,9 @@ import {
   snippetRemove,
   snippetTogglePin,
   snippetClearAll,
+  type SnippetAppScope,
+  type SnippetExpandMode,
+  type SnippetTriggerKind,
 } from '../../lib/ipc/commands';
 import { logService } from '../../services/log/logService';
 import { feedbackService } from '../../services/feedback/feedbackService.svelte';
@@ -31,6 +34,12 @@ export interface Snippet {
    * original expansion at rest and returns it decrypted for use and editing.
    */
   redactedKinds?: string[];
+  /** `regex` treats `keyword` as an end-anchored pattern; `$1` etc. in `expansion`. */
+  trigger?: SnippetTriggerKind;
+  /** `delimiter` waits for a space or punctuation after the keyword. */
+  expandMode?: SnippetExpandMode;
+  /** Limit expansion to, or away from, specific applications. */
+  appScope?: SnippetAppScope;
 }
 
 /**
@@ -29,6 +29,8 @@ export interface ManifestCommand {
   requireAnyOf?: string[];
   schedule?: {
     intervalSeconds?: number;
+    cron?: string;
+    timezone?: string;
   };
   searchable?: boolean;
 }
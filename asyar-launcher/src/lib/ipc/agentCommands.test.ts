@@ -57,12 +57,22 @@ describe('agent runner commands', () => {
 
   it('starts a persistent Rust agent run with the complete contract', async () => {
     const onEvent = {} as Channel<AgentStreamEvent>;
-    await agentsRunThread('agent-1', 'thread-1', 'hello', 'run-1', config, 'stream-1', onEvent);
+    await agentsRunThread(
+      'agent-1',
+      'thread-1',
+      'hello',
+      [],
+      'run-1',
+      config,
+      'stream-1',
+      onEvent,
+    );
 
     expect(invokeRaw).toHaveBeenCalledWith('agents_run_thread', {
       agentId: 'agent-1',
       threadId: 'thread-1',
       userText: 'hello',
+      attachments: [],
       runId: 'run-1',
       config,
       streamId: 'stream-1',
@@ -71,11 +81,12 @@ describe('agent runner commands', () => {
   });
 
   it('starts an ephemeral Rust run using the stored agent id', async () => {
-    vi.mocked(invokeRaw).mockResolvedValueOnce('answer');
+    const output = { text: 'answer', structured: null };
+    vi.mocked(invokeRaw).mockResolvedValueOnce(output);
 
     const onEvent = {} as Channel<AgentStreamEvent>;
     await expect(agentsRunSilent('agent-1', 'hello', config, 'stream-2', onEvent)).resolves.toBe(
-      'answer',
+      output,
     );
     expect(invokeRaw).toHaveBeenCalledWith('agents_run_silent', {
       agentId: 'agent-1',
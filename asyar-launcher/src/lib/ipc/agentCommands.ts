@@ -8,7 +8,12 @@ import type {
   ProviderId,
   ReasoningEffort,
 } from '../../services/ai/IProviderPlugin';
-import type { AgentProviderDescriptor, ModelInfo as ModelInfoContract } from '../../bindings';
+import type {
+  AgentProviderDescriptor,
+  ContentPart,
+  ModelInfo as ModelInfoContract,
+  RoutingPolicy,
+} from '../../bindings';
 
 // ── Agents ────────────────────────────────────────────────────────────────────
 
@@ -87,10 +92,114 @@ export async function agentsMessageInsert(
 
 export async function agentsMessagesList(
   threadId: string,
-): Promise<import('../../built-in-features/agents/types').MessageDef[] | null> {
+): Promise<import('../../built-in-features/agents/types').PathMessageDef[] | null> {
   return invokeSafe('agents_messages_list', { threadId });
 }
 
+export async function agentsThreadSummary(
+  threadId: string,
+): Promise<import('../../built-in-features/agents/types').ThreadSummaryDef | null> {
+  return invokeSafe('agents_thread_summary', { threadId });
+}
+
+export async function agentsMessageEdit(
+  threadId: string,
+  messageId: string,
+  text: string,
+): Promise<import('../../built-in-features/agents/types').MessageDef | null> {
+  return invokeSafe('agents_message_edit', { threadId, messageId, text });
+}
+
+export async function agentsThreadRegenerate(threadId: string): Promise<boolean> {
+  return invokeSafeVoid('agents_thread_regenerate', { threadId });
+}
+
+export async function agentsThreadSelectBranch(
+  threadId: string,
+  messageId: string,
+): Promise<boolean> {
+  return invokeSafeVoid('agents_thread_select_branch', { threadId, messageId });
+}
+
+export async function agentsMemoriesList(
+  agentId: string | null,
+): Promise<import('../../built-in-features/agents/types').MemoryDef[] | null> {
+  return invokeSafe('agents_memories_list', { agentId });
+}
+
+export async function agentsMemoryDelete(id: string): Promise<boolean> {
+  return invokeSafeVoid('agents_memory_delete', { id });
+}
+
+export async function agentsTriggersList(
+  agentId: string | null,
+): Promise<import('../../built-in-features/agents/types').AgentTriggerDef[] | null> {
+  return invokeSafe('agents_triggers_list', { agentId });
+}
+
+export async function agentsTriggerSave(
+  input: import('../../built-in-features/agents/types').AgentTriggerInput,
+): Promise<import('../../built-in-features/agents/types').AgentTriggerDef | null> {
+  return invokeSafe('agents_trigger_save', { input });
+}
+
+export async function agentsTriggerDelete(id: string): Promise<boolean> {
+  return invokeSafeVoid('agents_trigger_delete', { id });
+}
+
+export async function agentsToolPolicyGet(
+  agentId: string,
+): Promise<import('../../built-in-features/agents/types').AgentToolPolicy | null> {
+  return invokeSafe('agents_tool_policy_get', { agentId });
+}
+
+export async function agentsToolPolicySet(
+  agentId: string,
+  policy: import('../../built-in-features/agents/types').AgentToolPolicy,
+): Promise<import('../../built-in-features/agents/types').AgentToolPolicy | null> {
+  return invokeSafe('agents_tool_policy_set', { agentId, policy });
+}
+
+export async function agentsToolAuditList(
+  agentId: string | null,
+  limit?: number,
+): Promise<import('../../built-in-features/agents/types').AgentToolAuditDef[] | null> {
+  return invokeSafe('agents_tool_audit_list', { agentId, limit });
+}
+
+/** Answers a confirm-mode write; `false` when it was no longer waiting. */
+export async function agentsToolWriteResolve(
+  id: string,
+  approved: boolean,
+): Promise<boolean | null> {
+  return invokeSafe('agents_tool_write_resolve', { id, approved });
+}
+
+export async function agentsThreadUsage(
+  threadId: string,
+): Promise<import('../../built-in-features/agents/types').UsageDef[] | null> {
+  return invokeSafe('agents_thread_usage', { threadId });
+}
+
+export async function agentsSpendSummary(
+  group: import('../../bindings').SpendGroup,
+  sinceMs: number,
+  untilMs: number,
+): Promise<import('../../built-in-features/agents/types').SpendRowDef[] | null> {
+  return invokeSafe('agents_spend_summary', { group, sinceMs, untilMs });
+}
+
+/** Reads a file from disk into an attachment part for `agentsRunThread`. */
+export async function agentsAttachmentFromFile(path: string): Promise<ContentPart> {
+  return invokeRaw<ContentPart>('agents_attachment_from_file', { path });
+}
+
+export async function agentsRunAttempts(
+  runId: string,
+): Promise<import('../../built-in-features/agents/types').AttemptDef[] | null> {
+  return invokeSafe('agents_run_attempts', { runId });
+}
+
 export async function agentsToolsRegisterTier2(
   extensionId: string,
   tools: import('asyar-sdk/contracts').ManifestTool[],
@@ -136,6 +245,10 @@ export interface AgentEditorForm {
   silent: boolean;
   inputSource: import('../../built-in-features/agents/types').SilentInputSource;
   outputAction: import('../../built-in-features/agents/types').SilentOutputAction;
+  monthlyBudgetUsd: number | null;
+  routing: RoutingPolicy;
+  /** JSON Schema text; empty means free-text answers. */
+  outputSchema: string;
 }
 
 export interface AgentEditorViewModel {
@@ -199,6 +312,43 @@ export async function agentsProviderRemovalBlockers(
   });
 }
 
+/** Writes the selected agents to `path` as a portable bundle; resolves to the number exported. */
+export async function agentsExportBundle(
+  agentIds: string[],
+  configs: Record<string, ProviderConfig>,
+  path: string,
+): Promise<number> {
+  return invokeRaw('agents_export_bundle', { agentIds, configs, path });
+}
+
+export async function agentsImportPreview(
+  path: string,
+  defaultAgentId: string | null,
+  providers: IProviderPlugin[],
+  configs: Record<string, ProviderConfig>,
+): Promise<import('../../built-in-features/agents/types').AgentImportReport> {
+  return invokeRaw('agents_import_preview', {
+    path,
+    defaultAgentId,
+    providers: toAgentProviderDescriptors(providers),
+    configs,
+  });
+}
+
+export async function agentsImportBundle(
+  path: string,
+  defaultAgentId: string | null,
+  providers: IProviderPlugin[],
+  configs: Record<string, ProviderConfig>,
+): Promise<import('../../built-in-features/agents/types').AgentImportReport> {
+  return invokeRaw('agents_import_bundle', {
+    path,
+    defaultAgentId,
+    providers: toAgentProviderDescriptors(providers),
+    configs,
+  });
+}
+
 export async function agentsEditorListModels(
   providerId: string,
   config: ProviderConfig,
@@ -40,6 +40,7 @@ export * from './onboardingCommands';
 export * from './privacyCommands';
 export * from './scriptCommands';
 export * from './agentCommands';
+export * from './semanticIndexCommands';
 export * from './feedbackCommands';
 export * from './usageCommands';
 export * from './systemActionCommands';
@@ -192,14 +192,34 @@ export interface ScheduledTaskInfo {
   extensionName: string;
   commandId: string;
   commandName: string;
-  intervalSeconds: number;
+  intervalSeconds: number | null;
+  cron: string | null;
+  /** IANA zone the cron expression is read in (resolved when the manifest omits it). */
+  timezone: string | null;
   active: boolean;
+  /** Unix millis of the next cron fire; interval timers report `null`. */
+  nextFireAt: number | null;
 }
 
 export async function getScheduledTasks(): Promise<ScheduledTaskInfo[] | null> {
   return invokeSafe<ScheduledTaskInfo[]>('get_scheduled_tasks');
 }
 
+export interface UpcomingFire {
+  commandId: string;
+  commandName: string;
+  /** Unix millis. */
+  at: number;
+}
+
+/** Next cron fires of one extension, soonest first. Interval schedules are not projected. */
+export async function getExtensionUpcomingFires(
+  extensionId: string,
+  limit?: number,
+): Promise<UpcomingFire[] | null> {
+  return invokeSafe<UpcomingFire[]>('get_extension_upcoming_fires', { extensionId, limit });
+}
+
 // -- Theme types --
 
 export interface ThemeFontEntry {
@@ -1,8 +1,12 @@
 import { describe, it, expect, vi, beforeEach } from 'vitest';
 
-vi.mock('./invokeSafe', () => ({ invokeSafe: vi.fn(), invokeSafeVoid: vi.fn() }));
+vi.mock('./invokeSafe', () => ({
+  invokeSafe: vi.fn(),
+  invokeSafeVoid: vi.fn(),
+  invokeRaw: vi.fn(),
+}));
 
-import { invokeSafe, invokeSafeVoid } from './invokeSafe';
+import { invokeSafe, invokeSafeVoid, invokeRaw } from './invokeSafe';
 import {
   mcpListServers,
   mcpInstallServer,
@@ -15,11 +19,17 @@ import {
   mcpInvokeTool,
   mcpSetPermission,
   mcpGetPermission,
+  mcpGetPrompt,
+  mcpRenderPrompt,
+  mcpResourceAttachments,
+  mcpHostSetEnabled,
+  mcpHostReportToolResult,
 } from './mcpCommands';
 import type { McpServerInstallInput } from '../../built-in-features/mcp/types';
 
 const mockInvoke = invokeSafe as ReturnType<typeof vi.fn>;
 const mockInvokeVoid = invokeSafeVoid as ReturnType<typeof vi.fn>;
+const mockInvokeRaw = invokeRaw as ReturnType<typeof vi.fn>;
 
 const sampleInput: McpServerInstallInput = {
   id: 'my-server',
@@ -195,3 +205,54 @@ describe('mcpGetPermission', () => {
     expect(result).toBeNull();
   });
 });
+
+describe('mcp resources and prompts', () => {
+  it('sends prompt arguments under `arguments`', async () => {
+    mockInvoke.mockResolvedValue({ messages: [] });
+    await mcpGetPrompt('github', 'review', { code: 'x' });
+    expect(mockInvoke).toHaveBeenCalledWith('mcp_get_prompt', {
+      serverId: 'github',
+      name: 'review',
+      arguments: { code: 'x' },
+    });
+  });
+
+  it('lets attachment and render errors propagate', async () => {
+    mockInvokeRaw.mockRejectedValueOnce(new Error('archive.zip: binary resources'));
+    await expect(mcpResourceAttachments('files', 'file:///archive.zip')).rejects.toThrow(
+      'archive.zip',
+    );
+
+    mockInvokeRaw.mockResolvedValueOnce('Review x');
+    expect(await mcpRenderPrompt('github', 'review', { code: 'x' })).toBe('Review x');
+    expect(mockInvokeRaw).toHaveBeenLastCalledWith('mcp_render_prompt', {
+      serverId: 'github',
+      name: 'review',
+      arguments: { code: 'x' },
+    });
+  });
+});
+
+describe('mcp host', () => {
+  it('toggles the server and returns its status', async () => {
+    const status = {
+      enabled: true,
+      url: 'http://127.0.0.1:54330/mcp',
+      token: 't',
+      stdioCommand: [],
+    };
+    mockInvoke.mockResolvedValue(status);
+    expect(await mcpHostSetEnabled(true)).toEqual(status);
+    expect(mockInvoke).toHaveBeenCalledWith('mcp_host_set_enabled', { enabled: true });
+  });
+
+  it('reports a Tier-2 failure with its error', async () => {
+    mockInvoke.mockResolvedValue(true);
+    await mcpHostReportToolResult('call-1', null, 'worker not mounted');
+    expect(mockInvoke).toHaveBeenCalledWith('mcp_host_report_tool_result', {
+      id: 'call-1',
+      result: null,
+      error: 'worker not mounted',
+    });
+  });
+});
@@ -1,4 +1,5 @@
-import { invokeSafe, invokeSafeVoid } from './invokeSafe';
+import { invokeSafe, invokeSafeVoid, invokeRaw } from './invokeSafe';
+import type { ContentPart } from '../../bindings';
 import type {
   McpServerInstallInput,
   McpServerSummary,
@@ -8,6 +9,15 @@ import type {
   McpToolDescriptor,
   McpPermissionRow,
   McpRuntimeConsentNeeded,
+  McpResource,
+  McpResourceTemplate,
+  McpResourceContents,
+  McpPrompt,
+  McpPromptResult,
+  McpCatalogEntry,
+  McpHostStatus,
+  McpSamplingRoute,
+  McpElicitationAction,
 } from '../../built-in-features/mcp/types';
 
 export async function mcpListServers(): Promise<McpServerSummary[] | null> {
@@ -115,3 +125,98 @@ export async function mcpGetStrictMode(): Promise<boolean> {
 export async function mcpSetStrictMode(enabled: boolean): Promise<boolean> {
   return invokeSafeVoid('mcp_set_strict_mode', { enabled });
 }
+
+export async function mcpListResources(serverId: string): Promise<McpResource[] | null> {
+  return invokeSafe<McpResource[]>('mcp_list_resources', { serverId });
+}
+
+export async function mcpListResourceTemplates(
+  serverId: string,
+): Promise<McpResourceTemplate[] | null> {
+  return invokeSafe<McpResourceTemplate[]>('mcp_list_resource_templates', { serverId });
+}
+
+export async function mcpListPrompts(serverId: string): Promise<McpPrompt[] | null> {
+  return invokeSafe<McpPrompt[]>('mcp_list_prompts', { serverId });
+}
+
+export async function mcpReadResource(
+  serverId: string,
+  uri: string,
+): Promise<McpResourceContents[] | null> {
+  return invokeSafe<McpResourceContents[]>('mcp_read_resource', { serverId, uri });
+}
+
+export async function mcpGetPrompt(
+  serverId: string,
+  name: string,
+  args: Record<string, string>,
+): Promise<McpPromptResult | null> {
+  return invokeSafe<McpPromptResult>('mcp_get_prompt', { serverId, name, arguments: args });
+}
+
+export async function mcpListCatalog(): Promise<McpCatalogEntry[] | null> {
+  return invokeSafe<McpCatalogEntry[]>('mcp_list_catalog');
+}
+
+/** Throws with the server's or the attachment limits' reason, for the caller to surface. */
+export async function mcpResourceAttachments(
+  serverId: string,
+  uri: string,
+): Promise<ContentPart[]> {
+  return invokeRaw<ContentPart[]>('mcp_resource_attachments', { serverId, uri });
+}
+
+/** Throws when the server rejects the arguments or the prompt renders no text. */
+export async function mcpRenderPrompt(
+  serverId: string,
+  name: string,
+  args: Record<string, string>,
+): Promise<string> {
+  return invokeRaw<string>('mcp_render_prompt', { serverId, name, arguments: args });
+}
+
+export async function mcpHostStatus(): Promise<McpHostStatus | null> {
+  return invokeSafe<McpHostStatus>('mcp_host_status');
+}
+
+export async function mcpHostSetEnabled(enabled: boolean): Promise<McpHostStatus | null> {
+  return invokeSafe<McpHostStatus>('mcp_host_set_enabled', { enabled });
+}
+
+export async function mcpHostRotateToken(): Promise<McpHostStatus | null> {
+  return invokeSafe<McpHostStatus>('mcp_host_rotate_token');
+}
+
+/** `false` when the client's call had already timed out. */
+export async function mcpHostResolvePermission(
+  id: string,
+  decision: 'allow_once' | 'allow_always' | 'never' | 'cancel',
+): Promise<boolean | null> {
+  return invokeSafe<boolean>('mcp_host_resolve_permission', { id, decision });
+}
+
+export async function mcpHostReportToolResult(
+  id: string,
+  result: unknown,
+  error?: string,
+): Promise<boolean | null> {
+  return invokeSafe<boolean>('mcp_host_report_tool_result', { id, result, error });
+}
+
+export async function mcpResolveSampling(
+  id: string,
+  choice: 'allow_once' | 'allow_always' | 'never' | 'cancel',
+  route: McpSamplingRoute | null,
+): Promise<boolean | null> {
+  return invokeSafe<boolean>('mcp_resolve_sampling', { id, answer: { choice, route } });
+}
+
+/** Throws when accepted content doesn't fit the requested schema, for the form to show. */
+export async function mcpResolveElicitation(
+  id: string,
+  action: McpElicitationAction,
+  content?: Record<string, unknown>,
+): Promise<boolean> {
+  return invokeRaw<boolean>('mcp_resolve_elicitation', { id, action, content: content ?? null });
+}
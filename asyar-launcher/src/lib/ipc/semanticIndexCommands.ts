@@ -0,0 +1,66 @@
+// asyar-launcher/src/lib/ipc/semanticIndexCommands.ts
+// Tauri command wrappers, re-exported through ./commands (the barrel).
+import { invokeSafe, invokeSafeVoid } from './invokeSafe';
+import type { ProviderConfig } from '../../services/ai/IProviderPlugin';
+
+// ── Semantic index (hybrid BM25 + vector search) ─────────────────────────────
+
+export type EmbeddingSource = 'note' | 'clipboard' | 'snippet';
+
+export interface EmbeddingSettings {
+  providerId: string;
+  modelId: string;
+  config: ProviderConfig;
+}
+
+export interface SemanticIndexStatus {
+  configured: boolean;
+  model: string;
+  vectorCount: number;
+}
+
+export interface IndexSyncReport {
+  model: string;
+  embedded: number;
+  unchanged: number;
+  removed: number;
+}
+
+export interface SemanticHit {
+  source: EmbeddingSource;
+  id: string;
+  title: string;
+  snippet: string;
+  score: number;
+  similarity?: number;
+  keywordMatch: boolean;
+}
+
+export interface SemanticSearchResult {
+  hits: SemanticHit[];
+  vectorState: 'ready' | 'empty' | 'unavailable';
+}
+
+/** `null` falls back to a local Ollama install with its default embedding model. */
+export async function semanticIndexConfigure(settings: EmbeddingSettings | null): Promise<void> {
+  await invokeSafeVoid('semantic_index_configure', { settings });
+}
+
+export async function semanticIndexSync(): Promise<IndexSyncReport | null> {
+  return invokeSafe<IndexSyncReport>('semantic_index_sync');
+}
+
+export async function semanticIndexStatus(): Promise<SemanticIndexStatus | null> {
+  return invokeSafe<SemanticIndexStatus>('semantic_index_status');
+}
+
+export async function semanticSearch(
+  query: string,
+  options: { sources?: EmbeddingSource[]; limit?: number } = {},
+): Promise<SemanticSearchResult | null> {
+  return invokeSafe<SemanticSearchResult>('semantic_search', {
+    query,
+    sources: options.sources ?? null,
+    limit: options.limit ?? null,
+  });
+}
@@ -6,8 +6,18 @@ export interface ShellDescriptor {
   args: string[];
   pid: number;
   startedAt: number;
+  /** Output arrives as base64 terminal bytes on the `pty` stream. */
+  pty: boolean;
 }
 
+/** Terminal dimensions in character cells. */
+export interface ShellPtySize {
+  cols: number;
+  rows: number;
+}
+
+export type ShellSignal = 'SIGINT' | 'SIGTERM';
+
 export async function shellResolvePath(program: string): Promise<string | null> {
   return invokeSafe<string>('shell_resolve_path', { program });
 }
@@ -25,8 +35,15 @@ export async function shellSpawn(
   spawnId: string,
   program: string,
   args: string[],
+  /**
+   * Added to the launcher's own environment for this process only. Names must
+   * be `ASYAR_`-prefixed; anything else is rejected by the backend.
+   */
+  env?: Record<string, string>,
+  /** Run under a pseudo-terminal of this size instead of pipes. */
+  pty?: ShellPtySize,
 ): Promise<boolean> {
-  return invokeSafeVoid('shell_spawn', { extensionId, spawnId, program, args });
+  return invokeSafeVoid('shell_spawn', { extensionId, spawnId, program, args, env, pty });
 }
 
 export async function shellWriteStdin(
@@ -41,6 +58,22 @@ export async function shellCloseStdin(extensionId: string, spawnId: string): Pro
   return invokeSafeVoid('shell_close_stdin', { extensionId, spawnId });
 }
 
+export async function shellResize(
+  extensionId: string,
+  spawnId: string,
+  size: ShellPtySize,
+): Promise<boolean> {
+  return invokeSafeVoid('shell_resize', { extensionId, spawnId, cols: size.cols, rows: size.rows });
+}
+
+export async function shellSignal(
+  extensionId: string,
+  spawnId: string,
+  signal: ShellSignal,
+): Promise<boolean> {
+  return invokeSafeVoid('shell_signal', { extensionId, spawnId, signal });
+}
+
 export async function shellList(extensionId: string): Promise<ShellDescriptor[] | null> {
   return invokeSafe<ShellDescriptor[]>('shell_list', { extensionId });
 }
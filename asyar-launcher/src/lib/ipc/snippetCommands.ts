@@ -4,6 +4,15 @@ import { invokeSafe, invokeSafeVoid } from './invokeSafe';
 
 // ── Storage: Snippets ────────────────────────────────────────────────────────
 
+export type SnippetTriggerKind = 'literal' | 'regex';
+export type SnippetExpandMode = 'immediate' | 'delimiter';
+
+export interface SnippetAppScope {
+  mode: 'include' | 'exclude';
+  /** Bundle ids, app names, or executable names — matched case-insensitively. */
+  apps: string[];
+}
+
 export interface StoredSnippet {
   id: string;
   keyword?: string;
@@ -11,6 +20,26 @@ export interface StoredSnippet {
   name: string;
   createdAt: number;
   pinned: boolean;
+  trigger?: SnippetTriggerKind;
+  expandMode?: SnippetExpandMode;
+  appScope?: SnippetAppScope;
+}
+
+/** Mirrors `snippets::SnippetRule` on the Rust side. */
+export interface SnippetRule {
+  /** Stored snippet id; names the snippet in {@link SnippetRuleError}. */
+  id: string;
+  keyword: string;
+  expansion: string;
+  trigger?: SnippetTriggerKind;
+  expandMode?: SnippetExpandMode;
+  appScope?: SnippetAppScope;
+}
+
+/** A rule the listener left out because its regex does not compile. */
+export interface SnippetRuleError {
+  snippetId: string;
+  message: string;
 }
 
 export async function snippetUpsert(snippet: StoredSnippet): Promise<void> {
@@ -39,6 +68,17 @@ export async function syncSnippetsToRust(snippets: [string, string][]): Promise<
   await invokeSafe('sync_snippets_to_rust', { snippets });
 }
 
+export async function syncSnippetRulesToRust(
+  rules: SnippetRule[],
+): Promise<SnippetRuleError[] | null> {
+  return invokeSafe<SnippetRuleError[]>('sync_snippet_rules_to_rust', { rules });
+}
+
+/** The Rust compile error for a regex trigger, or `null` when it is valid. */
+export async function checkSnippetRegex(keyword: string): Promise<string | null> {
+  return invokeSafe<string | null>('check_snippet_regex', { keyword });
+}
+
 // boolean (not void): snippetService.setEnabled's { ok, error } contract
 // needs to distinguish success from failure.
 export async function setSnippetsEnabled(enabled: boolean): Promise<boolean> {
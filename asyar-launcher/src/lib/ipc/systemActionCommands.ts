@@ -6,6 +6,8 @@ import type { ProviderConfig } from '../../services/ai/IProviderPlugin';
 import type {
   AgentRunConfig as AgentRunConfigContract,
   AgentStreamEvent,
+  ContentPart,
+  SilentRunOutput,
   SystemAction,
 } from '../../bindings';
 
@@ -31,6 +33,7 @@ export async function agentsRunThread(
   agentId: string,
   threadId: string,
   userText: string,
+  attachments: ContentPart[],
   runId: string | null,
   config: AgentRunConfig,
   streamId: string,
@@ -40,6 +43,7 @@ export async function agentsRunThread(
     agentId,
     threadId,
     userText,
+    attachments,
     runId,
     config,
     streamId,
@@ -53,8 +57,8 @@ export async function agentsRunSilent(
   config: AgentRunConfig,
   streamId: string,
   onEvent: Channel<AgentStreamEvent>,
-): Promise<string> {
-  return invokeRaw<string>('agents_run_silent', {
+): Promise<SilentRunOutput> {
+  return invokeRaw<SilentRunOutput>('agents_run_silent', {
     agentId,
     userText,
     config,
@@ -7,6 +7,10 @@ import type {
   AppGroup,
   KillResult,
   ProcessSortBy,
+  ResourceAlert,
+  ResourceMetric,
+  ResourceSeries,
+  ResourceTrend,
   PickedColor,
 } from 'asyar-sdk/contracts';
 
@@ -86,6 +90,51 @@ export async function processKillCommand(
   return invokeSafe<KillResult>('process_kill', { extensionId, pids, force, confirmedProtected });
 }
 
+export async function processSamplingStartCommand(extensionId: string | null): Promise<boolean> {
+  return invokeSafeVoid('process_sampling_start', { extensionId });
+}
+
+export async function processSamplingStopCommand(extensionId: string | null): Promise<boolean> {
+  return invokeSafeVoid('process_sampling_stop', { extensionId });
+}
+
+export async function processHistoryCommand(
+  extensionId: string | null,
+  appNames: string[],
+  minutes: number | undefined,
+): Promise<ResourceSeries[] | null> {
+  return invokeSafe<ResourceSeries[]>('process_history', { extensionId, appNames, minutes });
+}
+
+export async function processTopCommand(
+  extensionId: string | null,
+  metric: ResourceMetric,
+  minutes: number | undefined,
+  limit: number | undefined,
+): Promise<ResourceTrend[] | null> {
+  return invokeSafe<ResourceTrend[]>('process_top', { extensionId, metric, minutes, limit });
+}
+
+export async function processAlertsListCommand(
+  extensionId: string | null,
+): Promise<ResourceAlert[] | null> {
+  return invokeSafe<ResourceAlert[]>('process_alerts_list', { extensionId });
+}
+
+export async function processAlertSaveCommand(
+  extensionId: string | null,
+  alert: ResourceAlert,
+): Promise<boolean> {
+  return invokeSafeVoid('process_alert_save', { extensionId, alert });
+}
+
+export async function processAlertDeleteCommand(
+  extensionId: string | null,
+  alertId: string,
+): Promise<boolean | null> {
+  return invokeSafe<boolean>('process_alert_delete', { extensionId, alertId });
+}
+
 export async function fsWatchCreate(
   extensionId: string | null,
   paths: string[],
@@ -24,7 +24,7 @@ export interface ProviderConfig {
   maxTokens?: number;
 }
 
-export type ChatStreamStatus = 'searching';
+export type ChatStreamStatus = 'searching' | 'summarizing';
 
 // ─── Tool calling types ────────────────────────────────────────────────────────
 
@@ -0,0 +1,58 @@
+import { settingsService } from '../settings/settingsService.svelte';
+import {
+  semanticIndexConfigure,
+  type EmbeddingSettings,
+} from '../../lib/ipc/semanticIndexCommands';
+import type { AISettings } from '../settings/types/AppSettingsType';
+
+/**
+ * Keeps Rust's `SemanticIndexState` embedding target in sync with
+ * `settings.ai.embeddings`. Mirrors `services/fileIndex/fileIndexConfigSync.svelte.ts`:
+ * push on init, then re-push only when the resolved settings change. The
+ * provider config (base URL, API key) is resolved here because the frontend
+ * owns `settings.ai.providers`; a choice whose provider is missing or
+ * disabled resolves to `null`, i.e. the local Ollama default.
+ */
+let unsubscribe: (() => void) | null = null;
+let lastPushed: string | null = null;
+
+export function resolveEmbeddingSettings(ai: AISettings): EmbeddingSettings | null {
+  const choice = ai.embeddings;
+  if (!choice) return null;
+  const config = ai.providers[choice.providerId];
+  if (!config?.enabled) return null;
+  return { providerId: choice.providerId, modelId: choice.modelId, config };
+}
+
+function push(ai: AISettings): void {
+  const settings = resolveEmbeddingSettings(ai);
+  const serialized = JSON.stringify(settings);
+  if (serialized === lastPushed) return;
+  lastPushed = serialized;
+  void semanticIndexConfigure(settings);
+}
+
+export function initSemanticIndexSync(): () => void {
+  if (unsubscribe) {
+    unsubscribe();
+    unsubscribe = null;
+  }
+
+  push(settingsService.currentSettings.ai);
+  unsubscribe = settingsService.subscribe((s) => push(s.ai));
+
+  return () => {
+    if (unsubscribe) {
+      unsubscribe();
+      unsubscribe = null;
+    }
+  };
+}
+
+/** Testing-only: reset internal state so fresh `initSemanticIndexSync()`
+ * calls start from scratch. Do not call from production code. */
+export function __resetSemanticIndexSyncForTest(): void {
+  if (unsubscribe) unsubscribe();
+  unsubscribe = null;
+  lastPushed = null;
+}
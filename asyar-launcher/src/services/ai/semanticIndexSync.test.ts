@@ -0,0 +1,102 @@
+import { describe, it, expect, vi, beforeEach, afterEach } from 'vitest';
+
+vi.mock('@tauri-apps/api/core', () => ({ invoke: vi.fn() }));
+
+let subscribedCallbacks: Array<(s: any) => void> = [];
+const settingsStateHolder: { current: any } = { current: {} };
+
+vi.mock('../settings/settingsService.svelte', () => ({
+  settingsService: {
+    get currentSettings() {
+      return settingsStateHolder.current;
+    },
+    subscribe(cb: (s: any) => void) {
+      subscribedCallbacks.push(cb);
+      return () => {
+        subscribedCallbacks = subscribedCallbacks.filter((f) => f !== cb);
+      };
+    },
+  },
+}));
+
+import { invoke } from '@tauri-apps/api/core';
+import {
+  initSemanticIndexSync,
+  resolveEmbeddingSettings,
+  __resetSemanticIndexSyncForTest,
+} from './semanticIndexSync.svelte';
+
+async function flush(): Promise<void> {
+  await Promise.resolve();
+  await Promise.resolve();
+}
+
+function ai(overrides: Record<string, unknown> = {}) {
+  return {
+    providers: {
+      openai: { enabled: true, apiKey: 'sk' },
+      ollama: { enabled: false },
+    },
+    temperature: 0.7,
+    maxTokens: 2048,
+    defaultAgentId: null,
+    tabContinuesLastThread: false,
+    ...overrides,
+  };
+}
+
+describe('semanticIndexSync', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+    subscribedCallbacks = [];
+    settingsStateHolder.current = { ai: ai() };
+    __resetSemanticIndexSyncForTest();
+  });
+
+  afterEach(() => {
+    __resetSemanticIndexSyncForTest();
+  });
+
+  it('resolves the chosen provider config, or null when unusable', () => {
+    expect(resolveEmbeddingSettings(ai() as any)).toBeNull();
+    expect(
+      resolveEmbeddingSettings(
+        ai({ embeddings: { providerId: 'openai', modelId: 'text-embedding-3-small' } }) as any,
+      ),
+    ).toEqual({
+      providerId: 'openai',
+      modelId: 'text-embedding-3-small',
+      config: { enabled: true, apiKey: 'sk' },
+    });
+    expect(
+      resolveEmbeddingSettings(
+        ai({ embeddings: { providerId: 'ollama', modelId: 'nomic-embed-text' } }) as any,
+      ),
+    ).toBeNull();
+  });
+
+  it('pushes on init and only re-pushes when the resolved settings change', async () => {
+    vi.mocked(invoke).mockResolvedValue(undefined);
+
+    initSemanticIndexSync();
+    await flush();
+    expect(invoke).toHaveBeenCalledTimes(1);
+    expect(invoke).toHaveBeenCalledWith('semantic_index_configure', { settings: null });
+
+    subscribedCallbacks.forEach((cb) => cb({ ai: ai({ temperature: 0.2 }) }));
+    await flush();
+    expect(invoke).toHaveBeenCalledTimes(1);
+
+    const next = ai({ embeddings: { providerId: 'openai', modelId: 'text-embedding-3-small' } });
+    subscribedCallbacks.forEach((cb) => cb({ ai: next }));
+    await flush();
+    expect(invoke).toHaveBeenCalledTimes(2);
+    expect(invoke).toHaveBeenLastCalledWith('semantic_index_configure', {
+      settings: {
+        providerId: 'openai',
+        modelId: 'text-embedding-3-small',
+        config: { enabled: true, apiKey: 'sk' },
+      },
+    });
+  });
+});
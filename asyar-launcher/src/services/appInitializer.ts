@@ -45,6 +45,7 @@ import { stateChangedBridge } from './extensionState/stateChangedBridge.svelte';
 import { rpcReplyBridge } from './extensionState/rpcReplyBridge.svelte';
 import { initScanPathsSync } from './application/scanPathsSync.svelte';
 import { initFileIndexConfigSync } from './fileIndex/fileIndexConfigSync.svelte';
+import { initSemanticIndexSync } from './ai/semanticIndexSync.svelte';
 import { trayClickBridge } from './statusBar/trayClickBridge.svelte';
 import { viewRegistry } from './extension/viewRegistry.svelte';
 import { workerRegistry } from './extension/workerRegistry.svelte';
@@ -163,6 +164,10 @@ export const appInitializer = {
       // detached-thread block), this only needs to catch config edits.
       initFileIndexConfigSync();
 
+      // Same again for the semantic index's embedding provider
+      // (`settings.ai.embeddings`), resolved against `settings.ai.providers`.
+      initSemanticIndexSync();
+
       // Initialize stores before extensionManager so extensions see real persisted data in initialize()
       await shortcutStore.init();
       await snippetStore.init();
@@ -26,7 +26,11 @@ import { extensionCacheService } from '../storage/extensionCacheService';
 import { feedbackService } from '../feedback/feedbackService.svelte';
 import { selectionService } from '../selection/selectionService';
 import { extensionOAuthService } from '../oauth/extensionOAuthService.svelte';
-import { shellService } from '../shell/shellService.svelte';
+import {
+  shellService,
+  type ShellPtySize,
+  type ShellSignal,
+} from '../shell/shellService.svelte';
 import { fileManagerService } from '../fileManager/fileManagerService';
 import { InteropService } from '../interop/interopService.svelte';
 import { applicationService } from '../application/applicationService';
@@ -145,6 +149,7 @@ export function buildServiceRegistry(deps: {
         args: string[] = [],
         spawnId: string,
         stdin?: string,
+        pty?: ShellPtySize,
         originRole?: 'view' | 'worker',
       ) =>
         shellService.spawn(
@@ -156,6 +161,8 @@ export function buildServiceRegistry(deps: {
           undefined,
           undefined,
           stdin,
+          undefined,
+          pty,
         ),
       list: (extensionId: string) => shellService.list(extensionId),
       attach: (extensionId: string, spawnId: string, originRole?: 'view' | 'worker') =>
@@ -168,6 +175,10 @@ export function buildServiceRegistry(deps: {
         shellService.writeStdin(spawnId, data, extensionId),
       'close-stdin': (extensionId: string, spawnId: string) =>
         shellService.closeStdin(spawnId, extensionId),
+      resize: (extensionId: string, spawnId: string, cols: number, rows: number) =>
+        shellService.resize(spawnId, { cols, rows }, extensionId),
+      signal: (extensionId: string, spawnId: string, signal: ShellSignal) =>
+        shellService.signal(spawnId, signal, extensionId),
     },
     fs: fileManagerService,
     interop: new InteropService({
@@ -11,10 +11,19 @@
  * orchestrator.
  */
 
+/**
+ * Returned by a dispatcher whose command opened a view (e.g. a `fullOutput`
+ * script showing its run). The launcher navigates there instead of hiding.
+ */
+export interface BuiltinDispatchView {
+  type: 'view';
+  viewPath: string;
+}
+
 export type BuiltinDynamicDispatcher = (
   dynamicId: string,
   args?: Record<string, unknown>,
-) => Promise<void>;
+) => Promise<void | BuiltinDispatchView>;
 
 const dispatchers = new Map<string, BuiltinDynamicDispatcher>();
 
@@ -246,10 +246,14 @@ export class ExtensionManager implements IExtensionManager {
         // Tier 1 built-in dynamic command — dispatched by the built-in
         // extension's own handler, registered at module load via
         // registerBuiltinDynamicDispatcher (see builtinDynamicDispatchers.ts).
-        await builtinDispatcher(dyn.dynamicId, args);
+        const result = await builtinDispatcher(dyn.dynamicId, args);
 
-        searchService.saveIndex();
-        void commands.hideWindow().then(resetLauncherState);
+        if (result?.type === 'view') {
+          this.navigateToView(result.viewPath);
+        } else {
+          searchService.saveIndex();
+          void commands.hideWindow().then(resetLauncherState);
+        }
 
         void commands
           .recordItemUsage(commandObjectId)
@@ -257,7 +261,7 @@ export class ExtensionManager implements IExtensionManager {
           .catch((err) =>
             logService.error(`Failed to record usage for ${commandObjectId}: ${err}`),
           );
-        return { type: 'no-view' };
+        return result ?? { type: 'no-view' };
       }
       // Tier 2 extension dynamic command — route to the worker iframe dispatcher.
       return this.handleDynamicCommandAction(dyn, commandObjectId, args);
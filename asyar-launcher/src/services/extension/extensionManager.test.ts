@@ -509,6 +509,22 @@ describe('ExtensionManager Characterization Tests', () => {
           unregisterBuiltinDynamicDispatcher('my-builtin');
         }
       });
+
+      it('navigates instead of hiding when a built-in dispatcher opens a view', async () => {
+        const { registerBuiltinDynamicDispatcher, unregisterBuiltinDynamicDispatcher } =
+          await import('./builtinDynamicDispatchers');
+        const view = { type: 'view' as const, viewPath: 'runs/RunView' };
+        registerBuiltinDynamicDispatcher('my-builtin', vi.fn(async () => view));
+
+        try {
+          const result = await extensionManager.handleCommandAction('cmd_my-builtin_dyn_xyz');
+          expect(result).toEqual(view);
+          expect(extensionManager.navigateToView).toHaveBeenCalledWith('runs/RunView');
+          expect(commands.hideWindow).not.toHaveBeenCalled();
+        } finally {
+          unregisterBuiltinDynamicDispatcher('my-builtin');
+        }
+      });
     });
   });
 
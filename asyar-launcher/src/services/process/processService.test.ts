@@ -94,5 +94,93 @@ describe('processService (host)', () => {
         confirmedProtected: false,
       });
     });
+
+    it('history: proxy {appNames,minutes} payload dispatched positionally reaches process_history', async () => {
+      vi.mocked(invoke).mockResolvedValueOnce([]);
+
+      const proxyPayload = { appNames: ['Safari'], minutes: 5 };
+      const args = ['ext-a', ...Object.values(proxyPayload)] as Parameters<
+        typeof processService.history
+      >;
+
+      await processService.history(...args);
+
+      expect(invoke).toHaveBeenCalledWith('process_history', {
+        extensionId: 'ext-a',
+        appNames: ['Safari'],
+        minutes: 5,
+      });
+    });
+
+    it('top: proxy {metric,minutes,limit} payload dispatched positionally reaches process_top', async () => {
+      vi.mocked(invoke).mockResolvedValueOnce([]);
+
+      const proxyPayload = { metric: 'memory' as const, minutes: undefined, limit: 3 };
+      const args = ['ext-a', ...Object.values(proxyPayload)] as Parameters<
+        typeof processService.top
+      >;
+
+      await processService.top(...args);
+
+      expect(invoke).toHaveBeenCalledWith('process_top', {
+        extensionId: 'ext-a',
+        metric: 'memory',
+        minutes: undefined,
+        limit: 3,
+      });
+    });
+  });
+
+  describe('resource history', () => {
+    const alert = {
+      id: 'mem',
+      appName: null,
+      metric: 'memory' as const,
+      threshold: 4 * 1024 ** 3,
+      sustainedSecs: 300,
+      enabled: true,
+    };
+
+    it('startSampling / stopSampling forward the caller id', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await processService.startSampling('ext-a');
+      await processService.stopSampling('ext-a');
+
+      expect(invoke).toHaveBeenNthCalledWith(1, 'process_sampling_start', { extensionId: 'ext-a' });
+      expect(invoke).toHaveBeenNthCalledWith(2, 'process_sampling_stop', { extensionId: 'ext-a' });
+    });
+
+    it('history falls back to an empty list when the command fails', async () => {
+      vi.mocked(invoke).mockRejectedValueOnce(new Error('boom'));
+
+      expect(await processService.history('ext-a', ['Safari'], undefined)).toEqual([]);
+    });
+
+    it('saveAlert forwards the alert to process_alert_save', async () => {
+      vi.mocked(invoke).mockResolvedValueOnce(undefined);
+
+      await processService.saveAlert('ext-a', alert);
+
+      expect(invoke).toHaveBeenCalledWith('process_alert_save', { extensionId: 'ext-a', alert });
+    });
+
+    it('saveAlert throws when the host rejects the alert', async () => {
+      vi.mocked(invoke).mockRejectedValueOnce({ kind: 'validation', message: 'bad threshold' });
+
+      await expect(processService.saveAlert('ext-a', alert)).rejects.toThrow(
+        'process_alert_save failed',
+      );
+    });
+
+    it('deleteAlert forwards the id and reports whether it existed', async () => {
+      vi.mocked(invoke).mockResolvedValueOnce(true);
+
+      expect(await processService.deleteAlert('ext-a', 'mem')).toBe(true);
+      expect(invoke).toHaveBeenCalledWith('process_alert_delete', {
+        extensionId: 'ext-a',
+        alertId: 'mem',
+      });
+    });
   });
 });
@@ -1,5 +1,23 @@
-import type { AppGroup, KillResult, ProcessSortBy } from 'asyar-sdk/contracts';
-import { processListCommand, processKillCommand } from '../../lib/ipc/systemCommands';
+import type {
+  AppGroup,
+  KillResult,
+  ProcessSortBy,
+  ResourceAlert,
+  ResourceMetric,
+  ResourceSeries,
+  ResourceTrend,
+} from 'asyar-sdk/contracts';
+import {
+  processListCommand,
+  processKillCommand,
+  processSamplingStartCommand,
+  processSamplingStopCommand,
+  processHistoryCommand,
+  processTopCommand,
+  processAlertsListCommand,
+  processAlertSaveCommand,
+  processAlertDeleteCommand,
+} from '../../lib/ipc/systemCommands';
 
 /**
  * Host-side thin wrapper over the Rust `process_*` Tauri commands.
@@ -11,6 +29,10 @@ import { processListCommand, processKillCommand } from '../../lib/ipc/systemComm
  * proxy's payload-key insertion order exactly:
  *   ProcessServiceProxy.list  sends { query, sortBy }
  *   ProcessServiceProxy.kill  sends { pids, force, confirmedProtected }
+ *   ProcessServiceProxy.history  sends { appNames, minutes }
+ *   ProcessServiceProxy.top  sends { metric, minutes, limit }
+ *   ProcessServiceProxy.saveAlert  sends { alert }
+ *   ProcessServiceProxy.deleteAlert  sends { alertId }
  * Privileged host-context calls pass `null` for `extensionId`.
  */
 export const processService = {
@@ -34,4 +56,38 @@ export const processService = {
       failed: pids.map((pid) => ({ pid, error: 'process_kill failed' })),
     };
   },
+  async startSampling(extensionId: string | null): Promise<void> {
+    await processSamplingStartCommand(extensionId);
+  },
+  async stopSampling(extensionId: string | null): Promise<void> {
+    await processSamplingStopCommand(extensionId);
+  },
+  async history(
+    extensionId: string | null,
+    appNames: string[],
+    minutes: number | undefined,
+  ): Promise<ResourceSeries[]> {
+    return (await processHistoryCommand(extensionId, appNames, minutes)) ?? [];
+  },
+  async top(
+    extensionId: string | null,
+    metric: ResourceMetric,
+    minutes: number | undefined,
+    limit: number | undefined,
+  ): Promise<ResourceTrend[]> {
+    return (await processTopCommand(extensionId, metric, minutes, limit)) ?? [];
+  },
+  async listAlerts(extensionId: string | null): Promise<ResourceAlert[]> {
+    return (await processAlertsListCommand(extensionId)) ?? [];
+  },
+  async saveAlert(extensionId: string | null, alert: ResourceAlert): Promise<void> {
+    // Unlike the reads there's no safe fallback: an alert that silently
+    // failed to save would never fire.
+    if (!(await processAlertSaveCommand(extensionId, alert))) {
+      throw new Error(`process_alert_save failed for alert "${alert.id}"`);
+    }
+  },
+  async deleteAlert(extensionId: string | null, alertId: string): Promise<boolean> {
+    return (await processAlertDeleteCommand(extensionId, alertId)) ?? false;
+  },
 };
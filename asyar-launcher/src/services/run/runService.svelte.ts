@@ -15,6 +15,36 @@ export interface LocalRunHandle {
   onCancel(cb: () => void): () => void;
 }
 
+/** One page of a run's full on-disk log (`runs_read_log`). */
+export interface RunLogPage {
+  runId: string;
+  offset: number;
+  lines: string[];
+  /** Offset of the next page, or null when this page reached the end. */
+  nextOffset: number | null;
+  /** False while the run is still writing. */
+  finished: boolean;
+}
+
+/** A log line matching `searchLogs`. `lineNumber` is a valid `readLog` offset. */
+export interface RunLogMatch {
+  runId: string;
+  lineNumber: number;
+  line: string;
+}
+
+/** A run of output text in one style (`runs_render_ansi`). Colours are CSS hex. */
+export interface AnsiSpan {
+  text: string;
+  fg?: string;
+  bg?: string;
+  bold?: boolean;
+  dim?: boolean;
+  italic?: boolean;
+  underline?: boolean;
+  strikethrough?: boolean;
+}
+
 const UNACK_FAILED_CAP = 5;
 
 export class RunService {
@@ -105,6 +135,7 @@ export class RunService {
     label: string,
     cancellable: boolean,
     subjectId: string | null = null,
+    inputs?: unknown,
   ): Promise<Run> {
     const run = await invokeSafe<Run>('runs_start', {
       id,
@@ -113,6 +144,7 @@ export class RunService {
       extensionId,
       cancellable,
       subjectId,
+      ...(inputs !== undefined ? { inputs } : {}),
     });
     if (!run) {
       throw new Error('runs_start failed');
@@ -152,6 +184,53 @@ export class RunService {
     await invokeSafe('runs_cancel', { id });
   }
 
+  /** Read one page of a run's full log. Null when the run has no log. */
+  async readLog(id: string, offset = 0, limit = 200): Promise<RunLogPage | null> {
+    return invokeSafe<RunLogPage>('runs_read_log', { id, offset, limit });
+  }
+
+  /** Case-insensitive search across finished run logs, newest runs first. */
+  async searchLogs(query: string, limit = 100): Promise<RunLogMatch[]> {
+    return (await invokeSafe<RunLogMatch[]>('runs_search_logs', { query, limit })) ?? [];
+  }
+
+  /**
+   * Split output lines into ANSI-styled spans, one list per line. Style
+   * carries from line to line within a call. Null when the call fails.
+   */
+  async renderAnsi(lines: string[]): Promise<AnsiSpan[][] | null> {
+    return invokeSafe<AnsiSpan[][]>('runs_render_ansi', { lines });
+  }
+
+  /** `line` without its ANSI escapes; the line as given if the call fails. */
+  async stripAnsi(line: string): Promise<string> {
+    return (await invokeSafe<string>('runs_strip_ansi', { line })) ?? line;
+  }
+
+  /**
+   * Whether `run` is finished and of a kind that can be started again.
+   * Only shell runs record their inputs today.
+   */
+  canRerun(run: Run): boolean {
+    const isTerminal =
+      run.status === 'succeeded' || run.status === 'failed' || run.status === 'cancelled';
+    return isTerminal && run.kind === 'shell-script' && !!run.extensionId;
+  }
+
+  /**
+   * Start `run` again with the inputs it was started with. Returns false
+   * when it recorded none (or retention has deleted its log).
+   */
+  async rerun(run: Run): Promise<boolean> {
+    if (!this.canRerun(run)) return false;
+    const inputs = await invokeSafe<unknown>('runs_get_inputs', { id: run.id });
+    if (inputs === null || inputs === undefined) return false;
+    // Dynamic import: shellService already imports this module.
+    const { shellService } = await import('../shell/shellService.svelte');
+    await shellService.rerun(run, inputs);
+    return true;
+  }
+
   /**
    * Move the highlighted run one slot up or down in the `combined` list,
    * wrapping at the ends. No-op when the list is empty. With nothing
@@ -315,6 +394,11 @@ export class RunService {
      * can light up the originating row with a status dot.
      */
     subjectId?: string | null;
+    /**
+     * Whatever is needed to start this run again, handed back by
+     * `rerun()`. Must be JSON-serializable.
+     */
+    inputs?: unknown;
   }): Promise<LocalRunHandle> {
     const id = crypto.randomUUID();
     await this.start(
@@ -324,6 +408,7 @@ export class RunService {
       input.label,
       input.cancellable ?? false,
       input.subjectId ?? null,
+      input.inputs,
     );
     return this.buildLocalHandle(id);
   }
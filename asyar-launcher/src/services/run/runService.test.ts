@@ -120,6 +120,50 @@ describe('startLocal', () => {
       }),
     );
   });
+
+  it('forwards inputs only when given', async () => {
+    vi.mocked(invokeSafe).mockResolvedValue(makeRun());
+    await runService.startLocal({
+      label: 'x',
+      kind: 'shell-script',
+      inputs: { program: 'echo', args: ['hi'] },
+    });
+    expect(invokeSafe).toHaveBeenCalledWith(
+      'runs_start',
+      expect.objectContaining({ inputs: { program: 'echo', args: ['hi'] } }),
+    );
+
+    vi.mocked(invokeSafe).mockClear();
+    await runService.startLocal({ label: 'x', kind: 'custom' });
+    expect(vi.mocked(invokeSafe).mock.calls[0][1]).not.toHaveProperty('inputs');
+  });
+});
+
+describe('rerun', () => {
+  it('only finished shell runs with an extension can be rerun', () => {
+    const finished = makeRun({ status: 'failed', extensionId: 'scripts' });
+    expect(runService.canRerun(finished)).toBe(true);
+    expect(runService.canRerun({ ...finished, status: 'running' })).toBe(false);
+    expect(runService.canRerun({ ...finished, kind: 'agent' })).toBe(false);
+    expect(runService.canRerun({ ...finished, extensionId: undefined })).toBe(false);
+  });
+
+  it('returns false without spawning when the run recorded no inputs', async () => {
+    vi.mocked(invokeSafe).mockResolvedValue(null);
+    const ok = await runService.rerun(makeRun({ status: 'succeeded', extensionId: 'scripts' }));
+    expect(ok).toBe(false);
+    expect(invokeSafe).toHaveBeenCalledWith('runs_get_inputs', { id: 'r1' });
+  });
+});
+
+describe('logs', () => {
+  it('readLog and searchLogs call the log commands', async () => {
+    vi.mocked(invokeSafe).mockResolvedValue(null);
+    expect(await runService.readLog('r1', 200)).toBeNull();
+    expect(invokeSafe).toHaveBeenCalledWith('runs_read_log', { id: 'r1', offset: 200, limit: 200 });
+    expect(await runService.searchLogs('error')).toEqual([]);
+    expect(invokeSafe).toHaveBeenCalledWith('runs_search_logs', { query: 'error', limit: 100 });
+  });
 });
 
 describe('write', () => {
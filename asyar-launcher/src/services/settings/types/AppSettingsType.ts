@@ -98,4 +98,9 @@ export interface AISettings {
   maxTokens: number;
   defaultAgentId: string | null;
   tabContinuesLastThread: boolean;
+  /**
+   * Provider + model that produce vectors for semantic search. Unset means a
+   * local Ollama install with its default embedding model.
+   */
+  embeddings?: { providerId: string; modelId: string } | null;
 }
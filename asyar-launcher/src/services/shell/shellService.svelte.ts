@@ -3,6 +3,7 @@ import { streamDispatcher } from '../extension/streamDispatcher.svelte';
 import { shellConsentService } from './shellConsentService.svelte';
 import { logService } from '../log/logService';
 import { runService, type LocalRunHandle } from '../run/runService.svelte';
+import type { Run } from 'asyar-sdk/contracts';
 import {
   shellResolvePath,
   shellKill,
@@ -11,12 +12,17 @@ import {
   shellCloseStdin,
   shellList,
   shellAttach,
+  shellResize,
+  shellSignal,
   type ShellDescriptor,
+  type ShellPtySize,
+  type ShellSignal,
 } from '../../lib/ipc/shellCommands';
 
 interface ShellChunkPayload {
   spawnId: string;
-  stream: 'stdout' | 'stderr';
+  /** `pty` chunks are base64 terminal bytes, not lines. */
+  stream: 'stdout' | 'stderr' | 'pty';
   data: string;
 }
 
@@ -30,7 +36,38 @@ interface ShellErrorPayload {
   message: string;
 }
 
-export type { ShellDescriptor };
+export type { ShellDescriptor, ShellPtySize, ShellSignal };
+
+/** What a shell run records so it can be re-run from the Runs view. */
+interface ShellRunInputs {
+  program: string;
+  args: string[];
+  stdin?: string;
+}
+
+/** How a spawned process ended, for `ShellSpawnHooks.onExit`. */
+export interface ShellExitResult {
+  /** Exit code 0. */
+  ok: boolean;
+  /**
+   * The last non-blank line the process printed, escapes and all. Always
+   * null for a PTY session, whose output isn't split into lines.
+   */
+  lastLine: string | null;
+  /** Why it failed when it did not exit cleanly. */
+  error?: string;
+}
+
+/**
+ * Extras for Tier 1 dispatch sites (scripts). The IPC router never passes
+ * these, so Tier 2 spawns can't set a child's environment.
+ */
+export interface ShellSpawnHooks {
+  env?: Record<string, string>;
+  /** Called with the id of the run tracking this spawn, once it exists. */
+  onRunStarted?: (runId: string) => void;
+  onExit?: (result: ShellExitResult) => void;
+}
 
 class ShellService {
   // Installed once so spawn() and attach() share a single chunk/done/error
@@ -86,6 +123,13 @@ class ShellService {
      * When provided, standard input is closed right after writing.
      */
     stdin?: string,
+    hooks?: ShellSpawnHooks,
+    /**
+     * Run under a pseudo-terminal of this size instead of pipes, for
+     * programs that need a TTY. Output then arrives as raw bytes on the
+     * `pty` stream and stays out of the run log.
+     */
+    pty?: ShellPtySize,
   ): Promise<{ streaming: true }> {
     const resolvedPath = await shellResolvePath(program);
     if (resolvedPath === null) {
@@ -115,6 +159,9 @@ class ShellService {
         cancellable: true,
         extensionId,
         subjectId,
+        // A terminal session needs someone at the keyboard, so it isn't
+        // offered for re-run.
+        inputs: pty ? undefined : ({ program, args, stdin } satisfies ShellRunInputs),
       });
     } catch (err) {
       logService.warn(
@@ -126,10 +173,13 @@ class ShellService {
       unsubscribeCancel = runHandle.onCancel(() => {
         void shellKill(spawnId);
       });
+      hooks?.onRunStarted?.(runHandle.id);
     }
 
+    let lastLine: string | null = null;
     const chunkUnlisten = await listen<ShellChunkPayload>('asyar:shell:chunk', (ev) => {
-      if (ev.payload.spawnId !== spawnId) return;
+      if (ev.payload.spawnId !== spawnId || ev.payload.stream === 'pty') return;
+      if (ev.payload.data.trim()) lastLine = ev.payload.data;
       void runHandle?.write(ev.payload.data).catch(() => {});
     });
 
@@ -137,8 +187,11 @@ class ShellService {
       if (ev.payload.spawnId !== spawnId) return;
       if ((ev.payload.exitCode ?? 0) === 0) {
         void runHandle?.done().catch(() => {});
+        hooks?.onExit?.({ ok: true, lastLine });
       } else {
-        void runHandle?.fail(`exit code ${ev.payload.exitCode}`).catch(() => {});
+        const error = `exit code ${ev.payload.exitCode}`;
+        void runHandle?.fail(error).catch(() => {});
+        hooks?.onExit?.({ ok: false, lastLine, error });
       }
       chunkUnlisten();
       doneUnlisten();
@@ -149,14 +202,16 @@ class ShellService {
     let errorUnlisten: UnlistenFn | undefined;
     errorUnlisten = await listen<ShellErrorPayload>('asyar:shell:error', (ev) => {
       if (ev.payload.spawnId !== spawnId) return;
-      void runHandle?.fail(ev.payload.message ?? 'shell error').catch(() => {});
+      const error = ev.payload.message ?? 'shell error';
+      void runHandle?.fail(error).catch(() => {});
+      hooks?.onExit?.({ ok: false, lastLine, error });
       chunkUnlisten();
       doneUnlisten();
       errorUnlisten?.();
       unsubscribeCancel?.();
     });
 
-    void shellSpawn(extensionId, spawnId, resolvedPath, args).then(async (ok) => {
+    void shellSpawn(extensionId, spawnId, resolvedPath, args, hooks?.env, pty).then(async (ok) => {
       if (ok) {
         if (stdin !== undefined) {
           try {
@@ -180,6 +235,7 @@ class ShellService {
       // runHandle directly here so the Run transitions out of "running" and
       // tear down the per-spawn listeners we registered above.
       void runHandle?.fail(message).catch(() => {});
+      hooks?.onExit?.({ ok: false, lastLine: null, error: message });
       unsubscribeCancel?.();
       chunkUnlisten();
       doneUnlisten();
@@ -189,6 +245,27 @@ class ShellService {
     return { streaming: true };
   }
 
+  /**
+   * Spawn a finished shell run again with the inputs it recorded. Goes
+   * through `spawn`, so path resolution and consent are checked afresh.
+   * Hooks aren't recorded, so a script's `@asyar.env` variables are not
+   * set on a re-run.
+   */
+  async rerun(run: Run, inputs: unknown): Promise<void> {
+    const { program, args, stdin } = inputs as ShellRunInputs;
+    if (!run.extensionId) return;
+    await this.spawn(
+      run.extensionId,
+      program,
+      args,
+      crypto.randomUUID(),
+      undefined,
+      run.subjectId ?? undefined,
+      run.label,
+      stdin,
+    );
+  }
+
   async writeStdin(spawnId: string, data: string, extensionId?: string): Promise<void> {
     const ok = await shellWriteStdin(extensionId ?? '', spawnId, data);
     if (!ok) {
@@ -203,6 +280,21 @@ class ShellService {
     }
   }
 
+  /** Tell a PTY session's program its terminal changed size. */
+  async resize(spawnId: string, size: ShellPtySize, extensionId?: string): Promise<void> {
+    const ok = await shellResize(extensionId ?? '', spawnId, size);
+    if (!ok) {
+      throw { code: 'RESIZE_FAILED', message: `Failed to resize terminal for spawn ${spawnId}` };
+    }
+  }
+
+  async signal(spawnId: string, signal: ShellSignal, extensionId?: string): Promise<void> {
+    const ok = await shellSignal(extensionId ?? '', spawnId, signal);
+    if (!ok) {
+      throw { code: 'SIGNAL_FAILED', message: `Failed to send ${signal} to spawn ${spawnId}` };
+    }
+  }
+
   async list(extensionId: string): Promise<ShellDescriptor[]> {
     return (await shellList(extensionId)) ?? [];
   }
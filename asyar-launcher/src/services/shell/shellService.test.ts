@@ -249,6 +249,59 @@ describe('ShellService', () => {
       });
     });
   });
+
+  describe('PTY sessions', () => {
+    it('spawn passes the terminal size through to shell_spawn', async () => {
+      vi.mocked(invoke).mockImplementation((cmd) => {
+        if (cmd === 'shell_resolve_path') return Promise.resolve('/usr/bin/top');
+        return Promise.resolve(undefined);
+      });
+
+      await shellService.spawn(
+        'org.asyar.test',
+        'top',
+        [],
+        'spawn-pty-1',
+        undefined,
+        undefined,
+        undefined,
+        undefined,
+        undefined,
+        { cols: 80, rows: 24 },
+      );
+
+      expect(invoke).toHaveBeenCalledWith(
+        'shell_spawn',
+        expect.objectContaining({ spawnId: 'spawn-pty-1', pty: { cols: 80, rows: 24 } }),
+      );
+    });
+
+    it('resize invokes shell_resize with the new dimensions', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await shellService.resize('spawn-1', { cols: 120, rows: 40 }, 'ext-a');
+
+      expect(invoke).toHaveBeenCalledWith('shell_resize', {
+        extensionId: 'ext-a',
+        spawnId: 'spawn-1',
+        cols: 120,
+        rows: 40,
+      });
+    });
+
+    it('signal invokes shell_signal and surfaces a refusal as SIGNAL_FAILED', async () => {
+      vi.mocked(invoke).mockRejectedValue(new Error('not owner'));
+
+      await expect(shellService.signal('spawn-1', 'SIGINT', 'ext-a')).rejects.toMatchObject({
+        code: 'SIGNAL_FAILED',
+      });
+      expect(invoke).toHaveBeenCalledWith('shell_signal', {
+        extensionId: 'ext-a',
+        spawnId: 'spawn-1',
+        signal: 'SIGINT',
+      });
+    });
+  });
 });
 
 // ── Run Tracker auto-promotion ─────────────────────────────────────────────────
@@ -367,6 +420,31 @@ describe('shellService.spawn run-tracker auto-promotion', () => {
     expect(mockLocalHandle.write).toHaveBeenNthCalledWith(3, 'line3\n');
   });
 
+  it('spawn_keeps_pty_output_and_inputs_out_of_the_run', async () => {
+    const { listenMock, fire } = makeListenCapture();
+    vi.mocked(listen).mockImplementation(listenMock as any);
+
+    await shellService.spawn(
+      'org.asyar.ext-a',
+      'python3',
+      [],
+      'spawn-rt-pty',
+      undefined,
+      undefined,
+      undefined,
+      undefined,
+      undefined,
+      { cols: 80, rows: 24 },
+    );
+
+    fire('asyar:shell:chunk', { spawnId: 'spawn-rt-pty', stream: 'pty', data: 'Pj4+IA==' });
+    await Promise.resolve();
+
+    expect(mockLocalHandle.write).not.toHaveBeenCalled();
+    const startInput = mockRunService.startLocal.mock.calls[0][0] as { inputs?: unknown };
+    expect(startInput.inputs).toBeUndefined();
+  });
+
   it('spawn_calls_handle_done_on_zero_exit_code', async () => {
     const { listenMock, fire } = makeListenCapture();
     vi.mocked(listen).mockImplementation(listenMock as any);
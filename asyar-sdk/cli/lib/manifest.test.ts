@@ -225,6 +225,33 @@ describe('manifest validation — schedule', () => {
     const errors = validateManifest(scheduled(300), './');
     expect(errors.filter((e) => e.field.includes('schedule'))).toHaveLength(0);
   });
+
+  const cronScheduled = (schedule: Record<string, unknown>): AsyarManifest =>
+    ({
+      ...backgroundOnly,
+      commands: [{ id: 'tick', name: 'Tick', description: 'x', mode: 'background', schedule }],
+    }) as unknown as AsyarManifest;
+  const scheduleErrors = (schedule: Record<string, unknown>) =>
+    validateManifest(cronScheduled(schedule), './').filter((e) => e.field.includes('schedule'));
+
+  it('accepts cron schedules with and without a timezone', () => {
+    expect(scheduleErrors({ cron: '30 8 * * mon-fri' })).toHaveLength(0);
+    expect(scheduleErrors({ cron: '0 9 * * mon#1', timezone: 'Europe/Berlin' })).toHaveLength(0);
+    expect(scheduleErrors({ cron: '@daily' })).toHaveLength(0);
+  });
+
+  it('rejects malformed cron schedules and unknown timezones', () => {
+    expect(scheduleErrors({ cron: '30 8 * *' })[0]?.field).toBe('commands[0].schedule.cron');
+    expect(scheduleErrors({ cron: '0 9 * * *', timezone: 'Mars/Olympus' })[0]?.message).toContain(
+      'unknown IANA timezone',
+    );
+    expect(scheduleErrors({ cron: '0 9 * * *', intervalSeconds: 60 })[0]?.message).toContain(
+      'not both',
+    );
+    expect(scheduleErrors({ intervalSeconds: 60, timezone: 'UTC' })[0]?.message).toContain(
+      'only applies to cron',
+    );
+  });
 });
 
 describe('manifest validation — seed', () => {
@@ -168,7 +168,9 @@ export interface ManifestCommand {
   trigger?: string;
   searchable?: boolean;
   schedule?: {
-    intervalSeconds: number;
+    intervalSeconds?: number;
+    cron?: string;
+    timezone?: string;
   };
   preferences?: PreferenceDeclaration[];
   arguments?: CommandArgument[];
@@ -661,23 +663,15 @@ function validateCommand(cmd: ManifestCommand, i: number, errors: ValidationErro
 
   if (cmd.schedule) {
     const schedule = cmd.schedule;
-    const intField = `${base}.schedule.intervalSeconds`;
-    if (
-      typeof schedule.intervalSeconds !== 'number' ||
-      !Number.isInteger(schedule.intervalSeconds) ||
-      schedule.intervalSeconds < 1
-    ) {
-      errors.push({ field: intField, message: 'intervalSeconds must be a positive integer' });
-    } else if (schedule.intervalSeconds < 10) {
-      errors.push({
-        field: intField,
-        message: `Minimum schedule interval is 10 seconds, got ${schedule.intervalSeconds}`,
-      });
-    } else if (schedule.intervalSeconds > 86400) {
+    if (schedule.cron !== undefined && schedule.intervalSeconds !== undefined) {
       errors.push({
-        field: intField,
-        message: `Maximum schedule interval is 86400 seconds (24 hours), got ${schedule.intervalSeconds}`,
+        field: `${base}.schedule`,
+        message: 'declare either intervalSeconds or cron, not both',
       });
+    } else if (schedule.cron !== undefined) {
+      validateCronSchedule(schedule, `${base}.schedule`, errors);
+    } else {
+      validateIntervalSchedule(schedule, `${base}.schedule`, errors);
     }
 
     if (cmd.mode !== 'background') {
@@ -709,6 +703,87 @@ function validateCommand(cmd: ManifestCommand, i: number, errors: ValidationErro
  * sense over two or more real, non-`required` arguments. Mirrors the host-side
  * `validate_require_any_of` in `extensions/mod.rs`.
  */
+const CRON_SHORTHANDS = [
+  '@hourly',
+  '@daily',
+  '@midnight',
+  '@weekly',
+  '@monthly',
+  '@yearly',
+  '@annually',
+];
+const CRON_FIELD = /^[A-Za-z0-9*,\-/#]+$/;
+
+function validateIntervalSchedule(
+  schedule: NonNullable<ManifestCommand['schedule']>,
+  base: string,
+  errors: ValidationError[],
+): void {
+  const intField = `${base}.intervalSeconds`;
+  if (
+    typeof schedule.intervalSeconds !== 'number' ||
+    !Number.isInteger(schedule.intervalSeconds) ||
+    schedule.intervalSeconds < 1
+  ) {
+    errors.push({ field: intField, message: 'intervalSeconds must be a positive integer' });
+  } else if (schedule.intervalSeconds < 10) {
+    errors.push({
+      field: intField,
+      message: `Minimum schedule interval is 10 seconds, got ${schedule.intervalSeconds}`,
+    });
+  } else if (schedule.intervalSeconds > 86400) {
+    errors.push({
+      field: intField,
+      message: `Maximum schedule interval is 86400 seconds (24 hours), got ${schedule.intervalSeconds}`,
+    });
+  }
+  if (schedule.timezone !== undefined) {
+    errors.push({
+      field: `${base}.timezone`,
+      message: 'timezone only applies to cron schedules',
+    });
+  }
+}
+
+/**
+ * Shape check only — the launcher parses the expression fully at discovery
+ * and strips a schedule it can't read.
+ */
+function validateCronSchedule(
+  schedule: NonNullable<ManifestCommand['schedule']>,
+  base: string,
+  errors: ValidationError[],
+): void {
+  const cron = schedule.cron;
+  const fields = typeof cron === 'string' ? cron.trim().split(/\s+/) : [];
+  const wellShaped =
+    typeof cron === 'string' &&
+    (CRON_SHORTHANDS.includes(cron.trim().toLowerCase()) ||
+      (fields.length === 5 && fields.every((field) => CRON_FIELD.test(field))));
+  if (!wellShaped) {
+    errors.push({
+      field: `${base}.cron`,
+      message: `cron must be five fields (minute hour day-of-month month day-of-week) or a shorthand like @daily, got "${String(cron)}"`,
+    });
+  }
+  if (schedule.timezone !== undefined && !isKnownTimeZone(schedule.timezone)) {
+    errors.push({
+      field: `${base}.timezone`,
+      message: `unknown IANA timezone "${String(schedule.timezone)}"`,
+    });
+  }
+}
+
+function isKnownTimeZone(timeZone: unknown): boolean {
+  if (typeof timeZone !== 'string' || timeZone.length === 0) return false;
+  try {
+    new Intl.DateTimeFormat('en-US', { timeZone });
+    return true;
+  } catch {
+    return false;
+  }
+}
+
 export function validateRequireAnyOf(
   group: unknown,
   args: CommandArgument[] | undefined,
@@ -76,6 +76,8 @@ export type {
   ShellHandle,
   ShellChunk,
   SpawnParams,
+  ShellPtySize,
+  ShellSignal,
   IPowerService,
   KeepAwakeOptions,
   ResolvedKeepAwakeOptions,
@@ -90,6 +92,13 @@ export type {
   KillResult,
   ListProcessesOptions,
   KillProcessesOptions,
+  ResourceMetric,
+  ResourceSample,
+  ResourceSeries,
+  ResourceTrend,
+  ResourceAlert,
+  ProcessHistoryOptions,
+  TopProcessesOptions,
   ISystemEventsService,
   SystemEvent,
   SystemEventKind,
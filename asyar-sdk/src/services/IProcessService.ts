@@ -45,13 +45,97 @@ export interface KillProcessesOptions {
   confirmedProtected?: boolean;
 }
 
+export type ResourceMetric = 'cpu' | 'memory';
+
+export interface ResourceSample {
+  /** Unix millis. */
+  at: number;
+  /** Summed over the app's processes; 100 is one full core. */
+  cpu: number;
+  memoryBytes: number;
+}
+
+/** Sparkline data for one app, oldest sample first. */
+export interface ResourceSeries {
+  appName: string;
+  samples: ResourceSample[];
+}
+
+/** One app's usage summarised over the requested range. */
+export interface ResourceTrend {
+  appName: string;
+  sampleCount: number;
+  avgCpu: number;
+  peakCpu: number;
+  avgMemoryBytes: number;
+  peakMemoryBytes: number;
+}
+
+/**
+ * Notify when an app stays above `threshold` for `sustainedSecs`. Ids are
+ * yours to pick; saving the same id again replaces the alert.
+ */
+export interface ResourceAlert {
+  id: string;
+  /** `AppGroup.appName` to watch; omit or `null` to watch every app. */
+  appName?: string | null;
+  metric: ResourceMetric;
+  /** Percent (100 = one core) for `cpu`, bytes for `memory`. */
+  threshold: number;
+  /** 0 to 1800 (the 30-minute history window). */
+  sustainedSecs: number;
+  enabled: boolean;
+  /**
+   * Your command the notification's "Show" action runs, with
+   * `{ appName, alertId }` as arguments.
+   */
+  commandId?: string | null;
+}
+
+export interface ProcessHistoryOptions {
+  /** `AppGroup.appName`s to fetch; unknown apps come back with no samples. */
+  appNames: string[];
+  /** How far back to read, 1-30. Defaults to 30. */
+  minutes?: number;
+}
+
+export interface TopProcessesOptions {
+  /** Rank by the average of this metric. */
+  metric: ResourceMetric;
+  /** How far back to read, 1-30. Defaults to 30. */
+  minutes?: number;
+  /** 1-100. Defaults to 10. */
+  limit?: number;
+}
+
 /**
- * Lists and kills OS processes. Requires `process:read` (list) and
- * `process:kill` (kill) manifest permissions. The host re-derives the
- * protected flag from a live snapshot and refuses protected kills unless
- * `confirmedProtected` is true.
+ * Lists and kills OS processes, and samples per-app resource history.
+ * Requires `process:read` (everything but kill) and `process:kill` (kill)
+ * manifest permissions. The host re-derives the protected flag from a live
+ * snapshot and refuses protected kills unless `confirmedProtected` is true.
  */
 export interface IProcessService {
   list(options: ListProcessesOptions): Promise<AppGroup[]>;
   kill(options: KillProcessesOptions): Promise<KillResult>;
+  /**
+   * Start recording per-app CPU and memory every 5 seconds, for
+   * {@link history} and {@link top}. Call when your view opens and
+   * {@link stopSampling} when it closes; repeated calls hold one lease.
+   * The SDK renews the lease while your extension is loaded, and the host
+   * drops it a minute after renewals stop.
+   */
+  startSampling(): Promise<void>;
+  stopSampling(): Promise<void>;
+  history(options: ProcessHistoryOptions): Promise<ResourceSeries[]>;
+  /** The heaviest apps over the last `minutes`, heaviest first. */
+  top(options: TopProcessesOptions): Promise<ResourceTrend[]>;
+  /** Your saved alerts; other extensions' are never visible. */
+  listAlerts(): Promise<ResourceAlert[]>;
+  /**
+   * Save an alert; enabled alerts keep sampling on while they exist. Also
+   * requires `notifications:send`. Rejects on an invalid alert.
+   */
+  saveAlert(alert: ResourceAlert): Promise<void>;
+  /** Resolves `false` when you had no alert with that id. */
+  deleteAlert(alertId: string): Promise<boolean>;
 }
@@ -3,11 +3,28 @@ export interface SpawnParams {
   args?: string[];
   /** Optional initial stdin string to write immediately on spawn (automatically closes stdin). */
   stdin?: string;
+  /**
+   * Run under a pseudo-terminal of this size instead of pipes, for programs
+   * that need a TTY: ssh prompts, `top`, REPLs. Output then arrives as raw
+   * bytes on the `pty` stream, ready for a terminal emulator.
+   */
+  pty?: ShellPtySize;
+}
+
+/** Terminal dimensions in character cells. */
+export interface ShellPtySize {
+  cols: number;
+  rows: number;
 }
 
+export type ShellSignal = 'SIGINT' | 'SIGTERM';
+
 export interface ShellChunk {
-  stream: 'stdout' | 'stderr';
+  stream: 'stdout' | 'stderr' | 'pty';
+  /** One line of output, or for `pty` the raw bytes base64-encoded. */
   data: string;
+  /** `pty` only: the raw terminal bytes, escape sequences included. */
+  bytes?: Uint8Array;
 }
 
 export interface ShellHandle {
@@ -18,7 +35,8 @@ export interface ShellHandle {
   readonly spawnId: string;
 
   /**
-   * Fired when a line of output is received from stdout or stderr.
+   * Fired when a line of output is received from stdout or stderr, or when
+   * a PTY session writes to its terminal.
    */
   onChunk(cb: (chunk: ShellChunk) => void): void;
 
@@ -38,11 +56,27 @@ export interface ShellHandle {
    */
   abort(): void;
 
-  /** Write string data to the running process's stdin. */
+  /**
+   * Write string data to the running process's stdin. For a PTY session
+   * this is typed into the terminal, so `'\x03'` is Ctrl-C.
+   */
   write(data: string): Promise<void>;
 
-  /** Close standard input (sends EOF to the child process). */
+  /**
+   * Close standard input (sends EOF to the child process). A PTY session
+   * is sent Ctrl-D instead.
+   */
   closeStdin(): Promise<void>;
+
+  /** Tell a PTY session's program its terminal changed size. */
+  resize(size: ShellPtySize): Promise<void>;
+
+  /**
+   * Ask the process to stop. On a PTY session the signal reaches the
+   * terminal's foreground job, as if Ctrl-C were typed. Windows supports
+   * `SIGINT` only for PTY sessions; `SIGTERM` terminates the process.
+   */
+  signal(signal: ShellSignal): Promise<void>;
 }
 
 /**
@@ -57,6 +91,8 @@ export interface ShellDescriptor {
   pid: number;
   /** Unix millis. */
   startedAt: number;
+  /** Spawned with `pty`; chunks arrive on the `pty` stream. */
+  pty: boolean;
 }
 
 export interface IShellService {
@@ -67,4 +67,92 @@ describe('ProcessServiceProxy', () => {
       confirmedProtected: false,
     });
   });
+
+  it('startSampling / stopSampling send empty payloads', async () => {
+    vi.mocked(mockBroker.invoke).mockResolvedValue(undefined);
+
+    await proxy.startSampling();
+    await proxy.stopSampling();
+
+    expect(mockBroker.invoke).toHaveBeenNthCalledWith(1, 'process:startSampling', {});
+    expect(mockBroker.invoke).toHaveBeenNthCalledWith(2, 'process:stopSampling', {});
+  });
+
+  it('renews the sampling lease until stopSampling', async () => {
+    vi.useFakeTimers();
+    try {
+      vi.mocked(mockBroker.invoke).mockResolvedValue(undefined);
+
+      await proxy.startSampling();
+      await proxy.startSampling();
+      vi.advanceTimersByTime(20_000);
+      expect(mockBroker.invoke).toHaveBeenCalledTimes(3);
+
+      await proxy.stopSampling();
+      vi.advanceTimersByTime(60_000);
+      expect(mockBroker.invoke).toHaveBeenCalledTimes(4);
+      expect(mockBroker.invoke).toHaveBeenLastCalledWith('process:stopSampling', {});
+    } finally {
+      vi.useRealTimers();
+    }
+  });
+
+  it('history sends appNames then minutes', async () => {
+    const series = [{ appName: 'Safari', samples: [{ at: 1, cpu: 2, memoryBytes: 3 }] }];
+    vi.mocked(mockBroker.invoke).mockResolvedValueOnce(series);
+
+    const res = await proxy.history({ appNames: ['Safari'] });
+
+    expect(res).toEqual(series);
+    expect(Object.keys(vi.mocked(mockBroker.invoke).mock.calls[0][1])).toEqual([
+      'appNames',
+      'minutes',
+    ]);
+    expect(mockBroker.invoke).toHaveBeenCalledWith('process:history', {
+      appNames: ['Safari'],
+      minutes: undefined,
+    });
+  });
+
+  it('top sends metric, minutes and limit in order', async () => {
+    vi.mocked(mockBroker.invoke).mockResolvedValueOnce([]);
+
+    await proxy.top({ metric: 'memory', minutes: 5, limit: 3 });
+
+    expect(Object.keys(vi.mocked(mockBroker.invoke).mock.calls[0][1])).toEqual([
+      'metric',
+      'minutes',
+      'limit',
+    ]);
+    expect(mockBroker.invoke).toHaveBeenCalledWith('process:top', {
+      metric: 'memory',
+      minutes: 5,
+      limit: 3,
+    });
+  });
+
+  it('alerts are listed, saved and deleted through the process namespace', async () => {
+    const alert = {
+      id: 'chrome-memory',
+      appName: 'Google Chrome',
+      metric: 'memory' as const,
+      threshold: 4 * 1024 ** 3,
+      sustainedSecs: 300,
+      enabled: true,
+    };
+    vi.mocked(mockBroker.invoke)
+      .mockResolvedValueOnce([alert])
+      .mockResolvedValueOnce(undefined)
+      .mockResolvedValueOnce(true);
+
+    expect(await proxy.listAlerts()).toEqual([alert]);
+    await proxy.saveAlert(alert);
+    expect(await proxy.deleteAlert('chrome-memory')).toBe(true);
+
+    expect(mockBroker.invoke).toHaveBeenNthCalledWith(1, 'process:listAlerts', {});
+    expect(mockBroker.invoke).toHaveBeenNthCalledWith(2, 'process:saveAlert', { alert });
+    expect(mockBroker.invoke).toHaveBeenNthCalledWith(3, 'process:deleteAlert', {
+      alertId: 'chrome-memory',
+    });
+  });
 });
@@ -4,14 +4,29 @@ import type {
   KillResult,
   ListProcessesOptions,
   KillProcessesOptions,
+  ProcessHistoryOptions,
+  TopProcessesOptions,
+  ResourceAlert,
+  ResourceSeries,
+  ResourceTrend,
 } from './IProcessService';
 import { BaseServiceProxy } from './BaseServiceProxy';
 
+/**
+ * How often a held sampling lease is renewed. The host lets a lease lapse
+ * after 60 seconds, so a view that unloads without `stopSampling` stops
+ * costing a process scan every 5 seconds shortly after.
+ */
+const SAMPLING_RENEW_MS = 20_000;
+
 /**
  * SDK proxy for the host process service. The IPC router injects the calling
- * extension's id; the host gates `process:read` / `process:kill`.
+ * extension's id; the host gates `process:read` / `process:kill`, and also
+ * `notifications:send` for saving and deleting alerts.
  */
 export class ProcessServiceProxy extends BaseServiceProxy implements IProcessService {
+  private samplingRenewal: ReturnType<typeof setInterval> | null = null;
+
   async list(options: ListProcessesOptions): Promise<AppGroup[]> {
     return this.broker.invoke<AppGroup[]>('process:list', {
       query: options.query,
@@ -26,4 +41,48 @@ export class ProcessServiceProxy extends BaseServiceProxy implements IProcessSer
       confirmedProtected: options.confirmedProtected ?? false,
     });
   }
+
+  async startSampling(): Promise<void> {
+    await this.broker.invoke<void>('process:startSampling', {});
+    if (this.samplingRenewal === null) {
+      this.samplingRenewal = setInterval(() => {
+        void this.broker.invoke<void>('process:startSampling', {}).catch(() => undefined);
+      }, SAMPLING_RENEW_MS);
+    }
+  }
+
+  async stopSampling(): Promise<void> {
+    if (this.samplingRenewal !== null) {
+      clearInterval(this.samplingRenewal);
+      this.samplingRenewal = null;
+    }
+    await this.broker.invoke<void>('process:stopSampling', {});
+  }
+
+  async history(options: ProcessHistoryOptions): Promise<ResourceSeries[]> {
+    return this.broker.invoke<ResourceSeries[]>('process:history', {
+      appNames: options.appNames,
+      minutes: options.minutes,
+    });
+  }
+
+  async top(options: TopProcessesOptions): Promise<ResourceTrend[]> {
+    return this.broker.invoke<ResourceTrend[]>('process:top', {
+      metric: options.metric,
+      minutes: options.minutes,
+      limit: options.limit,
+    });
+  }
+
+  async listAlerts(): Promise<ResourceAlert[]> {
+    return this.broker.invoke<ResourceAlert[]>('process:listAlerts', {});
+  }
+
+  async saveAlert(alert: ResourceAlert): Promise<void> {
+    await this.broker.invoke<void>('process:saveAlert', { alert });
+  }
+
+  async deleteAlert(alertId: string): Promise<boolean> {
+    return this.broker.invoke<boolean>('process:deleteAlert', { alertId });
+  }
 }
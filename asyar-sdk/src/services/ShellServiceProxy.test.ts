@@ -1,5 +1,6 @@
 import { describe, it, expect, vi, beforeEach } from 'vitest';
 import { ShellServiceProxy } from './ShellServiceProxy';
+import type { ShellChunk } from './IShellService';
 import { messageBroker } from '../ipc/MessageBroker';
 
 vi.mock('../ipc/MessageBroker', () => ({
@@ -473,4 +474,81 @@ describe('ShellServiceProxy', () => {
       expect(mockInvoke).not.toHaveBeenCalled();
     });
   });
+
+  // ── PTY sessions ───────────────────────────────────────────────────────────
+
+  describe('PTY sessions', () => {
+    function spawnPty() {
+      const { proxy, mockInvoke } = makeProxy();
+      let capturedId: string | undefined;
+      mockInvoke.mockImplementation((cmd: string, payload: { spawnId: string }) => {
+        if (cmd === 'shell:spawn') {
+          capturedId = payload.spawnId;
+          return Promise.resolve({ streaming: true });
+        }
+        return Promise.resolve();
+      });
+      const handle = proxy.spawn({ program: 'python3', pty: { cols: 80, rows: 24 } });
+      return { handle, mockInvoke, spawnId: () => capturedId! };
+    }
+
+    it('passes the terminal size in the spawn payload', async () => {
+      const { mockInvoke } = spawnPty();
+      await vi.waitFor(() => expect(mockInvoke).toHaveBeenCalled());
+      expect(mockInvoke.mock.calls[0][1]).toMatchObject({ pty: { cols: 80, rows: 24 } });
+    });
+
+    it('decodes pty chunks into raw bytes', async () => {
+      const { handle, spawnId } = spawnPty();
+      await vi.waitFor(() => expect(spawnId()).toBeDefined());
+      const chunks: ShellChunk[] = [];
+      handle.onChunk((chunk) => chunks.push(chunk));
+
+      // ">>> " then a lone 0xc3 — half of a UTF-8 sequence the next chunk completes.
+      fireStreamMessage({
+        type: 'asyar:stream',
+        streamId: spawnId(),
+        phase: 'chunk',
+        data: { stream: 'pty', data: 'Pj4+IMM=' },
+      });
+
+      expect(chunks).toHaveLength(1);
+      expect(Array.from(chunks[0].bytes!)).toEqual([0x3e, 0x3e, 0x3e, 0x20, 0xc3]);
+    });
+
+    it('resize() and signal() delegate to the broker with the spawnId', async () => {
+      const { handle, mockInvoke, spawnId } = spawnPty();
+      await vi.waitFor(() => expect(spawnId()).toBeDefined());
+
+      await handle.resize({ cols: 120, rows: 40 });
+      await handle.signal('SIGINT');
+
+      expect(mockInvoke).toHaveBeenCalledWith('shell:resize', {
+        spawnId: spawnId(),
+        cols: 120,
+        rows: 40,
+      });
+      expect(mockInvoke).toHaveBeenCalledWith('shell:signal', {
+        spawnId: spawnId(),
+        signal: 'SIGINT',
+      });
+    });
+
+    it('resize() and signal() are no-ops once the process has settled', async () => {
+      const { handle, mockInvoke, spawnId } = spawnPty();
+      await vi.waitFor(() => expect(spawnId()).toBeDefined());
+      fireStreamMessage({
+        type: 'asyar:stream',
+        streamId: spawnId(),
+        phase: 'done',
+        data: { exitCode: 0 },
+      });
+
+      mockInvoke.mockClear();
+      await handle.resize({ cols: 100, rows: 30 });
+      await handle.signal('SIGTERM');
+
+      expect(mockInvoke).not.toHaveBeenCalled();
+    });
+  });
 });
@@ -4,6 +4,8 @@ import {
   ShellChunk,
   ShellDescriptor,
   ShellHandle,
+  ShellPtySize,
+  ShellSignal,
   SpawnParams,
 } from './IShellService';
 
@@ -16,6 +18,7 @@ export class ShellServiceProxy extends BaseServiceProxy implements IShellService
         args: params.args,
         spawnId,
         stdin: params.stdin,
+        pty: params.pty,
       }),
     );
   }
@@ -73,7 +76,7 @@ export class ShellServiceProxy extends BaseServiceProxy implements IShellService
       switch (phase) {
         case 'chunk':
           if (data) {
-            chunkCb(data as ShellChunk);
+            chunkCb(decodeChunk(data as ShellChunk));
           }
           break;
         case 'done':
@@ -126,6 +129,29 @@ export class ShellServiceProxy extends BaseServiceProxy implements IShellService
         }
         await this.broker.invoke('shell:close-stdin', { spawnId });
       },
+      resize: async ({ cols, rows }: ShellPtySize) => {
+        if (settled) {
+          return;
+        }
+        await this.broker.invoke('shell:resize', { spawnId, cols, rows });
+      },
+      signal: async (signal: ShellSignal) => {
+        if (settled) {
+          return;
+        }
+        await this.broker.invoke('shell:signal', { spawnId, signal });
+      },
     };
   }
 }
+
+/** Terminal output crosses the host boundary base64-encoded. */
+function decodeChunk(chunk: ShellChunk): ShellChunk {
+  if (chunk.stream !== 'pty') return chunk;
+  const binary = atob(chunk.data);
+  const bytes = new Uint8Array(binary.length);
+  for (let i = 0; i < binary.length; i++) {
+    bytes[i] = binary.charCodeAt(i);
+  }
+  return { ...chunk, bytes };
+}
@@ -98,6 +98,13 @@ export type {
   KillResult,
   ListProcessesOptions,
   KillProcessesOptions,
+  ResourceMetric,
+  ResourceSample,
+  ResourceSeries,
+  ResourceTrend,
+  ResourceAlert,
+  ProcessHistoryOptions,
+  TopProcessesOptions,
 } from './IProcessService';
 export { ProcessServiceProxy } from './ProcessServiceProxy';
 
@@ -158,8 +158,16 @@ export interface ExtensionCommand {
    * `mode === "background"`.
    */
   component?: string;
+  /**
+   * Run this background command on a schedule: either every
+   * `intervalSeconds` (10–86400), or on a five-field `cron` expression read
+   * in `timezone` (an IANA name; the user's own zone when omitted).
+   * Declare exactly one of `intervalSeconds` and `cron`.
+   */
   schedule?: {
-    intervalSeconds: number;
+    intervalSeconds?: number;
+    cron?: string;
+    timezone?: string;
   };
   /**
    * If false, the command is excluded from the launcher's root search index.